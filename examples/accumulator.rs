@@ -0,0 +1,38 @@
+use symbolica::{
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{ResettableBuffer, State, Workspace},
+    streaming::AtomAccumulator,
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let mut sum = AtomAccumulator::new();
+
+    // generate terms one at a time, many of which cancel
+    for i in 0..5000 {
+        let term = parse(&format!(
+            "{}*x^{}*f(y) - x^{}*f(y) + 2*y^{}",
+            i % 3,
+            i % 7,
+            i % 7,
+            i % 2
+        ))
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+        sum.add_term(term.to_view(), &workspace, &state);
+    }
+
+    let mut out = OwnedAtom::new();
+    sum.to_atom(&workspace, &state, &mut out);
+
+    println!(
+        "> Sum with {} terms: {}",
+        sum.nterms(&workspace, &state),
+        AtomPrinter::new(out.to_view(), <_>::default(), &state)
+    );
+}
@@ -0,0 +1,80 @@
+use symbolica::{
+    poly::polynomial::MultivariatePolynomial,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, number::Number, OwnedAtom, OwnedNum},
+    rings::{
+        algebraic_number::AlgebraicExtension,
+        rational::{Rational, RationalField},
+        Field, Ring, RingPrinter,
+    },
+    state::{ResettableBuffer, State},
+};
+
+fn main() {
+    // sqrt(3) as a root of a^2 - 3
+    let field = RationalField::new();
+    let mut poly = MultivariatePolynomial::<_, u16>::new(1, field, None, None);
+    poly.append_monomial(Rational::Natural(-3, 1), &[0]);
+    poly.append_monomial(Rational::Natural(1, 1), &[2]);
+    let ext = AlgebraicExtension::new(poly.clone());
+
+    let a = ext.generator();
+    let b = ext.add(&a, &ext.one());
+    println!(
+        "> (1+a)^2 = {}",
+        RingPrinter {
+            ring: &ext,
+            element: &ext.mul(&b, &b)
+        }
+    );
+    println!(
+        "> 1/(1+a) = {}",
+        RingPrinter {
+            ring: &ext,
+            element: &ext.inv(&b)
+        }
+    );
+
+    // the real root of a^3 - a - 1
+    let mut cubic = MultivariatePolynomial::<_, u16>::new(1, field, None, None);
+    cubic.append_monomial(Rational::Natural(-1, 1), &[0]);
+    cubic.append_monomial(Rational::Natural(-1, 1), &[1]);
+    cubic.append_monomial(Rational::Natural(1, 1), &[3]);
+    let ext3 = AlgebraicExtension::new(cubic);
+    let a3 = ext3.generator();
+    println!(
+        "> a^5 = {}, 1/a = {}",
+        RingPrinter {
+            ring: &ext3,
+            element: &ext3.pow(&a3, 5)
+        },
+        RingPrinter {
+            ring: &ext3,
+            element: &ext3.inv(&a3)
+        }
+    );
+
+    // algebraic numbers as coefficients of atoms
+    let mut state = State::new();
+    let i = state.get_or_insert_algebraic_extension(&poly);
+    let mut n1 = OwnedAtom::<DefaultRepresentation>::new();
+    n1.transform_to_num()
+        .set_from_number(Number::AlgebraicNumber(b.clone(), i));
+    let mut n2 = OwnedAtom::<DefaultRepresentation>::new();
+    n2.transform_to_num()
+        .set_from_number(Number::AlgebraicNumber(ext.sub(&a, &ext.one()), i));
+
+    println!(
+        "> {} * {}",
+        AtomPrinter::new(n1.to_view(), <_>::default(), &state),
+        AtomPrinter::new(n2.to_view(), <_>::default(), &state)
+    );
+
+    if let (OwnedAtom::Num(n1), OwnedAtom::Num(n2)) = (&mut n1, &n2) {
+        n1.mul(&n2.to_num_view(), &state);
+    }
+    println!(
+        "> = {}",
+        AtomPrinter::new(n1.to_view(), <_>::default(), &state)
+    );
+}
@@ -0,0 +1,62 @@
+use symbolica::{
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{Assumptions, ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let inputs = ["(x^2)^(1/2)", "(x^a)^b", "(x^a)^n", "(x^2)^3"];
+
+    for assume in [false, true] {
+        if assume {
+            let x = state.get_or_insert_var("x");
+            state.set_assumptions(
+                x,
+                Assumptions {
+                    positive: true,
+                    ..Default::default()
+                },
+            );
+            let a = state.get_or_insert_var("a");
+            state.set_assumptions(
+                a,
+                Assumptions {
+                    real: true,
+                    ..Default::default()
+                },
+            );
+            println!("Assuming x > 0 and a real:");
+        } else {
+            println!("Without assumptions:");
+        }
+
+        let n = state.get_or_insert_var("n");
+        state.set_assumptions(
+            n,
+            Assumptions {
+                integer: true,
+                ..Default::default()
+            },
+        );
+
+        for input in inputs {
+            let expr: OwnedAtom<DefaultRepresentation> = parse(input)
+                .unwrap()
+                .to_atom(&mut state, &workspace)
+                .unwrap();
+
+            let mut out = OwnedAtom::new();
+            expr.to_view().normalize(&workspace, &state, &mut out);
+
+            println!(
+                "\t{} = {}",
+                input,
+                AtomPrinter::new(out.to_view(), <_>::default(), &state)
+            );
+        }
+    }
+}
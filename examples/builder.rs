@@ -0,0 +1,36 @@
+use symbolica::{
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let [x, y, f, two]: [OwnedAtom<DefaultRepresentation>; 4] = ["x", "y", "f(x,y)", "2"]
+        .map(|s| parse(s).unwrap().to_atom(&mut state, &workspace).unwrap());
+
+    let mut eager = OwnedAtom::new();
+    let mut lazy = OwnedAtom::new();
+    for (builder, out) in [
+        (x.to_view().builder(&state, &workspace), &mut eager),
+        (x.to_view().builder(&state, &workspace).lazy(), &mut lazy),
+    ] {
+        (-(builder + y.to_view() + f.to_view() - x.to_view()) * y.to_view() * two.to_view()
+            / f.to_view())
+        .pow(two.to_view())
+        .finish(out);
+    }
+
+    println!(
+        "> Eager: {}",
+        AtomPrinter::new(eager.to_view(), <_>::default(), &state)
+    );
+    println!(
+        "> Lazy: {}",
+        AtomPrinter::new(lazy.to_view(), <_>::default(), &state)
+    );
+    println!("> Equal: {}", eager.to_view() == lazy.to_view());
+}
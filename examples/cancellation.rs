@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use symbolica::{
+    cancel::CancellationToken,
+    parser::parse,
+    poly::{groebner::GroebnerBasis, polynomial::MultivariatePolynomial, to_polynomials},
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    rings::rational::RationalField,
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    // the cyclic-6 system, whose Groebner basis is expensive to compute
+    let exprs: Vec<OwnedAtom<DefaultRepresentation>> = [
+        "a+b+c+d+e+f",
+        "a*b+b*c+c*d+d*e+e*f+f*a",
+        "a*b*c+b*c*d+c*d*e+d*e*f+e*f*a+f*a*b",
+        "a*b*c*d+b*c*d*e+c*d*e*f+d*e*f*a+e*f*a*b+f*a*b*c",
+        "a*b*c*d*e+b*c*d*e*f+c*d*e*f*a+d*e*f*a*b+e*f*a*b*c+f*a*b*c*d",
+        "a*b*c*d*e*f-1",
+    ]
+    .iter()
+    .map(|e| parse(e).unwrap().to_atom(&mut state, &workspace).unwrap())
+    .collect();
+    let views: Vec<_> = exprs.iter().map(|e| e.to_view()).collect();
+    let polys: Vec<MultivariatePolynomial<_, u16>> =
+        to_polynomials(&views, RationalField::new()).unwrap();
+
+    let token = CancellationToken::with_timeout(Duration::from_millis(100));
+    match GroebnerBasis::try_new(&polys, &token) {
+        Ok(basis) => println!("> Basis with {} elements", basis.system.len()),
+        Err(e) => println!("> {}", e),
+    }
+}
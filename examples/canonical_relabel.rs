@@ -0,0 +1,54 @@
+use symbolica::{
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    // the same two-loop integral with different loop momentum labels
+    let integrals = [
+        "prop(k1,m)*prop(k2,0)*prop(k1+k2-p,0)",
+        "prop(k2,m)*prop(k1,0)*prop(k1+k2-p,0)",
+        "prop(k3,m)*prop(k2,0)*prop(k2+k3-p,0)",
+    ];
+    let vars = ["k1", "k2", "k3"].map(|x| state.get_or_insert_var(x));
+
+    let mut canonical = vec![];
+    for integral in integrals {
+        let expr: OwnedAtom<DefaultRepresentation> = parse(integral)
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+
+        let mut out = OwnedAtom::new();
+        let map = expr
+            .to_view()
+            .canonical_relabel(&vars, &workspace, &state, &mut out);
+
+        println!(
+            "> {} -> {} with {}",
+            AtomPrinter::new(expr.to_view(), <_>::default(), &state),
+            AtomPrinter::new(out.to_view(), <_>::default(), &state),
+            map.iter()
+                .map(|(from, to)| format!(
+                    "{}->{}",
+                    state.get_name(*from).unwrap(),
+                    state.get_name(*to).unwrap()
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        canonical.push(out);
+    }
+
+    println!(
+        "> All equal: {}",
+        canonical
+            .windows(2)
+            .all(|w| w[0].to_view() == w[1].to_view())
+    );
+}
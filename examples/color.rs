@@ -0,0 +1,34 @@
+use symbolica::{
+    parser::parse,
+    physics::color::ColorAlgebra,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let color = ColorAlgebra::new(&mut state);
+
+    for input in [
+        "T(a,i1,i2)*T(a,i2,i3)",
+        "f(a,c,d)*f(b,c,d)",
+        "T(a,i1,i2)*T(b,i2,i3)*f(a,b,c)",
+        "tr(a,b,c)*f(a,b,c)",
+    ] {
+        let input = parse(input)
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+
+        let mut out = OwnedAtom::new();
+        color.simplify(input.to_view(), &workspace, &state, &mut out);
+        println!(
+            "> {} = {}",
+            AtomPrinter::new(input.to_view(), <_>::default(), &state),
+            AtomPrinter::new(out.to_view(), <_>::default(), &state),
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use symbolica::{
+    derivative::Differentiator,
+    parser::parse,
+    printer::AtomPrinter,
+    representations::default::DefaultRepresentation,
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let f = parse("x^3*y^2 + (x + y)^-2 + f(z)*y")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let g = parse("x*y*z + x^2")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let vars = [state.get_or_insert_var("x"), state.get_or_insert_var("y")];
+
+    let mut d = Differentiator::new();
+
+    println!("> gradient:");
+    for e in d.gradient(f.to_view(), &vars, &workspace, &state).unwrap() {
+        println!(
+            "\t{}",
+            AtomPrinter::new(e.to_view(), <_>::default(), &state)
+        );
+    }
+
+    println!("> Jacobian:");
+    for row in d
+        .jacobian(&[f.to_view(), g.to_view()], &vars, &workspace, &state)
+        .unwrap()
+    {
+        for e in row {
+            print!(
+                "\t{}",
+                AtomPrinter::new(e.to_view(), <_>::default(), &state)
+            );
+        }
+        println!();
+    }
+
+    println!("> Hessian:");
+    for row in d.hessian(f.to_view(), &vars, &workspace, &state).unwrap() {
+        for e in row {
+            print!(
+                "\t{}",
+                AtomPrinter::new(e.to_view(), <_>::default(), &state)
+            );
+        }
+        println!();
+    }
+
+    // f(z) depends on z
+    let mut out = workspace.new_atom();
+    let z = state.get_or_insert_var("z");
+    if let Err(e) = f.to_view().derivative(z, &workspace, &state, out.get_mut()) {
+        println!("> {}", e);
+    }
+
+    derivative_markers();
+}
+
+fn derivative_markers() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let e = parse("f(x^2, g(x, y))*h(x)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let x = state.get_or_insert_var("x");
+    let der = state.get_or_insert_var("der");
+
+    let mut d = Differentiator::with_derivative_marker(der);
+    let mut de = workspace.new_atom();
+    d.derivative(e.to_view(), x, &workspace, &state, de.get_mut())
+        .unwrap();
+    println!(
+        "> d/dx {} = {}",
+        AtomPrinter::new(e.to_view(), <_>::default(), &state),
+        AtomPrinter::new(de.get().to_view(), <_>::default(), &state)
+    );
+
+    let mut dde = workspace.new_atom();
+    d.derivative(de.get().to_view(), x, &workspace, &state, dde.get_mut())
+        .unwrap();
+    println!(
+        "> d^2/dx^2 {} = {}",
+        AtomPrinter::new(e.to_view(), <_>::default(), &state),
+        AtomPrinter::new(dde.get().to_view(), <_>::default(), &state)
+    );
+}
@@ -0,0 +1,62 @@
+use symbolica::{
+    determinant::determinant,
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let matrices: [(&str, &[&str]); 3] = [
+        (
+            "Gram determinant",
+            &[
+                "p1.p1", "p1.p2", "p1.p3", //
+                "p1.p2", "p2.p2", "p2.p3", //
+                "p1.p3", "p2.p3", "p3.p3",
+            ],
+        ),
+        (
+            "Sparse",
+            &[
+                "0", "a", "0", "b", //
+                "c", "0", "0", "0", //
+                "0", "d", "e", "0", //
+                "f", "0", "g", "h",
+            ],
+        ),
+        (
+            "Singular",
+            &[
+                "x+y", "x-y", "1", //
+                "x", "y", "2", //
+                "2*x+y", "x", "3",
+            ],
+        ),
+    ];
+
+    for (name, entries) in matrices {
+        let atoms: Vec<OwnedAtom<DefaultRepresentation>> = entries
+            .iter()
+            .map(|e| {
+                parse(&e.replace('.', "_"))
+                    .unwrap()
+                    .to_atom(&mut state, &workspace)
+                    .unwrap()
+            })
+            .collect();
+        let views: Vec<_> = atoms.iter().map(|a| a.to_view()).collect();
+        let n = (views.len() as f64).sqrt() as usize;
+
+        let mut det = OwnedAtom::new();
+        determinant(&views, n, &workspace, &state, &mut det);
+        println!(
+            "> {}: {}",
+            name,
+            AtomPrinter::new(det.to_view(), <_>::default(), &state)
+        );
+    }
+}
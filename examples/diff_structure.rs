@@ -0,0 +1,38 @@
+use symbolica::{
+    parser::parse,
+    printer::AtomPrinter,
+    representations::default::DefaultRepresentation,
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let a = parse("x*f(y,z^2)+y^2+3*z")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let b = parse("x*f(y,z^3)+y^2+2*z+w")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let diff = a.to_view().diff_structure(&b.to_view());
+
+    if let Some((path, x, y)) = &diff.first_difference {
+        println!(
+            "> First difference at {:?}: {} != {}",
+            path,
+            AtomPrinter::new(*x, <_>::default(), &state),
+            AtomPrinter::new(*y, <_>::default(), &state)
+        );
+    }
+
+    for t in &diff.removed {
+        println!("> - {}", AtomPrinter::new(*t, <_>::default(), &state));
+    }
+    for t in &diff.added {
+        println!("> + {}", AtomPrinter::new(*t, <_>::default(), &state));
+    }
+}
@@ -0,0 +1,28 @@
+use symbolica::{
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let exprs: Vec<OwnedAtom<DefaultRepresentation>> = ["x^2+y^2-1", "x-y^3", "x*z-1/2"]
+        .iter()
+        .map(|e| parse(e).unwrap().to_atom(&mut state, &workspace).unwrap())
+        .collect();
+    let views: Vec<_> = exprs.iter().map(|e| e.to_view()).collect();
+
+    let x = state.get_or_insert_var("x");
+    let relations = OwnedAtom::eliminate(&views, x, &workspace, &state).unwrap();
+
+    println!("> Eliminating x:");
+    for r in &relations {
+        println!(
+            "\t{} = 0",
+            AtomPrinter::new(r.to_view(), <_>::default(), &state)
+        );
+    }
+}
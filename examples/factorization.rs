@@ -0,0 +1,75 @@
+use symbolica::{
+    parser::parse,
+    printer::{PolynomialPrinter, PrintMode},
+    representations::default::DefaultRepresentation,
+    rings::{
+        finite_field::{FiniteField, FiniteFieldCore},
+        integer::IntegerRing,
+    },
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let a = parse("(x^2+1)^3*(x^4+x+3)^2*(x-2)*(2*x^3-5*x+7)*(x^4-10*x^2+1)*-6")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let mut expanded = workspace.new_atom();
+    a.to_view().expand(&workspace, &state, &mut expanded);
+
+    let p = expanded
+        .to_view()
+        .to_polynomial::<_, u16>(IntegerRing::new(), None)
+        .unwrap();
+
+    println!("> factorization over the integers:");
+    for (f, m) in p.factor_univariate(0).unwrap() {
+        println!(
+            "\t({})^{}",
+            PolynomialPrinter::new(&f, &state, PrintMode::default()),
+            m
+        );
+    }
+
+    for prime in [5, 7, 13] {
+        let field = FiniteField::<u32>::new(prime);
+        let pp = expanded
+            .to_view()
+            .to_polynomial::<_, u16>(field, None)
+            .unwrap();
+
+        println!("> factorization modulo {}:", prime);
+        for (f, m) in pp.factor_univariate(0) {
+            println!(
+                "\t({})^{}",
+                PolynomialPrinter::new(&f, &state, PrintMode::default()),
+                m
+            );
+        }
+    }
+
+    let b = parse("(x^2*y+z+1)*(x*z-y^2+3)^2*(y+z)^3*(x^3+y*z+x*y)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let mut expanded = workspace.new_atom();
+    b.to_view().expand(&workspace, &state, &mut expanded);
+
+    let field = FiniteField::<u64>::new(18446744073709551557);
+    let pp = expanded
+        .to_view()
+        .to_polynomial::<_, u16>(field, None)
+        .unwrap();
+
+    println!("> multivariate factorization modulo {}:", field.get_prime());
+    for (f, m) in pp.factor().unwrap() {
+        println!(
+            "\t({})^{}",
+            PolynomialPrinter::new(&f, &state, PrintMode::default()),
+            m
+        );
+    }
+}
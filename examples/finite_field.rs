@@ -0,0 +1,29 @@
+use symbolica::rings::{
+    finite_field::{FiniteField, FiniteFieldCore},
+    Ring,
+};
+
+fn main() {
+    // p - 1 = 5 * 2^13, so that the general case of Tonelli-Shanks is used
+    let field = FiniteField::<u32>::new(40961);
+    let p = field.get_prime();
+
+    for a in [2, 3, 5, 16, 1000] {
+        let e = field.to_element(a);
+        match field.sqrt(&e) {
+            Some(r) => {
+                assert_eq!(field.mul(&r, &r), e);
+                println!("> sqrt({}) = {} mod {}", a, field.from_element(r), p);
+            }
+            None => println!("> {} is not a square mod {}", a, p),
+        }
+    }
+
+    let field = FiniteField::<u64>::new(1000003);
+    let p = field.get_prime();
+    let base = field.to_element(2);
+    let a = field.pow(&base, 123456);
+    let e = field.discrete_log(&base, &a, p - 1).unwrap();
+    println!("> log_2({}) = {} mod {}", field.from_element(a), e, p);
+    assert_eq!(field.pow(&base, e), a);
+}
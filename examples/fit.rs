@@ -0,0 +1,38 @@
+use symbolica::{
+    fit::{fit_multivariate, fit_univariate},
+    poly::polynomial::MultivariatePolynomial,
+    rings::finite_field::{FiniteField, FiniteFieldCore},
+    rings::Field,
+    rings::Ring,
+};
+
+fn main() {
+    let field = FiniteField::<u64>::new(1152921504606846883);
+
+    // (x^2 + 3) / (x - 5)
+    let (num, den): (MultivariatePolynomial<_, u16>, _) = fit_univariate(
+        |x| {
+            let n = field.add(&field.mul(x, x), &field.to_element(3));
+            let d = field.sub(x, &field.to_element(5));
+            field.div(&n, &d)
+        },
+        field,
+        10,
+    )
+    .unwrap();
+    println!("> Univariate: ({}) / ({})", num, den);
+
+    // (x*y + 1) / (x + y^2)
+    let (num, den): (MultivariatePolynomial<_, u16>, _) = fit_multivariate(
+        |p| {
+            let n = field.add(&field.mul(&p[0], &p[1]), &field.one());
+            let d = field.add(&p[0], &field.mul(&p[1], &p[1]));
+            field.div(&n, &d)
+        },
+        2,
+        field,
+        5,
+    )
+    .unwrap();
+    println!("> Multivariate: ({}) / ({})", num, den);
+}
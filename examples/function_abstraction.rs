@@ -0,0 +1,43 @@
+use symbolica::{
+    parser::parse,
+    poly::FunctionAbstraction,
+    printer::{AtomPrinter, RationalPolynomialPrinter},
+    representations::default::DefaultRepresentation,
+    rings::{integer::IntegerRing, rational::RationalField},
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let expr = parse("(sin(x)^2-1)/(sin(x)+1) + f(x,y)*x^(1/2)/f(x,y)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let mut abstraction = FunctionAbstraction::new();
+    let rat = expr
+        .to_view()
+        .to_rational_polynomial_with_abstraction::<_, _, u8>(
+            &workspace,
+            &mut state,
+            RationalField::new(),
+            IntegerRing::new(),
+            &mut abstraction,
+        )
+        .unwrap();
+
+    println!(
+        "> Rational polynomial: {}",
+        RationalPolynomialPrinter::new(&rat, &state, <_>::default())
+    );
+
+    let mut out = workspace.new_atom();
+    out.get_mut()
+        .from_rational_polynomial_with_abstraction(&workspace, &state, &rat, &abstraction);
+    println!(
+        "> Substituted back: {}",
+        AtomPrinter::new(out.get().to_view(), <_>::default(), &state)
+    );
+}
@@ -0,0 +1,51 @@
+use ahash::HashMap;
+use symbolica::{
+    function_table::{export_function_table, import_function_table},
+    state::{FunctionAttribute, InverseDomain, State},
+};
+
+fn main() {
+    let mut state = State::new();
+    let exp = state.get_or_insert_var("exp");
+    let log = state.get_or_insert_var("log");
+    let t = state.get_or_insert_var("T");
+    let d = state.get_or_insert_var("d");
+    state.set_inverse_function(exp, log, InverseDomain::Real);
+    state.set_function_attributes(t, &[FunctionAttribute::NonCommutative]);
+    state.set_function_attributes(d, &[FunctionAttribute::KroneckerDelta]);
+
+    let mut annotations = HashMap::default();
+    annotations.insert(t, "SU(N) generator \"T^a\"".to_string());
+
+    let mut table = vec![];
+    export_function_table(&state, &annotations, &mut table).unwrap();
+    let table = String::from_utf8(table).unwrap();
+    println!("{}", table);
+
+    // a project that already uses `d` for something else
+    let mut other = State::new();
+    let d = other.get_or_insert_var("d");
+    other.set_function_attributes(d, &[FunctionAttribute::Conditional]);
+
+    let result = import_function_table(&table, &mut other).unwrap();
+    for id in &result.imported {
+        println!("> Imported {}", other.get_name(*id).unwrap());
+    }
+    for c in &result.collisions {
+        println!(
+            "> Collision for {}: existing {:?}, imported {:?}",
+            c.existing.name, c.existing.attributes, c.imported.attributes
+        );
+    }
+    for (id, a) in &result.annotations {
+        println!("> {}: {}", other.get_name(*id).unwrap(), a);
+    }
+
+    let exp = other.get_or_insert_var("exp");
+    let log = other.get_or_insert_var("log");
+    assert_eq!(
+        other.get_inverse_function(exp),
+        Some((log, InverseDomain::Real))
+    );
+    assert_eq!(result.collisions.len(), 1);
+}
@@ -0,0 +1,62 @@
+use arbitrary::{Arbitrary, Unstructured};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use symbolica::{
+    fuzz::{ArbitraryAtom, AtomGenerator, GeneratorSettings},
+    poly::polynomial::MultivariatePolynomial,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    rings::integer::IntegerRing,
+    state::{ResettableBuffer, State, Workspace},
+};
+
+// run with `cargo run --example fuzz --features arbitrary`
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut data = vec![0u8; 1 << 16];
+    rng.fill(&mut data[..]);
+    let mut u = Unstructured::new(&data);
+
+    // test that (a * b) / b = a for random polynomials
+    for _ in 0..100 {
+        let a = MultivariatePolynomial::<IntegerRing, u8>::arbitrary(&mut u).unwrap();
+        let mut b = MultivariatePolynomial::<IntegerRing, u8>::arbitrary(&mut u).unwrap();
+        if b.is_zero() {
+            continue;
+        }
+        if a.nvars != b.nvars {
+            b = MultivariatePolynomial::arbitrary_with(
+                &mut u,
+                a.field,
+                a.nvars,
+                None,
+                &GeneratorSettings::default(),
+            )
+            .unwrap();
+            if b.is_zero() {
+                continue;
+            }
+        }
+
+        assert_eq!((&a * &b).divides(&b), Some(a));
+    }
+    println!("> Division test passed");
+
+    let generator = AtomGenerator::new(2, 1, GeneratorSettings::default(), &mut state);
+    for _ in 0..5 {
+        let mut out = OwnedAtom::new();
+        generator
+            .generate(&mut u, &workspace, &state, &mut out)
+            .unwrap();
+        println!(
+            "> {}",
+            AtomPrinter::new(out.to_view(), <_>::default(), &state)
+        );
+    }
+
+    // an expression with its own state, as used by a fuzz target
+    let a = ArbitraryAtom::<DefaultRepresentation>::arbitrary(&mut u).unwrap();
+    println!("> {:?}", a);
+}
@@ -0,0 +1,65 @@
+use symbolica::{
+    parser::parse,
+    physics::gamma::GammaAlgebra,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let mut gamma = GammaAlgebra::new(&mut state);
+    let p = state.get_or_insert_var("p");
+    let q = state.get_or_insert_var("q");
+    gamma.add_vector(p);
+    gamma.add_vector(q);
+    let d = state.get_or_insert_var("D");
+    gamma.set_dimension(Some(d));
+
+    let input = parse("gamma(mu)*gamma(a)*gamma(b)*gamma(mu)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let mut out = OwnedAtom::new();
+    gamma.contract(input.to_view(), &workspace, &state, &mut out);
+    println!(
+        "> {} = {}",
+        AtomPrinter::new(input.to_view(), <_>::default(), &state),
+        AtomPrinter::new(out.to_view(), <_>::default(), &state),
+    );
+
+    let input = parse("gamma(p)*gamma(mu)*gamma(q)*gamma(mu)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    gamma.trace(input.to_view(), &workspace, &state, &mut out);
+    println!(
+        "> Tr({}) = {}",
+        AtomPrinter::new(input.to_view(), <_>::default(), &state),
+        AtomPrinter::new(out.to_view(), <_>::default(), &state),
+    );
+
+    // traces with gamma5 are computed in 4 dimensions
+    gamma.set_dimension(None);
+    let input = parse("gamma(a)*gamma(b)*gamma(p)*gamma(q)*gamma5()")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    gamma.trace(input.to_view(), &workspace, &state, &mut out);
+    println!(
+        "> Tr({}) = {}",
+        AtomPrinter::new(input.to_view(), <_>::default(), &state),
+        AtomPrinter::new(out.to_view(), <_>::default(), &state),
+    );
+
+    gamma.left_projector(&workspace, &state, &mut out);
+    println!(
+        "> P_L = {}",
+        AtomPrinter::new(out.to_view(), <_>::default(), &state),
+    );
+}
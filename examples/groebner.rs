@@ -0,0 +1,116 @@
+use symbolica::{
+    parser::parse,
+    poly::{
+        groebner::{solve_polynomial_system, GroebnerBasis, PolynomialSystemSolution},
+        polynomial::MultivariatePolynomial,
+        to_polynomials,
+    },
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    rings::rational::RationalField,
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    for system in [
+        &["x^2+y^2-1", "x-y"][..],
+        &["x^2-2", "y^2+1", "x*y-z"],
+        &["x*y-1", "x^2*y-x"],
+        &["x-1", "x-2"],
+        &["x^2", "y^3-y"],
+    ] {
+        let exprs: Vec<OwnedAtom<DefaultRepresentation>> = system
+            .iter()
+            .map(|e| parse(e).unwrap().to_atom(&mut state, &workspace).unwrap())
+            .collect();
+        let views: Vec<_> = exprs.iter().map(|e| e.to_view()).collect();
+        let polys: Vec<MultivariatePolynomial<_, u16>> =
+            to_polynomials(&views, RationalField::new()).unwrap();
+
+        println!("> Solving {:?}:", system);
+        match solve_polynomial_system(&polys) {
+            PolynomialSystemSolution::Inconsistent => println!("\tNo solutions"),
+            PolynomialSystemSolution::ZeroDimensional { count, solutions } => {
+                println!("\t{} solutions", count);
+                for s in solutions {
+                    let s: Vec<_> = s
+                        .iter()
+                        .map(|(re, im)| format!("{:.6}{:+.6}i", re, im))
+                        .collect();
+                    println!("\t{}", s.join(", "));
+                }
+            }
+            PolynomialSystemSolution::PositiveDimensional {
+                free_variables,
+                basis,
+            } => {
+                println!("\tFree variables {:?}, with basis:", free_variables);
+                for p in basis.system {
+                    println!("\t{} = 0", p);
+                }
+            }
+        }
+    }
+
+    // implicitize x = t^2, y = t^3
+    let exprs: Vec<OwnedAtom<DefaultRepresentation>> = ["t^2", "t^3"]
+        .iter()
+        .map(|e| parse(e).unwrap().to_atom(&mut state, &workspace).unwrap())
+        .collect();
+    let views: Vec<_> = exprs.iter().map(|e| e.to_view()).collect();
+    let param: Vec<MultivariatePolynomial<_, u16>> =
+        to_polynomials(&views, RationalField::new()).unwrap();
+    for p in MultivariatePolynomial::implicitize(&param) {
+        println!("> Implicit relation of (t^2, t^3): {} = 0", p);
+    }
+
+    // invert (x, y) -> (x, y + x^2)
+    let exprs: Vec<OwnedAtom<DefaultRepresentation>> = ["x", "y+x^2"]
+        .iter()
+        .map(|e| parse(e).unwrap().to_atom(&mut state, &workspace).unwrap())
+        .collect();
+    let views: Vec<_> = exprs.iter().map(|e| e.to_view()).collect();
+    let map: Vec<MultivariatePolynomial<_, u16>> =
+        to_polynomials(&views, RationalField::new()).unwrap();
+    let inverse = MultivariatePolynomial::invert_map(&map).unwrap();
+    println!(
+        "> Inverse of (x, y+x^2): ({})",
+        inverse
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    // a system whose basis has large coefficients, computed modulo primes
+    let exprs: Vec<OwnedAtom<DefaultRepresentation>> = [
+        "7*x*y-3*z^2+5/2*y-11",
+        "13/3*x*z-y^2+2*z-17/5",
+        "y*z+9*x-4/7*z^2+1",
+    ]
+    .iter()
+    .map(|e| parse(e).unwrap().to_atom(&mut state, &workspace).unwrap())
+    .collect();
+    let views: Vec<_> = exprs.iter().map(|e| e.to_view()).collect();
+    let ideal: Vec<MultivariatePolynomial<_, u16>> =
+        to_polynomials(&views, RationalField::new()).unwrap();
+
+    let modular = GroebnerBasis::new_modular(&ideal);
+    let direct = GroebnerBasis::new(&ideal);
+    let equal = modular.system.len() == direct.system.len()
+        && modular
+            .system
+            .iter()
+            .zip(&direct.system)
+            .all(|(a, b)| (a.clone() - b.clone()).is_zero());
+    println!(
+        "> Modular Gröbner basis with {} polynomials, equal to direct computation: {}",
+        modular.system.len(),
+        equal
+    );
+    for p in &modular.system {
+        println!("\t{} = 0", p);
+    }
+}
@@ -0,0 +1,40 @@
+use symbolica::{
+    poly::polynomial::MultivariatePolynomial,
+    rings::integer::{Integer, IntegerRing},
+    state::State,
+};
+
+fn main() {
+    let mut state = State::new();
+    let vars = ["x", "y", "z", "w"];
+    let field = IntegerRing::new();
+
+    let a = MultivariatePolynomial::<IntegerRing, u8>::parse("1+x+y+z+w", &vars, field, &mut state)
+        .unwrap();
+    let b = MultivariatePolynomial::<IntegerRing, u8>::parse(
+        "3+x*y+2*z^2*w+x^3",
+        &vars,
+        field,
+        &mut state,
+    )
+    .unwrap();
+    let p = &(&(&a * &a) * &a) * &b;
+
+    println!(
+        "> Operations with order [3, 2, 1, 0]: {}",
+        p.horner_op_count(&[3, 2, 1, 0])
+    );
+
+    let (order, scheme) = p.optimize_horner_scheme(1000, 10., 0.99);
+    println!(
+        "> Optimized order {:?}: {:?}",
+        order,
+        scheme.op_count(field)
+    );
+
+    let values = [2, 3, 5, 7].map(Integer::Natural);
+    println!(
+        "> Value at (2, 3, 5, 7): {:?}",
+        scheme.evaluate(field, &values)
+    );
+}
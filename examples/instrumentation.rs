@@ -0,0 +1,31 @@
+use symbolica::{
+    instrumentation,
+    poly::polynomial::MultivariatePolynomial,
+    rings::integer::{Integer, IntegerRing},
+};
+
+// run with `cargo run --example instrumentation --features instrumentation`
+fn main() {
+    let field = IntegerRing::new();
+    let mut a = MultivariatePolynomial::<_, u16>::new(3, field, None, None);
+    a.append_monomial(Integer::Natural(1), &[0, 0, 0]);
+    a.append_monomial(Integer::Natural(1), &[1, 0, 0]);
+    a.append_monomial(Integer::Natural(2), &[0, 1, 0]);
+    a.append_monomial(Integer::Natural(3), &[0, 0, 1]);
+
+    let mut b = a.clone();
+    b.append_monomial(Integer::Natural(1), &[1, 1, 1]);
+
+    let mut p1 = a.clone();
+    let mut p2 = b.clone();
+    for _ in 0..3 {
+        p1 = p1.heap_mul(&a);
+        p2 = p2.heap_mul(&b);
+    }
+
+    // the gcd is a^4
+    let g = MultivariatePolynomial::gcd(&p1, &p2.heap_mul(&p1));
+    println!("> gcd has {} terms", g.nterms);
+
+    print!("{}", instrumentation::summary());
+}
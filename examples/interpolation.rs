@@ -0,0 +1,41 @@
+use ahash::HashMap;
+use symbolica::{
+    interpolation::InterpolationTable,
+    parser::parse,
+    representations::default::DefaultRepresentation,
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    // a parton density that is only known at a set of nodes
+    let x: Vec<f64> = (1..=20).map(|i| i as f64 / 20.).collect();
+    let y: Vec<f64> = x.iter().map(|x| x.powf(-0.5) * (1. - x).powi(3)).collect();
+
+    let pdf = state.get_or_insert_var("pdf");
+    state.set_interpolation_table(pdf, Some(InterpolationTable::new(x, y).unwrap()));
+
+    let expr = parse("pdf(z)*pdf(3/10/z)/z")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let z = state.get_or_insert_var("z");
+    let mut values = HashMap::default();
+    for v in [0.4, 0.5, 0.75] {
+        values.insert(z, v);
+        let exact = |x: f64| x.powf(-0.5) * (1. - x).powi(3);
+        println!(
+            "> z = {}: {:.6} (exact {:.6})",
+            v,
+            expr.to_view().evaluate_f64(&values, &state).unwrap(),
+            exact(v) * exact(0.3 / v) / v
+        );
+    }
+
+    // outside of the range of the table
+    values.insert(z, 0.01);
+    assert_eq!(expr.to_view().evaluate_f64(&values, &state), None);
+}
@@ -0,0 +1,51 @@
+use ahash::HashMap;
+use symbolica::{
+    interval::Interval,
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    rings::rational::RationalField,
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let input: OwnedAtom<DefaultRepresentation> = parse("2+x^2-x*y+1/3*y^3")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let x = state.get_or_insert_var("x");
+    let y = state.get_or_insert_var("y");
+
+    let mut domain = HashMap::default();
+    domain.insert(x, Interval::new(0., 1.));
+    domain.insert(y, Interval::new(0., 1.));
+
+    let r = input.to_view().evaluate_interval(&domain).unwrap();
+    println!(
+        "> {} on [0,1]^2 is in {}",
+        AtomPrinter::new(input.to_view(), <_>::default(), &state),
+        r
+    );
+    assert!(r.is_positive());
+
+    let den = parse("1/(x-y)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    assert!(den.to_view().evaluate_interval(&domain).is_none());
+
+    let poly = input
+        .to_view()
+        .to_polynomial::<_, u8>(RationalField::new(), None)
+        .unwrap();
+    let values = [Interval::new(-1., 1.), Interval::new(0.5, 2.)];
+    println!(
+        "> {} on [-1,1]x[0.5,2] is in {}",
+        poly,
+        poly.evaluate_interval(&values)
+    );
+}
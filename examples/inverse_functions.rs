@@ -0,0 +1,51 @@
+use symbolica::{
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{Assumptions, InverseDomain, ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    // log inverts exp for real arguments, asin inverts sin only on the principal domain
+    let exp = state.get_or_insert_var("exp");
+    let log = state.get_or_insert_var("log");
+    state.set_inverse_function(exp, log, InverseDomain::Real);
+    let sin = state.get_or_insert_var("sin");
+    let asin = state.get_or_insert_var("asin");
+    state.set_inverse_function(sin, asin, InverseDomain::None);
+
+    let x = state.get_or_insert_var("x");
+    state.set_assumptions(
+        x,
+        Assumptions {
+            real: true,
+            ..Default::default()
+        },
+    );
+
+    for input in [
+        "exp(log(z))",
+        "log(exp(z))",
+        "log(exp(x))",
+        "sin(asin(z))",
+        "asin(sin(x))",
+        "2*exp(log(x+1))^2",
+    ] {
+        let expr: OwnedAtom<DefaultRepresentation> = parse(input)
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+
+        let mut out = OwnedAtom::new();
+        expr.to_view().normalize(&workspace, &state, &mut out);
+
+        println!(
+            "\t{} = {}",
+            input,
+            AtomPrinter::new(out.to_view(), <_>::default(), &state)
+        );
+    }
+}
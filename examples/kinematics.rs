@@ -0,0 +1,139 @@
+use symbolica::{
+    parser::parse,
+    physics::kinematics::{kallen, Kinematics},
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let [p1, p2, p3, m1, m2, s, t, u] =
+        ["p1", "p2", "p3", "m1", "m2", "s", "t", "u"].map(|x| state.get_or_insert_var(x));
+
+    // a massive two-particle state
+    let mut kin = Kinematics::new(&mut state);
+    kin.set_on_shell(p1, Some(m1));
+    kin.set_on_shell(p2, Some(m2));
+    kin.add_invariant(p1, p2, s);
+
+    let mut gram = OwnedAtom::new();
+    kin.gram_determinant(&[p1, p2], &workspace, &state, &mut gram);
+    println!(
+        "> Gram determinant: {}",
+        AtomPrinter::new(gram.to_view(), <_>::default(), &state)
+    );
+
+    let mut replaced = OwnedAtom::new();
+    kin.get_rules(&workspace, &state).apply(
+        gram.to_view(),
+        &state,
+        &workspace,
+        &mut replaced,
+        None,
+    );
+    let mut expanded = OwnedAtom::new();
+    replaced.to_view().expand(&workspace, &state, &mut expanded);
+    println!(
+        "> With invariants: {}",
+        AtomPrinter::new(expanded.to_view(), <_>::default(), &state)
+    );
+
+    let args = ["s", "m1^2", "m2^2", "-1/4"]
+        .map(|x| parse(x).unwrap().to_atom(&mut state, &workspace).unwrap());
+    let mut lambda = OwnedAtom::new();
+    kallen(
+        args[0].to_view(),
+        args[1].to_view(),
+        args[2].to_view(),
+        &workspace,
+        &state,
+        &mut lambda,
+    );
+    println!(
+        "> Källén function: {}",
+        AtomPrinter::new(lambda.to_view(), <_>::default(), &state)
+    );
+
+    let mut scaled = OwnedAtom::new();
+    (lambda.to_view().builder(&state, &workspace) * args[3].to_view()).finish(&mut scaled);
+    let mut scaled_expanded = OwnedAtom::new();
+    scaled
+        .to_view()
+        .expand(&workspace, &state, &mut scaled_expanded);
+    println!(
+        "> Gram determinant = -λ/4: {}",
+        scaled_expanded.to_view() == expanded.to_view()
+    );
+
+    // three massless momenta
+    let mut kin = Kinematics::new(&mut state);
+    for p in [p1, p2, p3] {
+        kin.set_on_shell(p, None);
+    }
+    kin.add_invariant(p1, p2, s);
+    kin.add_invariant(p1, p3, t);
+    kin.add_invariant(p2, p3, u);
+
+    let mut gram = OwnedAtom::new();
+    kin.gram_determinant(&[p1, p2, p3], &workspace, &state, &mut gram);
+    let mut replaced = OwnedAtom::new();
+    kin.get_rules(&workspace, &state).apply(
+        gram.to_view(),
+        &state,
+        &workspace,
+        &mut replaced,
+        None,
+    );
+    println!(
+        "> Massless Gram determinant: {}",
+        AtomPrinter::new(replaced.to_view(), <_>::default(), &state)
+    );
+
+    scalar_product_relations();
+}
+
+fn scalar_product_relations() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let [p1, p2, p3, p4, s, t, u] =
+        ["p1", "p2", "p3", "p4", "s", "t", "u"].map(|x| state.get_or_insert_var(x));
+
+    // massless 2 -> 2 scattering with all momenta incoming
+    let mut kin = Kinematics::new(&mut state);
+    for p in [p1, p2, p3, p4] {
+        kin.set_on_shell(p, None);
+    }
+    kin.add_invariant(p1, p2, s);
+    kin.add_invariant(p1, p3, t);
+    kin.add_invariant(p2, p3, u);
+
+    let relations = kin.momentum_conservation(&[p1, p2, p3, p4], &workspace, &state);
+    let views: Vec<_> = relations.iter().map(|r| r.to_view()).collect();
+    let rules = kin.solve_relations(&views, &workspace, &state).unwrap();
+    for sp in ["dot(p1,p4)", "dot(p2,p4)", "dot(p3,p4)"] {
+        let sp = parse(sp).unwrap().to_atom(&mut state, &workspace).unwrap();
+        let mut out = OwnedAtom::new();
+        kin.canonicalize(sp.to_view(), &rules, &workspace, &state, &mut out);
+        println!(
+            "> {} = {}",
+            AtomPrinter::new(sp.to_view(), <_>::default(), &state),
+            AtomPrinter::new(out.to_view(), <_>::default(), &state)
+        );
+    }
+
+    let expr = parse("dot(p1+p2,p3+p4)^2 - dot(p4,p1-2*p2)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let mut out = OwnedAtom::new();
+    kin.canonicalize(expr.to_view(), &rules, &workspace, &state, &mut out);
+    println!(
+        "> {} = {}",
+        AtomPrinter::new(expr.to_view(), <_>::default(), &state),
+        AtomPrinter::new(out.to_view(), <_>::default(), &state)
+    );
+}
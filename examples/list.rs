@@ -0,0 +1,128 @@
+use ahash::HashMap;
+use symbolica::{
+    id::{Pattern, PatternRestriction},
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, AtomView, OwnedAtom, OwnedMul},
+    state::{FunctionAttribute, ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let v = state.get_or_insert_var("v");
+    state.set_function_attributes(v, &[FunctionAttribute::List]);
+
+    let a = parse("v(x, y+1, 3)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let b = parse("v(2, x, y)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let second = a.to_view().get_list_element(1, &state).unwrap();
+    println!(
+        "> Second element of {}: {}",
+        AtomPrinter::new(a.to_view(), <_>::default(), &state),
+        AtomPrinter::new(second, <_>::default(), &state)
+    );
+
+    // multiply the elements pairwise
+    let mut product = OwnedAtom::new();
+    a.to_view()
+        .zip_lists(
+            b.to_view(),
+            |e1: AtomView<'_, DefaultRepresentation>, e2, out| {
+                let mut m = workspace.new_atom();
+                let mul = m.get_mut().transform_to_mul();
+                mul.extend(e1);
+                mul.extend(e2);
+                mul.set_dirty(true);
+                let mut norm = workspace.new_atom();
+                m.get()
+                    .to_view()
+                    .normalize(&workspace, &state, norm.get_mut());
+                norm.get().to_view().expand(&workspace, &state, out);
+            },
+            &workspace,
+            &state,
+            &mut product,
+        )
+        .unwrap();
+    println!(
+        "> Pairwise product: {}",
+        AtomPrinter::new(product.to_view(), <_>::default(), &state)
+    );
+
+    // rename every element with a pattern
+    let pattern = Pattern::from_view(
+        parse("x")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap()
+            .to_view(),
+        &state,
+    );
+    let rhs = Pattern::from_view(
+        parse("z^2")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap()
+            .to_view(),
+        &state,
+    );
+    let mut mapped = OwnedAtom::new();
+    product
+        .to_view()
+        .map_list(
+            |e, out| {
+                pattern.replace_all(e, &rhs, &state, &workspace, &HashMap::default(), out);
+            },
+            &workspace,
+            &state,
+            &mut mapped,
+        )
+        .unwrap();
+    println!(
+        "> With x = z^2: {}",
+        AtomPrinter::new(mapped.to_view(), <_>::default(), &state)
+    );
+
+    // lists can be matched as function arguments
+    let expr = parse("f(v(1, 2), v(a, b, c))")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let lhs = parse("v(x_, y_)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let rhs = parse("x_ + y_")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let mut restrictions = HashMap::default();
+    for w in ["x_", "y_"] {
+        restrictions.insert(
+            state.get_or_insert_var(w),
+            vec![PatternRestriction::Length(1, Some(1))],
+        );
+    }
+    let mut out = OwnedAtom::new();
+    Pattern::from_view(lhs.to_view(), &state).replace_all(
+        expr.to_view(),
+        &Pattern::from_view(rhs.to_view(), &state),
+        &state,
+        &workspace,
+        &restrictions,
+        &mut out,
+    );
+    println!(
+        "> Sum of lists of length 2 in {}: {}",
+        AtomPrinter::new(expr.to_view(), <_>::default(), &state),
+        AtomPrinter::new(out.to_view(), <_>::default(), &state)
+    );
+}
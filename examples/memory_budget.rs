@@ -0,0 +1,48 @@
+use ahash::HashMap;
+use symbolica::{
+    budget::MemoryBudget,
+    id::{Pattern, Rule, RuleSet},
+    parser::parse,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+
+    // every application doubles the size of the expression
+    let workspace: Workspace<DefaultRepresentation> =
+        Workspace::new().with_memory_budget(MemoryBudget::new(1 << 20));
+
+    let expr = parse("f(x)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let lhs = parse("f(x_)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let rhs = parse("f(x_+x_^2)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let mut rules = RuleSet::new();
+    rules.add_rule(Rule {
+        name: "grow".to_owned(),
+        lhs: Pattern::from_view(lhs.to_view(), &state),
+        rhs: Pattern::from_view(rhs.to_view(), &state),
+        restrictions: HashMap::default(),
+        priority: 0,
+    });
+
+    let mut out = OwnedAtom::new();
+    match rules.try_apply(expr.to_view(), &state, &workspace, &mut out, None) {
+        Ok(_) => println!("> Fixed point found"),
+        Err(e) => println!("> {}", e),
+    }
+    println!(
+        "> Memory of the workspace: {} bytes",
+        workspace.memory_usage()
+    );
+}
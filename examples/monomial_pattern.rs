@@ -0,0 +1,48 @@
+use symbolica::{
+    parser::parse,
+    poly::monomial_pattern::MonomialPattern,
+    printer::{PolynomialPrinter, PrintMode},
+    representations::default::DefaultRepresentation,
+    rings::rational::RationalField,
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let a = parse("x^3 + 2*x^2*y + 3*x^2 + 4*x + 5*y^2")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let p = a
+        .to_view()
+        .to_polynomial::<_, u8>(RationalField::new(), None)
+        .unwrap();
+    let (x, y) = (0, 1);
+
+    // degree at least 2 in x and no y
+    let pattern = MonomialPattern::new()
+        .with_min_degree(x, 2)
+        .with_degree(y, 0);
+
+    print!("> monomials of degree >= 2 in x and 0 in y:");
+    for m in p.find_monomials(&pattern) {
+        print!(" {}*x^{}", m.coefficient, m.exponents[x]);
+    }
+    println!();
+
+    // replace x^n by x^(n-2)*(1 - y), lowering the degree in x
+    let r = p.replace_monomials(&pattern, |m| {
+        let mut exp = m.exponents.to_vec();
+        exp[x] -= 2;
+        let t = p.new_from_monomial(m.coefficient.clone(), exp.clone());
+        exp[y] += 1;
+        t - p.new_from_monomial(m.coefficient.clone(), exp)
+    });
+
+    println!(
+        "> {}",
+        PolynomialPrinter::new(&r, &state, PrintMode::default())
+    );
+}
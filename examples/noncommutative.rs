@@ -0,0 +1,29 @@
+use symbolica::{
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{FunctionAttribute, ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    // gamma matrices do not commute
+    let gamma = state.get_or_insert_var("g");
+    state.set_function_attributes(gamma, &[FunctionAttribute::NonCommutative]);
+
+    let input = parse("g(mu)*y*g(nu)*2*g(mu)*x*(g(nu)+g(rho))*g(rho)*g(rho)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let mut o = OwnedAtom::new();
+    input.to_view().expand(&workspace, &state, &mut o);
+
+    println!(
+        "> Expansion of {}: {}",
+        AtomPrinter::new(input.to_view(), <_>::default(), &state),
+        AtomPrinter::new(o.to_view(), <_>::default(), &state),
+    );
+}
@@ -0,0 +1,36 @@
+use symbolica::{
+    parser::parse,
+    printer::{PolynomialPrinter, PrintMode},
+    representations::default::DefaultRepresentation,
+    rings::integer::IntegerRing,
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let a = parse("(x - 5*y + 7)*(x^2 - 3*y^2 + 12)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let mut expanded = workspace.new_atom();
+    a.to_view().expand(&workspace, &state, &mut expanded);
+
+    let p = expanded
+        .to_view()
+        .to_polynomial::<_, u8>(IntegerRing::new(), None)
+        .unwrap();
+
+    println!(
+        "> f = {}",
+        PolynomialPrinter::new(&p, &state, PrintMode::default())
+    );
+    println!("> max norm: {}", p.max_norm());
+    println!("> l1 norm: {}", p.l1_norm());
+    println!("> l2 norm (rounded up): {}", p.l2_norm_ceil());
+    println!(
+        "> the coefficients of every factor are at most {}",
+        p.mignotte_bound()
+    );
+}
@@ -0,0 +1,83 @@
+use ahash::HashMap;
+use symbolica::{
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    rings::rational::{Rational, RationalField},
+    slp::{conditional_evaluation_fn, EvaluationFn, Slp},
+    state::{Assumptions, FunctionAttribute, ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    // if(c, a, b) is a if c > 0 and b otherwise
+    let cond = state.get_or_insert_var("if");
+    state.set_function_attributes(cond, &[FunctionAttribute::Conditional]);
+
+    let m = state.get_or_insert_var("m");
+    state.set_assumptions(
+        m,
+        Assumptions {
+            positive: true,
+            ..Default::default()
+        },
+    );
+
+    for input in [
+        "if(3, x, y)",
+        "if(m^2*(-1), x, y)",
+        "if(z, x^2, x*x)",
+        "if(z, x, y)",
+    ] {
+        let expr: OwnedAtom<DefaultRepresentation> = parse(input)
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+
+        let mut out = OwnedAtom::new();
+        expr.to_view().normalize(&workspace, &state, &mut out);
+
+        println!(
+            "\t{} = {}",
+            input,
+            AtomPrinter::new(out.to_view(), <_>::default(), &state)
+        );
+    }
+
+    // a function with a threshold at x = 1
+    let f = parse("if(x - 1, x^2, 2*x - 1)*y")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let x = state.get_or_insert_var("x");
+    let mut df = workspace.new_atom();
+    f.to_view()
+        .derivative(x, &workspace, &state, df.get_mut())
+        .unwrap();
+    println!(
+        "> d/dx {} = {}",
+        AtomPrinter::new(f.to_view(), <_>::default(), &state),
+        AtomPrinter::new(df.get().to_view(), <_>::default(), &state)
+    );
+
+    let field = RationalField::new();
+    let slp = Slp::from_atom(f.to_view(), field).unwrap();
+    let mut functions: HashMap<_, EvaluationFn<RationalField>> = HashMap::default();
+    functions.insert(cond, conditional_evaluation_fn(field));
+
+    for x_value in [0, 3] {
+        let values: Vec<_> = slp
+            .variables
+            .iter()
+            .map(|v| Rational::Natural(if *v == x { x_value } else { 2 }, 1))
+            .collect();
+        println!(
+            "> Value at x = {}, y = 2: {:?}",
+            x_value,
+            slp.evaluate(&values, &functions).unwrap()
+        );
+    }
+}
@@ -1,9 +1,12 @@
 use symbolica::{
-    poly::polynomial::MultivariatePolynomial,
+    parser::parse,
+    poly::{polynomial::MultivariatePolynomial, to_polynomials},
+    representations::{default::DefaultRepresentation, OwnedAtom},
     rings::{
         integer::{Integer, IntegerRing},
         rational::{Rational, RationalField},
     },
+    state::{ResettableBuffer, State, Workspace},
 };
 use tracing_subscriber::{fmt, prelude::*, util::SubscriberInitExt, EnvFilter};
 
@@ -51,6 +54,59 @@ fn gcd_rat_poly() {
     println!("\t{}", MultivariatePolynomial::gcd(&a, &b));
 }
 
+fn gcd_multiple_poly() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let exprs: Vec<OwnedAtom<DefaultRepresentation>> = [
+        "6*(x+y+1)*(x-y)",
+        "4*(x+y+1)*(x^2+1)",
+        "10*(x+y+1)*(y+2)",
+        "8*(x+y+1)^2",
+    ]
+    .iter()
+    .map(|e| {
+        let a = parse(e).unwrap().to_atom(&mut state, &workspace).unwrap();
+        let mut expanded = OwnedAtom::new();
+        a.to_view().expand(&workspace, &state, &mut expanded);
+        expanded
+    })
+    .collect();
+    let views: Vec<_> = exprs.iter().map(|e| e.to_view()).collect();
+    let polys = to_polynomials::<_, _, u8>(&views, IntegerRing::new()).unwrap();
+
+    println!("> Polynomial gcd of");
+    for p in &polys {
+        println!("\t{}", p);
+    }
+    println!("> =\t{}", MultivariatePolynomial::gcd_multiple(&polys));
+}
+
+fn gcd_known_bounds() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let exprs: Vec<OwnedAtom<DefaultRepresentation>> = [
+        "(x^2*y+z^3+3*x*y*z-1)*(x+y^2+z+5)^2*x^2",
+        "(x^2*y+z^3+3*x*y*z-1)*(x*y*z-y-7)^3*x^3",
+    ]
+    .iter()
+    .map(|e| {
+        let a = parse(e).unwrap().to_atom(&mut state, &workspace).unwrap();
+        let mut expanded = OwnedAtom::new();
+        a.to_view().expand(&workspace, &state, &mut expanded);
+        expanded
+    })
+    .collect();
+    let views: Vec<_> = exprs.iter().map(|e| e.to_view()).collect();
+    let polys = to_polynomials::<_, _, u8>(&views, IntegerRing::new()).unwrap();
+
+    // the gcd has at most degree 4 in x, 1 in y and 3 in z
+    let g = MultivariatePolynomial::gcd_with_bounds(&polys[0], &polys[1], &[2, 0, 1], &[4, 1, 3]);
+    assert_eq!(g, MultivariatePolynomial::gcd(&polys[0], &polys[1]));
+    println!("> Polynomial gcd with known degree bounds =\n\t{}", g);
+}
+
 fn main() {
     tracing_subscriber::registry()
         .with(fmt::layer())
@@ -59,4 +115,6 @@ fn main() {
 
     gcd_integer_poly();
     gcd_rat_poly();
+    gcd_multiple_poly();
+    gcd_known_bounds();
 }
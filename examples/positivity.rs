@@ -0,0 +1,34 @@
+use symbolica::{
+    poly::polynomial::MultivariatePolynomial,
+    rings::integer::{Integer, IntegerRing},
+};
+
+fn main() {
+    let field = IntegerRing::new();
+
+    // x^2 - x*y + y^2 is positive, but has a negative coefficient
+    let mut a = MultivariatePolynomial::<IntegerRing, u8>::new(2, field, None, None);
+    a.append_monomial(Integer::Natural(1), &[2, 0]);
+    a.append_monomial(Integer::Natural(-1), &[1, 1]);
+    a.append_monomial(Integer::Natural(1), &[0, 2]);
+    println!("> {}: {:?}", a, a.check_positivity(10));
+
+    // 1 - x + x^2
+    let mut b = MultivariatePolynomial::<IntegerRing, u8>::new(1, field, None, None);
+    b.append_monomial(Integer::Natural(1), &[0]);
+    b.append_monomial(Integer::Natural(-1), &[1]);
+    b.append_monomial(Integer::Natural(1), &[2]);
+    println!("> {}: {:?}", b, b.check_positivity(10));
+
+    // x^2 - 3*x*y + y^2 is negative at x = y = 1
+    let mut c = MultivariatePolynomial::<IntegerRing, u8>::new(2, field, None, None);
+    c.append_monomial(Integer::Natural(1), &[2, 0]);
+    c.append_monomial(Integer::Natural(-3), &[1, 1]);
+    c.append_monomial(Integer::Natural(1), &[0, 2]);
+    println!("> {}: {:?}", c, c.check_positivity(10));
+
+    // x*y is non-negative
+    let mut d = MultivariatePolynomial::<IntegerRing, u8>::new(2, field, None, None);
+    d.append_monomial(Integer::Natural(1), &[1, 1]);
+    println!("> {}: {:?}", d, d.check_positivity(10));
+}
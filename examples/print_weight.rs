@@ -0,0 +1,41 @@
+use symbolica::{
+    parser::parse,
+    printer::AtomPrinter,
+    representations::default::DefaultRepresentation,
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let input = parse("g^4*x^2 + 3*g^2*x*y + g*y^2 + x^3 + g^2*e^2*x")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let mut expr = workspace.new_atom();
+    input.to_view().normalize(&workspace, &state, &mut expr);
+
+    println!(
+        "> Default: {}",
+        AtomPrinter::new(expr.to_view(), <_>::default(), &state)
+    );
+
+    // order the terms by their order in the couplings
+    let g = state.get_or_insert_var("g");
+    let e = state.get_or_insert_var("e");
+    state.set_print_weight(Some(Box::new(
+        move |id| {
+            if id == g || id == e {
+                1
+            } else {
+                0
+            }
+        },
+    )));
+
+    println!(
+        "> Ordered by coupling: {}",
+        AtomPrinter::new(expr.to_view(), <_>::default(), &state)
+    );
+}
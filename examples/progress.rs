@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+use symbolica::{
+    poly::polynomial::MultivariatePolynomial,
+    progress::{set_progress_observer, Progress},
+    rings::integer::{Integer, IntegerRing},
+};
+
+fn main() {
+    set_progress_observer(
+        Some(Box::new(|p: &Progress| {
+            println!(
+                "> {}: {}/{} after {:?}",
+                p.algorithm,
+                p.done,
+                p.total.unwrap_or(0),
+                p.elapsed
+            )
+        })),
+        Duration::from_millis(100),
+    );
+
+    // (1+x+y+z)^20 * (1+x+y+z)^20 computed with sparse multiplication
+    let field = IntegerRing::new();
+    let mut a = MultivariatePolynomial::<_, u16>::new(3, field, None, None);
+    a.append_monomial(Integer::Natural(1), &[0, 0, 0]);
+    a.append_monomial(Integer::Natural(1), &[1, 0, 0]);
+    a.append_monomial(Integer::Natural(1), &[0, 1, 0]);
+    a.append_monomial(Integer::Natural(1), &[0, 0, 1]);
+
+    let mut p = a.clone();
+    for _ in 0..19 {
+        p = p.heap_mul(&a);
+    }
+
+    let r = p.heap_mul(&p);
+    println!("> Result has {} terms", r.nterms);
+
+    set_progress_observer(None, Duration::ZERO);
+}
@@ -0,0 +1,50 @@
+use symbolica::{
+    parser::parse,
+    poly::FractionalExponent,
+    printer::{AtomPrinter, PolynomialPrinter},
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    rings::integer::IntegerRing,
+    state::{ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    // a truncated series around the branch point x = 0
+    let series = parse("1 + 2*x^(1/2) + x^(3/2)*y")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let poly = series
+        .to_view()
+        .to_polynomial::<_, FractionalExponent<2>>(IntegerRing::new(), None)
+        .unwrap();
+    let cube = poly.pow(3);
+    println!(
+        "> Cube: {}",
+        PolynomialPrinter::new(&cube, &state, <_>::default())
+    );
+
+    let mut cube_expr = OwnedAtom::new();
+    cube_expr.from_polynomial(&workspace, &state, &cube);
+
+    let mut expected = OwnedAtom::new();
+    parse("(1 + 2*x^(1/2) + x^(3/2)*y)^3")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap()
+        .to_view()
+        .expand(&workspace, &state, &mut expected);
+
+    println!(
+        "> Expanded: {}",
+        AtomPrinter::new(expected.to_view(), <_>::default(), &state)
+    );
+    println!(
+        "> Converted: {}",
+        AtomPrinter::new(cube_expr.to_view(), <_>::default(), &state)
+    );
+    println!("> Equal: {}", cube_expr.to_view() == expected.to_view());
+}
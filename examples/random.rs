@@ -0,0 +1,54 @@
+use rand::{rngs::StdRng, SeedableRng};
+use symbolica::{
+    poly::polynomial::MultivariatePolynomial,
+    printer::AtomPrinter,
+    random::RandomAtomGenerator,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    rings::integer::IntegerRing,
+    state::{ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let a = MultivariatePolynomial::<_, u8>::random(
+        IntegerRing::new(),
+        3,
+        10,
+        4,
+        (-100, 100),
+        &mut rng,
+    );
+    let b = MultivariatePolynomial::<_, u8>::random(
+        IntegerRing::new(),
+        3,
+        10,
+        4,
+        (-100, 100),
+        &mut rng,
+    );
+    assert_eq!(a.nterms, 10);
+    println!("> a = {}", a);
+    println!("> b = {}", b);
+
+    // the gcd of a*b and a is a up to a unit
+    let g = MultivariatePolynomial::gcd(&(&a * &b), &a);
+    println!("> gcd(a*b, a) = {}", g);
+
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+    let vars = [state.get_or_insert_var("x"), state.get_or_insert_var("y")];
+    let funcs = [state.get_or_insert_var("f")];
+
+    let mut generator = RandomAtomGenerator::new(&vars, &funcs);
+    generator.max_depth = 2;
+
+    for _ in 0..3 {
+        let mut e = OwnedAtom::new();
+        generator.generate(&mut rng, &workspace, &state, &mut e);
+        println!(
+            "> {}",
+            AtomPrinter::new(e.to_view(), <_>::default(), &state)
+        );
+    }
+}
@@ -1,9 +1,11 @@
 use symbolica::{
     parser::parse,
-    printer::{PrintMode, RationalPolynomialPrinter},
+    printer::{PrintMode, RationalPolynomialPrinter, SymbolicaPrintOptions},
     representations::{default::DefaultRepresentation, OwnedAtom},
     rings::{
-        integer::IntegerRing, rational::RationalField, rational_polynomial::RationalPolynomial,
+        integer::IntegerRing,
+        rational::RationalField,
+        rational_polynomial::{RationalPolynomial, RationalPolynomialNormalization},
     },
     state::{State, Workspace},
 };
@@ -30,4 +32,39 @@ fn main() {
         "{}",
         RationalPolynomialPrinter::new(&rat, &state, <_>::default())
     );
+
+    println!(
+        "d/dx: {}",
+        RationalPolynomialPrinter::new(&rat.derivative(0), &state, <_>::default())
+    );
+
+    let expr: OwnedAtom<DefaultRepresentation> = parse("(3*x+6*y)/(-2*x^2-4)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let rat: RationalPolynomial<IntegerRing, u8> = expr
+        .to_view()
+        .to_rational_polynomial(
+            &workspace,
+            &state,
+            RationalField::new(),
+            IntegerRing::new(),
+            None,
+        )
+        .unwrap();
+    for norm in [
+        RationalPolynomialNormalization::None,
+        RationalPolynomialNormalization::PrimitiveMonic,
+        RationalPolynomialNormalization::ContentFront,
+    ] {
+        let print_mode = PrintMode::Symbolica(SymbolicaPrintOptions {
+            rational_polynomial_normalization: norm,
+            ..Default::default()
+        });
+        println!(
+            "{:?}: {}",
+            norm,
+            RationalPolynomialPrinter::new(&rat, &state, print_mode)
+        );
+    }
 }
@@ -0,0 +1,48 @@
+use symbolica::{
+    parser::parse,
+    poly::{polynomial::MultivariatePolynomial, to_polynomials},
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    rings::{
+        rational::RationalField,
+        rationalized_polynomial::{RationalizedPolynomial, RationalizedPolynomialRing},
+        Ring,
+    },
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let exprs: Vec<OwnedAtom<DefaultRepresentation>> = ["x^2/3+x*y/6-y/2", "3/4*x-y/5+1/2"]
+        .iter()
+        .map(|e| parse(e).unwrap().to_atom(&mut state, &workspace).unwrap())
+        .collect();
+    let views: Vec<_> = exprs.iter().map(|e| e.to_view()).collect();
+    let polys = to_polynomials::<_, _, u8>(&views, RationalField::new()).unwrap();
+
+    let a = RationalizedPolynomial::from_rational_polynomial(&polys[0]);
+    let b = RationalizedPolynomial::from_rational_polynomial(&polys[1]);
+    println!("> {} = ({})/{}", polys[0], a.numerator, a.denominator);
+    println!("> {} = ({})/{}", polys[1], b.numerator, b.denominator);
+
+    let prod = &(&a * &b) + &a;
+    println!("> a*b+a = {}", prod);
+    assert_eq!(
+        prod.to_rational_polynomial(),
+        &(&polys[0] * &polys[1]) + &polys[0]
+    );
+
+    let q = prod.divides(&a).unwrap();
+    println!("> (a*b+a)/a = {}", q);
+    let ring = RationalizedPolynomialRing::new();
+    assert_eq!(q, ring.add(&b, &ring.one()));
+
+    let ab = &a * &b;
+    let g = ab.gcd(&(&b * &b));
+    println!("> gcd(a*b, b^2) = {}", g);
+    println!(
+        "> over the rationals: {}",
+        MultivariatePolynomial::gcd(&ab.to_rational_polynomial(), &(&polys[1] * &polys[1]))
+    );
+}
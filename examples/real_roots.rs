@@ -0,0 +1,38 @@
+use symbolica::{
+    parser::parse,
+    printer::{PolynomialPrinter, PrintMode},
+    representations::default::DefaultRepresentation,
+    rings::rational::{Rational, RationalField},
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    // roots at -3, 1/2 (twice), sqrt(2) and -sqrt(2)
+    let a = parse("(x+3)*(2*x-1)^2*(x^2-2)*(x^2+1)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let mut expanded = workspace.new_atom();
+    a.to_view().expand(&workspace, &state, &mut expanded);
+    let p = expanded
+        .to_view()
+        .to_polynomial::<_, u8>(RationalField::new(), None)
+        .unwrap();
+
+    println!(
+        "> f = {}",
+        PolynomialPrinter::new(&p, &state, PrintMode::default())
+    );
+    println!("> number of real roots: {}", p.count_real_roots(0));
+    println!(
+        "> number of real roots in (0, 2]: {}",
+        p.count_real_roots_in(0, (&Rational::Natural(0, 1), &Rational::Natural(2, 1)))
+    );
+
+    for (a, b) in p.isolate_real_roots(0) {
+        println!("> root in ({}, {}]", a, b);
+    }
+}
@@ -0,0 +1,67 @@
+use ahash::HashMap;
+use symbolica::{
+    id::{MatchStack, Pattern, ReplaceWith},
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom, OwnedMul},
+    state::{ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace = Workspace::new();
+
+    let expr: OwnedAtom<DefaultRepresentation> = parse("f(1,2)*f(3,3)*f(x,y)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let pat_expr = parse("f(x_,y_)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let pattern = Pattern::from_view(pat_expr.to_view(), &state);
+    let restrictions = HashMap::default();
+
+    let x = state.get_or_insert_var("x_");
+    let y = state.get_or_insert_var("y_");
+
+    // only replace when the two arguments are different
+    let condition = |m: &MatchStack<DefaultRepresentation>| m.get(x) != m.get(y);
+
+    // construct x_*y_ programmatically
+    let map = |m: &MatchStack<DefaultRepresentation>,
+               workspace: &Workspace<DefaultRepresentation>,
+               state: &State,
+               out: &mut OwnedAtom<DefaultRepresentation>| {
+        let mut mul_h = workspace.new_atom();
+        let mul = mul_h.get_mut().transform_to_mul();
+
+        for id in [x, y] {
+            let mut arg = workspace.new_atom();
+            m.get(id).unwrap().to_atom(arg.get_mut());
+            mul.extend(arg.get().to_view());
+        }
+        mul.set_dirty(true);
+
+        mul_h.get().to_view().normalize(workspace, state, out);
+    };
+
+    let mut out = OwnedAtom::new();
+
+    pattern.replace_all_with(
+        expr.to_view(),
+        &ReplaceWith::Conditional(&condition, Box::new(ReplaceWith::Map(&map))),
+        &state,
+        &workspace,
+        &restrictions,
+        &mut out,
+    );
+
+    println!(
+        "> Replace all {} with x_*y_ if x_ != y_ in {}: {}",
+        AtomPrinter::new(pat_expr.to_view(), <_>::default(), &state),
+        AtomPrinter::new(expr.to_view(), <_>::default(), &state),
+        AtomPrinter::new(out.to_view(), <_>::default(), &state)
+    );
+}
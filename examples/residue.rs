@@ -0,0 +1,52 @@
+use symbolica::{
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let x = state.get_or_insert_var("x");
+
+    for (input, point) in [
+        ("1/(x^2+1)", "0"),
+        ("1/(x^2-1)", "1"),
+        ("1/(x^2-1)", "-1"),
+        ("x^2/(x-2)^3", "2"),
+        ("(x+3)/(x^3*(x-1)^2)", "0"),
+        ("f(y)/((x-a)^2*(x-b))", "a"),
+        ("1/(x^2-2)", "2^(1/2)"),
+        ("sin(x)/x^2", "0"),
+    ] {
+        let expr: OwnedAtom<DefaultRepresentation> = parse(input)
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+        let point = parse(point)
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+
+        let mut out = OwnedAtom::new();
+        match expr
+            .to_view()
+            .residue(x, point.to_view(), &workspace, &mut state, &mut out)
+        {
+            Ok(()) => println!(
+                "> Res({}, x = {}) = {}",
+                AtomPrinter::new(expr.to_view(), <_>::default(), &state),
+                AtomPrinter::new(point.to_view(), <_>::default(), &state),
+                AtomPrinter::new(out.to_view(), <_>::default(), &state)
+            ),
+            Err(e) => println!(
+                "> Res({}, x = {}): {}",
+                AtomPrinter::new(expr.to_view(), <_>::default(), &state),
+                AtomPrinter::new(point.to_view(), <_>::default(), &state),
+                e
+            ),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use ahash::HashMap;
+use symbolica::{
+    id::{Pattern, Rule, RuleSet},
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let expr: OwnedAtom<DefaultRepresentation> = parse("f(5)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let mut rules = RuleSet::new();
+    for (name, lhs, rhs, priority) in [
+        ("recursion", "f(x_)", "f(x_-1)*x_", 0),
+        ("base case", "f(1)", "1", 1),
+    ] {
+        let lhs = parse(lhs).unwrap().to_atom(&mut state, &workspace).unwrap();
+        let rhs = parse(rhs).unwrap().to_atom(&mut state, &workspace).unwrap();
+        rules.add_rule(Rule {
+            name: name.to_owned(),
+            lhs: Pattern::from_view(lhs.to_view(), &state),
+            rhs: Pattern::from_view(rhs.to_view(), &state),
+            restrictions: HashMap::default(),
+            priority,
+        });
+    }
+
+    let mut out = OwnedAtom::new();
+    let mut trace = vec![];
+    let converged = rules.apply(
+        expr.to_view(),
+        &state,
+        &workspace,
+        &mut out,
+        Some(&mut trace),
+    );
+
+    println!(
+        "> {} = {}",
+        AtomPrinter::new(expr.to_view(), <_>::default(), &state),
+        AtomPrinter::new(out.to_view(), <_>::default(), &state)
+    );
+    println!("converged: {}", converged);
+    for i in trace {
+        println!("\tapplied {}", rules.get_rules()[i].name);
+    }
+}
@@ -0,0 +1,54 @@
+use symbolica::{
+    parser::parse,
+    physics::{graph::Graph, sector::sector_decomposition},
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    // the massless one-loop triangle with two on-shell legs
+    let mut graph = Graph::new();
+    for i in 0..3 {
+        graph.add_edge(i, (i + 1) % 3, None);
+    }
+
+    let p1 = state.get_or_insert_var("p1");
+    let p2 = state.get_or_insert_var("p2");
+    let p3 = state.get_or_insert_var("p3");
+    graph.add_external(0, p1);
+    graph.add_external(1, p2);
+    graph.add_external(2, p3);
+    graph.set_zero_scalar_product(p1, p1);
+    graph.set_zero_scalar_product(p2, p2);
+    let p12 = state.get_or_insert_var("p12");
+    graph.set_scalar_product(p1, p2, p12);
+
+    let params: Vec<_> = (1..=graph.get_edge_count())
+        .map(|i| state.get_or_insert_var(format!("x{}", i)))
+        .collect();
+    let (u, f) = graph.symanzik_polynomials::<u8>(&params).unwrap();
+
+    // in D = 4 - 2*ep dimensions, the integrand is U^(-1+2*ep) * F^(-1-ep)
+    let a = parse("-1+2*ep")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let b = parse("-1-ep")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let mut out = OwnedAtom::new();
+    for sector in sector_decomposition(&[u, f], &[0; 3]) {
+        sector.to_atom(&[a.to_view(), b.to_view()], &workspace, &state, &mut out);
+        println!(
+            "> Sector {}: {}",
+            sector.primary + 1,
+            AtomPrinter::new(out.to_view(), <_>::default(), &state)
+        );
+    }
+}
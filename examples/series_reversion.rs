@@ -0,0 +1,30 @@
+use symbolica::{
+    parser::parse,
+    printer::{PolynomialPrinter, PrintMode},
+    representations::default::DefaultRepresentation,
+    rings::rational::RationalField,
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    // the series of sin(x) up to x^7
+    let a = parse("x - x^3/6 + x^5/120 - x^7/5040")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let p = a
+        .to_view()
+        .to_polynomial::<_, u16>(RationalField::new(), None)
+        .unwrap();
+
+    // the series of arcsin(y) up to y^7
+    let inv = p.series_reversion(0, 7).unwrap();
+    println!(
+        "> reversion of {} = {}",
+        PolynomialPrinter::new(&p, &state, PrintMode::default()),
+        PolynomialPrinter::new(&inv, &state, PrintMode::default())
+    );
+}
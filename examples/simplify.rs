@@ -0,0 +1,63 @@
+use ahash::HashMap;
+use symbolica::{
+    id::{Pattern, Rule, RuleSet},
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    simplify::{SimplifyOptions, SimplifyPass},
+    state::{ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let lhs = parse("sin(x_)^2")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let rhs = parse("1-cos(x_)^2")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let mut trig = RuleSet::new();
+    trig.add_rule(Rule {
+        name: "pythagoras".to_owned(),
+        lhs: Pattern::from_view(lhs.to_view(), &state),
+        rhs: Pattern::from_view(rhs.to_view(), &state),
+        restrictions: HashMap::default(),
+        priority: 0,
+    });
+
+    let x = state.get_or_insert_var("x");
+    let options = SimplifyOptions {
+        max_size: 100,
+        passes: vec![
+            SimplifyPass::Expand,
+            SimplifyPass::Collect(x),
+            SimplifyPass::NormalizeFractions,
+            SimplifyPass::Rules(&trig),
+        ],
+    };
+
+    for input in [
+        "(x+1)^2-x^2",
+        "a*x+b*x+a*x^2+b*x^2",
+        "1/(1/x+1/y)",
+        "(sin(y)^2+cos(y)^2)*(x+1)",
+    ] {
+        let expr: OwnedAtom<DefaultRepresentation> = parse(input)
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+
+        let mut out = OwnedAtom::new();
+        expr.to_view()
+            .simplify(&options, &workspace, &state, &mut out);
+        println!(
+            "> {} -> {}",
+            AtomPrinter::new(expr.to_view(), <_>::default(), &state),
+            AtomPrinter::new(out.to_view(), <_>::default(), &state)
+        );
+    }
+}
@@ -0,0 +1,33 @@
+use ahash::HashMap;
+use symbolica::{
+    parser::parse,
+    representations::default::DefaultRepresentation,
+    rings::rational::{Rational, RationalField},
+    slp::{EvaluationFn, Slp},
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let expr = parse("(x+y)^2*f(x+y) + 3/(x+y)^2 + 2*5")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let slp = Slp::from_atom(expr.to_view(), RationalField::new()).unwrap();
+    println!("> Program:\n{}", slp.to_code(&state));
+
+    let mut functions: HashMap<_, EvaluationFn<RationalField>> = HashMap::default();
+    functions.insert(
+        state.get_or_insert_var("f"),
+        Box::new(|args: &[Rational]| args[0].clone()),
+    );
+
+    let values = [Rational::Natural(1, 1), Rational::Natural(2, 1)];
+    println!(
+        "> Value at (1, 2): {:?}",
+        slp.evaluate(&values, &functions).unwrap()
+    );
+}
@@ -0,0 +1,63 @@
+use symbolica::{
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    solve::Equation,
+    state::{InverseDomain, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let exp = state.get_or_insert_var("exp");
+    let log = state.get_or_insert_var("log");
+    state.set_inverse_function(exp, log, InverseDomain::Real);
+
+    let x = state.get_or_insert_var("x");
+
+    for (lhs, rhs) in [
+        ("a*x+b", "c"),
+        ("x^2-2*x", "-1"),
+        ("x^2+b*x+c", "0"),
+        ("x^3-6*x", "9"),
+        ("x^3-3*x+1", "0"),
+        ("x^4-10*x^2+1", "0"),
+        ("x^5-x", "0"),
+        ("x^4+x+1", "0"),
+        ("3*exp(2*x+1)", "y"),
+        ("log(x^2)", "y"),
+        ("x^6+x+1", "0"),
+        ("x*(x+1)", "x^2+x"),
+    ] {
+        let lhs: OwnedAtom<DefaultRepresentation> =
+            parse(lhs).unwrap().to_atom(&mut state, &workspace).unwrap();
+        let rhs = parse(rhs).unwrap().to_atom(&mut state, &workspace).unwrap();
+
+        println!(
+            "> {} = {}",
+            AtomPrinter::new(lhs.to_view(), <_>::default(), &state),
+            AtomPrinter::new(rhs.to_view(), <_>::default(), &state)
+        );
+
+        match Equation::new(lhs, rhs).solve(x, &workspace, &state) {
+            Ok(solutions) => {
+                for s in solutions {
+                    print!(
+                        "\tx = {}",
+                        AtomPrinter::new(s.value.to_view(), <_>::default(), &state)
+                    );
+                    for (c, a) in &s.conditions {
+                        print!(
+                            ", if {} satisfies {:?}",
+                            AtomPrinter::new(c.to_view(), <_>::default(), &state),
+                            a
+                        );
+                    }
+                    println!();
+                }
+            }
+            Err(e) => println!("\t{}", e),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use symbolica::{
+    parser::parse,
+    printer::RationalPolynomialPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    rings::{
+        integer::IntegerRing,
+        linear_system::SparseMatrix,
+        rational::RationalField,
+        rational_polynomial::{RationalPolynomial, RationalPolynomialField},
+        Ring,
+    },
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+    let var_map = [state.get_or_insert_var("d"), state.get_or_insert_var("s")];
+
+    // relations between the integrals I0, ..., I3, where the last column is
+    // the master integral M that the integrals are reduced to
+    let system: [&[(&str, u32)]; 4] = [
+        &[("d-4", 0), ("s", 1), ("-1", 4)],
+        &[("1", 1), ("(d-3)/(s+1)", 2)],
+        &[("s", 2), ("d-2", 3), ("2", 4)],
+        &[("1", 0), ("1/s", 3), ("-d", 4)],
+    ];
+
+    let mut entries = vec![];
+    for (r, row) in system.iter().enumerate() {
+        for (expr, c) in row.iter() {
+            let e: OwnedAtom<DefaultRepresentation> = parse(expr)
+                .unwrap()
+                .to_atom(&mut state, &workspace)
+                .unwrap();
+            let rat: RationalPolynomial<IntegerRing, u8> = e
+                .to_view()
+                .to_rational_polynomial(
+                    &workspace,
+                    &state,
+                    RationalField::new(),
+                    IntegerRing::new(),
+                    Some(&var_map),
+                )
+                .unwrap();
+            entries.push((r as u32, *c, rat));
+        }
+    }
+
+    let field = RationalPolynomialField::new(IntegerRing::new());
+    let m = SparseMatrix::new(4, 5, entries, field);
+
+    let (reduced, pivots) = m.row_reduce_fraction_free(4);
+    for (r, c) in pivots.iter().enumerate() {
+        let coeff = reduced
+            .row(r as u32)
+            .find(|(c, _)| *c == 4)
+            .map(|(_, v)| field.neg(v))
+            .unwrap_or_else(|| field.zero());
+        println!(
+            "I{} = ({})*M",
+            c,
+            RationalPolynomialPrinter::new(&coeff, &state, <_>::default())
+        );
+    }
+}
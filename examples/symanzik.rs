@@ -0,0 +1,33 @@
+use symbolica::{physics::graph::Graph, printer::PolynomialPrinter, state::State};
+
+fn main() {
+    let mut state = State::new();
+
+    // the two-loop sunrise graph with three massive propagators
+    let m = state.get_or_insert_var("m");
+    let p1 = state.get_or_insert_var("p1");
+    let p2 = state.get_or_insert_var("p2");
+    let s = state.get_or_insert_var("s");
+
+    let mut graph = Graph::new();
+    for _ in 0..3 {
+        graph.add_edge(0, 1, Some(m));
+    }
+    graph.add_external(0, p1);
+    graph.add_external(1, p2);
+    graph.set_scalar_product(p1, p1, s);
+
+    let params: Vec<_> = (1..=graph.get_edge_count())
+        .map(|i| state.get_or_insert_var(format!("x{}", i)))
+        .collect();
+
+    let (u, f) = graph.symanzik_polynomials::<u8>(&params).unwrap();
+    println!(
+        "> U = {}",
+        PolynomialPrinter::new(&u, &state, <_>::default())
+    );
+    println!(
+        "> F = {}",
+        PolynomialPrinter::new(&f, &state, <_>::default())
+    );
+}
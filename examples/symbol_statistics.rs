@@ -0,0 +1,79 @@
+use symbolica::{
+    parser::parse,
+    printer::{AtomPrinter, PolynomialPrinter},
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    rings::integer::IntegerRing,
+    state::{Assumptions, ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    for unused in ["a", "b", "c"] {
+        state.get_or_insert_var(unused);
+    }
+    let y = state.get_or_insert_var("y");
+    state.set_assumptions(
+        y,
+        Assumptions {
+            positive: true,
+            ..Default::default()
+        },
+    );
+
+    let expr: OwnedAtom<DefaultRepresentation> = parse("x^3*y + f(x)^2*y^(1/2) + x*z^-2 + 5")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let stats = expr.to_view().get_symbol_statistics();
+    for id in stats.get_identifiers() {
+        let u = stats.get(id).unwrap();
+        println!(
+            "{}: count={}, max_power={}, variable={}, function={}, non_integer_power={}",
+            state.get_name(id).unwrap(),
+            u.count,
+            u.max_power,
+            u.variable,
+            u.function,
+            u.non_integer_power
+        );
+    }
+
+    let var_map = stats.get_var_map();
+    println!(
+        "> Variable map: {:?}",
+        var_map
+            .iter()
+            .map(|v| state.get_name(*v).unwrap().as_str())
+            .collect::<Vec<_>>()
+    );
+
+    let poly = parse("x^3*z + 2*x*z^2")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap()
+        .to_view()
+        .to_polynomial::<_, u8>(IntegerRing::new(), Some(&var_map))
+        .unwrap();
+    println!(
+        "> Polynomial: {}",
+        PolynomialPrinter::new(&poly, &state, <_>::default())
+    );
+
+    let (pruned_state, map) = state.prune(&stats);
+    let mut pruned = OwnedAtom::new();
+    expr.to_view()
+        .rename_pruned(&map, &workspace, &pruned_state, &mut pruned);
+
+    let (mut full, mut small) = (vec![], vec![]);
+    state.export(&mut full).unwrap();
+    pruned_state.export(&mut small).unwrap();
+    println!(
+        "> Pruned state from {} to {} bytes: {}",
+        full.len(),
+        small.len(),
+        AtomPrinter::new(pruned.to_view(), <_>::default(), &pruned_state)
+    );
+}
@@ -0,0 +1,52 @@
+use symbolica::{
+    parser::parse,
+    poly::{polynomial::MultivariatePolynomial, to_polynomials},
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    rings::{
+        finite_field::{FiniteField, FiniteFieldCore},
+        integer::IntegerRing,
+    },
+    state::{ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let exprs: Vec<OwnedAtom<DefaultRepresentation>> = [
+        "(x+y+z)*(x*y+x*z+y*z+w)*(x^2+y^2+z^2)",
+        "(x+y+z)^2*(x*y*z-2)*(x*y+x*z+y*z+w)",
+        "(x-y)^2*(x*y+1)*(x+y+z)",
+    ]
+    .iter()
+    .map(|e| {
+        let a = parse(e).unwrap().to_atom(&mut state, &workspace).unwrap();
+        let mut expanded = OwnedAtom::new();
+        a.to_view().expand(&workspace, &state, &mut expanded);
+        expanded
+    })
+    .collect();
+    let views: Vec<_> = exprs.iter().map(|e| e.to_view()).collect();
+    let polys = to_polynomials::<_, _, u8>(&views, IntegerRing::new()).unwrap();
+
+    for p in &polys {
+        println!("> {} is symmetric in {:?}", p, p.symmetric_groups());
+    }
+
+    let groups = polys[0].symmetric_groups();
+    println!(
+        "> In elementary symmetric polynomials: {}",
+        polys[0].to_elementary_symmetric(&groups[0]).unwrap()
+    );
+
+    let gcd = MultivariatePolynomial::gcd_symmetric(&polys[0], &polys[1]);
+    println!("> gcd = {}", gcd);
+    assert_eq!(gcd, MultivariatePolynomial::gcd(&polys[0], &polys[1]));
+
+    let field = FiniteField::<u64>::new(18446744073709551557);
+    let p = views[2].to_polynomial::<_, u8>(field, None).unwrap();
+    println!("> Factorization of {}:", p);
+    for (f, m) in p.factor_symmetric().unwrap() {
+        println!("\t({})^{}", f, m);
+    }
+}
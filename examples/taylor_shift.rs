@@ -0,0 +1,34 @@
+use symbolica::{
+    parser::parse,
+    printer::{PolynomialPrinter, PrintMode},
+    representations::default::DefaultRepresentation,
+    rings::{integer::Integer, integer::IntegerRing},
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let a = parse("x^3*y + 2*x*y^2 - 4*x^2 + 5")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let p = a
+        .to_view()
+        .to_polynomial::<_, u8>(IntegerRing::new(), None)
+        .unwrap();
+
+    let shifted = p.shift(0, &Integer::Natural(2));
+    println!(
+        "> ({}) with x -> x + 2: {}",
+        PolynomialPrinter::new(&p, &state, PrintMode::default()),
+        PolynomialPrinter::new(&shifted, &state, PrintMode::default())
+    );
+
+    let shifted = p.shift_all(&[(0, Integer::Natural(2)), (1, Integer::Natural(-1))]);
+    println!(
+        "> with x -> x + 2 and y -> y - 1: {}",
+        PolynomialPrinter::new(&shifted, &state, PrintMode::default())
+    );
+}
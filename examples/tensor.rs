@@ -0,0 +1,43 @@
+use symbolica::{
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{FunctionAttribute, ResettableBuffer, State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let delta = state.get_or_insert_var("delta");
+    state.set_function_attributes(delta, &[FunctionAttribute::KroneckerDelta]);
+    let eps = state.get_or_insert_var("eps");
+    state.set_function_attributes(eps, &[FunctionAttribute::LeviCivita]);
+
+    for input in [
+        "delta(j,i)",
+        "delta(1,2)+delta(3,3)",
+        "eps(j,i,k)",
+        "eps(1,3,2)+eps(i,j,i)",
+        "delta(i,j)*v(j)",
+        "delta(i,j)*delta(j,k)*delta(k,l)",
+        "delta(i,j)^2",
+        "eps(i,j,k)*eps(i,j,k)",
+        "eps(i,j,k)*eps(i,j,l)*v(l)",
+        "eps(i,j,k)*eps(i,l,m)",
+    ] {
+        let expr: OwnedAtom<DefaultRepresentation> = parse(input)
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+
+        let mut out = OwnedAtom::new();
+        expr.to_view().normalize(&workspace, &state, &mut out);
+
+        println!(
+            "\t{} = {}",
+            input,
+            AtomPrinter::new(out.to_view(), <_>::default(), &state)
+        );
+    }
+}
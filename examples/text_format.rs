@@ -0,0 +1,51 @@
+use symbolica::{
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{Assumptions, FunctionAttribute, ResettableBuffer, State, Workspace},
+    text_format::{export_text, import_text},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let nc = state.get_or_insert_var("nc");
+    state.set_function_attributes(nc, &[FunctionAttribute::NonCommutative]);
+    let x = state.get_or_insert_var("x");
+    state.set_assumptions(
+        x,
+        Assumptions {
+            positive: true,
+            ..Default::default()
+        },
+    );
+
+    let input = parse(
+        "x^2*f(1/2,-3,[3%7],[x0%x0^2-2]) + nc(2)*nc(1) - x/3 + 123456789012345678901234567890*y^(1/2)
+        + 5*[y+1,x;x,y]",
+    )
+    .unwrap()
+    .to_atom(&mut state, &workspace)
+    .unwrap();
+    let mut expr = OwnedAtom::new();
+    input.to_view().normalize(&workspace, &state, &mut expr);
+
+    println!(
+        "> Input: {}",
+        AtomPrinter::new(expr.to_view(), <_>::default(), &state)
+    );
+
+    let mut text = vec![];
+    export_text(expr.to_view(), &state, &mut text).unwrap();
+    let text = String::from_utf8(text).unwrap();
+    print!("{}", text);
+
+    // read the expression into a new state and write it again
+    let mut new_state = State::new();
+    let read = import_text(&text, &mut new_state, &workspace).unwrap();
+    let mut text2 = vec![];
+    export_text(read.to_view(), &new_state, &mut text2).unwrap();
+    assert_eq!(text, String::from_utf8(text2).unwrap());
+    println!("> Round trip succeeded");
+}
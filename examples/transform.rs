@@ -0,0 +1,62 @@
+use ahash::HashMap;
+use symbolica::{
+    id::{Pattern, Rule},
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, OwnedAtom},
+    state::{ResettableBuffer, State, Workspace},
+    transform::TransformTable,
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let mut laplace = TransformTable::laplace(&mut state, &workspace);
+    let mellin = TransformTable::mellin(&mut state, &workspace);
+
+    // add the transform of the Heaviside step function theta(t-a), with a > 0
+    let lhs = parse("laplace(theta(t_+a_), t_, s_)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    let rhs = parse("exp(a_*s_)*s_^-1")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+    laplace.add_rule(Rule {
+        name: "step".to_owned(),
+        lhs: Pattern::from_view(lhs.to_view(), &state),
+        rhs: Pattern::from_view(rhs.to_view(), &state),
+        restrictions: HashMap::default(),
+        priority: 1,
+    });
+
+    let t = state.get_or_insert_var("t");
+    let s = state.get_or_insert_var("s");
+
+    for (table, input) in [
+        (&laplace, "3+2*t^2+a*exp(t*-4)"),
+        (&laplace, "c*sin(w*t)+cos(t)"),
+        (&laplace, "t^5*exp(2*t)"),
+        (&laplace, "theta(t-2)"),
+        (&laplace, "f(t)+exp(t^2)"),
+        (&mellin, "exp(a*t*-1)"),
+        (&mellin, "t^2*(1+t)^-3"),
+    ] {
+        let expr: OwnedAtom<DefaultRepresentation> = parse(input)
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+
+        let mut out = OwnedAtom::new();
+        table.transform(expr.to_view(), t, s, &workspace, &state, &mut out);
+
+        println!(
+            "> {}[{}] = {}",
+            state.get_name(table.get_name()).unwrap(),
+            input,
+            AtomPrinter::new(out.to_view(), <_>::default(), &state)
+        );
+    }
+}
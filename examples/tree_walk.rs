@@ -27,4 +27,16 @@ fn main() {
             AtomPrinter::new(view, <_>::default(), &state)
         );
     }
+
+    // random access to a subterm by its location
+    let loc = [2, 0, 0];
+    let mut view = expr.to_view();
+    for i in loc {
+        view = view.nth_child(i).unwrap();
+    }
+    println!(
+        "> Atom at location {:?}: {}",
+        loc,
+        AtomPrinter::new(view, <_>::default(), &state)
+    );
 }
@@ -0,0 +1,27 @@
+use symbolica::{
+    parser::parse,
+    representations::default::DefaultRepresentation,
+    state::{State, Workspace},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    // preallocate buffers so that the expansion does not have to allocate new ones
+    workspace.reserve_atoms(16);
+
+    let input = parse("(1+y+x)^5*(x+1)")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let mut out = workspace.new_atom();
+    input.to_view().expand(&workspace, &state, out.get_mut());
+
+    println!("> After expansion: {:?}", workspace.atom_stats());
+
+    drop(out);
+    workspace.clear();
+    println!("> After clearing: {:?}", workspace.atom_stats());
+}
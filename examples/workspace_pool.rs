@@ -0,0 +1,60 @@
+use symbolica::{
+    parser::parse,
+    printer::AtomPrinter,
+    representations::{default::DefaultRepresentation, Add, AtomView, OwnedAdd},
+    state::{SendAtom, State, Workspace, WorkspacePool},
+};
+
+fn main() {
+    let mut state = State::new();
+    let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+    let input = parse("(x+y)^4 + (x-y)^3 + (1+x)^5 + (y+2)^2")
+        .unwrap()
+        .to_atom(&mut state, &workspace)
+        .unwrap();
+
+    let AtomView::Add(terms) = input.to_view() else {
+        unreachable!()
+    };
+    let terms: Vec<SendAtom<DefaultRepresentation>> = terms.iter().map(SendAtom::new).collect();
+
+    // expand every term on a different thread
+    let pool = WorkspacePool::new();
+    let (pool_ref, state_ref) = (&pool, &state);
+    let expanded: Vec<SendAtom<DefaultRepresentation>> = std::thread::scope(|s| {
+        let threads: Vec<_> = terms
+            .into_iter()
+            .map(|t| {
+                s.spawn(move || {
+                    pool_ref.with(|workspace| {
+                        let mut out = workspace.new_atom();
+                        t.to_view().expand(workspace, state_ref, out.get_mut());
+                        SendAtom::from(out)
+                    })
+                })
+            })
+            .collect();
+
+        threads.into_iter().map(|t| t.join().unwrap()).collect()
+    });
+
+    println!("> Workspaces in pool: {}", pool.len());
+
+    let mut sum = workspace.new_atom();
+    let add = sum.transform_to_add();
+    for e in expanded {
+        let h = e.into_handle(&workspace);
+        add.extend(h.get().to_view());
+    }
+    add.set_dirty(true);
+
+    let mut out = workspace.new_atom();
+    sum.get()
+        .to_view()
+        .normalize(&workspace, &state, out.get_mut());
+    println!(
+        "> Expanded: {}",
+        AtomPrinter::new(out.get().to_view(), <_>::default(), &state)
+    );
+}
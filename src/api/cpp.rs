@@ -13,7 +13,7 @@ use crate::{
     parser::parse,
     printer::{PrintMode, RationalPolynomialPrinter},
     representations::default::DefaultRepresentation,
-    rings::rational_polynomial::RationalPolynomial,
+    rings::rational_polynomial::{RationalPolynomial, RationalPolynomialNormalization},
     state::{State, Workspace},
 };
 
@@ -120,7 +120,9 @@ unsafe extern "C" fn simplify(
                             terms_on_new_line: false,
                             color_top_level_sum: false,
                             print_finite_field: false,
-                            explicit_rational_polynomial
+                            explicit_rational_polynomial,
+                            rational_polynomial_normalization:
+                                RationalPolynomialNormalization::None
                         })
                     }
                 )
@@ -150,7 +152,9 @@ unsafe extern "C" fn simplify(
                                 terms_on_new_line: false,
                                 color_top_level_sum: false,
                                 print_finite_field: false,
-                                explicit_rational_polynomial
+                                explicit_rational_polynomial,
+                                rational_polynomial_normalization:
+                                    RationalPolynomialNormalization::None
                             })
                         }
                     )
@@ -179,7 +183,9 @@ unsafe extern "C" fn simplify(
                                 terms_on_new_line: false,
                                 color_top_level_sum: false,
                                 print_finite_field: false,
-                                explicit_rational_polynomial
+                                explicit_rational_polynomial,
+                                rational_polynomial_normalization:
+                                    RationalPolynomialNormalization::None
                             })
                         }
                     )
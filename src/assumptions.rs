@@ -0,0 +1,111 @@
+use crate::{
+    representations::{number::BorrowedNumber, Add, Atom, AtomView, Mul, Num, Pow, Var},
+    state::State,
+};
+
+/// The sign of a number, if it is rational.
+fn number_sign(n: &BorrowedNumber) -> Option<std::cmp::Ordering> {
+    match n {
+        BorrowedNumber::Natural(n, _) => Some(n.cmp(&0)),
+        BorrowedNumber::Large(r) => Some(r.to_rat().cmp0()),
+        BorrowedNumber::FiniteField(_, _)
+        | BorrowedNumber::RationalPolynomial(_)
+        | BorrowedNumber::AlgebraicNumber(_, _) => None,
+    }
+}
+
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Check if the expression is real for all values of the variables
+    /// that satisfy their assumptions. A result of `false` means unknown.
+    pub fn is_real(&self, state: &State) -> bool {
+        match self {
+            AtomView::Num(n) => number_sign(&n.get_number_view()).is_some(),
+            AtomView::Var(v) => state.get_assumptions(v.get_name()).real,
+            AtomView::Fun(_) => false,
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                base.is_real(state) && exp.is_integer(state)
+                    || base.is_positive(state) && exp.is_real(state)
+            }
+            AtomView::Mul(m) => m.iter().all(|a| a.is_real(state)),
+            AtomView::Add(a) => a.iter().all(|a| a.is_real(state)),
+        }
+    }
+
+    /// Check if the expression is an integer for all values of the variables
+    /// that satisfy their assumptions. A result of `false` means unknown.
+    pub fn is_integer(&self, state: &State) -> bool {
+        match self {
+            AtomView::Num(n) => match n.get_number_view() {
+                BorrowedNumber::Natural(_, d) => d == 1,
+                BorrowedNumber::Large(r) => r.to_rat().is_integer(),
+                BorrowedNumber::FiniteField(_, _)
+                | BorrowedNumber::RationalPolynomial(_)
+                | BorrowedNumber::AlgebraicNumber(_, _) => false,
+            },
+            AtomView::Var(v) => state.get_assumptions(v.get_name()).integer,
+            AtomView::Fun(_) => false,
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                base.is_integer(state)
+                    && exp.is_integer(state)
+                    && (exp.is_positive(state) || exp.is_zero())
+            }
+            AtomView::Mul(m) => m.iter().all(|a| a.is_integer(state)),
+            AtomView::Add(a) => a.iter().all(|a| a.is_integer(state)),
+        }
+    }
+
+    /// Check if the expression is strictly positive for all values of the variables
+    /// that satisfy their assumptions. A result of `false` means unknown.
+    pub fn is_positive(&self, state: &State) -> bool {
+        match self {
+            AtomView::Num(n) => {
+                number_sign(&n.get_number_view()) == Some(std::cmp::Ordering::Greater)
+            }
+            AtomView::Var(v) => state.get_assumptions(v.get_name()).positive,
+            AtomView::Fun(_) => false,
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                base.is_positive(state) && exp.is_real(state)
+            }
+            AtomView::Mul(m) => m.iter().all(|a| a.is_positive(state)),
+            AtomView::Add(a) => a.iter().all(|a| a.is_positive(state)),
+        }
+    }
+
+    /// Check if the expression is zero or negative for all values of the variables
+    /// that satisfy their assumptions. A result of `false` means unknown.
+    pub fn is_nonpositive(&self, state: &State) -> bool {
+        match self {
+            AtomView::Num(n) => matches!(
+                number_sign(&n.get_number_view()),
+                Some(std::cmp::Ordering::Less | std::cmp::Ordering::Equal)
+            ),
+            AtomView::Var(_) | AtomView::Fun(_) | AtomView::Pow(_) => false,
+            AtomView::Mul(m) => {
+                m.iter().filter(|a| a.is_nonpositive(state)).count() == 1
+                    && m.iter()
+                        .all(|a| a.is_positive(state) || a.is_nonpositive(state))
+            }
+            AtomView::Add(a) => a.iter().all(|a| a.is_nonpositive(state)),
+        }
+    }
+
+    /// Check if the expression is non-zero for all values of the variables
+    /// that satisfy their assumptions. A result of `false` means unknown.
+    pub fn is_nonzero(&self, state: &State) -> bool {
+        match self {
+            AtomView::Num(n) => !n.is_zero(),
+            AtomView::Var(v) => state.get_assumptions(v.get_name()).nonzero,
+            AtomView::Fun(_) => false,
+            AtomView::Pow(p) => p.get_base().is_nonzero(state),
+            AtomView::Mul(m) => m.iter().all(|a| a.is_nonzero(state)),
+            AtomView::Add(_) => self.is_positive(state),
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        matches!(self, AtomView::Num(n) if n.is_zero())
+    }
+}
@@ -0,0 +1,162 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt::{Display, Formatter},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// A limit on memory, which is either set on the atom buffers of a [`Workspace`](crate::state::Workspace)
+/// with [`Workspace::with_memory_budget`](crate::state::Workspace::with_memory_budget), or on
+/// the whole process with [`CancellationToken::with_memory_budget`](crate::cancel::CancellationToken::with_memory_budget).
+///
+/// The memory of a workspace is accounted by the workspace itself, so no global allocator
+/// is required, but memory that is not held by the workspace, such as the output atom,
+/// is not counted. The memory of the process is counted by the [`CountingAllocator`].
+/// The budget is checked at the heads of the main loops of the algorithms that support it,
+/// so the memory use can exceed the limit by the amount that is allocated between two checks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryBudget {
+    limit: usize,
+}
+
+/// Diagnostics of a computation that was aborted because it exceeded a [`MemoryBudget`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutOfBudget {
+    /// The limit in bytes.
+    pub limit: usize,
+    /// The number of bytes that were in use when the computation was aborted.
+    pub used: usize,
+    /// The algorithm that was running when the computation was aborted.
+    pub location: &'static str,
+}
+
+impl Display for OutOfBudget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Memory budget exceeded in {}: {} bytes used, limit is {} bytes",
+            self.location, self.used, self.limit
+        )
+    }
+}
+
+impl std::error::Error for OutOfBudget {}
+
+impl MemoryBudget {
+    /// Create a budget of `limit` bytes.
+    pub fn new(limit: usize) -> Self {
+        MemoryBudget { limit }
+    }
+
+    pub fn get_limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Return an error if `used` bytes exceed the budget.
+    pub fn check(&self, used: usize, location: &'static str) -> Result<(), OutOfBudget> {
+        if used > self.limit {
+            Err(OutOfBudget {
+                limit: self.limit,
+                used,
+                location,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The number of bytes that are allocated through the [`CountingAllocator`].
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+/// A global allocator that counts the bytes that are allocated by the process, so
+/// that a [`MemoryBudget`] on a [`CancellationToken`](crate::cancel::CancellationToken)
+/// also limits the memory of the polynomial algorithms, which do not use a workspace.
+/// The allocations are delegated to the system allocator, so it cannot be combined
+/// with the `faster_alloc` feature, which installs jemalloc. It is installed with
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: symbolica::budget::CountingAllocator = symbolica::budget::CountingAllocator;
+/// ```
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    /// Get the number of bytes that are currently allocated, or 0 if the
+    /// allocator is not installed.
+    pub fn allocated() -> usize {
+        ALLOCATED.load(Ordering::Relaxed)
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                ALLOCATED.fetch_add(new_size - layout.size(), Ordering::Relaxed);
+            } else {
+                ALLOCATED.fetch_sub(layout.size() - new_size, Ordering::Relaxed);
+            }
+        }
+        new_ptr
+    }
+}
+
+// the tests install the counting allocator, which conflicts with jemalloc
+#[cfg(all(test, not(feature = "faster_alloc")))]
+mod tests {
+    use crate::{
+        cancel::{CancellationReason, CancellationToken},
+        poly::{groebner::GroebnerBasis, polynomial::MultivariatePolynomial},
+        rings::rational::{Rational, RationalField},
+    };
+
+    use super::{CountingAllocator, MemoryBudget};
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn test_process_memory_budget() {
+        // the ideal {x^2 - y, x*y - 1}
+        let mut f = MultivariatePolynomial::<_, u16>::new(2, RationalField::new(), None, None);
+        f.append_monomial(Rational::new(-1, 1), &[0, 1]);
+        f.append_monomial(Rational::new(1, 1), &[2, 0]);
+        let mut g = MultivariatePolynomial::<_, u16>::new(2, RationalField::new(), None, None);
+        g.append_monomial(Rational::new(-1, 1), &[0, 0]);
+        g.append_monomial(Rational::new(1, 1), &[1, 1]);
+        let ideal = [f, g];
+
+        assert!(CountingAllocator::allocated() > 0);
+
+        let token = CancellationToken::new().with_memory_budget(MemoryBudget::new(1));
+        let err = GroebnerBasis::try_new(&ideal, &token).unwrap_err();
+        assert!(matches!(
+            err.reason,
+            CancellationReason::OutOfBudget(b) if b.limit == 1 && b.location == "groebner"
+        ));
+
+        let token = CancellationToken::new().with_memory_budget(MemoryBudget::new(usize::MAX));
+        assert!(GroebnerBasis::try_new(&ideal, &token).is_ok());
+    }
+}
@@ -0,0 +1,190 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::{
+    representations::{
+        number::Number, Atom, AtomView, OwnedAdd, OwnedAtom, OwnedMul, OwnedNum, OwnedPow,
+    },
+    state::{BufferHandle, State, Workspace},
+};
+
+/// A builder for expressions that are constructed from a chain of arithmetic operations,
+/// for example `(a + b) * c - d`.
+///
+/// By default the expression is normalized after every operation. In [`AtomBuilder::lazy`]
+/// mode the operations are accumulated in an unnormalized expression and the result is
+/// only normalized once in [`AtomBuilder::finish`], so that a chain of `k` operations
+/// does not pay for `k` normalizations. Sums of sums and products of products are
+/// flattened as they are built.
+pub struct AtomBuilder<'a, P: Atom> {
+    state: &'a State,
+    workspace: &'a Workspace<P>,
+    out: BufferHandle<'a, OwnedAtom<P>>,
+    lazy: bool,
+}
+
+impl<'a, P: Atom> AtomBuilder<'a, P> {
+    /// Create a new builder that starts from the expression `start`.
+    pub fn new(start: AtomView<'_, P>, state: &'a State, workspace: &'a Workspace<P>) -> Self {
+        let mut out = workspace.new_atom();
+        out.get_mut().from_view(&start);
+        Self {
+            state,
+            workspace,
+            out,
+            lazy: false,
+        }
+    }
+
+    /// Postpone the normalization of the expression until [`AtomBuilder::finish`].
+    /// The intermediate expression returned by [`AtomBuilder::as_view`] is then
+    /// not normalized.
+    pub fn lazy(mut self) -> Self {
+        self.lazy = true;
+        self
+    }
+
+    /// Get a view of the current expression.
+    pub fn as_view(&self) -> AtomView<'_, P> {
+        self.out.get().to_view()
+    }
+
+    /// Normalize the expression and write it into `out`.
+    pub fn finish(self, out: &mut OwnedAtom<P>) {
+        self.as_view().normalize(self.workspace, self.state, out);
+    }
+
+    /// Raise the expression to the power `exp`.
+    pub fn pow(mut self, exp: AtomView<'_, P>) -> Self {
+        let mut h = self.workspace.new_atom();
+        let pow = h.get_mut().transform_to_pow();
+        pow.set_from_base_and_exp(self.as_view(), exp);
+        pow.set_dirty(true);
+        self.update(h);
+        self
+    }
+
+    /// Add `rhs` to the expression.
+    pub fn add_view(mut self, rhs: AtomView<'_, P>) -> Self {
+        if let OwnedAtom::Add(a) = self.out.get_mut() {
+            a.extend(rhs);
+            a.set_dirty(true);
+            self.normalize_eager();
+        } else {
+            let mut h = self.workspace.new_atom();
+            let add = h.get_mut().transform_to_add();
+            add.extend(self.as_view());
+            add.extend(rhs);
+            add.set_dirty(true);
+            self.update(h);
+        }
+        self
+    }
+
+    /// Multiply the expression by `rhs`.
+    pub fn mul_view(mut self, rhs: AtomView<'_, P>) -> Self {
+        if let OwnedAtom::Mul(m) = self.out.get_mut() {
+            m.extend(rhs);
+            m.set_dirty(true);
+            self.normalize_eager();
+        } else {
+            let mut h = self.workspace.new_atom();
+            let mul = h.get_mut().transform_to_mul();
+            mul.extend(self.as_view());
+            mul.extend(rhs);
+            mul.set_dirty(true);
+            self.update(h);
+        }
+        self
+    }
+
+    /// Subtract `rhs` from the expression.
+    pub fn sub_view(self, rhs: AtomView<'_, P>) -> Self {
+        let mut h = self.workspace.new_atom();
+        let mul = h.get_mut().transform_to_mul();
+        mul.extend(rhs);
+        mul.extend(self.minus_one().get().to_view());
+        mul.set_dirty(true);
+        self.add_view(h.get().to_view())
+    }
+
+    /// Divide the expression by `rhs`.
+    pub fn div_view(self, rhs: AtomView<'_, P>) -> Self {
+        let mut h = self.workspace.new_atom();
+        let pow = h.get_mut().transform_to_pow();
+        pow.set_from_base_and_exp(rhs, self.minus_one().get().to_view());
+        pow.set_dirty(true);
+        self.mul_view(h.get().to_view())
+    }
+
+    fn minus_one(&self) -> BufferHandle<'a, OwnedAtom<P>> {
+        let mut h = self.workspace.new_atom();
+        h.get_mut()
+            .transform_to_num()
+            .set_from_number(Number::Natural(-1, 1));
+        h
+    }
+
+    /// Replace the expression by the unnormalized expression `new`.
+    fn update(&mut self, mut new: BufferHandle<'a, OwnedAtom<P>>) {
+        std::mem::swap(&mut self.out, &mut new);
+        self.normalize_eager();
+    }
+
+    /// Normalize the expression, unless the builder is lazy.
+    fn normalize_eager(&mut self) {
+        if !self.lazy {
+            let mut h = self.workspace.new_atom();
+            self.as_view()
+                .normalize(self.workspace, self.state, h.get_mut());
+            std::mem::swap(&mut self.out, &mut h);
+        }
+    }
+}
+
+impl<'a, 'b, P: Atom> Add<AtomView<'b, P>> for AtomBuilder<'a, P> {
+    type Output = AtomBuilder<'a, P>;
+
+    fn add(self, rhs: AtomView<'b, P>) -> Self::Output {
+        self.add_view(rhs)
+    }
+}
+
+impl<'a, 'b, P: Atom> Sub<AtomView<'b, P>> for AtomBuilder<'a, P> {
+    type Output = AtomBuilder<'a, P>;
+
+    fn sub(self, rhs: AtomView<'b, P>) -> Self::Output {
+        self.sub_view(rhs)
+    }
+}
+
+impl<'a, 'b, P: Atom> Mul<AtomView<'b, P>> for AtomBuilder<'a, P> {
+    type Output = AtomBuilder<'a, P>;
+
+    fn mul(self, rhs: AtomView<'b, P>) -> Self::Output {
+        self.mul_view(rhs)
+    }
+}
+
+impl<'a, 'b, P: Atom> Div<AtomView<'b, P>> for AtomBuilder<'a, P> {
+    type Output = AtomBuilder<'a, P>;
+
+    fn div(self, rhs: AtomView<'b, P>) -> Self::Output {
+        self.div_view(rhs)
+    }
+}
+
+impl<'a, P: Atom> Neg for AtomBuilder<'a, P> {
+    type Output = AtomBuilder<'a, P>;
+
+    fn neg(self) -> Self::Output {
+        let minus_one = self.minus_one();
+        self.mul_view(minus_one.get().to_view())
+    }
+}
+
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Start a chain of arithmetic operations on the expression.
+    pub fn builder<'b>(&self, state: &'b State, workspace: &'b Workspace<P>) -> AtomBuilder<'b, P> {
+        AtomBuilder::new(*self, state, workspace)
+    }
+}
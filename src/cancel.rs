@@ -0,0 +1,173 @@
+use std::{
+    fmt::{Display, Formatter},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::budget::{CountingAllocator, MemoryBudget, OutOfBudget};
+
+/// A token to cancel a long-running computation, either explicitly from another
+/// thread with [`CancellationToken::cancel`] or when a deadline has passed.
+///
+/// The token is passed to the cancellable entry points, such as
+/// [`MultivariatePolynomial::try_gcd`](crate::poly::polynomial::MultivariatePolynomial::try_gcd),
+/// [`MultivariatePolynomial::try_heap_mul`](crate::poly::polynomial::MultivariatePolynomial::try_heap_mul),
+/// [`MultivariatePolynomial::try_factor`](crate::poly::polynomial::MultivariatePolynomial::try_factor),
+/// [`GroebnerBasis::try_new`](crate::poly::groebner::GroebnerBasis::try_new) and
+/// [`AtomView::try_normalize`](crate::representations::AtomView::try_normalize), which check it
+/// at the heads of their main loops and return [`Cancelled`] when it is cancelled.
+/// Work that is done on other threads is not checked.
+///
+/// A token can also carry a [`MemoryBudget`] for the memory of the whole process, see
+/// [`CancellationToken::with_memory_budget`].
+#[derive(Clone, Debug)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    budget: Option<MemoryBudget>,
+    start: Instant,
+    checkpoints: Arc<AtomicUsize>,
+}
+
+/// The reason why a computation was aborted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CancellationReason {
+    Cancelled,
+    DeadlineExceeded,
+    OutOfBudget(OutOfBudget),
+}
+
+/// Diagnostics of a computation that was aborted by a [`CancellationToken`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cancelled {
+    pub reason: CancellationReason,
+    /// The algorithm that was running when the computation was aborted.
+    pub location: &'static str,
+    /// The number of checkpoints that were passed.
+    pub checkpoints: usize,
+    /// The time since the token was created.
+    pub elapsed: Duration,
+}
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Computation aborted in {} (", self.location)?;
+        match &self.reason {
+            CancellationReason::Cancelled => write!(f, "cancelled")?,
+            CancellationReason::DeadlineExceeded => write!(f, "deadline exceeded")?,
+            CancellationReason::OutOfBudget(b) => write!(
+                f,
+                "memory budget exceeded: {} bytes used, limit is {} bytes",
+                b.used, b.limit
+            )?,
+        }
+        write!(
+            f,
+            ") after {:?} and {} checkpoints",
+            self.elapsed, self.checkpoints
+        )
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            deadline: None,
+            budget: None,
+            start: Instant::now(),
+            checkpoints: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Create a token that is cancelled after `timeout`.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_deadline(Instant::now() + timeout)
+    }
+
+    /// Create a token that is cancelled at `deadline`.
+    pub fn with_deadline(deadline: Instant) -> Self {
+        CancellationToken {
+            deadline: Some(deadline),
+            ..Self::new()
+        }
+    }
+
+    /// Abort the computation when the process uses more memory than `budget`. The memory
+    /// is counted by the [`CountingAllocator`], which must be installed as the global
+    /// allocator; otherwise the budget is never exceeded.
+    pub fn with_memory_budget(mut self, budget: MemoryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Cancel the computation. All clones of the token are cancelled as well.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) || self.is_expired()
+    }
+
+    fn is_expired(&self) -> bool {
+        self.deadline.map(|d| Instant::now() >= d).unwrap_or(false)
+    }
+
+    /// Return an error if the token is cancelled. `location` is used in the diagnostics.
+    pub fn check(&self, location: &'static str) -> Result<(), Cancelled> {
+        let checkpoints = self.checkpoints.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let reason = if self.cancelled.load(Ordering::Relaxed) {
+            CancellationReason::Cancelled
+        } else if self.is_expired() {
+            CancellationReason::DeadlineExceeded
+        } else if let Some(Err(e)) = self
+            .budget
+            .map(|b| b.check(CountingAllocator::allocated(), location))
+        {
+            CancellationReason::OutOfBudget(e)
+        } else {
+            return Ok(());
+        };
+
+        Err(Cancelled {
+            reason,
+            location,
+            checkpoints,
+            elapsed: self.start.elapsed(),
+        })
+    }
+}
+
+/// Check the token of a computation, if it has one.
+#[inline(always)]
+pub(crate) fn checkpoint(
+    token: Option<&CancellationToken>,
+    location: &'static str,
+) -> Result<(), Cancelled> {
+    match token {
+        Some(t) => t.check(location),
+        None => Ok(()),
+    }
+}
+
+/// Get the result of a computation that has no token and can therefore not be cancelled.
+#[inline(always)]
+pub(crate) fn uncancellable<T>(r: Result<T, Cancelled>) -> T {
+    match r {
+        Ok(r) => r,
+        Err(c) => unreachable!("A computation without a token was aborted: {}", c),
+    }
+}
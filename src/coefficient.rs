@@ -25,6 +25,7 @@ impl<'a, P: Atom> AtomView<'a, P> {
         match self {
             Self::Num(n) => {
                 if let BorrowedNumber::RationalPolynomial(r) = n.get_number_view() {
+                    let r = r.to_rational_polynomial();
                     let old_var_map = r.get_var_map().unwrap();
                     if old_var_map != vars {
                         if old_var_map.iter().all(|x| vars.contains(x)) {
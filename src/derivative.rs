@@ -0,0 +1,447 @@
+use std::hash::{Hash, Hasher};
+
+use ahash::{AHasher, HashMap, HashMapExt};
+
+use crate::{
+    representations::{
+        number::{BorrowedNumber, Number},
+        Add, Atom, AtomView, Fun, Identifier, Mul, Num, OwnedAdd, OwnedAtom, OwnedFun, OwnedMul,
+        OwnedNum, OwnedPow, Pow, Var,
+    },
+    state::{State, Workspace},
+};
+
+/// A matrix of expressions, stored row by row.
+pub type AtomMatrix<P> = Vec<Vec<OwnedAtom<P>>>;
+
+type DerivativeCache<P> = HashMap<(u64, Identifier), Vec<(OwnedAtom<P>, OwnedAtom<P>)>>;
+
+/// Computes derivatives of expressions, reusing the derivatives of subexpressions that
+/// occur more than once, also across different calls.
+///
+/// Functions whose arguments depend on the variable and powers whose exponents depend
+/// on the variable cannot be differentiated and yield an error. The exception are
+/// conditionals `f(c, a, b)`, whose derivative `f(c, a', b')` is valid away from
+/// the points where the condition changes sign.
+///
+/// With a derivative marker `der`, see [`Differentiator::with_derivative_marker`], the
+/// derivatives of functions are written with the chain rule in terms of the markers
+/// `der(n_1, ..., n_k, f(a_1, ..., a_k))`, which represent the derivative of `f` of
+/// order `n_i` in its `i`th argument, evaluated at `a_1, ..., a_k`.
+pub struct Differentiator<P: Atom> {
+    cache: DerivativeCache<P>,
+    derivative_marker: Option<Identifier>,
+}
+
+impl<P: Atom> Default for Differentiator<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Atom> Differentiator<P> {
+    pub fn new() -> Self {
+        Differentiator {
+            cache: HashMap::new(),
+            derivative_marker: None,
+        }
+    }
+
+    /// Create a differentiator that writes the derivatives of functions in terms of the
+    /// function `der`. For example, the derivative of `f(x^2, y)` in `x` is
+    /// `der(1, 0, f(x^2, y))*2*x`, and the derivative of that marker in `x`
+    /// contains `der(2, 0, f(x^2, y))`. Only derivatives of the innermost functions remain.
+    pub fn with_derivative_marker(der: Identifier) -> Self {
+        Differentiator {
+            cache: HashMap::new(),
+            derivative_marker: Some(der),
+        }
+    }
+
+    /// Take the derivative of `expr` in `x` and write the normalized result in `out`.
+    pub fn derivative(
+        &mut self,
+        expr: AtomView<'_, P>,
+        x: Identifier,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) -> Result<(), String> {
+        if !depends_on(expr, x) {
+            out.transform_to_num()
+                .set_from_number(Number::Natural(0, 1));
+            return Ok(());
+        }
+
+        if let AtomView::Var(_) = expr {
+            out.transform_to_num()
+                .set_from_number(Number::Natural(1, 1));
+            return Ok(());
+        }
+
+        let key = (structural_hash(expr), x);
+        if let Some(entries) = self.cache.get(&key) {
+            if let Some((_, d)) = entries.iter().find(|(e, _)| e.to_view() == expr) {
+                out.from_view(&d.to_view());
+                return Ok(());
+            }
+        }
+
+        let mut res = workspace.new_atom();
+        match expr {
+            AtomView::Num(_) | AtomView::Var(_) => unreachable!(),
+            AtomView::Fun(f) if f.get_nargs() == 3 && state.is_conditional(f.get_name()) => {
+                let mut args = f.iter();
+                let cond = args.next().unwrap();
+
+                let fun = res.transform_to_fun();
+                fun.set_from_name(f.get_name());
+                fun.add_arg(cond);
+                for a in args {
+                    let mut da = workspace.new_atom();
+                    self.derivative(a, x, workspace, state, da.get_mut())?;
+                    fun.add_arg(da.get().to_view());
+                }
+                fun.set_dirty(true);
+            }
+            AtomView::Fun(_) if self.derivative_marker.is_some() => {
+                self.chain_rule(expr, x, workspace, state, res.get_mut())?;
+            }
+            AtomView::Fun(f) => {
+                return Err(format!(
+                    "Cannot differentiate function {} with an argument that depends on {}",
+                    state.get_name(f.get_name()).unwrap(),
+                    state.get_name(x).unwrap()
+                ));
+            }
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                if depends_on(exp, x) {
+                    return Err(format!(
+                        "Cannot differentiate a power with an exponent that depends on {}",
+                        state.get_name(x).unwrap()
+                    ));
+                }
+
+                // exp * base^(exp - 1) * base'
+                let mut db = workspace.new_atom();
+                self.derivative(base, x, workspace, state, db.get_mut())?;
+
+                let mut num_h = workspace.new_atom();
+                num_h
+                    .transform_to_num()
+                    .set_from_number(Number::Natural(-1, 1));
+                let mut new_exp = workspace.new_atom();
+                let a = new_exp.transform_to_add();
+                a.extend(exp);
+                a.extend(num_h.get().to_view());
+                a.set_dirty(true);
+
+                let mut pow_h = workspace.new_atom();
+                let pow = pow_h.transform_to_pow();
+                pow.set_from_base_and_exp(base, new_exp.get().to_view());
+                pow.set_dirty(true);
+
+                let mul = res.transform_to_mul();
+                mul.extend(exp);
+                mul.extend(pow_h.get().to_view());
+                mul.extend(db.get().to_view());
+                mul.set_dirty(true);
+            }
+            AtomView::Mul(m) => {
+                // product rule
+                let args: Vec<_> = m.iter().collect();
+                let add = res.transform_to_add();
+                for (i, a) in args.iter().enumerate() {
+                    if !depends_on(*a, x) {
+                        continue;
+                    }
+
+                    let mut da = workspace.new_atom();
+                    self.derivative(*a, x, workspace, state, da.get_mut())?;
+
+                    let mut term_h = workspace.new_atom();
+                    let term = term_h.transform_to_mul();
+                    for (j, b) in args.iter().enumerate() {
+                        if i == j {
+                            term.extend(da.get().to_view());
+                        } else {
+                            term.extend(*b);
+                        }
+                    }
+                    term.set_dirty(true);
+                    add.extend(term_h.get().to_view());
+                }
+                add.set_dirty(true);
+            }
+            AtomView::Add(a) => {
+                let add = res.transform_to_add();
+                for t in a.iter() {
+                    let mut dt = workspace.new_atom();
+                    self.derivative(t, x, workspace, state, dt.get_mut())?;
+                    add.extend(dt.get().to_view());
+                }
+                add.set_dirty(true);
+            }
+        }
+
+        res.get().to_view().normalize(workspace, state, out);
+
+        self.cache.entry(key).or_default().push((
+            OwnedAtom::new_from_view(&expr),
+            OwnedAtom::new_from_view(&out.to_view()),
+        ));
+
+        Ok(())
+    }
+
+    /// Write the derivative of the function `f` in `x` as a sum over its arguments of
+    /// derivative markers times the derivatives of the arguments.
+    fn chain_rule(
+        &mut self,
+        expr: AtomView<'_, P>,
+        x: Identifier,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) -> Result<(), String> {
+        let der = self.derivative_marker.unwrap();
+        let AtomView::Fun(f) = expr else {
+            unreachable!()
+        };
+
+        // the derivative orders and the differentiated function
+        let (mut orders, inner) = if f.get_name() == der {
+            let args: Vec<_> = f.iter().collect();
+            let malformed = || {
+                format!(
+                    "Derivative marker {} should have the form {}(n_1, ..., n_k, f(a_1, ..., a_k))",
+                    state.get_name(der).unwrap(),
+                    state.get_name(der).unwrap()
+                )
+            };
+
+            let Some((AtomView::Fun(g), orders)) = args.split_last() else {
+                return Err(malformed());
+            };
+            if g.get_nargs() != orders.len() {
+                return Err(malformed());
+            }
+
+            let orders = orders
+                .iter()
+                .map(|o| match o {
+                    AtomView::Num(n) => match n.get_number_view() {
+                        BorrowedNumber::Natural(n, 1) if n >= 0 => Ok(n),
+                        _ => Err(malformed()),
+                    },
+                    _ => Err(malformed()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            (orders, *args.last().unwrap())
+        } else {
+            (vec![0; f.get_nargs()], expr)
+        };
+
+        let AtomView::Fun(g) = inner else {
+            unreachable!()
+        };
+
+        let add = out.transform_to_add();
+        for (i, a) in g.iter().enumerate() {
+            if !depends_on(a, x) {
+                continue;
+            }
+
+            let mut da = workspace.new_atom();
+            self.derivative(a, x, workspace, state, da.get_mut())?;
+
+            orders[i] += 1;
+            let mut marker = workspace.new_atom();
+            let fun = marker.transform_to_fun();
+            fun.set_from_name(der);
+            for o in &orders {
+                let mut n = workspace.new_atom();
+                n.transform_to_num().set_from_number(Number::Natural(*o, 1));
+                fun.add_arg(n.get().to_view());
+            }
+            fun.add_arg(inner);
+            fun.set_dirty(true);
+            orders[i] -= 1;
+
+            let mut term = workspace.new_atom();
+            let mul = term.transform_to_mul();
+            mul.extend(marker.get().to_view());
+            mul.extend(da.get().to_view());
+            mul.set_dirty(true);
+            add.extend(term.get().to_view());
+        }
+        add.set_dirty(true);
+
+        Ok(())
+    }
+
+    /// Compute the derivatives of `expr` in all variables `vars`.
+    pub fn gradient(
+        &mut self,
+        expr: AtomView<'_, P>,
+        vars: &[Identifier],
+        workspace: &Workspace<P>,
+        state: &State,
+    ) -> Result<Vec<OwnedAtom<P>>, String> {
+        vars.iter()
+            .map(|x| {
+                let mut out = workspace.new_atom();
+                self.derivative(expr, *x, workspace, state, out.get_mut())?;
+                Ok(OwnedAtom::new_from_view(&out.get().to_view()))
+            })
+            .collect()
+    }
+
+    /// Compute the Jacobian matrix, where row `i` is the gradient of `exprs[i]`.
+    pub fn jacobian(
+        &mut self,
+        exprs: &[AtomView<'_, P>],
+        vars: &[Identifier],
+        workspace: &Workspace<P>,
+        state: &State,
+    ) -> Result<AtomMatrix<P>, String> {
+        exprs
+            .iter()
+            .map(|e| self.gradient(*e, vars, workspace, state))
+            .collect()
+    }
+
+    /// Compute the symmetric matrix of second derivatives of `expr`.
+    pub fn hessian(
+        &mut self,
+        expr: AtomView<'_, P>,
+        vars: &[Identifier],
+        workspace: &Workspace<P>,
+        state: &State,
+    ) -> Result<AtomMatrix<P>, String> {
+        let gradient = self.gradient(expr, vars, workspace, state)?;
+
+        let mut hessian: AtomMatrix<P> = Vec::with_capacity(vars.len());
+        for (i, g) in gradient.iter().enumerate() {
+            let mut row = Vec::with_capacity(vars.len());
+            for (j, x) in vars.iter().enumerate() {
+                if j < i {
+                    row.push(OwnedAtom::new_from_view(&hessian[j][i].to_view()));
+                } else {
+                    let mut out = workspace.new_atom();
+                    self.derivative(g.to_view(), *x, workspace, state, out.get_mut())?;
+                    row.push(OwnedAtom::new_from_view(&out.get().to_view()));
+                }
+            }
+            hessian.push(row);
+        }
+
+        Ok(hessian)
+    }
+}
+
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Take the derivative of the expression in `x` and write the normalized result
+    /// in `out`. Use a [`Differentiator`] to take many derivatives of related expressions.
+    pub fn derivative(
+        &self,
+        x: Identifier,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) -> Result<(), String> {
+        Differentiator::new().derivative(*self, x, workspace, state, out)
+    }
+}
+
+/// Compute the derivatives of `expr` in all variables `vars`.
+pub fn gradient<P: Atom>(
+    expr: AtomView<'_, P>,
+    vars: &[Identifier],
+    workspace: &Workspace<P>,
+    state: &State,
+) -> Result<Vec<OwnedAtom<P>>, String> {
+    Differentiator::new().gradient(expr, vars, workspace, state)
+}
+
+/// Compute the Jacobian matrix, where row `i` is the gradient of `exprs[i]`.
+pub fn jacobian<P: Atom>(
+    exprs: &[AtomView<'_, P>],
+    vars: &[Identifier],
+    workspace: &Workspace<P>,
+    state: &State,
+) -> Result<AtomMatrix<P>, String> {
+    Differentiator::new().jacobian(exprs, vars, workspace, state)
+}
+
+/// Compute the symmetric matrix of second derivatives of `expr`.
+pub fn hessian<P: Atom>(
+    expr: AtomView<'_, P>,
+    vars: &[Identifier],
+    workspace: &Workspace<P>,
+    state: &State,
+) -> Result<AtomMatrix<P>, String> {
+    Differentiator::new().hessian(expr, vars, workspace, state)
+}
+
+/// Check if the expression contains the variable `x`.
+pub(crate) fn depends_on<P: Atom>(expr: AtomView<'_, P>, x: Identifier) -> bool {
+    match expr {
+        AtomView::Num(_) => false,
+        AtomView::Var(v) => v.get_name() == x,
+        AtomView::Fun(f) => f.iter().any(|a| depends_on(a, x)),
+        AtomView::Pow(p) => {
+            let (b, e) = p.get_base_exp();
+            depends_on(b, x) || depends_on(e, x)
+        }
+        AtomView::Mul(m) => m.iter().any(|a| depends_on(a, x)),
+        AtomView::Add(a) => a.iter().any(|a| depends_on(a, x)),
+    }
+}
+
+/// Hash the structure of an expression. Numbers are not distinguished,
+/// so that equal hashes have to be verified.
+fn structural_hash<P: Atom>(expr: AtomView<'_, P>) -> u64 {
+    fn hash<P: Atom>(expr: AtomView<'_, P>, h: &mut AHasher) {
+        match expr {
+            AtomView::Num(_) => 0u8.hash(h),
+            AtomView::Var(v) => {
+                1u8.hash(h);
+                v.get_name().hash(h);
+            }
+            AtomView::Fun(f) => {
+                2u8.hash(h);
+                f.get_name().hash(h);
+                f.get_nargs().hash(h);
+                for a in f.iter() {
+                    hash(a, h);
+                }
+            }
+            AtomView::Pow(p) => {
+                3u8.hash(h);
+                let (b, e) = p.get_base_exp();
+                hash(b, h);
+                hash(e, h);
+            }
+            AtomView::Mul(m) => {
+                4u8.hash(h);
+                m.get_nargs().hash(h);
+                for a in m.iter() {
+                    hash(a, h);
+                }
+            }
+            AtomView::Add(a) => {
+                5u8.hash(h);
+                a.get_nargs().hash(h);
+                for t in a.iter() {
+                    hash(t, h);
+                }
+            }
+        }
+    }
+
+    let mut h = AHasher::default();
+    hash(expr, &mut h);
+    h.finish()
+}
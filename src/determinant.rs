@@ -0,0 +1,171 @@
+//! Determinants of small matrices of expressions, such as Gram determinants.
+//!
+//! The determinant is computed with a Laplace expansion along the rows, where
+//! the minors are memoized by the set of columns they consist of. This takes
+//! `O(n 2^n)` operations instead of the `O(n!)` of a naive expansion, and in contrast
+//! to Gaussian elimination it does not introduce any divisions.
+
+use ahash::{HashMap, HashMapExt};
+
+use crate::{
+    representations::{
+        number::Number, Atom, AtomView, Num, OwnedAdd, OwnedAtom, OwnedMul, OwnedNum,
+    },
+    state::{ResettableBuffer, State, Workspace},
+    tensor::permutation_sign,
+};
+
+/// Check if the atom is the number zero.
+fn is_zero<P: Atom>(a: AtomView<'_, P>) -> bool {
+    matches!(a, AtomView::Num(n) if n.is_zero())
+}
+
+struct MinorCache<'a, 'b, P: Atom> {
+    matrix: Vec<AtomView<'a, P>>,
+    n: usize,
+    workspace: &'b Workspace<P>,
+    state: &'b State,
+    minors: HashMap<u64, OwnedAtom<P>>,
+}
+
+impl<'a, 'b, P: Atom> MinorCache<'a, 'b, P> {
+    /// Compute the expanded determinant of the minor with the last `columns.count_ones()`
+    /// rows and the columns in the bit set `columns`.
+    fn minor(&mut self, columns: u64) {
+        if self.minors.contains_key(&columns) {
+            return;
+        }
+
+        let row = self.n - columns.count_ones() as usize;
+
+        let mut res = OwnedAtom::<P>::new();
+        if columns == 0 {
+            res.transform_to_num()
+                .set_from_number(Number::Natural(1, 1));
+            self.minors.insert(columns, res);
+            return;
+        }
+
+        let mut add_h = self.workspace.new_atom();
+        let add = add_h.get_mut().transform_to_add();
+        let mut sign = 1;
+        let mut has_terms = false;
+        for col in 0..self.n {
+            if columns & (1 << col) == 0 {
+                continue;
+            }
+
+            let entry = self.matrix[row * self.n + col];
+            if !is_zero(entry) {
+                let sub_columns = columns & !(1 << col);
+                self.minor(sub_columns);
+                let sub = self.minors[&sub_columns].to_view();
+
+                if !is_zero(sub) {
+                    let mut num_h = self.workspace.new_atom();
+                    num_h
+                        .get_mut()
+                        .transform_to_num()
+                        .set_from_number(Number::Natural(sign, 1));
+
+                    let mut mul_h = self.workspace.new_atom();
+                    let mul = mul_h.get_mut().transform_to_mul();
+                    mul.extend(num_h.get().to_view());
+                    mul.extend(entry);
+                    mul.extend(sub);
+                    mul.set_dirty(true);
+                    add.extend(mul_h.get().to_view());
+                    has_terms = true;
+                }
+            }
+
+            sign = -sign;
+        }
+
+        if has_terms {
+            add.set_dirty(true);
+            let mut norm_h = self.workspace.new_atom();
+            add_h
+                .get()
+                .to_view()
+                .normalize(self.workspace, self.state, norm_h.get_mut());
+            norm_h
+                .get()
+                .to_view()
+                .expand(self.workspace, self.state, &mut res);
+        } else {
+            res.transform_to_num()
+                .set_from_number(Number::Natural(0, 1));
+        }
+
+        self.minors.insert(columns, res);
+    }
+}
+
+/// Compute the expanded determinant of the `n x n` matrix `matrix`, given in row-major order,
+/// and write it into `out`. The rows with the most zeros are expanded first, and minors
+/// that are zero after expansion are not expanded further.
+///
+/// The memory and time grow as `2^n`, so this function is meant for small matrices,
+/// up to about `10 x 10`.
+pub fn determinant<P: Atom>(
+    matrix: &[AtomView<'_, P>],
+    n: usize,
+    workspace: &Workspace<P>,
+    state: &State,
+    out: &mut OwnedAtom<P>,
+) {
+    assert_eq!(matrix.len(), n * n, "Matrix is not of size {}x{}", n, n);
+    assert!(n < 64, "Matrix is too large");
+
+    let zero_row = (0..n).any(|r| (0..n).all(|c| is_zero(matrix[r * n + c])));
+    let zero_col = (0..n).any(|c| (0..n).all(|r| is_zero(matrix[r * n + c])));
+    if zero_row || zero_col {
+        out.transform_to_num()
+            .set_from_number(Number::Natural(0, 1));
+        return;
+    }
+
+    // expand the sparsest rows first, as they have the fewest minors
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|r| (0..n).filter(|c| !is_zero(matrix[r * n + c])).count());
+
+    let mut cache = MinorCache {
+        matrix: order
+            .iter()
+            .flat_map(|r| &matrix[r * n..(r + 1) * n])
+            .copied()
+            .collect(),
+        n,
+        workspace,
+        state,
+        minors: HashMap::new(),
+    };
+
+    let all_columns = if n == 0 { 0 } else { u64::MAX >> (64 - n) };
+    cache.minor(all_columns);
+    let det = cache.minors[&all_columns].to_view();
+
+    if permutation_sign(&order) == 1 {
+        out.from_view(&det);
+    } else {
+        let mut num_h = workspace.new_atom();
+        num_h
+            .get_mut()
+            .transform_to_num()
+            .set_from_number(Number::Natural(-1, 1));
+
+        let mut mul_h = workspace.new_atom();
+        let mul = mul_h.get_mut().transform_to_mul();
+        mul.extend(num_h.get().to_view());
+        mul.extend(det);
+        mul.set_dirty(true);
+
+        let mut norm_h = workspace.new_atom();
+        mul_h
+            .get()
+            .to_view()
+            .normalize(workspace, state, norm_h.get_mut());
+        norm_h.get().to_view().expand(workspace, state, out);
+    }
+}
@@ -0,0 +1,147 @@
+use std::cmp::Ordering;
+
+use crate::representations::{Add, Atom, AtomView, Fun, Mul, Pow, Var};
+
+/// The structural difference between two expressions, created by
+/// [`AtomView::diff_structure`].
+#[derive(Debug)]
+pub struct StructureDiff<'a, 'b, P: Atom> {
+    /// The path of argument indices from the root to the first subterm that
+    /// differs, together with the subterms, or `None` if the expressions are equal.
+    /// The base and exponent of a power have index 0 and 1.
+    pub first_difference: Option<(Vec<usize>, AtomView<'a, P>, AtomView<'b, P>)>,
+    /// The terms that only appear in the first expression.
+    pub removed: Vec<AtomView<'a, P>>,
+    /// The terms that only appear in the second expression.
+    pub added: Vec<AtomView<'b, P>>,
+}
+
+impl<'a, 'b, P: Atom> StructureDiff<'a, 'b, P> {
+    pub fn is_equal(&self) -> bool {
+        self.first_difference.is_none()
+    }
+}
+
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Compare the expression to `other`, reporting the first subterm that
+    /// differs and the terms that were removed and added in `other`.
+    /// Both expressions should be normalized.
+    pub fn diff_structure<'b>(&self, other: &AtomView<'b, P>) -> StructureDiff<'a, 'b, P> {
+        let mut path = vec![];
+        let first_difference = if Self::first_difference(*self, *other, &mut path) {
+            let (mut a, mut b) = (*self, *other);
+            for i in &path {
+                a = a.get_arg(*i);
+                b = b.get_arg(*i);
+            }
+            Some((path, a, b))
+        } else {
+            None
+        };
+
+        let mut terms_a = self.get_terms();
+        let mut terms_b = other.get_terms();
+        terms_a.sort_by(|a, b| a.cmp_terms(b));
+        terms_b.sort_by(|a, b| a.cmp_terms(b));
+
+        let mut removed = vec![];
+        let mut added = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < terms_a.len() && j < terms_b.len() {
+            match terms_a[i].cmp_terms(&terms_b[j]) {
+                Ordering::Less => {
+                    removed.push(terms_a[i]);
+                    i += 1;
+                }
+                Ordering::Greater => {
+                    added.push(terms_b[j]);
+                    j += 1;
+                }
+                Ordering::Equal => {
+                    if terms_a[i] != terms_b[j] {
+                        removed.push(terms_a[i]);
+                        added.push(terms_b[j]);
+                    }
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        removed.extend_from_slice(&terms_a[i..]);
+        added.extend_from_slice(&terms_b[j..]);
+
+        StructureDiff {
+            first_difference,
+            removed,
+            added,
+        }
+    }
+
+    fn get_terms(&self) -> Vec<AtomView<'a, P>> {
+        match self {
+            AtomView::Add(a) => a.iter().collect(),
+            _ => vec![*self],
+        }
+    }
+
+    /// Get the argument `index` of a function, power, product or sum.
+    fn get_arg(&self, index: usize) -> AtomView<'a, P> {
+        match self {
+            AtomView::Fun(f) => f.iter().nth(index).unwrap(),
+            AtomView::Pow(p) => {
+                let (b, e) = p.get_base_exp();
+                if index == 0 {
+                    b
+                } else {
+                    e
+                }
+            }
+            AtomView::Mul(m) => m.iter().nth(index).unwrap(),
+            AtomView::Add(a) => a.iter().nth(index).unwrap(),
+            AtomView::Num(_) | AtomView::Var(_) => unreachable!("Atom has no arguments"),
+        }
+    }
+
+    /// Get the arguments of a function, power, product or sum.
+    fn get_args(&self) -> Vec<AtomView<'a, P>> {
+        match self {
+            AtomView::Fun(f) => f.iter().collect(),
+            AtomView::Pow(p) => {
+                let (b, e) = p.get_base_exp();
+                vec![b, e]
+            }
+            AtomView::Mul(m) => m.iter().collect(),
+            AtomView::Add(a) => a.iter().collect(),
+            AtomView::Num(_) | AtomView::Var(_) => vec![],
+        }
+    }
+
+    /// Find the path to the first subterm in which `a` and `b` differ.
+    /// Returns `false` if they are equal.
+    fn first_difference<'b>(a: AtomView<'a, P>, b: AtomView<'b, P>, path: &mut Vec<usize>) -> bool {
+        if a == b {
+            return false;
+        }
+
+        let same_head = match (a, b) {
+            (AtomView::Fun(f1), AtomView::Fun(f2)) => f1.get_name() == f2.get_name(),
+            (AtomView::Pow(_), AtomView::Pow(_))
+            | (AtomView::Mul(_), AtomView::Mul(_))
+            | (AtomView::Add(_), AtomView::Add(_)) => true,
+            (AtomView::Var(v1), AtomView::Var(v2)) => v1.get_name() == v2.get_name(),
+            _ => false,
+        };
+
+        if same_head {
+            for (i, (c1, c2)) in a.get_args().into_iter().zip(b.get_args()).enumerate() {
+                if c1 != c2 {
+                    path.push(i);
+                    AtomView::first_difference(c1, c2, path);
+                    return true;
+                }
+            }
+        }
+
+        true
+    }
+}
@@ -0,0 +1,34 @@
+use std::fmt::{Display, Formatter};
+
+/// An error from a fallible operation in the polynomial and ring arithmetic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The number of variables of the operands is different.
+    NVarsMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// The variable maps of the operands are different.
+    VarMapMismatch,
+    DivisionByZero,
+    /// The division has a non-zero remainder.
+    InexactDivision,
+    /// An exponent does not fit in the exponent type.
+    ExponentOverflow,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NVarsMismatch { expected, found } => {
+                write!(f, "nvars mismatched: got {}, expected {}", found, expected)
+            }
+            Error::VarMapMismatch => f.write_str("Variable maps are different"),
+            Error::DivisionByZero => f.write_str("Division by zero"),
+            Error::InexactDivision => f.write_str("Division is not exact"),
+            Error::ExponentOverflow => f.write_str("Overflow in exponent"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -42,7 +42,90 @@ impl<'a, P: Atom> AtomView<'a, P> {
                     return changed;
                 };
 
-                if let AtomView::Add(a) = new_base.get().to_view() {
+                if matches!(
+                    new_base.get().to_view(),
+                    AtomView::Add(_) | AtomView::Mul(_)
+                ) && new_base.get().to_view().is_noncommutative(state)
+                {
+                    // expand the power as an ordered product, as (A+B)^2 = A^2+A*B+B*A+B^2
+                    // and (A*B)^2 = A*B*A*B for non-commutative A and B
+                    let mut terms: SmallVec<[AtomView<P>; 10]> = SmallVec::new();
+                    if let AtomView::Add(a) = new_base.get().to_view() {
+                        terms.extend(a.iter());
+                    } else {
+                        terms.push(new_base.get().to_view());
+                    }
+
+                    let mut sum: SmallVec<[BufferHandle<OwnedAtom<P>>; 10]> = SmallVec::new();
+                    let mut one = workspace.new_atom();
+                    one.get_mut()
+                        .transform_to_num()
+                        .set_from_number(Number::Natural(1, 1));
+                    sum.push(one);
+
+                    for _ in 0..num {
+                        let mut new_sum: SmallVec<[BufferHandle<OwnedAtom<P>>; 10]> =
+                            SmallVec::new();
+                        for s in &sum {
+                            for t in &terms {
+                                let mut mul_h = workspace.new_atom();
+                                let mul = mul_h.get_mut().transform_to_mul();
+                                if let AtomView::Mul(m) = s.get().to_view() {
+                                    for f in m.iter() {
+                                        mul.extend(f);
+                                    }
+                                } else {
+                                    mul.extend(s.get().to_view());
+                                }
+                                if let AtomView::Mul(m) = t {
+                                    for f in m.iter() {
+                                        mul.extend(f);
+                                    }
+                                } else {
+                                    mul.extend(*t);
+                                }
+                                mul.set_dirty(true);
+
+                                let mut norm_h = workspace.new_atom();
+                                mul_h
+                                    .get()
+                                    .to_view()
+                                    .normalize(workspace, state, norm_h.get_mut());
+                                new_sum.push(norm_h);
+                            }
+                        }
+                        sum = new_sum;
+                    }
+
+                    let mut add_h = workspace.new_atom();
+                    let add = add_h.get_mut().transform_to_add();
+                    for s in &sum {
+                        let mut expanded_h = workspace.new_atom();
+                        s.get()
+                            .to_view()
+                            .expand(workspace, state, expanded_h.get_mut());
+                        add.extend(expanded_h.get().to_view());
+                    }
+                    add.set_dirty(true);
+
+                    if negative {
+                        let mut pow_h = workspace.new_atom();
+                        let pow = pow_h.get_mut().transform_to_pow();
+
+                        let mut num_h = workspace.new_atom();
+                        let num = num_h.get_mut().transform_to_num();
+                        num.set_from_number(Number::Natural(-1, 1));
+
+                        pow.set_from_base_and_exp(add_h.get().to_view(), num_h.get().to_view());
+                        pow.set_dirty(true);
+
+                        pow_h.get().to_view().normalize(workspace, state, out);
+                    } else {
+                        add_h.get().to_view().normalize(workspace, state, out);
+                    }
+
+                    true
+                } else if let AtomView::Add(a) = new_base.get().to_view() {
                     // expand (a+b+c+..)^n
                     let mut args: SmallVec<[AtomView<P>; 10]> =
                         SmallVec::with_capacity(a.get_nargs());
@@ -149,6 +232,19 @@ impl<'a, P: Atom> AtomView<'a, P> {
                     }
                     mul.set_dirty(true);
 
+                    // the factors may be sums, so the product has to be expanded as well
+                    let mut norm_h = workspace.new_atom();
+                    mul_h
+                        .get()
+                        .to_view()
+                        .normalize(workspace, state, norm_h.get_mut());
+
+                    let mut expanded_h = workspace.new_atom();
+                    norm_h
+                        .get()
+                        .to_view()
+                        .expand(workspace, state, expanded_h.get_mut());
+
                     if negative {
                         let mut pow_h = workspace.new_atom();
                         let pow = pow_h.get_mut().transform_to_pow();
@@ -157,11 +253,14 @@ impl<'a, P: Atom> AtomView<'a, P> {
                         let num = num_h.get_mut().transform_to_num();
                         num.set_from_number(Number::Natural(-1, 1));
 
-                        pow.set_from_base_and_exp(mul_h.get().to_view(), num_h.get().to_view());
+                        pow.set_from_base_and_exp(
+                            expanded_h.get().to_view(),
+                            num_h.get().to_view(),
+                        );
                         pow.set_dirty(true);
                         pow_h.get().to_view().normalize(workspace, state, out);
                     } else {
-                        mul_h.get().to_view().normalize(workspace, state, out);
+                        out.from_view(&expanded_h.get().to_view());
                     }
                     true
                 } else {
@@ -274,3 +373,78 @@ impl<'a, P: Atom> AtomView<'a, P> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::parse,
+        representations::{default::DefaultRepresentation, OwnedAtom},
+        state::{FunctionAttribute, ResettableBuffer, State, Workspace},
+    };
+
+    fn noncommutative_state() -> State {
+        let mut state = State::new();
+        for name in ["A", "B"] {
+            let id = state.get_or_insert_var(name);
+            state.set_function_attributes(id, &[FunctionAttribute::NonCommutative]);
+        }
+        state
+    }
+
+    fn expand(
+        input: &str,
+        state: &mut State,
+        workspace: &Workspace<DefaultRepresentation>,
+    ) -> OwnedAtom<DefaultRepresentation> {
+        let a = parse(input).unwrap().to_atom(state, workspace).unwrap();
+        let mut out = OwnedAtom::new();
+        a.to_view().expand(workspace, state, &mut out);
+        out
+    }
+
+    #[test]
+    fn test_noncommutative_power() {
+        let mut state = noncommutative_state();
+        let workspace = Workspace::new();
+
+        let a = parse("(A(x)*B(x))^2")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+        let expected = parse("A(x)*B(x)*A(x)*B(x)")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+        assert!(matches!(a, OwnedAtom::Pow(_)));
+        assert_ne!(a.to_view(), expected.to_view());
+        assert_eq!(
+            expand("(A(x)*B(x))^2", &mut state, &workspace).to_view(),
+            expected.to_view()
+        );
+    }
+
+    #[test]
+    fn test_noncommutative_sum_power() {
+        let mut state = noncommutative_state();
+        let workspace = Workspace::new();
+
+        let expected = parse("A(x)^2+A(x)*B(x)+B(x)*A(x)+B(x)^2")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+        assert_eq!(
+            expand("(A(x)+B(x))^2", &mut state, &workspace).to_view(),
+            expected.to_view()
+        );
+
+        // commuting factors are still collected
+        let expected = parse("A(x)^2+2*y*A(x)+y^2")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+        assert_eq!(
+            expand("(A(x)+y)^2", &mut state, &workspace).to_view(),
+            expected.to_view()
+        );
+    }
+}
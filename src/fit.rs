@@ -0,0 +1,305 @@
+use std::fmt::{Display, Formatter};
+
+use smallvec::SmallVec;
+
+use crate::{
+    poly::{polynomial::MultivariatePolynomial, Exponent, INLINED_EXPONENTS},
+    rings::{linear_system::Matrix, Field, Ring},
+    utils,
+};
+
+/// The range from which sample points are drawn.
+const SAMPLE_RANGE: (i64, i64) = (1, 1 << 31);
+
+/// The numerator and denominator of a rational function.
+type NumDen<F, E> = (MultivariatePolynomial<F, E>, MultivariatePolynomial<F, E>);
+
+/// An error of the reconstruction of a rational function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FitError {
+    /// No rational function with degrees up to the maximal degree agrees with the samples.
+    DegreeTooHigh,
+    /// All elements of the field have been sampled, so that no new sample can be drawn.
+    FieldTooSmall,
+}
+
+impl Display for FitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FitError::DegreeTooHigh => {
+                f.write_str("No rational function up to the maximal degree fits the samples")
+            }
+            FitError::FieldTooSmall => {
+                f.write_str("Could not find a new sample point: the field is too small")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FitError {}
+
+/// A Thiele continued fraction `a_0 + (x - x_0) / (a_1 + (x - x_1) / (a_2 + ...))`.
+struct ThieleFraction<F: Field> {
+    field: F,
+    points: Vec<F::Element>,
+    coeffs: Vec<F::Element>,
+}
+
+impl<F: Field> ThieleFraction<F> {
+    fn new(field: F) -> Self {
+        ThieleFraction {
+            field,
+            points: vec![],
+            coeffs: vec![],
+        }
+    }
+
+    /// Evaluate the fraction at `x`, or return `None` if a division by zero occurs.
+    fn evaluate(&self, x: &F::Element) -> Option<F::Element> {
+        let mut r = self.coeffs.last()?.clone();
+        for (a, p) in self.coeffs.iter().zip(&self.points).rev().skip(1) {
+            if F::is_zero(&r) {
+                return None;
+            }
+            r = self
+                .field
+                .add(a, &self.field.div(&self.field.sub(x, p), &r));
+        }
+        Some(r)
+    }
+
+    /// Add the sample `f(x) = y` by computing the reciprocal differences. Returns `false`
+    /// if the sample is unlucky and cannot be used.
+    fn add_sample(&mut self, x: F::Element, y: F::Element) -> bool {
+        let mut t = y;
+        for (a, p) in self.coeffs.iter().zip(&self.points) {
+            let d = self.field.sub(&t, a);
+            if F::is_zero(&d) {
+                return false;
+            }
+            t = self.field.div(&self.field.sub(&x, p), &d);
+        }
+
+        self.points.push(x);
+        self.coeffs.push(t);
+        true
+    }
+
+    /// Convert the continued fraction to a numerator and denominator.
+    fn to_rational<E: Exponent>(
+        &self,
+    ) -> (MultivariatePolynomial<F, E>, MultivariatePolynomial<F, E>) {
+        let field = self.field;
+        let mut x = MultivariatePolynomial::new(1, field, None, None);
+        x.append_monomial(field.one(), &[E::one()]);
+        let constant = |c: &F::Element| {
+            MultivariatePolynomial::<F, E>::new(1, field, None, None).add_monomial(c.clone())
+        };
+
+        let mut num = constant(self.coeffs.last().unwrap());
+        let mut den = constant(&field.one());
+        for (a, p) in self.coeffs.iter().zip(&self.points).rev().skip(1) {
+            // a + (x - p) * den / num
+            let shift = x.clone() - constant(p);
+            let new_num = &num * &constant(a) + &shift * &den;
+            den = num;
+            num = new_num;
+        }
+
+        // make the denominator monic
+        let inv = field.inv(&den.lcoeff());
+        for c in num.coefficients.iter_mut().chain(&mut den.coefficients) {
+            field.mul_assign(c, &inv);
+        }
+
+        (num, den)
+    }
+}
+
+/// Reconstruct a univariate rational function from the black box `f`, using
+/// Thiele continued fractions. The black box is sampled at random points, until
+/// the interpolation agrees with a new sample. Returns the numerator and denominator, where
+/// the denominator is monic, [`FitError::DegreeTooHigh`] if the sum of the degrees of the
+/// numerator and the denominator exceeds `max_degree`, or [`FitError::FieldTooSmall`] if
+/// every element of the field has been sampled.
+pub fn fit_univariate<F: Field, E: Exponent, B: FnMut(&F::Element) -> F::Element>(
+    mut f: B,
+    field: F,
+    max_degree: usize,
+) -> Result<NumDen<F, E>, FitError> {
+    let mut rng = utils::rng("fit::fit_univariate");
+    let mut fraction = ThieleFraction::new(field);
+    let mut sampled = vec![];
+    let mut unlucky = 0;
+    let mut repeated = 0;
+
+    while fraction.coeffs.len() <= max_degree + 1 {
+        let x = field.sample(&mut rng, SAMPLE_RANGE);
+        if sampled.contains(&x) {
+            // assume that the field is exhausted after many repeated draws in a row
+            repeated += 1;
+            if repeated > 100 * (sampled.len() + 1) {
+                return Err(FitError::FieldTooSmall);
+            }
+            continue;
+        }
+        repeated = 0;
+        sampled.push(x.clone());
+
+        let y = f(&x);
+        if fraction.evaluate(&x).as_ref() == Some(&y) {
+            return Ok(fraction.to_rational());
+        }
+
+        if !fraction.add_sample(x, y) {
+            unlucky += 1;
+            if unlucky > max_degree + 10 {
+                return Err(FitError::DegreeTooHigh);
+            }
+        }
+    }
+
+    Err(FitError::DegreeTooHigh)
+}
+
+/// Evaluate the monomial with exponents `exp` at `point`.
+fn evaluate_monomial<F: Ring>(field: F, exp: &[u32], point: &[F::Element]) -> F::Element {
+    let mut r = field.one();
+    for (e, x) in exp.iter().zip(point) {
+        if *e > 0 {
+            field.mul_assign(&mut r, &field.pow(x, *e as u64));
+        }
+    }
+    r
+}
+
+/// Reconstruct a multivariate rational function in `nvars` variables from the black box `f`,
+/// typically over a finite field. An ansatz for the numerator and denominator is made, starting
+/// from the lowest total degrees, and its coefficients are determined from random samples by solving
+/// a linear system. A solution is accepted if it agrees with additional samples.
+/// Returns the numerator and denominator, where the leading coefficient of the denominator is one,
+/// or [`FitError::DegreeTooHigh`] if no solution with total degrees up to `max_degree` is found.
+pub fn fit_multivariate<F: Field, E: Exponent, B: FnMut(&[F::Element]) -> F::Element>(
+    mut f: B,
+    nvars: usize,
+    field: F,
+    max_degree: u32,
+) -> Result<NumDen<F, E>, FitError> {
+    const CHECK_SAMPLES: usize = 3;
+
+    let mut rng = utils::rng("fit::fit_multivariate");
+    let mut samples: Vec<(Vec<F::Element>, F::Element)> = vec![];
+    for total in 0..=2 * max_degree {
+        for num_degree in total.saturating_sub(max_degree)..=total.min(max_degree) {
+            let num_monomials = utils::monomials(nvars, num_degree);
+            let den_monomials = utils::monomials(nvars, total - num_degree);
+            let n_unknowns = num_monomials.len() + den_monomials.len();
+
+            // solve num(x) - f(x) * den(x) = 0, with a random normalization of the denominator
+            while samples.len() < n_unknowns + CHECK_SAMPLES {
+                let point: Vec<_> = (0..nvars)
+                    .map(|_| field.sample(&mut rng, SAMPLE_RANGE))
+                    .collect();
+                let y = f(&point);
+                samples.push((point, y));
+            }
+
+            let mut m = Matrix::new(n_unknowns as u32 + 1, n_unknowns as u32, field);
+            let mut b = Matrix::new(n_unknowns as u32 + 1, 1, field);
+            for (r, (point, y)) in samples[..n_unknowns].iter().enumerate() {
+                for (c, exp) in num_monomials.iter().enumerate() {
+                    m[(r as u32, c as u32)] = evaluate_monomial(field, exp, point);
+                }
+                for (c, exp) in den_monomials.iter().enumerate() {
+                    m[(r as u32, (num_monomials.len() + c) as u32)] =
+                        field.neg(&field.mul(y, &evaluate_monomial(field, exp, point)));
+                }
+            }
+            for c in 0..den_monomials.len() {
+                m[(n_unknowns as u32, (num_monomials.len() + c) as u32)] =
+                    field.sample(&mut rng, SAMPLE_RANGE);
+            }
+            b[(n_unknowns as u32, 0)] = field.one();
+
+            let Ok(sol) = m.solve_particular(&b) else {
+                continue;
+            };
+
+            let to_poly = |monomials: &[Vec<u32>], coeffs: &[F::Element]| {
+                let mut p = MultivariatePolynomial::<F, E>::new(nvars, field, None, None);
+                for (exp, c) in monomials.iter().zip(coeffs) {
+                    if !F::is_zero(c) {
+                        let exp: SmallVec<[E; INLINED_EXPONENTS]> =
+                            exp.iter().map(|e| E::from_u32(*e)).collect();
+                        p.append_monomial(c.clone(), &exp);
+                    }
+                }
+                p
+            };
+
+            let num_coeffs = &sol.data[..num_monomials.len()];
+            let den_coeffs = &sol.data[num_monomials.len()..];
+
+            // check the solution on the remaining samples
+            let agrees = samples[n_unknowns..].iter().all(|(point, y)| {
+                let mut n = field.zero();
+                for (exp, c) in num_monomials.iter().zip(num_coeffs) {
+                    field.add_mul_assign(&mut n, c, &evaluate_monomial(field, exp, point));
+                }
+                let mut d = field.zero();
+                for (exp, c) in den_monomials.iter().zip(den_coeffs) {
+                    field.add_mul_assign(&mut d, c, &evaluate_monomial(field, exp, point));
+                }
+                !F::is_zero(&d) && n == field.mul(y, &d)
+            });
+
+            if !agrees {
+                continue;
+            }
+
+            let mut num = to_poly(&num_monomials, num_coeffs);
+            let mut den = to_poly(&den_monomials, den_coeffs);
+            let inv = field.inv(&den.lcoeff());
+            for c in num.coefficients.iter_mut().chain(&mut den.coefficients) {
+                field.mul_assign(c, &inv);
+            }
+
+            return Ok((num, den));
+        }
+    }
+
+    Err(FitError::DegreeTooHigh)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        poly::polynomial::MultivariatePolynomial,
+        rings::{
+            finite_field::{FiniteField, FiniteFieldCore},
+            Ring,
+        },
+    };
+
+    use super::{fit_univariate, FitError, ThieleFraction};
+
+    #[test]
+    fn test_fit_field_too_small() {
+        // a black box that never agrees with the interpolation of its previous samples
+        let field = FiniteField::<u32>::new(3);
+        let mut fraction = ThieleFraction::new(field);
+        let r: Result<(MultivariatePolynomial<_, u16>, _), _> = fit_univariate(
+            |x| {
+                let y = match fraction.evaluate(x) {
+                    Some(y) => field.add(&y, &field.one()),
+                    None => field.zero(),
+                };
+                fraction.add_sample(*x, y);
+                y
+            },
+            field,
+            10,
+        );
+        assert_eq!(r, Err(FitError::FieldTooSmall));
+    }
+}
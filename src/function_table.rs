@@ -0,0 +1,635 @@
+//! Export of the functions registered in a [`State`] to JSON, so that model
+//! definitions can be shared between projects. A function is exported with its
+//! attributes, its declared inverse and an optional user annotation:
+//! ```json
+//! {
+//!   "version": 1,
+//!   "functions": [
+//!     {"name": "exp", "attributes": [], "inverse": {"name": "log", "domain": "real"}},
+//!     {"name": "T", "attributes": ["noncommutative"], "annotation": "SU(N) generator"}
+//!   ]
+//! }
+//! ```
+//! The table is merged into an existing state with [`import_function_table`], which
+//! reports the functions whose imported definition differs from the existing one.
+
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+};
+
+use ahash::HashMap;
+
+use crate::{
+    representations::Identifier,
+    state::{FunctionAttribute, InverseDomain, State},
+};
+
+/// The version of the function table format that is written by [`export_function_table`].
+pub const FUNCTION_TABLE_VERSION: u32 = 1;
+
+/// The maximal nesting depth of arrays and objects in a function table, which bounds
+/// the recursion of the parser on malicious input.
+const MAX_JSON_DEPTH: usize = 64;
+
+/// The definition of a function in a function table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionDefinition {
+    pub name: String,
+    pub attributes: Vec<FunctionAttribute>,
+    /// The name of the inverse and the domain on which it inverts the function.
+    pub inverse: Option<(String, InverseDomain)>,
+    pub annotation: Option<String>,
+}
+
+/// A function that is already defined in the state, with a definition that
+/// differs from the imported one. The existing definition is kept.
+#[derive(Debug, Clone)]
+pub struct FunctionCollision {
+    pub id: Identifier,
+    pub existing: FunctionDefinition,
+    pub imported: FunctionDefinition,
+}
+
+/// The result of merging a function table into a state.
+#[derive(Debug, Clone, Default)]
+pub struct FunctionTableImport {
+    /// The functions that were newly defined.
+    pub imported: Vec<Identifier>,
+    /// The functions whose definition conflicts with the existing one.
+    pub collisions: Vec<FunctionCollision>,
+    /// The annotations of all functions in the table.
+    pub annotations: HashMap<Identifier, String>,
+}
+
+fn attribute_name(a: FunctionAttribute) -> &'static str {
+    match a {
+        FunctionAttribute::NonCommutative => "noncommutative",
+        FunctionAttribute::KroneckerDelta => "kroneckerdelta",
+        FunctionAttribute::LeviCivita => "levicivita",
+        FunctionAttribute::Conditional => "conditional",
+        FunctionAttribute::List => "list",
+    }
+}
+
+fn attribute_from_name(name: &str) -> Option<FunctionAttribute> {
+    match name {
+        "noncommutative" => Some(FunctionAttribute::NonCommutative),
+        "kroneckerdelta" => Some(FunctionAttribute::KroneckerDelta),
+        "levicivita" => Some(FunctionAttribute::LeviCivita),
+        "conditional" => Some(FunctionAttribute::Conditional),
+        "list" => Some(FunctionAttribute::List),
+        _ => None,
+    }
+}
+
+fn domain_name(d: InverseDomain) -> &'static str {
+    match d {
+        InverseDomain::All => "all",
+        InverseDomain::Real => "real",
+        InverseDomain::Positive => "positive",
+        InverseDomain::None => "none",
+    }
+}
+
+fn domain_from_name(name: &str) -> Option<InverseDomain> {
+    match name {
+        "all" => Some(InverseDomain::All),
+        "real" => Some(InverseDomain::Real),
+        "positive" => Some(InverseDomain::Positive),
+        "none" => Some(InverseDomain::None),
+        _ => None,
+    }
+}
+
+impl FunctionDefinition {
+    /// Get the definition of the function `id` in `state`, without annotation.
+    pub fn from_state(id: Identifier, state: &State) -> Self {
+        FunctionDefinition {
+            name: state.get_name(id).unwrap().to_string(),
+            attributes: state.get_function_attributes(id).to_vec(),
+            inverse: state
+                .get_inverse_function(id)
+                .map(|(inv, d)| (state.get_name(inv).unwrap().to_string(), d)),
+            annotation: None,
+        }
+    }
+
+    /// Check if the function has no attributes and no inverse.
+    fn is_empty(&self) -> bool {
+        self.attributes.is_empty() && self.inverse.is_none()
+    }
+
+    /// Check if both definitions have the same attributes, in any order, and the same inverse.
+    fn same_definition(&self, other: &Self) -> bool {
+        self.inverse == other.inverse
+            && self.attributes.len() == other.attributes.len()
+            && self.attributes.iter().all(|a| other.attributes.contains(a))
+    }
+
+    fn write_json(&self, out: &mut String) {
+        out.push_str("{\"name\": ");
+        write_json_string(&self.name, out);
+
+        out.push_str(", \"attributes\": [");
+        for (i, a) in self.attributes.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            write_json_string(attribute_name(*a), out);
+        }
+        out.push(']');
+
+        if let Some((inverse, domain)) = &self.inverse {
+            out.push_str(", \"inverse\": {\"name\": ");
+            write_json_string(inverse, out);
+            out.push_str(", \"domain\": ");
+            write_json_string(domain_name(*domain), out);
+            out.push('}');
+        }
+
+        if let Some(annotation) = &self.annotation {
+            out.push_str(", \"annotation\": ");
+            write_json_string(annotation, out);
+        }
+        out.push('}');
+    }
+
+    fn from_json(value: &JsonValue) -> Result<Self, String> {
+        let name = value
+            .get("name")
+            .and_then(|n| n.as_str())
+            .ok_or("Function without name")?;
+
+        let mut attributes = vec![];
+        if let Some(a) = value.get("attributes") {
+            let JsonValue::Array(a) = a else {
+                return Err(format!("Attributes of {} are not a list", name));
+            };
+            for a in a {
+                let a = a
+                    .as_str()
+                    .and_then(attribute_from_name)
+                    .ok_or_else(|| format!("Unknown attribute {} of {}", a, name))?;
+                if !attributes.contains(&a) {
+                    attributes.push(a);
+                }
+            }
+        }
+
+        let inverse = match value.get("inverse") {
+            Some(i) => {
+                let inverse = i
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .ok_or_else(|| format!("Inverse of {} without name", name))?;
+                let domain = match i.get("domain") {
+                    Some(d) => d
+                        .as_str()
+                        .and_then(domain_from_name)
+                        .ok_or_else(|| format!("Unknown inverse domain {} of {}", d, name))?,
+                    None => InverseDomain::All,
+                };
+                Some((inverse.to_string(), domain))
+            }
+            None => None,
+        };
+
+        let annotation = match value.get("annotation") {
+            Some(a) => Some(
+                a.as_str()
+                    .ok_or_else(|| format!("Annotation of {} is not a string", name))?
+                    .to_string(),
+            ),
+            None => None,
+        };
+
+        Ok(FunctionDefinition {
+            name: name.to_string(),
+            attributes,
+            inverse,
+            annotation,
+        })
+    }
+}
+
+/// Write all functions in `state` that have attributes or an inverse, or that have an
+/// annotation in `annotations`, as a JSON function table to `dest`. The functions are
+/// sorted by name, so that the output is suitable for version control.
+pub fn export_function_table<W: Write>(
+    state: &State,
+    annotations: &HashMap<Identifier, String>,
+    dest: &mut W,
+) -> io::Result<()> {
+    let mut functions = vec![];
+    let mut i = 0;
+    while state.get_name(Identifier::from(i)).is_some() {
+        let id = Identifier::from(i);
+        let mut def = FunctionDefinition::from_state(id, state);
+        def.annotation = annotations.get(&id).cloned();
+        if !def.is_empty() || def.annotation.is_some() {
+            functions.push(def);
+        }
+        i += 1;
+    }
+    functions.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut out = String::new();
+    writeln!(out, "{{\n  \"version\": {},", FUNCTION_TABLE_VERSION).unwrap();
+    out.push_str("  \"functions\": [");
+    for (i, f) in functions.iter().enumerate() {
+        out.push_str(if i > 0 { ",\n    " } else { "\n    " });
+        f.write_json(&mut out);
+    }
+    if !functions.is_empty() {
+        out.push_str("\n  ");
+    }
+    out.push_str("]\n}\n");
+
+    dest.write_all(out.as_bytes())
+}
+
+/// Merge a function table that was written by [`export_function_table`] into `state`.
+/// Functions that are not defined in `state` yet, or that have neither attributes nor an
+/// inverse, get the imported definition. Functions that already have a different
+/// definition keep it and are reported as a [`FunctionCollision`].
+///
+/// The table is validated before `state` is modified, so that an error leaves
+/// `state` unchanged.
+pub fn import_function_table(
+    input: &str,
+    state: &mut State,
+) -> Result<FunctionTableImport, String> {
+    let table = JsonParser::new(input).parse_document()?;
+
+    let version = table
+        .get("version")
+        .and_then(|v| v.as_u32())
+        .ok_or("Missing version")?;
+    if version > FUNCTION_TABLE_VERSION {
+        return Err(format!(
+            "Unsupported version {}, the latest supported version is {}",
+            version, FUNCTION_TABLE_VERSION
+        ));
+    }
+
+    let Some(JsonValue::Array(functions)) = table.get("functions") else {
+        return Err("Missing list of functions".into());
+    };
+    let definitions = functions
+        .iter()
+        .map(FunctionDefinition::from_json)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (i, d) in definitions.iter().enumerate() {
+        if definitions[..i].iter().any(|d2| d2.name == d.name) {
+            return Err(format!("Duplicate function {}", d.name));
+        }
+    }
+
+    let mut result = FunctionTableImport::default();
+    for def in definitions {
+        let id = state.get_or_insert_var(&def.name);
+        if let Some(annotation) = &def.annotation {
+            result.annotations.insert(id, annotation.clone());
+        }
+
+        let existing = FunctionDefinition::from_state(id, state);
+        if existing.same_definition(&def) {
+            continue;
+        }
+
+        if !existing.is_empty() {
+            result.collisions.push(FunctionCollision {
+                id,
+                existing,
+                imported: def,
+            });
+            continue;
+        }
+
+        state.set_function_attributes(id, &def.attributes);
+        if let Some((inverse, domain)) = &def.inverse {
+            let inverse = state.get_or_insert_var(inverse);
+            state.set_inverse_function(id, inverse, *domain);
+        }
+        result.imported.push(id);
+    }
+
+    Ok(result)
+}
+
+fn write_json_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// A parsed JSON value. Numbers are only used for the version and are kept as text.
+#[derive(Debug)]
+enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(o) => o.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn as_u32(&self) -> Option<u32> {
+        match self {
+            JsonValue::Number(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonValue::Null => f.write_str("null"),
+            JsonValue::Bool(b) => write!(f, "{}", b),
+            JsonValue::Number(n) => f.write_str(n),
+            JsonValue::String(s) => {
+                let mut out = String::new();
+                write_json_string(s, &mut out);
+                f.write_str(&out)
+            }
+            JsonValue::Array(_) => f.write_str("[...]"),
+            JsonValue::Object(_) => f.write_str("{...}"),
+        }
+    }
+}
+
+/// A recursive descent parser for JSON, with a nesting depth of at most [`MAX_JSON_DEPTH`].
+struct JsonParser<'a> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn new(input: &'a str) -> Self {
+        JsonParser {
+            input,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<JsonValue, String> {
+        let v = self.parse_value()?;
+        self.skip_whitespace();
+        if self.pos < self.input.len() {
+            return Err(self.error("Unexpected trailing characters"));
+        }
+        Ok(v)
+    }
+
+    fn error(&self, msg: &str) -> String {
+        format!("{} at position {}", msg, self.pos)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", c)))
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+        if matches!(self.peek(), Some('{' | '[')) {
+            if self.depth == MAX_JSON_DEPTH {
+                return Err(self.error("Maximal nesting depth exceeded"));
+            }
+            self.depth += 1;
+            let v = self.parse_container();
+            self.depth -= 1;
+            return v;
+        }
+
+        match self.peek() {
+            Some('"') => Ok(JsonValue::String(self.parse_string()?)),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let len = self.input[self.pos..]
+                    .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+                    .unwrap_or(self.input.len() - self.pos);
+                let n = &self.input[self.pos..self.pos + len];
+                if n.parse::<f64>().is_err() {
+                    return Err(self.error("Invalid number"));
+                }
+                self.pos += len;
+                Ok(JsonValue::Number(n.to_string()))
+            }
+            _ => {
+                for (word, value) in [
+                    ("null", JsonValue::Null),
+                    ("true", JsonValue::Bool(true)),
+                    ("false", JsonValue::Bool(false)),
+                ] {
+                    if self.input[self.pos..].starts_with(word) {
+                        self.pos += word.len();
+                        return Ok(value);
+                    }
+                }
+                Err(self.error("Expected a value"))
+            }
+        }
+    }
+
+    fn parse_container(&mut self) -> Result<JsonValue, String> {
+        match self.peek() {
+            Some('{') => {
+                self.pos += 1;
+                let mut fields = vec![];
+                self.skip_whitespace();
+                if self.peek() == Some('}') {
+                    self.pos += 1;
+                    return Ok(JsonValue::Object(fields));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.parse_string()?;
+                    self.expect(':')?;
+                    fields.push((key, self.parse_value()?));
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some('}') => {
+                            self.pos += 1;
+                            return Ok(JsonValue::Object(fields));
+                        }
+                        _ => return Err(self.error("Expected ',' or '}'")),
+                    }
+                }
+            }
+            Some('[') => {
+                self.pos += 1;
+                let mut elements = vec![];
+                self.skip_whitespace();
+                if self.peek() == Some(']') {
+                    self.pos += 1;
+                    return Ok(JsonValue::Array(elements));
+                }
+                loop {
+                    elements.push(self.parse_value()?);
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some(']') => {
+                            self.pos += 1;
+                            return Ok(JsonValue::Array(elements));
+                        }
+                        _ => return Err(self.error("Expected ',' or ']'")),
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        if self.peek() != Some('"') {
+            return Err(self.error("Expected a string"));
+        }
+        self.pos += 1;
+
+        let mut s = String::new();
+        loop {
+            let c = self
+                .peek()
+                .ok_or_else(|| self.error("Unterminated string"))?;
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(s),
+                c if (c as u32) < 0x20 => {
+                    return Err(self.error("Unescaped control character in string"))
+                }
+                '\\' => {
+                    let e = self
+                        .peek()
+                        .ok_or_else(|| self.error("Unterminated string"))?;
+                    self.pos += e.len_utf8();
+                    match e {
+                        '"' | '\\' | '/' => s.push(e),
+                        'b' => s.push('\u{8}'),
+                        'f' => s.push('\u{c}'),
+                        'n' => s.push('\n'),
+                        'r' => s.push('\r'),
+                        't' => s.push('\t'),
+                        'u' => {
+                            let mut c = self.parse_hex4()?;
+                            if (0xD800..0xDC00).contains(&c) {
+                                // a surrogate pair
+                                if !self.input[self.pos..].starts_with("\\u") {
+                                    return Err(self.error("Unpaired surrogate"));
+                                }
+                                self.pos += 2;
+                                let low = self.parse_hex4()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(self.error("Unpaired surrogate"));
+                                }
+                                c = 0x10000 + ((c - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            s.push(
+                                char::from_u32(c)
+                                    .ok_or_else(|| self.error("Invalid unicode escape"))?,
+                            );
+                        }
+                        _ => return Err(self.error("Invalid escape sequence")),
+                    }
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, String> {
+        let hex = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("Invalid unicode escape"))?;
+        let c = u32::from_str_radix(hex, 16).map_err(|_| self.error("Invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JsonParser, JsonValue, MAX_JSON_DEPTH};
+
+    fn parse(input: &str) -> Result<JsonValue, String> {
+        JsonParser::new(input).parse_document()
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let v = parse(r#" {"a": [1, -2.5e3, null, true], "b": {"c": "x\"\u00e9\ud83d\ude00"}} "#)
+            .unwrap();
+        assert!(matches!(v.get("a"), Some(JsonValue::Array(a)) if a.len() == 4));
+        assert_eq!(
+            v.get("b").and_then(|b| b.get("c")).and_then(|c| c.as_str()),
+            Some("x\"\u{e9}\u{1f600}")
+        );
+
+        for invalid in [
+            r#"{"a": 1,}"#,
+            r#"[1 2]"#,
+            r#""\u+041""#,
+            r#""\u00g1""#,
+            r#""\ud83d""#,
+            "\"a\nb\"",
+            r#"{"a": 1} x"#,
+        ] {
+            assert!(parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_json_depth() {
+        let nested = |depth: usize| "[".repeat(depth) + "]".repeat(depth).as_str();
+        assert!(parse(&nested(MAX_JSON_DEPTH)).is_ok());
+        assert!(parse(&nested(MAX_JSON_DEPTH + 1)).is_err());
+        assert!(parse(&nested(1_000_000)).is_err());
+    }
+}
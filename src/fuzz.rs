@@ -0,0 +1,347 @@
+//! Generation of random valid instances from unstructured data with the
+//! [`arbitrary`] crate, for property testing and fuzzing. Enabled with the
+//! `arbitrary` feature.
+
+use std::fmt;
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+use rug::{integer::Order, Integer as ArbitraryPrecisionInteger};
+
+use crate::{
+    poly::{polynomial::MultivariatePolynomial, Exponent},
+    printer::AtomPrinter,
+    representations::{
+        number::Number, Atom, AtomView, Identifier, Num, OwnedAdd, OwnedAtom, OwnedFun, OwnedMul,
+        OwnedNum, OwnedPow, OwnedVar,
+    },
+    rings::{
+        finite_field::{FiniteField, FiniteFieldCore},
+        integer::{Integer, IntegerRing},
+        rational::{Rational, RationalField},
+        rational_polynomial::{FromNumeratorAndDenominator, RationalPolynomial},
+        Field, Ring,
+    },
+    state::{ResettableBuffer, State, Workspace},
+};
+
+/// Limits on the size of the generated instances.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GeneratorSettings {
+    /// The maximal number of variables of a polynomial.
+    pub max_vars: usize,
+    /// The maximal number of terms of a polynomial or arguments of a sum,
+    /// product or function.
+    pub max_terms: usize,
+    /// The maximal exponent of a variable.
+    pub max_degree: u32,
+    /// The maximal number of bytes of a large integer.
+    pub max_coefficient_bytes: usize,
+    /// The maximal depth of an expression.
+    pub max_depth: usize,
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        GeneratorSettings {
+            max_vars: 3,
+            max_terms: 5,
+            max_degree: 4,
+            max_coefficient_bytes: 16,
+            max_depth: 3,
+        }
+    }
+}
+
+/// A ring whose elements can be generated from unstructured data.
+pub trait ArbitraryElement: Ring {
+    fn arbitrary_element(
+        &self,
+        u: &mut Unstructured,
+        settings: &GeneratorSettings,
+    ) -> Result<Self::Element>;
+}
+
+impl ArbitraryElement for IntegerRing {
+    fn arbitrary_element(
+        &self,
+        u: &mut Unstructured,
+        settings: &GeneratorSettings,
+    ) -> Result<Integer> {
+        // mostly small integers, since they have the most special cases
+        if u.ratio(1, 4)? {
+            let len = u.int_in_range(0..=settings.max_coefficient_bytes)?;
+            let mut n = ArbitraryPrecisionInteger::from_digits(u.bytes(len)?, Order::Lsf);
+            if bool::arbitrary(u)? {
+                n = -n;
+            }
+            Ok(Integer::from_large(n))
+        } else {
+            Ok(Integer::Natural(u.int_in_range(-10..=10)?))
+        }
+    }
+}
+
+impl ArbitraryElement for RationalField {
+    fn arbitrary_element(
+        &self,
+        u: &mut Unstructured,
+        settings: &GeneratorSettings,
+    ) -> Result<Rational> {
+        let num = IntegerRing::new().arbitrary_element(u, settings)?;
+        let mut den = IntegerRing::new().arbitrary_element(u, settings)?;
+        if IntegerRing::is_zero(&den) {
+            den = Integer::Natural(1);
+        }
+
+        Ok(self.div(&to_rational(num), &to_rational(den)))
+    }
+}
+
+fn to_rational(n: Integer) -> Rational {
+    match n {
+        Integer::Natural(n) => Rational::Natural(n, 1),
+        Integer::Large(n) => Rational::Large(n.into()),
+    }
+}
+
+impl ArbitraryElement for FiniteField<u32> {
+    fn arbitrary_element(
+        &self,
+        u: &mut Unstructured,
+        _settings: &GeneratorSettings,
+    ) -> Result<Self::Element> {
+        Ok(self.to_element(u.int_in_range(0..=self.get_prime() - 1)?))
+    }
+}
+
+impl ArbitraryElement for FiniteField<u64> {
+    fn arbitrary_element(
+        &self,
+        u: &mut Unstructured,
+        _settings: &GeneratorSettings,
+    ) -> Result<Self::Element> {
+        Ok(self.to_element(u.int_in_range(0..=self.get_prime() - 1)?))
+    }
+}
+
+impl<F: ArbitraryElement, E: Exponent> MultivariatePolynomial<F, E> {
+    /// Generate a polynomial in `nvars` variables over `field`.
+    pub fn arbitrary_with(
+        u: &mut Unstructured,
+        field: F,
+        nvars: usize,
+        var_map: Option<&[Identifier]>,
+        settings: &GeneratorSettings,
+    ) -> Result<Self> {
+        let mut poly = MultivariatePolynomial::new(nvars, field, None, var_map);
+
+        let nterms = u.int_in_range(0..=settings.max_terms)?;
+        let mut exponents = vec![E::zero(); nvars];
+        for _ in 0..nterms {
+            for e in &mut exponents {
+                *e = E::from_u32(u.int_in_range(0..=settings.max_degree)?);
+            }
+            let coeff = field.arbitrary_element(u, settings)?;
+            poly.append_monomial(coeff, &exponents);
+        }
+
+        Ok(poly)
+    }
+}
+
+impl<'a, E: Exponent> Arbitrary<'a> for MultivariatePolynomial<IntegerRing, E> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let settings = GeneratorSettings::default();
+        let nvars = u.int_in_range(1..=settings.max_vars)?;
+        Self::arbitrary_with(u, IntegerRing::new(), nvars, None, &settings)
+    }
+}
+
+impl<'a, E: Exponent> Arbitrary<'a> for MultivariatePolynomial<RationalField, E> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let settings = GeneratorSettings::default();
+        let nvars = u.int_in_range(1..=settings.max_vars)?;
+        Self::arbitrary_with(u, RationalField::new(), nvars, None, &settings)
+    }
+}
+
+impl<E: Exponent> RationalPolynomial<IntegerRing, E> {
+    /// Generate a reduced rational polynomial in `nvars` variables.
+    pub fn arbitrary_with(
+        u: &mut Unstructured,
+        nvars: usize,
+        var_map: Option<&[Identifier]>,
+        settings: &GeneratorSettings,
+    ) -> Result<Self> {
+        let field = IntegerRing::new();
+        let num = MultivariatePolynomial::arbitrary_with(u, field, nvars, var_map, settings)?;
+        let mut den = MultivariatePolynomial::arbitrary_with(u, field, nvars, var_map, settings)?;
+        if den.is_zero() {
+            den = num.new_from_constant(field.one());
+        }
+
+        Ok(RationalPolynomial::from_num_den(num, den, field, true))
+    }
+}
+
+impl<'a, E: Exponent> Arbitrary<'a> for RationalPolynomial<IntegerRing, E> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let settings = GeneratorSettings::default();
+        let nvars = u.int_in_range(1..=settings.max_vars)?;
+        Self::arbitrary_with(u, nvars, None, &settings)
+    }
+}
+
+/// A generator of normalized expressions in a fixed set of variables and functions.
+#[derive(Clone, Debug)]
+pub struct AtomGenerator {
+    pub variables: Vec<Identifier>,
+    pub functions: Vec<Identifier>,
+    pub settings: GeneratorSettings,
+}
+
+impl AtomGenerator {
+    /// Create a generator for expressions in the variables `x0`, `x1`, ...
+    /// and the functions `f0`, `f1`, ...
+    pub fn new(
+        nvars: usize,
+        nfunctions: usize,
+        settings: GeneratorSettings,
+        state: &mut State,
+    ) -> AtomGenerator {
+        AtomGenerator {
+            variables: (0..nvars)
+                .map(|i| state.get_or_insert_var(format!("x{}", i)))
+                .collect(),
+            functions: (0..nfunctions)
+                .map(|i| state.get_or_insert_var(format!("f{}", i)))
+                .collect(),
+            settings,
+        }
+    }
+
+    /// Generate a normalized expression and write it in `out`.
+    pub fn generate<P: Atom>(
+        &self,
+        u: &mut Unstructured,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) -> Result<()> {
+        let mut h = workspace.new_atom();
+        self.generate_unnormalized(u, self.settings.max_depth, workspace, state, h.get_mut())?;
+        h.get().to_view().normalize(workspace, state, out);
+        Ok(())
+    }
+
+    fn generate_unnormalized<P: Atom>(
+        &self,
+        u: &mut Unstructured,
+        depth: usize,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) -> Result<()> {
+        // numbers and variables only at the maximal depth
+        let kind = if depth == 0 {
+            u.int_in_range(0..=1)?
+        } else {
+            u.int_in_range(0..=5)?
+        };
+        let nargs = u.int_in_range(1..=self.settings.max_terms.max(1))?;
+
+        match kind {
+            1 if !self.variables.is_empty() => {
+                out.transform_to_var()
+                    .set_from_id(*u.choose(&self.variables)?);
+            }
+            2 if !self.functions.is_empty() => {
+                let f = out.transform_to_fun();
+                f.set_from_name(*u.choose(&self.functions)?);
+                for _ in 0..nargs {
+                    let mut arg = workspace.new_atom();
+                    self.generate_unnormalized(u, depth - 1, workspace, state, arg.get_mut())?;
+                    f.add_arg(arg.get().to_view());
+                }
+                f.set_dirty(true);
+            }
+            3 => {
+                let mut base = workspace.new_atom();
+                self.generate_unnormalized(u, depth - 1, workspace, state, base.get_mut())?;
+
+                // prevent division by zero
+                let mut norm_base = workspace.new_atom();
+                base.get()
+                    .to_view()
+                    .normalize(workspace, state, norm_base.get_mut());
+                let mut e = u.int_in_range(1..=self.settings.max_degree.max(1) as i64)?;
+                let is_zero = matches!(norm_base.get().to_view(), AtomView::Num(n) if n.is_zero());
+                if !is_zero && bool::arbitrary(u)? {
+                    e = -e;
+                }
+
+                let mut exp = workspace.new_atom();
+                exp.get_mut()
+                    .transform_to_num()
+                    .set_from_number(Number::Natural(e, 1));
+
+                let p = out.transform_to_pow();
+                p.set_from_base_and_exp(norm_base.get().to_view(), exp.get().to_view());
+                p.set_dirty(true);
+            }
+            4 => {
+                let m = out.transform_to_mul();
+                for _ in 0..nargs {
+                    let mut arg = workspace.new_atom();
+                    self.generate_unnormalized(u, depth - 1, workspace, state, arg.get_mut())?;
+                    m.extend(arg.get().to_view());
+                }
+                m.set_dirty(true);
+            }
+            5 => {
+                let a = out.transform_to_add();
+                for _ in 0..nargs {
+                    let mut arg = workspace.new_atom();
+                    self.generate_unnormalized(u, depth - 1, workspace, state, arg.get_mut())?;
+                    a.extend(arg.get().to_view());
+                }
+                a.set_dirty(true);
+            }
+            _ => {
+                let n = RationalField::new().arbitrary_element(u, &self.settings)?;
+                out.transform_to_num().set_from_number(n.into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A normalized expression together with the state that defines its variables and
+/// functions, generated by an [`AtomGenerator`] with the default settings in the
+/// variables `x0`, `x1`, `x2` and the functions `f0`, `f1`.
+pub struct ArbitraryAtom<P: Atom> {
+    pub state: State,
+    pub atom: OwnedAtom<P>,
+}
+
+impl<'a, P: Atom> Arbitrary<'a> for ArbitraryAtom<P> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut state = State::new();
+        let workspace = Workspace::new();
+        let generator = AtomGenerator::new(3, 2, GeneratorSettings::default(), &mut state);
+
+        let mut atom = OwnedAtom::new();
+        generator.generate(u, &workspace, &state, &mut atom)?;
+        Ok(ArbitraryAtom { state, atom })
+    }
+}
+
+impl<P: Atom> fmt::Debug for ArbitraryAtom<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(
+            &AtomPrinter::new(self.atom.to_view(), <_>::default(), &self.state),
+            f,
+        )
+    }
+}
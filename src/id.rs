@@ -2,6 +2,7 @@ use ahash::HashMap;
 use smallvec::{smallvec, SmallVec};
 
 use crate::{
+    budget::OutOfBudget,
     representations::{
         Add, Atom, AtomView, Fun, Identifier, ListSlice, Mul, OwnedAdd, OwnedAtom, OwnedFun,
         OwnedMul, OwnedPow, Pow, SliceType, Var,
@@ -9,6 +10,10 @@ use crate::{
     state::{ResettableBuffer, State, Workspace},
 };
 
+/// A pattern that can be matched to an expression. Variables and functions that end with
+/// an underscore are wildcards. In a list of function arguments, factors or terms, a wildcard
+/// can match a sequence: `x_` and `x__` match one or more entries and `x___` matches
+/// zero or more entries, unless a [`PatternRestriction::Length`] is set.
 pub enum Pattern<P: Atom> {
     Wildcard(Identifier),
     Fn(Identifier, bool, Vec<Self>), // bool signifies that the identifier is a wildcard
@@ -28,6 +33,91 @@ impl<'a, P: Atom> AtomView<'a, P> {
     pub fn into_pattern(self, state: &State) -> Pattern<P> {
         Pattern::from_view(self, state)
     }
+
+    /// Replace all variables in `map` by their values in a single traversal.
+    /// The replacements are simultaneous: a variable that appears in a
+    /// replacement value is not replaced again.
+    /// Returns `true` if a replacement was made.
+    pub fn replace_all_multiple(
+        &self,
+        map: &HashMap<Identifier, AtomView<'_, P>>,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) -> bool {
+        let mut h = workspace.new_atom();
+        if self.replace_variables(map, workspace, h.get_mut()) {
+            h.get().to_view().normalize(workspace, state, out);
+            true
+        } else {
+            out.from_view(self);
+            false
+        }
+    }
+
+    /// Replace all variables in `map` by their values, without normalizing the output.
+    pub(crate) fn replace_variables(
+        &self,
+        map: &HashMap<Identifier, AtomView<'_, P>>,
+        workspace: &Workspace<P>,
+        out: &mut OwnedAtom<P>,
+    ) -> bool {
+        let mut changed = false;
+        match self {
+            AtomView::Num(_) => {
+                out.from_view(self);
+            }
+            AtomView::Var(v) => {
+                if let Some(value) = map.get(&v.get_name()) {
+                    out.from_view(value);
+                    changed = true;
+                } else {
+                    out.from_view(self);
+                }
+            }
+            AtomView::Fun(f) => {
+                let fun = out.transform_to_fun();
+                fun.set_from_name(f.get_name());
+                for arg in f.iter() {
+                    let mut arg_h = workspace.new_atom();
+                    changed |= arg.replace_variables(map, workspace, arg_h.get_mut());
+                    fun.add_arg(arg_h.get().to_view());
+                }
+                fun.set_dirty(changed);
+            }
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                let mut base_h = workspace.new_atom();
+                changed |= base.replace_variables(map, workspace, base_h.get_mut());
+                let mut exp_h = workspace.new_atom();
+                changed |= exp.replace_variables(map, workspace, exp_h.get_mut());
+
+                let pow = out.transform_to_pow();
+                pow.set_from_base_and_exp(base_h.get().to_view(), exp_h.get().to_view());
+                pow.set_dirty(changed);
+            }
+            AtomView::Mul(m) => {
+                let mul = out.transform_to_mul();
+                for arg in m.iter() {
+                    let mut arg_h = workspace.new_atom();
+                    changed |= arg.replace_variables(map, workspace, arg_h.get_mut());
+                    mul.extend(arg_h.get().to_view());
+                }
+                mul.set_dirty(changed);
+            }
+            AtomView::Add(a) => {
+                let add = out.transform_to_add();
+                for arg in a.iter() {
+                    let mut arg_h = workspace.new_atom();
+                    changed |= arg.replace_variables(map, workspace, arg_h.get_mut());
+                    add.extend(arg_h.get().to_view());
+                }
+                add.set_dirty(changed);
+            }
+        }
+
+        changed
+    }
 }
 
 impl<P: Atom> Pattern<P> {
@@ -161,6 +251,7 @@ impl<P: Atom> Pattern<P> {
                                 unreachable!("Wildcard cannot be function name")
                             }
                         }
+                        continue;
                     }
 
                     let mut handle = workspace.new_atom();
@@ -191,6 +282,7 @@ impl<P: Atom> Pattern<P> {
                                 unreachable!("Wildcard cannot be function name")
                             }
                         }
+                        continue;
                     }
 
                     let mut handle = workspace.new_atom();
@@ -230,6 +322,7 @@ impl<P: Atom> Pattern<P> {
                                 unreachable!("Wildcard cannot be function name")
                             }
                         }
+                        continue;
                     }
 
                     let mut handle = workspace.new_atom();
@@ -266,6 +359,7 @@ impl<P: Atom> Pattern<P> {
                                 unreachable!("Wildcard cannot be function name")
                             }
                         }
+                        continue;
                     }
 
                     let mut handle = workspace.new_atom();
@@ -291,13 +385,46 @@ impl<P: Atom> Pattern<P> {
         workspace: &Workspace<P>,
         restrictions: &HashMap<Identifier, Vec<PatternRestriction<P>>>,
         out: &mut OwnedAtom<P>,
+    ) -> bool {
+        self.replace_all_with(
+            target,
+            &ReplaceWith::Pattern(rhs),
+            state,
+            workspace,
+            restrictions,
+            out,
+        )
+    }
+
+    /// Replace all occurrences of the pattern in the target, where the replacement is
+    /// either a pattern or constructed by a callback from the match stack.
+    /// For a conditional replacement, only matches for which the condition on the complete
+    /// match stack holds are replaced.
+    /// For every matched atom, the first canonical match is used and then the atom is skipped.
+    pub fn replace_all_with<'a>(
+        &self,
+        target: AtomView<'a, P>,
+        rhs: &ReplaceWith<'_, P>,
+        state: &'a State,
+        workspace: &Workspace<P>,
+        restrictions: &HashMap<Identifier, Vec<PatternRestriction<P>>>,
+        out: &mut OwnedAtom<P>,
     ) -> bool {
         let mut match_stack = MatchStack::new(restrictions);
         let mut it = SubSliceIterator::new(self, target, state, &match_stack, true);
-        if let Some((_, used_flags)) = it.next(&mut match_stack) {
+
+        let mut matched: Option<SmallVec<[bool; 10]>> = None;
+        while let Some((_, used_flags)) = it.next(&mut match_stack) {
+            if rhs.accepts(&match_stack) {
+                matched = Some(used_flags.into());
+                break;
+            }
+        }
+
+        if let Some(used_flags) = matched {
             let mut handle = workspace.new_atom();
             let rhs_subs = handle.get_mut();
-            rhs.substitute_wildcards(workspace, rhs_subs, &match_stack);
+            rhs.substitute(&match_stack, workspace, state, rhs_subs);
 
             match target {
                 AtomView::Mul(m) => {
@@ -349,8 +476,14 @@ impl<P: Atom> Pattern<P> {
                         let mut child_handle = workspace.new_atom();
                         let child_buf = child_handle.get_mut();
 
-                        submatch |=
-                            self.replace_all(child, rhs, state, workspace, restrictions, child_buf);
+                        submatch |= self.replace_all_with(
+                            child,
+                            rhs,
+                            state,
+                            workspace,
+                            restrictions,
+                            child_buf,
+                        );
 
                         out.add_arg(child_buf.to_view());
                     }
@@ -366,11 +499,12 @@ impl<P: Atom> Pattern<P> {
                     let mut base_handle = workspace.new_atom();
                     let base_out = base_handle.get_mut();
                     let mut submatch =
-                        self.replace_all(base, rhs, state, workspace, restrictions, base_out);
+                        self.replace_all_with(base, rhs, state, workspace, restrictions, base_out);
 
                     let mut exp_handle = workspace.new_atom();
                     let exp_out = exp_handle.get_mut();
-                    submatch |= self.replace_all(exp, rhs, state, workspace, restrictions, exp_out);
+                    submatch |=
+                        self.replace_all_with(exp, rhs, state, workspace, restrictions, exp_out);
 
                     out.set_from_base_and_exp(base_out.to_view(), exp_out.to_view());
 
@@ -385,8 +519,14 @@ impl<P: Atom> Pattern<P> {
                         let mut child_handle = workspace.new_atom();
                         let child_buf = child_handle.get_mut();
 
-                        submatch |=
-                            self.replace_all(child, rhs, state, workspace, restrictions, child_buf);
+                        submatch |= self.replace_all_with(
+                            child,
+                            rhs,
+                            state,
+                            workspace,
+                            restrictions,
+                            child_buf,
+                        );
 
                         out.extend(child_buf.to_view());
                     }
@@ -401,8 +541,14 @@ impl<P: Atom> Pattern<P> {
                         let mut child_handle = workspace.new_atom();
                         let child_buf = child_handle.get_mut();
 
-                        submatch |=
-                            self.replace_all(child, rhs, state, workspace, restrictions, child_buf);
+                        submatch |= self.replace_all_with(
+                            child,
+                            rhs,
+                            state,
+                            workspace,
+                            restrictions,
+                            child_buf,
+                        );
 
                         out.extend(child_buf.to_view());
                     }
@@ -436,6 +582,49 @@ impl<P: Atom> std::fmt::Debug for Pattern<P> {
     }
 }
 
+/// A callback that writes a replacement into its last argument, based on the current match stack.
+pub type MatchMap<'c, P> =
+    dyn Fn(&MatchStack<'_, '_, P>, &Workspace<P>, &State, &mut OwnedAtom<P>) + 'c;
+
+/// A condition on a complete match, for example to test a relation
+/// between more than two wildcards.
+pub type MatchCondition<'c, P> = dyn Fn(&MatchStack<'_, '_, P>) -> bool + 'c;
+
+/// The right-hand side of a replacement.
+pub enum ReplaceWith<'c, P: Atom> {
+    /// A pattern in which the wildcards are substituted by their matches.
+    Pattern(&'c Pattern<P>),
+    /// A callback that constructs the replacement.
+    Map(&'c MatchMap<'c, P>),
+    /// A replacement that is only applied when the condition holds.
+    Conditional(&'c MatchCondition<'c, P>, Box<ReplaceWith<'c, P>>),
+}
+
+impl<'c, P: Atom> ReplaceWith<'c, P> {
+    /// Check if the conditions of all nested conditional replacements hold.
+    fn accepts(&self, match_stack: &MatchStack<'_, '_, P>) -> bool {
+        match self {
+            ReplaceWith::Conditional(c, r) => c(match_stack) && r.accepts(match_stack),
+            _ => true,
+        }
+    }
+
+    /// Write the replacement for the current match stack in `out`.
+    fn substitute(
+        &self,
+        match_stack: &MatchStack<'_, '_, P>,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        match self {
+            ReplaceWith::Pattern(rhs) => rhs.substitute_wildcards(workspace, out, match_stack),
+            ReplaceWith::Map(f) => f(match_stack, workspace, state, out),
+            ReplaceWith::Conditional(_, r) => r.substitute(match_stack, workspace, state, out),
+        }
+    }
+}
+
 /// Restrictions for a wildcard. Note that a length restriction
 /// applies at any level and therefore
 /// `x_*f(x_) : length(x) == 2`
@@ -474,7 +663,7 @@ impl<'a, P: Atom> std::fmt::Debug for Match<'a, P> {
 
 impl<'a, P: Atom> Match<'a, P> {
     /// Create a new atom from a matched subexpression.
-    fn to_atom(&self, out: &mut OwnedAtom<P>) {
+    pub fn to_atom(&self, out: &mut OwnedAtom<P>) {
         match self {
             Self::Single(v) => {
                 out.from_view(v);
@@ -627,8 +816,9 @@ impl<'a, 'b, P: Atom> MatchStack<'a, 'b, P> {
     }
 
     /// Get the range of an identifier based on previous matches and based
-    /// on restrictions.
-    pub fn get_range(&self, identifier: Identifier) -> (usize, Option<usize>) {
+    /// on restrictions. Without restrictions, the minimal length is 0 for wildcards
+    /// of level 3 and 1 otherwise.
+    pub fn get_range(&self, identifier: Identifier, state: &State) -> (usize, Option<usize>) {
         for (rk, rv) in self.stack.iter() {
             if rk == &identifier {
                 return match rv {
@@ -670,8 +860,14 @@ impl<'a, 'b, P: Atom> MatchStack<'a, 'b, P> {
             }
         }
 
-        // defaulft the minimum to 1
-        (minimal.unwrap_or(1), maximal)
+        // default the minimum to 1, unless the wildcard can match an empty sequence
+        let default_minimal = if state.get_wildcard_level(identifier) >= 3 {
+            0
+        } else {
+            1
+        };
+
+        (minimal.unwrap_or(default_minimal), maximal)
     }
 }
 
@@ -777,7 +973,7 @@ impl<'a, 'b, P: Atom> SubSliceIterator<'a, 'b, P> {
         let min_length: usize = pat_list
             .iter()
             .map(|x| match x {
-                Pattern::Wildcard(id) => match_stack.get_range(*id).0,
+                Pattern::Wildcard(id) => match_stack.get_range(*id, state).0,
                 _ => 1,
             })
             .sum();
@@ -815,7 +1011,7 @@ impl<'a, 'b, P: Atom> SubSliceIterator<'a, 'b, P> {
         let min_length: usize = pattern
             .iter()
             .map(|x| match x {
-                Pattern::Wildcard(id) => match_stack.get_range(*id).0,
+                Pattern::Wildcard(id) => match_stack.get_range(*id, state).0,
                 _ => 1,
             })
             .sum();
@@ -827,7 +1023,9 @@ impl<'a, 'b, P: Atom> SubSliceIterator<'a, 'b, P> {
         let max_length: usize = pattern
             .iter()
             .map(|x| match x {
-                Pattern::Wildcard(id) => match_stack.get_range(*id).1.unwrap_or(target.len()),
+                Pattern::Wildcard(id) => {
+                    match_stack.get_range(*id, state).1.unwrap_or(target.len())
+                }
                 _ => 1,
             })
             .sum();
@@ -885,7 +1083,7 @@ impl<'a, 'b, P: Atom> SubSliceIterator<'a, 'b, P> {
                 // add new iterator
                 let it = match &self.pattern[self.iterators.len()] {
                     Pattern::Wildcard(name) => {
-                        let range = match_stack.get_range(*name);
+                        let range = match_stack.get_range(*name, self.state);
 
                         PatternIter::Wildcard(WildcardIter {
                             initialized: false,
@@ -1513,10 +1711,9 @@ impl<'a: 'b, 'b, P: Atom + 'a + 'b> ReplaceIterator<'a, 'b, P> {
 
     /// Return the next replacement.
     pub fn next(&mut self, workspace: &Workspace<P>, out: &mut OwnedAtom<P>) -> Option<()> {
-        let Some((position, used_flags, _target, match_stack)) =
-            self.pattern_tree_iterator.next()
+        let Some((position, used_flags, _target, match_stack)) = self.pattern_tree_iterator.next()
         else {
-            return None
+            return None;
         };
         let mut rhs_handle = workspace.new_atom();
         let new_rhs = rhs_handle.get_mut();
@@ -1536,3 +1733,294 @@ impl<'a: 'b, 'b, P: Atom + 'a + 'b> ReplaceIterator<'a, 'b, P> {
         Some(())
     }
 }
+
+/// A rewrite rule `lhs -> rhs`, where the wildcards in `lhs` may be restricted.
+pub struct Rule<P: Atom> {
+    pub name: String,
+    pub lhs: Pattern<P>,
+    pub rhs: Pattern<P>,
+    pub restrictions: HashMap<Identifier, Vec<PatternRestriction<P>>>,
+    pub priority: i32,
+}
+
+/// A set of rewrite rules that is applied until a fixed point is reached.
+/// Rules with a higher priority are tried first, and after every rule that
+/// changes the expression, the rule application restarts with the rule
+/// with the highest priority.
+pub struct RuleSet<P: Atom> {
+    rules: Vec<Rule<P>>,
+    max_iterations: usize,
+}
+
+impl<P: Atom> Default for RuleSet<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Atom> RuleSet<P> {
+    /// Create an empty rule set.
+    pub fn new() -> Self {
+        Self {
+            rules: vec![],
+            max_iterations: 1000,
+        }
+    }
+
+    /// Set the maximum number of rule applications in a single call to `apply`.
+    pub fn set_max_iterations(&mut self, max_iterations: usize) -> &mut Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Add a rule. Rules with the same priority are tried in the order they are added.
+    pub fn add_rule(&mut self, rule: Rule<P>) -> &mut Self {
+        let pos = self.rules.partition_point(|r| r.priority >= rule.priority);
+        self.rules.insert(pos, rule);
+        self
+    }
+
+    /// Get the rules, ordered by decreasing priority.
+    pub fn get_rules(&self) -> &[Rule<P>] {
+        &self.rules
+    }
+
+    /// Apply the rules to `target` until no rule changes the expression anymore,
+    /// and write the result in `out`. If `trace` is set, the index of every rule
+    /// that fired is recorded, which helps to debug non-terminating rewrites.
+    ///
+    /// Returns `false` if the maximum number of iterations was reached before
+    /// a fixed point was found.
+    pub fn apply(
+        &self,
+        target: AtomView<'_, P>,
+        state: &State,
+        workspace: &Workspace<P>,
+        out: &mut OwnedAtom<P>,
+        trace: Option<&mut Vec<usize>>,
+    ) -> bool {
+        match self.apply_impl(target, state, workspace, out, trace, false) {
+            Ok(r) => r,
+            Err(_) => unreachable!("The memory budget is not checked"),
+        }
+    }
+
+    /// Apply the rules like [`RuleSet::apply`], but check the memory budget of
+    /// `workspace` before every iteration and return an error when it is exceeded.
+    /// The content of `out` is unspecified after an error.
+    pub fn try_apply(
+        &self,
+        target: AtomView<'_, P>,
+        state: &State,
+        workspace: &Workspace<P>,
+        out: &mut OwnedAtom<P>,
+        trace: Option<&mut Vec<usize>>,
+    ) -> Result<bool, OutOfBudget> {
+        self.apply_impl(target, state, workspace, out, trace, true)
+    }
+
+    fn apply_impl(
+        &self,
+        target: AtomView<'_, P>,
+        state: &State,
+        workspace: &Workspace<P>,
+        out: &mut OwnedAtom<P>,
+        mut trace: Option<&mut Vec<usize>>,
+        check_budget: bool,
+    ) -> Result<bool, OutOfBudget> {
+        out.from_view(&target);
+
+        let mut handle = workspace.new_atom();
+        let new = handle.get_mut();
+        let mut handle_norm = workspace.new_atom();
+        let norm = handle_norm.get_mut();
+
+        for _ in 0..self.max_iterations {
+            if check_budget {
+                workspace.check_budget("rule_set")?;
+            }
+
+            let mut fired = false;
+            for (i, rule) in self.rules.iter().enumerate() {
+                if !rule.lhs.replace_all(
+                    out.to_view(),
+                    &rule.rhs,
+                    state,
+                    workspace,
+                    &rule.restrictions,
+                    new,
+                ) {
+                    continue;
+                }
+
+                // replacements in subexpressions are not normalized yet
+                new.to_view().normalize(workspace, state, norm);
+
+                if norm.to_view() != out.to_view() {
+                    std::mem::swap(out, norm);
+
+                    if let Some(t) = trace.as_mut() {
+                        t.push(i);
+                    }
+
+                    fired = true;
+                    break;
+                }
+            }
+
+            if !fired {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ahash::HashMap;
+
+    use super::{Match, MatchStack, Pattern, ReplaceWith, Rule, RuleSet};
+    use crate::{
+        budget::MemoryBudget,
+        parser::parse,
+        representations::{
+            default::DefaultRepresentation, number::BorrowedNumber, AtomView, Num, OwnedAtom,
+        },
+        state::{ResettableBuffer, State, Workspace},
+    };
+
+    #[test]
+    fn test_nested_conditional_replacement() {
+        let mut state = State::new();
+        let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+        let expr = parse("f(1,2)+f(2,2)+f(3,1)")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+        let lhs = parse("f(x_,y_)")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+        let rhs = parse("g(x_,y_)")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+        let expected = parse("f(1,2)+f(2,2)+g(3,1)")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+
+        let x = state.get_or_insert_var("x_");
+        let y = state.get_or_insert_var("y_");
+
+        let lhs = Pattern::from_view(lhs.to_view(), &state);
+        let rhs = Pattern::from_view(rhs.to_view(), &state);
+
+        let different = |m: &MatchStack<DefaultRepresentation>| m.get(x) != m.get(y);
+        let not_one = |m: &MatchStack<DefaultRepresentation>| {
+            !matches!(m.get(x), Some(Match::Single(AtomView::Num(n)))
+                if n.get_number_view() == BorrowedNumber::Natural(1, 1))
+        };
+
+        let mut out = OwnedAtom::new();
+        lhs.replace_all_with(
+            expr.to_view(),
+            &ReplaceWith::Conditional(
+                &different,
+                Box::new(ReplaceWith::Conditional(
+                    &not_one,
+                    Box::new(ReplaceWith::Pattern(&rhs)),
+                )),
+            ),
+            &state,
+            &workspace,
+            &HashMap::default(),
+            &mut out,
+        );
+
+        let mut norm = OwnedAtom::new();
+        out.to_view().normalize(&workspace, &state, &mut norm);
+        assert_eq!(norm.to_view(), expected.to_view());
+    }
+
+    #[test]
+    fn test_substitute_wildcard_arguments() {
+        let mut state = State::new();
+        let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+        let expr = parse("f(a,b)")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+        let lhs = parse("f(x_,y_)")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+        // wildcards that are arguments of a function, power, product and sum
+        let rhs = parse("g(x_,y_)*x_^y_+x_+x_*y_")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+        let expected = parse("g(a,b)*a^b+a+a*b")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+
+        let lhs = Pattern::from_view(lhs.to_view(), &state);
+        let rhs = Pattern::from_view(rhs.to_view(), &state);
+
+        let mut out = OwnedAtom::new();
+        assert!(lhs.replace_all(
+            expr.to_view(),
+            &rhs,
+            &state,
+            &workspace,
+            &HashMap::default(),
+            &mut out,
+        ));
+
+        let mut norm = OwnedAtom::new();
+        out.to_view().normalize(&workspace, &state, &mut norm);
+        assert_eq!(norm.to_view(), expected.to_view());
+    }
+
+    #[test]
+    fn test_rule_set_memory_budget() {
+        let mut state = State::new();
+        let workspace: Workspace<DefaultRepresentation> =
+            Workspace::new().with_memory_budget(MemoryBudget::new(1 << 16));
+
+        let expr = parse("f(x)")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+        let lhs = parse("f(x_)")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+        let rhs = parse("f(x_+x_^2)")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+
+        let mut rules = RuleSet::new();
+        rules.add_rule(Rule {
+            name: "grow".to_owned(),
+            lhs: Pattern::from_view(lhs.to_view(), &state),
+            rhs: Pattern::from_view(rhs.to_view(), &state),
+            restrictions: HashMap::default(),
+            priority: 0,
+        });
+
+        let mut out = OwnedAtom::new();
+        let err = rules
+            .try_apply(expr.to_view(), &state, &workspace, &mut out, None)
+            .unwrap_err();
+        assert_eq!(err.limit, 1 << 16);
+        assert!(err.used > err.limit);
+        assert!(workspace.memory_usage() >= err.used);
+    }
+}
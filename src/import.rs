@@ -0,0 +1,273 @@
+use ahash::HashMap;
+
+use crate::{
+    parser::{parse, Token},
+    representations::{Atom, OwnedAtom},
+    state::{State, Workspace},
+};
+
+/// Rename the function heads in a parsed expression using `fn_map`.
+fn rename_functions(token: &mut Token, fn_map: &HashMap<String, String>) {
+    match token {
+        Token::Fn(_, args) => {
+            if let Some(Token::ID(name)) = args.first_mut() {
+                if let Some(new_name) = fn_map.get(name.as_str()) {
+                    *name = new_name.as_str().into();
+                }
+            }
+
+            for a in args.iter_mut().skip(1) {
+                rename_functions(a, fn_map);
+            }
+        }
+        Token::Op(_, _, _, args) => {
+            for a in args {
+                rename_functions(a, fn_map);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Strip a FORM assignment such as `L F =` or `F =` from the start of a statement.
+fn strip_form_assignment(statement: &str) -> &str {
+    if let Some((lhs, rhs)) = statement.split_once('=') {
+        let mut words = lhs.split_whitespace();
+        let is_declaration = match (words.next(), words.next(), words.next()) {
+            (Some(_), None, None) => true,
+            (Some(w), Some(_), None) => {
+                matches!(w, "L" | "G" | "Local" | "Global")
+            }
+            _ => false,
+        };
+
+        if is_declaration {
+            return rhs;
+        }
+    }
+
+    statement
+}
+
+/// Import an expression written in FORM syntax, such as the output of a `Print` statement.
+///
+/// The input may consist of several statements separated by `;`. Statements of the form
+/// `$x = expr` (optionally preceded by `#`) define local dollar variables, which are
+/// substituted in all following statements. The last statement is the expression that
+/// is imported, where a leading assignment such as `L F =` is ignored.
+/// Function names are renamed using `fn_map`.
+pub fn import_form<P: Atom>(
+    input: &str,
+    fn_map: &HashMap<String, String>,
+    state: &mut State,
+    workspace: &Workspace<P>,
+) -> Result<OwnedAtom<P>, String> {
+    let mut dollars: Vec<(String, String)> = vec![];
+    let mut expr = None;
+
+    for statement in input.split(';') {
+        if statement.trim().is_empty() {
+            continue;
+        }
+
+        let mut s = statement.replace("**", "^");
+
+        let t = s.trim().trim_start_matches('#').trim_start();
+        let mut definition = None;
+        if t.starts_with('$') {
+            if let Some((name, value)) = t.split_once('=') {
+                let name = name.trim();
+                if name
+                    .chars()
+                    .skip(1)
+                    .all(|c| c.is_alphanumeric() || c == '_')
+                {
+                    definition = Some((name.to_owned(), value.to_owned()));
+                }
+            }
+        }
+
+        if let Some((_, value)) = &mut definition {
+            s = std::mem::take(value);
+        }
+
+        // substitute previously defined dollar variables, longest names first
+        // so that `$xy` is not replaced by the value of `$x`
+        for (name, value) in dollars.iter().rev() {
+            s = s.replace(name.as_str(), &format!("({})", value));
+        }
+
+        if let Some((name, _)) = definition {
+            dollars.retain(|(n, _)| *n != name);
+            dollars.push((name, s.trim().to_owned()));
+            dollars.sort_by_key(|(n, _)| n.len());
+            continue;
+        }
+
+        expr = Some(strip_form_assignment(&s).to_owned());
+    }
+
+    let Some(expr) = expr else {
+        return Err("No expression found".to_owned());
+    };
+
+    let mut token = parse(&expr)?;
+    rename_functions(&mut token, fn_map);
+    token.to_atom(state, workspace)
+}
+
+/// Translate a Mathematica expression to Symbolica syntax, starting at position `i`.
+/// The translation stops at a `,` or `]` at the top level, or at the end of the input.
+fn translate_mathematica(input: &[char], i: &mut usize) -> Result<String, String> {
+    let mut out = String::with_capacity(input.len() - *i);
+    let mut depth = 0;
+
+    while *i < input.len() {
+        let c = input[*i];
+        match c {
+            '(' if input.get(*i + 1) == Some(&'*') => {
+                // skip a comment
+                *i += 2;
+                while *i + 1 < input.len() && !(input[*i] == '*' && input[*i + 1] == ')') {
+                    *i += 1;
+                }
+
+                if *i + 1 >= input.len() {
+                    return Err("Unterminated comment".to_owned());
+                }
+                *i += 2;
+            }
+            '(' => {
+                depth += 1;
+                out.push(c);
+                *i += 1;
+            }
+            ')' => {
+                if depth == 0 {
+                    return Err(format!("Unbalanced parenthesis at index {}", i));
+                }
+                depth -= 1;
+                out.push(c);
+                *i += 1;
+            }
+            ',' | ']' if depth == 0 => break,
+            '[' => return Err(format!("Unexpected [ at index {}", i)),
+            '`' => return Err(format!("Contexts are not supported, at index {}", i)),
+            c if c.is_alphabetic() || c == '$' => {
+                let start = *i;
+                while *i < input.len() && (input[*i].is_alphanumeric() || input[*i] == '$') {
+                    *i += 1;
+                }
+                let name: String = input[start..*i].iter().collect();
+
+                let mut j = *i;
+                while j < input.len() && input[j].is_whitespace() {
+                    j += 1;
+                }
+
+                if j == input.len() || input[j] != '[' {
+                    out.push_str(&name);
+                    continue;
+                }
+
+                // read the arguments of the function
+                *i = j + 1;
+                let mut args = vec![];
+                loop {
+                    let arg = translate_mathematica(input, i)?;
+                    if *i == input.len() {
+                        return Err(format!("Unterminated arguments of {}", name));
+                    }
+
+                    if !arg.trim().is_empty() {
+                        args.push(arg);
+                    }
+
+                    *i += 1;
+                    if input[*i - 1] == ']' {
+                        break;
+                    }
+                }
+
+                match name.as_str() {
+                    "Power" | "Pow" => {
+                        if args.len() < 2 {
+                            return Err(format!("{} needs at least two arguments", name));
+                        }
+
+                        // powers are right associative
+                        let mut pow = args.pop().unwrap();
+                        for a in args.iter().rev() {
+                            pow = format!("(({})^({}))", a, pow);
+                        }
+                        out.push_str(&pow);
+                    }
+                    "Rational" => {
+                        if args.len() != 2 {
+                            return Err("Rational needs two arguments".to_owned());
+                        }
+                        out.push_str(&format!("(({})/({}))", args[0], args[1]));
+                    }
+                    "Times" | "Plus" => {
+                        let (op, empty) = if name == "Times" {
+                            ('*', "1")
+                        } else {
+                            ('+', "0")
+                        };
+
+                        if args.is_empty() {
+                            out.push_str(empty);
+                            continue;
+                        }
+
+                        out.push('(');
+                        for (k, a) in args.iter().enumerate() {
+                            if k > 0 {
+                                out.push(op);
+                            }
+                            out.push('(');
+                            out.push_str(a);
+                            out.push(')');
+                        }
+                        out.push(')');
+                    }
+                    _ => {
+                        out.push_str(&name);
+                        out.push('(');
+                        out.push_str(&args.join(","));
+                        out.push(')');
+                    }
+                }
+            }
+            _ => {
+                out.push(c);
+                *i += 1;
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Import an expression written in Mathematica `InputForm` or `FullForm`.
+/// Function calls `f[x]` become functions and `Power`, `Rational`, `Times` and `Plus`
+/// are converted to their Symbolica equivalents. All other function names
+/// are renamed using `fn_map`, for example to map `Log` to `log`.
+pub fn import_mathematica<P: Atom>(
+    input: &str,
+    fn_map: &HashMap<String, String>,
+    state: &mut State,
+    workspace: &Workspace<P>,
+) -> Result<OwnedAtom<P>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
+    let expr = translate_mathematica(&chars, &mut i)?;
+
+    if i != chars.len() {
+        return Err(format!("Unexpected {} at index {}", chars[i], i));
+    }
+
+    let mut token = parse(&expr)?;
+    rename_functions(&mut token, fn_map);
+    token.to_atom(state, workspace)
+}
@@ -0,0 +1,261 @@
+//! Timing and operation counts of the core algorithms. The timings are enabled with the
+//! `instrumentation` feature and the operation counts with the `stats` feature, which is
+//! implied by `instrumentation`.
+//!
+//! Every instrumented call of an algorithm adds its duration and the size of its input
+//! to a summary that can be obtained with [`summary`]. Nested calls of the same algorithm
+//! are only counted once.
+//!
+//! In debug builds, every call also creates a `tracing` span at the debug level. The
+//! crate enables the `release_max_level_warn` feature of `tracing`, so that these spans
+//! are compiled out of release builds, in which only the [`summary`] is available.
+//!
+//! The summary also counts the coefficient operations and heap pushes of the polynomial
+//! multiplication and division, and the number of gcd computations. In contrast to timings,
+//! the counts do not depend on the machine, so that different algorithms or inputs can be
+//! compared by the work they perform. The counts are shared by all threads.
+
+#[cfg(feature = "stats")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "instrumentation")]
+use std::{cell::RefCell, sync::Mutex, time::Instant};
+use std::{
+    fmt::{Display, Formatter},
+    time::Duration,
+};
+
+/// Statistics of the calls of an instrumented algorithm.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SpanStatistics {
+    pub name: &'static str,
+    pub calls: usize,
+    pub total_time: Duration,
+    pub max_time: Duration,
+    /// The sum of the sizes of the inputs, for example in number of terms.
+    pub total_size: usize,
+    pub max_size: usize,
+}
+
+/// The number of operations performed by the polynomial algorithms.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OperationCounts {
+    pub coefficient_muls: u64,
+    /// The number of additions of products of coefficients, which is the number of
+    /// products minus the number of terms they contribute to.
+    pub coefficient_adds: u64,
+    /// The number of divisions of coefficients by the leading coefficient of a divisor.
+    pub coefficient_divisions: u64,
+    pub heap_pushes: u64,
+    pub gcd_calls: u64,
+}
+
+/// A summary of the time spent in the instrumented algorithms.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    /// The statistics per algorithm, sorted by descending total time.
+    pub spans: Vec<SpanStatistics>,
+    pub operations: OperationCounts,
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<12} {:>10} {:>14} {:>14} {:>12} {:>12}",
+            "algorithm", "calls", "total", "max", "total size", "max size"
+        )?;
+        for s in &self.spans {
+            writeln!(
+                f,
+                "{:<12} {:>10} {:>14.3?} {:>14.3?} {:>12} {:>12}",
+                s.name, s.calls, s.total_time, s.max_time, s.total_size, s.max_size
+            )?;
+        }
+
+        let o = &self.operations;
+        for (name, count) in [
+            ("coefficient mults", o.coefficient_muls),
+            ("coefficient adds", o.coefficient_adds),
+            ("coefficient divisions", o.coefficient_divisions),
+            ("heap pushes", o.heap_pushes),
+            ("gcd calls", o.gcd_calls),
+        ] {
+            writeln!(f, "{:<22} {:>14}", name, count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "instrumentation")]
+static STATISTICS: Mutex<Vec<SpanStatistics>> = Mutex::new(Vec::new());
+
+#[cfg(feature = "stats")]
+pub(crate) struct Counters {
+    pub(crate) coefficient_muls: AtomicU64,
+    pub(crate) coefficient_adds: AtomicU64,
+    pub(crate) coefficient_divisions: AtomicU64,
+    pub(crate) heap_pushes: AtomicU64,
+    pub(crate) gcd_calls: AtomicU64,
+}
+
+#[cfg(feature = "stats")]
+pub(crate) static COUNTERS: Counters = Counters {
+    coefficient_muls: AtomicU64::new(0),
+    coefficient_adds: AtomicU64::new(0),
+    coefficient_divisions: AtomicU64::new(0),
+    heap_pushes: AtomicU64::new(0),
+    gcd_calls: AtomicU64::new(0),
+};
+
+#[cfg(feature = "instrumentation")]
+thread_local!(
+    static ACTIVE_SPANS: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+);
+
+/// Get the summary of all instrumented calls since the start of the
+/// program or the last [`reset`]. The timings are empty if the
+/// `instrumentation` feature is disabled, and the operation counts are
+/// zero if the `stats` feature is disabled.
+pub fn summary() -> Summary {
+    #[cfg(feature = "instrumentation")]
+    let spans = {
+        let mut spans = STATISTICS.lock().unwrap().clone();
+        spans.sort_by_key(|s| std::cmp::Reverse(s.total_time));
+        spans
+    };
+    #[cfg(not(feature = "instrumentation"))]
+    let spans = vec![];
+
+    #[cfg(feature = "stats")]
+    let operations = OperationCounts {
+        coefficient_muls: COUNTERS.coefficient_muls.load(Ordering::Relaxed),
+        coefficient_adds: COUNTERS.coefficient_adds.load(Ordering::Relaxed),
+        coefficient_divisions: COUNTERS.coefficient_divisions.load(Ordering::Relaxed),
+        heap_pushes: COUNTERS.heap_pushes.load(Ordering::Relaxed),
+        gcd_calls: COUNTERS.gcd_calls.load(Ordering::Relaxed),
+    };
+    #[cfg(not(feature = "stats"))]
+    let operations = OperationCounts::default();
+
+    Summary { spans, operations }
+}
+
+/// Clear the statistics and set all operation counts to zero.
+pub fn reset() {
+    #[cfg(feature = "instrumentation")]
+    STATISTICS.lock().unwrap().clear();
+
+    #[cfg(feature = "stats")]
+    {
+        for c in [
+            &COUNTERS.coefficient_muls,
+            &COUNTERS.coefficient_adds,
+            &COUNTERS.coefficient_divisions,
+            &COUNTERS.heap_pushes,
+            &COUNTERS.gcd_calls,
+        ] {
+            c.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A guard that records the duration of an instrumented call when it is dropped.
+#[cfg(feature = "instrumentation")]
+pub(crate) struct SpanGuard {
+    name: &'static str,
+    size: usize,
+    start: Instant,
+    _span: tracing::span::EnteredSpan,
+}
+
+#[cfg(feature = "instrumentation")]
+impl SpanGuard {
+    /// Start a span for the algorithm `name`, if the algorithm
+    /// is not already running on the current thread.
+    pub(crate) fn new(name: &'static str, size: usize) -> Option<SpanGuard> {
+        let nested = ACTIVE_SPANS.with(|a| {
+            let mut a = a.borrow_mut();
+            let nested = a.contains(&name);
+            if !nested {
+                a.push(name);
+            }
+            nested
+        });
+
+        if nested {
+            return None;
+        }
+
+        Some(SpanGuard {
+            name,
+            size,
+            start: Instant::now(),
+            // compiled out in release builds, see the module documentation
+            _span: tracing::debug_span!("instrumented", algorithm = name, size).entered(),
+        })
+    }
+}
+
+#[cfg(feature = "instrumentation")]
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        let time = self.start.elapsed();
+        ACTIVE_SPANS.with(|a| a.borrow_mut().retain(|n| *n != self.name));
+
+        let mut stats = STATISTICS.lock().unwrap();
+        let s = match stats.iter().position(|s| s.name == self.name) {
+            Some(p) => &mut stats[p],
+            None => {
+                stats.push(SpanStatistics {
+                    name: self.name,
+                    ..Default::default()
+                });
+                stats.last_mut().unwrap()
+            }
+        };
+
+        s.calls += 1;
+        s.total_time += time;
+        s.max_time = s.max_time.max(time);
+        s.total_size += self.size;
+        s.max_size = s.max_size.max(self.size);
+    }
+}
+
+/// Instrument the rest of the current scope as a call of the algorithm `name`
+/// with an input of size `size`.
+#[cfg(feature = "instrumentation")]
+macro_rules! instrument_scope {
+    ($name:literal, $size:expr) => {
+        let _instrumentation_guard = $crate::instrumentation::SpanGuard::new($name, $size);
+    };
+}
+
+#[cfg(not(feature = "instrumentation"))]
+macro_rules! instrument_scope {
+    ($name:literal, $size:expr) => {};
+}
+
+pub(crate) use instrument_scope;
+
+/// Add `n` to the operation counter `counter`.
+#[cfg(feature = "stats")]
+macro_rules! count_operations {
+    ($counter:ident, $n:expr) => {
+        $crate::instrumentation::COUNTERS
+            .$counter
+            .fetch_add($n as u64, std::sync::atomic::Ordering::Relaxed);
+    };
+}
+
+/// Without the feature, the count is not evaluated but still uses its variables.
+#[cfg(not(feature = "stats"))]
+macro_rules! count_operations {
+    ($counter:ident, $n:expr) => {
+        if false {
+            let _ = $n;
+        }
+    };
+}
+
+pub(crate) use count_operations;
@@ -0,0 +1,152 @@
+//! Numerical interpolation tables for functions that are only known at a set of nodes,
+//! such as parton distribution functions. A table is attached to a function with
+//! [`State::set_interpolation_table`], after which [`AtomView::evaluate_f64`]
+//! evaluates `f(x)` with a cubic spline through the nodes.
+
+use ahash::HashMap;
+
+use crate::{
+    representations::{
+        number::BorrowedNumber, Add, Atom, AtomView, Fun, Identifier, Mul, Num, Pow, Var,
+    },
+    state::State,
+    terms::number_to_f64,
+};
+
+/// A natural cubic spline through the nodes `(x_i, y_i)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InterpolationTable {
+    x: Vec<f64>,
+    y: Vec<f64>,
+    /// The second derivative of the spline at every node.
+    d2y: Vec<f64>,
+}
+
+impl InterpolationTable {
+    /// Create a spline through the nodes `(x[i], y[i])`. The `x` values must be finite
+    /// and strictly increasing, and there must be at least two nodes.
+    pub fn new(x: Vec<f64>, y: Vec<f64>) -> Result<Self, String> {
+        if x.len() != y.len() {
+            return Err(format!(
+                "Expected as many values as nodes: {} vs {}",
+                y.len(),
+                x.len()
+            ));
+        }
+        if x.len() < 2 {
+            return Err("At least two nodes are required".to_owned());
+        }
+        if x.iter().chain(&y).any(|v| !v.is_finite()) {
+            return Err("The nodes and values must be finite".to_owned());
+        }
+        if x.windows(2).any(|w| w[0] >= w[1]) {
+            return Err("The nodes must be strictly increasing".to_owned());
+        }
+
+        // solve the tridiagonal system for the second derivatives, which
+        // vanish at the end points, with the Thomas algorithm
+        let n = x.len();
+        let mut d2y = vec![0.; n];
+        let mut c = vec![0.; n];
+        for i in 1..n - 1 {
+            let h0 = x[i] - x[i - 1];
+            let h1 = x[i + 1] - x[i];
+            let rhs = 6. * ((y[i + 1] - y[i]) / h1 - (y[i] - y[i - 1]) / h0);
+            let diag = 2. * (h0 + h1) - h0 * c[i - 1];
+            c[i] = h1 / diag;
+            d2y[i] = (rhs - h0 * d2y[i - 1]) / diag;
+        }
+        for i in (1..n - 1).rev() {
+            d2y[i] -= c[i] * d2y[i + 1];
+        }
+
+        Ok(InterpolationTable { x, y, d2y })
+    }
+
+    /// Get the nodes `x` and the values `y` of the spline.
+    pub fn get_nodes(&self) -> (&[f64], &[f64]) {
+        (&self.x, &self.y)
+    }
+
+    /// Get the range `[x_0, x_n]` of the nodes.
+    pub fn range(&self) -> (f64, f64) {
+        (self.x[0], self.x[self.x.len() - 1])
+    }
+
+    /// Evaluate the spline at `x`, or return `None` if `x` is outside the range
+    /// of the nodes, as the table does not extrapolate.
+    pub fn evaluate(&self, x: f64) -> Option<f64> {
+        let (lo, hi) = self.range();
+        if !(lo..=hi).contains(&x) {
+            return None;
+        }
+
+        // the index of the interval [x_i, x_{i+1}] that contains x
+        let i = self
+            .x
+            .partition_point(|v| *v <= x)
+            .clamp(1, self.x.len() - 1)
+            - 1;
+
+        let h = self.x[i + 1] - self.x[i];
+        let a = (self.x[i + 1] - x) / h;
+        let b = (x - self.x[i]) / h;
+        Some(
+            a * self.y[i]
+                + b * self.y[i + 1]
+                + ((a * a * a - a) * self.d2y[i] + (b * b * b - b) * self.d2y[i + 1]) * h * h / 6.,
+        )
+    }
+}
+
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Evaluate the expression as a floating point number, where every variable has
+    /// the value in `values`. A function with a single argument is evaluated using
+    /// its interpolation table in `state`.
+    ///
+    /// Returns `None` if a variable has no value, if a function has no interpolation
+    /// table or is evaluated outside its range, or if the expression contains numbers
+    /// that are not rational.
+    pub fn evaluate_f64(&self, values: &HashMap<Identifier, f64>, state: &State) -> Option<f64> {
+        match self {
+            AtomView::Num(n) => number_to_f64(n.get_number_view()),
+            AtomView::Var(v) => values.get(&v.get_name()).cloned(),
+            AtomView::Fun(f) => {
+                let table = state.get_interpolation_table(f.get_name())?;
+                if f.get_nargs() != 1 {
+                    return None;
+                }
+
+                let arg = f.iter().next().unwrap().evaluate_f64(values, state)?;
+                table.evaluate(arg)
+            }
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                let base = base.evaluate_f64(values, state)?;
+                if let AtomView::Num(n) = exp {
+                    if let BorrowedNumber::Natural(e, 1) = n.get_number_view() {
+                        if let Ok(e) = i32::try_from(e) {
+                            return Some(base.powi(e));
+                        }
+                    }
+                }
+
+                Some(base.powf(exp.evaluate_f64(values, state)?))
+            }
+            AtomView::Mul(m) => {
+                let mut r = 1.;
+                for x in m.iter() {
+                    r *= x.evaluate_f64(values, state)?;
+                }
+                Some(r)
+            }
+            AtomView::Add(a) => {
+                let mut r = 0.;
+                for x in a.iter() {
+                    r += x.evaluate_f64(values, state)?;
+                }
+                Some(r)
+            }
+        }
+    }
+}
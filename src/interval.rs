@@ -0,0 +1,280 @@
+//! Interval arithmetic with outward rounding. Every operation returns an interval
+//! that is guaranteed to contain the exact result for all values in the input
+//! intervals, which can be used to prove that an expression has a definite sign
+//! on a domain, for example that a denominator does not vanish on an integration domain.
+
+use std::fmt::{self, Display};
+
+use ahash::HashMap;
+
+use crate::{
+    poly::{polynomial::MultivariatePolynomial, Exponent},
+    representations::{
+        number::BorrowedNumber, Add, Atom, AtomView, Identifier, Mul, Num, Pow, Var,
+    },
+    rings::{integer::Integer, rational::Rational, Ring},
+};
+
+/// The largest integer below which all integers are exactly representable as `f64`.
+const EXACT_F64_INTEGER: u64 = 1 << 53;
+
+/// A closed interval `[lo, hi]` of floating point numbers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+impl Interval {
+    /// Create the interval `[lo, hi]`.
+    pub fn new(lo: f64, hi: f64) -> Self {
+        assert!(lo <= hi, "Empty interval [{}, {}]", lo, hi);
+        Interval { lo, hi }
+    }
+
+    /// Create the interval that only contains `x`.
+    pub fn point(x: f64) -> Self {
+        Interval { lo: x, hi: x }
+    }
+
+    /// Create an interval that contains `x`, where `x` is off by at most one unit in the last place.
+    fn around(x: f64) -> Self {
+        Interval {
+            lo: x.next_down().next_down(),
+            hi: x.next_up().next_up(),
+        }
+    }
+
+    pub fn contains(&self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+
+    pub fn contains_zero(&self) -> bool {
+        self.contains(0.)
+    }
+
+    /// Check if all values in the interval are positive.
+    pub fn is_positive(&self) -> bool {
+        self.lo > 0.
+    }
+
+    /// Check if all values in the interval are negative.
+    pub fn is_negative(&self) -> bool {
+        self.hi < 0.
+    }
+
+    pub fn width(&self) -> f64 {
+        (self.hi - self.lo).next_up()
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        Interval {
+            lo: (self.lo + other.lo).next_down(),
+            hi: (self.hi + other.hi).next_up(),
+        }
+    }
+
+    pub fn neg(&self) -> Self {
+        Interval {
+            lo: -self.hi,
+            hi: -self.lo,
+        }
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        let p = [
+            self.lo * other.lo,
+            self.lo * other.hi,
+            self.hi * other.lo,
+            self.hi * other.hi,
+        ];
+
+        Interval {
+            lo: p.iter().cloned().fold(f64::INFINITY, f64::min).next_down(),
+            hi: p
+                .iter()
+                .cloned()
+                .fold(f64::NEG_INFINITY, f64::max)
+                .next_up(),
+        }
+    }
+
+    /// Compute `1/self`, or `None` if the interval contains zero.
+    pub fn inv(&self) -> Option<Self> {
+        if self.contains_zero() {
+            return None;
+        }
+
+        Some(Interval {
+            lo: (1. / self.hi).next_down(),
+            hi: (1. / self.lo).next_up(),
+        })
+    }
+
+    /// Compute `self/other`, or `None` if `other` contains zero.
+    pub fn div(&self, other: &Self) -> Option<Self> {
+        Some(self.mul(&other.inv()?))
+    }
+
+    /// Compute `self^n`. Even powers are non-negative, so that `[-1,2]^2 = [0,4]`
+    /// instead of the `[-2,4]` of repeated multiplication.
+    /// Returns `None` for negative powers of an interval that contains zero.
+    pub fn powi(&self, n: i64) -> Option<Self> {
+        if n < 0 {
+            return self.powi(-n)?.inv();
+        }
+
+        if n == 0 {
+            return Some(Interval::point(1.));
+        }
+
+        let n = n as u64;
+        let r = if n % 2 == 1 || self.lo >= 0. {
+            // monotonically increasing
+            Interval {
+                lo: pow_bound(self.lo, n, false),
+                hi: pow_bound(self.hi, n, true),
+            }
+        } else if self.hi <= 0. {
+            Interval {
+                lo: pow_bound(-self.hi, n, false),
+                hi: pow_bound(-self.lo, n, true),
+            }
+        } else {
+            Interval {
+                lo: 0.,
+                hi: pow_bound(self.hi.max(-self.lo), n, true),
+            }
+        };
+        Some(r)
+    }
+}
+
+/// Compute a lower or upper bound of `x^n`.
+fn pow_bound(x: f64, n: u64, upper: bool) -> f64 {
+    if x < 0. {
+        // only called for odd powers
+        return -pow_bound(-x, n, !upper);
+    }
+
+    let mut r = 1.;
+    for _ in 0..n {
+        r *= x;
+        r = if upper { r.next_up() } else { r.next_down() };
+    }
+    r.max(0.)
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}, {}]", self.lo, self.hi)
+    }
+}
+
+/// A number that can be enclosed in an interval.
+pub trait ToInterval {
+    fn to_interval(&self) -> Interval;
+}
+
+impl ToInterval for Integer {
+    fn to_interval(&self) -> Interval {
+        match self {
+            Integer::Natural(n) if n.unsigned_abs() <= EXACT_F64_INTEGER => {
+                Interval::point(*n as f64)
+            }
+            Integer::Natural(n) => Interval::around(*n as f64),
+            Integer::Large(n) => Interval::around(n.to_f64()),
+        }
+    }
+}
+
+impl ToInterval for Rational {
+    fn to_interval(&self) -> Interval {
+        match self {
+            Rational::Natural(n, 1) => Integer::Natural(*n).to_interval(),
+            Rational::Natural(n, d) => Integer::Natural(*n)
+                .to_interval()
+                .div(&Integer::Natural(*d).to_interval())
+                .unwrap(),
+            Rational::Large(r) => Interval::around(r.to_f64()),
+        }
+    }
+}
+
+impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E>
+where
+    F::Element: ToInterval,
+{
+    /// Compute an interval that contains all values of the polynomial when the
+    /// variable `i` takes values in `values[i]`.
+    pub fn evaluate_interval(&self, values: &[Interval]) -> Interval {
+        assert_eq!(
+            values.len(),
+            self.nvars,
+            "Expected a value for every variable"
+        );
+
+        let mut res = Interval::point(0.);
+        for t in self {
+            let mut term = t.coefficient.to_interval();
+            for (v, e) in values.iter().zip(t.exponents) {
+                if !e.is_zero() {
+                    term = term.mul(&v.powi(e.to_u32() as i64).unwrap());
+                }
+            }
+            res = res.add(&term);
+        }
+        res
+    }
+}
+
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Compute an interval that contains all values of the expression when every
+    /// variable takes values in its interval in `values`.
+    ///
+    /// Returns `None` if a variable has no interval, if the expression contains
+    /// functions, non-integer powers or non-rational numbers, or if it divides
+    /// by an interval that contains zero.
+    pub fn evaluate_interval(&self, values: &HashMap<Identifier, Interval>) -> Option<Interval> {
+        match self {
+            AtomView::Num(n) => match n.get_number_view() {
+                BorrowedNumber::Natural(n, d) => Some(Rational::Natural(n, d).to_interval()),
+                BorrowedNumber::Large(r) => Some(Rational::Large(r.to_rat()).to_interval()),
+                BorrowedNumber::FiniteField(_, _)
+                | BorrowedNumber::RationalPolynomial(_)
+                | BorrowedNumber::AlgebraicNumber(_, _) => None,
+            },
+            AtomView::Var(v) => values.get(&v.get_name()).cloned(),
+            AtomView::Fun(_) => None,
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                let AtomView::Num(n) = exp else {
+                    return None;
+                };
+                let BorrowedNumber::Natural(e, 1) = n.get_number_view() else {
+                    return None;
+                };
+
+                base.evaluate_interval(values)?.powi(e)
+            }
+            AtomView::Mul(m) => {
+                let mut r = Interval::point(1.);
+                for x in m.iter() {
+                    r = r.mul(&x.evaluate_interval(values)?);
+                }
+                Some(r)
+            }
+            AtomView::Add(a) => {
+                let mut r = Interval::point(0.);
+                for x in a.iter() {
+                    r = r.add(&x.evaluate_interval(values)?);
+                }
+                Some(r)
+            }
+        }
+    }
+}
@@ -1,15 +1,45 @@
 pub mod api;
+pub mod assumptions;
+pub mod budget;
+pub mod builder;
+pub mod cancel;
 pub mod coefficient;
+pub mod derivative;
+pub mod determinant;
+pub mod diff;
+pub mod error;
 pub mod expand;
+pub mod fit;
+pub mod function_table;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
 pub mod id;
+pub mod import;
+pub mod instrumentation;
+pub mod interpolation;
+pub mod interval;
+pub mod list;
 pub mod normalize;
 pub mod parser;
+pub mod physics;
 pub mod poly;
 pub mod printer;
+pub mod progress;
+pub mod random;
 pub mod representations;
+pub mod residue;
 pub mod rings;
+pub mod simplify;
+pub mod slp;
+pub mod solve;
 pub mod state;
+pub mod statistics;
 pub mod streaming;
+pub mod symmetrize;
+pub mod tensor;
+pub mod terms;
+pub mod text_format;
+pub mod transform;
 pub mod utils;
 
 #[cfg(feature = "faster_alloc")]
@@ -0,0 +1,123 @@
+//! Lists of expressions. A list is a function `f(a_1, ..., a_n)` where `f` has the
+//! attribute [`FunctionAttribute::List`], so that lists can be used as function arguments
+//! and matched by patterns like any other function, for example with `f(x_, y___)`.
+//!
+//! There is no separate list atom: a list is an ordinary function atom whose name
+//! carries the attribute, so that normalization, printing, serialization and the
+//! pattern matcher handle lists without changes. As a consequence, a list is only
+//! recognized as such in combination with the [`State`] that holds the attribute.
+//!
+//! [`FunctionAttribute::List`]: crate::state::FunctionAttribute::List
+
+use crate::{
+    representations::{Atom, AtomView, Fun, Identifier, OwnedAtom, OwnedFun},
+    state::{State, Workspace},
+};
+
+/// Write the normalized list `name(elements)` in `out`.
+pub fn build_list<P: Atom>(
+    name: Identifier,
+    elements: &[AtomView<'_, P>],
+    workspace: &Workspace<P>,
+    state: &State,
+    out: &mut OwnedAtom<P>,
+) -> Result<(), String> {
+    if !state.is_list(name) {
+        return Err(format!("{} is not a list", state.get_name(name).unwrap()));
+    }
+
+    let mut h = workspace.new_atom();
+    let f = h.get_mut().transform_to_fun();
+    f.set_from_name(name);
+    for e in elements {
+        f.add_arg(*e);
+    }
+    f.set_dirty(true);
+    h.get().to_view().normalize(workspace, state, out);
+    Ok(())
+}
+
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Check if the expression is a list.
+    pub fn is_list(&self, state: &State) -> bool {
+        matches!(self, AtomView::Fun(f) if state.is_list(f.get_name()))
+    }
+
+    /// Get the elements of a list, or `None` if the expression is not a list.
+    pub fn get_list_elements(&self, state: &State) -> Option<Vec<AtomView<'a, P>>> {
+        match self {
+            AtomView::Fun(f) if state.is_list(f.get_name()) => Some(f.iter().collect()),
+            _ => None,
+        }
+    }
+
+    /// Get the element at position `index` of a list, or `None` if the
+    /// expression is not a list or the index is out of bounds.
+    pub fn get_list_element(&self, index: usize, state: &State) -> Option<AtomView<'a, P>> {
+        match self {
+            AtomView::Fun(f) if state.is_list(f.get_name()) => f.iter().nth(index),
+            _ => None,
+        }
+    }
+
+    /// Apply `map` to every element of a list and write the list of results in `out`.
+    pub fn map_list(
+        &self,
+        mut map: impl FnMut(AtomView<'a, P>, &mut OwnedAtom<P>),
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) -> Result<(), String> {
+        let AtomView::Fun(f) = self else {
+            return Err("Expected a list".to_owned());
+        };
+        if !state.is_list(f.get_name()) {
+            return Err("Expected a list".to_owned());
+        }
+
+        let mut results = Vec::with_capacity(f.get_nargs());
+        for e in f.iter() {
+            let mut r = workspace.new_atom();
+            map(e, r.get_mut());
+            results.push(r);
+        }
+
+        let views: Vec<_> = results.iter().map(|r| r.get().to_view()).collect();
+        build_list(f.get_name(), &views, workspace, state, out)
+    }
+
+    /// Apply `zip` to the pairs of elements at the same position in two lists of equal
+    /// length and write the list of results in `out`, which has the name of the first list.
+    pub fn zip_lists<'b>(
+        &self,
+        other: AtomView<'b, P>,
+        mut zip: impl FnMut(AtomView<'a, P>, AtomView<'b, P>, &mut OwnedAtom<P>),
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) -> Result<(), String> {
+        let (AtomView::Fun(f1), AtomView::Fun(f2)) = (self, other) else {
+            return Err("Expected two lists".to_owned());
+        };
+        if !state.is_list(f1.get_name()) || !state.is_list(f2.get_name()) {
+            return Err("Expected two lists".to_owned());
+        }
+        if f1.get_nargs() != f2.get_nargs() {
+            return Err(format!(
+                "Cannot zip lists of length {} and {}",
+                f1.get_nargs(),
+                f2.get_nargs()
+            ));
+        }
+
+        let mut results = Vec::with_capacity(f1.get_nargs());
+        for (e1, e2) in f1.iter().zip(f2.iter()) {
+            let mut r = workspace.new_atom();
+            zip(e1, e2, r.get_mut());
+            results.push(r);
+        }
+
+        let views: Vec<_> = results.iter().map(|r| r.get().to_view()).collect();
+        build_list(f1.get_name(), &views, workspace, state, out)
+    }
+}
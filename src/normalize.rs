@@ -3,17 +3,23 @@ use std::cmp::Ordering;
 use smallvec::SmallVec;
 
 use crate::{
+    cancel::{checkpoint, uncancellable, CancellationToken, Cancelled},
+    instrumentation::instrument_scope,
+    progress,
     representations::{
         number::{BorrowedNumber, Number},
         Add, Atom, AtomView, Fun, ListSlice, Mul, Num, OwnedAdd, OwnedAtom, OwnedFun, OwnedMul,
         OwnedNum, OwnedPow, OwnedVar, Pow, Var,
     },
-    state::{BufferHandle, ResettableBuffer, State, Workspace},
+    state::{BufferHandle, InverseDomain, ResettableBuffer, State, Workspace},
 };
 
+/// The number of terms of a sum between two progress reports during normalization.
+const PROGRESS_INTERVAL: usize = 1 << 12;
+
 impl<'a, P: Atom> AtomView<'a, P> {
     /// Compare two atoms.
-    fn cmp(&self, other: &AtomView<'_, P>) -> Ordering {
+    pub(crate) fn cmp(&self, other: &AtomView<'_, P>) -> Ordering {
         match (&self, other) {
             (Self::Num(n1), AtomView::Num(n2)) => n1.get_number_view().cmp(&n2.get_number_view()),
             (Self::Num(_), _) => Ordering::Greater,
@@ -92,6 +98,102 @@ impl<'a, P: Atom> AtomView<'a, P> {
         }
     }
 
+    /// Check if the atom contains a non-commutative function
+    /// that is not nested in the arguments of another function.
+    pub fn is_noncommutative(&self, state: &State) -> bool {
+        match self {
+            Self::Num(_) | Self::Var(_) => false,
+            Self::Fun(f) => state.is_noncommutative(f.get_name()),
+            Self::Pow(p) => p.get_base().is_noncommutative(state),
+            Self::Mul(m) => m.iter().any(|a| a.is_noncommutative(state)),
+            Self::Add(a) => a.iter().any(|a| a.is_noncommutative(state)),
+        }
+    }
+
+    /// If the atom is a composite `f(g(x))` of a function and its declared inverse
+    /// that simplifies to `x`, return `x`.
+    fn get_inverse_composite_argument(&self, state: &State) -> Option<AtomView<'a, P>> {
+        let AtomView::Fun(outer) = self else {
+            return None;
+        };
+        if outer.get_nargs() != 1 {
+            return None;
+        }
+        let AtomView::Fun(inner) = outer.iter().next().unwrap() else {
+            return None;
+        };
+        if inner.get_nargs() != 1 {
+            return None;
+        }
+        let arg = inner.iter().next().unwrap();
+
+        if let Some((inv, _)) = state.get_inverse_function(outer.get_name()) {
+            if inv == inner.get_name() {
+                return Some(arg);
+            }
+        }
+
+        let (inv, domain) = state.get_inverse_function(inner.get_name())?;
+        if inv != outer.get_name() {
+            return None;
+        }
+
+        match domain {
+            InverseDomain::All => Some(arg),
+            InverseDomain::Real if arg.is_real(state) => Some(arg),
+            InverseDomain::Positive if arg.is_positive(state) => Some(arg),
+            _ => None,
+        }
+    }
+
+    /// If the atom is a conditional `f(c, a, b)` whose condition has a known sign
+    /// or whose branches are equal, return the branch it equals.
+    fn get_conditional_branch(&self, state: &State) -> Option<AtomView<'a, P>> {
+        let AtomView::Fun(f) = self else {
+            return None;
+        };
+        if f.get_nargs() != 3 || !state.is_conditional(f.get_name()) {
+            return None;
+        }
+
+        let mut args = f.iter();
+        let (cond, a, b) = (args.next()?, args.next()?, args.next()?);
+        if cond.is_positive(state) || a == b {
+            Some(a)
+        } else if cond.is_nonpositive(state) {
+            Some(b)
+        } else {
+            None
+        }
+    }
+
+    /// Compare factors in a term, taking non-commutative factors into account.
+    /// Non-commutative factors are placed after the commutative factors and before
+    /// the coefficient, and they compare as equal so that a stable sort preserves their order.
+    fn cmp_factors_noncommutative(&self, other: &AtomView<'_, P>, state: &State) -> Ordering {
+        match (
+            self.is_noncommutative(state),
+            other.is_noncommutative(state),
+        ) {
+            (true, true) => Ordering::Equal,
+            (true, false) => {
+                if let AtomView::Num(_) = other {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            }
+            (false, true) => {
+                if let Self::Num(_) = self {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                }
+            }
+            (false, false) => self.cmp_factors(other),
+        }
+    }
+
     /// Compare factors in a term. `x` and `x^2` are placed next to each other by sorting a power based on the base only.
     fn cmp_factors(&self, other: &AtomView<'_, P>) -> Ordering {
         match (&self, other) {
@@ -290,13 +392,14 @@ impl<P: Atom> OwnedAtom<P> {
                     }
 
                     let AtomView::Num(n) = &exp1 else {
-                        unimplemented!("No support for non-numerical powers yet");
+                        // symbolic powers are kept as they are
+                        return false;
                     };
                     new_exp.set_from_view(n);
                 }
 
                 let AtomView::Num(n2) = &exp2 else {
-                    unimplemented!("No support for non-numerical powers yet")
+                    return false;
                 };
                 new_exp.add(n2, state);
 
@@ -322,7 +425,7 @@ impl<P: Atom> OwnedAtom<P> {
                 return false;
             }
             let AtomView::Num(n) = &exp else {
-                unimplemented!("No support for non-numerical powers yet")
+                return false;
             };
             let num = helper.transform_to_num();
 
@@ -421,6 +524,25 @@ impl<P: Atom> OwnedAtom<P> {
                         num.add(&BorrowedNumber::Natural(1, 1), state)
                     };
 
+                    if matches!(new_coeff, Number::Natural(1, 1)) {
+                        // the coefficients add up to one, so drop the coefficient
+                        if non_coeff1.len() == 1 {
+                            helper.from_view(&non_coeff1.get(0));
+                        } else {
+                            let mul = helper.transform_to_mul();
+                            for a in non_coeff1.iter() {
+                                mul.extend(a);
+                            }
+                        }
+
+                        drop(non_coeff1);
+                        drop(non_coeff2);
+                        drop(slice2);
+                        drop(slice);
+                        std::mem::swap(self, helper);
+                        return true;
+                    }
+
                     // help the borrow checker by dropping all references
                     drop(non_coeff1);
                     drop(non_coeff2);
@@ -550,11 +672,45 @@ impl<'a, P: Atom> AtomView<'a, P> {
 
     /// Normalize an atom.
     pub fn normalize(&self, workspace: &Workspace<P>, state: &State, out: &mut OwnedAtom<P>) {
+        uncancellable(self.normalize_impl(workspace, state, out, None))
+    }
+
+    /// Normalize an atom with [`AtomView::normalize`], checking `token` for cancellation
+    /// before every subexpression that is normalized. The content of `out` is unspecified
+    /// when the normalization is cancelled.
+    pub fn try_normalize(
+        &self,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+        token: &CancellationToken,
+    ) -> Result<(), Cancelled> {
+        self.normalize_impl(workspace, state, out, Some(token))
+    }
+
+    fn normalize_impl(
+        &self,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+        token: Option<&CancellationToken>,
+    ) -> Result<(), Cancelled> {
         if !self.is_dirty() {
             out.from_view(self);
-            return;
+            return Ok(());
         }
 
+        checkpoint(token, "normalize")?;
+
+        instrument_scope!(
+            "normalize",
+            match self {
+                AtomView::Mul(m) => m.get_nargs(),
+                AtomView::Add(a) => a.get_nargs(),
+                _ => 1,
+            }
+        );
+
         match self {
             Self::Mul(t) => {
                 let mut atom_test_buf: SmallVec<[BufferHandle<OwnedAtom<P>>; 20]> = SmallVec::new();
@@ -564,7 +720,7 @@ impl<'a, P: Atom> AtomView<'a, P> {
                     let new_at = handle.get_mut();
 
                     if a.is_dirty() {
-                        a.normalize(workspace, state, new_at);
+                        a.normalize_impl(workspace, state, new_at, token)?;
                     } else {
                         new_at.from_view(&a);
                     }
@@ -595,7 +751,11 @@ impl<'a, P: Atom> AtomView<'a, P> {
                     }
                 }
 
-                atom_test_buf.sort_by(|a, b| a.get().to_view().cmp_factors(&b.get().to_view()));
+                atom_test_buf.sort_by(|a, b| {
+                    a.get()
+                        .to_view()
+                        .cmp_factors_noncommutative(&b.get().to_view(), state)
+                });
 
                 if !atom_test_buf.is_empty() {
                     let out_mul = out.transform_to_mul();
@@ -628,15 +788,36 @@ impl<'a, P: Atom> AtomView<'a, P> {
                         }
                     }
 
-                    if cur_len == 0 {
+                    let last_is_one =
+                        matches!(last_buf.get().to_view(), AtomView::Num(n) if n.is_one());
+                    let last_is_zero =
+                        matches!(last_buf.get().to_view(), AtomView::Num(n) if n.is_zero());
+                    if last_is_zero {
+                        // the coefficient is sorted last
+                        let on = out.transform_to_num();
+                        on.set_from_number(Number::Natural(0, 1));
+                    } else if cur_len == 0 {
                         out.from_view(&last_buf.get().to_view());
-                    } else {
+                    } else if !last_is_one {
                         out_mul.extend(last_buf.get().to_view());
+                    } else if cur_len == 1 {
+                        // the numbers multiplied to one, leaving a single factor
+                        let factor = out_mul.to_mul_view().iter().next().unwrap();
+                        last_buf.get_mut().from_view(&factor);
+                        out.from_view(&last_buf.get().to_view());
                     }
                 } else {
                     let on = out.transform_to_num();
                     on.set_from_number(Number::Natural(1, 1));
                 }
+
+                let mut contracted = workspace.new_atom();
+                if out
+                    .to_view()
+                    .contract_indices(workspace, state, contracted.get_mut())
+                {
+                    out.from_view(&contracted.get().to_view());
+                }
             }
             Self::Num(n) => {
                 let normalized_num = n.get_number_view().normalize();
@@ -648,20 +829,31 @@ impl<'a, P: Atom> AtomView<'a, P> {
                 vv.set_from_view(v);
             }
             Self::Fun(f) => {
-                let out = out.transform_to_fun();
-                out.set_from_name(f.get_name());
+                let out_f = out.transform_to_fun();
+                out_f.set_from_name(f.get_name());
 
                 let mut handle = workspace.new_atom();
                 let new_at = handle.get_mut();
                 for a in f.iter() {
                     if a.is_dirty() {
                         new_at.reset(); // TODO: needed?
-                        a.normalize(workspace, state, new_at);
-                        out.add_arg(new_at.to_view());
+                        a.normalize_impl(workspace, state, new_at, token)?;
+                        out_f.add_arg(new_at.to_view());
                     } else {
-                        out.add_arg(a);
+                        out_f.add_arg(a);
                     }
                 }
+
+                let view = out.to_view();
+                if let Some(arg) = view
+                    .get_inverse_composite_argument(state)
+                    .or_else(|| view.get_conditional_branch(state))
+                {
+                    handle.get_mut().from_view(&arg);
+                    out.from_view(&handle.get().to_view());
+                } else if view.simplify_index_function(workspace, state, handle.get_mut()) {
+                    out.from_view(&handle.get().to_view());
+                }
             }
             Self::Pow(p) => {
                 let (base, exp) = p.get_base_exp();
@@ -670,14 +862,14 @@ impl<'a, P: Atom> AtomView<'a, P> {
                 let mut exp_handle = workspace.new_atom();
 
                 if base.is_dirty() {
-                    base.normalize(workspace, state, base_handle.get_mut());
+                    base.normalize_impl(workspace, state, base_handle.get_mut(), token)?;
                 } else {
                     // TODO: prevent copy
                     base_handle.get_mut().from_view(&base);
                 };
 
                 if exp.is_dirty() {
-                    exp.normalize(workspace, state, exp_handle.get_mut());
+                    exp.normalize_impl(workspace, state, exp_handle.get_mut(), token)?;
                 } else {
                     // TODO: prevent copy
                     exp_handle.get_mut().from_view(&exp);
@@ -710,28 +902,65 @@ impl<'a, P: Atom> AtomView<'a, P> {
 
                             let ne = exp_handle.get_mut().transform_to_num();
                             ne.set_from_number(new_exp_num);
-                        } else if let AtomView::Pow(p_base) = base_handle.get().to_view() {
-                            // simplify x^2^3
-                            let (p_base_base, p_base_exp) = p_base.get_base_exp();
-                            if let AtomView::Num(n) = p_base_exp {
-                                let new_exp = n.get_number_view().mul(&e.get_number_view(), state);
-
-                                if let Number::Natural(1, 1) = &new_exp {
-                                    out.from_view(&p_base_base);
-                                    break 'pow_simplify;
+                        } else if let AtomView::Mul(m) = base_handle.get().to_view() {
+                            // turn (x*y)^2 into x^2*y^2, which is only valid for integer powers
+                            // of commuting factors
+                            if matches!(e.get_number_view(), BorrowedNumber::Natural(_, 1))
+                                && !m.iter().any(|a| a.is_noncommutative(state))
+                            {
+                                let mut mul_h = workspace.new_atom();
+                                let mul = mul_h.get_mut().transform_to_mul();
+
+                                for arg in m.iter() {
+                                    let mut pow_h = workspace.new_atom();
+                                    let pow = pow_h.get_mut().transform_to_pow();
+                                    pow.set_from_base_and_exp(arg, exp_handle.get().to_view());
+                                    pow.set_dirty(true);
+                                    mul.extend(pow_h.get().to_view());
                                 }
+                                mul.set_dirty(true);
 
-                                let ne = exp_handle.get_mut().transform_to_num();
-                                ne.set_from_number(new_exp);
-
-                                let out = out.transform_to_pow();
-                                out.set_from_base_and_exp(p_base_base, exp_handle.get().to_view());
-
+                                mul_h
+                                    .get()
+                                    .to_view()
+                                    .normalize_impl(workspace, state, out, token)?;
                                 break 'pow_simplify;
                             }
-                        } else if let AtomView::Mul(_) = base_handle.get().to_view() {
-                            // TODO: turn (x*y)^2 into x^2*y^2?
-                            // for now, expand() needs to be used
+                        }
+                    }
+
+                    if let AtomView::Pow(p_base) = base_handle.get().to_view() {
+                        // simplify (x^a)^b to x^(a*b), which is only valid
+                        // for integer b or for positive x and real a
+                        let (p_base_base, p_base_exp) = p_base.get_base_exp();
+                        let exp = exp_handle.get().to_view();
+                        if exp.is_integer(state)
+                            || p_base_base.is_positive(state) && p_base_exp.is_real(state)
+                        {
+                            let mut mul_h = workspace.new_atom();
+                            let mul = mul_h.get_mut().transform_to_mul();
+                            mul.extend(p_base_exp);
+                            mul.extend(exp);
+                            mul.set_dirty(true);
+
+                            let mut new_exp_h = workspace.new_atom();
+                            mul_h.get().to_view().normalize_impl(
+                                workspace,
+                                state,
+                                new_exp_h.get_mut(),
+                                token,
+                            )?;
+
+                            let mut pow_h = workspace.new_atom();
+                            let pow = pow_h.get_mut().transform_to_pow();
+                            pow.set_from_base_and_exp(p_base_base, new_exp_h.get().to_view());
+                            pow.set_dirty(true);
+
+                            pow_h
+                                .get()
+                                .to_view()
+                                .normalize_impl(workspace, state, out, token)?;
+                            break 'pow_simplify;
                         }
                     }
 
@@ -741,16 +970,30 @@ impl<'a, P: Atom> AtomView<'a, P> {
                         exp_handle.get().to_view(),
                     );
                 }
+
+                let mut contracted = workspace.new_atom();
+                if out
+                    .to_view()
+                    .contract_indices(workspace, state, contracted.get_mut())
+                {
+                    out.from_view(&contracted.get().to_view());
+                }
             }
             Self::Add(a) => {
                 let mut atom_test_buf: SmallVec<[BufferHandle<OwnedAtom<P>>; 20]> = SmallVec::new();
 
-                for a in a.iter() {
+                let nargs = a.get_nargs();
+                for (i, a) in a.iter().enumerate() {
+                    if i % PROGRESS_INTERVAL == PROGRESS_INTERVAL - 1 {
+                        progress::report("normalize", i, Some(nargs));
+                        checkpoint(token, "normalize")?;
+                    }
+
                     let mut handle = workspace.new_atom();
                     let new_at = handle.get_mut();
 
                     if a.is_dirty() {
-                        a.normalize(workspace, state, new_at);
+                        a.normalize_impl(workspace, state, new_at, token)?;
                     } else {
                         new_at.from_view(&a);
                     }
@@ -813,10 +1056,20 @@ impl<'a, P: Atom> AtomView<'a, P> {
                         }
                     }
 
+                    let last_is_zero =
+                        matches!(last_buf.get().to_view(), AtomView::Num(n) if n.is_zero());
+
                     if cur_len == 0 {
                         out.from_view(&last_buf.get().to_view());
-                    } else {
+                    } else if !last_is_zero {
                         out_add.extend(last_buf.get().to_view());
+                    } else if cur_len == 1 {
+                        // the last terms cancelled, so that a single term is left
+                        let mut single = workspace.new_atom();
+                        if let AtomView::Add(a) = out.to_view() {
+                            single.get_mut().from_view(&a.iter().next().unwrap());
+                        }
+                        out.from_view(&single.get().to_view());
                     }
                 } else {
                     let on = out.transform_to_num();
@@ -824,5 +1077,104 @@ impl<'a, P: Atom> AtomView<'a, P> {
                 }
             }
         }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cancel::{CancellationToken, Cancelled},
+        parser::parse,
+        representations::{default::DefaultRepresentation, OwnedAdd, OwnedAtom},
+        state::{ResettableBuffer, State, Workspace},
+    };
+
+    fn parse_atom(
+        input: &str,
+        state: &mut State,
+        workspace: &Workspace<DefaultRepresentation>,
+    ) -> OwnedAtom<DefaultRepresentation> {
+        parse(input).unwrap().to_atom(state, workspace).unwrap()
+    }
+
+    #[test]
+    fn test_cancel_last_terms() {
+        let mut state = State::new();
+        let workspace = Workspace::new();
+
+        for (input, expected) in [
+            ("a+b+c-c", "a+b"),
+            ("a+b-b", "a"),
+            ("a+b+2-2", "a+b"),
+            ("a+2-2", "a"),
+        ] {
+            let a = parse_atom(input, &mut state, &workspace);
+            let b = parse_atom(expected, &mut state, &workspace);
+            assert_eq!(a.to_view(), b.to_view(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_drop_coefficient_one() {
+        let mut state = State::new();
+        let workspace = Workspace::new();
+
+        for (input, expected) in [
+            ("2*x-x", "x"),
+            ("2*x*y-x*y", "x*y"),
+            ("x*2*(1/2)", "x"),
+            ("x*y*3*(1/3)", "x*y"),
+        ] {
+            let a = parse_atom(input, &mut state, &workspace);
+            let b = parse_atom(expected, &mut state, &workspace);
+            assert_eq!(a.to_view(), b.to_view(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_zero_coefficient() {
+        let mut state = State::new();
+        let workspace = Workspace::new();
+
+        for input in ["x*0", "x*y*0", "f(x)*x^2*0", "x*2*0"] {
+            let a = parse_atom(input, &mut state, &workspace);
+            let b = parse_atom("0", &mut state, &workspace);
+            assert_eq!(a.to_view(), b.to_view(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_try_normalize_cancelled() {
+        let mut state = State::new();
+        let workspace = Workspace::new();
+
+        // the unnormalized sum x+x
+        let x = parse_atom("x", &mut state, &workspace);
+        let mut sum = OwnedAtom::<DefaultRepresentation>::new();
+        let add = sum.transform_to_add();
+        add.extend(x.to_view());
+        add.extend(x.to_view());
+        add.set_dirty(true);
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let mut out = OwnedAtom::new();
+        assert!(matches!(
+            sum.to_view()
+                .try_normalize(&workspace, &state, &mut out, &token),
+            Err(Cancelled {
+                location: "normalize",
+                ..
+            })
+        ));
+
+        let token = CancellationToken::new();
+        sum.to_view()
+            .try_normalize(&workspace, &state, &mut out, &token)
+            .unwrap();
+        let expected = parse_atom("2*x", &mut state, &workspace);
+        assert_eq!(out.to_view(), expected.to_view());
     }
 }
@@ -9,11 +9,18 @@ use smartstring::{LazyCompact, SmartString};
 use crate::{
     poly::{polynomial::MultivariatePolynomial, Exponent},
     representations::{
+        default::DefaultRepresentation,
         number::{ConvertToRing, Number},
         tree::AtomTree,
         Atom, Identifier, OwnedAtom,
     },
-    rings::Ring,
+    rings::{
+        algebraic_number::AlgebraicNumber,
+        finite_field::{FiniteField, FiniteFieldCore},
+        integer::IntegerRing,
+        rational::RationalField,
+        Ring,
+    },
     state::{State, Workspace},
 };
 
@@ -237,6 +244,7 @@ impl Token {
                 }
                 Ok(AtomTree::Fn(state.get_or_insert_var(name), atom_args))
             }
+            Self::RationalPolynomial(c) => Ok(AtomTree::Num(parse_coefficient(c, state)?)),
             x => Err(format!("Unexpected token {}", x)),
         }
     }
@@ -425,7 +433,11 @@ pub fn parse(input: &str) -> Result<Token, String> {
                         stack.push(Token::OpenParenthesis)
                     }
                 }
-                b')' => stack.push(Token::CloseParenthesis),
+                b')' => match stack.last_mut() {
+                    // close a function without arguments
+                    Some(Token::Fn(mr, args)) if *mr && args.len() == 1 => *mr = false,
+                    _ => stack.push(Token::CloseParenthesis),
+                },
                 b'/' => {
                     if matches!(
                         stack.last().unwrap(),
@@ -549,6 +561,81 @@ pub fn parse(input: &str) -> Result<Token, String> {
     }
 }
 
+/// Parse a coefficient that is written between square brackets: a finite field
+/// element `n%p`, an algebraic number `a%m` where the number `a` and the minimal
+/// polynomial `m` are polynomials in `x0`, or a rational polynomial `num` or `num,den`,
+/// optionally followed by the order of its variables, as in `x+y,z;z,x,y`.
+fn parse_coefficient(coeff: &str, state: &mut State) -> Result<Number, String> {
+    let workspace = Workspace::<DefaultRepresentation>::new();
+
+    if let Some((a, m)) = coeff.split_once('%') {
+        if let Ok(p) = m.trim().parse::<u64>() {
+            let n = match Integer::parse(a.trim()) {
+                Ok(n) => n.complete(),
+                Err(e) => return Err(format!("Could not parse number: {}", e)),
+            };
+            let field = FiniteField::<u64>::new(p);
+            let n = n.modulo(&Integer::from(p)).to_u64().unwrap();
+            let index = state.get_or_insert_finite_field(field);
+            return Ok(Number::FiniteField(field.to_element(n), index));
+        }
+
+        // parse the polynomials in x0 without registering x0
+        let mut poly_state = State::new();
+        let x0 = poly_state.get_or_insert_var("x0");
+        let mut polys = vec![];
+        for p in [a, m] {
+            let atom = parse(p)?.to_atom(&mut poly_state, &workspace)?;
+            let mut norm = workspace.new_atom();
+            atom.to_view()
+                .normalize(&workspace, &poly_state, norm.get_mut());
+            let mut poly = norm
+                .get()
+                .to_view()
+                .to_polynomial::<_, u16>(RationalField::new(), Some(&[x0]))?;
+            poly.var_map = None;
+            polys.push(poly);
+        }
+
+        let index = state.get_or_insert_algebraic_extension(&polys[1]);
+        let poly = polys.swap_remove(0);
+        return Ok(Number::from_algebraic(AlgebraicNumber { poly }, index));
+    }
+
+    let (fraction, var_map) = match coeff.split_once(';') {
+        Some((fraction, vars)) => (
+            fraction,
+            Some(
+                vars.split(',')
+                    .map(|v| state.get_or_insert_var(v.trim()))
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+        None => (coeff, None),
+    };
+
+    let expr = match fraction.split_once(',') {
+        Some((num, den)) => format!("({})/({})", num, den),
+        None => fraction.to_string(),
+    };
+
+    let atom = parse(&expr)?.to_atom(state, &workspace)?;
+    let mut norm = workspace.new_atom();
+    atom.to_view().normalize(&workspace, state, norm.get_mut());
+    let r = norm
+        .get()
+        .to_view()
+        .to_rational_polynomial(
+            &workspace,
+            state,
+            RationalField::new(),
+            IntegerRing::new(),
+            var_map.as_deref(),
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(Number::RationalPolynomial(r))
+}
+
 /// A special routine that can parse a polynomial written in expanded form,
 /// where the coefficient comes first.
 pub fn parse_polynomial<'a, R: Ring + ConvertToRing, E: Exponent>(
@@ -0,0 +1,5 @@
+pub mod color;
+pub mod gamma;
+pub mod graph;
+pub mod kinematics;
+pub mod sector;
@@ -0,0 +1,631 @@
+use crate::{
+    poly::polynomial::MultivariatePolynomial,
+    representations::{
+        number::{BorrowedNumber, Number},
+        Add, Atom, AtomView, Fun, Identifier, Mul, Num, OwnedAdd, OwnedAtom, OwnedFun, OwnedMul,
+        OwnedNum, OwnedPow, OwnedVar, Pow, Var,
+    },
+    rings::{
+        rational::{Rational, RationalField},
+        Ring,
+    },
+    state::{State, Workspace},
+};
+
+/// A product of generators `(T^a1*...*T^an)_ij`.
+#[derive(Clone, Debug)]
+struct Chain {
+    generators: Vec<usize>,
+    from: usize,
+    to: usize,
+}
+
+/// A term `c * N^n_power * T(..)*...*tr(..)*...*f(..)*...*dA(..)*...`, where the
+/// coefficient `c` is a polynomial in `N`, possibly multiplied by the imaginary unit.
+/// Indices are positions in the list of identifiers of the expression.
+#[derive(Clone)]
+struct ColorTerm {
+    coeff: MultivariatePolynomial<RationalField, u16>,
+    n_power: i32,
+    imaginary: bool,
+    chains: Vec<Chain>,
+    traces: Vec<Vec<usize>>,
+    structure_constants: Vec<[usize; 3]>,
+    adjoint_deltas: Vec<[usize; 2]>,
+}
+
+impl ColorTerm {
+    fn mul_rational(&mut self, num: i64, den: i64) {
+        self.coeff = self.coeff.clone().mul_coeff(Rational::Natural(num, den));
+    }
+
+    fn mul_imaginary(&mut self) {
+        if self.imaginary {
+            self.mul_rational(-1, 1);
+        }
+        self.imaginary = !self.imaginary;
+    }
+
+    /// Count the number of times the adjoint index `a` appears.
+    fn count_adjoint(&self, a: usize) -> usize {
+        self.chains
+            .iter()
+            .flat_map(|c| c.generators.iter())
+            .chain(self.traces.iter().flatten())
+            .chain(self.structure_constants.iter().flatten())
+            .chain(self.adjoint_deltas.iter().flatten())
+            .filter(|x| **x == a)
+            .count()
+    }
+}
+
+/// The algebra of the generators of SU(N) in the fundamental representation,
+/// acting on expressions built from
+/// - `T(a1,...,an,i,j)`: the product of generators `(T^a1*...*T^an)_ij`,
+/// - `tr(a1,...,an)`: the trace of a product of generators,
+/// - `f(a,b,c)`: the structure constants, defined by `[T^a,T^b] = i*f(a,b,c)*T^c`,
+/// - `dF(i,j)` and `dA(a,b)`: the delta in the fundamental and adjoint representation,
+/// - `N`: the number of colors, and `i`: the imaginary unit.
+///
+/// The generators are normalized as `tr(a,b) = dA(a,b)/2`. Indices that appear twice
+/// in a term are summed over.
+pub struct ColorAlgebra {
+    pub generator: Identifier,
+    pub trace: Identifier,
+    pub structure_constant: Identifier,
+    pub fundamental_delta: Identifier,
+    pub adjoint_delta: Identifier,
+    pub n: Identifier,
+    pub imaginary_unit: Identifier,
+}
+
+impl ColorAlgebra {
+    /// Create the algebra, registering the symbols in `state`.
+    pub fn new(state: &mut State) -> Self {
+        ColorAlgebra {
+            generator: state.get_or_insert_var("T"),
+            trace: state.get_or_insert_var("tr"),
+            structure_constant: state.get_or_insert_var("f"),
+            fundamental_delta: state.get_or_insert_var("dF"),
+            adjoint_delta: state.get_or_insert_var("dA"),
+            n: state.get_or_insert_var("N"),
+            imaginary_unit: state.get_or_insert_var("i"),
+        }
+    }
+
+    /// Simplify the color structure of every term of `expr`, by contracting all
+    /// repeated indices using the Fierz identity
+    /// `T(a,i,j)*T(a,k,l) = 1/2*(dF(i,l)*dF(k,j) - 1/N*dF(i,j)*dF(k,l))`.
+    /// Structure constants with a repeated index are written as traces.
+    /// Fully contracted color factors become rational functions of `N`.
+    /// The expression should be expanded.
+    pub fn simplify<P: Atom>(
+        &self,
+        expr: AtomView<P>,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let mut add_h = workspace.new_atom();
+        let add = add_h.get_mut().transform_to_add();
+
+        let mut terms = vec![];
+        if let AtomView::Add(a) = expr {
+            terms.extend(a.iter());
+        } else {
+            terms.push(expr);
+        }
+
+        for term in terms {
+            let mut t = ColorTerm {
+                coeff: MultivariatePolynomial::new(1, RationalField::new(), None, None)
+                    .new_from_constant(Rational::Natural(1, 1)),
+                n_power: 0,
+                imaginary: false,
+                chains: vec![],
+                traces: vec![],
+                structure_constants: vec![],
+                adjoint_deltas: vec![],
+            };
+            let mut indices = vec![];
+            let mut rest = vec![];
+            if let AtomView::Mul(m) = term {
+                for factor in m.iter() {
+                    self.parse_factor(factor, &mut t, &mut indices, &mut rest);
+                }
+            } else {
+                self.parse_factor(term, &mut t, &mut indices, &mut rest);
+            }
+
+            for r in self.simplify_term(t) {
+                self.add_term(&r, &indices, &rest, workspace, add);
+            }
+        }
+
+        if add.to_add_view().get_nargs() == 0 {
+            out.transform_to_num()
+                .set_from_number(Number::Natural(0, 1));
+            return;
+        }
+
+        add.set_dirty(true);
+        add_h.get().to_view().normalize(workspace, state, out);
+    }
+
+    /// Get the arguments of a function as indices, if they are all variables.
+    fn index_args<'a, P: Atom>(f: &P::F<'a>, indices: &mut Vec<Identifier>) -> Option<Vec<usize>> {
+        let args = f
+            .iter()
+            .map(|a| match a {
+                AtomView::Var(v) => Some(v.get_name()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(
+            args.into_iter()
+                .map(|id| match indices.iter().position(|x| *x == id) {
+                    Some(p) => p,
+                    None => {
+                        indices.push(id);
+                        indices.len() - 1
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// Add the factor to the term if it is part of the color algebra, or else to `rest`.
+    fn parse_factor<'a, P: Atom>(
+        &self,
+        factor: AtomView<'a, P>,
+        t: &mut ColorTerm,
+        indices: &mut Vec<Identifier>,
+        rest: &mut Vec<AtomView<'a, P>>,
+    ) {
+        match factor {
+            AtomView::Fun(f) => {
+                let name = f.get_name();
+                if [
+                    self.generator,
+                    self.trace,
+                    self.structure_constant,
+                    self.fundamental_delta,
+                    self.adjoint_delta,
+                ]
+                .contains(&name)
+                {
+                    if let Some(args) = Self::index_args::<P>(&f, indices) {
+                        let n = args.len();
+                        if name == self.generator && n >= 2 {
+                            t.chains.push(Chain {
+                                generators: args[..n - 2].to_vec(),
+                                from: args[n - 2],
+                                to: args[n - 1],
+                            });
+                            return;
+                        }
+                        if name == self.fundamental_delta && n == 2 {
+                            t.chains.push(Chain {
+                                generators: vec![],
+                                from: args[0],
+                                to: args[1],
+                            });
+                            return;
+                        }
+                        if name == self.trace {
+                            t.traces.push(args);
+                            return;
+                        }
+                        if name == self.structure_constant && n == 3 {
+                            t.structure_constants.push([args[0], args[1], args[2]]);
+                            return;
+                        }
+                        if name == self.adjoint_delta && n == 2 {
+                            t.adjoint_deltas.push([args[0], args[1]]);
+                            return;
+                        }
+                    }
+                }
+
+                rest.push(factor);
+            }
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                if let (AtomView::Fun(_), AtomView::Num(n)) = (base, exp) {
+                    if let BorrowedNumber::Natural(n, 1) = n.get_number_view() {
+                        if n > 0 {
+                            let mut base_rest = vec![];
+                            for _ in 0..n {
+                                self.parse_factor(base, t, indices, &mut base_rest);
+                            }
+                            if base_rest.is_empty() {
+                                return;
+                            }
+                        }
+                    }
+                }
+                rest.push(factor);
+            }
+            _ => rest.push(factor),
+        }
+    }
+
+    /// Apply all simplifications to the term until no more rules apply.
+    fn simplify_term(&self, t: ColorTerm) -> Vec<ColorTerm> {
+        let mut todo = vec![t];
+        let mut done = vec![];
+        while let Some(mut t) = todo.pop() {
+            if t.coeff.is_zero() || !self.contract_deltas(&mut t) {
+                continue;
+            }
+
+            // f(a,b,c) = -2*i*(tr(a,b,c) - tr(c,b,a))
+            if let Some(pos) = t
+                .structure_constants
+                .iter()
+                .position(|f| f.iter().any(|a| t.count_adjoint(*a) > 1))
+            {
+                let [a, b, c] = t.structure_constants.swap_remove(pos);
+                let mut t1 = t.clone();
+                t1.traces.push(vec![a, b, c]);
+                t1.mul_imaginary();
+                t1.mul_rational(-2, 1);
+
+                let mut t2 = t;
+                t2.traces.push(vec![c, b, a]);
+                t2.mul_imaginary();
+                t2.mul_rational(2, 1);
+
+                todo.push(t1);
+                todo.push(t2);
+                continue;
+            }
+
+            match self.fierz(&t) {
+                Some(new_terms) => todo.extend(new_terms),
+                None => done.push(t),
+            }
+        }
+        done
+    }
+
+    /// Contract deltas, merge chains of generators with a common fundamental index
+    /// and evaluate simple traces. Returns `false` if the term vanishes.
+    fn contract_deltas(&self, t: &mut ColorTerm) -> bool {
+        'contract: loop {
+            for k in 0..t.adjoint_deltas.len() {
+                let [a, b] = t.adjoint_deltas[k];
+                if a == b {
+                    t.adjoint_deltas.swap_remove(k);
+                    // dA(a,a) = N^2-1
+                    let n2 = t.coeff.new_from_monomial(Rational::Natural(1, 1), vec![2]);
+                    t.coeff = &t.coeff * &(n2 - t.coeff.new_from_constant(Rational::Natural(1, 1)));
+                    continue 'contract;
+                }
+
+                for (x, y) in [(b, a), (a, b)] {
+                    let found = t
+                        .chains
+                        .iter_mut()
+                        .flat_map(|c| c.generators.iter_mut())
+                        .chain(t.traces.iter_mut().flatten())
+                        .chain(t.structure_constants.iter_mut().flatten())
+                        .chain(
+                            t.adjoint_deltas
+                                .iter_mut()
+                                .enumerate()
+                                .filter(|(l, _)| *l != k)
+                                .flat_map(|(_, d)| d.iter_mut()),
+                        )
+                        .find(|i| **i == x);
+
+                    if let Some(i) = found {
+                        *i = y;
+                        t.adjoint_deltas.swap_remove(k);
+                        continue 'contract;
+                    }
+                }
+            }
+
+            for k in 0..t.chains.len() {
+                if t.chains[k].from == t.chains[k].to {
+                    let c = t.chains.swap_remove(k);
+                    t.traces.push(c.generators);
+                    continue 'contract;
+                }
+
+                if let Some(l) = t.chains.iter().position(|c| c.from == t.chains[k].to) {
+                    let next = t.chains.swap_remove(l);
+                    let k = if k == t.chains.len() { l } else { k };
+                    t.chains[k].generators.extend(next.generators);
+                    t.chains[k].to = next.to;
+                    continue 'contract;
+                }
+            }
+
+            break;
+        }
+
+        // tr() = N, tr(a) = 0 and tr(a,b) = dA(a,b)/2
+        let mut vanishes = false;
+        let mut n_power = 0;
+        let mut deltas = vec![];
+        t.traces.retain(|tr| match tr.len() {
+            0 => {
+                n_power += 1;
+                false
+            }
+            1 => {
+                vanishes = true;
+                false
+            }
+            2 => {
+                deltas.push([tr[0], tr[1]]);
+                false
+            }
+            _ => true,
+        });
+        t.n_power += n_power;
+
+        if !deltas.is_empty() {
+            t.mul_rational(1, 1 << deltas.len());
+            t.adjoint_deltas.extend(deltas);
+            return self.contract_deltas(t);
+        }
+
+        !vanishes
+            && t.structure_constants
+                .iter()
+                .all(|[a, b, c]| a != b && b != c && a != c)
+    }
+
+    /// Apply the Fierz identity to an adjoint index that appears twice,
+    /// or return `None` if there are no such indices.
+    fn fierz(&self, t: &ColorTerm) -> Option<Vec<ColorTerm>> {
+        // the containers of generators, where the traces are placed after the chains
+        let nchains = t.chains.len();
+        let generators = |i: usize| {
+            if i < nchains {
+                &t.chains[i].generators
+            } else {
+                &t.traces[i - nchains]
+            }
+        };
+
+        let mut occurrences = vec![];
+        'find: for i in 0..nchains + t.traces.len() {
+            for (p, a) in generators(i).iter().enumerate() {
+                if let Some((j, q)) = occurrences
+                    .iter()
+                    .find(|(_, _, b)| b == a)
+                    .map(|(j, q, _)| (*j, *q))
+                {
+                    occurrences = vec![(j, q, *a), (i, p, *a)];
+                    break 'find;
+                }
+                occurrences.push((i, p, *a));
+            }
+        }
+
+        if occurrences.len() != 2 || occurrences[0].2 != occurrences[1].2 {
+            return None;
+        }
+        let (i, p, _) = occurrences[0];
+        let (j, q, _) = occurrences[1];
+
+        // write the trace `tr(X, a, Y)` as `tr(a, Y, X)`
+        let rotate = |gens: &[usize], p: usize| {
+            let mut r = gens[p + 1..].to_vec();
+            r.extend_from_slice(&gens[..p]);
+            r
+        };
+
+        let mut t1 = t.clone();
+        let mut t2 = t.clone();
+        t1.mul_rational(1, 2);
+        t2.mul_rational(-1, 2);
+        t2.n_power -= 1;
+
+        if i == j {
+            let gens = generators(i);
+            let (x, y, z) = (&gens[..p], &gens[p + 1..q], &gens[q + 1..]);
+            t1.traces.push(y.to_vec());
+            if i < nchains {
+                // X*T^a*Y*T^a*Z = 1/2*tr(Y)*X*Z - 1/(2N)*X*Y*Z
+                t1.chains[i].generators = [x, z].concat();
+                t2.chains[i].generators = [x, y, z].concat();
+            } else {
+                // tr(T^a*Y*T^a*Z) = 1/2*tr(Y)*tr(Z) - 1/(2N)*tr(Y*Z)
+                t1.traces[i - nchains] = [z, x].concat();
+                t2.traces[i - nchains] = [y, z, x].concat();
+            }
+        } else if j < nchains {
+            // (X*T^a*Y)_ij*(U*T^a*V)_kl = 1/2*(X*V)_il*(U*Y)_kj - 1/(2N)*(X*Y)_ij*(U*V)_kl
+            let (c1, c2) = (&t.chains[i], &t.chains[j]);
+            let (x, y) = (&c1.generators[..p], &c1.generators[p + 1..]);
+            let (u, v) = (&c2.generators[..q], &c2.generators[q + 1..]);
+
+            t1.chains[i] = Chain {
+                generators: [x, v].concat(),
+                from: c1.from,
+                to: c2.to,
+            };
+            t1.chains[j] = Chain {
+                generators: [u, y].concat(),
+                from: c2.from,
+                to: c1.to,
+            };
+            t2.chains[i].generators = [x, y].concat();
+            t2.chains[j].generators = [u, v].concat();
+        } else if i < nchains {
+            // (X*T^a*Y)_ij*tr(T^a*B) = 1/2*(X*B*Y)_ij - 1/(2N)*(X*Y)_ij*tr(B)
+            let c = &t.chains[i];
+            let (x, y) = (&c.generators[..p], &c.generators[p + 1..]);
+            let b = rotate(&t.traces[j - nchains], q);
+
+            t1.traces.swap_remove(j - nchains);
+            t1.chains[i].generators = [x, &b, y].concat();
+            t2.chains[i].generators = [x, y].concat();
+            t2.traces[j - nchains] = b;
+        } else {
+            // tr(T^a*A)*tr(T^a*B) = 1/2*tr(A*B) - 1/(2N)*tr(A)*tr(B)
+            let a = rotate(&t.traces[i - nchains], p);
+            let b = rotate(&t.traces[j - nchains], q);
+
+            t1.traces[i - nchains] = [a.as_slice(), &b].concat();
+            t1.traces.swap_remove(j - nchains);
+            t2.traces[i - nchains] = a;
+            t2.traces[j - nchains] = b;
+        }
+
+        Some(vec![t1, t2])
+    }
+
+    /// Add the term multiplied by the factors in `rest` to `add`, writing
+    /// every monomial in `N` of the coefficient as a separate term.
+    fn add_term<P: Atom>(
+        &self,
+        t: &ColorTerm,
+        indices: &[Identifier],
+        rest: &[AtomView<P>],
+        workspace: &Workspace<P>,
+        add: &mut P::OA,
+    ) {
+        let mut color_h = workspace.new_atom();
+        let sign = self.color_factors(t, indices, rest, workspace, color_h.get_mut());
+
+        for monomial in &t.coeff {
+            let mut mul_h = workspace.new_atom();
+            let mul = mul_h.get_mut().transform_to_mul();
+
+            let pow = monomial.exponents[0] as i64 + t.n_power as i64;
+            let mut n_h = workspace.new_atom();
+            n_h.get_mut().transform_to_var().set_from_id(self.n);
+            if pow == 1 {
+                mul.extend(n_h.get().to_view());
+            } else if pow != 0 {
+                let mut e_h = workspace.new_atom();
+                e_h.get_mut()
+                    .transform_to_num()
+                    .set_from_number(Number::Natural(pow, 1));
+                let mut pow_h = workspace.new_atom();
+                pow_h
+                    .get_mut()
+                    .transform_to_pow()
+                    .set_from_base_and_exp(n_h.get().to_view(), e_h.get().to_view());
+                mul.extend(pow_h.get().to_view());
+            }
+
+            let mut num_h = workspace.new_atom();
+            num_h.get_mut().transform_to_num().set_from_number(
+                RationalField::new()
+                    .mul(monomial.coefficient, &Rational::Natural(sign, 1))
+                    .into(),
+            );
+            mul.extend(num_h.get().to_view());
+            mul.extend(color_h.get().to_view());
+            mul.set_dirty(true);
+            add.extend(mul_h.get().to_view());
+        }
+    }
+
+    /// Write the color structure of the term multiplied by the factors in `rest`
+    /// as a product in `out`, returning the sign from reordering structure constants.
+    fn color_factors<P: Atom>(
+        &self,
+        t: &ColorTerm,
+        indices: &[Identifier],
+        rest: &[AtomView<P>],
+        workspace: &Workspace<P>,
+        out: &mut OwnedAtom<P>,
+    ) -> i64 {
+        let mut sign = 1;
+        let mul = out.transform_to_mul();
+
+        let mut h = workspace.new_atom();
+        if t.imaginary {
+            h.get_mut()
+                .transform_to_var()
+                .set_from_id(self.imaginary_unit);
+            mul.extend(h.get().to_view());
+        }
+
+        let mut arg_h = workspace.new_atom();
+        let mut add_fun = |name: Identifier, args: &[usize]| {
+            let f = h.get_mut().transform_to_fun();
+            f.set_from_name(name);
+            for a in args {
+                arg_h.get_mut().transform_to_var().set_from_id(indices[*a]);
+                f.add_arg(arg_h.get().to_view());
+            }
+            mul.extend(h.get().to_view());
+        };
+
+        for c in &t.chains {
+            if c.generators.is_empty() {
+                add_fun(self.fundamental_delta, &[c.from, c.to]);
+            } else {
+                add_fun(
+                    self.generator,
+                    &[c.generators.as_slice(), &[c.from, c.to]].concat(),
+                );
+            }
+        }
+
+        for tr in &t.traces {
+            // rotate the trace such that the smallest index comes first
+            let first = (0..tr.len())
+                .min_by_key(|p| {
+                    tr[*p..]
+                        .iter()
+                        .chain(&tr[..*p])
+                        .map(|a| indices[*a])
+                        .collect::<Vec<_>>()
+                })
+                .unwrap();
+            add_fun(self.trace, &[&tr[first..], &tr[..first]].concat());
+        }
+
+        for f in &t.structure_constants {
+            // sort the indices of the antisymmetric structure constant
+            let mut f = *f;
+            for i in 1..3 {
+                for j in (1..=i).rev() {
+                    if indices[f[j - 1]] <= indices[f[j]] {
+                        break;
+                    }
+                    f.swap(j - 1, j);
+                    sign = -sign;
+                }
+            }
+            add_fun(self.structure_constant, &f);
+        }
+
+        for d in &t.adjoint_deltas {
+            let mut d = *d;
+            if indices[d[0]] > indices[d[1]] {
+                d.swap(0, 1);
+            }
+            add_fun(self.adjoint_delta, &d);
+        }
+
+        for r in rest {
+            mul.extend(*r);
+        }
+
+        if t.chains.is_empty()
+            && t.traces.is_empty()
+            && t.structure_constants.is_empty()
+            && t.adjoint_deltas.is_empty()
+            && rest.is_empty()
+            && !t.imaginary
+        {
+            out.transform_to_num()
+                .set_from_number(Number::Natural(1, 1));
+        } else {
+            mul.set_dirty(true);
+        }
+        sign
+    }
+}
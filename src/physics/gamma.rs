@@ -0,0 +1,659 @@
+use crate::{
+    poly::polynomial::MultivariatePolynomial,
+    representations::{
+        number::{BorrowedNumber, Number},
+        Add, Atom, AtomView, Fun, Identifier, Mul, Num, OwnedAdd, OwnedAtom, OwnedFun, OwnedMul,
+        OwnedNum, OwnedVar, Pow, Var,
+    },
+    rings::integer::{Integer, IntegerRing},
+    state::{FunctionAttribute, State, Workspace},
+};
+
+/// An element of a product of gamma matrices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Slot {
+    Gamma(Identifier),
+    Gamma5,
+}
+
+/// A term `c * g(..)*...*eps(..)*...*gamma(..)*...`, where the coefficient `c`
+/// is a polynomial in the dimension, possibly multiplied by the imaginary unit.
+#[derive(Clone)]
+struct GammaTerm {
+    coeff: MultivariatePolynomial<IntegerRing, u16>,
+    imaginary: bool,
+    metrics: Vec<[Identifier; 2]>,
+    epsilons: Vec<[Identifier; 4]>,
+    chain: Vec<Slot>,
+}
+
+impl GammaTerm {
+    fn mul_int(&mut self, n: i64) {
+        self.coeff = self.coeff.clone().mul_coeff(Integer::Natural(n));
+    }
+
+    fn mul_imaginary(&mut self) {
+        if self.imaginary {
+            self.mul_int(-1);
+        }
+        self.imaginary = !self.imaginary;
+    }
+
+    fn gammas(&self) -> Vec<Identifier> {
+        self.chain
+            .iter()
+            .filter_map(|s| match s {
+                Slot::Gamma(i) => Some(*i),
+                Slot::Gamma5 => None,
+            })
+            .collect()
+    }
+}
+
+/// The algebra of Dirac gamma matrices, acting on expressions built from
+/// - `gamma(mu)`: a gamma matrix with Lorentz index `mu`, or a slashed vector `gamma(p)`,
+/// - `gamma5()`: the chiral gamma matrix,
+/// - `g(mu,nu)`: the metric, or the dot product `g(p,q)` of two vectors,
+/// - `eps(mu,nu,rho,sigma)`: the Levi-Civita tensor,
+/// - `i`: the imaginary unit.
+///
+/// Products of gamma matrices are non-commutative. Every symbol that is not registered
+/// as a vector with [`GammaAlgebra::add_vector`] is a Lorentz index, which is summed over
+/// when it appears twice in a term. The dimension is 4, unless a symbol for the dimension
+/// is set with [`GammaAlgebra::set_dimension`]. Traces with `gamma5` use an anticommuting
+/// `gamma5` and the four-dimensional identity `Tr(gamma(a)*gamma(b)*gamma(c)*gamma(d)*gamma5()) = -4*i*eps(a,b,c,d)`.
+pub struct GammaAlgebra {
+    pub gamma: Identifier,
+    pub gamma5: Identifier,
+    pub metric: Identifier,
+    pub levi_civita: Identifier,
+    pub imaginary_unit: Identifier,
+    dimension: Option<Identifier>,
+    vectors: Vec<Identifier>,
+}
+
+impl GammaAlgebra {
+    /// Create the algebra in 4 dimensions, registering the symbols in `state`
+    /// and marking the gamma matrices as non-commutative.
+    pub fn new(state: &mut State) -> Self {
+        let gamma = state.get_or_insert_var("gamma");
+        let gamma5 = state.get_or_insert_var("gamma5");
+        state.set_function_attributes(gamma, &[FunctionAttribute::NonCommutative]);
+        state.set_function_attributes(gamma5, &[FunctionAttribute::NonCommutative]);
+
+        GammaAlgebra {
+            gamma,
+            gamma5,
+            metric: state.get_or_insert_var("g"),
+            levi_civita: state.get_or_insert_var("eps"),
+            imaginary_unit: state.get_or_insert_var("i"),
+            dimension: None,
+            vectors: vec![],
+        }
+    }
+
+    /// Set the symbol of the dimension, or `None` to work in 4 dimensions.
+    pub fn set_dimension(&mut self, dimension: Option<Identifier>) {
+        self.dimension = dimension;
+    }
+
+    /// Register a vector, which is not summed over when it appears twice.
+    pub fn add_vector(&mut self, vector: Identifier) {
+        if !self.vectors.contains(&vector) {
+            self.vectors.push(vector);
+        }
+    }
+
+    fn is_index(&self, id: Identifier) -> bool {
+        !self.vectors.contains(&id)
+    }
+
+    /// Write the left chiral projector `(1-gamma5())/2` into `out`.
+    pub fn left_projector<P: Atom>(
+        &self,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        self.projector(-1, workspace, state, out);
+    }
+
+    /// Write the right chiral projector `(1+gamma5())/2` into `out`.
+    pub fn right_projector<P: Atom>(
+        &self,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        self.projector(1, workspace, state, out);
+    }
+
+    fn projector<P: Atom>(
+        &self,
+        sign: i64,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let mut add_h = workspace.new_atom();
+        let add = add_h.get_mut().transform_to_add();
+
+        let mut half_h = workspace.new_atom();
+        half_h
+            .get_mut()
+            .transform_to_num()
+            .set_from_number(Number::Natural(1, 2));
+        add.extend(half_h.get().to_view());
+
+        let mut g5_h = workspace.new_atom();
+        g5_h.get_mut().transform_to_fun().set_from_name(self.gamma5);
+        half_h
+            .get_mut()
+            .transform_to_num()
+            .set_from_number(Number::Natural(sign, 2));
+
+        let mut mul_h = workspace.new_atom();
+        let mul = mul_h.get_mut().transform_to_mul();
+        mul.extend(g5_h.get().to_view());
+        mul.extend(half_h.get().to_view());
+        mul.set_dirty(true);
+        add.extend(mul_h.get().to_view());
+        add.set_dirty(true);
+
+        add_h.get().to_view().normalize(workspace, state, out);
+    }
+
+    /// Simplify the products of gamma matrices in every term of `expr` by
+    /// contracting repeated indices, anticommuting `gamma5` to the right,
+    /// and contracting metrics with other tensors. The expression should be expanded.
+    pub fn contract<P: Atom>(
+        &self,
+        expr: AtomView<P>,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        self.map_terms(expr, workspace, state, out, |t| self.simplify(t));
+    }
+
+    /// Replace the product of gamma matrices in every term of `expr` by its trace.
+    /// The expression should be expanded.
+    pub fn trace<P: Atom>(
+        &self,
+        expr: AtomView<P>,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        self.map_terms(expr, workspace, state, out, |t| {
+            let mut traced = vec![];
+            for t in self.simplify(t) {
+                self.trace_chain(t, &mut traced);
+            }
+            traced.into_iter().flat_map(|t| self.simplify(t)).collect()
+        });
+    }
+
+    /// Apply `f` to the gamma algebra part of every term and collect the results.
+    fn map_terms<P: Atom>(
+        &self,
+        expr: AtomView<P>,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+        f: impl Fn(GammaTerm) -> Vec<GammaTerm>,
+    ) {
+        let mut add_h = workspace.new_atom();
+        let add = add_h.get_mut().transform_to_add();
+
+        let mut terms = vec![];
+        if let AtomView::Add(a) = expr {
+            terms.extend(a.iter());
+        } else {
+            terms.push(expr);
+        }
+
+        for term in terms {
+            let mut t = GammaTerm {
+                coeff: self.new_coeff(),
+                imaginary: false,
+                metrics: vec![],
+                epsilons: vec![],
+                chain: vec![],
+            };
+            let mut rest = vec![];
+            if let AtomView::Mul(m) = term {
+                for factor in m.iter() {
+                    self.parse_factor(factor, &mut t, &mut rest);
+                }
+            } else {
+                self.parse_factor(term, &mut t, &mut rest);
+            }
+
+            for r in f(t) {
+                let mut mul_h = workspace.new_atom();
+                self.term_to_atom(&r, &rest, workspace, state, mul_h.get_mut());
+                add.extend(mul_h.get().to_view());
+            }
+        }
+
+        if add.to_add_view().get_nargs() == 0 {
+            out.transform_to_num()
+                .set_from_number(Number::Natural(0, 1));
+            return;
+        }
+
+        add.set_dirty(true);
+        add_h.get().to_view().normalize(workspace, state, out);
+    }
+
+    fn new_coeff(&self) -> MultivariatePolynomial<IntegerRing, u16> {
+        MultivariatePolynomial::new(
+            1,
+            IntegerRing::new(),
+            None,
+            Some(&[self.dimension.unwrap_or(self.imaginary_unit)]),
+        )
+        .new_from_constant(Integer::Natural(1))
+    }
+
+    /// Get the trace of the metric, which is the dimension.
+    fn dimension_factor(&self) -> MultivariatePolynomial<IntegerRing, u16> {
+        let one = self.new_coeff();
+        match self.dimension {
+            Some(_) => one.new_from_monomial(Integer::Natural(1), vec![1]),
+            None => one.mul_coeff(Integer::Natural(4)),
+        }
+    }
+
+    /// Get the arguments of a function if they are all variables.
+    fn var_args<'a, P: Atom>(f: &P::F<'a>) -> Option<Vec<Identifier>> {
+        f.iter()
+            .map(|a| match a {
+                AtomView::Var(v) => Some(v.get_name()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Add the factor to the term if it is part of the gamma algebra, or else to `rest`.
+    fn parse_factor<'a, P: Atom>(
+        &self,
+        factor: AtomView<'a, P>,
+        t: &mut GammaTerm,
+        rest: &mut Vec<AtomView<'a, P>>,
+    ) {
+        match factor {
+            AtomView::Fun(f) => {
+                let name = f.get_name();
+                if name == self.gamma5 && f.get_nargs() == 0 {
+                    t.chain.push(Slot::Gamma5);
+                    return;
+                }
+
+                if let Some(args) = Self::var_args::<P>(&f) {
+                    if name == self.gamma && args.len() == 1 {
+                        t.chain.push(Slot::Gamma(args[0]));
+                        return;
+                    }
+                    if name == self.metric && args.len() == 2 {
+                        t.metrics.push([args[0], args[1]]);
+                        return;
+                    }
+                    if name == self.levi_civita && args.len() == 4 {
+                        t.epsilons.push([args[0], args[1], args[2], args[3]]);
+                        return;
+                    }
+                }
+
+                assert!(
+                    name != self.gamma,
+                    "The argument of a gamma matrix must be a variable"
+                );
+                rest.push(factor);
+            }
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                if let (AtomView::Fun(f), AtomView::Num(n)) = (base, exp) {
+                    if let BorrowedNumber::Natural(n, 1) = n.get_number_view() {
+                        if n > 0
+                            && [self.gamma, self.gamma5, self.metric, self.levi_civita]
+                                .contains(&f.get_name())
+                        {
+                            let mut base_rest = vec![];
+                            for _ in 0..n {
+                                self.parse_factor(base, t, &mut base_rest);
+                            }
+                            if base_rest.is_empty() {
+                                return;
+                            }
+                        }
+                    }
+                }
+                rest.push(factor);
+            }
+            _ => rest.push(factor),
+        }
+    }
+
+    /// Apply all simplifications to the term until no more rules apply.
+    fn simplify(&self, t: GammaTerm) -> Vec<GammaTerm> {
+        let mut todo = vec![t];
+        let mut done = vec![];
+        while let Some(mut t) = todo.pop() {
+            if t.coeff.is_zero() {
+                continue;
+            }
+
+            self.move_gamma5_right(&mut t);
+            if !self.contract_metrics(&mut t) {
+                continue;
+            }
+
+            match self.contract_chain(&t) {
+                Some(new_terms) => todo.extend(new_terms),
+                None => done.push(t),
+            }
+        }
+        done
+    }
+
+    /// Move all `gamma5` to the end of the chain using `gamma5*gamma(mu) = -gamma(mu)*gamma5`
+    /// and `gamma5*gamma5 = 1`.
+    fn move_gamma5_right(&self, t: &mut GammaTerm) {
+        let mut n_gamma5 = 0;
+        let mut n_swaps = 0;
+        for (i, s) in t.chain.iter().enumerate() {
+            if *s == Slot::Gamma5 {
+                n_gamma5 += 1;
+                n_swaps += t.chain[i + 1..]
+                    .iter()
+                    .filter(|s| **s != Slot::Gamma5)
+                    .count();
+            }
+        }
+
+        if n_gamma5 == 0 {
+            return;
+        }
+
+        t.chain.retain(|s| *s != Slot::Gamma5);
+        if n_gamma5 % 2 == 1 {
+            t.chain.push(Slot::Gamma5);
+        }
+        if n_swaps % 2 == 1 {
+            t.mul_int(-1);
+        }
+    }
+
+    /// Contract metrics with repeated indices, and bring metrics and Levi-Civita
+    /// tensors in a canonical form. Returns `false` if the term vanishes.
+    fn contract_metrics(&self, t: &mut GammaTerm) -> bool {
+        'contract: loop {
+            for mi in 0..t.metrics.len() {
+                let [a, b] = t.metrics[mi];
+                if a == b && self.is_index(a) {
+                    t.metrics.swap_remove(mi);
+                    t.coeff = &t.coeff * &self.dimension_factor();
+                    continue 'contract;
+                }
+
+                for (mu, other) in [(a, b), (b, a)] {
+                    if !self.is_index(mu) {
+                        continue;
+                    }
+
+                    let found = t
+                        .metrics
+                        .iter_mut()
+                        .enumerate()
+                        .filter(|(mj, _)| *mj != mi)
+                        .flat_map(|(_, m)| m.iter_mut())
+                        .chain(t.epsilons.iter_mut().flat_map(|e| e.iter_mut()))
+                        .chain(t.chain.iter_mut().filter_map(|s| match s {
+                            Slot::Gamma(i) => Some(i),
+                            Slot::Gamma5 => None,
+                        }))
+                        .find(|i| **i == mu);
+
+                    if let Some(i) = found {
+                        *i = other;
+                        t.metrics.swap_remove(mi);
+                        continue 'contract;
+                    }
+                }
+            }
+            break;
+        }
+
+        for m in &mut t.metrics {
+            m.sort();
+        }
+        t.metrics.sort();
+
+        // sort the indices of the Levi-Civita tensors and keep track of the sign of the permutation
+        let mut n_swaps = 0;
+        for e in &mut t.epsilons {
+            for i in 1..4 {
+                for j in (1..=i).rev() {
+                    if e[j - 1] <= e[j] {
+                        break;
+                    }
+                    e.swap(j - 1, j);
+                    n_swaps += 1;
+                }
+            }
+
+            if e.windows(2).any(|w| w[0] == w[1]) {
+                return false;
+            }
+        }
+        t.epsilons.sort();
+
+        if n_swaps % 2 == 1 {
+            t.mul_int(-1);
+        }
+
+        true
+    }
+
+    /// Perform a single contraction step on the gamma matrices, or return `None` if
+    /// the chain is fully simplified. The closest pair of equal indices
+    /// `gamma(mu)*X*gamma(mu)` is contracted by anticommuting the last gamma matrix to the
+    /// left, and `gamma(mu)*gamma(mu) = D` and `gamma(p)*gamma(p) = g(p,p)` are applied.
+    /// Finally, the gamma matrices are sorted by anticommuting them.
+    fn contract_chain(&self, t: &GammaTerm) -> Option<Vec<GammaTerm>> {
+        let mut best: Option<(usize, usize)> = None;
+        for j in 0..t.chain.len() {
+            let Slot::Gamma(x) = t.chain[j] else {
+                continue;
+            };
+
+            if let Some(i) = t.chain[..j].iter().rposition(|s| *s == Slot::Gamma(x)) {
+                if (self.is_index(x) || i + 1 == j)
+                    && best.map(|(bi, bj)| j - i < bj - bi).unwrap_or(true)
+                {
+                    best = Some((i, j));
+                }
+            }
+        }
+
+        let Some((i, j)) = best else {
+            // sort the chain
+            let j = t.chain.windows(2).position(|w| match (w[0], w[1]) {
+                (Slot::Gamma(a), Slot::Gamma(b)) => a > b,
+                _ => false,
+            })? + 1;
+            return Some(Self::anticommute(t, j));
+        };
+        let Slot::Gamma(x) = t.chain[j] else {
+            unreachable!()
+        };
+
+        if i + 1 == j {
+            let mut t = t.clone();
+            t.chain.drain(i..=j);
+            if self.is_index(x) {
+                t.coeff = &t.coeff * &self.dimension_factor();
+            } else {
+                t.metrics.push([x, x]);
+            }
+            return Some(vec![t]);
+        }
+
+        Some(Self::anticommute(t, j))
+    }
+
+    /// Swap the gamma matrices at `j - 1` and `j` using
+    /// `gamma(a)*gamma(x) = 2*g(a,x) - gamma(x)*gamma(a)`.
+    fn anticommute(t: &GammaTerm, j: usize) -> Vec<GammaTerm> {
+        let (Slot::Gamma(a), Slot::Gamma(x)) = (t.chain[j - 1], t.chain[j]) else {
+            unreachable!()
+        };
+
+        let mut t1 = t.clone();
+        t1.chain.drain(j - 1..=j);
+        t1.metrics.push([a, x]);
+        t1.mul_int(2);
+
+        let mut t2 = t.clone();
+        t2.chain.swap(j - 1, j);
+        t2.mul_int(-1);
+
+        vec![t1, t2]
+    }
+
+    /// Compute the trace of the chain of the term.
+    fn trace_chain(&self, t: GammaTerm, out: &mut Vec<GammaTerm>) {
+        let has_gamma5 = t.chain.last() == Some(&Slot::Gamma5);
+        let gammas = t.gammas();
+        if gammas.len() % 2 == 1 {
+            return;
+        }
+
+        let with_chain = |t: &GammaTerm, chain: Vec<Identifier>, gamma5: bool| {
+            let mut t = t.clone();
+            t.chain = chain.into_iter().map(Slot::Gamma).collect();
+            if gamma5 {
+                t.chain.push(Slot::Gamma5);
+            }
+            t
+        };
+
+        if !has_gamma5 {
+            if gammas.is_empty() {
+                let mut t = t;
+                t.mul_int(4);
+                out.push(t);
+                return;
+            }
+
+            // Tr(gamma(a1)*...*gamma(an)) = sum_k (-1)^k g(a1,ak) Tr(... without a1 and ak)
+            for k in 1..gammas.len() {
+                let chain = gammas[1..]
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i + 1 != k)
+                    .map(|(_, g)| *g)
+                    .collect();
+                let mut nt = with_chain(&t, chain, false);
+                nt.metrics.push([gammas[0], gammas[k]]);
+                if k % 2 == 0 {
+                    nt.mul_int(-1);
+                }
+                self.trace_chain(nt, out);
+            }
+            return;
+        }
+
+        if gammas.len() < 4 {
+            return;
+        }
+
+        // use gamma(a)*gamma(b)*gamma(c) = g(a,b)*gamma(c) - g(a,c)*gamma(b) + g(b,c)*gamma(a)
+        //     + i*eps(a,b,c,mu)*gamma(mu)*gamma5()
+        let (a, b, c) = (gammas[0], gammas[1], gammas[2]);
+        let r = &gammas[3..];
+        for (m, first, sign) in [([a, b], c, 1), ([a, c], b, -1), ([b, c], a, 1)] {
+            let mut chain = vec![first];
+            chain.extend_from_slice(r);
+            let mut nt = with_chain(&t, chain, true);
+            nt.metrics.push(m);
+            nt.mul_int(sign);
+            self.trace_chain(nt, out);
+        }
+
+        // the remaining trace i*eps(a,b,c,mu)*Tr(gamma(mu)*gamma5()*R*gamma5()) = -i*eps(a,b,c,mu)*Tr(gamma(mu)*R)
+        // is computed directly, so that no new index is needed
+        for (k, rk) in r.iter().enumerate() {
+            let chain = r
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != k)
+                .map(|(_, g)| *g)
+                .collect();
+            let mut nt = with_chain(&t, chain, false);
+            nt.epsilons.push([a, b, c, *rk]);
+            nt.mul_imaginary();
+            if k % 2 == 0 {
+                nt.mul_int(-1);
+            }
+            self.trace_chain(nt, out);
+        }
+    }
+
+    /// Convert the term multiplied by the factors in `rest` to an atom.
+    fn term_to_atom<P: Atom>(
+        &self,
+        t: &GammaTerm,
+        rest: &[AtomView<P>],
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let mut coeff_h = workspace.new_atom();
+        coeff_h
+            .get_mut()
+            .from_polynomial(workspace, state, &t.coeff);
+
+        let mul = out.transform_to_mul();
+        mul.extend(coeff_h.get().to_view());
+
+        let mut h = workspace.new_atom();
+        if t.imaginary {
+            h.get_mut()
+                .transform_to_var()
+                .set_from_id(self.imaginary_unit);
+            mul.extend(h.get().to_view());
+        }
+
+        let mut arg_h = workspace.new_atom();
+        let mut add_fun = |name: Identifier, args: &[Identifier]| {
+            let f = h.get_mut().transform_to_fun();
+            f.set_from_name(name);
+            for a in args {
+                arg_h.get_mut().transform_to_var().set_from_id(*a);
+                f.add_arg(arg_h.get().to_view());
+            }
+            mul.extend(h.get().to_view());
+        };
+
+        for m in &t.metrics {
+            add_fun(self.metric, m);
+        }
+        for e in &t.epsilons {
+            add_fun(self.levi_civita, e);
+        }
+        for s in &t.chain {
+            match s {
+                Slot::Gamma(i) => add_fun(self.gamma, &[*i]),
+                Slot::Gamma5 => add_fun(self.gamma5, &[]),
+            }
+        }
+
+        for r in rest {
+            mul.extend(*r);
+        }
+        mul.set_dirty(true);
+    }
+}
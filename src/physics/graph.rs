@@ -0,0 +1,283 @@
+use ahash::HashMap;
+
+use crate::{
+    poly::{polynomial::MultivariatePolynomial, Exponent},
+    representations::Identifier,
+    rings::integer::{Integer, IntegerRing},
+};
+
+/// The Symanzik polynomials `(U, F)`.
+pub type SymanzikPolynomials<E> = (
+    MultivariatePolynomial<IntegerRing, E>,
+    MultivariatePolynomial<IntegerRing, E>,
+);
+
+/// A propagator between two vertices, with an optional squared mass.
+#[derive(Clone, Copy, Debug)]
+struct Edge {
+    from: usize,
+    to: usize,
+    mass: Option<Identifier>,
+}
+
+/// A Feynman graph, given by its propagators and the external momenta that
+/// flow into its vertices, from which the Symanzik polynomials can be generated.
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    edges: Vec<Edge>,
+    externals: Vec<(usize, Identifier)>,
+    scalar_products: HashMap<(Identifier, Identifier), Option<Identifier>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a propagator between the vertices `from` and `to`, with the
+    /// variable `mass` as squared mass, and return its index.
+    pub fn add_edge(&mut self, from: usize, to: usize, mass: Option<Identifier>) -> usize {
+        self.edges.push(Edge { from, to, mass });
+        self.edges.len() - 1
+    }
+
+    /// Add an external momentum `momentum` flowing into `vertex`.
+    pub fn add_external(&mut self, vertex: usize, momentum: Identifier) {
+        self.externals.push((vertex, momentum));
+    }
+
+    /// Set the variable that represents the scalar product of the external
+    /// momenta `p` and `q`. Use `p == q` for the square of a momentum.
+    pub fn set_scalar_product(&mut self, p: Identifier, q: Identifier, var: Identifier) {
+        self.scalar_products.insert((p, q), Some(var));
+        self.scalar_products.insert((q, p), Some(var));
+    }
+
+    /// Set the scalar product of the external momenta `p` and `q` to zero,
+    /// for example to put a massless momentum on shell with `p == q`.
+    pub fn set_zero_scalar_product(&mut self, p: Identifier, q: Identifier) {
+        self.scalar_products.insert((p, q), None);
+        self.scalar_products.insert((q, p), None);
+    }
+
+    pub fn get_edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Get the number of loops of the graph, assuming it is connected.
+    pub fn get_loop_count(&self) -> usize {
+        self.edges.len() + 1 - self.get_vertex_count()
+    }
+
+    fn get_vertex_count(&self) -> usize {
+        self.edges
+            .iter()
+            .flat_map(|e| [e.from, e.to])
+            .chain(self.externals.iter().map(|e| e.0))
+            .max()
+            .map(|v| v + 1)
+            .unwrap_or(0)
+    }
+
+    /// Generate the Symanzik polynomials `(U, F)` with the Feynman parameters
+    /// `params`, one for every edge. With `x_e` the parameter of edge `e`,
+    /// ```text
+    /// U = sum_{spanning trees T} prod_{e not in T} x_e
+    /// F = sum_{spanning 2-forests (T1,T2)} prod_{e not in (T1,T2)} x_e * (-s_T1) + U * sum_e x_e m_e^2
+    /// ```
+    /// where `s_T1` is the square of the momentum flowing into `T1`, expressed in the
+    /// scalar products set with [`Graph::set_scalar_product`]. The tree `T1` is the one
+    /// that does not contain the vertex of the last external momentum, so that this
+    /// momentum is eliminated using momentum conservation.
+    ///
+    /// The variables of the polynomials are the Feynman parameters, followed by the masses
+    /// and scalar products that appear in `F`.
+    pub fn symanzik_polynomials<E: Exponent>(
+        &self,
+        params: &[Identifier],
+    ) -> Result<SymanzikPolynomials<E>, String> {
+        if params.len() != self.edges.len() {
+            return Err(format!(
+                "Expected {} Feynman parameters, but got {}",
+                self.edges.len(),
+                params.len()
+            ));
+        }
+
+        let nvertices = self.get_vertex_count();
+        if nvertices == 0 {
+            return Err("The graph has no vertices".to_owned());
+        }
+
+        let mut var_map = params.to_vec();
+        let mut var = |id: Identifier| match var_map.iter().position(|x| *x == id) {
+            Some(p) => p,
+            None => {
+                var_map.push(id);
+                var_map.len() - 1
+            }
+        };
+
+        // the exponents of the monomials and the kinematic factors of F
+        let mut u_terms: Vec<Vec<usize>> = vec![];
+        let mut f_terms: Vec<(Vec<usize>, usize)> = vec![];
+
+        for tree in self.forests(nvertices - 1) {
+            u_terms.push(self.complement(&tree));
+        }
+
+        if u_terms.is_empty() {
+            return Err("The graph is not connected".to_owned());
+        }
+
+        for e in &self.edges {
+            if let Some(m) = e.mass {
+                var(m);
+            }
+        }
+
+        let last_vertex = self.externals.last().map(|e| e.0);
+        for forest in self.forests(nvertices.saturating_sub(2)) {
+            let component = self.components(nvertices, &forest);
+            let t1 = match last_vertex {
+                Some(v) => 1 - component[v],
+                None => break,
+            };
+
+            let momenta: Vec<_> = self
+                .externals
+                .iter()
+                .filter(|(v, _)| component[*v] == t1)
+                .map(|e| e.1)
+                .collect();
+
+            let x = self.complement(&forest);
+            for p in &momenta {
+                for q in &momenta {
+                    let sp = self
+                        .scalar_products
+                        .get(&(*p, *q))
+                        .ok_or_else(|| format!("Missing scalar product of {:?} and {:?}", p, q))?;
+                    if let Some(sp) = sp {
+                        f_terms.push((x.clone(), var(*sp)));
+                    }
+                }
+            }
+        }
+
+        let nvars = var_map.len();
+        let field = IntegerRing::new();
+        let mut u = MultivariatePolynomial::new(nvars, field, None, Some(&var_map));
+        let mut f = MultivariatePolynomial::new(nvars, field, None, Some(&var_map));
+
+        let mut exp = vec![E::zero(); nvars];
+        for t in &u_terms {
+            exp.iter_mut().for_each(|e| *e = E::zero());
+            for x in t {
+                exp[*x] = E::one();
+            }
+            u.append_monomial(Integer::Natural(1), &exp);
+
+            for (i, e) in self.edges.iter().enumerate() {
+                if let Some(m) = e.mass {
+                    let m = var_map.iter().position(|x| *x == m).unwrap();
+                    let mut exp_m = exp.clone();
+                    exp_m[i] += E::one();
+                    exp_m[m] += E::one();
+                    f.append_monomial(Integer::Natural(1), &exp_m);
+                }
+            }
+        }
+
+        for (t, sp) in &f_terms {
+            exp.iter_mut().for_each(|e| *e = E::zero());
+            for x in t {
+                exp[*x] = E::one();
+            }
+            exp[*sp] = E::one();
+            f.append_monomial(Integer::Natural(-1), &exp);
+        }
+
+        Ok((u, f))
+    }
+
+    /// Get all sets of `size` edges that do not contain a cycle.
+    fn forests(&self, size: usize) -> Vec<Vec<usize>> {
+        fn find(parent: &mut [usize], v: usize) -> usize {
+            if parent[v] != v {
+                parent[v] = find(parent, parent[v]);
+            }
+            parent[v]
+        }
+
+        fn rec(
+            edges: &[Edge],
+            start: usize,
+            size: usize,
+            parent: &[usize],
+            cur: &mut Vec<usize>,
+            res: &mut Vec<Vec<usize>>,
+        ) {
+            if cur.len() == size {
+                res.push(cur.clone());
+                return;
+            }
+
+            for i in start..edges.len() {
+                if edges.len() - i < size - cur.len() {
+                    break;
+                }
+
+                let mut parent = parent.to_vec();
+                let (a, b) = (
+                    find(&mut parent, edges[i].from),
+                    find(&mut parent, edges[i].to),
+                );
+                if a == b {
+                    continue;
+                }
+                parent[a] = b;
+
+                cur.push(i);
+                rec(edges, i + 1, size, &parent, cur, res);
+                cur.pop();
+            }
+        }
+
+        let parent: Vec<_> = (0..self.get_vertex_count()).collect();
+        let mut res = vec![];
+        rec(&self.edges, 0, size, &parent, &mut vec![], &mut res);
+        res
+    }
+
+    /// Get the edges that are not in the forest.
+    fn complement(&self, forest: &[usize]) -> Vec<usize> {
+        (0..self.edges.len())
+            .filter(|e| !forest.contains(e))
+            .collect()
+    }
+
+    /// Label the vertices with 0 or 1, depending on the tree of the 2-forest they belong to.
+    fn components(&self, nvertices: usize, forest: &[usize]) -> Vec<usize> {
+        let mut component = vec![usize::MAX; nvertices];
+        let mut stack = vec![0];
+        component[0] = 0;
+        while let Some(v) = stack.pop() {
+            for e in forest.iter().map(|e| &self.edges[*e]) {
+                for (a, b) in [(e.from, e.to), (e.to, e.from)] {
+                    if a == v && component[b] == usize::MAX {
+                        component[b] = 0;
+                        stack.push(b);
+                    }
+                }
+            }
+        }
+
+        for c in &mut component {
+            if *c == usize::MAX {
+                *c = 1;
+            }
+        }
+        component
+    }
+}
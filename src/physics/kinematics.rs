@@ -0,0 +1,593 @@
+use ahash::HashMap;
+
+use crate::{
+    determinant::determinant,
+    id::{Pattern, Rule, RuleSet},
+    representations::{
+        number::{BorrowedNumber, Number},
+        Add, Atom, AtomView, Fun, Identifier, Mul, Num, OwnedAdd, OwnedAtom, OwnedFun, OwnedMul,
+        OwnedNum, OwnedPow, OwnedVar, Pow, Var,
+    },
+    rings::{linear_system::Matrix, rational::Rational, rational::RationalField, Ring},
+    state::{BufferHandle, ResettableBuffer, State, Workspace},
+};
+
+/// A term of a relation, split into its rational coefficient, its scalar product and its other factors.
+type RelationTerm<P> = (Rational, Option<(Identifier, Identifier)>, OwnedAtom<P>);
+
+/// Kinematic invariants of a process, expressed in the scalar products `dot(p,q)`
+/// of momenta. The arguments of a scalar product are sorted by identifier, so that
+/// every scalar product has a unique representation.
+///
+/// Momenta can be put on shell with [`Kinematics::set_on_shell`] and Mandelstam
+/// invariants `s = (p+q)^2` can be introduced with [`Kinematics::add_invariant`]. Both
+/// are applied as rewrite rules, obtained from [`Kinematics::get_rules`].
+pub struct Kinematics {
+    pub dot: Identifier,
+    on_shell: Vec<(Identifier, Option<Identifier>)>,
+    invariants: Vec<(Identifier, Identifier, Identifier)>,
+}
+
+impl Kinematics {
+    /// Create the kinematics with the scalar product `dot`.
+    pub fn new(state: &mut State) -> Self {
+        Kinematics {
+            dot: state.get_or_insert_var("dot"),
+            on_shell: vec![],
+            invariants: vec![],
+        }
+    }
+
+    /// Put the momentum `p` on shell, `p^2 = mass^2`, or `p^2 = 0` if `mass` is `None`.
+    pub fn set_on_shell(&mut self, p: Identifier, mass: Option<Identifier>) {
+        self.on_shell.retain(|(q, _)| *q != p);
+        self.on_shell.push((p, mass));
+    }
+
+    /// Introduce the invariant `s = (p+q)^2`, which replaces the scalar product
+    /// `dot(p,q) = (s - p^2 - q^2)/2`.
+    pub fn add_invariant(&mut self, p: Identifier, q: Identifier, s: Identifier) {
+        assert!(p != q, "The momenta of an invariant must be different");
+        self.invariants.push((p, q, s));
+    }
+
+    /// Write the scalar product `dot(p,q)` into `out`.
+    pub fn scalar_product<P: Atom>(
+        &self,
+        p: Identifier,
+        q: Identifier,
+        workspace: &Workspace<P>,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let fun = out.transform_to_fun();
+        fun.set_from_name(self.dot);
+        fun.add_arg(var(p.min(q), workspace).get().to_view());
+        fun.add_arg(var(p.max(q), workspace).get().to_view());
+    }
+
+    /// Write the Gram determinant `det(dot(p_i,p_j))` of the momenta `momenta` into `out`.
+    /// The Gram determinant vanishes if the momenta are linearly dependent.
+    pub fn gram_determinant<P: Atom>(
+        &self,
+        momenta: &[Identifier],
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let mut entries: Vec<OwnedAtom<P>> = vec![];
+        for p in momenta {
+            for q in momenta {
+                let mut e = OwnedAtom::new();
+                self.scalar_product(*p, *q, workspace, &mut e);
+                entries.push(e);
+            }
+        }
+
+        let views: Vec<_> = entries.iter().map(|e| e.to_view()).collect();
+        determinant(&views, momenta.len(), workspace, state, out);
+    }
+
+    /// Get the rules that put momenta on shell and that replace scalar products by
+    /// the Mandelstam invariants. The on-shell rules have a higher priority, so that
+    /// they are also applied to the squares that are introduced by the invariants.
+    pub fn get_rules<P: Atom>(&self, workspace: &Workspace<P>, state: &State) -> RuleSet<P> {
+        let mut rules = RuleSet::new();
+
+        for (p, mass) in &self.on_shell {
+            let mut lhs = workspace.new_atom();
+            self.scalar_product(*p, *p, workspace, lhs.get_mut());
+
+            let mut rhs = workspace.new_atom();
+            match mass {
+                Some(m) => {
+                    let m = var(*m, workspace);
+                    m.get()
+                        .to_view()
+                        .builder(state, workspace)
+                        .pow(num(2, 1, workspace).get().to_view())
+                        .finish(rhs.get_mut());
+                }
+                None => {
+                    rhs.get_mut()
+                        .transform_to_num()
+                        .set_from_number(Number::Natural(0, 1));
+                }
+            }
+
+            rules.add_rule(Rule {
+                name: format!("on-shell {}", state.get_name(*p).unwrap()),
+                lhs: Pattern::from_view(lhs.get().to_view(), state),
+                rhs: Pattern::from_view(rhs.get().to_view(), state),
+                restrictions: HashMap::default(),
+                priority: 1,
+            });
+        }
+
+        for (p, q, s) in &self.invariants {
+            let mut lhs = workspace.new_atom();
+            self.scalar_product(*p, *q, workspace, lhs.get_mut());
+            let mut pp = workspace.new_atom();
+            self.scalar_product(*p, *p, workspace, pp.get_mut());
+            let mut qq = workspace.new_atom();
+            self.scalar_product(*q, *q, workspace, qq.get_mut());
+
+            let mut rhs = workspace.new_atom();
+            let s_h = var(*s, workspace);
+            (s_h.get().to_view().builder(state, workspace).lazy()
+                - pp.get().to_view()
+                - qq.get().to_view())
+            .mul_view(num(1, 2, workspace).get().to_view())
+            .finish(rhs.get_mut());
+
+            rules.add_rule(Rule {
+                name: format!("invariant {}", state.get_name(*s).unwrap()),
+                lhs: Pattern::from_view(lhs.get().to_view(), state),
+                rhs: Pattern::from_view(rhs.get().to_view(), state),
+                restrictions: HashMap::default(),
+                priority: 0,
+            });
+        }
+
+        rules
+    }
+
+    /// Get the relations `dot(p_i, p_1 + ... + p_n) = 0` that follow from the conservation
+    /// of the momenta `momenta`, where all momenta are incoming. The relations can be
+    /// solved with [`Kinematics::solve_relations`].
+    pub fn momentum_conservation<P: Atom>(
+        &self,
+        momenta: &[Identifier],
+        workspace: &Workspace<P>,
+        state: &State,
+    ) -> Vec<OwnedAtom<P>> {
+        let mut sum = workspace.new_atom();
+        let add = sum.get_mut().transform_to_add();
+        for q in momenta {
+            add.extend(var(*q, workspace).get().to_view());
+        }
+        add.set_dirty(true);
+
+        momenta
+            .iter()
+            .map(|p| {
+                let mut fun = workspace.new_atom();
+                let f = fun.get_mut().transform_to_fun();
+                f.set_from_name(self.dot);
+                f.add_arg(var(*p, workspace).get().to_view());
+                f.add_arg(sum.get().to_view());
+                f.set_dirty(true);
+
+                let mut out = OwnedAtom::new();
+                fun.get().to_view().normalize(workspace, state, &mut out);
+                out
+            })
+            .collect()
+    }
+
+    /// Expand the expression and write every scalar product of linear combinations of
+    /// momenta with rational coefficients, such as `dot(p1+2*p2,p3)`, as a sum of scalar
+    /// products of momenta with sorted arguments.
+    pub fn expand_scalar_products<P: Atom>(
+        &self,
+        expr: AtomView<'_, P>,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let mut expanded = workspace.new_atom();
+        expr.expand(workspace, state, expanded.get_mut());
+
+        let mut res = workspace.new_atom();
+        self.map_scalar_products(expanded.get().to_view(), workspace, state, res.get_mut());
+        res.get().to_view().expand(workspace, state, out);
+    }
+
+    fn map_scalar_products<P: Atom>(
+        &self,
+        expr: AtomView<'_, P>,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let mut res = workspace.new_atom();
+        match expr {
+            AtomView::Fun(f) if f.get_name() == self.dot && f.get_nargs() == 2 => {
+                let mut args = f.iter();
+                let (Some(a), Some(b)) = (
+                    momentum_terms(args.next().unwrap()),
+                    momentum_terms(args.next().unwrap()),
+                ) else {
+                    out.from_view(&expr);
+                    return;
+                };
+
+                let add = res.get_mut().transform_to_add();
+                for (p, cp) in &a {
+                    for (q, cq) in &b {
+                        let mut term = workspace.new_atom();
+                        let mul = term.get_mut().transform_to_mul();
+                        for c in [cp, cq].into_iter().flatten() {
+                            mul.extend(*c);
+                        }
+                        let mut sp = workspace.new_atom();
+                        self.scalar_product(*p, *q, workspace, sp.get_mut());
+                        mul.extend(sp.get().to_view());
+                        mul.set_dirty(true);
+                        add.extend(term.get().to_view());
+                    }
+                }
+                add.set_dirty(true);
+            }
+            AtomView::Num(_) | AtomView::Var(_) | AtomView::Fun(_) => {
+                out.from_view(&expr);
+                return;
+            }
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                let mut new_base = workspace.new_atom();
+                self.map_scalar_products(base, workspace, state, new_base.get_mut());
+                let pow = res.get_mut().transform_to_pow();
+                pow.set_from_base_and_exp(new_base.get().to_view(), exp);
+                pow.set_dirty(true);
+            }
+            AtomView::Mul(m) => {
+                let mul = res.get_mut().transform_to_mul();
+                for a in m.iter() {
+                    let mut new_a = workspace.new_atom();
+                    self.map_scalar_products(a, workspace, state, new_a.get_mut());
+                    mul.extend(new_a.get().to_view());
+                }
+                mul.set_dirty(true);
+            }
+            AtomView::Add(a) => {
+                let add = res.get_mut().transform_to_add();
+                for t in a.iter() {
+                    let mut new_t = workspace.new_atom();
+                    self.map_scalar_products(t, workspace, state, new_t.get_mut());
+                    add.extend(new_t.get().to_view());
+                }
+                add.set_dirty(true);
+            }
+        }
+
+        res.get().to_view().normalize(workspace, state, out);
+    }
+
+    /// Solve the `relations`, which are expressions that vanish and that are linear over the
+    /// rationals in the scalar products, for as many scalar products as possible. Before
+    /// solving, the scalar products in the relations are expanded and the rules of
+    /// [`Kinematics::get_rules`] are applied. Scalar products of momenta that are registered
+    /// later are eliminated first.
+    ///
+    /// Returns the rules that replace every eliminated scalar product by a linear combination
+    /// of the remaining scalar products and the other terms of the relations, such as
+    /// invariants and masses. Relations that only constrain these other terms, such as
+    /// `s+t+u=0`, are not used. Use [`Kinematics::canonicalize`] to apply the rules.
+    pub fn solve_relations<P: Atom>(
+        &self,
+        relations: &[AtomView<'_, P>],
+        workspace: &Workspace<P>,
+        state: &State,
+    ) -> Result<RuleSet<P>, String> {
+        let field = RationalField::new();
+        let empty = RuleSet::new();
+
+        let mut scalar_products: Vec<(Identifier, Identifier)> = vec![];
+        let mut others: Vec<OwnedAtom<P>> = vec![];
+        let mut rows = vec![];
+        for r in relations {
+            let mut rel = OwnedAtom::new();
+            self.canonicalize(*r, &empty, workspace, state, &mut rel);
+
+            let mut row = vec![];
+            let terms: Vec<_> = match rel.to_view() {
+                AtomView::Add(a) => a.iter().collect(),
+                x => vec![x],
+            };
+            for t in terms {
+                let (coeff, sp, rest) = self.split_term(t, workspace, state)?;
+                match sp {
+                    Some(sp) => {
+                        let col = match scalar_products.iter().position(|x| *x == sp) {
+                            Some(c) => c,
+                            None => {
+                                scalar_products.push(sp);
+                                scalar_products.len() - 1
+                            }
+                        };
+                        row.push((Ok(col), coeff));
+                    }
+                    None => {
+                        let col = match others.iter().position(|x| x.to_view() == rest.to_view()) {
+                            Some(c) => c,
+                            None => {
+                                others.push(rest);
+                                others.len() - 1
+                            }
+                        };
+                        row.push((Err(col), coeff));
+                    }
+                }
+            }
+            rows.push(row);
+        }
+
+        // eliminate the scalar products with the highest identifiers first
+        let mut order: Vec<usize> = (0..scalar_products.len()).collect();
+        order.sort_by_key(|c| {
+            let (p, q) = scalar_products[*c];
+            std::cmp::Reverse((q.to_u32(), p.to_u32()))
+        });
+        let mut column = vec![0; scalar_products.len()];
+        for (i, c) in order.iter().enumerate() {
+            column[*c] = i;
+        }
+
+        let n_sp = scalar_products.len();
+        let mut m = Matrix::new(rows.len() as u32, (n_sp + others.len()) as u32, field);
+        for (r, row) in rows.into_iter().enumerate() {
+            for (col, coeff) in row {
+                let col = match col {
+                    Ok(c) => column[c],
+                    Err(c) => n_sp + c,
+                };
+                let e = &mut m[(r as u32, col as u32)];
+                *e = field.add(e, &coeff);
+            }
+        }
+
+        let pivots = m.row_reduce(n_sp as u32);
+
+        let mut rules = RuleSet::new();
+        for (r, pivot) in pivots.into_iter().enumerate() {
+            let (p, q) = scalar_products[order[pivot as usize]];
+
+            // start with a zero, so that the sum is never empty
+            let zero = num(0, 1, workspace);
+            let mut rhs_sum = workspace.new_atom();
+            let add = rhs_sum.get_mut().transform_to_add();
+            add.extend(zero.get().to_view());
+            for c in pivot as usize + 1..n_sp + others.len() {
+                let coeff = &m[(r as u32, c as u32)];
+                if RationalField::is_zero(coeff) {
+                    continue;
+                }
+
+                let mut n = workspace.new_atom();
+                n.get_mut()
+                    .transform_to_num()
+                    .set_from_number(Number::from(field.neg(coeff)));
+
+                let mut term = workspace.new_atom();
+                let mul = term.get_mut().transform_to_mul();
+                mul.extend(n.get().to_view());
+                if c < n_sp {
+                    let (p, q) = scalar_products[order[c]];
+                    let mut sp = workspace.new_atom();
+                    self.scalar_product(p, q, workspace, sp.get_mut());
+                    mul.extend(sp.get().to_view());
+                } else {
+                    mul.extend(others[c - n_sp].to_view());
+                }
+                mul.set_dirty(true);
+                add.extend(term.get().to_view());
+            }
+            add.set_dirty(true);
+
+            let mut rhs = workspace.new_atom();
+            rhs_sum
+                .get()
+                .to_view()
+                .normalize(workspace, state, rhs.get_mut());
+            let mut lhs = workspace.new_atom();
+            self.scalar_product(p, q, workspace, lhs.get_mut());
+
+            rules.add_rule(Rule {
+                name: format!(
+                    "relation {}.{}",
+                    state.get_name(p).unwrap(),
+                    state.get_name(q).unwrap()
+                ),
+                lhs: Pattern::from_view(lhs.get().to_view(), state),
+                rhs: Pattern::from_view(rhs.get().to_view(), state),
+                restrictions: HashMap::default(),
+                priority: 0,
+            });
+        }
+
+        Ok(rules)
+    }
+
+    /// Split a term of a relation into its rational coefficient, the scalar product
+    /// it contains and the remaining factors.
+    fn split_term<P: Atom>(
+        &self,
+        term: AtomView<'_, P>,
+        workspace: &Workspace<P>,
+        state: &State,
+    ) -> Result<RelationTerm<P>, String> {
+        let factors: Vec<_> = match term {
+            AtomView::Mul(m) => m.iter().collect(),
+            x => vec![x],
+        };
+
+        let mut coeff = Rational::Natural(1, 1);
+        let mut sp = None;
+        let mut has_rest = false;
+        let one = num(1, 1, workspace);
+        let mut rest = workspace.new_atom();
+        let mul = rest.get_mut().transform_to_mul();
+        mul.extend(one.get().to_view());
+        for f in factors {
+            match f {
+                AtomView::Num(n) => {
+                    coeff = match n.get_number_view() {
+                        BorrowedNumber::Natural(n, d) => Rational::Natural(n, d),
+                        BorrowedNumber::Large(r) => Rational::Large(r.to_rat()),
+                        _ => return Err("Relations must have rational coefficients".into()),
+                    };
+                }
+                AtomView::Fun(g) if g.get_name() == self.dot => {
+                    let mut args = g.iter();
+                    let (Some(AtomView::Var(p)), Some(AtomView::Var(q)), None) =
+                        (args.next(), args.next(), args.next())
+                    else {
+                        return Err(
+                            "Scalar products must be of linear combinations of momenta".into()
+                        );
+                    };
+                    if sp.is_some() {
+                        return Err("Relations must be linear in the scalar products".into());
+                    }
+                    sp = Some((p.get_name(), q.get_name()));
+                }
+                AtomView::Pow(p) if matches!(p.get_base_exp().0, AtomView::Fun(g) if g.get_name() == self.dot) =>
+                {
+                    return Err("Relations must be linear in the scalar products".into());
+                }
+                _ => {
+                    mul.extend(f);
+                    has_rest = true;
+                }
+            }
+        }
+        mul.set_dirty(true);
+
+        if sp.is_some() && has_rest {
+            return Err("Scalar products must have rational coefficients in relations".into());
+        }
+
+        let mut out = OwnedAtom::new();
+        rest.get().to_view().normalize(workspace, state, &mut out);
+        Ok((coeff, sp, out))
+    }
+
+    /// Write the expression in a canonical form, by expanding the scalar products, applying
+    /// the rules of [`Kinematics::get_rules`] and the rules `relations` obtained from
+    /// [`Kinematics::solve_relations`], and expanding the result.
+    pub fn canonicalize<P: Atom>(
+        &self,
+        expr: AtomView<'_, P>,
+        relations: &RuleSet<P>,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let mut expanded = workspace.new_atom();
+        self.expand_scalar_products(expr, workspace, state, expanded.get_mut());
+
+        let mut replaced = workspace.new_atom();
+        self.get_rules(workspace, state).apply(
+            expanded.get().to_view(),
+            state,
+            workspace,
+            replaced.get_mut(),
+            None,
+        );
+
+        let mut solved = workspace.new_atom();
+        relations.apply(
+            replaced.get().to_view(),
+            state,
+            workspace,
+            solved.get_mut(),
+            None,
+        );
+        solved.get().to_view().expand(workspace, state, out);
+    }
+}
+
+/// Write a linear combination of momenta with rational coefficients as a list of momenta
+/// and their coefficients, where `None` is a coefficient of one.
+fn momentum_terms<P: Atom>(
+    expr: AtomView<'_, P>,
+) -> Option<Vec<(Identifier, Option<AtomView<'_, P>>)>> {
+    fn term<P: Atom>(t: AtomView<'_, P>) -> Option<(Identifier, Option<AtomView<'_, P>>)> {
+        match t {
+            AtomView::Var(v) => Some((v.get_name(), None)),
+            AtomView::Mul(m) if m.get_nargs() == 2 => {
+                let mut it = m.iter();
+                match (it.next().unwrap(), it.next().unwrap()) {
+                    (AtomView::Var(v), n @ AtomView::Num(_))
+                    | (n @ AtomView::Num(_), AtomView::Var(v)) => Some((v.get_name(), Some(n))),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    match expr {
+        AtomView::Add(a) => a.iter().map(term).collect(),
+        x => Some(vec![term(x)?]),
+    }
+}
+
+fn var<P: Atom>(id: Identifier, workspace: &Workspace<P>) -> BufferHandle<'_, OwnedAtom<P>> {
+    let mut h = workspace.new_atom();
+    h.get_mut().transform_to_var().set_from_id(id);
+    h
+}
+
+fn num<P: Atom>(num: i64, den: i64, workspace: &Workspace<P>) -> BufferHandle<'_, OwnedAtom<P>> {
+    let mut h = workspace.new_atom();
+    h.get_mut()
+        .transform_to_num()
+        .set_from_number(Number::Natural(num, den));
+    h
+}
+
+/// Write the Källén function `λ(a,b,c) = a^2 + b^2 + c^2 - 2ab - 2ac - 2bc` into `out`.
+/// For a decay of a particle with mass `M` into particles with masses `m1` and `m2`,
+/// the momentum of the decay products in the rest frame is `sqrt(λ(M^2,m1^2,m2^2))/(2M)`.
+pub fn kallen<'a, P: Atom>(
+    a: AtomView<'a, P>,
+    b: AtomView<'a, P>,
+    c: AtomView<'a, P>,
+    workspace: &Workspace<P>,
+    state: &State,
+    out: &mut OwnedAtom<P>,
+) {
+    let two = num(2, 1, workspace);
+    let minus_two = num(-2, 1, workspace);
+
+    let mut squares = a.builder(state, workspace).lazy().pow(two.get().to_view());
+    for x in [b, c] {
+        let mut sq = workspace.new_atom();
+        x.builder(state, workspace)
+            .lazy()
+            .pow(two.get().to_view())
+            .finish(sq.get_mut());
+        squares = squares + sq.get().to_view();
+    }
+
+    for (x, y) in [(a, b), (a, c), (b, c)] {
+        let mut prod = workspace.new_atom();
+        (x.builder(state, workspace).lazy() * y * minus_two.get().to_view()).finish(prod.get_mut());
+        squares = squares + prod.get().to_view();
+    }
+
+    let mut res = workspace.new_atom();
+    squares.finish(res.get_mut());
+    res.get().to_view().expand(workspace, state, out);
+}
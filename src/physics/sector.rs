@@ -0,0 +1,282 @@
+use crate::{
+    poly::{polynomial::MultivariatePolynomial, Exponent},
+    representations::{
+        number::Number, Atom, AtomView, OwnedAdd, OwnedAtom, OwnedMul, OwnedNum, OwnedPow, OwnedVar,
+    },
+    rings::integer::IntegerRing,
+    state::{State, Workspace},
+};
+
+/// A sector of a sector decomposition, representing the integral over the
+/// unit hypercube of
+/// ```text
+/// prod_j t_j^c_j * prod_k (t^m_k * P_k(t))^a_k
+/// ```
+/// where the polynomials `P_k` have a constant term, so that all
+/// singularities at `t_j = 0` are contained in the monomials.
+#[derive(Clone, Debug)]
+pub struct Sector<E: Exponent> {
+    /// The integration variable that was set to one in the primary sector.
+    pub primary: usize,
+    /// The exponents `c_j` of the integration variables.
+    pub exponents: Vec<i64>,
+    /// The polynomials `P_k` and the exponents of their monomial prefactor `m_k`.
+    pub factors: Vec<(MultivariatePolynomial<IntegerRing, E>, Vec<u32>)>,
+}
+
+impl<E: Exponent> Sector<E> {
+    /// Get the exponent of the integration variable `var` that comes from the
+    /// monomial prefactors, as a sum of the integer `exponents` and the multiples of
+    /// the powers `a_k` of the factors.
+    pub fn get_exponent(&self, var: usize) -> (i64, Vec<u32>) {
+        (
+            self.exponents[var],
+            self.factors.iter().map(|(_, m)| m[var]).collect(),
+        )
+    }
+
+    /// Write the integrand of the sector as an atom, using `powers` as the
+    /// powers `a_k` of the factors. The primary variable is omitted.
+    pub fn to_atom<P: Atom>(
+        &self,
+        powers: &[AtomView<P>],
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let var_map = self.factors[0]
+            .0
+            .var_map
+            .as_ref()
+            .expect("No variable map present in polynomial");
+
+        let mut mul_h = workspace.new_atom();
+        let mul = mul_h.transform_to_mul();
+
+        for (j, var) in var_map.iter().enumerate().take(self.exponents.len()) {
+            if j == self.primary {
+                continue;
+            }
+
+            let (c, m) = self.get_exponent(j);
+            if c == 0 && m.iter().all(|x| *x == 0) {
+                continue;
+            }
+
+            let mut exp_h = workspace.new_atom();
+            let exp = exp_h.transform_to_add();
+            let mut num_h = workspace.new_atom();
+            num_h
+                .transform_to_num()
+                .set_from_number(Number::Natural(c, 1));
+            exp.extend(num_h.get().to_view());
+
+            for (k, p) in m.iter().zip(powers) {
+                if *k > 0 {
+                    let mut term_h = workspace.new_atom();
+                    let term = term_h.transform_to_mul();
+                    num_h
+                        .transform_to_num()
+                        .set_from_number(Number::Natural(*k as i64, 1));
+                    term.extend(num_h.get().to_view());
+                    term.extend(*p);
+                    term.set_dirty(true);
+                    exp.extend(term_h.get().to_view());
+                }
+            }
+            exp.set_dirty(true);
+
+            let mut var_h = workspace.new_atom();
+            var_h.transform_to_var().set_from_id(*var);
+            let mut pow_h = workspace.new_atom();
+            let pow = pow_h.transform_to_pow();
+            pow.set_from_base_and_exp(var_h.get().to_view(), exp_h.get().to_view());
+            pow.set_dirty(true);
+            mul.extend(pow_h.get().to_view());
+        }
+
+        for ((poly, _), p) in self.factors.iter().zip(powers) {
+            let mut poly_h = workspace.new_atom();
+            poly_h.from_polynomial(workspace, state, poly);
+            let mut pow_h = workspace.new_atom();
+            let pow = pow_h.transform_to_pow();
+            pow.set_from_base_and_exp(poly_h.get().to_view(), *p);
+            pow.set_dirty(true);
+            mul.extend(pow_h.get().to_view());
+        }
+
+        mul.set_dirty(true);
+        mul_h.get().to_view().normalize(workspace, state, out);
+    }
+}
+
+/// Decompose the projective integral
+/// ```text
+/// int_{x_j >= 0} prod_j dx_j x_j^c_j delta(1 - sum_j x_j) prod_k P_k(x)^a_k
+/// ```
+/// into sectors in which all singularities at the boundary of the integration
+/// domain are factorized into monomials, using iterated sector decomposition.
+/// The integration variables are the first `exponents.len()` variables of the
+/// homogeneous polynomials `P_k`, and the other variables are treated as parameters.
+/// The powers `a_k` must be such that the integrand is projectively invariant, which
+/// is the case for the Symanzik polynomials of a Feynman integral.
+pub fn sector_decomposition<E: Exponent>(
+    polynomials: &[MultivariatePolynomial<IntegerRing, E>],
+    exponents: &[i64],
+) -> Vec<Sector<E>> {
+    let n = exponents.len();
+
+    let mut todo = vec![];
+    for primary in 0..n {
+        let factors = polynomials
+            .iter()
+            .map(|p| {
+                let p = substitute(p, n, |e| {
+                    let mut e = e.to_vec();
+                    e[primary] = E::zero();
+                    e
+                });
+                (p, vec![0; n])
+            })
+            .collect();
+
+        let mut exponents = exponents.to_vec();
+        exponents[primary] = 0;
+        todo.push(Sector {
+            primary,
+            exponents,
+            factors,
+        });
+    }
+
+    let mut sectors = vec![];
+    while let Some(mut sector) = todo.pop() {
+        for (p, m) in &mut sector.factors {
+            factor_monomial(p, m, n);
+        }
+
+        let unresolved = sector
+            .factors
+            .iter()
+            .find(|(p, _)| !p.is_zero() && !has_constant_term(p, n));
+
+        let set = match unresolved {
+            Some((p, _)) => vanishing_set(p, n, sector.primary),
+            None => {
+                sectors.push(sector);
+                continue;
+            }
+        };
+
+        // for every k in the set, map t_j -> t_k * t_j for all other j in the set
+        for &k in &set {
+            let others: Vec<_> = set.iter().filter(|j| **j != k).cloned().collect();
+
+            let mut s = sector.clone();
+            s.exponents[k] += others.iter().map(|j| s.exponents[*j]).sum::<i64>();
+            s.exponents[k] += others.len() as i64;
+
+            for (p, m) in &mut s.factors {
+                m[k] += others.iter().map(|j| m[*j]).sum::<u32>();
+                *p = substitute(p, n, |e| {
+                    let mut e = e.to_vec();
+                    for j in &others {
+                        let ej = e[*j];
+                        e[k] += ej;
+                    }
+                    e
+                });
+            }
+
+            todo.push(s);
+        }
+    }
+
+    sectors
+}
+
+/// Rebuild the polynomial by mapping the exponents of every term.
+fn substitute<E: Exponent>(
+    p: &MultivariatePolynomial<IntegerRing, E>,
+    n: usize,
+    map: impl Fn(&[E]) -> Vec<E>,
+) -> MultivariatePolynomial<IntegerRing, E> {
+    let mut res = MultivariatePolynomial::new(
+        p.nvars,
+        p.field,
+        Some(p.nterms),
+        p.var_map.as_ref().map(|x| x.as_slice()),
+    );
+
+    for t in 0..p.nterms {
+        let mut e = map(&p.exponents(t)[..n]);
+        e.extend_from_slice(&p.exponents(t)[n..]);
+        res.append_monomial(p.coefficients[t].clone(), &e);
+    }
+    res
+}
+
+/// Divide the polynomial by the largest monomial in the first `n` variables that
+/// divides it, and add its exponents to `monomial`.
+fn factor_monomial<E: Exponent>(
+    p: &mut MultivariatePolynomial<IntegerRing, E>,
+    monomial: &mut [u32],
+    n: usize,
+) {
+    if p.is_zero() {
+        return;
+    }
+
+    let min: Vec<_> = (0..n)
+        .map(|j| (0..p.nterms).map(|t| p.exponents(t)[j]).min().unwrap())
+        .collect();
+
+    if min.iter().all(|e| *e == E::zero()) {
+        return;
+    }
+
+    for (m, e) in monomial.iter_mut().zip(&min) {
+        *m += e.to_u32();
+    }
+
+    *p = substitute(p, n, |e| e.iter().zip(&min).map(|(a, b)| *a - *b).collect());
+}
+
+/// Check if the polynomial has a term without integration variables.
+fn has_constant_term<E: Exponent>(p: &MultivariatePolynomial<IntegerRing, E>, n: usize) -> bool {
+    (0..p.nterms).any(|t| p.exponents(t)[..n].iter().all(|e| *e == E::zero()))
+}
+
+/// Find a smallest set of integration variables such that the polynomial vanishes
+/// when all of them are zero, i.e., every term contains one of them.
+fn vanishing_set<E: Exponent>(
+    p: &MultivariatePolynomial<IntegerRing, E>,
+    n: usize,
+    primary: usize,
+) -> Vec<usize> {
+    let vars: Vec<_> = (0..n).filter(|j| *j != primary).collect();
+
+    for size in 2..=vars.len() {
+        let mut set: Vec<_> = (0..size).collect();
+        loop {
+            if (0..p.nterms).all(|t| set.iter().any(|j| p.exponents(t)[vars[*j]] > E::zero())) {
+                return set.iter().map(|j| vars[*j]).collect();
+            }
+
+            // go to the next subset of the given size
+            let mut i = size;
+            while i > 0 && set[i - 1] == vars.len() - size + i - 1 {
+                i -= 1;
+            }
+            if i == 0 {
+                break;
+            }
+            set[i - 1] += 1;
+            for l in i..size {
+                set[l] = set[l - 1] + 1;
+            }
+        }
+    }
+
+    unreachable!("The polynomial does not vanish at the boundary")
+}
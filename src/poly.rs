@@ -1,25 +1,39 @@
+pub mod compressed;
+pub mod dispatch;
+pub mod factor;
 pub mod gcd;
+pub mod groebner;
+pub mod horner;
+pub mod monomial_pattern;
+pub mod orthogonal;
 pub mod polynomial;
+pub mod positivity;
+pub mod real_roots;
+pub mod symmetric;
 
 use std::borrow::Cow;
 use std::fmt::{Debug, Display};
 use std::hash::Hash;
-use std::ops::{Add as OpAdd, AddAssign, Div, Mul as OpMul, Neg, Sub};
+use std::ops::{Add as OpAdd, AddAssign, BitAnd, Div, Mul as OpMul, Neg, Sub};
 
 use rug::{Complete, Integer as ArbitraryPrecisionInteger};
 use smallvec::{smallvec, SmallVec};
 use smartstring::{LazyCompact, SmartString};
 
-use crate::parser::{parse_polynomial, Operator, Token};
+use ahash::HashMap;
+
+use crate::id::Pattern;
+use crate::parser::{parse, parse_polynomial, Operator, Token};
 use crate::representations::number::{BorrowedNumber, ConvertToRing, Number};
 use crate::representations::{
-    Add, Atom, AtomView, Identifier, Mul, Num, OwnedAdd, OwnedAtom, OwnedMul, OwnedNum, OwnedPow,
-    OwnedVar, Pow, Var,
+    Add, Atom, AtomView, Fun, Identifier, Mul, Num, OwnedAdd, OwnedAtom, OwnedFun, OwnedMul,
+    OwnedNum, OwnedPow, OwnedVar, Pow, Var,
 };
 use crate::rings::integer::{Integer, IntegerRing};
+use crate::rings::rational::RationalField;
 use crate::rings::rational_polynomial::{FromNumeratorAndDenominator, RationalPolynomial};
 use crate::rings::{EuclideanDomain, Ring};
-use crate::state::{State, Workspace};
+use crate::state::{ResettableBuffer, State, Workspace};
 use crate::utils;
 
 use self::gcd::PolynomialGCD;
@@ -27,6 +41,9 @@ use self::polynomial::MultivariatePolynomial;
 
 pub const INLINED_EXPONENTS: usize = 6;
 
+/// The type of the exponents of a polynomial. Exponents are `Send + Sync` for the same
+/// reason as the [`Ring`] bounds: polynomials are multiplied in parallel by the
+/// multiplication operator.
 pub trait Exponent:
     Hash
     + Debug
@@ -41,6 +58,8 @@ pub trait Exponent:
     + Copy
     + PartialEq
     + Eq
+    + Send
+    + Sync
 {
     fn zero() -> Self;
     fn one() -> Self;
@@ -52,6 +71,23 @@ pub trait Exponent:
     fn checked_add(&self, other: &Self) -> Option<Self>;
     fn gcd(&self, other: &Self) -> Self;
 
+    /// Convert from the power `num/den` of a variable, or return `None` if the power
+    /// cannot be represented. This function may panic if the exponent is too large.
+    #[inline]
+    fn from_fraction(num: u32, den: u32) -> Option<Self> {
+        if den == 1 {
+            Some(Self::from_u32(num))
+        } else {
+            None
+        }
+    }
+
+    /// Convert the exponent to the power `(num, den)` of a variable, with `num/den` in lowest terms.
+    #[inline]
+    fn to_fraction(&self) -> (u32, u32) {
+        (self.to_u32(), 1)
+    }
+
     /// Pack a list of exponents into a number, such that arithmetic and
     /// comparisons can be performed. The caller must guarantee that:
     /// - the list is no longer than 8 entries
@@ -65,8 +101,40 @@ pub trait Exponent:
     /// - each entry is not larger than 2^16 - 1
     fn pack_u16(list: &[Self]) -> u64;
     fn unpack_u16(n: u64, out: &mut [Self]);
+
+    /// Pack a list of exponents into a number, such that arithmetic and
+    /// comparisons can be performed. The caller must guarantee that:
+    /// - the list is no longer than 16 entries
+    /// - each entry is not larger than 255
+    fn pack_u128(list: &[Self]) -> u128;
+    fn unpack_u128(n: u128, out: &mut [Self]);
+}
+
+/// An unsigned integer that holds packed exponents.
+pub trait PackedExponents: Copy + Ord + OpAdd<Output = Self> + BitAnd<Output = Self> {
+    fn zero() -> Self;
+    fn wrapping_sub(self, other: Self) -> Self;
+}
+
+macro_rules! impl_packed_exponents {
+    ($ty: ty) => {
+        impl PackedExponents for $ty {
+            #[inline]
+            fn zero() -> Self {
+                0
+            }
+
+            #[inline]
+            fn wrapping_sub(self, other: Self) -> Self {
+                <$ty>::wrapping_sub(self, other)
+            }
+        }
+    };
 }
 
+impl_packed_exponents!(u64);
+impl_packed_exponents!(u128);
+
 macro_rules! impl_exponent {
     ($ty: ty) => {
         impl Exponent for $ty {
@@ -145,6 +213,21 @@ macro_rules! impl_exponent {
                     *o = ss.swap_bytes();
                 }
             }
+
+            fn pack_u128(list: &[Self]) -> u128 {
+                let mut num: u128 = 0;
+                for x in list.iter().rev() {
+                    num = (num << 8) + (*x as u8 as u128);
+                }
+                num.swap_bytes()
+            }
+
+            fn unpack_u128(mut n: u128, out: &mut [Self]) {
+                n = n.swap_bytes();
+                for (i, o) in out.iter_mut().enumerate() {
+                    *o = ((n >> (8 * i)) & 255) as Self;
+                }
+            }
         }
     };
 }
@@ -153,6 +236,237 @@ impl_exponent!(u32);
 impl_exponent!(u16);
 impl_exponent!(u8);
 
+/// An exponent that is a multiple of `1/D`, which allows for polynomials with
+/// fractional powers such as Puiseux series in `x^(1/D)`.
+///
+/// The exponent is stored as its numerator and all polynomial routines treat it as
+/// an integer power of `x^(1/D)`. This means that [`Exponent::to_u32`], [`Exponent::from_u32`]
+/// and [`Exponent::one`] work in units of `1/D`. Only the conversion from and to expressions
+/// and the printing use the actual power of the variable, see [`Exponent::from_fraction`]
+/// and [`Exponent::to_fraction`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct FractionalExponent<const D: u32>(u32);
+
+impl<const D: u32> FractionalExponent<D> {
+    /// Create the exponent `num/den`. Panics if `num/den` is not a multiple of `1/D`.
+    pub fn new(num: u32, den: u32) -> Self {
+        Self::from_fraction(num, den)
+            .unwrap_or_else(|| panic!("Exponent {}/{} is not a multiple of 1/{}", num, den, D))
+    }
+
+    /// Get the exponent in units of `1/D`.
+    pub fn numerator(&self) -> u32 {
+        self.0
+    }
+}
+
+impl<const D: u32> Display for FractionalExponent<D> {
+    /// Print the exponent as a fraction in parentheses, so that it
+    /// can be used in the exponent of a power.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.to_fraction() {
+            (n, 1) => write!(f, "{}", n),
+            (n, d) => write!(f, "({}/{})", n, d),
+        }
+    }
+}
+
+impl<const D: u32> OpAdd for FractionalExponent<D> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl<const D: u32> AddAssign for FractionalExponent<D> {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl<const D: u32> Sub for FractionalExponent<D> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl<const D: u32> OpMul for FractionalExponent<D> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self(self.0 * rhs.0)
+    }
+}
+
+impl<const D: u32> Div for FractionalExponent<D> {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, rhs: Self) -> Self {
+        Self(self.0 / rhs.0)
+    }
+}
+
+impl<const D: u32> FractionalExponent<D> {
+    #[inline]
+    fn as_u32_slice(list: &[Self]) -> &[u32] {
+        // the exponent is a transparent wrapper around u32
+        unsafe { std::slice::from_raw_parts(list.as_ptr() as *const u32, list.len()) }
+    }
+
+    #[inline]
+    fn as_u32_slice_mut(list: &mut [Self]) -> &mut [u32] {
+        unsafe { std::slice::from_raw_parts_mut(list.as_mut_ptr() as *mut u32, list.len()) }
+    }
+}
+
+impl<const D: u32> Exponent for FractionalExponent<D> {
+    #[inline]
+    fn zero() -> Self {
+        Self(0)
+    }
+
+    #[inline]
+    fn one() -> Self {
+        Self(1)
+    }
+
+    #[inline]
+    fn to_u32(&self) -> u32 {
+        self.0
+    }
+
+    #[inline]
+    fn from_u32(n: u32) -> Self {
+        Self(n)
+    }
+
+    #[inline]
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    #[inline]
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    #[inline]
+    fn gcd(&self, other: &Self) -> Self {
+        Self(utils::gcd_unsigned(self.0 as u64, other.0 as u64) as u32)
+    }
+
+    fn from_fraction(num: u32, den: u32) -> Option<Self> {
+        let n = num as u64 * D as u64;
+        if !n.is_multiple_of(den as u64) {
+            return None;
+        }
+
+        let n = n / den as u64;
+        assert!(
+            n <= u32::MAX as u64,
+            "Exponent {}/{} too large for denominator {}",
+            num,
+            den,
+            D
+        );
+        Some(Self(n as u32))
+    }
+
+    fn to_fraction(&self) -> (u32, u32) {
+        let g = utils::gcd_unsigned(self.0 as u64, D as u64) as u32;
+        (self.0 / g, D / g)
+    }
+
+    fn pack(list: &[Self]) -> u64 {
+        u32::pack(Self::as_u32_slice(list))
+    }
+
+    fn unpack(n: u64, out: &mut [Self]) {
+        u32::unpack(n, Self::as_u32_slice_mut(out))
+    }
+
+    fn pack_u16(list: &[Self]) -> u64 {
+        u32::pack_u16(Self::as_u32_slice(list))
+    }
+
+    fn unpack_u16(n: u64, out: &mut [Self]) {
+        u32::unpack_u16(n, Self::as_u32_slice_mut(out))
+    }
+
+    fn pack_u128(list: &[Self]) -> u128 {
+        u32::pack_u128(Self::as_u32_slice(list))
+    }
+
+    fn unpack_u128(n: u128, out: &mut [Self]) {
+        u32::unpack_u128(n, Self::as_u32_slice_mut(out))
+    }
+}
+
+/// Get a canonical variable map of all variables that occur in `atoms`, sorted by identifier,
+/// such that polynomials constructed from these atoms have a consistent variable ordering.
+/// Arguments of functions are not scanned.
+pub fn get_var_map<P: Atom>(
+    atoms: &[AtomView<'_, P>],
+) -> SmallVec<[Identifier; INLINED_EXPONENTS]> {
+    fn collect<P: Atom>(
+        atom: AtomView<'_, P>,
+        vars: &mut SmallVec<[Identifier; INLINED_EXPONENTS]>,
+    ) {
+        match atom {
+            AtomView::Num(_) | AtomView::Fun(_) => {}
+            AtomView::Var(v) => {
+                if !vars.contains(&v.get_name()) {
+                    vars.push(v.get_name());
+                }
+            }
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                collect(base, vars);
+                collect(exp, vars);
+            }
+            AtomView::Mul(m) => {
+                for arg in m.iter() {
+                    collect(arg, vars);
+                }
+            }
+            AtomView::Add(a) => {
+                for arg in a.iter() {
+                    collect(arg, vars);
+                }
+            }
+        }
+    }
+
+    let mut vars = SmallVec::new();
+    for a in atoms {
+        collect(*a, &mut vars);
+    }
+    vars.sort();
+    vars
+}
+
+/// Convert the expressions `atoms` to polynomials that share the
+/// variable map constructed by [`get_var_map`].
+pub fn to_polynomials<P: Atom, R: Ring + ConvertToRing, E: Exponent>(
+    atoms: &[AtomView<'_, P>],
+    field: R,
+) -> Result<Vec<MultivariatePolynomial<R, E>>, &'static str> {
+    let var_map = get_var_map(atoms);
+    atoms
+        .iter()
+        .map(|a| a.to_polynomial(field, Some(&var_map)))
+        .collect()
+}
+
 impl<'a, P: Atom> AtomView<'a, P> {
     /// Convert an expression to a polynomial.
     ///
@@ -163,7 +477,7 @@ impl<'a, P: Atom> AtomView<'a, P> {
         field: R,
         var_map: Option<&[Identifier]>,
     ) -> Result<MultivariatePolynomial<R, E>, &'static str> {
-        fn check_factor<P: Atom>(
+        fn check_factor<P: Atom, E: Exponent>(
             factor: &AtomView<'_, P>,
             vars: &mut SmallVec<[Identifier; INLINED_EXPONENTS]>,
             allow_new_vars: bool,
@@ -208,7 +522,11 @@ impl<'a, P: Atom> AtomView<'a, P> {
                     match exp {
                         AtomView::Num(n) => match n.get_number_view() {
                             BorrowedNumber::Natural(n, d) => {
-                                if d == 1 && n >= 0 && n <= u32::MAX as i64 {
+                                if n >= 0
+                                    && n <= u32::MAX as i64
+                                    && d <= u32::MAX as i64
+                                    && E::from_fraction(n as u32, d as u32).is_some()
+                                {
                                     Ok(())
                                 } else {
                                     Err("Exponent negative or a fraction")
@@ -228,6 +546,9 @@ impl<'a, P: Atom> AtomView<'a, P> {
                             BorrowedNumber::RationalPolynomial(_) => {
                                 Err("Rational polynomial not supported in conversion routine")
                             }
+                            BorrowedNumber::AlgebraicNumber(_, _) => {
+                                Err("Algebraic number not supported in conversion routine")
+                            }
                         },
                         _ => Err("base must be a variable"),
                     }
@@ -237,7 +558,7 @@ impl<'a, P: Atom> AtomView<'a, P> {
             }
         }
 
-        fn check_term<P: Atom>(
+        fn check_term<P: Atom, E: Exponent>(
             term: &AtomView<'_, P>,
             vars: &mut SmallVec<[Identifier; INLINED_EXPONENTS]>,
             allow_new_vars: bool,
@@ -245,11 +566,11 @@ impl<'a, P: Atom> AtomView<'a, P> {
             match term {
                 AtomView::Mul(m) => {
                     for factor in m.iter() {
-                        check_factor(&factor, vars, allow_new_vars)?;
+                        check_factor::<_, E>(&factor, vars, allow_new_vars)?;
                     }
                     Ok(())
                 }
-                _ => check_factor(term, vars, allow_new_vars),
+                _ => check_factor::<_, E>(term, vars, allow_new_vars),
             }
         }
 
@@ -260,12 +581,12 @@ impl<'a, P: Atom> AtomView<'a, P> {
         match self {
             AtomView::Add(a) => {
                 for term in a.iter() {
-                    check_term(&term, &mut vars, var_map.is_none())?;
+                    check_term::<_, E>(&term, &mut vars, var_map.is_none())?;
                     n_terms += 1;
                 }
             }
             _ => {
-                check_term(self, &mut vars, var_map.is_none())?;
+                check_term::<_, E>(self, &mut vars, var_map.is_none())?;
                 n_terms += 1;
             }
         }
@@ -286,7 +607,8 @@ impl<'a, P: Atom> AtomView<'a, P> {
                 }
                 AtomView::Var(v) => {
                     let id = v.get_name();
-                    exponents[vars.iter().position(|v| *v == id).unwrap()] += E::one();
+                    exponents[vars.iter().position(|v| *v == id).unwrap()] +=
+                        E::from_fraction(1, 1).unwrap();
                 }
                 AtomView::Pow(p) => {
                     let (base, exp) = p.get_base_exp();
@@ -301,12 +623,14 @@ impl<'a, P: Atom> AtomView<'a, P> {
 
                     match exp {
                         AtomView::Num(n) => match n.get_number_view() {
-                            BorrowedNumber::Natural(r, _) => {
-                                exponents[var_index] += E::from_u32(r as u32)
+                            BorrowedNumber::Natural(r, d) => {
+                                exponents[var_index] +=
+                                    E::from_fraction(r as u32, d as u32).unwrap()
                             }
                             BorrowedNumber::Large(r) => {
                                 exponents[var_index] +=
-                                    E::from_u32(r.to_rat().numer().to_u32().unwrap())
+                                    E::from_fraction(r.to_rat().numer().to_u32().unwrap(), 1)
+                                        .unwrap()
                             }
                             _ => unreachable!(),
                         },
@@ -473,10 +797,11 @@ impl<P: Atom> OwnedAtom<P> {
                     let var = var_h.transform_to_var();
                     var.set_from_id(var_id);
 
-                    if pow > E::one() {
+                    let (pow_num, pow_den) = pow.to_fraction();
+                    if (pow_num, pow_den) != (1, 1) {
                         let mut num_h = workspace.new_atom();
                         let num = num_h.transform_to_num();
-                        num.set_from_number(Number::Natural(pow.to_u32() as i64, 1));
+                        num.set_from_number(Number::Natural(pow_num as i64, pow_den as i64));
 
                         let mut pow_h = workspace.new_atom();
                         let pow = pow_h.transform_to_pow();
@@ -508,6 +833,389 @@ impl<P: Atom> OwnedAtom<P> {
     }
 }
 
+impl<P: Atom> OwnedAtom<P> {
+    /// Convert a rational polynomial to an expression `num * den^-1`.
+    pub fn from_rational_polynomial<E: Exponent>(
+        &mut self,
+        workspace: &Workspace<P>,
+        state: &State,
+        poly: &RationalPolynomial<IntegerRing, E>,
+    ) {
+        if poly.denominator.is_one() {
+            self.from_polynomial(workspace, state, &poly.numerator);
+            return;
+        }
+
+        let mut num_h = workspace.new_atom();
+        num_h
+            .get_mut()
+            .from_polynomial(workspace, state, &poly.numerator);
+
+        let mut den_h = workspace.new_atom();
+        den_h
+            .get_mut()
+            .from_polynomial(workspace, state, &poly.denominator);
+
+        let mut exp_h = workspace.new_atom();
+        let exp = exp_h.transform_to_num();
+        exp.set_from_number(Number::Natural(-1, 1));
+
+        let mut pow_h = workspace.new_atom();
+        let pow = pow_h.transform_to_pow();
+        pow.set_from_base_and_exp(den_h.get().to_view(), exp_h.get().to_view());
+        pow.set_dirty(true);
+
+        let mut mul_h = workspace.new_atom();
+        let mul = mul_h.transform_to_mul();
+        mul.extend(num_h.get().to_view());
+        mul.extend(pow_h.get().to_view());
+        mul.set_dirty(true);
+
+        mul_h.get().to_view().normalize(workspace, state, self);
+    }
+
+    /// Eliminate the variable `var` from the system of equations `exprs = 0`,
+    /// where every expression must be a rational function. The numerators of the
+    /// expressions are combined using resultants, see [`MultivariatePolynomial::eliminate`].
+    pub fn eliminate(
+        exprs: &[AtomView<'_, P>],
+        var: Identifier,
+        workspace: &Workspace<P>,
+        state: &State,
+    ) -> Result<Vec<OwnedAtom<P>>, Cow<'static, str>> {
+        let mut polys = exprs
+            .iter()
+            .map(|e| {
+                e.to_rational_polynomial::<_, _, u16>(
+                    workspace,
+                    state,
+                    RationalField::new(),
+                    IntegerRing::new(),
+                    None,
+                )
+                .map(|r| r.numerator)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // bring all polynomials to the same variable map
+        if let Some((first, rest)) = polys.split_first_mut() {
+            for p in rest.iter_mut() {
+                first.unify_var_map(p);
+            }
+            for p in rest.iter_mut() {
+                first.unify_var_map(p);
+            }
+        }
+
+        let x = polys
+            .first()
+            .and_then(|p| p.var_map.as_ref())
+            .and_then(|vm| vm.iter().position(|v| *v == var));
+
+        let relations = match x {
+            Some(x) => MultivariatePolynomial::eliminate(&polys, x),
+            None => polys,
+        };
+
+        Ok(relations
+            .iter()
+            .map(|r| {
+                let mut out = OwnedAtom::new();
+                out.from_polynomial(workspace, state, r);
+                out
+            })
+            .collect())
+    }
+}
+
+/// A map from temporary variables to the non-polynomial subexpressions they represent,
+/// such as `sin(x)` or `x^(1/2)`. It is filled by
+/// [`AtomView::to_rational_polynomial_with_abstraction`] and used to substitute the
+/// subexpressions back in [`OwnedAtom::from_rational_polynomial_with_abstraction`].
+/// Every temporary variable is a new variable of the state, so that it cannot collide
+/// with a user variable or with the variables of another abstraction.
+pub struct FunctionAbstraction<P: Atom> {
+    map: Vec<(Identifier, OwnedAtom<P>)>,
+}
+
+impl<P: Atom> Default for FunctionAbstraction<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Atom> FunctionAbstraction<P> {
+    pub fn new() -> Self {
+        FunctionAbstraction { map: vec![] }
+    }
+
+    /// Get the list of temporary variables and the subexpressions they represent.
+    pub fn get_map(&self) -> &[(Identifier, OwnedAtom<P>)] {
+        &self.map
+    }
+
+    /// Get the temporary variable for `atom`, creating a fresh one if needed.
+    fn get_or_insert(&mut self, atom: AtomView<'_, P>, state: &mut State) -> Identifier {
+        if let Some((id, _)) = self.map.iter().find(|(_, a)| a.to_view() == atom) {
+            return *id;
+        }
+
+        let id = state.new_temporary_var("__abs");
+        let mut a = OwnedAtom::new();
+        a.from_view(&atom);
+        self.map.push((id, a));
+        id
+    }
+}
+
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Replace all subexpressions that are not polynomial, such as functions and
+    /// powers with a non-integer exponent, by temporary variables.
+    fn abstract_non_polynomial(
+        &self,
+        workspace: &Workspace<P>,
+        state: &mut State,
+        abstraction: &mut FunctionAbstraction<P>,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let mut h = workspace.new_atom();
+        match self {
+            Self::Num(_) | Self::Var(_) => {
+                out.from_view(self);
+                return;
+            }
+            Self::Fun(_) => {
+                let id = abstraction.get_or_insert(*self, state);
+                out.transform_to_var().set_from_id(id);
+                return;
+            }
+            Self::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                let integer_exp = match exp {
+                    Self::Num(n) => match n.get_number_view() {
+                        BorrowedNumber::Natural(_, d) => d == 1,
+                        BorrowedNumber::Large(r) => r.to_rat().is_integer(),
+                        _ => false,
+                    },
+                    _ => false,
+                };
+
+                if !integer_exp {
+                    let id = abstraction.get_or_insert(*self, state);
+                    out.transform_to_var().set_from_id(id);
+                    return;
+                }
+
+                let mut base_h = workspace.new_atom();
+                base.abstract_non_polynomial(workspace, state, abstraction, base_h.get_mut());
+
+                let pow = h.transform_to_pow();
+                pow.set_from_base_and_exp(base_h.get().to_view(), exp);
+                pow.set_dirty(true);
+            }
+            Self::Mul(m) => {
+                let mul = h.transform_to_mul();
+                for arg in m.iter() {
+                    let mut arg_h = workspace.new_atom();
+                    arg.abstract_non_polynomial(workspace, state, abstraction, arg_h.get_mut());
+                    mul.extend(arg_h.get().to_view());
+                }
+                mul.set_dirty(true);
+            }
+            Self::Add(a) => {
+                let add = h.transform_to_add();
+                for arg in a.iter() {
+                    let mut arg_h = workspace.new_atom();
+                    arg.abstract_non_polynomial(workspace, state, abstraction, arg_h.get_mut());
+                    add.extend(arg_h.get().to_view());
+                }
+                add.set_dirty(true);
+            }
+        }
+
+        h.get().to_view().normalize(workspace, state, out);
+    }
+
+    /// Convert an expression to a rational polynomial, where every subexpression that is
+    /// not a rational function, such as `sin(x)` or `f(x,y)`, is replaced by a temporary
+    /// variable. The temporary variables are recorded in `abstraction`, so that
+    /// the subexpressions can be substituted back after the computation.
+    pub fn to_rational_polynomial_with_abstraction<
+        R: EuclideanDomain + ConvertToRing,
+        RO: EuclideanDomain + PolynomialGCD<E>,
+        E: Exponent,
+    >(
+        &self,
+        workspace: &Workspace<P>,
+        state: &mut State,
+        field: R,
+        out_field: RO,
+        abstraction: &mut FunctionAbstraction<P>,
+    ) -> Result<RationalPolynomial<RO, E>, Cow<'static, str>>
+    where
+        RationalPolynomial<RO, E>:
+            FromNumeratorAndDenominator<R, RO, E> + FromNumeratorAndDenominator<RO, RO, E>,
+    {
+        let mut h = workspace.new_atom();
+        self.abstract_non_polynomial(workspace, state, abstraction, h.get_mut());
+        h.get()
+            .to_view()
+            .to_rational_polynomial(workspace, state, field, out_field, None)
+    }
+}
+
+impl<P: Atom> OwnedAtom<P> {
+    /// Convert a rational polynomial to an expression, substituting the temporary
+    /// variables in `abstraction` by the subexpressions they represent.
+    pub fn from_rational_polynomial_with_abstraction<E: Exponent>(
+        &mut self,
+        workspace: &Workspace<P>,
+        state: &State,
+        poly: &RationalPolynomial<IntegerRing, E>,
+        abstraction: &FunctionAbstraction<P>,
+    ) {
+        self.from_rational_polynomial(workspace, state, poly);
+
+        let restrictions = HashMap::default();
+        let mut changed = false;
+        for (id, atom) in &abstraction.map {
+            let mut var_h = workspace.new_atom();
+            var_h.transform_to_var().set_from_id(*id);
+            let lhs = Pattern::from_view(var_h.get().to_view(), state);
+            let rhs = Pattern::from_view(atom.to_view(), state);
+
+            let mut out = workspace.new_atom();
+            if lhs.replace_all(
+                self.to_view(),
+                &rhs,
+                state,
+                workspace,
+                &restrictions,
+                out.get_mut(),
+            ) {
+                std::mem::swap(out.get_mut(), self);
+                changed = true;
+            }
+        }
+
+        if changed {
+            let mut norm = workspace.new_atom();
+            self.to_view().normalize(workspace, state, &mut norm);
+            std::mem::swap(norm.get_mut(), self);
+        }
+    }
+}
+
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Check if the expression has a negative numerical power outside of functions.
+    fn has_denominator(&self) -> bool {
+        match self {
+            Self::Num(_) | Self::Var(_) | Self::Fun(_) => false,
+            Self::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                if let Self::Num(n) = exp {
+                    let negative = match n.get_number_view() {
+                        BorrowedNumber::Natural(n, _) => n < 0,
+                        BorrowedNumber::Large(r) => r.to_rat() < 0,
+                        _ => false,
+                    };
+
+                    if negative {
+                        return true;
+                    }
+                }
+
+                base.has_denominator()
+            }
+            Self::Mul(m) => m.iter().any(|x| x.has_denominator()),
+            Self::Add(a) => a.iter().any(|x| x.has_denominator()),
+        }
+    }
+
+    /// Write nested fractions such as `1/(1/x+1/y)` and `x/(x*y)` in a canonical
+    /// form `num/den`, where `num` and `den` are expanded polynomials without common factors.
+    /// Subexpressions that cannot be converted to a rational polynomial, for example
+    /// because they contain functions, are left intact but their arguments are simplified.
+    /// Returns `true` if the expression changed.
+    pub fn normalize_fractions(
+        &self,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) -> bool {
+        if self.has_denominator() {
+            if let Ok(r) = self.to_rational_polynomial::<_, _, u16>(
+                workspace,
+                state,
+                RationalField::new(),
+                IntegerRing::new(),
+                None,
+            ) {
+                out.from_rational_polynomial(workspace, state, &r);
+                return true;
+            }
+        }
+
+        let mut changed = false;
+        let mut h = workspace.new_atom();
+        match self {
+            Self::Num(_) | Self::Var(_) => {
+                out.from_view(self);
+                return false;
+            }
+            Self::Fun(f) => {
+                let fun = h.transform_to_fun();
+                fun.set_from_name(f.get_name());
+
+                for arg in f.iter() {
+                    let mut arg_h = workspace.new_atom();
+                    changed |= arg.normalize_fractions(workspace, state, arg_h.get_mut());
+                    fun.add_arg(arg_h.get().to_view());
+                }
+                fun.set_dirty(changed);
+            }
+            Self::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+
+                let mut base_h = workspace.new_atom();
+                changed |= base.normalize_fractions(workspace, state, base_h.get_mut());
+                let mut exp_h = workspace.new_atom();
+                changed |= exp.normalize_fractions(workspace, state, exp_h.get_mut());
+
+                let pow = h.transform_to_pow();
+                pow.set_from_base_and_exp(base_h.get().to_view(), exp_h.get().to_view());
+                pow.set_dirty(changed);
+            }
+            Self::Mul(m) => {
+                let mul = h.transform_to_mul();
+                for arg in m.iter() {
+                    let mut arg_h = workspace.new_atom();
+                    changed |= arg.normalize_fractions(workspace, state, arg_h.get_mut());
+                    mul.extend(arg_h.get().to_view());
+                }
+                mul.set_dirty(changed);
+            }
+            Self::Add(a) => {
+                let add = h.transform_to_add();
+                for arg in a.iter() {
+                    let mut arg_h = workspace.new_atom();
+                    changed |= arg.normalize_fractions(workspace, state, arg_h.get_mut());
+                    add.extend(arg_h.get().to_view());
+                }
+                add.set_dirty(changed);
+            }
+        }
+
+        if changed {
+            h.get().to_view().normalize(workspace, state, out);
+        } else {
+            out.from_view(self);
+        }
+
+        changed
+    }
+}
+
 impl Token {
     pub fn to_polynomial<R: Ring + ConvertToRing, E: Exponent>(
         &self,
@@ -538,8 +1246,11 @@ impl Token {
                     field.mul_assign(coefficient, &num);
                 }
                 Token::ID(x) => {
-                    let index = var_name_map.iter().position(|v| v == x).unwrap();
-                    exponents[index] += E::one();
+                    let index = var_name_map
+                        .iter()
+                        .position(|v| v == x)
+                        .ok_or_else(|| format!("Unknown variable {}", x))?;
+                    exponents[index] += E::from_fraction(1, 1).unwrap();
                 }
                 Token::Op(_, _, Operator::Neg, args) => {
                     if args.len() != 1 {
@@ -555,7 +1266,10 @@ impl Token {
                     }
 
                     let var_index = match &args[0] {
-                        Token::ID(v) => var_name_map.iter().position(|v1| v == v1).unwrap(),
+                        Token::ID(v) => var_name_map
+                            .iter()
+                            .position(|v1| v == v1)
+                            .ok_or_else(|| format!("Unknown variable {}", v))?,
                         _ => Err("Unsupported base")?,
                     };
 
@@ -566,13 +1280,13 @@ impl Token {
                         if x < 1 || x > u32::MAX as i64 {
                             Err("Invalid exponent")?;
                         }
-                        exponents[var_index] += E::from_u32(x as u32);
+                        exponents[var_index] += E::from_fraction(x as u32, 1).unwrap();
                     } else {
                         match ArbitraryPrecisionInteger::parse(n) {
                             Ok(x) => {
                                 let p: ArbitraryPrecisionInteger = x.complete();
                                 let exp = p.to_u32().ok_or("Cannot convert to u32")?;
-                                exponents[var_index] += E::from_u32(exp);
+                                exponents[var_index] += E::from_fraction(exp, 1).unwrap();
                             }
                             Err(e) => Err(format!("Could not parse number: {}", e))?,
                         }
@@ -812,3 +1526,81 @@ impl Token {
         }
     }
 }
+
+impl<R: Ring + ConvertToRing, E: Exponent> MultivariatePolynomial<R, E> {
+    /// Parse an expanded polynomial in the variables `var_names` directly into a
+    /// polynomial over `field`, without constructing an intermediate atom.
+    pub fn parse<S: AsRef<str>>(
+        input: &str,
+        var_names: &[S],
+        field: R,
+        state: &mut State,
+    ) -> Result<Self, Cow<'static, str>> {
+        let var_map: Vec<_> = var_names
+            .iter()
+            .map(|v| state.get_or_insert_var(v))
+            .collect();
+        let var_name_map: Vec<SmartString<LazyCompact>> =
+            var_names.iter().map(|v| v.as_ref().into()).collect();
+
+        parse(input)?.to_polynomial(field, &var_map, &var_name_map)
+    }
+}
+
+impl<RO: EuclideanDomain + PolynomialGCD<E>, E: Exponent> RationalPolynomial<RO, E> {
+    /// Parse a rational polynomial in the variables `var_names`, with coefficients
+    /// read in `field` and converted to `out_field`. Intermediate atoms are only
+    /// constructed for subexpressions that the fast routines cannot handle.
+    pub fn parse<P: Atom, R: EuclideanDomain + ConvertToRing, S: AsRef<str>>(
+        input: &str,
+        var_names: &[S],
+        field: R,
+        out_field: RO,
+        workspace: &Workspace<P>,
+        state: &mut State,
+    ) -> Result<Self, Cow<'static, str>>
+    where
+        Self: FromNumeratorAndDenominator<R, RO, E> + FromNumeratorAndDenominator<RO, RO, E>,
+    {
+        let var_map: Vec<_> = var_names
+            .iter()
+            .map(|v| state.get_or_insert_var(v))
+            .collect();
+        let var_name_map: Vec<SmartString<LazyCompact>> =
+            var_names.iter().map(|v| v.as_ref().into()).collect();
+
+        parse(input)?.to_rational_polynomial(
+            workspace,
+            state,
+            field,
+            out_field,
+            &var_map,
+            &var_name_map,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smartstring::{LazyCompact, SmartString};
+
+    use crate::{parser::parse, rings::rational::RationalField, state::State};
+
+    use super::polynomial::MultivariatePolynomial;
+
+    #[test]
+    fn test_token_unknown_variable() {
+        let mut state = State::new();
+        let var_map = [state.get_or_insert_var("x")];
+        let var_name_map: [SmartString<LazyCompact>; 1] = ["x".into()];
+
+        for input in ["x*y", "x^2*y^3", "y^2"] {
+            let r: Result<MultivariatePolynomial<_, u8>, _> = parse(input).unwrap().to_polynomial(
+                RationalField::new(),
+                &var_map,
+                &var_name_map,
+            );
+            assert_eq!(r.unwrap_err(), "Unknown variable y", "{}", input);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use smallvec::{smallvec, SmallVec};
+
+use crate::{
+    representations::Identifier,
+    rings::{
+        finite_field::{FiniteField, FiniteFieldElement},
+        integer::{Integer, IntegerRing},
+        rational::{Rational, RationalField},
+        Ring,
+    },
+};
+
+use super::{polynomial::MultivariatePolynomial, Exponent, INLINED_EXPONENTS};
+
+/// The largest absolute value of a coefficient that is stored inline.
+const MAX_INLINE: i64 = 1 << 61;
+
+/// A ring whose elements can be stored as small integers in a [`CompressedPolynomial`].
+pub trait CompressibleRing: Ring {
+    /// Convert the element to a small integer, if possible.
+    fn to_small(&self, a: &Self::Element) -> Option<i64>;
+    /// Convert a small integer created by [`CompressibleRing::to_small`] back to an element.
+    fn element_from_small(&self, a: i64) -> Self::Element;
+}
+
+impl CompressibleRing for IntegerRing {
+    fn to_small(&self, a: &Integer) -> Option<i64> {
+        match a {
+            Integer::Natural(n) => Some(*n),
+            Integer::Large(_) => None,
+        }
+    }
+
+    fn element_from_small(&self, a: i64) -> Integer {
+        Integer::Natural(a)
+    }
+}
+
+impl CompressibleRing for RationalField {
+    fn to_small(&self, a: &Rational) -> Option<i64> {
+        match a {
+            Rational::Natural(n, 1) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn element_from_small(&self, a: i64) -> Rational {
+        Rational::Natural(a, 1)
+    }
+}
+
+macro_rules! impl_compressible_finite_field {
+    ($u:ty) => {
+        impl CompressibleRing for FiniteField<$u> {
+            fn to_small(&self, a: &FiniteFieldElement<$u>) -> Option<i64> {
+                i64::try_from(a.0).ok()
+            }
+
+            fn element_from_small(&self, a: i64) -> FiniteFieldElement<$u> {
+                FiniteFieldElement(a as $u)
+            }
+        }
+    };
+}
+
+impl_compressible_finite_field!(u32);
+impl_compressible_finite_field!(u64);
+
+/// A memory-efficient representation of a [`MultivariatePolynomial`], for storing
+/// polynomials with a very large number of terms. The exponents of every term are
+/// stored as the difference with the exponents of the previous term, and coefficients
+/// that are small integers are stored inline, both as variable-length integers.
+/// Other coefficients are stored separately.
+#[derive(Clone, Debug)]
+pub struct CompressedPolynomial<F: Ring, E: Exponent> {
+    data: Vec<u8>,
+    large_coefficients: Vec<F::Element>,
+    nterms: usize,
+    nvars: usize,
+    field: F,
+    var_map: Option<SmallVec<[Identifier; INLINED_EXPONENTS]>>,
+    _phantom_exp: std::marker::PhantomData<E>,
+}
+
+fn write_varint(dest: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        dest.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    dest.push(n as u8);
+}
+
+fn read_varint(source: &[u8], pos: &mut usize) -> u64 {
+    let mut n = 0;
+    let mut shift = 0;
+    loop {
+        let b = source[*pos];
+        *pos += 1;
+        n |= ((b & 0x7f) as u64) << shift;
+        if b & 0x80 == 0 {
+            return n;
+        }
+        shift += 7;
+    }
+}
+
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+fn unzigzag(n: u64) -> i64 {
+    (n >> 1) as i64 ^ -((n & 1) as i64)
+}
+
+impl<F: CompressibleRing, E: Exponent> MultivariatePolynomial<F, E> {
+    /// Compress the polynomial. See [`CompressedPolynomial`].
+    pub fn compress(&self) -> CompressedPolynomial<F, E> {
+        let mut data = vec![];
+        let mut large_coefficients = vec![];
+        let mut prev = vec![0u32; self.nvars];
+        for t in 0..self.nterms {
+            for (p, e) in prev.iter_mut().zip(self.exponents(t)) {
+                let e = e.to_u32();
+                write_varint(&mut data, zigzag(e as i64 - *p as i64));
+                *p = e;
+            }
+
+            let c = &self.coefficients[t];
+            match self.field.to_small(c) {
+                Some(n) if (-MAX_INLINE..MAX_INLINE).contains(&n) => {
+                    write_varint(&mut data, zigzag(n) << 1)
+                }
+                _ => {
+                    write_varint(&mut data, 1);
+                    large_coefficients.push(c.clone());
+                }
+            }
+        }
+
+        data.shrink_to_fit();
+        large_coefficients.shrink_to_fit();
+
+        CompressedPolynomial {
+            data,
+            large_coefficients,
+            nterms: self.nterms,
+            nvars: self.nvars,
+            field: self.field,
+            var_map: self.var_map.clone(),
+            _phantom_exp: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<F: CompressibleRing, E: Exponent> CompressedPolynomial<F, E> {
+    /// Get the number of terms.
+    pub fn nterms(&self) -> usize {
+        self.nterms
+    }
+
+    /// Get the number of variables.
+    pub fn nvars(&self) -> usize {
+        self.nvars
+    }
+
+    /// Get the number of bytes used to store the terms, excluding the
+    /// heap memory of coefficients that are not stored inline.
+    pub fn size_in_bytes(&self) -> usize {
+        self.data.len() + self.large_coefficients.len() * std::mem::size_of::<F::Element>()
+    }
+
+    /// Iterate over the terms in the order of the original polynomial,
+    /// decompressing one term at a time.
+    pub fn iter(&self) -> CompressedPolynomialIterator<'_, F, E> {
+        CompressedPolynomialIterator {
+            poly: self,
+            pos: 0,
+            large_index: 0,
+            term: 0,
+            exponents: smallvec![E::zero(); self.nvars],
+        }
+    }
+
+    /// Convert back to a regular polynomial.
+    pub fn decompress(&self) -> MultivariatePolynomial<F, E> {
+        let mut res = MultivariatePolynomial::new(
+            self.nvars,
+            self.field,
+            Some(self.nterms),
+            self.var_map.as_ref().map(|x| x.as_slice()),
+        );
+
+        // the terms are already sorted
+        for (c, e) in self.iter() {
+            res.coefficients.push(c);
+            res.exponents.extend_from_slice(&e);
+            res.nterms += 1;
+        }
+        res
+    }
+}
+
+impl<'a, F: CompressibleRing, E: Exponent> IntoIterator for &'a CompressedPolynomial<F, E> {
+    type Item = (F::Element, SmallVec<[E; INLINED_EXPONENTS]>);
+    type IntoIter = CompressedPolynomialIterator<'a, F, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the terms of a [`CompressedPolynomial`], that yields
+/// the coefficient and exponents of every term.
+pub struct CompressedPolynomialIterator<'a, F: Ring, E: Exponent> {
+    poly: &'a CompressedPolynomial<F, E>,
+    pos: usize,
+    large_index: usize,
+    term: usize,
+    exponents: SmallVec<[E; INLINED_EXPONENTS]>,
+}
+
+impl<'a, F: CompressibleRing, E: Exponent> Iterator for CompressedPolynomialIterator<'a, F, E> {
+    type Item = (F::Element, SmallVec<[E; INLINED_EXPONENTS]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.term == self.poly.nterms {
+            return None;
+        }
+        self.term += 1;
+
+        let data = &self.poly.data;
+        for e in &mut self.exponents {
+            let d = unzigzag(read_varint(data, &mut self.pos));
+            *e = E::from_u32((e.to_u32() as i64 + d) as u32);
+        }
+
+        let tag = read_varint(data, &mut self.pos);
+        let c = if tag & 1 == 1 {
+            self.large_index += 1;
+            self.poly.large_coefficients[self.large_index - 1].clone()
+        } else {
+            self.poly.field.element_from_small(unzigzag(tag >> 1))
+        };
+
+        Some((c, self.exponents.clone()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.poly.nterms - self.term;
+        (n, Some(n))
+    }
+}
@@ -0,0 +1,239 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use rayon::prelude::*;
+
+use crate::{instrumentation::count_operations, rings::Ring};
+
+use super::{polynomial::MultivariatePolynomial, Exponent};
+
+/// The minimal degree for which dense univariate multiplication is used.
+const DENSE_MIN_DEGREE: usize = 16;
+/// The minimal fraction of non-zero terms for which dense univariate multiplication is used.
+const DENSE_MIN_DENSITY: f64 = 0.5;
+/// The minimal product of the number of terms for which parallel multiplication is used.
+const PARALLEL_MIN_WORK: usize = 1 << 22;
+
+/// The algorithm used for the multiplication of polynomials.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MulAlgorithm {
+    /// Choose the algorithm based on the shape of the polynomials.
+    #[default]
+    Auto,
+    /// Heap multiplication, using packed exponents when possible.
+    Heap,
+    /// Dense multiplication for univariate polynomials.
+    DenseUnivariate,
+    /// Heap multiplication of chunks of the largest polynomial in parallel.
+    Parallel,
+}
+
+/// The algorithm used for the division of polynomials.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum DivisionAlgorithm {
+    /// Use packed exponents when the degrees are small enough.
+    #[default]
+    Auto,
+    /// Heap division with unpacked exponents.
+    Heap,
+}
+
+/// The algorithm used for the gcd of polynomials.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum GcdAlgorithm {
+    /// Try the heuristic gcd first, and fall back to the modular algorithm.
+    #[default]
+    Auto,
+    /// Always use the modular algorithm.
+    Modular,
+}
+
+static MUL_ALGORITHM: AtomicU8 = AtomicU8::new(MulAlgorithm::Auto as u8);
+static DIVISION_ALGORITHM: AtomicU8 = AtomicU8::new(DivisionAlgorithm::Auto as u8);
+static GCD_ALGORITHM: AtomicU8 = AtomicU8::new(GcdAlgorithm::Auto as u8);
+
+/// Override the algorithm used for polynomial multiplication. If the selected
+/// algorithm does not apply to the input, heap multiplication is used.
+pub fn set_mul_algorithm(algorithm: MulAlgorithm) {
+    MUL_ALGORITHM.store(algorithm as u8, Ordering::Relaxed);
+}
+
+/// Get the algorithm used for polynomial multiplication.
+pub fn get_mul_algorithm() -> MulAlgorithm {
+    match MUL_ALGORITHM.load(Ordering::Relaxed) {
+        1 => MulAlgorithm::Heap,
+        2 => MulAlgorithm::DenseUnivariate,
+        3 => MulAlgorithm::Parallel,
+        _ => MulAlgorithm::Auto,
+    }
+}
+
+/// Override the algorithm used for polynomial division.
+pub fn set_division_algorithm(algorithm: DivisionAlgorithm) {
+    DIVISION_ALGORITHM.store(algorithm as u8, Ordering::Relaxed);
+}
+
+/// Get the algorithm used for polynomial division.
+pub fn get_division_algorithm() -> DivisionAlgorithm {
+    match DIVISION_ALGORITHM.load(Ordering::Relaxed) {
+        1 => DivisionAlgorithm::Heap,
+        _ => DivisionAlgorithm::Auto,
+    }
+}
+
+/// Override the algorithm used for polynomial gcds.
+pub fn set_gcd_algorithm(algorithm: GcdAlgorithm) {
+    GCD_ALGORITHM.store(algorithm as u8, Ordering::Relaxed);
+}
+
+/// Get the algorithm used for polynomial gcds.
+pub fn get_gcd_algorithm() -> GcdAlgorithm {
+    match GCD_ALGORITHM.load(Ordering::Relaxed) {
+        1 => GcdAlgorithm::Modular,
+        _ => GcdAlgorithm::Auto,
+    }
+}
+
+impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
+    /// Get the only variable that appears in the polynomial, or `None`
+    /// if the polynomial is constant or depends on multiple variables.
+    fn univariate_var(&self) -> Option<usize> {
+        let mut var = None;
+        for e in self.exponents.chunks(self.nvars) {
+            for (i, ee) in e.iter().enumerate() {
+                if !ee.is_zero() {
+                    match var {
+                        None => var = Some(i),
+                        Some(v) if v != i => return None,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        var
+    }
+
+    /// Multiply two polynomials that only depend on the variable `var`
+    /// using a dense representation.
+    fn mul_dense_univariate(&self, other: &Self, var: usize) -> Self {
+        let to_dense = |p: &Self| {
+            let mut dense = vec![p.field.zero(); p.degree(var).to_u32() as usize + 1];
+            for t in 0..p.nterms {
+                dense[p.exponents(t)[var].to_u32() as usize] = p.coefficients[t].clone();
+            }
+            dense
+        };
+
+        let a = to_dense(self);
+        let b = to_dense(other);
+        let mut c = vec![self.field.zero(); a.len() + b.len() - 1];
+        // the entries of c below `end` have received a product
+        let (mut products, mut end) = (0, 0);
+        for (i, ca) in a.iter().enumerate() {
+            if F::is_zero(ca) {
+                continue;
+            }
+            for (cc, cb) in c[i..].iter_mut().zip(&b) {
+                self.field.add_mul_assign(cc, ca, cb);
+            }
+            products += b.len();
+            count_operations!(coefficient_adds, end.max(i) - i);
+            end = i + b.len();
+        }
+        count_operations!(coefficient_muls, products);
+
+        let mut res = self.new_from(Some(c.len()));
+        let mut exp = vec![E::zero(); self.nvars];
+        for (k, cc) in c.into_iter().enumerate() {
+            if !F::is_zero(&cc) {
+                exp[var] = E::from_u32(k as u32);
+                res.append_monomial(cc, &exp);
+            }
+        }
+        res
+    }
+
+    /// Multiply two polynomials by splitting the largest polynomial into
+    /// chunks that are multiplied in parallel.
+    pub fn mul_parallel(&self, other: &Self) -> Self {
+        if self.nterms < other.nterms {
+            return other.mul_parallel(self);
+        }
+
+        let chunk_size = (self.nterms / rayon::current_num_threads()).max(1);
+        let chunks: Vec<Self> = (0..self.nterms)
+            .step_by(chunk_size)
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|start| {
+                let end = (start + chunk_size).min(self.nterms);
+                let chunk = Self {
+                    coefficients: self.coefficients[start..end].to_vec(),
+                    exponents: self.exponents[start * self.nvars..end * self.nvars].to_vec(),
+                    nterms: end - start,
+                    nvars: self.nvars,
+                    field: self.field,
+                    var_map: self.var_map.clone(),
+                };
+                chunk.heap_mul(other)
+            })
+            .collect();
+
+        chunks.into_iter().fold(self.new_from(None), |acc, c| {
+            // the terms of the chunks with equal exponents are added
+            let nterms = acc.nterms + c.nterms;
+            let r = acc + c;
+            count_operations!(coefficient_adds, nterms - r.nterms);
+            r
+        })
+    }
+
+    /// Multiply two polynomials, using the algorithm set by [`set_mul_algorithm`].
+    /// By default, dense multiplication is used for dense univariate polynomials,
+    /// parallel multiplication for large polynomials and heap multiplication otherwise.
+    pub fn mul_dispatch(&self, other: &Self) -> Self {
+        if self.nterms <= 1 || other.nterms <= 1 {
+            return self.heap_mul(other);
+        }
+
+        let algorithm = get_mul_algorithm();
+
+        let dense_var = match (self.univariate_var(), other.univariate_var()) {
+            (Some(v1), Some(v2)) if v1 == v2 => Some(v1),
+            _ => None,
+        };
+
+        match algorithm {
+            MulAlgorithm::Heap => return self.heap_mul(other),
+            MulAlgorithm::DenseUnivariate => {
+                if let Some(var) = dense_var {
+                    return self.mul_dense_univariate(other, var);
+                }
+                return self.heap_mul(other);
+            }
+            MulAlgorithm::Parallel => return self.mul_parallel(other),
+            MulAlgorithm::Auto => {}
+        }
+
+        if let Some(var) = dense_var {
+            let is_dense = |p: &Self| {
+                let deg = p.degree(var).to_u32() as usize;
+                deg >= DENSE_MIN_DEGREE && p.nterms as f64 >= DENSE_MIN_DENSITY * (deg + 1) as f64
+            };
+
+            if is_dense(self) && is_dense(other) {
+                return self.mul_dense_univariate(other, var);
+            }
+        }
+
+        if self.nterms.saturating_mul(other.nterms) >= PARALLEL_MIN_WORK
+            && rayon::current_num_threads() > 1
+        {
+            return self.mul_parallel(other);
+        }
+
+        self.heap_mul(other)
+    }
+}
@@ -0,0 +1,1311 @@
+//! Factorization of univariate polynomials. Over finite fields, the polynomial is made
+//! square-free and split with the Cantor–Zassenhaus algorithm. Over the integers, a
+//! factorization modulo a small prime is lifted with Hensel lifting and recombined.
+
+use std::fmt::{Display, Formatter};
+
+use rug::{ops::Pow, Integer as ArbitraryPrecisionInteger};
+use smallvec::{smallvec, SmallVec};
+
+use crate::{
+    cancel::{checkpoint, uncancellable, CancellationToken, Cancelled},
+    rings::{
+        finite_field::{FiniteField, FiniteFieldCore, FiniteFieldWorkspace, ToFiniteField},
+        integer::{Integer, IntegerRing, SMALL_PRIMES},
+        EuclideanDomain, Field, Ring,
+    },
+    utils,
+};
+
+use super::{
+    polynomial::{dense_quot_rem, MultivariatePolynomial},
+    Exponent, INLINED_EXPONENTS,
+};
+
+/// A dense univariate polynomial, starting from the constant term, without trailing zeros.
+type Dense<R> = Vec<<R as Ring>::Element>;
+
+/// Dense univariate factors with their multiplicity or degree.
+type DenseFactors<R> = Vec<(Dense<R>, usize)>;
+
+/// Multivariate factors with their multiplicity.
+type Factors<F, E> = Vec<(MultivariatePolynomial<F, E>, usize)>;
+
+/// A prime field with the irreducible factors of a polynomial over it.
+type ModularFactorization = (FiniteField<u32>, Vec<Dense<FiniteField<u32>>>);
+
+/// A monomial in all variables but one with its coefficient, a dense polynomial in that variable.
+type ErrorTerm<R, E> = (SmallVec<[E; INLINED_EXPONENTS]>, Dense<R>);
+
+/// An error of a factorization.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FactorizationError {
+    /// The factorization was aborted by a [`CancellationToken`].
+    Cancelled(Cancelled),
+    /// No evaluation point was found at which the univariate image of a
+    /// multivariate polynomial is square-free. This happens when the field is
+    /// too small compared to the degree of the polynomial.
+    FieldTooSmall,
+    /// None of the small primes keeps an integer polynomial square-free with the same
+    /// degree, so that no modular factorization can be lifted.
+    NoSuitablePrime,
+}
+
+impl From<Cancelled> for FactorizationError {
+    fn from(c: Cancelled) -> Self {
+        FactorizationError::Cancelled(c)
+    }
+}
+
+impl Display for FactorizationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FactorizationError::Cancelled(c) => c.fmt(f),
+            FactorizationError::FieldTooSmall => f.write_str(
+                "Could not find a good evaluation point: the field is too small for the polynomial",
+            ),
+            FactorizationError::NoSuitablePrime => {
+                f.write_str("Could not find a prime for which the polynomial stays square-free")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FactorizationError {}
+
+/// The number of suitable primes that are tried to find a modular factorization
+/// with few factors.
+const PRIMES_TO_TRY: usize = 3;
+
+/// The number of random evaluation points that are tried to reduce a
+/// multivariate polynomial to a square-free univariate polynomial.
+const EVALUATION_ATTEMPTS: usize = 100;
+
+impl<UField: FiniteFieldWorkspace, E: Exponent> MultivariatePolynomial<FiniteField<UField>, E>
+where
+    FiniteField<UField>: FiniteFieldCore<UField>,
+{
+    /// Write a polynomial that only depends on `x` as a product of powers of monic,
+    /// square-free and pairwise coprime polynomials, preceded by the leading coefficient
+    /// if it is not one.
+    pub fn square_free_factorization(&self, x: usize) -> Vec<(Self, usize)> {
+        let (lc, f) = self.to_dense_monic(x);
+        let mut res = self.constant_factor(lc);
+        for (g, m) in ff_square_free(&self.field, f) {
+            res.push((from_dense(self, x, &g), m));
+        }
+        res
+    }
+
+    /// Split a monic square-free polynomial that only depends on `x` into products of
+    /// all irreducible factors of the same degree. Returns the products with the degree.
+    pub fn distinct_degree_factorization(&self, x: usize) -> Vec<(Self, usize)> {
+        let (_, f) = self.to_dense_monic(x);
+        uncancellable(ff_distinct_degree(&self.field, f, None))
+            .into_iter()
+            .map(|(g, d)| (from_dense(self, x, &g), d))
+            .collect()
+    }
+
+    /// Split a monic square-free polynomial that only depends on `x` and whose
+    /// irreducible factors all have degree `d` into these factors.
+    pub fn equal_degree_factorization(&self, x: usize, d: usize) -> Vec<Self> {
+        let (_, f) = self.to_dense_monic(x);
+        let mut res = vec![];
+        uncancellable(ff_equal_degree(&self.field, f, d, &mut res, None));
+        res.iter().map(|g| from_dense(self, x, g)).collect()
+    }
+
+    /// Factor a polynomial that only depends on `x` into monic irreducible
+    /// polynomials with their multiplicity, preceded by the leading coefficient
+    /// if it is not one.
+    pub fn factor_univariate(&self, x: usize) -> Vec<(Self, usize)> {
+        let (lc, f) = self.to_dense_monic(x);
+        let mut res = self.constant_factor(lc);
+        for (g, m) in uncancellable(ff_factor(&self.field, f, None)) {
+            res.push((from_dense(self, x, &g), m));
+        }
+        res
+    }
+
+    fn to_dense_monic(
+        &self,
+        x: usize,
+    ) -> (
+        <FiniteField<UField> as Ring>::Element,
+        Dense<FiniteField<UField>>,
+    ) {
+        let f = to_dense(self, x);
+        (f.last().unwrap().clone(), dense_monic(&self.field, &f))
+    }
+
+    fn constant_factor(&self, lc: <FiniteField<UField> as Ring>::Element) -> Vec<(Self, usize)> {
+        if self.field.is_one(&lc) {
+            vec![]
+        } else {
+            vec![(self.new_from_constant(lc), 1)]
+        }
+    }
+}
+
+impl<UField: FiniteFieldWorkspace, E: Exponent> MultivariatePolynomial<FiniteField<UField>, E>
+where
+    FiniteField<UField>: FiniteFieldCore<UField>,
+    <FiniteField<UField> as Ring>::Element: Copy,
+{
+    /// Factor the polynomial into monic irreducible polynomials with their multiplicity,
+    /// preceded by the leading coefficient if it is not one.
+    ///
+    /// A multivariate polynomial is reduced to a univariate one by substituting random
+    /// values for all variables but one. The univariate factors are lifted back to
+    /// multivariate factors with Hensel lifting. Good substitutions are rare in
+    /// small fields, so a large prime should be used, for example with `FiniteField<u64>`.
+    /// If no good substitution is found, [`FactorizationError::FieldTooSmall`] is returned.
+    pub fn factor(&self) -> Result<Vec<(Self, usize)>, FactorizationError> {
+        self.factor_impl(None)
+    }
+
+    /// Factor the polynomial with [`MultivariatePolynomial::factor`], checking `token`
+    /// for cancellation in the main loops of the factorization.
+    pub fn try_factor(
+        &self,
+        token: &CancellationToken,
+    ) -> Result<Vec<(Self, usize)>, FactorizationError> {
+        self.factor_impl(Some(token))
+    }
+
+    fn factor_impl(
+        &self,
+        token: Option<&CancellationToken>,
+    ) -> Result<Vec<(Self, usize)>, FactorizationError> {
+        assert!(!self.is_zero(), "Cannot factor zero");
+
+        let mut f = self.clone();
+        let lc = f.lcoeff();
+        f.normalize();
+
+        // the gcd routines do not keep the variable map
+        f.var_map = None;
+
+        let mut res = f.constant_factor(lc);
+        if !f.is_constant() {
+            res.extend(ff_factor_multivariate(&f, token)?);
+        }
+
+        for (g, _) in &mut res {
+            g.var_map = self.var_map.clone();
+        }
+        Ok(res)
+    }
+}
+
+impl<E: Exponent> MultivariatePolynomial<IntegerRing, E> {
+    /// Write a polynomial that only depends on `x` as a product of powers of primitive,
+    /// square-free and pairwise coprime polynomials with a positive leading coefficient,
+    /// preceded by the signed content if it is not one.
+    pub fn square_free_factorization(&self, x: usize) -> Vec<(Self, usize)> {
+        let (content, f) = self.content_and_primitive_part(x);
+        let mut res = self.constant_factor(content);
+        res.extend(uncancellable(f.square_free_primitive(x, None)));
+        res
+    }
+
+    /// Factor a polynomial that only depends on `x` into irreducible primitive
+    /// polynomials with a positive leading coefficient and their multiplicity,
+    /// preceded by the signed content if it is not one.
+    ///
+    /// Returns an error if no small prime keeps the polynomial square-free.
+    pub fn factor_univariate(&self, x: usize) -> Result<Vec<(Self, usize)>, FactorizationError> {
+        self.factor_univariate_impl(x, None)
+    }
+
+    /// Factor the polynomial with [`MultivariatePolynomial::factor_univariate`], checking
+    /// `token` for cancellation in the main loops of the factorization.
+    pub fn try_factor_univariate(
+        &self,
+        x: usize,
+        token: &CancellationToken,
+    ) -> Result<Vec<(Self, usize)>, FactorizationError> {
+        self.factor_univariate_impl(x, Some(token))
+    }
+
+    fn factor_univariate_impl(
+        &self,
+        x: usize,
+        token: Option<&CancellationToken>,
+    ) -> Result<Vec<(Self, usize)>, FactorizationError> {
+        let (content, f) = self.content_and_primitive_part(x);
+        let mut res = self.constant_factor(content);
+        for (g, m) in f.square_free_primitive(x, token)? {
+            for h in zassenhaus(&to_dense(&g, x), token)? {
+                res.push((from_dense(self, x, &h), m));
+            }
+        }
+        Ok(res)
+    }
+
+    fn content_and_primitive_part(&self, x: usize) -> (Integer, Self) {
+        assert!(!self.is_zero(), "Cannot factor zero");
+        to_dense(self, x); // check that the polynomial is univariate
+
+        let mut content = self.content();
+        if self.lcoeff().is_negative() {
+            content = -&content;
+        }
+        (content.clone(), self.clone().div_coeff(&content))
+    }
+
+    fn constant_factor(&self, content: Integer) -> Vec<(Self, usize)> {
+        if content.is_one() {
+            vec![]
+        } else {
+            vec![(self.new_from_constant(content), 1)]
+        }
+    }
+
+    /// Yun's square-free factorization of a primitive polynomial with a
+    /// positive leading coefficient.
+    fn square_free_primitive(
+        &self,
+        x: usize,
+        token: Option<&CancellationToken>,
+    ) -> Result<Vec<(Self, usize)>, Cancelled> {
+        let positive = |p: Self| {
+            if p.lcoeff().is_negative() {
+                -p
+            } else {
+                p
+            }
+        };
+
+        let mut res = vec![];
+        let df = self.derivative(x);
+        let g = positive(MultivariatePolynomial::gcd_impl(self, &df, token)?);
+        let mut c = self / &g;
+        let mut d = &(&df / &g) - &c.derivative(x);
+        let mut i = 1;
+        while !c.is_constant() {
+            checkpoint(token, "factor")?;
+            let a = positive(MultivariatePolynomial::gcd_impl(&c, &d, token)?);
+            c = &c / &a;
+            d = &(&d / &a) - &c.derivative(x);
+            if !a.is_constant() {
+                res.push((a, i));
+            }
+            i += 1;
+        }
+        Ok(res)
+    }
+}
+
+/// Convert a dense polynomial in `x` to a polynomial with the same variables as `template`.
+fn from_dense<F: Ring, E: Exponent>(
+    template: &MultivariatePolynomial<F, E>,
+    x: usize,
+    p: &[F::Element],
+) -> MultivariatePolynomial<F, E> {
+    let mut res = template.new_from(Some(p.len()));
+    let mut e: SmallVec<[E; INLINED_EXPONENTS]> = smallvec![E::zero(); template.nvars];
+    for (i, c) in p.iter().enumerate() {
+        e[x] = E::from_u32(i as u32);
+        res.append_monomial_back(c.clone(), &e);
+    }
+    res
+}
+
+/// Convert a non-zero polynomial that only depends on `x` to a dense polynomial.
+fn to_dense<F: Ring, E: Exponent>(p: &MultivariatePolynomial<F, E>, x: usize) -> Dense<F> {
+    assert!(!p.is_zero(), "Cannot factor zero");
+
+    let mut res = vec![p.field.zero(); p.degree(x).to_u32() as usize + 1];
+    for t in p {
+        assert!(
+            t.exponents
+                .iter()
+                .enumerate()
+                .all(|(i, e)| i == x || e.is_zero()),
+            "Polynomial is not univariate in variable {}",
+            x
+        );
+        res[t.exponents[x].to_u32() as usize] = t.coefficient.clone();
+    }
+    res
+}
+
+fn trim<R: Ring>(p: &mut Dense<R>) {
+    while p.last().map(|c| R::is_zero(c)).unwrap_or(false) {
+        p.pop();
+    }
+}
+
+fn degree<R: Ring>(p: &Dense<R>) -> usize {
+    p.len().saturating_sub(1)
+}
+
+fn dense_mul<R: Ring>(ring: &R, a: &[R::Element], b: &[R::Element]) -> Dense<R> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+
+    let mut res = vec![ring.zero(); a.len() + b.len() - 1];
+    for (i, aa) in a.iter().enumerate() {
+        for (j, bb) in b.iter().enumerate() {
+            ring.add_mul_assign(&mut res[i + j], aa, bb);
+        }
+    }
+    trim::<R>(&mut res);
+    res
+}
+
+fn dense_sub<R: Ring>(ring: &R, a: &[R::Element], b: &[R::Element]) -> Dense<R> {
+    let mut res = a.to_vec();
+    if res.len() < b.len() {
+        res.resize(b.len(), ring.zero());
+    }
+    for (r, bb) in res.iter_mut().zip(b) {
+        ring.sub_assign(r, bb);
+    }
+    trim::<R>(&mut res);
+    res
+}
+
+fn dense_add<R: Ring>(ring: &R, a: &[R::Element], b: &[R::Element]) -> Dense<R> {
+    let mut res = a.to_vec();
+    if res.len() < b.len() {
+        res.resize(b.len(), ring.zero());
+    }
+    for (r, bb) in res.iter_mut().zip(b) {
+        ring.add_assign(r, bb);
+    }
+    trim::<R>(&mut res);
+    res
+}
+
+fn dense_monic<F: Field>(field: &F, a: &[F::Element]) -> Dense<F> {
+    let inv = field.inv(a.last().unwrap());
+    a.iter().map(|c| field.mul(c, &inv)).collect()
+}
+
+fn dense_rem<F: Field>(field: &F, a: &[F::Element], b: &[F::Element]) -> Dense<F> {
+    dense_quot_rem(field, a, b).1
+}
+
+/// Compute the monic gcd `g` and `s`, `t` such that `s * a + t * b = g`.
+fn dense_extended_gcd<F: Field>(
+    field: &F,
+    a: &[F::Element],
+    b: &[F::Element],
+) -> (Dense<F>, Dense<F>, Dense<F>) {
+    let (mut old_r, mut r) = (a.to_vec(), b.to_vec());
+    let (mut old_s, mut s) = (vec![field.one()], vec![]);
+    let (mut old_t, mut t) = (vec![], vec![field.one()]);
+
+    while !r.is_empty() {
+        let (q, new_r) = dense_quot_rem(field, &old_r, &r);
+        old_r = std::mem::replace(&mut r, new_r);
+        let new_s = dense_sub(field, &old_s, &dense_mul(field, &q, &s));
+        old_s = std::mem::replace(&mut s, new_s);
+        let new_t = dense_sub(field, &old_t, &dense_mul(field, &q, &t));
+        old_t = std::mem::replace(&mut t, new_t);
+    }
+
+    if old_r.is_empty() {
+        return (old_r, old_s, old_t);
+    }
+
+    let inv = field.inv(old_r.last().unwrap());
+    let scale = |p: Dense<F>| -> Dense<F> { p.iter().map(|c| field.mul(c, &inv)).collect() };
+    (scale(old_r), scale(old_s), scale(old_t))
+}
+
+fn dense_gcd<F: Field>(field: &F, a: &[F::Element], b: &[F::Element]) -> Dense<F> {
+    let (mut a, mut b) = (a.to_vec(), b.to_vec());
+    while !b.is_empty() {
+        let r = dense_rem(field, &a, &b);
+        a = std::mem::replace(&mut b, r);
+    }
+
+    if a.is_empty() {
+        a
+    } else {
+        dense_monic(field, &a)
+    }
+}
+
+fn dense_derivative<R: Ring>(ring: &R, a: &[R::Element]) -> Dense<R> {
+    let mut n = ring.zero();
+    let mut res = Vec::with_capacity(a.len().saturating_sub(1));
+    for c in a.iter().skip(1) {
+        ring.add_assign(&mut n, &ring.one());
+        res.push(ring.mul(c, &n));
+    }
+    trim::<R>(&mut res);
+    res
+}
+
+/// Compute `a^e mod m`.
+fn dense_pow_mod<F: Field>(
+    field: &F,
+    a: &[F::Element],
+    e: &ArbitraryPrecisionInteger,
+    m: &[F::Element],
+) -> Dense<F> {
+    let mut res = vec![field.one()];
+    for i in (0..e.significant_bits()).rev() {
+        res = dense_rem(field, &dense_mul(field, &res, &res), m);
+        if e.get_bit(i) {
+            res = dense_rem(field, &dense_mul(field, &res, a), m);
+        }
+    }
+    dense_rem(field, &res, m)
+}
+
+/// Square-free factorization of a monic polynomial over a prime field.
+fn ff_square_free<UField: FiniteFieldWorkspace>(
+    field: &FiniteField<UField>,
+    f: Dense<FiniteField<UField>>,
+) -> Vec<(Dense<FiniteField<UField>>, usize)>
+where
+    FiniteField<UField>: FiniteFieldCore<UField>,
+{
+    let mut res = vec![];
+    if f.len() < 2 {
+        return res;
+    }
+
+    let df = dense_derivative(field, &f);
+    let mut c = dense_gcd(field, &f, &df);
+    let mut w = dense_quot_rem(field, &f, &c).0;
+
+    // factors whose multiplicity is not divisible by p
+    let mut i = 1;
+    while w.len() > 1 {
+        let y = dense_gcd(field, &w, &c);
+        let z = dense_quot_rem(field, &w, &y).0;
+        if z.len() > 1 {
+            res.push((z, i));
+        }
+        i += 1;
+        c = dense_quot_rem(field, &c, &y).0;
+        w = y;
+    }
+
+    // the rest is a polynomial in x^p, of which the p-th root is taken
+    if c.len() > 1 {
+        let p = field.get_prime().to_u64() as usize;
+        let root: Dense<FiniteField<UField>> = c.iter().step_by(p).cloned().collect();
+        for (g, m) in ff_square_free(field, root) {
+            res.push((g, m * p));
+        }
+    }
+
+    res
+}
+
+/// Split a monic square-free polynomial into the products of its irreducible
+/// factors of equal degree.
+fn ff_distinct_degree<UField: FiniteFieldWorkspace>(
+    field: &FiniteField<UField>,
+    mut f: Dense<FiniteField<UField>>,
+    token: Option<&CancellationToken>,
+) -> Result<DenseFactors<FiniteField<UField>>, Cancelled>
+where
+    FiniteField<UField>: FiniteFieldCore<UField>,
+{
+    let p = ArbitraryPrecisionInteger::from(field.get_prime().to_u64());
+    let x = vec![field.zero(), field.one()];
+
+    let mut res = vec![];
+    let mut h = x.clone();
+    let mut d = 1;
+    while 2 * d <= degree::<FiniteField<UField>>(&f) {
+        checkpoint(token, "factor")?;
+
+        // h = x^(p^d) mod f
+        h = dense_pow_mod(field, &h, &p, &f);
+
+        let g = dense_gcd(field, &f, &dense_sub(field, &h, &x));
+        if g.len() > 1 {
+            f = dense_quot_rem(field, &f, &g).0;
+            h = dense_rem(field, &h, &f);
+            res.push((g, d));
+        }
+        d += 1;
+    }
+
+    if f.len() > 1 {
+        let d = degree::<FiniteField<UField>>(&f);
+        res.push((f, d));
+    }
+
+    Ok(res)
+}
+
+/// Split a monic square-free polynomial whose irreducible factors all have degree `d`
+/// with the probabilistic Cantor–Zassenhaus algorithm.
+fn ff_equal_degree<UField: FiniteFieldWorkspace>(
+    field: &FiniteField<UField>,
+    f: Dense<FiniteField<UField>>,
+    d: usize,
+    res: &mut Vec<Dense<FiniteField<UField>>>,
+    token: Option<&CancellationToken>,
+) -> Result<(), Cancelled>
+where
+    FiniteField<UField>: FiniteFieldCore<UField>,
+{
+    let n = degree::<FiniteField<UField>>(&f);
+    if n <= d {
+        res.push(f);
+        return Ok(());
+    }
+
+    let p = field.get_prime().to_u64();
+    // (p^d - 1) / 2
+    let exp = (ArbitraryPrecisionInteger::from(p).pow(d as u32) - 1u32) / 2u32;
+
+    let mut rng = utils::rng("factor::equal_degree_factorization");
+    loop {
+        checkpoint(token, "factor")?;
+
+        let mut a: Dense<FiniteField<UField>> = (0..n)
+            .map(|_| field.sample(&mut rng, (0, i64::MAX)))
+            .collect();
+        trim::<FiniteField<UField>>(&mut a);
+        if a.len() < 2 {
+            continue;
+        }
+
+        // a^((p^d - 1) / 2) - 1 shares half of the factors with f on average,
+        // since the field has odd characteristic
+        let b = dense_pow_mod(field, &a, &exp, &f);
+        let b = dense_sub(field, &b, &[field.one()]);
+
+        let g = dense_gcd(field, &f, &b);
+        if g.len() > 1 && g.len() < f.len() {
+            let h = dense_quot_rem(field, &f, &g).0;
+            ff_equal_degree(field, g, d, res, token)?;
+            return ff_equal_degree(field, h, d, res, token);
+        }
+    }
+}
+
+/// Factor a monic polynomial over a prime field into monic irreducible factors.
+fn ff_factor<UField: FiniteFieldWorkspace>(
+    field: &FiniteField<UField>,
+    f: Dense<FiniteField<UField>>,
+    token: Option<&CancellationToken>,
+) -> Result<DenseFactors<FiniteField<UField>>, Cancelled>
+where
+    FiniteField<UField>: FiniteFieldCore<UField>,
+{
+    let mut res = vec![];
+    for (g, m) in ff_square_free(field, f) {
+        for (h, d) in ff_distinct_degree(field, g, token)? {
+            let mut factors = vec![];
+            ff_equal_degree(field, h, d, &mut factors, token)?;
+            res.extend(factors.into_iter().map(|h| (h, m)));
+        }
+    }
+    Ok(res)
+}
+
+/// Factor a monic non-constant multivariate polynomial over a prime field.
+fn ff_factor_multivariate<UField: FiniteFieldWorkspace, E: Exponent>(
+    f: &MultivariatePolynomial<FiniteField<UField>, E>,
+    token: Option<&CancellationToken>,
+) -> Result<Factors<FiniteField<UField>, E>, FactorizationError>
+where
+    FiniteField<UField>: FiniteFieldCore<UField>,
+    <FiniteField<UField> as Ring>::Element: Copy,
+{
+    let p = f.field.get_prime().to_u64() as usize;
+
+    let Some(x) = (0..f.nvars).find(|v| !f.derivative(*v).is_zero()) else {
+        // all exponents are multiples of p, so f = g^p where the coefficients of g
+        // are the same as those of f, since a^p = a in a prime field
+        let mut g = f.clone();
+        for e in &mut g.exponents {
+            *e = E::from_u32(e.to_u32() / p as u32);
+        }
+        return Ok(ff_factor_multivariate(&g, token)?
+            .into_iter()
+            .map(|(h, m)| (h, m * p))
+            .collect());
+    };
+
+    // split off repeated factors and the content in x
+    let mut g = MultivariatePolynomial::gcd_impl(f, &f.derivative(x), token)?;
+    if !g.is_constant() {
+        g.normalize();
+        let mut res = ff_factor_multivariate(&g, token)?;
+        for (h, m) in ff_factor_multivariate(&(f / &g), token)? {
+            if let Some(r) = res.iter_mut().find(|r| r.0 == h) {
+                r.1 += m;
+            } else {
+                res.push((h, m));
+            }
+        }
+        return Ok(res);
+    }
+
+    // f is square-free and primitive in x
+    let others: Vec<_> = (0..f.nvars)
+        .filter(|v| *v != x && !f.degree(*v).is_zero())
+        .collect();
+    if others.is_empty() {
+        return Ok(ff_factor(&f.field, to_dense(f, x), token)?
+            .into_iter()
+            .map(|(g, m)| (from_dense(f, x, &g), m))
+            .collect());
+    }
+
+    Ok(hensel_factor(f, x, &others, token)?
+        .into_iter()
+        .map(|g| (g, 1))
+        .collect())
+}
+
+/// Factor a polynomial that is square-free and primitive in `x` and that depends
+/// on the variables `others`, by lifting the factors of a univariate image.
+fn hensel_factor<UField: FiniteFieldWorkspace, E: Exponent>(
+    f: &MultivariatePolynomial<FiniteField<UField>, E>,
+    x: usize,
+    others: &[usize],
+    token: Option<&CancellationToken>,
+) -> Result<Vec<MultivariatePolynomial<FiniteField<UField>, E>>, FactorizationError>
+where
+    FiniteField<UField>: FiniteFieldCore<UField>,
+    <FiniteField<UField> as Ring>::Element: Copy,
+{
+    let field = f.field;
+    let d = f.degree(x).to_u32();
+    let coeffs = f.to_univariate_polynomial_list(x);
+    let lc = coeffs.last().unwrap().0.clone();
+
+    // make the polynomial monic in x with x -> x / lc and multiplying by lc^(d-1),
+    // so that the leading coefficients of the factors are known
+    let monic = if lc.is_constant() {
+        f.clone().mul_coeff(field.inv(&lc.lcoeff()))
+    } else {
+        let mut monic = f.new_from(None);
+        for (c, j) in &coeffs {
+            let j = j.to_u32();
+            let c = if j == d {
+                c.new_from_constant(field.one())
+            } else {
+                c * &lc.pow((d - 1 - j) as u64)
+            };
+            monic = monic + mul_x_pow(c, x, j);
+        }
+        monic
+    };
+
+    // find a point where the univariate image is square-free
+    let mut rng = utils::rng("factor::hensel_factor");
+    let mut image = None;
+    for _ in 0..EVALUATION_ATTEMPTS {
+        checkpoint(token, "factor")?;
+
+        let point: Vec<_> = others
+            .iter()
+            .map(|v| (*v, field.sample(&mut rng, (0, i64::MAX))))
+            .collect();
+
+        let mut u = monic.clone();
+        for (v, a) in &point {
+            u = u.replace(*v, a);
+        }
+
+        let u = to_dense(&u, x);
+        if dense_gcd(&field, &u, &dense_derivative(&field, &u)).len() == 1 {
+            image = Some((point, u));
+            break;
+        }
+    }
+    let (point, u) = image.ok_or(FactorizationError::FieldTooSmall)?;
+
+    let factors: Vec<_> = ff_factor(&field, u, token)?
+        .into_iter()
+        .map(|(g, _)| g)
+        .collect();
+    if factors.len() == 1 {
+        let mut f = f.clone();
+        f.normalize();
+        return Ok(vec![f]);
+    }
+
+    // move the evaluation point to the origin and lift the factors
+    let shifted = monic.shift_all(&point);
+    let max_degree = (0..shifted.nterms)
+        .map(|t| degree_except(shifted.exponents(t), x))
+        .max()
+        .unwrap();
+    let mut lifted = multivariate_hensel_lift(&shifted, x, &factors, max_degree, token)?;
+
+    // combine the lifted factors to true factors
+    let mut rest = shifted;
+    let mut true_factors = vec![];
+    let mut size = 1;
+    'next_size: while 2 * size <= lifted.len() {
+        for subset in subsets(lifted.len(), size) {
+            checkpoint(token, "factor")?;
+
+            let mut g = rest.new_from_constant(field.one());
+            for i in &subset {
+                g = &g * &lifted[*i];
+                g.retain_terms(|_, e| degree_except(e, x) <= max_degree);
+            }
+
+            if let Some(q) = rest.divides(&g) {
+                rest = q;
+                for i in subset.iter().rev() {
+                    lifted.remove(*i);
+                }
+                true_factors.push(g);
+                continue 'next_size;
+            }
+        }
+        size += 1;
+    }
+    true_factors.push(rest);
+
+    // undo the shift and the transformation to a monic polynomial
+    let origin: Vec<_> = point.iter().map(|(v, a)| (*v, field.neg(a))).collect();
+    Ok(true_factors
+        .into_iter()
+        .map(|g| {
+            let mut g = g.shift_all(&origin);
+            if !lc.is_constant() {
+                let mut h = g.new_from(None);
+                for (c, j) in g.to_univariate_polynomial_list(x) {
+                    let j = j.to_u32();
+                    h = h + mul_x_pow(&c * &lc.pow(j as u64), x, j);
+                }
+                g = &h / &h.univariate_content(x);
+            }
+            g.normalize();
+            g
+        })
+        .collect())
+}
+
+/// Lift the monic univariate factors `factors` of `f` at the origin of all variables except
+/// `x` to multivariate factors whose terms have a total degree of at most `max_degree`
+/// in these variables.
+fn multivariate_hensel_lift<UField: FiniteFieldWorkspace, E: Exponent>(
+    f: &MultivariatePolynomial<FiniteField<UField>, E>,
+    x: usize,
+    factors: &[Dense<FiniteField<UField>>],
+    max_degree: u32,
+    token: Option<&CancellationToken>,
+) -> Result<Vec<MultivariatePolynomial<FiniteField<UField>, E>>, Cancelled>
+where
+    FiniteField<UField>: FiniteFieldCore<UField>,
+{
+    let field = &f.field;
+
+    // s_i = (prod_{j != i} u_j)^-1 mod u_i, so that sum_i (e * s_i mod u_i) prod_{j != i} u_j = e
+    let inverses: Vec<_> = factors
+        .iter()
+        .enumerate()
+        .map(|(i, u)| {
+            let mut b = vec![field.one()];
+            for (j, v) in factors.iter().enumerate() {
+                if i != j {
+                    b = dense_rem(field, &dense_mul(field, &b, v), u);
+                }
+            }
+            dense_extended_gcd(field, &b, u).1
+        })
+        .collect();
+
+    let mut lifted: Vec<_> = factors.iter().map(|u| from_dense(f, x, u)).collect();
+    for k in 1..=max_degree {
+        checkpoint(token, "factor")?;
+
+        let mut product = f.new_from_constant(field.one());
+        for u in &lifted {
+            product = &product * u;
+            product.retain_terms(|_, e| degree_except(e, x) <= k);
+        }
+
+        // the error of degree k, grouped by the monomial in the other variables
+        let mut error = f.clone();
+        error.retain_terms(|_, e| degree_except(e, x) == k);
+        product.retain_terms(|_, e| degree_except(e, x) == k);
+        let error = error - product;
+
+        let mut groups: Vec<ErrorTerm<FiniteField<UField>, E>> = vec![];
+        for t in &error {
+            let mut m: SmallVec<[E; INLINED_EXPONENTS]> = t.exponents.into();
+            m[x] = E::zero();
+            let i = match groups.iter().position(|g| g.0 == m) {
+                Some(i) => i,
+                None => {
+                    groups.push((m, vec![]));
+                    groups.len() - 1
+                }
+            };
+
+            let e = &mut groups[i].1;
+            let j = t.exponents[x].to_u32() as usize;
+            if e.len() <= j {
+                e.resize(j + 1, field.zero());
+            }
+            e[j] = t.coefficient.clone();
+        }
+
+        for (m, e) in groups {
+            for ((l, u), s) in lifted.iter_mut().zip(factors).zip(&inverses) {
+                let sigma = dense_rem(field, &dense_mul(field, &e, s), u);
+                let mut correction = l.new_from(Some(sigma.len()));
+                let mut exp = m.clone();
+                for (j, c) in sigma.iter().enumerate() {
+                    exp[x] = E::from_u32(j as u32);
+                    correction.append_monomial_back(c.clone(), &exp);
+                }
+                *l = l.clone() + correction;
+            }
+        }
+    }
+
+    Ok(lifted)
+}
+
+/// Multiply `p` by `x^j`.
+fn mul_x_pow<F: Ring, E: Exponent>(
+    mut p: MultivariatePolynomial<F, E>,
+    x: usize,
+    j: u32,
+) -> MultivariatePolynomial<F, E> {
+    for t in p.exponents.chunks_mut(p.nvars) {
+        t[x] += E::from_u32(j);
+    }
+    p
+}
+
+/// The total degree of a monomial in all variables except `x`.
+fn degree_except<E: Exponent>(exponents: &[E], x: usize) -> u32 {
+    exponents
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != x)
+        .map(|(_, e)| e.to_u32())
+        .sum()
+}
+
+/// All subsets of `0..n` of size `size`, in lexicographic order.
+fn subsets(n: usize, size: usize) -> impl Iterator<Item = Vec<usize>> {
+    let mut next = if size <= n {
+        Some((0..size).collect::<Vec<_>>())
+    } else {
+        None
+    };
+
+    std::iter::from_fn(move || {
+        let current = next.take()?;
+
+        let mut i = size;
+        while i > 0 && current[i - 1] == n - size + i - 1 {
+            i -= 1;
+        }
+        if i > 0 {
+            let mut s = current.clone();
+            s[i - 1] += 1;
+            for j in i..size {
+                s[j] = s[j - 1] + 1;
+            }
+            next = Some(s);
+        }
+
+        Some(current)
+    })
+}
+
+/// Factor a primitive square-free integer polynomial with a positive leading coefficient.
+fn zassenhaus(
+    f: &Dense<IntegerRing>,
+    token: Option<&CancellationToken>,
+) -> Result<Vec<Dense<IntegerRing>>, FactorizationError> {
+    if f.len() <= 2 {
+        return Ok(vec![f.clone()]);
+    }
+
+    let ring = IntegerRing::new();
+    let lc = f.last().unwrap().clone();
+
+    // select the prime with the fewest modular factors
+    let mut best: Option<ModularFactorization> = None;
+    let mut tried = 0;
+    // the finite fields require an odd prime
+    for p in &SMALL_PRIMES[1..] {
+        let field = FiniteField::<u32>::new(*p as u32);
+        let fp: Dense<FiniteField<u32>> = f.iter().map(|c| c.to_finite_field(&field)).collect();
+        if FiniteField::<u32>::is_zero(fp.last().unwrap()) {
+            continue;
+        }
+
+        let fp = dense_monic(&field, &fp);
+        if dense_gcd(&field, &fp, &dense_derivative(&field, &fp)).len() > 1 {
+            continue;
+        }
+
+        let factors: Vec<_> = ff_factor(&field, fp, token)?
+            .into_iter()
+            .map(|(g, _)| g)
+            .collect();
+        if factors.len() == 1 {
+            return Ok(vec![f.clone()]);
+        }
+
+        if best
+            .as_ref()
+            .map(|b| factors.len() < b.1.len())
+            .unwrap_or(true)
+        {
+            best = Some((field, factors));
+        }
+
+        tried += 1;
+        if tried == PRIMES_TO_TRY {
+            break;
+        }
+    }
+
+    let (field, factors) = best.ok_or(FactorizationError::NoSuitablePrime)?;
+    let p = Integer::new(field.get_prime() as i64);
+
+    // the coefficients of lc * g for a factor g of f are bounded by lc * B
+    let mut poly = MultivariatePolynomial::<_, u32>::new(1, ring, None, None);
+    for (i, c) in f.iter().enumerate() {
+        poly.append_monomial(c.clone(), &[i as u32]);
+    }
+    let bound = &(&Integer::new(2) * &lc) * &poly.mignotte_bound();
+    let mut k = 1;
+    let mut pk = p.clone();
+    while pk <= bound {
+        pk = &pk * &p;
+        k += 1;
+    }
+
+    let mut lifted = hensel_lift(f, &factors, &field, k, token)?;
+    Ok(recombine(f.clone(), &mut lifted, &pk, token)?)
+}
+
+/// Lift the monic factors `factors` of `f / lc(f)` modulo `p` to monic factors modulo `p^k`.
+fn hensel_lift(
+    f: &Dense<IntegerRing>,
+    factors: &[Dense<FiniteField<u32>>],
+    field: &FiniteField<u32>,
+    k: usize,
+    token: Option<&CancellationToken>,
+) -> Result<Vec<Dense<IntegerRing>>, Cancelled> {
+    let ring = IntegerRing::new();
+    let p = Integer::new(field.get_prime() as i64);
+    let pk = p.pow(k as u64);
+
+    if factors.len() == 1 {
+        // make f monic modulo p^k
+        let (_, inv, _) = Integer::extended_gcd(f.last().unwrap(), &pk);
+        return Ok(vec![f.iter().map(|c| ring.rem(&(c * &inv), &pk)).collect()]);
+    }
+
+    let mid = factors.len() / 2;
+    let mut g0 = vec![f.last().unwrap().to_finite_field(field)];
+    for g in &factors[..mid] {
+        g0 = dense_mul(field, &g0, g);
+    }
+    let mut h0 = vec![field.one()];
+    for h in &factors[mid..] {
+        h0 = dense_mul(field, &h0, h);
+    }
+
+    let (g, h) = hensel_lift_two(f, &g0, &h0, field, k, token)?;
+
+    let mut res = hensel_lift(&g, &factors[..mid], field, k, token)?;
+    res.extend(hensel_lift(&h, &factors[mid..], field, k, token)?);
+    Ok(res)
+}
+
+/// Lift `f = g0 * h0 mod p`, where `h0` is monic and `g0` and `h0` are coprime,
+/// to `f = g * h mod p^k`, where `h` is monic and `lc(g) = lc(f)`.
+fn hensel_lift_two(
+    f: &Dense<IntegerRing>,
+    g0: &Dense<FiniteField<u32>>,
+    h0: &Dense<FiniteField<u32>>,
+    field: &FiniteField<u32>,
+    k: usize,
+    token: Option<&CancellationToken>,
+) -> Result<(Dense<IntegerRing>, Dense<IntegerRing>), Cancelled> {
+    let ring = IntegerRing::new();
+    let p = Integer::new(field.get_prime() as i64);
+    let to_int = |a: &Dense<FiniteField<u32>>| -> Dense<IntegerRing> {
+        a.iter()
+            .map(|c| Integer::from_finite_field_u32(*field, c))
+            .collect()
+    };
+
+    let (_, s, t) = dense_extended_gcd(field, g0, h0);
+
+    let mut g = to_int(g0);
+    *g.last_mut().unwrap() = f.last().unwrap().clone();
+    let mut h = to_int(h0);
+
+    let mut m = p.clone();
+    for _ in 1..k {
+        checkpoint(token, "factor")?;
+
+        // the error (f - g * h) / m modulo p
+        let e = dense_sub(&ring, f, &dense_mul(&ring, &g, &h));
+        let mut e: Dense<FiniteField<u32>> = e
+            .iter()
+            .map(|c| ring.quot_rem(c, &m).0.to_finite_field(field))
+            .collect();
+        trim::<FiniteField<u32>>(&mut e);
+
+        // solve sigma * g0 + tau * h0 = e with deg(sigma) < deg(h0)
+        let (q, sigma) = dense_quot_rem(field, &dense_mul(field, &s, &e), h0);
+        let tau = dense_add(field, &dense_mul(field, &t, &e), &dense_mul(field, &q, g0));
+
+        let scale = |a: &Dense<FiniteField<u32>>| -> Dense<IntegerRing> {
+            to_int(a).iter().map(|c| c * &m).collect()
+        };
+        g = dense_add(&ring, &g, &scale(&tau));
+        h = dense_add(&ring, &h, &scale(&sigma));
+        m = &m * &p;
+    }
+
+    let reduce = |a: Dense<IntegerRing>| -> Dense<IntegerRing> {
+        let mut r: Dense<IntegerRing> = a.iter().map(|c| ring.rem(c, &m)).collect();
+        trim::<IntegerRing>(&mut r);
+        r
+    };
+    Ok((reduce(g), reduce(h)))
+}
+
+/// Find the true factors of `f` by combining the lifted modular factors modulo `pk`.
+fn recombine(
+    mut f: Dense<IntegerRing>,
+    factors: &mut Vec<Dense<IntegerRing>>,
+    pk: &Integer,
+    token: Option<&CancellationToken>,
+) -> Result<Vec<Dense<IntegerRing>>, Cancelled> {
+    let ring = IntegerRing::new();
+    let half_pk = pk / &Integer::new(2);
+
+    let mut res = vec![];
+    let mut size = 1;
+    'next_size: while 2 * size <= factors.len() {
+        for subset in subsets(factors.len(), size) {
+            checkpoint(token, "factor")?;
+
+            // lc(f) * product of the subset, in the symmetric representation
+            let mut g = vec![f.last().unwrap().clone()];
+            for i in &subset {
+                g = dense_mul(&ring, &g, &factors[*i]);
+                for c in &mut g {
+                    *c = ring.rem(c, pk);
+                    if *c > half_pk {
+                        *c = &*c - pk;
+                    }
+                }
+            }
+            trim::<IntegerRing>(&mut g);
+
+            let content = g.iter().fold(Integer::zero(), |acc, c| ring.gcd(&acc, c));
+            let g: Dense<IntegerRing> = g.iter().map(|c| c / &content).collect();
+
+            if let Some(q) = dense_exact_div(&f, &g) {
+                f = q;
+                for i in subset.iter().rev() {
+                    factors.remove(*i);
+                }
+                res.push(g);
+                continue 'next_size;
+            }
+        }
+
+        size += 1;
+    }
+
+    if f.len() > 1 {
+        res.push(f);
+    }
+    Ok(res)
+}
+
+/// Divide `a` by `b` over the integers, if the division is exact.
+fn dense_exact_div(a: &Dense<IntegerRing>, b: &Dense<IntegerRing>) -> Option<Dense<IntegerRing>> {
+    let ring = IntegerRing::new();
+    if a.len() < b.len() {
+        return None;
+    }
+
+    let mut r = a.clone();
+    let mut q = vec![Integer::zero(); r.len() - b.len() + 1];
+    for i in (0..q.len()).rev() {
+        let (c, rem) = ring.quot_rem(&r[i + b.len() - 1], b.last().unwrap());
+        if !rem.is_zero() {
+            return None;
+        }
+        for (j, bb) in b.iter().enumerate() {
+            ring.sub_mul_assign(&mut r[i + j], bb, &c);
+        }
+        q[i] = c;
+    }
+
+    if r.iter().all(|c| c.is_zero()) {
+        Some(q)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smartstring::{LazyCompact, SmartString};
+
+    use crate::{
+        parser::parse,
+        poly::{polynomial::MultivariatePolynomial, Exponent},
+        representations::number::ConvertToRing,
+        rings::{
+            finite_field::{FiniteField, FiniteFieldCore},
+            integer::{Integer, IntegerRing, SMALL_PRIMES},
+            Ring,
+        },
+        state::State,
+    };
+
+    use super::FactorizationError;
+
+    /// Parse an expanded polynomial in `x` and `y`.
+    fn poly<R: Ring + ConvertToRing>(input: &str, field: R) -> MultivariatePolynomial<R, u16> {
+        let mut state = State::new();
+        let var_map = [state.get_or_insert_var("x"), state.get_or_insert_var("y")];
+        let var_name_map: [SmartString<LazyCompact>; 2] = ["x".into(), "y".into()];
+        parse(input)
+            .unwrap()
+            .to_polynomial(field, &var_map, &var_name_map)
+            .unwrap()
+    }
+
+    /// Multiply the factors with their multiplicities.
+    fn expand<R: Ring, E: Exponent>(
+        factors: &[(MultivariatePolynomial<R, E>, usize)],
+    ) -> MultivariatePolynomial<R, E> {
+        factors.iter().fold(
+            factors[0].0.new_from_constant(factors[0].0.field.one()),
+            |acc, (f, m)| acc * &f.pow(*m as u64),
+        )
+    }
+
+    /// Check that `factors` consist of exactly `expected` and multiply to `f`.
+    fn check_factors<R: Ring + ConvertToRing + PartialEq>(
+        f: &MultivariatePolynomial<R, u16>,
+        factors: &[(MultivariatePolynomial<R, u16>, usize)],
+        expected: &[(&str, usize)],
+    ) {
+        assert_eq!(&expand(factors), f);
+        assert_eq!(factors.len(), expected.len());
+        for (e, m) in expected {
+            let factor = (poly(e, f.field), *m);
+            assert!(factors.contains(&factor), "missing factor {} of {}", e, f);
+        }
+    }
+
+    #[test]
+    fn test_factor_univariate_finite_field() {
+        // x^2+3 is irreducible, as -3 is not a square modulo 17
+        let field = FiniteField::<u32>::new(17);
+        let f = poly("3", field)
+            * &poly("x+1", field).pow(2)
+            * &poly("x^2+3", field)
+            * &poly("x+5", field);
+
+        check_factors(
+            &f,
+            &f.factor_univariate(0),
+            &[("3", 1), ("x+1", 2), ("x^2+3", 1), ("x+5", 1)],
+        );
+    }
+
+    #[test]
+    fn test_factor_univariate_integer() {
+        let ring = IntegerRing::new();
+        let f = poly("-6", ring)
+            * &poly("2*x+3", ring).pow(3)
+            * &poly("x^2+1", ring)
+            * &poly("3*x-1", ring)
+            * &poly("x^4-x+1", ring);
+
+        check_factors(
+            &f,
+            &f.factor_univariate(0).unwrap(),
+            &[
+                ("-6", 1),
+                ("2*x+3", 3),
+                ("x^2+1", 1),
+                ("3*x-1", 1),
+                ("x^4-x+1", 1),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_factor_multivariate_finite_field() {
+        let field = FiniteField::<u64>::new(1000000007);
+        let f = poly("5", field)
+            * &poly("x*y+1", field).pow(2)
+            * &poly("x^2+y+3", field)
+            * &poly("y+2", field).pow(3)
+            * &poly("x^3*y^2+x*y+y^3+7", field);
+
+        let factors = f.factor().unwrap();
+        assert_eq!(expand(&factors), f);
+        assert_eq!(factors.len(), 5);
+
+        // the factors are monic and preceded by the leading coefficient
+        for (e, m) in [
+            ("5", 1),
+            ("x*y+1", 2),
+            ("x^2+y+3", 1),
+            ("y+2", 3),
+            ("x^3*y^2+x*y+y^3+7", 1),
+        ] {
+            let mut factor = poly(e, field);
+            if !factor.is_constant() {
+                factor.normalize();
+            }
+            assert!(factors.contains(&(factor, m)), "missing factor {}", e);
+        }
+    }
+
+    #[test]
+    fn test_factor_field_too_small() {
+        // y^3 - y vanishes on all of F_3, so every univariate image x^2 is a square
+        let field = FiniteField::<u32>::new(3);
+        let mut p = MultivariatePolynomial::<_, u16>::new(2, field, None, None);
+        p.append_monomial(field.one(), &[2, 0]);
+        p.append_monomial(field.one(), &[0, 3]);
+        p.append_monomial(field.neg(&field.one()), &[0, 1]);
+
+        assert_eq!(p.factor(), Err(FactorizationError::FieldTooSmall));
+    }
+
+    #[test]
+    fn test_factor_no_suitable_prime() {
+        // the leading coefficient vanishes modulo all small primes
+        let lc = SMALL_PRIMES
+            .iter()
+            .fold(Integer::one(), |acc, p| &acc * &Integer::new(*p));
+        let mut p = MultivariatePolynomial::<_, u16>::new(1, IntegerRing::new(), None, None);
+        p.append_monomial(Integer::one(), &[0]);
+        p.append_monomial(lc, &[2]);
+
+        assert_eq!(
+            p.factor_univariate(0),
+            Err(FactorizationError::NoSuitablePrime)
+        );
+    }
+}
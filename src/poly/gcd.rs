@@ -1,5 +1,4 @@
 use ahash::{HashMap, HashSet, HashSetExt};
-use rand;
 use smallvec::{smallvec, SmallVec};
 use std::borrow::Cow;
 use std::cmp::{max, min, Ordering};
@@ -7,7 +6,11 @@ use std::mem;
 use std::ops::Add;
 use tracing::{debug, instrument};
 
+use crate::cancel::{checkpoint, uncancellable, CancellationToken, Cancelled};
+use crate::instrumentation::count_operations;
+use crate::instrumentation::instrument_scope;
 use crate::poly::INLINED_EXPONENTS;
+use crate::progress;
 use crate::rings::finite_field::{
     FiniteField, FiniteFieldCore, FiniteFieldWorkspace, ToFiniteField,
 };
@@ -15,7 +18,9 @@ use crate::rings::integer::{Integer, IntegerRing, SMALL_PRIMES};
 use crate::rings::linear_system::{LinearSolverError, Matrix};
 use crate::rings::rational::RationalField;
 use crate::rings::{EuclideanDomain, Field, Ring};
+use crate::utils;
 
+use super::dispatch::{get_gcd_algorithm, GcdAlgorithm};
 use super::polynomial::MultivariatePolynomial;
 use super::Exponent;
 
@@ -291,7 +296,7 @@ where
         return Err(GCDError::BadOriginalImage);
     }
 
-    let mut rng = rand::thread_rng();
+    let mut rng = utils::rng("gcd::construct_new_image_single_scale");
 
     let mut failure_count = 0;
 
@@ -537,7 +542,7 @@ where
     FiniteField<UField>: FiniteFieldCore<UField>,
     <FiniteField<UField> as Ring>::Element: Copy,
 {
-    let mut rng = rand::thread_rng();
+    let mut rng = utils::rng("gcd::construct_new_image_multiple_scales");
 
     let mut failure_count = 0;
 
@@ -1057,7 +1062,7 @@ where
     /// upper bound could be too tight due to an unfortunate
     /// sample point, but this is rare.
     fn get_gcd_var_bound(ap: &Self, bp: &Self, vars: &[usize], var: usize) -> E {
-        let mut rng = rand::thread_rng();
+        let mut rng = utils::rng("gcd::get_gcd_var_bound");
 
         // store a table for variables raised to a certain power
         let mut cache = (0..ap.nvars)
@@ -1127,7 +1132,8 @@ where
         vars: &[usize],         // variables
         bounds: &mut [E],       // degree bounds
         tight_bounds: &mut [E], // tighter degree bounds
-    ) -> Option<Self> {
+        token: Option<&CancellationToken>,
+    ) -> Result<Option<Self>, Cancelled> {
         let lastvar = *vars.last().unwrap();
 
         // if we are in the univariate case, return the univariate gcd
@@ -1135,10 +1141,10 @@ where
         if vars.len() == 1 {
             let gg = MultivariatePolynomial::univariate_gcd(a, b);
             if gg.degree(vars[0]) > bounds[vars[0]] {
-                return None;
+                return Ok(None);
             }
             bounds[vars[0]] = gg.degree(vars[0]); // update degree bound
-            return Some(gg);
+            return Ok(Some(gg));
         }
 
         // the gcd of the content in the last variable should be 1
@@ -1148,7 +1154,7 @@ where
             // TODO: we assume that a content of -1 is also allowed
             // like in the special case gcd_(-x0*x1,-x0-x0*x1)
             if c.nterms != 1 || c.coefficients[0] != a.field.neg(&a.field.one()) {
-                return None;
+                return Ok(None);
             }
         }
 
@@ -1157,11 +1163,13 @@ where
             &b.lcoeff_last_varorder(vars),
         );
 
-        let mut rng = rand::thread_rng();
+        let mut rng = utils::rng("gcd::gcd_shape_modular");
 
         let mut failure_count = 0;
 
         'newfirstnum: loop {
+            checkpoint(token, "gcd")?;
+
             // if we had two failures, it may be that the tight degree bound
             // was too tight due to an unfortunate prime/evaluation, so we relax it
             if failure_count == 2 {
@@ -1198,14 +1206,15 @@ where
                     &vars[..vars.len() - 1],
                     bounds,
                     tight_bounds,
-                ) {
+                    token,
+                )? {
                     Some(x) => x,
-                    None => return None,
+                    None => return Ok(None),
                 }
             } else {
                 let gg = MultivariatePolynomial::univariate_gcd(&av, &bv);
                 if gg.degree(vars[0]) > bounds[vars[0]] {
-                    return None;
+                    return Ok(None);
                 }
                 bounds[vars[0]] = gg.degree(vars[0]); // update degree bound
                 gg
@@ -1257,9 +1266,12 @@ where
 
             // sparse reconstruction
             'newnum: loop {
-                if gseq.len()
-                    == (tight_bounds[lastvar].to_u32() + gamma.ldegree_max().to_u32() + 1) as usize
-                {
+                checkpoint(token, "gcd")?;
+
+                let nsamples =
+                    (tight_bounds[lastvar].to_u32() + gamma.ldegree_max().to_u32() + 1) as usize;
+                progress::report("gcd", gseq.len(), Some(nsamples));
+                if gseq.len() == nsamples {
                     break;
                 }
 
@@ -1390,7 +1402,7 @@ where
             };
 
             if g1.is_one() || (a1.divides(&g1).is_some() && b1.divides(&g1).is_some()) {
-                return Some(gc);
+                return Ok(Some(gc));
             }
 
             // if the gcd is bad, we had a bad number
@@ -1454,7 +1466,10 @@ impl<R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> MultivariatePolynomial<
         PolynomialGCD::gcd_multiple(f)
     }
 
-    /// Apply a GCD repeatedly to a list of polynomials.
+    /// Apply a GCD repeatedly to a list of polynomials. The polynomials are
+    /// combined pairwise, starting with the smallest ones, so that the
+    /// intermediate gcds stay small. The computation stops as soon as
+    /// a gcd of one is found.
     #[inline(always)]
     pub fn repeated_gcd(mut f: Vec<MultivariatePolynomial<R, E>>) -> MultivariatePolynomial<R, E> {
         if f.len() == 1 {
@@ -1465,17 +1480,57 @@ impl<R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> MultivariatePolynomial<
             return MultivariatePolynomial::gcd(&f[0], &f[1]);
         }
 
-        f.sort_unstable_by_key(|p| p.nterms);
+        while f.len() > 1 {
+            f.sort_unstable_by_key(|p| p.nterms);
 
-        let mut gcd = f.pop().unwrap();
-        for p in f {
-            if gcd.is_one() {
-                return gcd;
+            let mut next = Vec::with_capacity(f.len().div_ceil(2));
+            let mut it = f.into_iter();
+            while let Some(a) = it.next() {
+                let Some(b) = it.next() else {
+                    next.push(a);
+                    break;
+                };
+
+                let gcd = MultivariatePolynomial::gcd(&a, &b);
+                if gcd.is_one() {
+                    return gcd;
+                }
+                next.push(gcd);
             }
 
-            gcd = MultivariatePolynomial::gcd(&gcd, &p);
+            f = next;
         }
-        gcd
+
+        f.pop().unwrap()
+    }
+
+    /// Compute the gcd of multiple polynomials. The content of the coefficients
+    /// is extracted first, so that the polynomial gcds only operate on
+    /// primitive polynomials, and the computation stops early if the gcd is one.
+    pub fn gcd_multiple(polys: &[Self]) -> Self {
+        assert!(!polys.is_empty(), "Cannot compute the gcd of an empty list");
+
+        let f: Vec<_> = polys.iter().filter(|p| !p.is_zero()).collect();
+        if f.is_empty() {
+            return polys[0].clone();
+        }
+
+        let field = f[0].field;
+        let mut content = f[0].content();
+        let f: Vec<_> = f
+            .into_iter()
+            .map(|p| {
+                let c = p.content();
+                content = field.gcd(&content, &c);
+                p.clone().div_coeff(&c)
+            })
+            .collect();
+
+        if f.iter().any(|p| p.is_constant()) {
+            return f[0].new_from_constant(content);
+        }
+
+        PolynomialGCD::gcd_multiple(f).mul_coeff(content)
     }
 
     /// Compute the GCD for simple cases.
@@ -1529,17 +1584,38 @@ impl<R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> MultivariatePolynomial<
     }
 
     /// Compute the gcd of two multivariate polynomials.
-    #[instrument(skip_all)]
     pub fn gcd(
         a: &MultivariatePolynomial<R, E>,
         b: &MultivariatePolynomial<R, E>,
     ) -> MultivariatePolynomial<R, E> {
+        uncancellable(MultivariatePolynomial::gcd_impl(a, b, None))
+    }
+
+    /// Compute the gcd of two multivariate polynomials, or return an error
+    /// when the `token` is cancelled. The token is checked in the main
+    /// loops of the modular gcd algorithms.
+    pub fn try_gcd(
+        a: &MultivariatePolynomial<R, E>,
+        b: &MultivariatePolynomial<R, E>,
+        token: &CancellationToken,
+    ) -> Result<MultivariatePolynomial<R, E>, Cancelled> {
+        MultivariatePolynomial::gcd_impl(a, b, Some(token))
+    }
+
+    #[instrument(skip_all)]
+    pub(crate) fn gcd_impl(
+        a: &MultivariatePolynomial<R, E>,
+        b: &MultivariatePolynomial<R, E>,
+        token: Option<&CancellationToken>,
+    ) -> Result<MultivariatePolynomial<R, E>, Cancelled> {
         debug_assert_eq!(a.nvars, b.nvars);
         debug!("gcd of {} and {}", a, b);
+        instrument_scope!("gcd", a.nterms + b.nterms);
+        count_operations!(gcd_calls, 1);
 
         if let Some(g) = MultivariatePolynomial::simple_gcd(a, b) {
             debug!("Simple {} ", g);
-            return g;
+            return Ok(g);
         }
 
         // a and b are only copied when needed
@@ -1576,12 +1652,12 @@ impl<R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> MultivariatePolynomial<
         let mut base_degree: SmallVec<[Option<E>; INLINED_EXPONENTS]> = smallvec![None; a.nvars];
 
         if let Some(g) = MultivariatePolynomial::simple_gcd(&a, &b) {
-            return rescale_gcd(
+            return Ok(rescale_gcd(
                 g,
                 &shared_degree,
                 &base_degree,
                 &a.new_from_constant(a.field.one()),
-            );
+            ));
         }
 
         // check if the polynomial are functions of x^n, n > 1
@@ -1657,14 +1733,16 @@ impl<R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> MultivariatePolynomial<
             g
         }
 
-        if let Some(gcd) = PolynomialGCD::heuristic_gcd(&a, &b) {
-            debug!("Heuristic gcd succeeded: {}", gcd.0);
-            return rescale_gcd(
-                gcd.0,
-                &shared_degree,
-                &base_degree,
-                &a.new_from_constant(a.field.one()),
-            );
+        if get_gcd_algorithm() == GcdAlgorithm::Auto {
+            if let Some(gcd) = PolynomialGCD::heuristic_gcd(&a, &b) {
+                debug!("Heuristic gcd succeeded: {}", gcd.0);
+                return Ok(rescale_gcd(
+                    gcd.0,
+                    &shared_degree,
+                    &base_degree,
+                    &a.new_from_constant(a.field.one()),
+                ));
+            }
         }
 
         // store which variables appear in which expression
@@ -1681,12 +1759,12 @@ impl<R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> MultivariatePolynomial<
 
         if a == b {
             debug!("Equal {} ", a);
-            return rescale_gcd(
+            return Ok(rescale_gcd(
                 a.into_owned(),
                 &shared_degree,
                 &base_degree,
                 &MultivariatePolynomial::one(b.field),
-            );
+            ));
         }
 
         // compute the gcd efficiently if some variables do not occur in both
@@ -1710,30 +1788,30 @@ impl<R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> MultivariatePolynomial<
 
             let f = a1.into_values().chain(b1.into_values()).collect();
 
-            return rescale_gcd(
+            return Ok(rescale_gcd(
                 PolynomialGCD::gcd_multiple(f),
                 &shared_degree,
                 &base_degree,
                 &MultivariatePolynomial::one(a.field),
-            );
+            ));
         }
 
         // try if b divides a or vice versa, doing a heuristical length check first
         if a.nterms >= b.nterms && a.divides(&b).is_some() {
-            return rescale_gcd(
+            return Ok(rescale_gcd(
                 b.into_owned(),
                 &shared_degree,
                 &base_degree,
                 &MultivariatePolynomial::one(a.field),
-            );
+            ));
         }
         if a.nterms <= b.nterms && b.divides(&a).is_some() {
-            return rescale_gcd(
+            return Ok(rescale_gcd(
                 a.into_owned(),
                 &shared_degree,
                 &base_degree,
                 &MultivariatePolynomial::one(b.field),
-            );
+            ));
         }
 
         // check if the polynomial is linear in a variable and compute the gcd using the univariate content
@@ -1745,40 +1823,67 @@ impl<R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> MultivariatePolynomial<
 
                 if !cont.is_one() {
                     let cont_p2 = p2.univariate_content(var);
-                    cont = MultivariatePolynomial::gcd(&cont, &cont_p2);
+                    cont = MultivariatePolynomial::gcd_impl(&cont, &cont_p2, token)?;
                 }
 
                 if p2.divides(&p1_prim).is_some() {
-                    return rescale_gcd(p1_prim, &shared_degree, &base_degree, &cont);
+                    return Ok(rescale_gcd(p1_prim, &shared_degree, &base_degree, &cont));
                 } else {
-                    return rescale_gcd(
+                    return Ok(rescale_gcd(
                         cont,
                         &shared_degree,
                         &base_degree,
                         &p1.new_from_constant(p1.field.one()),
-                    );
+                    ));
                 }
             }
         }
 
-        let mut vars: SmallVec<[_; INLINED_EXPONENTS]> = scratch
+        let vars: SmallVec<[_; INLINED_EXPONENTS]> = scratch
             .iter()
             .enumerate()
             .filter_map(|(i, v)| if *v == 3 { Some(i) } else { None })
             .collect();
 
+        let (g, content) = MultivariatePolynomial::gcd_interpolate(a, b, vars, None, token)?;
+        Ok(rescale_gcd(g, &shared_degree, &base_degree, &content))
+    }
+
+    /// Compute the gcd of the polynomials `a` and `b` that both depend on all variables in `vars`
+    /// using the modular algorithm for the coefficient ring, after removing the content in the
+    /// first variable. Returns the gcd without the content and the gcd of the contents.
+    ///
+    /// If `user_bounds` is `None`, the degree bounds are estimated and the variables are
+    /// ordered by decreasing bound. Otherwise, the order of `vars` and the bounds are used as given.
+    fn gcd_interpolate(
+        mut a: Cow<'_, Self>,
+        mut b: Cow<'_, Self>,
+        mut vars: SmallVec<[usize; INLINED_EXPONENTS]>,
+        user_bounds: Option<&[E]>,
+        token: Option<&CancellationToken>,
+    ) -> Result<(Self, Self), Cancelled> {
         // determine safe bounds for variables in the gcd
         let mut bounds: SmallVec<[_; INLINED_EXPONENTS]> =
             (0..a.nvars).map(|i| a.degree(i).min(b.degree(i))).collect();
 
-        // find better upper bounds for all variables
-        // these bounds could actually be wrong due to an unfortunate prime or sampling points
-        let mut tight_bounds = R::get_gcd_var_bounds(&a, &b, &vars, &bounds);
-
-        // Determine a good variable ordering based on the estimated degree (decreasing) in the gcd.
-        // If it is different from the input, make a copy and rearrange so that the
-        // polynomials do not have to be sorted after filling in variables.
-        vars.sort_by(|&i, &j| tight_bounds[j].cmp(&tight_bounds[i]));
+        let mut tight_bounds: SmallVec<[_; INLINED_EXPONENTS]> =
+            if let Some(user_bounds) = user_bounds {
+                user_bounds
+                    .iter()
+                    .zip(&bounds)
+                    .map(|(u, b)| (*u).min(*b))
+                    .collect()
+            } else {
+                // find better upper bounds for all variables
+                // these bounds could actually be wrong due to an unfortunate prime or sampling points
+                let tight_bounds = R::get_gcd_var_bounds(&a, &b, &vars, &bounds);
+
+                // Determine a good variable ordering based on the estimated degree (decreasing) in the gcd.
+                // If it is different from the input, make a copy and rearrange so that the
+                // polynomials do not have to be sorted after filling in variables.
+                vars.sort_by(|&i, &j| tight_bounds[j].cmp(&tight_bounds[i]));
+                tight_bounds
+            };
 
         // strip the gcd of the univariate contents wrt the new first variable
         let content = if vars.len() > 1 {
@@ -1842,13 +1947,82 @@ impl<R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> MultivariatePolynomial<
             },
             &mut bounds,
             &mut tight_bounds,
-        );
+            token,
+        )?;
 
         if rearrange {
             g = g.rearrange(&vars, true);
         }
 
-        rescale_gcd(g, &shared_degree, &base_degree, &content)
+        Ok((g, content))
+    }
+
+    /// Compute the gcd of two multivariate polynomials with the modular algorithm, where the
+    /// caller supplies the variable order `vars` and upper bounds `bounds[v]` on the degree of
+    /// the gcd in every variable `v`. This skips the heuristic gcd and the estimation of the
+    /// degree bounds using images modulo a prime, which saves work when tight bounds are known.
+    ///
+    /// `vars` must contain every variable that occurs in `a` or `b`; the first variable is
+    /// the main variable of the interpolation. Bounds that are too small are relaxed
+    /// after failed interpolations, but this costs more work than a probing stage.
+    pub fn gcd_with_bounds(
+        a: &MultivariatePolynomial<R, E>,
+        b: &MultivariatePolynomial<R, E>,
+        vars: &[usize],
+        bounds: &[E],
+    ) -> MultivariatePolynomial<R, E> {
+        debug_assert_eq!(a.nvars, b.nvars);
+        assert_eq!(
+            bounds.len(),
+            a.nvars,
+            "Expected a degree bound for every variable"
+        );
+        instrument_scope!("gcd", a.nterms + b.nterms);
+        count_operations!(gcd_calls, 1);
+
+        if let Some(g) = MultivariatePolynomial::simple_gcd(a, b) {
+            return g;
+        }
+
+        let mut scratch: SmallVec<[i32; INLINED_EXPONENTS]> = smallvec![0i32; a.nvars];
+        for (p, inc) in [(a, 1), (b, 2)] {
+            for t in p.into_iter() {
+                for (e, ee) in scratch.iter_mut().zip(t.exponents) {
+                    if !ee.is_zero() {
+                        *e |= inc;
+                    }
+                }
+            }
+        }
+
+        for (v, s) in scratch.iter().enumerate() {
+            assert!(
+                *s == 0 || vars.contains(&v),
+                "Variable {} occurs in the input but not in the variable order",
+                v
+            );
+        }
+
+        // variables that do not occur in both polynomials cannot be interpolated
+        if scratch.iter().any(|x| *x > 0 && *x < 3) {
+            debug!("Not all variables occur in both polynomials, using the regular gcd");
+            return MultivariatePolynomial::gcd(a, b);
+        }
+
+        let vars = vars.iter().filter(|v| scratch[**v] == 3).cloned().collect();
+        let (mut g, content) = uncancellable(MultivariatePolynomial::gcd_interpolate(
+            Cow::Borrowed(a),
+            Cow::Borrowed(b),
+            vars,
+            Some(bounds),
+            None,
+        ));
+
+        if !content.is_one() {
+            g = g * &content;
+        }
+        g.normalize();
+        g
     }
 }
 
@@ -1861,21 +2035,8 @@ where
         &self,
         field: FiniteField<u32>,
     ) -> MultivariatePolynomial<FiniteField<u32>, E> {
-        let mut newc = Vec::with_capacity(self.coefficients.len());
-        let mut newe = Vec::with_capacity(self.exponents.len());
-
-        for m in self.into_iter() {
-            let nc = m.coefficient.to_finite_field(&field);
-            if !FiniteField::<u32>::is_zero(&nc) {
-                newc.push(nc);
-                newe.extend(m.exponents);
-            }
-        }
-
-        let mut a = MultivariatePolynomial::new(self.nvars, field, None, None);
-        a.nterms = newc.len();
-        a.exponents = newe;
-        a.coefficients = newc;
+        let mut a = self.map_coeff(|c| c.to_finite_field(&field), field);
+        a.var_map = None;
         a
     }
 }
@@ -2058,7 +2219,7 @@ impl<E: Exponent> MultivariatePolynomial<IntegerRing, E> {
     /// Compute the gcd of multiple polynomials efficiently.
     /// `gcd(f0,f1,f2,...)=gcd(f0,f1+k2*f(2)+k3*f(3))`
     /// with high likelihood.
-    pub fn gcd_multiple(
+    fn gcd_linear_combination(
         mut f: Vec<MultivariatePolynomial<IntegerRing, E>>,
     ) -> MultivariatePolynomial<IntegerRing, E> {
         assert!(!f.is_empty());
@@ -2169,7 +2330,8 @@ impl<E: Exponent> MultivariatePolynomial<IntegerRing, E> {
         vars: &[usize], // variables
         bounds: &mut [E],
         tight_bounds: &mut [E],
-    ) -> Self {
+        token: Option<&CancellationToken>,
+    ) -> Result<Self, Cancelled> {
         debug!("Zippel gcd of {} and {}", a, b);
         #[cfg(debug_assertions)]
         {
@@ -2186,6 +2348,8 @@ impl<E: Exponent> MultivariatePolynomial<IntegerRing, E> {
         let mut pi = 0;
 
         'newfirstprime: loop {
+            checkpoint(token, "gcd")?;
+            progress::report("gcd", pi, None);
             pi += 1;
 
             if pi == LARGE_U32_PRIMES.len() {
@@ -2217,7 +2381,8 @@ impl<E: Exponent> MultivariatePolynomial<IntegerRing, E> {
                 vars,
                 bounds,
                 tight_bounds,
-            ) {
+                token,
+            )? {
                 Some(x) => x,
                 None => {
                     debug!("Modular GCD failed: getting new prime");
@@ -2262,14 +2427,11 @@ impl<E: Exponent> MultivariatePolynomial<IntegerRing, E> {
             let lcoeff_factor = gp.field.div(&gammap, &gpc);
 
             // construct the gcd suggestion in Z
-            let mut gm = a.new_from(Some(gp.nterms));
-            gm.nterms = gp.nterms;
-            gm.exponents = gp.exponents.clone();
-            gm.coefficients = gp
-                .coefficients
-                .iter()
-                .map(|x| Integer::from_finite_field_u32(gp.field, &gp.field.mul(x, &lcoeff_factor)))
-                .collect();
+            let mut gm = gp.map_coeff(
+                |x| Integer::from_finite_field_u32(gp.field, &gp.field.mul(x, &lcoeff_factor)),
+                a.field,
+            );
+            gm.var_map = a.var_map.clone();
 
             let mut m = Integer::Natural(p as i64); // size of finite field
 
@@ -2279,6 +2441,9 @@ impl<E: Exponent> MultivariatePolynomial<IntegerRing, E> {
 
             // add new primes until we can reconstruct the full gcd
             'newprime: loop {
+                checkpoint(token, "gcd")?;
+                progress::report("gcd", pi, None);
+
                 if gm == old_gm {
                     // divide by integer content
                     let gmc = gm.content();
@@ -2286,7 +2451,7 @@ impl<E: Exponent> MultivariatePolynomial<IntegerRing, E> {
 
                     debug!("Final suggested gcd: {}", gc);
                     if gc.is_one() || (a.divides(&gc).is_some() && b.divides(&gc).is_some()) {
-                        return gc;
+                        return Ok(gc);
                     }
 
                     // if it does not divide, we need more primes
@@ -2436,7 +2601,8 @@ pub trait PolynomialGCD<E: Exponent>: Ring {
         vars: &[usize],
         bounds: &mut [E],
         tight_bounds: &mut [E],
-    ) -> MultivariatePolynomial<Self, E>;
+        token: Option<&CancellationToken>,
+    ) -> Result<MultivariatePolynomial<Self, E>, Cancelled>;
     fn get_gcd_var_bounds(
         a: &MultivariatePolynomial<Self, E>,
         b: &MultivariatePolynomial<Self, E>,
@@ -2504,7 +2670,7 @@ impl<E: Exponent> PolynomialGCD<E> for IntegerRing {
     }
 
     fn gcd_multiple(f: Vec<MultivariatePolynomial<Self, E>>) -> MultivariatePolynomial<Self, E> {
-        MultivariatePolynomial::gcd_multiple(f)
+        MultivariatePolynomial::gcd_linear_combination(f)
     }
 
     fn gcd(
@@ -2513,8 +2679,9 @@ impl<E: Exponent> PolynomialGCD<E> for IntegerRing {
         vars: &[usize],
         bounds: &mut [E],
         tight_bounds: &mut [E],
-    ) -> MultivariatePolynomial<IntegerRing, E> {
-        MultivariatePolynomial::gcd_zippel(a, b, vars, bounds, tight_bounds)
+        token: Option<&CancellationToken>,
+    ) -> Result<MultivariatePolynomial<IntegerRing, E>, Cancelled> {
+        MultivariatePolynomial::gcd_zippel(a, b, vars, bounds, tight_bounds, token)
     }
 
     fn get_gcd_var_bounds(
@@ -2572,7 +2739,8 @@ impl<E: Exponent> PolynomialGCD<E> for RationalField {
         vars: &[usize],
         bounds: &mut [E],
         tight_bounds: &mut [E],
-    ) -> MultivariatePolynomial<Self, E> {
+        token: Option<&CancellationToken>,
+    ) -> Result<MultivariatePolynomial<Self, E>, Cancelled> {
         // remove the content so that the polynomials have integer coefficients
         let content = a.field.gcd(&a.content(), &b.content());
 
@@ -2603,7 +2771,7 @@ impl<E: Exponent> PolynomialGCD<E> for RationalField {
         }
 
         let res_int =
-            MultivariatePolynomial::gcd_zippel(&a_int, &b_int, vars, bounds, tight_bounds);
+            MultivariatePolynomial::gcd_zippel(&a_int, &b_int, vars, bounds, tight_bounds, token)?;
 
         let mut res = a.new_from(Some(res_int.nterms));
 
@@ -2614,7 +2782,7 @@ impl<E: Exponent> PolynomialGCD<E> for RationalField {
             );
         }
 
-        res
+        Ok(res)
     }
 
     fn get_gcd_var_bounds(
@@ -2671,9 +2839,13 @@ where
         vars: &[usize],
         bounds: &mut [E],
         tight_bounds: &mut [E],
-    ) -> MultivariatePolynomial<FiniteField<UField>, E> {
+        token: Option<&CancellationToken>,
+    ) -> Result<MultivariatePolynomial<FiniteField<UField>, E>, Cancelled> {
         assert!(!a.is_zero() || !b.is_zero());
-        MultivariatePolynomial::gcd_shape_modular(a, b, vars, bounds, tight_bounds).unwrap()
+        Ok(
+            MultivariatePolynomial::gcd_shape_modular(a, b, vars, bounds, tight_bounds, token)?
+                .unwrap(),
+        )
     }
 
     fn get_gcd_var_bounds(
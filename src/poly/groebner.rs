@@ -0,0 +1,1070 @@
+use std::{
+    collections::BTreeMap,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use ahash::HashMap;
+use rand::Rng;
+use tracing::debug;
+
+use crate::{
+    cancel::{checkpoint, uncancellable, CancellationToken, Cancelled},
+    rings::{
+        finite_field::{FiniteField, FiniteFieldCore, ToFiniteField},
+        integer::{Integer, IntegerRing},
+        linear_system::Matrix,
+        rational::{Rational, RationalField},
+        Field, Ring,
+    },
+    utils,
+};
+
+use super::{gcd::LARGE_U32_PRIMES, polynomial::MultivariatePolynomial, Exponent};
+
+/// A reduced Gröbner basis of an ideal, in the lexicographic monomial order
+/// in which the polynomials are stored.
+#[derive(Clone, Debug)]
+pub struct GroebnerBasis<F: Field, E: Exponent> {
+    pub system: Vec<MultivariatePolynomial<F, E>>,
+}
+
+/// An S-pair `(i, j)` of the basis whose remainder was added to the basis
+/// in Buchberger's algorithm, with the leading monomial of the remainder.
+type TraceStep<E> = (usize, usize, Vec<E>);
+
+/// Check if the monomial `a` divides the monomial `b`.
+fn divides<E: Exponent>(a: &[E], b: &[E]) -> bool {
+    a.iter().zip(b).all(|(ea, eb)| ea <= eb)
+}
+
+impl<F: Field, E: Exponent> GroebnerBasis<F, E> {
+    /// Compute the reduced Gröbner basis of the ideal generated by `ideal`
+    /// with Buchberger's algorithm. Pairs with coprime leading monomials
+    /// are skipped, as their S-polynomial always reduces to zero.
+    pub fn new(ideal: &[MultivariatePolynomial<F, E>]) -> Self {
+        uncancellable(Self::new_impl(ideal, None))
+    }
+
+    /// Compute the reduced Gröbner basis of the ideal generated by `ideal`, or return
+    /// an error when the `token` is cancelled. The token is checked before every S-pair
+    /// is reduced.
+    pub fn try_new(
+        ideal: &[MultivariatePolynomial<F, E>],
+        token: &CancellationToken,
+    ) -> Result<Self, Cancelled> {
+        Self::new_impl(ideal, Some(token))
+    }
+
+    fn new_impl(
+        ideal: &[MultivariatePolynomial<F, E>],
+        token: Option<&CancellationToken>,
+    ) -> Result<Self, Cancelled> {
+        let mut basis: Vec<_> = ideal
+            .iter()
+            .filter(|p| !p.is_zero())
+            .map(Self::make_monic)
+            .collect();
+
+        Self::buchberger(&mut basis, &mut vec![], token)?;
+        Ok(GroebnerBasis {
+            system: Self::interreduce(&basis),
+        })
+    }
+
+    /// Extend the monic generators `basis` to a Gröbner basis. Every S-pair that
+    /// does not reduce to zero is recorded in `trace`, with the leading monomial of its remainder.
+    fn buchberger(
+        basis: &mut Vec<MultivariatePolynomial<F, E>>,
+        trace: &mut Vec<TraceStep<E>>,
+        token: Option<&CancellationToken>,
+    ) -> Result<(), Cancelled> {
+        let mut pairs: Vec<(usize, usize)> = (0..basis.len())
+            .flat_map(|j| (0..j).map(move |i| (i, j)))
+            .collect();
+
+        while let Some((i, j)) = pairs.pop() {
+            checkpoint(token, "groebner")?;
+
+            let (lt_i, lt_j) = (basis[i].last_exponents(), basis[j].last_exponents());
+            if lt_i
+                .iter()
+                .zip(lt_j)
+                .all(|(a, b)| a.is_zero() || b.is_zero())
+            {
+                continue;
+            }
+
+            let s = Self::reduce(&Self::s_polynomial(&basis[i], &basis[j]), basis);
+            if !s.is_zero() {
+                trace.push((i, j, s.last_exponents().to_vec()));
+                pairs.extend((0..basis.len()).map(|k| (k, basis.len())));
+                basis.push(Self::make_monic(&s));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Extend the monic generators `basis` by only reducing the S-pairs in `trace`,
+    /// in order. Returns `false` if a remainder has a different leading monomial
+    /// than in the trace, which means that the trace or the current field is unlucky.
+    fn replay(basis: &mut Vec<MultivariatePolynomial<F, E>>, trace: &[TraceStep<E>]) -> bool {
+        for (i, j, lm) in trace {
+            let s = Self::reduce(&Self::s_polynomial(&basis[*i], &basis[*j]), basis);
+            if s.is_zero() || s.last_exponents() != lm.as_slice() {
+                return false;
+            }
+            basis.push(Self::make_monic(&s));
+        }
+        true
+    }
+
+    /// Check if the monic polynomials `basis` form a Gröbner basis,
+    /// i.e., if all their S-polynomials reduce to zero.
+    fn is_groebner_basis(basis: &[MultivariatePolynomial<F, E>]) -> bool {
+        (0..basis.len()).all(|j| {
+            (0..j).all(|i| {
+                let (lt_i, lt_j) = (basis[i].last_exponents(), basis[j].last_exponents());
+                lt_i.iter()
+                    .zip(lt_j)
+                    .all(|(a, b)| a.is_zero() || b.is_zero())
+                    || Self::reduce(&Self::s_polynomial(&basis[i], &basis[j]), basis).is_zero()
+            })
+        })
+    }
+
+    /// Turn the Gröbner basis `basis` into the reduced Gröbner basis, sorted by leading monomial.
+    fn interreduce(basis: &[MultivariatePolynomial<F, E>]) -> Vec<MultivariatePolynomial<F, E>> {
+        // remove the polynomials whose leading monomial is divisible by another one
+        let mut minimal: Vec<MultivariatePolynomial<F, E>> = vec![];
+        for (i, p) in basis.iter().enumerate() {
+            let redundant = basis.iter().enumerate().any(|(j, q)| {
+                divides(q.last_exponents(), p.last_exponents())
+                    && (q.last_exponents() != p.last_exponents() || j < i)
+            });
+            if !redundant {
+                minimal.push(p.clone());
+            }
+        }
+
+        let mut system = vec![];
+        for i in 0..minimal.len() {
+            let (before, rest) = minimal.split_at(i);
+            let others: Vec<_> = before.iter().chain(&rest[1..]).cloned().collect();
+            system.push(Self::reduce(&rest[0], &others));
+        }
+        system.sort_by(|a, b| a.last_exponents().cmp(b.last_exponents()));
+        system
+    }
+
+    /// Divide `p` by its leading coefficient.
+    fn make_monic(p: &MultivariatePolynomial<F, E>) -> MultivariatePolynomial<F, E> {
+        let inv = p.field.inv(&p.lcoeff());
+        p.clone().mul_coeff(inv)
+    }
+
+    /// Compute the S-polynomial of `a` and `b`, that cancels their leading terms.
+    fn s_polynomial(
+        a: &MultivariatePolynomial<F, E>,
+        b: &MultivariatePolynomial<F, E>,
+    ) -> MultivariatePolynomial<F, E> {
+        let lcm: Vec<E> = a
+            .last_exponents()
+            .iter()
+            .zip(b.last_exponents())
+            .map(|(ea, eb)| *ea.max(eb))
+            .collect();
+
+        let shift = |p: &MultivariatePolynomial<F, E>| {
+            let m: Vec<E> = lcm
+                .iter()
+                .zip(p.last_exponents())
+                .map(|(l, e)| *l - *e)
+                .collect();
+            let inv = p.field.inv(&p.lcoeff());
+            p.clone().checked_mul_monomial(&inv, &m).unwrap()
+        };
+
+        shift(a) - shift(b)
+    }
+
+    /// Compute the normal form of `p` with respect to `basis`, by repeatedly
+    /// cancelling terms that are divisible by a leading monomial of the basis.
+    pub fn reduce(
+        p: &MultivariatePolynomial<F, E>,
+        basis: &[MultivariatePolynomial<F, E>],
+    ) -> MultivariatePolynomial<F, E> {
+        let mut p = p.clone();
+        let mut r = p.new_from(None);
+
+        while !p.is_zero() {
+            let lt = p.last_exponents().to_vec();
+            let lc = p.lcoeff();
+
+            if let Some(g) = basis.iter().find(|g| divides(g.last_exponents(), &lt)) {
+                let m: Vec<E> = lt
+                    .iter()
+                    .zip(g.last_exponents())
+                    .map(|(a, b)| *a - *b)
+                    .collect();
+                let c = p.field.div(&lc, &g.lcoeff());
+                p = p - g.clone().checked_mul_monomial(&c, &m).unwrap();
+            } else {
+                p.coefficients.pop();
+                p.exponents.truncate((p.nterms - 1) * p.nvars);
+                p.nterms -= 1;
+                r.append_monomial(lc, &lt);
+            }
+        }
+
+        r
+    }
+
+    /// Check if the ideal contains one, in which case the polynomial system has no solutions.
+    pub fn is_inconsistent(&self) -> bool {
+        self.system.iter().any(|p| p.is_constant())
+    }
+
+    /// Check if the ideal is zero-dimensional, i.e., if the polynomial system has finitely
+    /// many solutions. This is the case when every variable has a pure power as a leading
+    /// monomial, or when the system is inconsistent.
+    pub fn is_zero_dimensional(&self) -> bool {
+        let Some(nvars) = self.system.first().map(|p| p.nvars) else {
+            return false;
+        };
+
+        self.is_inconsistent()
+            || (0..nvars).all(|v| {
+                self.system.iter().any(|p| {
+                    p.last_exponents()
+                        .iter()
+                        .enumerate()
+                        .all(|(i, e)| (i == v) != e.is_zero())
+                })
+            })
+    }
+
+    /// Get a maximal set of variables that are independent modulo the ideal, starting
+    /// from the last variable. These variables parametrize the solutions of the polynomial system.
+    pub fn free_variables(&self) -> Vec<usize> {
+        let Some(nvars) = self.system.first().map(|p| p.nvars) else {
+            return vec![];
+        };
+
+        let mut free: Vec<usize> = vec![];
+        for v in (0..nvars).rev() {
+            free.push(v);
+            let dependent = self.system.iter().any(|p| {
+                p.last_exponents()
+                    .iter()
+                    .enumerate()
+                    .all(|(i, e)| e.is_zero() || free.contains(&i))
+            });
+            if dependent {
+                free.pop();
+            }
+        }
+        free.reverse();
+        free
+    }
+
+    /// Get the monomials that are not divisible by any leading monomial of the basis,
+    /// which form a basis of the quotient ring. Returns `None` if the ideal is
+    /// not zero-dimensional.
+    pub fn standard_monomials(&self) -> Option<Vec<Vec<E>>> {
+        if !self.is_zero_dimensional() {
+            return None;
+        }
+
+        let nvars = self.system[0].nvars;
+        let is_standard = |m: &[E]| !self.system.iter().any(|p| divides(p.last_exponents(), m));
+
+        let mut monomials = vec![];
+        let mut stack = vec![vec![E::zero(); nvars]];
+        if !is_standard(&stack[0]) {
+            return Some(monomials);
+        }
+
+        while let Some(m) = stack.pop() {
+            // only increase the variables from the last non-zero one, so that
+            // every monomial is generated once
+            let start = m.iter().rposition(|e| !e.is_zero()).unwrap_or(0);
+            for v in start..nvars {
+                let mut next = m.clone();
+                next[v] += E::one();
+                if is_standard(&next) {
+                    stack.push(next);
+                }
+            }
+            monomials.push(m);
+        }
+
+        monomials.sort();
+        Some(monomials)
+    }
+
+    /// Count the number of solutions of the polynomial system, with multiplicity,
+    /// or return `None` if there are infinitely many.
+    pub fn count_solutions(&self) -> Option<usize> {
+        self.standard_monomials().map(|m| m.len())
+    }
+
+    /// Get the matrix of the multiplication by `f` in the quotient ring, in the basis of
+    /// the `monomials` returned by [`GroebnerBasis::standard_monomials`]. Column `j`
+    /// contains the normal form of `f` times the `j`-th monomial.
+    pub fn multiplication_matrix(
+        &self,
+        f: &MultivariatePolynomial<F, E>,
+        monomials: &[Vec<E>],
+    ) -> Matrix<F> {
+        let field = f.field;
+        let index: HashMap<&[E], usize> = monomials
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (m.as_slice(), i))
+            .collect();
+
+        let n = monomials.len() as u32;
+        let mut m = Matrix::new(n, n, field);
+        for (j, mon) in monomials.iter().enumerate() {
+            let p = Self::reduce(
+                &f.clone().checked_mul_monomial(&field.one(), mon).unwrap(),
+                &self.system,
+            );
+            for t in 0..p.nterms {
+                m[(index[p.exponents(t)] as u32, j as u32)] = p.coefficients[t].clone();
+            }
+        }
+        m
+    }
+}
+
+/// The number of consecutive primes whose image does not match the trace,
+/// after which the trace is assumed to be unlucky and is recomputed.
+const MAX_UNLUCKY_PRIMES: usize = 3;
+
+/// The coefficients of the images of a Gröbner basis, combined over all primes.
+type CombinedImage<E> = Vec<BTreeMap<Vec<E>, Integer>>;
+
+impl<E: Exponent> GroebnerBasis<RationalField, E> {
+    /// Compute the reduced Gröbner basis of the ideal generated by `ideal` with a
+    /// modular algorithm, which avoids the growth of the intermediate coefficients
+    /// of a computation over the rationals.
+    ///
+    /// Buchberger's algorithm is run modulo a first prime, while recording which S-pairs
+    /// have a non-zero remainder. For the next primes, only these S-pairs are reduced.
+    /// The reduced bases are combined with the Chinese remainder theorem and their
+    /// coefficients are recovered with rational reconstruction, until the result stabilizes.
+    ///
+    /// The result is verified by checking that every generator of `ideal` reduces to zero
+    /// and that it is a Gröbner basis. This proves that the result generates an ideal that
+    /// contains `ideal`, which equals it unless the first prime is unlucky. This is the case
+    /// for finitely many primes only.
+    pub fn new_modular(ideal: &[MultivariatePolynomial<RationalField, E>]) -> Self {
+        let ideal: Vec<_> = ideal.iter().filter(|p| !p.is_zero()).cloned().collect();
+        if ideal.is_empty() {
+            return GroebnerBasis { system: vec![] };
+        }
+
+        let mut primes = LARGE_U32_PRIMES.iter();
+        let mut next_image = || loop {
+            let Some(p) = primes.next() else {
+                panic!("Ran out of primes for the modular Gröbner basis computation");
+            };
+
+            if let Some(image) = Self::to_finite_field_image(&ideal, *p) {
+                return (*p, image);
+            }
+        };
+
+        'newtrace: loop {
+            let (p, mut basis) = next_image();
+            let mut trace = vec![];
+            uncancellable(GroebnerBasis::buchberger(&mut basis, &mut trace, None));
+            let first = GroebnerBasis::interreduce(&basis);
+            debug!("Trace modulo {} with {} useful S-pairs", p, trace.len());
+
+            let mut combined: CombinedImage<E> = vec![BTreeMap::new(); first.len()];
+            let mut modulus = Integer::Natural(1);
+            Self::add_image(&mut combined, &mut modulus, &first);
+
+            let mut last_candidate = None;
+            let mut unlucky = 0;
+            loop {
+                if let Some(candidate) = Self::reconstruct(&combined, &modulus, &ideal[0]) {
+                    if last_candidate.as_ref() == Some(&candidate) {
+                        if ideal.iter().all(|g| Self::reduce(g, &candidate).is_zero())
+                            && Self::is_groebner_basis(&candidate)
+                        {
+                            return GroebnerBasis { system: candidate };
+                        }
+
+                        debug!("Verification failed: computing a new trace");
+                        continue 'newtrace;
+                    }
+                    last_candidate = Some(candidate);
+                }
+
+                let (p, mut basis) = next_image();
+                let image = if GroebnerBasis::replay(&mut basis, &trace) {
+                    Some(GroebnerBasis::interreduce(&basis))
+                } else {
+                    None
+                };
+
+                match image {
+                    Some(image)
+                        if image.len() == first.len()
+                            && image
+                                .iter()
+                                .zip(&first)
+                                .all(|(a, b)| a.last_exponents() == b.last_exponents()) =>
+                    {
+                        unlucky = 0;
+                        Self::add_image(&mut combined, &mut modulus, &image);
+                    }
+                    _ => {
+                        debug!("Image modulo {} does not match the trace", p);
+                        unlucky += 1;
+                        if unlucky == MAX_UNLUCKY_PRIMES {
+                            continue 'newtrace;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Map the generators to monic polynomials modulo `p`, or return `None`
+    /// if `p` divides a denominator or a leading coefficient.
+    fn to_finite_field_image(
+        ideal: &[MultivariatePolynomial<RationalField, E>],
+        p: u32,
+    ) -> Option<Vec<MultivariatePolynomial<FiniteField<u32>, E>>> {
+        let field = FiniteField::<u32>::new(p);
+
+        let mut image = vec![];
+        for g in ideal {
+            if g.coefficients
+                .iter()
+                .any(|c| FiniteField::<u32>::is_zero(&c.denominator().to_finite_field(&field)))
+            {
+                return None;
+            }
+
+            let gp = g.to_finite_field_u32(field);
+            if gp.is_zero() || gp.last_exponents() != g.last_exponents() {
+                return None;
+            }
+            image.push(GroebnerBasis::make_monic(&gp));
+        }
+
+        Some(image)
+    }
+
+    /// Combine the reduced Gröbner basis `image` modulo a prime with the images
+    /// modulo `modulus` using the Chinese remainder theorem.
+    fn add_image(
+        combined: &mut CombinedImage<E>,
+        modulus: &mut Integer,
+        image: &[MultivariatePolynomial<FiniteField<u32>, E>],
+    ) {
+        let field = image[0].field;
+        let p = Integer::Natural(field.get_prime() as i64);
+
+        for (c, g) in combined.iter_mut().zip(image) {
+            let mut new: BTreeMap<Vec<E>, Integer> = (0..g.nterms)
+                .map(|t| {
+                    (
+                        g.exponents(t).to_vec(),
+                        Integer::from_finite_field_u32(field, &g.coefficients[t]),
+                    )
+                })
+                .collect();
+
+            for (e, v) in c.iter_mut() {
+                let n = new.remove(e).unwrap_or(Integer::Natural(0));
+                *v = Integer::chinese_remainder(v.clone(), n, modulus.clone(), p.clone());
+            }
+
+            for (e, n) in new {
+                let v =
+                    Integer::chinese_remainder(Integer::Natural(0), n, modulus.clone(), p.clone());
+                c.insert(e, v);
+            }
+        }
+
+        *modulus = IntegerRing::new().mul(modulus, &p);
+    }
+
+    /// Reconstruct the rational coefficients of the combined images, or return `None`
+    /// if the modulus is too small. The polynomials are created with the variables of `template`.
+    fn reconstruct(
+        combined: &CombinedImage<E>,
+        modulus: &Integer,
+        template: &MultivariatePolynomial<RationalField, E>,
+    ) -> Option<Vec<MultivariatePolynomial<RationalField, E>>> {
+        combined
+            .iter()
+            .map(|c| {
+                let mut g = template.new_from(Some(c.len()));
+                for (e, v) in c {
+                    let r = Rational::rational_reconstruction(v, modulus)?;
+                    if !RationalField::is_zero(&r) {
+                        g.append_monomial(r, e);
+                    }
+                }
+                Some(g)
+            })
+            .collect()
+    }
+}
+
+impl<F: Field, E: Exponent> MultivariatePolynomial<F, E> {
+    /// Construct the equations `y_i - polys[i](x)` in the variables `[x_0, .., x_{n-1}, y_0, ..]`,
+    /// so that the variables `x` come first in the lexicographic order.
+    fn graph_ideal(polys: &[Self]) -> Vec<Self> {
+        let nvars = polys[0].nvars;
+        let total = nvars + polys.len();
+        let order: Vec<_> = (0..total).map(|i| (i < nvars).then_some(i)).collect();
+
+        polys
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let mut p = p.rearrange_with_growth(&order);
+                p.var_map = None;
+
+                let mut exp = vec![E::zero(); total];
+                exp[nvars + i] = E::one();
+                p.new_from_monomial(p.field.one(), exp) - p
+            })
+            .collect()
+    }
+
+    /// Restrict the polynomial to the last `n` variables, which are the only ones it depends on.
+    fn project_last(&self, n: usize) -> Self {
+        let order: Vec<_> = (self.nvars - n..self.nvars).map(Some).collect();
+        self.rearrange_with_growth(&order)
+    }
+
+    /// Compute the implicit polynomial relations between the coordinates `x_i` of the
+    /// parametrization `x_i = parametrization[i](t)`, by eliminating the parameters `t` with
+    /// a lexicographic Gröbner basis. For example, `x = t^2, y = t^3` yields `y^2 - x^3`.
+    /// The returned polynomials have one variable per coordinate.
+    pub fn implicitize(parametrization: &[Self]) -> Vec<Self> {
+        if parametrization.is_empty() {
+            return vec![];
+        }
+
+        let nparams = parametrization[0].nvars;
+        let basis = GroebnerBasis::new(&Self::graph_ideal(parametrization));
+
+        basis
+            .system
+            .into_iter()
+            .filter(|p| {
+                (0..p.nterms).all(|t| p.exponents(t)[..nparams].iter().all(|e| e.is_zero()))
+            })
+            .map(|p| p.project_last(parametrization.len()))
+            .collect()
+    }
+
+    /// Compute the inverse of the polynomial map `y_i = map[i](x)`, that has as many
+    /// components as variables. Returns polynomials `g_i` such that `x_i = g_i(y)`,
+    /// or `None` if the inverse is not a polynomial map.
+    pub fn invert_map(map: &[Self]) -> Option<Vec<Self>> {
+        let nvars = map.first()?.nvars;
+        if map.len() != nvars {
+            return None;
+        }
+
+        // in the lexicographic order, the basis contains x_i - g_i(y) for an invertible map
+        let basis = GroebnerBasis::new(&Self::graph_ideal(map));
+
+        let mut inverse = vec![];
+        for i in 0..nvars {
+            let p = basis.system.iter().find(|p| {
+                p.last_exponents().iter().enumerate().all(|(j, e)| {
+                    if j == i {
+                        *e == E::one()
+                    } else {
+                        e.is_zero()
+                    }
+                })
+            })?;
+
+            let mut g = p.clone();
+            g.coefficients.pop();
+            g.exponents.truncate((g.nterms - 1) * g.nvars);
+            g.nterms -= 1;
+
+            if (0..g.nterms).any(|t| g.exponents(t)[..nvars].iter().any(|e| !e.is_zero())) {
+                return None;
+            }
+
+            inverse.push(-g.project_last(nvars));
+        }
+
+        Some(inverse)
+    }
+}
+
+/// The solutions of a system of polynomial equations.
+#[derive(Clone, Debug)]
+pub enum PolynomialSystemSolution<E: Exponent> {
+    /// The system has no solutions.
+    Inconsistent,
+    /// The system has `count` solutions, counted with multiplicity. Every solution
+    /// is given as the numerical value `(re, im)` of every variable.
+    ZeroDimensional {
+        count: usize,
+        solutions: Vec<Vec<(f64, f64)>>,
+    },
+    /// The system has infinitely many solutions, that are parametrized by
+    /// the `free_variables`. The lexicographic Gröbner basis expresses the other variables
+    /// in terms of the free variables.
+    PositiveDimensional {
+        free_variables: Vec<usize>,
+        basis: GroebnerBasis<RationalField, E>,
+    },
+}
+
+/// Solve the system of polynomial equations `system = 0` using a Gröbner basis.
+/// The solutions of a zero-dimensional system are computed numerically from the
+/// eigenvectors of the multiplication matrix of a random linear combination of the variables.
+pub fn solve_polynomial_system<E: Exponent>(
+    system: &[MultivariatePolynomial<RationalField, E>],
+) -> PolynomialSystemSolution<E> {
+    let basis = GroebnerBasis::new(system);
+    if basis.is_inconsistent() {
+        return PolynomialSystemSolution::Inconsistent;
+    }
+
+    let Some(monomials) = basis.standard_monomials() else {
+        return PolynomialSystemSolution::PositiveDimensional {
+            free_variables: basis.free_variables(),
+            basis,
+        };
+    };
+
+    let nvars = system[0].nvars;
+    let field = RationalField::new();
+    let var = |v: usize| {
+        let mut exp = vec![E::zero(); nvars];
+        exp[v] = E::one();
+        system[0].new_from_monomial(field.one(), exp)
+    };
+
+    let to_f64 = |m: &Matrix<RationalField>| -> Vec<Vec<Complex>> {
+        m.row_iter()
+            .map(|r| {
+                r.iter()
+                    .map(|c| Complex::new(rational_to_f64(c), 0.))
+                    .collect()
+            })
+            .collect()
+    };
+
+    let mut rng = utils::rng("groebner::solve_polynomial_system");
+    let mut combination = system[0].new_from(None);
+    for v in 0..nvars {
+        combination = combination + var(v).mul_coeff(Rational::Natural(rng.gen_range(1..100), 1));
+    }
+
+    let m_comb = to_f64(&basis.multiplication_matrix(&combination, &monomials));
+    let m_vars: Vec<_> = (0..nvars)
+        .map(|v| to_f64(&basis.multiplication_matrix(&var(v), &monomials)))
+        .collect();
+
+    // the evaluation at a solution is a left eigenvector of every multiplication matrix
+    let solutions = eigenvalues(m_comb.clone())
+        .into_iter()
+        .map(|l| {
+            let w = left_eigenvector(&m_comb, l);
+            m_vars
+                .iter()
+                .map(|m| {
+                    let x = rayleigh_quotient(m, &w);
+                    (x.re, x.im)
+                })
+                .collect()
+        })
+        .collect();
+
+    PolynomialSystemSolution::ZeroDimensional {
+        count: monomials.len(),
+        solutions,
+    }
+}
+
+fn rational_to_f64(r: &Rational) -> f64 {
+    match r {
+        Rational::Natural(n, d) => *n as f64 / *d as f64,
+        Rational::Large(r) => r.to_f64(),
+    }
+}
+
+/// A complex floating point number, used for the numerical eigenvalue computations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    fn zero() -> Self {
+        Complex::new(0., 0.)
+    }
+
+    fn conj(self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+
+    fn norm(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    fn sqrt(self) -> Self {
+        let r = self.norm();
+        let re = ((r + self.re) / 2.).sqrt();
+        let im = ((r - self.re) / 2.).sqrt();
+        Complex::new(re, if self.im < 0. { -im } else { im })
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        let d = other.re * other.re + other.im * other.im;
+        let n = self * other.conj();
+        Complex::new(n.re / d, n.im / d)
+    }
+}
+
+/// Compute the eigenvalues of the square matrix `a` by reducing it to Hessenberg form
+/// and applying the shifted QR algorithm.
+fn eigenvalues(mut a: Vec<Vec<Complex>>) -> Vec<Complex> {
+    let n = a.len();
+
+    // reduce to Hessenberg form with stabilized elementary similarity transformations
+    for m in 1..n.saturating_sub(1) {
+        let pivot = (m..n)
+            .max_by(|i, j| a[*i][m - 1].norm().total_cmp(&a[*j][m - 1].norm()))
+            .unwrap();
+        if pivot != m {
+            a.swap(pivot, m);
+            for row in &mut a {
+                row.swap(pivot, m);
+            }
+        }
+
+        if a[m][m - 1].norm() == 0. {
+            continue;
+        }
+
+        for i in m + 1..n {
+            let y = a[i][m - 1] / a[m][m - 1];
+            if y.norm() == 0. {
+                continue;
+            }
+            let (top, bottom) = a.split_at_mut(i);
+            for (x, t) in bottom[0].iter_mut().zip(&top[m]) {
+                *x = *x - y * *t;
+            }
+            for row in &mut a {
+                row[m] = row[m] + y * row[i];
+            }
+        }
+    }
+
+    let mut eigenvalues = vec![];
+    let mut hi = n;
+    let mut iterations = 0;
+    while hi > 0 {
+        let h = hi - 1;
+
+        // find the start of the unreduced block
+        let mut l = h;
+        while l > 0 {
+            let scale = a[l][l].norm() + a[l - 1][l - 1].norm();
+            if a[l][l - 1].norm() <= f64::EPSILON * scale.max(f64::MIN_POSITIVE) {
+                a[l][l - 1] = Complex::zero();
+                break;
+            }
+            l -= 1;
+        }
+
+        if l == h {
+            eigenvalues.push(a[h][h]);
+            hi -= 1;
+            iterations = 0;
+            continue;
+        }
+
+        iterations += 1;
+        assert!(iterations < 1000, "QR algorithm did not converge");
+
+        // Wilkinson shift from the trailing 2x2 block, with an exceptional shift to break cycles
+        let (p, q, r, s) = (a[h - 1][h - 1], a[h - 1][h], a[h][h - 1], a[h][h]);
+        let mu = if iterations % 10 == 0 {
+            s + Complex::new(a[h][h - 1].norm(), 0.)
+        } else {
+            let half = Complex::new(0.5, 0.);
+            let mean = (p + s) * half;
+            let disc = ((p - s) * (p - s) * half * half + q * r).sqrt();
+            let (m1, m2) = (mean + disc, mean - disc);
+            if (m1 - s).norm() < (m2 - s).norm() {
+                m1
+            } else {
+                m2
+            }
+        };
+
+        for (i, row) in a.iter_mut().enumerate().take(hi).skip(l) {
+            row[i] = row[i] - mu;
+        }
+
+        // QR decomposition of the block with Givens rotations, followed by RQ
+        let mut rotations = vec![];
+        for k in l..h {
+            let (x, y) = (a[k][k], a[k + 1][k]);
+            let norm = x.norm().hypot(y.norm());
+            let (c, s) = if norm == 0. {
+                (Complex::new(1., 0.), Complex::zero())
+            } else {
+                let norm = Complex::new(norm, 0.);
+                (x / norm, y / norm)
+            };
+
+            let (top, bottom) = a.split_at_mut(k + 1);
+            for (t, b) in top[k][k..hi].iter_mut().zip(&mut bottom[0][k..hi]) {
+                let (u, v) = (*t, *b);
+                *t = c.conj() * u + s.conj() * v;
+                *b = c * v - s * u;
+            }
+            rotations.push((c, s));
+        }
+
+        for (k, (c, s)) in (l..h).zip(rotations) {
+            for row in a.iter_mut().take((k + 2).min(hi)).skip(l) {
+                let (u, v) = (row[k], row[k + 1]);
+                row[k] = c * u + s * v;
+                row[k + 1] = c.conj() * v - s.conj() * u;
+            }
+        }
+
+        for (i, row) in a.iter_mut().enumerate().take(hi).skip(l) {
+            row[i] = row[i] + mu;
+        }
+    }
+
+    eigenvalues
+}
+
+/// Solve `a x = b` with Gaussian elimination with partial pivoting.
+fn solve_linear(mut a: Vec<Vec<Complex>>, mut b: Vec<Complex>) -> Vec<Complex> {
+    let n = a.len();
+    for k in 0..n {
+        let pivot = (k..n)
+            .max_by(|i, j| a[*i][k].norm().total_cmp(&a[*j][k].norm()))
+            .unwrap();
+        a.swap(pivot, k);
+        b.swap(pivot, k);
+
+        if a[k][k].norm() == 0. {
+            a[k][k] = Complex::new(f64::EPSILON, 0.);
+        }
+
+        let (top, bottom) = a.split_at_mut(k + 1);
+        for (i, row) in bottom.iter_mut().enumerate() {
+            let y = row[k] / top[k][k];
+            for (x, t) in row[k..].iter_mut().zip(&top[k][k..]) {
+                *x = *x - y * *t;
+            }
+            b[k + 1 + i] = b[k + 1 + i] - y * b[k];
+        }
+    }
+
+    let mut x = vec![Complex::zero(); n];
+    for k in (0..n).rev() {
+        let mut s = b[k];
+        for (aa, xx) in a[k][k + 1..].iter().zip(&x[k + 1..]) {
+            s = s - *aa * *xx;
+        }
+        x[k] = s / a[k][k];
+    }
+    x
+}
+
+/// Compute a left eigenvector of `a` with eigenvalue `l` using inverse iteration.
+fn left_eigenvector(a: &[Vec<Complex>], l: Complex) -> Vec<Complex> {
+    let n = a.len();
+    let shift = l + Complex::new(1e-10 * (1. + l.norm()), 0.);
+    let shifted: Vec<Vec<Complex>> = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| if i == j { a[j][i] - shift } else { a[j][i] })
+                .collect()
+        })
+        .collect();
+
+    let mut w = vec![Complex::new(1., 0.); n];
+    for _ in 0..3 {
+        w = solve_linear(shifted.clone(), w);
+        let norm = w.iter().map(|x| x.norm() * x.norm()).sum::<f64>().sqrt();
+        for x in &mut w {
+            *x = *x / Complex::new(norm, 0.);
+        }
+    }
+    w
+}
+
+/// Compute `(w^H a^T w) / (w^H w)`, which is the eigenvalue of `a` for the left eigenvector `w`.
+fn rayleigh_quotient(a: &[Vec<Complex>], w: &[Complex]) -> Complex {
+    let mut num = Complex::zero();
+    let mut den = Complex::zero();
+    for (i, wi) in w.iter().enumerate() {
+        let mut aw = Complex::zero();
+        for (row, wj) in a.iter().zip(w) {
+            aw = aw + row[i] * *wj;
+        }
+        num = num + wi.conj() * aw;
+        den = den + wi.conj() * *wi;
+    }
+    num / den
+}
+
+#[cfg(test)]
+mod tests {
+    use smartstring::{LazyCompact, SmartString};
+
+    use crate::{
+        parser::parse,
+        poly::polynomial::MultivariatePolynomial,
+        rings::{rational::RationalField, Field},
+        state::State,
+    };
+
+    use super::GroebnerBasis;
+
+    /// Parse expanded polynomials in the variables `vars` with rational coefficients.
+    fn polys(vars: &[&str], inputs: &[&str]) -> Vec<MultivariatePolynomial<RationalField, u16>> {
+        let mut state = State::new();
+        let var_map: Vec<_> = vars.iter().map(|v| state.get_or_insert_var(v)).collect();
+        let var_name_map: Vec<SmartString<LazyCompact>> =
+            vars.iter().map(|v| (*v).into()).collect();
+        inputs
+            .iter()
+            .map(|i| {
+                parse(i)
+                    .unwrap()
+                    .to_polynomial(RationalField::new(), &var_map, &var_name_map)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    /// Check that the reduced Gröbner basis of `ideal` is `expected`, up to normalization.
+    fn check_basis(
+        vars: &[&str],
+        ideal: &[&str],
+        expected: &[&str],
+    ) -> GroebnerBasis<RationalField, u16> {
+        let basis = GroebnerBasis::new(&polys(vars, ideal));
+        assert_eq!(basis.system.len(), expected.len(), "{:?}", ideal);
+        for e in polys(vars, expected) {
+            let inv = e.field.inv(&e.lcoeff());
+            let e = e.mul_coeff(inv);
+            assert!(
+                basis.system.contains(&e),
+                "{} not in basis of {:?}",
+                e,
+                ideal
+            );
+        }
+        basis
+    }
+
+    #[test]
+    fn test_reduced_basis() {
+        // x = y^2 follows from x^2 = y and x*y = 1
+        let basis = check_basis(&["x", "y"], &["x^2-y", "x*y-1"], &["x-y^2", "y^3-1"]);
+        assert_eq!(basis.count_solutions(), Some(3));
+
+        let basis = check_basis(&["x", "y"], &["x^2+y^2-1", "x-y"], &["x-y", "2*y^2-1"]);
+        assert_eq!(basis.count_solutions(), Some(2));
+
+        // cyclic-3
+        let basis = check_basis(
+            &["x", "y", "z"],
+            &["x+y+z", "x*y+y*z+z*x", "x*y*z-1"],
+            &["x+y+z", "y^2+y*z+z^2", "z^3-1"],
+        );
+        assert_eq!(basis.count_solutions(), Some(6));
+    }
+
+    #[test]
+    fn test_inconsistent_and_positive_dimensional() {
+        let basis = check_basis(&["x", "y"], &["x*y-1", "x"], &["1"]);
+        assert!(basis.is_inconsistent());
+        assert_eq!(basis.count_solutions(), Some(0));
+
+        // the plane x = y in three dimensions
+        let basis = check_basis(&["x", "y", "z"], &["x-y", "x^2-y^2"], &["x-y"]);
+        assert!(!basis.is_zero_dimensional());
+        assert_eq!(basis.count_solutions(), None);
+    }
+
+    #[test]
+    fn test_modular_basis() {
+        for ideal in [
+            &["x^2+y^2-1", "x-y"][..],
+            &["3*x^2+2*x*y-5", "7*y^2-2*x+1"],
+            // the coefficients of the basis need several primes to be reconstructed
+            &[
+                "123456789012345678901*x*y-5",
+                "x^2-987654321987654321*y^2+3*y",
+            ],
+            &["x+y+z", "x*y+y*z+z*x", "x*y*z-1"],
+        ] {
+            let ideal = polys(&["x", "y", "z"], ideal);
+            assert_eq!(
+                GroebnerBasis::new_modular(&ideal).system,
+                GroebnerBasis::new(&ideal).system
+            );
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use rand::Rng;
+
+use crate::rings::Ring;
+use crate::utils;
+
+use super::polynomial::MultivariatePolynomial;
+use super::Exponent;
+
+/// A multivariate Horner scheme, which writes a polynomial
+/// as `x^n * a + b`, where `a` and `b` are again Horner schemes and `b` does not depend on `x`.
+#[derive(Clone, Debug)]
+pub enum HornerScheme<F: Ring> {
+    /// The expression `var^pow * mul + add`.
+    Node {
+        var: usize,
+        pow: u32,
+        mul: Box<HornerScheme<F>>,
+        add: Option<Box<HornerScheme<F>>>,
+    },
+    Leaf(F::Element),
+}
+
+/// The number of operations needed to evaluate a Horner scheme.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OperationCount {
+    pub additions: usize,
+    pub multiplications: usize,
+}
+
+impl OperationCount {
+    /// The total number of operations.
+    pub fn total(&self) -> usize {
+        self.additions + self.multiplications
+    }
+}
+
+impl<F: Ring> HornerScheme<F> {
+    /// Count the number of additions and multiplications needed to evaluate the scheme.
+    /// A power `x^n` is counted as `n-1` multiplications.
+    pub fn op_count(&self, field: F) -> OperationCount {
+        match self {
+            HornerScheme::Node { pow, mul, add, .. } => {
+                let mut count = mul.op_count(field);
+                count.multiplications += *pow as usize - 1;
+                if !matches!(&**mul, HornerScheme::Leaf(c) if field.is_one(c)) {
+                    count.multiplications += 1;
+                }
+
+                if let Some(add) = add {
+                    let c = add.op_count(field);
+                    count.additions += c.additions + 1;
+                    count.multiplications += c.multiplications;
+                }
+                count
+            }
+            HornerScheme::Leaf(_) => OperationCount::default(),
+        }
+    }
+
+    /// Evaluate the scheme, where the variable `i` has value `values[i]`.
+    pub fn evaluate(&self, field: F, values: &[F::Element]) -> F::Element {
+        match self {
+            HornerScheme::Node { var, pow, mul, add } => {
+                let mut r = field.pow(&values[*var], *pow as u64);
+                field.mul_assign(&mut r, &mul.evaluate(field, values));
+                if let Some(add) = add {
+                    field.add_assign(&mut r, &add.evaluate(field, values));
+                }
+                r
+            }
+            HornerScheme::Leaf(c) => c.clone(),
+        }
+    }
+}
+
+impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
+    /// Find the first variable in `order[start..]` that occurs in the terms and return its
+    /// position in `order` and its lowest non-zero power.
+    fn horner_split_var(
+        exponents: &[E],
+        nvars: usize,
+        terms: &[usize],
+        order: &[usize],
+        start: usize,
+    ) -> Option<(usize, E)> {
+        for (k, &v) in order.iter().enumerate().skip(start) {
+            let min_pow = terms
+                .iter()
+                .map(|t| exponents[t * nvars + v])
+                .filter(|e| !e.is_zero())
+                .min();
+
+            if let Some(d) = min_pow {
+                return Some((k, d));
+            }
+        }
+        None
+    }
+
+    /// Compute the number of operations of the Horner scheme of the selected terms
+    /// with the variable order `order[start..]`, without constructing it.
+    fn horner_cost(
+        &self,
+        exponents: &mut [E],
+        terms: &[usize],
+        order: &[usize],
+        start: usize,
+    ) -> usize {
+        let nvars = self.nvars;
+        let Some((k, d)) = Self::horner_split_var(exponents, nvars, terms, order, start) else {
+            return 0;
+        };
+        let v = order[k];
+
+        let (with, without): (Vec<usize>, Vec<usize>) = terms
+            .iter()
+            .partition(|t| !exponents[*t * nvars + v].is_zero());
+
+        for t in &with {
+            exponents[t * nvars + v] = exponents[t * nvars + v] - d;
+        }
+
+        let mut cost = d.to_u32() as usize - 1;
+        let is_one = with.len() == 1
+            && exponents[with[0] * nvars..(with[0] + 1) * nvars]
+                .iter()
+                .all(|e| e.is_zero())
+            && self.field.is_one(&self.coefficients[with[0]]);
+        if !is_one {
+            cost += 1 + self.horner_cost(exponents, &with, order, k);
+        }
+
+        for t in &with {
+            exponents[t * nvars + v] += d;
+        }
+
+        if !without.is_empty() {
+            cost += 1 + self.horner_cost(exponents, &without, order, k + 1);
+        }
+
+        cost
+    }
+
+    fn horner_build(
+        &self,
+        exponents: &mut [E],
+        terms: &[usize],
+        order: &[usize],
+        start: usize,
+    ) -> HornerScheme<F> {
+        let nvars = self.nvars;
+        let Some((k, d)) = Self::horner_split_var(exponents, nvars, terms, order, start) else {
+            // only the constant term is left
+            return HornerScheme::Leaf(self.coefficients[terms[0]].clone());
+        };
+        let v = order[k];
+
+        let (with, without): (Vec<usize>, Vec<usize>) = terms
+            .iter()
+            .partition(|t| !exponents[*t * nvars + v].is_zero());
+
+        for t in &with {
+            exponents[t * nvars + v] = exponents[t * nvars + v] - d;
+        }
+
+        let mul = self.horner_build(exponents, &with, order, k);
+
+        for t in &with {
+            exponents[t * nvars + v] += d;
+        }
+
+        let add = if without.is_empty() {
+            None
+        } else {
+            Some(Box::new(self.horner_build(
+                exponents,
+                &without,
+                order,
+                k + 1,
+            )))
+        };
+
+        HornerScheme::Node {
+            var: v,
+            pow: d.to_u32(),
+            mul: Box::new(mul),
+            add,
+        }
+    }
+
+    /// Construct a Horner scheme, where the variables are extracted in the order `order`.
+    pub fn to_horner_scheme(&self, order: &[usize]) -> HornerScheme<F> {
+        if self.is_zero() {
+            return HornerScheme::Leaf(self.field.zero());
+        }
+
+        let terms: Vec<_> = (0..self.nterms).collect();
+        let mut exponents = self.exponents.clone();
+        self.horner_build(&mut exponents, &terms, order, 0)
+    }
+
+    /// Count the number of operations of the Horner scheme with variable order `order`.
+    pub fn horner_op_count(&self, order: &[usize]) -> usize {
+        if self.is_zero() {
+            return 0;
+        }
+
+        let terms: Vec<_> = (0..self.nterms).collect();
+        let mut exponents = self.exponents.clone();
+        self.horner_cost(&mut exponents, &terms, order, 0)
+    }
+
+    /// Search for the variable order that minimizes the number of operations of the Horner scheme
+    /// using simulated annealing. The search starts from the order in which the variables
+    /// that occur in the most terms come first, and performs `iterations` random swaps of two variables,
+    /// starting at temperature `start_temperature` which is multiplied by `cooling_rate` after every step.
+    ///
+    /// Returns the best order and its Horner scheme.
+    pub fn optimize_horner_scheme(
+        &self,
+        iterations: usize,
+        start_temperature: f64,
+        cooling_rate: f64,
+    ) -> (Vec<usize>, HornerScheme<F>) {
+        let mut occurrences = vec![0; self.nvars];
+        for t in 0..self.nterms {
+            for (o, e) in occurrences.iter_mut().zip(self.exponents(t)) {
+                if !e.is_zero() {
+                    *o += 1;
+                }
+            }
+        }
+
+        let mut order: Vec<_> = (0..self.nvars).collect();
+        order.sort_by_key(|v| std::cmp::Reverse(occurrences[*v]));
+
+        let terms: Vec<_> = (0..self.nterms).collect();
+        let mut exponents = self.exponents.clone();
+
+        let mut cost = self.horner_op_count(&order);
+        let mut best_order = order.clone();
+        let mut best_cost = cost;
+
+        if self.nvars > 1 && !self.is_zero() {
+            let mut rng = utils::rng("horner::optimize_horner_scheme");
+            let mut temperature = start_temperature;
+
+            for _ in 0..iterations {
+                let i = rng.gen_range(0..self.nvars);
+                let j = rng.gen_range(0..self.nvars - 1);
+                let j = if j >= i { j + 1 } else { j };
+
+                order.swap(i, j);
+                let new_cost = self.horner_cost(&mut exponents, &terms, &order, 0);
+
+                let delta = new_cost as f64 - cost as f64;
+                if delta <= 0. || rng.gen::<f64>() < (-delta / temperature).exp() {
+                    cost = new_cost;
+                    if cost < best_cost {
+                        best_cost = cost;
+                        best_order.clone_from(&order);
+                    }
+                } else {
+                    order.swap(i, j);
+                }
+
+                temperature *= cooling_rate;
+            }
+        }
+
+        let scheme = self.to_horner_scheme(&best_order);
+        (best_order, scheme)
+    }
+}
@@ -0,0 +1,82 @@
+use crate::rings::Ring;
+
+use super::{
+    polynomial::{MonomialView, MultivariatePolynomial},
+    Exponent,
+};
+
+/// A pattern that matches the monomials of a polynomial by restricting
+/// the degrees of some of its variables. Variables without a restriction
+/// may have any degree.
+///
+/// For example, the monomials with a degree of at least 2 in `x` and degree 0 in `y`
+/// are matched by
+/// ```ignore
+/// MonomialPattern::new().with_min_degree(x, 2).with_degree(y, 0)
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MonomialPattern {
+    /// The variable index with the minimal and maximal degree.
+    bounds: Vec<(usize, u32, Option<u32>)>,
+}
+
+impl MonomialPattern {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require a degree of at least `degree` in variable `var`.
+    pub fn with_min_degree(mut self, var: usize, degree: u32) -> Self {
+        self.bounds.push((var, degree, None));
+        self
+    }
+
+    /// Require a degree of at most `degree` in variable `var`.
+    pub fn with_max_degree(mut self, var: usize, degree: u32) -> Self {
+        self.bounds.push((var, 0, Some(degree)));
+        self
+    }
+
+    /// Require a degree of exactly `degree` in variable `var`.
+    pub fn with_degree(mut self, var: usize, degree: u32) -> Self {
+        self.bounds.push((var, degree, Some(degree)));
+        self
+    }
+
+    /// Check if a monomial with exponents `exponents` matches the pattern.
+    pub fn matches<E: Exponent>(&self, exponents: &[E]) -> bool {
+        self.bounds.iter().all(|(var, min, max)| {
+            let e = exponents.get(*var).map(|e| e.to_u32()).unwrap_or(0);
+            e >= *min && max.map(|m| e <= m).unwrap_or(true)
+        })
+    }
+}
+
+impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
+    /// Get all monomials that match `pattern`, in the order of the polynomial.
+    pub fn find_monomials(&self, pattern: &MonomialPattern) -> Vec<MonomialView<'_, F, E>> {
+        self.into_iter()
+            .filter(|m| pattern.matches(m.exponents))
+            .collect()
+    }
+
+    /// Replace every monomial that matches `pattern` by the polynomial that is returned
+    /// by `replace` for it. Monomials that do not match are kept.
+    pub fn replace_monomials(
+        &self,
+        pattern: &MonomialPattern,
+        mut replace: impl FnMut(MonomialView<F, E>) -> Self,
+    ) -> Self {
+        let mut kept = self.new_from(None);
+        let mut replaced = self.new_from(None);
+        for m in self {
+            if pattern.matches(m.exponents) {
+                replaced = replaced + replace(m);
+            } else {
+                kept.append_monomial_back(m.coefficient.clone(), m.exponents);
+            }
+        }
+
+        kept + replaced
+    }
+}
@@ -0,0 +1,168 @@
+use crate::rings::Field;
+
+use super::{polynomial::MultivariatePolynomial, Exponent, INLINED_EXPONENTS};
+use smallvec::{smallvec, SmallVec};
+
+/// A basis of orthogonal polynomials.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrthogonalBasis {
+    /// Chebyshev polynomials of the first kind, `T_{n+1} = 2x T_n - T_{n-1}`.
+    Chebyshev,
+    /// Legendre polynomials, `(n+1) P_{n+1} = (2n+1) x P_n - n P_{n-1}`.
+    Legendre,
+}
+
+/// Convert `n` to an element of the field.
+fn field_element<F: Field>(field: F, mut n: u64) -> F::Element {
+    let mut r = field.zero();
+    let mut pow = field.one();
+    while n > 0 {
+        if n & 1 == 1 {
+            field.add_assign(&mut r, &pow);
+        }
+        pow = field.add(&pow, &pow);
+        n >>= 1;
+    }
+    r
+}
+
+impl OrthogonalBasis {
+    /// Get the coefficients `(a, b)` of the recurrence `P_{n+1} = a x P_n - b P_{n-1}`.
+    fn recurrence<F: Field>(&self, field: F, n: u64) -> (F::Element, F::Element) {
+        match self {
+            OrthogonalBasis::Chebyshev => {
+                if n == 0 {
+                    (field.one(), field.zero())
+                } else {
+                    (field_element(field, 2), field.one())
+                }
+            }
+            OrthogonalBasis::Legendre => {
+                let inv = field.inv(&field_element(field, n + 1));
+                (
+                    field.mul(&field_element(field, 2 * n + 1), &inv),
+                    field.mul(&field_element(field, n), &inv),
+                )
+            }
+        }
+    }
+
+    /// Get the dense coefficients in the monomial basis of the first `n`
+    /// basis polynomials.
+    pub fn polynomials<F: Field>(&self, field: F, n: usize) -> Vec<Vec<F::Element>> {
+        let mut polys: Vec<Vec<F::Element>> = Vec::with_capacity(n);
+        for k in 0..n {
+            if k == 0 {
+                polys.push(vec![field.one()]);
+                continue;
+            }
+
+            let (a, b) = self.recurrence(field, k as u64 - 1);
+            let mut p = vec![field.zero(); k + 1];
+            for (i, c) in polys[k - 1].iter().enumerate() {
+                p[i + 1] = field.mul(&a, c);
+            }
+            if k > 1 {
+                for (i, c) in polys[k - 2].iter().enumerate() {
+                    field.sub_mul_assign(&mut p[i], &b, c);
+                }
+            }
+            polys.push(p);
+        }
+        polys
+    }
+
+    /// Evaluate the series `sum_k coeffs[k] P_k(x)`.
+    pub fn evaluate<F: Field>(
+        &self,
+        field: F,
+        coeffs: &[F::Element],
+        x: &F::Element,
+    ) -> F::Element {
+        let mut res = field.zero();
+        let mut prev = field.zero();
+        let mut cur = field.one();
+        for (k, c) in coeffs.iter().enumerate() {
+            field.add_mul_assign(&mut res, c, &cur);
+
+            let (a, b) = self.recurrence(field, k as u64);
+            let mut next = field.mul(&field.mul(&a, x), &cur);
+            field.sub_mul_assign(&mut next, &b, &prev);
+            prev = cur;
+            cur = next;
+        }
+        res
+    }
+}
+
+impl<F: Field, E: Exponent> MultivariatePolynomial<F, E> {
+    /// Write a polynomial that only depends on the variable `var` as `sum_k c_k P_k(var)`,
+    /// where `P_k` are the polynomials of `basis`, and return the coefficients `c_k`.
+    pub fn to_orthogonal_basis(&self, var: usize, basis: OrthogonalBasis) -> Vec<F::Element> {
+        assert!(
+            (0..self.nterms).all(|t| self
+                .exponents(t)
+                .iter()
+                .enumerate()
+                .all(|(i, e)| i == var || e.is_zero())),
+            "Polynomial must be univariate in variable {}",
+            var
+        );
+
+        if self.is_zero() {
+            return vec![];
+        }
+
+        let degree = self.degree(var).to_u32() as usize;
+        let mut monomial = vec![self.field.zero(); degree + 1];
+        for t in 0..self.nterms {
+            monomial[self.exponents(t)[var].to_u32() as usize] = self.coefficients[t].clone();
+        }
+
+        // subtract the basis polynomials, starting from the highest degree
+        let polys = basis.polynomials(self.field, degree + 1);
+        let mut coeffs = vec![self.field.zero(); degree + 1];
+        for k in (0..=degree).rev() {
+            if F::is_zero(&monomial[k]) {
+                continue;
+            }
+
+            let c = self.field.div(&monomial[k], &polys[k][k]);
+            for (m, p) in monomial.iter_mut().zip(&polys[k]) {
+                self.field.sub_mul_assign(m, &c, p);
+            }
+            coeffs[k] = c;
+        }
+
+        coeffs
+    }
+
+    /// Construct the polynomial `sum_k coeffs[k] P_k(x)` in the variable `var`, where
+    /// `P_k` are the polynomials of `basis`.
+    pub fn from_orthogonal_basis(
+        coeffs: &[F::Element],
+        basis: OrthogonalBasis,
+        var: usize,
+        nvars: usize,
+        field: F,
+    ) -> Self {
+        let polys = basis.polynomials(field, coeffs.len());
+
+        let mut monomial = vec![field.zero(); coeffs.len()];
+        for (c, p) in coeffs.iter().zip(&polys) {
+            for (m, pp) in monomial.iter_mut().zip(p) {
+                field.add_mul_assign(m, c, pp);
+            }
+        }
+
+        let mut res = Self::new(nvars, field, Some(coeffs.len()), None);
+        let mut exp: SmallVec<[E; INLINED_EXPONENTS]> = smallvec![E::zero(); nvars];
+        for (k, c) in monomial.into_iter().enumerate() {
+            if !F::is_zero(&c) {
+                exp[var] = E::from_u32(k as u32);
+                res.append_monomial(c, &exp);
+            }
+        }
+        res
+    }
+}
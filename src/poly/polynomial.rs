@@ -6,12 +6,34 @@ use std::fmt::Display;
 use std::mem;
 use std::ops::{Add, Div, Mul, Neg, Sub};
 
+use rug::Integer as ArbitraryPrecisionInteger;
+
+use crate::cancel::{checkpoint, uncancellable, CancellationToken, Cancelled};
+use crate::error::Error;
+use crate::instrumentation::{count_operations, instrument_scope};
+use crate::progress;
 use crate::representations::Identifier;
+use crate::rings::integer::{Integer, IntegerRing};
+use crate::rings::linear_system::Matrix;
 use crate::rings::{EuclideanDomain, Field, Ring, RingPrinter};
+use crate::state::State;
+use crate::utils;
 
-use super::{Exponent, INLINED_EXPONENTS};
+use super::dispatch::{get_division_algorithm, DivisionAlgorithm};
+use super::{Exponent, PackedExponents, INLINED_EXPONENTS};
 use smallvec::{smallvec, SmallVec};
 
+/// The number of products of terms in a multiplication between two progress reports.
+const PROGRESS_INTERVAL: usize = 1 << 16;
+
+/// The length of a dense polynomial below which a Taylor shift uses the Horner scheme
+/// instead of divide and conquer.
+const SHIFT_HORNER_LENGTH: usize = 32;
+
+/// The length of a dense polynomial below which schoolbook multiplication is used
+/// instead of Karatsuba multiplication.
+const KARATSUBA_LENGTH: usize = 16;
+
 /// Multivariate polynomial with a sparse degree and variable dense representation.
 // TODO: implement EuclideanDomain for MultivariatePolynomial
 #[derive(Clone)]
@@ -270,6 +292,48 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
         *other = newother;
     }
 
+    /// Rewrite the polynomial in terms of the variables in `var_map`, without modifying `self`.
+    /// Variables of `self` that do not occur in the polynomial may be absent from `var_map`.
+    /// Returns `None` if a variable that occurs in the polynomial is not in `var_map`.
+    pub fn to_var_map(&self, var_map: &[Identifier]) -> Option<Self> {
+        if self.var_map.as_deref() == Some(var_map) {
+            return Some(self.clone());
+        }
+
+        let mut new_pos = vec![None; self.nvars];
+        for (var, pos) in new_pos.iter_mut().enumerate() {
+            *pos = self
+                .var_map
+                .as_ref()
+                .and_then(|vm| var_map.iter().position(|v| *v == vm[var]));
+
+            if pos.is_none() && !self.degree(var).is_zero() {
+                return None;
+            }
+        }
+
+        let mut res = Self::new(var_map.len(), self.field, Some(self.nterms), Some(var_map));
+        let mut newexp: SmallVec<[E; INLINED_EXPONENTS]> = smallvec![E::zero(); var_map.len()];
+        for t in self.into_iter() {
+            for c in &mut newexp {
+                *c = E::zero();
+            }
+
+            for (pos, e) in new_pos.iter().zip(t.exponents) {
+                if let Some(p) = pos {
+                    newexp[*p] = *e;
+                }
+            }
+            res.append_monomial(t.coefficient.clone(), &newexp);
+        }
+        Some(res)
+    }
+
+    /// Rewrite the polynomial in terms of the variable map of `other`. See [`Self::to_var_map`].
+    pub fn to_var_map_of(&self, other: &Self) -> Option<Self> {
+        self.to_var_map(other.var_map.as_deref().unwrap_or(&[]))
+    }
+
     /// Reverse the monomial ordering in-place.
     fn reverse(&mut self) {
         if self.nterms < 2 {
@@ -278,13 +342,13 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
 
         self.coefficients.reverse();
 
-        let midu = if self.nvars * self.nterms % 2 == 0 {
+        let midu = if self.nterms % 2 == 0 {
             self.nterms / 2
         } else {
             self.nterms / 2 + 1
         };
 
-        let (l, r) = self.exponents.split_at_mut(midu);
+        let (l, r) = self.exponents.split_at_mut(midu * self.nvars);
 
         let rend = r.len();
         for i in 0..self.nterms / 2 {
@@ -316,7 +380,7 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
         assert_eq!(self.exponents.len(), self.nterms * self.nvars);
 
         assert!(
-            self.coefficients.iter().all(F::is_zero),
+            !self.coefficients.iter().any(F::is_zero),
             "Inconsistent polynomial (0 coefficient): {}",
             self
         );
@@ -436,6 +500,59 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
     }
 }
 
+impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
+    /// Compute a 128-bit fingerprint of the polynomial that is stable across processes.
+    /// Variables are identified by their name in `state` if a variable map is present,
+    /// so that the fingerprint does not depend on the order of the variable map or on
+    /// the identifiers that a state assigns to the names.
+    pub fn fingerprint(&self, state: &State) -> u128 {
+        let mut total = 0u128;
+        for t in 0..self.nterms {
+            let mut vars: SmallVec<[(String, u32); INLINED_EXPONENTS]> = self
+                .exponents(t)
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| !e.is_zero())
+                .map(|(i, e)| {
+                    let name = match &self.var_map {
+                        Some(v) => state
+                            .get_name(v[i])
+                            .map(|n| n.to_string())
+                            .unwrap_or_else(|| format!("#{}", v[i].to_u32())),
+                        None => format!("#{}", i),
+                    };
+                    (name, e.to_u32())
+                })
+                .collect();
+            vars.sort_unstable();
+
+            let mut h = utils::StableHasher::new();
+            for (v, e) in vars {
+                h.write(&(v.len() as u32).to_le_bytes());
+                h.write(v.as_bytes());
+                h.write(&e.to_le_bytes());
+            }
+            h.write(b"|");
+            h.write(
+                RingPrinter {
+                    ring: &self.field,
+                    element: &self.coefficients[t],
+                }
+                .to_string()
+                .as_bytes(),
+            );
+
+            // the sum is independent of the order of the terms
+            total = total.wrapping_add(h.finish());
+        }
+
+        let mut h = utils::StableHasher::new();
+        h.write(self.field.to_string().as_bytes());
+        h.write(&total.to_le_bytes());
+        h.finish()
+    }
+}
+
 impl<F: Ring + fmt::Debug, E: Exponent + fmt::Debug> fmt::Debug for MultivariatePolynomial<F, E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.is_zero() {
@@ -496,7 +613,7 @@ impl<F: Ring + Display, E: Exponent> Display for MultivariatePolynomial<F, E> {
                     write!(f, "*")?;
                 }
                 write!(f, "x{}", i)?;
-                if e.to_u32() != 1 {
+                if e.to_fraction() != (1, 1) {
                     write!(f, "^{}", e)?;
                 }
             }
@@ -664,7 +781,7 @@ impl<'a, 'b, F: Ring, E: Exponent> Mul<&'a MultivariatePolynomial<F, E>>
 
     #[inline]
     fn mul(self, other: &'a MultivariatePolynomial<F, E>) -> Self::Output {
-        self.heap_mul(other)
+        self.mul_dispatch(other)
     }
 }
 
@@ -673,7 +790,7 @@ impl<'a, F: Ring, E: Exponent> Mul<&'a Self> for MultivariatePolynomial<F, E> {
 
     #[inline]
     fn mul(self, other: &'a Self) -> Self {
-        self.heap_mul(other)
+        self.mul_dispatch(other)
     }
 }
 
@@ -734,6 +851,7 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
         for c in &mut self.coefficients {
             self.field.mul_assign(c, coefficient);
         }
+        count_operations!(coefficient_muls, self.nterms);
         for i in 0..self.nterms {
             let ee = self.exponents_mut(i);
             for (e1, e2) in ee.iter_mut().zip(exponents) {
@@ -743,6 +861,182 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
         self
     }
 
+    /// Check that `other` has the same number of variables and a compatible variable map.
+    fn check_compatible(&self, other: &Self) -> Result<(), Error> {
+        if self.is_zero() || other.is_zero() {
+            return Ok(());
+        }
+
+        if self.nvars != other.nvars {
+            return Err(Error::NVarsMismatch {
+                expected: self.nvars,
+                found: other.nvars,
+            });
+        }
+
+        if self.var_map.is_some() && other.var_map.is_some() && self.var_map != other.var_map {
+            return Err(Error::VarMapMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Add two polynomials, returning an error instead of panicking if they are incompatible.
+    pub fn try_add(self, other: Self) -> Result<Self, Error> {
+        self.check_compatible(&other)?;
+        Ok(self + other)
+    }
+
+    /// Subtract two polynomials, returning an error instead of panicking if they are incompatible.
+    pub fn try_sub(self, other: Self) -> Result<Self, Error> {
+        self.check_compatible(&other)?;
+        Ok(self - other)
+    }
+
+    /// Multiply two polynomials, returning an error instead of panicking if they are
+    /// incompatible or if an exponent of the product does not fit in `E`.
+    pub fn try_mul(&self, other: &Self) -> Result<Self, Error> {
+        self.check_compatible(other)?;
+
+        if !self.is_zero()
+            && !other.is_zero()
+            && (0..self.nvars).any(|i| self.degree(i).checked_add(&other.degree(i)).is_none())
+        {
+            return Err(Error::ExponentOverflow);
+        }
+
+        Ok(self.heap_mul(other))
+    }
+
+    /// Multiply every term by the monomial `coefficient * x^exponents`, returning
+    /// an error if the number of variables is wrong or if an exponent overflows.
+    pub fn checked_mul_monomial(
+        mut self,
+        coefficient: &F::Element,
+        exponents: &[E],
+    ) -> Result<Self, Error> {
+        if self.nvars != exponents.len() {
+            return Err(Error::NVarsMismatch {
+                expected: self.nvars,
+                found: exponents.len(),
+            });
+        }
+
+        if F::is_zero(coefficient) {
+            return Ok(self.new_from(None));
+        }
+
+        for i in 0..self.nterms {
+            let ee = self.exponents_mut(i);
+            for (e1, e2) in ee.iter_mut().zip(exponents) {
+                *e1 = e1.checked_add(e2).ok_or(Error::ExponentOverflow)?;
+            }
+        }
+
+        for c in &mut self.coefficients {
+            self.field.mul_assign(c, coefficient);
+        }
+
+        // the coefficient may be a zero divisor
+        self.retain_terms(|c, _| !F::is_zero(c));
+        Ok(self)
+    }
+
+    /// Map every coefficient to the ring `new_field` using `f`. Terms whose new
+    /// coefficient is zero are removed.
+    pub fn map_coeff<G: Ring>(
+        &self,
+        f: impl Fn(&F::Element) -> G::Element,
+        new_field: G,
+    ) -> MultivariatePolynomial<G, E> {
+        let mut coefficients = Vec::with_capacity(self.nterms);
+        let mut exponents = Vec::with_capacity(self.exponents.len());
+
+        for m in self.into_iter() {
+            let nc = f(m.coefficient);
+            if !G::is_zero(&nc) {
+                coefficients.push(nc);
+                exponents.extend_from_slice(m.exponents);
+            }
+        }
+
+        MultivariatePolynomial {
+            nterms: coefficients.len(),
+            coefficients,
+            exponents,
+            nvars: self.nvars,
+            field: new_field,
+            var_map: self.var_map.clone(),
+        }
+    }
+
+    /// Keep only the terms for which `f(coefficient, exponents)` returns `true`.
+    /// The filtering is done in place, so no reallocation is needed.
+    pub fn retain_terms(&mut self, mut f: impl FnMut(&F::Element, &[E]) -> bool) {
+        let mut new_nterms = 0;
+        for i in 0..self.nterms {
+            if !f(&self.coefficients[i], self.exponents(i)) {
+                continue;
+            }
+
+            if new_nterms != i {
+                self.coefficients.swap(new_nterms, i);
+                self.exponents.copy_within(
+                    i * self.nvars..(i + 1) * self.nvars,
+                    new_nterms * self.nvars,
+                );
+            }
+            new_nterms += 1;
+        }
+
+        self.coefficients.truncate(new_nterms);
+        self.exponents.truncate(new_nterms * self.nvars);
+        self.nterms = new_nterms;
+    }
+
+    /// Transform every term in place using `f`, which may modify both the coefficient
+    /// and the exponents. Terms with a zero coefficient are removed afterwards, and if
+    /// the exponents were changed such that the terms are no longer sorted,
+    /// the polynomial is rebuilt, merging terms with equal exponents.
+    pub fn map_terms(&mut self, mut f: impl FnMut(&mut F::Element, &mut [E])) {
+        for (i, c) in self.coefficients.iter_mut().enumerate() {
+            f(c, &mut self.exponents[i * self.nvars..(i + 1) * self.nvars]);
+        }
+
+        let sorted = (1..self.nterms).all(|i| {
+            Self::cmp_exponents(self.exponents(i - 1), self.exponents(i)) == Ordering::Less
+        });
+
+        if sorted {
+            self.retain_terms(|c, _| !F::is_zero(c));
+            return;
+        }
+
+        let mut res = self.new_from(Some(self.nterms));
+        for (i, c) in mem::take(&mut self.coefficients).into_iter().enumerate() {
+            res.append_monomial(c, &self.exponents[i * self.nvars..(i + 1) * self.nvars]);
+        }
+        *self = res;
+    }
+
+    /// Raise the polynomial to the power `e`, using binary exponentiation.
+    pub fn pow(&self, mut e: u64) -> Self {
+        let mut base = self.clone();
+        let mut res = self.new_from_constant(self.field.one());
+
+        while e > 0 {
+            if e % 2 == 1 {
+                res = &res * &base;
+            }
+            e /= 2;
+            if e > 0 {
+                base = &base * &base;
+            }
+        }
+
+        res
+    }
+
     /// Get the degree of the variable `x`.
     /// This operation is O(n).
     pub fn degree(&self, x: usize) -> E {
@@ -983,6 +1277,105 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
         res
     }
 
+    /// Take the derivative of the polynomial in variable `x`.
+    pub fn derivative(&self, x: usize) -> Self {
+        let mut res = self.new_from(Some(self.nterms));
+        let mut e: SmallVec<[E; INLINED_EXPONENTS]> = smallvec![E::zero(); self.nvars];
+        for t in self {
+            let d = t.exponents[x].to_u32();
+            if d == 0 {
+                continue;
+            }
+
+            // compute d * coefficient with double-and-add, since rings have no map from integers
+            let mut c = self.field.zero();
+            let mut pow = t.coefficient.clone();
+            let mut n = d;
+            while n > 0 {
+                if n & 1 == 1 {
+                    self.field.add_assign(&mut c, &pow);
+                }
+                pow = self.field.add(&pow, &pow);
+                n >>= 1;
+            }
+
+            e.copy_from_slice(t.exponents);
+            e[x] = E::from_u32(d - 1);
+            res.append_monomial_back(c, &e);
+        }
+
+        res
+    }
+
+    /// Substitute `x -> x + a` for variable `x` (a Taylor shift).
+    ///
+    /// The polynomial is split into univariate polynomials in `x`. Low degrees are
+    /// shifted in place with the Horner scheme, and higher degrees with divide and
+    /// conquer, `f(x+a) = f_0(x+a) + (x+a)^m f_1(x+a)`, using Karatsuba multiplication,
+    /// which takes `O(d^1.6)` ring operations for degree `d` instead of `O(d^2)`.
+    pub fn shift(&self, x: usize, a: &F::Element) -> Self {
+        if F::is_zero(a) || self.is_constant() {
+            return self.clone();
+        }
+
+        // group the terms by the exponents of the other variables
+        let mut groups: HashMap<SmallVec<[E; INLINED_EXPONENTS]>, Vec<F::Element>> =
+            HashMap::new();
+        for t in self {
+            let mut rest: SmallVec<[E; INLINED_EXPONENTS]> = t.exponents.into();
+            rest[x] = E::zero();
+
+            let d = t.exponents[x].to_u32() as usize;
+            let coeffs = groups.entry(rest).or_default();
+            if coeffs.len() <= d {
+                coeffs.resize(d + 1, self.field.zero());
+            }
+            coeffs[d] = t.coefficient.clone();
+        }
+
+        // (x+a)^(2^k) for the splits of the divide and conquer
+        let max_len = groups.values().map(|c| c.len()).max().unwrap();
+        let max_k = if max_len > SHIFT_HORNER_LENGTH {
+            (max_len - 1).ilog2() as usize
+        } else {
+            0
+        };
+        let mut powers = vec![vec![a.clone(), self.field.one()]];
+        while powers.len() <= max_k {
+            let last = powers.last().unwrap();
+            powers.push(dense_karatsuba_mul(&self.field, last, last));
+        }
+
+        let mut terms = Vec::with_capacity(self.nterms);
+        for (mut exp, c) in groups {
+            let c = dense_taylor_shift(&self.field, c, a, &powers);
+
+            for (d, c) in c.into_iter().enumerate() {
+                if !F::is_zero(&c) {
+                    exp[x] = E::from_u32(d as u32);
+                    terms.push((exp.clone(), c));
+                }
+            }
+        }
+
+        terms.sort_unstable_by(|(e1, _), (e2, _)| Self::cmp_exponents(e1, e2));
+
+        let mut res = self.new_from(Some(terms.len()));
+        for (e, c) in terms {
+            res.append_monomial_back(c, &e);
+        }
+        res
+    }
+
+    /// Substitute `x -> x + a` for all pairs `(x, a)` in `shifts`.
+    pub fn shift_all(&self, shifts: &[(usize, F::Element)]) -> Self {
+        let mut res = self.clone();
+        for (x, a) in shifts {
+            res = res.shift(*x, a);
+        }
+        res
+    }
+
     /// Replace all variables except `v` in the polynomial by elements from
     /// the ring.
     pub fn replace_all_except(
@@ -1121,28 +1514,44 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
     /// monomials that have that exponent can be summed. Then, new monomials combinations are added that
     /// should be considered next as they are smaller than the current monomial.
     pub fn heap_mul(&self, other: &Self) -> Self {
+        uncancellable(self.heap_mul_impl(other, None))
+    }
+
+    /// Multiply with [`MultivariatePolynomial::heap_mul`], checking `token` for cancellation
+    /// while the product is being accumulated.
+    pub fn try_heap_mul(&self, other: &Self, token: &CancellationToken) -> Result<Self, Cancelled> {
+        self.heap_mul_impl(other, Some(token))
+    }
+
+    fn heap_mul_impl(
+        &self,
+        other: &Self,
+        token: Option<&CancellationToken>,
+    ) -> Result<Self, Cancelled> {
         if self.nterms == 0 || other.nterms == 0 {
-            return Self::new_from(self, None);
+            return Ok(Self::new_from(self, None));
         }
 
         if self.nterms == 1 {
-            return other
+            return Ok(other
                 .clone()
-                .mul_monomial(&self.coefficients[0], &self.exponents);
+                .mul_monomial(&self.coefficients[0], &self.exponents));
         }
 
         if other.nterms == 1 {
-            return self
+            return Ok(self
                 .clone()
-                .mul_monomial(&other.coefficients[0], &other.exponents);
+                .mul_monomial(&other.coefficients[0], &other.exponents));
         }
 
         // place the smallest polynomial first, as this is faster
         // in the heap algorithm
         if self.nterms > other.nterms {
-            return other.heap_mul(self);
+            return other.heap_mul_impl(self, token);
         }
 
+        instrument_scope!("mul", self.nterms + other.nterms);
+
         // use a special routine if the exponents can be packed into a u64
         let mut pack_u8 = true;
         if self.nvars <= 8
@@ -1158,7 +1567,18 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
                 deg <= 255 || self.nvars <= 4 && deg <= 65535
             })
         {
-            return self.heap_mul_packed_exp(other, pack_u8);
+            return self.heap_mul_packed_exp_impl(other, pack_u8, token);
+        }
+
+        if self.nvars <= 16
+            && (0..self.nvars).all(|i| {
+                self.degree(i)
+                    .to_u32()
+                    .saturating_add(other.degree(i).to_u32())
+                    <= 255
+            })
+        {
+            return self.heap_mul_packed_exp_u128_impl(other, token);
         }
 
         let mut res = self.new_from(Some(self.nterms));
@@ -1176,6 +1596,7 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
             .map(|(e1, e2)| *e1 + *e2)
             .collect();
         cache.insert(monom.clone(), vec![(0, 0)]);
+        count_operations!(heap_pushes, 1);
         h.push(Reverse(monom));
 
         let mut m_cache: Vec<E> = vec![E::zero(); self.nvars];
@@ -1186,13 +1607,23 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
         let mut in_heap = vec![false; other.nterms];
         in_heap[0] = true;
 
+        let total = self.nterms * other.nterms;
+        let mut products = 0;
+        let mut monomials = 0;
         while !h.is_empty() {
             let cur_mon = h.pop().unwrap();
+            monomials += 1;
 
             let mut coefficient = self.field.zero();
 
             let mut q = cache.remove(&cur_mon.0).unwrap();
 
+            if products / PROGRESS_INTERVAL != (products + q.len()) / PROGRESS_INTERVAL {
+                progress::report("heap_mul", products, Some(total));
+                checkpoint(token, "heap_mul")?;
+            }
+            products += q.len();
+
             for (i, j) in q.drain(..) {
                 self.field.add_mul_assign(
                     &mut coefficient,
@@ -1214,6 +1645,7 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
                     if let Some(e) = cache.get_mut(&m_cache) {
                         e.push((i + 1, j));
                     } else {
+                        count_operations!(heap_pushes, 1);
                         h.push(Reverse(m_cache.clone())); // only add when new
                         if let Some(mut qq) = q_cache.pop() {
                             qq.push((i + 1, j));
@@ -1238,6 +1670,7 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
                     if let Some(e) = cache.get_mut(&m_cache) {
                         e.push((i, j + 1));
                     } else {
+                        count_operations!(heap_pushes, 1);
                         h.push(Reverse(m_cache.clone())); // only add when new
 
                         if let Some(mut qq) = q_cache.pop() {
@@ -1260,6 +1693,80 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
                 res.nterms += 1;
             }
         }
+
+        // the first product of every monomial is not added to another
+        count_operations!(coefficient_muls, products);
+        count_operations!(coefficient_adds, products - monomials);
+        Ok(res)
+    }
+
+    /// Multiply two polynomials, but do not generate terms where the exponent of
+    /// variable `i` is larger than `max_degrees[i]`.
+    pub fn mul_truncated(&self, other: &Self, max_degrees: &[E]) -> Self {
+        assert_eq!(max_degrees.len(), self.nvars, "nvars mismatched");
+        self.mul_filtered(other, |e| {
+            e.iter().zip(max_degrees).all(|(e, m)| *e <= m.to_u32())
+        })
+    }
+
+    /// Multiply two polynomials, but do not generate terms with a total degree
+    /// that is larger than `max_degree`.
+    pub fn mul_truncated_total_degree(&self, other: &Self, max_degree: u32) -> Self {
+        self.mul_filtered(other, |e| {
+            e.iter().map(|x| *x as u64).sum::<u64>() <= max_degree as u64
+        })
+    }
+
+    /// Multiply two polynomials, keeping only the terms whose exponents satisfy `keep`.
+    /// The filter must be monotonic: if a monomial is rejected, all its multiples
+    /// must be rejected as well.
+    ///
+    /// The filter is applied to the exponents as `u32` before they are converted to `E`,
+    /// so that the exponents of the rejected terms may exceed the range of `E`.
+    fn mul_filtered(&self, other: &Self, keep: impl Fn(&[u32]) -> bool) -> Self {
+        let mut exp = vec![0; self.nvars];
+        let mut keep_exponents = |e: &[E]| {
+            for (x, e) in exp.iter_mut().zip(e) {
+                *x = e.to_u32();
+            }
+            keep(&exp)
+        };
+
+        let mut a = self.clone();
+        a.retain_terms(|_, e| keep_exponents(e));
+        let mut b = other.clone();
+        b.retain_terms(|_, e| keep_exponents(e));
+
+        let mut terms = Vec::with_capacity(a.nterms.max(b.nterms));
+        for ta in &a {
+            'next: for tb in &b {
+                for ((e, e1), e2) in exp.iter_mut().zip(ta.exponents).zip(tb.exponents) {
+                    // an exponent beyond u32 is rejected by every filter
+                    match e1.to_u32().checked_add(e2.to_u32()) {
+                        Some(x) => *e = x,
+                        None => continue 'next,
+                    }
+                }
+
+                if keep(&exp) {
+                    let e: Vec<E> = exp.iter().map(|x| E::from_u32(*x)).collect();
+                    terms.push((e, self.field.mul(ta.coefficient, tb.coefficient)));
+                }
+            }
+        }
+
+        terms.sort_unstable_by(|(e1, _), (e2, _)| Self::cmp_exponents(e1, e2));
+
+        count_operations!(coefficient_muls, terms.len());
+        count_operations!(
+            coefficient_adds,
+            terms.windows(2).filter(|w| w[0].0 == w[1].0).count()
+        );
+
+        let mut res = self.new_from(Some(terms.len()));
+        for (e, c) in terms {
+            res.append_monomial_back(c, &e);
+        }
         res
     }
 
@@ -1267,31 +1774,67 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
     /// Each exponent is limited to 65535 if there are four or fewer variables,
     /// or 255 if there are 8 or fewer variables.
     pub fn heap_mul_packed_exp(&self, other: &Self, pack_u8: bool) -> Self {
-        let mut res = self.new_from(Some(self.nterms));
+        uncancellable(self.heap_mul_packed_exp_impl(other, pack_u8, None))
+    }
 
-        let pack_a: Vec<_> = if pack_u8 {
-            self.exponents.chunks(self.nvars).map(E::pack).collect()
-        } else {
-            self.exponents.chunks(self.nvars).map(E::pack_u16).collect()
-        };
-        let pack_b: Vec<_> = if pack_u8 {
-            other.exponents.chunks(self.nvars).map(E::pack).collect()
-        } else {
-            other
-                .exponents
-                .chunks(self.nvars)
-                .map(E::pack_u16)
-                .collect()
-        };
+    fn heap_mul_packed_exp_impl(
+        &self,
+        other: &Self,
+        pack_u8: bool,
+        token: Option<&CancellationToken>,
+    ) -> Result<Self, Cancelled> {
+        let pack: fn(&[E]) -> u64 = if pack_u8 { E::pack } else { E::pack_u16 };
+        let unpack: fn(u64, &mut [E]) = if pack_u8 { E::unpack } else { E::unpack_u16 };
+
+        let pack_a: Vec<_> = self.exponents.chunks(self.nvars).map(pack).collect();
+        let pack_b: Vec<_> = other.exponents.chunks(self.nvars).map(pack).collect();
+        self.heap_mul_packed(other, &pack_a, &pack_b, unpack, token)
+    }
+
+    /// Heap multiplication, but with the exponents packed into a `u128`.
+    /// Each exponent is limited to 255 and there can be at most 16 variables.
+    pub fn heap_mul_packed_exp_u128(&self, other: &Self) -> Self {
+        uncancellable(self.heap_mul_packed_exp_u128_impl(other, None))
+    }
+
+    fn heap_mul_packed_exp_u128_impl(
+        &self,
+        other: &Self,
+        token: Option<&CancellationToken>,
+    ) -> Result<Self, Cancelled> {
+        let pack_a: Vec<_> = self
+            .exponents
+            .chunks(self.nvars)
+            .map(E::pack_u128)
+            .collect();
+        let pack_b: Vec<_> = other
+            .exponents
+            .chunks(self.nvars)
+            .map(E::pack_u128)
+            .collect();
+        self.heap_mul_packed(other, &pack_a, &pack_b, E::unpack_u128, token)
+    }
+
+    /// Heap multiplication with the packed exponents `pack_a` of `self` and `pack_b` of `other`.
+    fn heap_mul_packed<P: PackedExponents>(
+        &self,
+        other: &Self,
+        pack_a: &[P],
+        pack_b: &[P],
+        unpack: fn(P, &mut [E]),
+        token: Option<&CancellationToken>,
+    ) -> Result<Self, Cancelled> {
+        let mut res = self.new_from(Some(self.nterms));
 
-        let mut cache: BTreeMap<u64, Vec<(usize, usize)>> = BTreeMap::new();
+        let mut cache: BTreeMap<P, Vec<(usize, usize)>> = BTreeMap::new();
         let mut q_cache: Vec<Vec<(usize, usize)>> = vec![];
 
         // create a min-heap since our polynomials are sorted smallest to largest
-        let mut h: BinaryHeap<Reverse<u64>> = BinaryHeap::with_capacity(self.nterms);
+        let mut h: BinaryHeap<Reverse<P>> = BinaryHeap::with_capacity(self.nterms);
 
-        let monom: u64 = pack_a[0] + pack_b[0];
+        let monom: P = pack_a[0] + pack_b[0];
         cache.insert(monom, vec![(0, 0)]);
+        count_operations!(heap_pushes, 1);
         h.push(Reverse(monom));
 
         // i=merged_index[j] signifies that self[i]*other[j] has been merged
@@ -1300,13 +1843,23 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
         let mut in_heap = vec![false; other.nterms];
         in_heap[0] = true;
 
+        let total = self.nterms * other.nterms;
+        let mut products = 0;
+        let mut monomials = 0;
         while !h.is_empty() {
             let cur_mon = h.pop().unwrap();
+            monomials += 1;
 
             let mut coefficient = self.field.zero();
 
             let mut q = cache.remove(&cur_mon.0).unwrap();
 
+            if products / PROGRESS_INTERVAL != (products + q.len()) / PROGRESS_INTERVAL {
+                progress::report("heap_mul", products, Some(total));
+                checkpoint(token, "heap_mul")?;
+            }
+            products += q.len();
+
             for (i, j) in q.drain(..) {
                 self.field.add_mul_assign(
                     &mut coefficient,
@@ -1321,6 +1874,7 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
                     if let Some(e) = cache.get_mut(&m) {
                         e.push((i + 1, j));
                     } else {
+                        count_operations!(heap_pushes, 1);
                         h.push(Reverse(m)); // only add when new
                         if let Some(mut qq) = q_cache.pop() {
                             qq.push((i + 1, j));
@@ -1338,6 +1892,7 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
                     if let Some(e) = cache.get_mut(&m) {
                         e.push((i, j + 1));
                     } else {
+                        count_operations!(heap_pushes, 1);
                         h.push(Reverse(m)); // only add when new
 
                         if let Some(mut qq) = q_cache.pop() {
@@ -1360,15 +1915,15 @@ impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
 
                 res.exponents.resize(len + self.nvars, E::zero());
 
-                if pack_u8 {
-                    E::unpack(cur_mon.0, &mut res.exponents[len..len + self.nvars]);
-                } else {
-                    E::unpack_u16(cur_mon.0, &mut res.exponents[len..len + self.nvars]);
-                }
+                unpack(cur_mon.0, &mut res.exponents[len..len + self.nvars]);
                 res.nterms += 1;
             }
         }
-        res
+
+        // the first product of every monomial is not added to another
+        count_operations!(coefficient_muls, products);
+        count_operations!(coefficient_adds, products - monomials);
+        Ok(res)
     }
 }
 
@@ -1502,6 +2057,26 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
         (q, r)
     }
 
+    /// Divide `self` by `div`, returning an error if `div` is zero or
+    /// if the division is not exact.
+    pub fn try_div(&self, div: &Self) -> Result<Self, Error> {
+        if div.is_zero() {
+            return Err(Error::DivisionByZero);
+        }
+        self.check_compatible(div)?;
+        self.divides(div).ok_or(Error::InexactDivision)
+    }
+
+    /// Divide two multivariate polynomials and return the quotient and remainder,
+    /// or an error if `div` is zero or the polynomials are incompatible.
+    pub fn try_quot_rem(&self, div: &Self) -> Result<(Self, Self), Error> {
+        if div.is_zero() {
+            return Err(Error::DivisionByZero);
+        }
+        self.check_compatible(div)?;
+        Ok(self.quot_rem(div, false))
+    }
+
     pub fn divides(&self, div: &Self) -> Option<Self> {
         if self.is_zero() {
             return Some(self.clone());
@@ -1513,6 +2088,10 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
             return None;
         }
 
+        if !self.divides_heuristic(div) {
+            return None;
+        }
+
         let (a, b) = self.quot_rem(div, true);
         if b.nterms == 0 {
             Some(a)
@@ -1521,6 +2100,74 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
         }
     }
 
+    /// Cheap necessary conditions for `div` to divide `self`. If this function
+    /// returns `false`, `div` is certainly not a divisor.
+    fn divides_heuristic(&self, div: &Self) -> bool {
+        // the leading and trailing terms of the dividend are the products
+        // of the leading and trailing terms of the quotient and the divisor
+        for (a, b) in [(self.nterms - 1, div.nterms - 1), (0, 0)] {
+            if self
+                .exponents(a)
+                .iter()
+                .zip(div.exponents(b))
+                .any(|(e1, e2)| e1 < e2)
+            {
+                return false;
+            }
+
+            let (_, r) = self
+                .field
+                .quot_rem(&self.coefficients[a], &div.coefficients[b]);
+            if !F::is_zero(&r) {
+                return false;
+            }
+        }
+
+        // the total degree and the lowest total degree are additive
+        let total_degrees = |p: &Self| {
+            let mut min = u32::MAX;
+            let mut max = 0;
+            for t in p {
+                let d = t.exponents.iter().map(|e| e.to_u32()).sum::<u32>();
+                min = min.min(d);
+                max = max.max(d);
+            }
+            (min, max)
+        };
+
+        let (self_min, self_max) = total_degrees(self);
+        let (div_min, div_max) = total_degrees(div);
+        if self_max < div_max || self_min < div_min || self_max - self_min < div_max - div_min {
+            return false;
+        }
+
+        // evaluate all variables except one at random points and check
+        // that the resulting univariate polynomials divide
+        if self.nvars > 1 && div.nterms > 1 {
+            let var = (0..self.nvars).max_by_key(|v| div.degree(*v)).unwrap();
+
+            let mut rng = utils::rng("polynomial::divides");
+            let r: Vec<_> = (0..self.nvars)
+                .filter(|v| *v != var)
+                .map(|v| (v, self.field.sample(&mut rng, (1, 1000))))
+                .collect();
+
+            let mut cache = vec![vec![]; self.nvars];
+            let div_eval = div.replace_all_except(var, &r, &mut cache);
+            if div_eval.is_zero() {
+                return true;
+            }
+
+            let self_eval = self.replace_all_except(var, &r, &mut cache);
+            let (_, rem) = self_eval.quot_rem(&div_eval, true);
+            if !rem.is_zero() {
+                return false;
+            }
+        }
+
+        true
+    }
+
     /// Divide two multivariate polynomials and return the quotient and remainder.
     pub fn quot_rem(&self, div: &Self, abort_on_remainder: bool) -> (Self, Self) {
         assert!(!div.is_zero(), "Cannot divide by 0 polynomial");
@@ -1585,7 +2232,8 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
         }
 
         let mut pack_u8 = true;
-        if self.nvars <= 8
+        if get_division_algorithm() == DivisionAlgorithm::Auto
+            && self.nvars <= 8
             && (0..self.nvars).all(|i| {
                 let deg = self.degree(i).to_u32();
                 if deg > 127 {
@@ -1596,6 +2244,11 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
             })
         {
             self.heap_division_packed_exp(div, abort_on_remainder, pack_u8)
+        } else if get_division_algorithm() == DivisionAlgorithm::Auto
+            && self.nvars <= 16
+            && (0..self.nvars).all(|i| self.degree(i).to_u32() <= 127)
+        {
+            self.heap_division_packed_exp_u128(div, abort_on_remainder)
         } else {
             self.heap_division(div, abort_on_remainder)
         }
@@ -1605,6 +2258,8 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
     /// monomial exponents appear in the heap.
     /// Reference: "Sparse polynomial division using a heap" by Monagan, Pearce (2011)
     pub fn heap_division(&self, div: &Self, abort_on_remainder: bool) -> (Self, Self) {
+        instrument_scope!("division", self.nterms + div.nterms);
+
         let mut q = self.new_from(Some(self.nterms));
         let mut r = self.new_from(None);
 
@@ -1647,6 +2302,8 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                             &q.coefficients[i],
                             div.coefficient_back(j),
                         );
+                        count_operations!(coefficient_muls, 1);
+                        count_operations!(coefficient_adds, 1);
 
                         if next_in_divisor && j + 1 < div.nterms {
                             // quotient heap product
@@ -1662,6 +2319,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                             if let Some(e) = cache.get_mut(&m_cache) {
                                 e.push((i, j + 1, true));
                             } else {
+                                count_operations!(heap_pushes, 1);
                                 h.push(m_cache.clone()); // only add when new
                                 if let Some(mut qq) = q_cache.pop() {
                                     qq.push((i, j + 1, true));
@@ -1688,6 +2346,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                                 if let Some(e) = cache.get_mut(&m_cache) {
                                     e.push((i + 1, j, false));
                                 } else {
+                                    count_operations!(heap_pushes, 1);
                                     h.push(m_cache.clone()); // only add when new
                                     if let Some(mut qq) = q_cache.pop() {
                                         qq.push((i + 1, j, false));
@@ -1712,6 +2371,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                                 if let Some(e) = cache.get_mut(&m_cache) {
                                     e.push((i, j + 1, false));
                                 } else {
+                                    count_operations!(heap_pushes, 1);
                                     h.push(m_cache.clone()); // only add when new
 
                                     if let Some(mut qq) = q_cache.pop() {
@@ -1731,6 +2391,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
 
             if !F::is_zero(&c) && div.last_exponents().iter().zip(&m).all(|(ge, me)| me >= ge) {
                 let (quot, rem) = self.field.quot_rem(&c, &div.lcoeff());
+                count_operations!(coefficient_divisions, 1);
                 if !F::is_zero(&rem) {
                     // TODO: support upgrade to a RationalField
                     if abort_on_remainder {
@@ -1768,6 +2429,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                     if let Some(e) = cache.get_mut(&m_cache) {
                         e.push((q.nterms - 1, 1, true));
                     } else {
+                        count_operations!(heap_pushes, 1);
                         h.push(m_cache.clone()); // only add when new
                         if let Some(mut qq) = q_cache.pop() {
                             qq.push((q.nterms - 1, 1, true));
@@ -1784,6 +2446,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                         if let Some(e) = cache.get_mut(&m_cache) {
                             e.push((q.nterms - 1, 1, false));
                         } else {
+                            count_operations!(heap_pushes, 1);
                             h.push(m_cache.clone()); // only add when new
                             if let Some(mut qq) = q_cache.pop() {
                                 qq.push((q.nterms - 1, 1, false));
@@ -1804,6 +2467,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                     if let Some(e) = cache.get_mut(&m_cache) {
                         e.push((q.nterms - 1, 1, false));
                     } else {
+                        count_operations!(heap_pushes, 1);
                         h.push(m_cache.clone()); // only add when new
                         if let Some(mut qq) = q_cache.pop() {
                             qq.push((q.nterms - 1, 1, false));
@@ -1850,38 +2514,79 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
         abort_on_remainder: bool,
         pack_u8: bool,
     ) -> (Self, Self) {
-        let mut q = self.new_from(Some(self.nterms));
-        let mut r = self.new_from(None);
-
-        let pack_a: Vec<_> = if pack_u8 {
-            self.exponents.chunks(self.nvars).map(E::pack).collect()
-        } else {
-            self.exponents.chunks(self.nvars).map(E::pack_u16).collect()
-        };
-        let pack_div: Vec<_> = if pack_u8 {
-            div.exponents.chunks(div.nvars).map(E::pack).collect()
+        let pack: fn(&[E]) -> u64 = if pack_u8 { E::pack } else { E::pack_u16 };
+        let unpack: fn(u64, &mut [E]) = if pack_u8 { E::unpack } else { E::unpack_u16 };
+        let overflow_mask = if pack_u8 {
+            9259542123273814144u64
         } else {
-            div.exponents.chunks(div.nvars).map(E::pack_u16).collect()
+            9223512776490647552u64
         };
 
+        let pack_a: Vec<_> = self.exponents.chunks(self.nvars).map(pack).collect();
+        let pack_div: Vec<_> = div.exponents.chunks(div.nvars).map(pack).collect();
+        self.heap_division_packed(
+            div,
+            abort_on_remainder,
+            &pack_a,
+            &pack_div,
+            overflow_mask,
+            unpack,
+        )
+    }
+
+    /// Heap division, but with the exponents packed into a `u128`.
+    /// Each exponent is limited to 127 and there can be at most 16 variables.
+    pub fn heap_division_packed_exp_u128(
+        &self,
+        div: &Self,
+        abort_on_remainder: bool,
+    ) -> (Self, Self) {
+        let pack_a: Vec<_> = self
+            .exponents
+            .chunks(self.nvars)
+            .map(E::pack_u128)
+            .collect();
+        let pack_div: Vec<_> = div.exponents.chunks(div.nvars).map(E::pack_u128).collect();
+        self.heap_division_packed(
+            div,
+            abort_on_remainder,
+            &pack_a,
+            &pack_div,
+            u128::from_ne_bytes([128; 16]),
+            E::unpack_u128,
+        )
+    }
+
+    /// Heap division with the packed exponents `pack_a` of `self` and `pack_div` of `div`.
+    /// The bits in `overflow_mask` signal that a subtraction of packed exponents has
+    /// overflowed, i.e., that a monomial does not divide another.
+    fn heap_division_packed<P: PackedExponents>(
+        &self,
+        div: &Self,
+        abort_on_remainder: bool,
+        pack_a: &[P],
+        pack_div: &[P],
+        overflow_mask: P,
+        unpack: fn(P, &mut [E]),
+    ) -> (Self, Self) {
+        let mut q = self.new_from(Some(self.nterms));
+        let mut r = self.new_from(None);
+
         let mut div_monomial_in_heap = vec![false; div.nterms];
         let mut merged_index_of_div_monomial_in_quotient = vec![0; div.nterms];
 
-        let mut cache: BTreeMap<u64, Vec<(usize, usize, bool)>> = BTreeMap::new();
+        let mut cache: BTreeMap<P, Vec<(usize, usize, bool)>> = BTreeMap::new();
 
-        #[inline(always)]
-        fn divides(a: u64, b: u64, pack_u8: bool) -> Option<u64> {
-            let d = a.overflowing_sub(b).0;
-            if pack_u8 && (d & 9259542123273814144u64 == 0)
-                || !pack_u8 && (d & 9223512776490647552u64 == 0)
-            {
+        let divides = |a: P, b: P| {
+            let d = a.wrapping_sub(b);
+            if d & overflow_mask == P::zero() {
                 Some(d)
             } else {
                 None
             }
-        }
+        };
 
-        let mut h: BinaryHeap<u64> = BinaryHeap::with_capacity(self.nterms);
+        let mut h: BinaryHeap<P> = BinaryHeap::with_capacity(self.nterms);
         let mut q_cache: Vec<Vec<(usize, usize, bool)>> = Vec::with_capacity(self.nterms);
 
         let mut m;
@@ -1916,6 +2621,8 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                             &q.coefficients[i],
                             div.coefficient_back(j),
                         );
+                        count_operations!(coefficient_muls, 1);
+                        count_operations!(coefficient_adds, 1);
 
                         if next_in_divisor && j + 1 < div.nterms {
                             // quotient heap product
@@ -1925,6 +2632,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                             if let Some(e) = cache.get_mut(&m_cache) {
                                 e.push((i, j + 1, true));
                             } else {
+                                count_operations!(heap_pushes, 1);
                                 h.push(m_cache); // only add when new
                                 if let Some(mut qq) = q_cache.pop() {
                                     qq.push((i, j + 1, true));
@@ -1945,6 +2653,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                                 if let Some(e) = cache.get_mut(&m_cache) {
                                     e.push((i + 1, j, false));
                                 } else {
+                                    count_operations!(heap_pushes, 1);
                                     h.push(m_cache); // only add when new
                                     if let Some(mut qq) = q_cache.pop() {
                                         qq.push((i + 1, j, false));
@@ -1963,6 +2672,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                                 if let Some(e) = cache.get_mut(&m_cache) {
                                     e.push((i, j + 1, false));
                                 } else {
+                                    count_operations!(heap_pushes, 1);
                                     h.push(m_cache); // only add when new
 
                                     if let Some(mut qq) = q_cache.pop() {
@@ -1980,9 +2690,10 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                 }
             }
 
-            let q_e = divides(m, pack_div[pack_div.len() - 1], pack_u8);
+            let q_e = divides(m, pack_div[pack_div.len() - 1]);
             if !F::is_zero(&c) && q_e.is_some() {
                 let (quot, rem) = self.field.quot_rem(&c, &div.lcoeff());
+                count_operations!(coefficient_divisions, 1);
                 if !F::is_zero(&rem) {
                     // TODO: support upgrade to a RationalField
                     if abort_on_remainder {
@@ -1998,11 +2709,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                 let len = q.exponents.len();
                 q.exponents.resize(len + self.nvars, E::zero());
 
-                if pack_u8 {
-                    E::unpack(q_e, &mut q.exponents[len..len + self.nvars]);
-                } else {
-                    E::unpack_u16(q_e, &mut q.exponents[len..len + self.nvars]);
-                }
+                unpack(q_e, &mut q.exponents[len..len + self.nvars]);
                 q.nterms += 1;
                 q_exp.push(q_e);
 
@@ -2010,7 +2717,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                     continue;
                 }
 
-                m_cache = q_exp.last().unwrap() + pack_div[pack_div.len() - 2];
+                m_cache = *q_exp.last().unwrap() + pack_div[pack_div.len() - 2];
 
                 if q.nterms < div.nterms {
                     // using quotient heap
@@ -2018,6 +2725,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                     if let Some(e) = cache.get_mut(&m_cache) {
                         e.push((q.nterms - 1, 1, true));
                     } else {
+                        count_operations!(heap_pushes, 1);
                         h.push(m_cache); // only add when new
                         if let Some(mut qq) = q_cache.pop() {
                             qq.push((q.nterms - 1, 1, true));
@@ -2034,6 +2742,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                         if let Some(e) = cache.get_mut(&m_cache) {
                             e.push((q.nterms - 1, 1, false));
                         } else {
+                            count_operations!(heap_pushes, 1);
                             h.push(m_cache); // only add when new
                             if let Some(mut qq) = q_cache.pop() {
                                 qq.push((q.nterms - 1, 1, false));
@@ -2054,6 +2763,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                     if let Some(e) = cache.get_mut(&m_cache) {
                         e.push((q.nterms - 1, 1, false));
                     } else {
+                        count_operations!(heap_pushes, 1);
                         h.push(m_cache); // only add when new
                         if let Some(mut qq) = q_cache.pop() {
                             qq.push((q.nterms - 1, 1, false));
@@ -2074,11 +2784,7 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
                     let len = r.exponents.len();
                     r.exponents.resize(len + self.nvars, E::zero());
 
-                    if pack_u8 {
-                        E::unpack(m, &mut r.exponents[len..len + self.nvars]);
-                    } else {
-                        E::unpack_u16(m, &mut r.exponents[len..len + self.nvars]);
-                    }
+                    unpack(m, &mut r.exponents[len..len + self.nvars]);
                 }
             }
         }
@@ -2096,6 +2802,111 @@ impl<F: EuclideanDomain, E: Exponent> MultivariatePolynomial<F, E> {
 
         (q, r)
     }
+
+    /// Compute the resultant of `self` and `other` with respect to the variable `x`,
+    /// as the determinant of the Sylvester matrix. The determinant is computed
+    /// with fraction-free Gaussian elimination.
+    pub fn resultant(&self, other: &Self, x: usize) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return self.new_from(None);
+        }
+
+        let m = self.degree(x).to_u32() as usize;
+        let n = other.degree(x).to_u32() as usize;
+
+        if m == 0 && n == 0 {
+            return self.new_from_constant(self.field.one());
+        }
+        if m == 0 {
+            return self.pow(n as u64);
+        }
+        if n == 0 {
+            return other.pow(m as u64);
+        }
+
+        // coefficients from highest to lowest power of x
+        let coeffs = |p: &Self, d: usize| {
+            let mut c = vec![p.new_from(None); d + 1];
+            for (cc, e) in p.to_univariate_polynomial_list(x) {
+                c[d - e.to_u32() as usize] = cc;
+            }
+            c
+        };
+        let a = coeffs(self, m);
+        let b = coeffs(other, n);
+
+        let size = m + n;
+        let mut mat = vec![vec![self.new_from(None); size]; size];
+        for i in 0..n {
+            for (j, c) in a.iter().enumerate() {
+                mat[i][i + j] = c.clone();
+            }
+        }
+        for i in 0..m {
+            for (j, c) in b.iter().enumerate() {
+                mat[n + i][i + j] = c.clone();
+            }
+        }
+
+        // Bareiss algorithm
+        let mut negate = false;
+        let mut prev_pivot = self.new_from_constant(self.field.one());
+        for k in 0..size - 1 {
+            if mat[k][k].is_zero() {
+                let Some(p) = (k + 1..size).find(|r| !mat[*r][k].is_zero()) else {
+                    return self.new_from(None);
+                };
+                mat.swap(k, p);
+                negate = !negate;
+            }
+
+            for i in k + 1..size {
+                for j in k + 1..size {
+                    let v = &(&mat[i][j] * &mat[k][k]) - &(&mat[i][k] * &mat[k][j]);
+                    mat[i][j] = &v / &prev_pivot;
+                }
+                mat[i][k] = self.new_from(None);
+            }
+
+            prev_pivot = mat[k][k].clone();
+        }
+
+        let det = mat[size - 1][size - 1].clone();
+        if negate {
+            -det
+        } else {
+            det
+        }
+    }
+
+    /// Eliminate the variable `x` from the system of polynomial equations `polys = 0`
+    /// using resultants. The polynomials that do not depend on `x` are kept, and
+    /// the polynomial with the lowest degree in `x` is used to eliminate `x`
+    /// from the others. The solutions of the original system are also solutions
+    /// of the returned system.
+    pub fn eliminate(polys: &[Self], x: usize) -> Vec<Self> {
+        let mut res = vec![];
+        let mut with_x = vec![];
+        for p in polys {
+            if p.degree(x) > E::zero() {
+                with_x.push(p);
+            } else {
+                res.push(p.clone());
+            }
+        }
+
+        with_x.sort_by_key(|p| p.degree(x));
+        if let Some((pivot, rest)) = with_x.split_first() {
+            for p in rest {
+                let r = pivot.resultant(p, x);
+                if !r.is_zero() {
+                    res.push(r);
+                }
+            }
+        }
+
+        res
+    }
 }
 
 impl<F: Field, E: Exponent> MultivariatePolynomial<F, E> {
@@ -2159,6 +2970,330 @@ impl<F: Field, E: Exponent> MultivariatePolynomial<F, E> {
 
         self.synthetic_division(div)
     }
+
+    /// Write the polynomial as a polynomial in `basis`, i.e., find `q` such that
+    /// `self = q(basis[0], basis[1], ...)`, for example to express a symmetric polynomial
+    /// in `x+y` and `x*y`. Every polynomial in the basis must have a positive degree.
+    ///
+    /// All products of the basis polynomials up to the total degree of `self` are considered, so
+    /// representations that require cancellations of higher-degree terms are not found.
+    /// The returned polynomial has one variable per basis polynomial.
+    pub fn express_in_basis(&self, basis: &[Self]) -> Option<Self> {
+        let total_degree = |p: &Self| {
+            (0..p.nterms)
+                .map(|t| p.exponents(t).iter().map(|e| e.to_u32()).sum::<u32>())
+                .max()
+                .unwrap_or(0)
+        };
+
+        let degrees: Vec<_> = basis.iter().map(total_degree).collect();
+        assert!(
+            degrees.iter().all(|d| *d > 0),
+            "Basis polynomials must have a positive degree"
+        );
+
+        // generate all exponents of the basis polynomials that do not exceed the degree
+        let max_degree = total_degree(self);
+        let mut candidates: Vec<Vec<u32>> = vec![vec![]];
+        for d in &degrees {
+            let mut new_candidates = vec![];
+            for c in &candidates {
+                let used: u32 = c.iter().zip(&degrees).map(|(e, d)| e * d).sum();
+                for e in 0..=(max_degree - used) / d {
+                    let mut c = c.clone();
+                    c.push(e);
+                    new_candidates.push(c);
+                }
+            }
+            candidates = new_candidates;
+        }
+
+        let products: Vec<Self> = candidates
+            .iter()
+            .map(|c| {
+                let mut r = self.new_from_constant(self.field.one());
+                for (b, e) in basis.iter().zip(c) {
+                    if *e > 0 {
+                        r = &r * &b.pow(*e as u64);
+                    }
+                }
+                r
+            })
+            .collect();
+
+        // assign a row to every monomial
+        let mut rows: HashMap<&[E], u32> = HashMap::new();
+        for p in products.iter().chain(std::iter::once(self)) {
+            for t in 0..p.nterms {
+                let n = rows.len() as u32;
+                rows.entry(p.exponents(t)).or_insert(n);
+            }
+        }
+
+        let mut m = Matrix::new(rows.len() as u32, products.len() as u32, self.field);
+        for (c, p) in products.iter().enumerate() {
+            for t in 0..p.nterms {
+                m[(rows[p.exponents(t)], c as u32)] = p.coefficients[t].clone();
+            }
+        }
+
+        let mut b = Matrix::new(rows.len() as u32, 1, self.field);
+        for t in 0..self.nterms {
+            b[(rows[self.exponents(t)], 0)] = self.coefficients[t].clone();
+        }
+
+        let sol = m.solve_particular(&b).ok()?;
+
+        let mut res = MultivariatePolynomial::new(basis.len(), self.field, None, None);
+        for (c, exp) in sol.data.into_iter().zip(&candidates) {
+            if !F::is_zero(&c) {
+                let exp: SmallVec<[E; INLINED_EXPONENTS]> =
+                    exp.iter().map(|e| E::from_u32(*e)).collect();
+                res.append_monomial(c, &exp);
+            }
+        }
+
+        Some(res)
+    }
+
+    /// Compute the compositional inverse of the power series `self` in the variable `x`,
+    /// truncated to order `order`, using Lagrange inversion. The result `g` satisfies
+    /// `self(g(y)) = y + O(y^(order+1))`, where terms of `self` of a degree larger than
+    /// `order` are ignored.
+    ///
+    /// Returns `None` if `self` depends on other variables, if it has a constant term,
+    /// if its linear coefficient is zero, or if the characteristic of the field is
+    /// not larger than `order`.
+    pub fn series_reversion(&self, x: usize, order: u32) -> Option<Self> {
+        let n = order as usize;
+        let mut c = vec![self.field.zero(); n + 1];
+        for t in self {
+            if t.exponents
+                .iter()
+                .enumerate()
+                .any(|(i, e)| i != x && !e.is_zero())
+            {
+                return None;
+            }
+
+            let d = t.exponents[x].to_u32() as usize;
+            if d <= n {
+                c[d] = t.coefficient.clone();
+            }
+        }
+
+        if !F::is_zero(&c[0]) || n == 0 || F::is_zero(&c[1]) {
+            return None;
+        }
+
+        // the power series x/self up to order n - 1
+        let inv_lin = self.field.inv(&c[1]);
+        let mut h = vec![self.field.zero(); n];
+        h[0] = inv_lin.clone();
+        for k in 1..n {
+            let mut s = self.field.zero();
+            for j in 1..=k {
+                if j < n {
+                    self.field.add_mul_assign(&mut s, &c[j + 1], &h[k - j]);
+                }
+            }
+            h[k] = self.field.neg(&self.field.mul(&s, &inv_lin));
+        }
+
+        // the coefficient of y^k is the coefficient of x^(k-1) in (x/self)^k, divided by k
+        let mut res = self.new_from(Some(n));
+        let mut exp = vec![E::zero(); self.nvars];
+        let mut power = h.clone();
+        let mut k_elem = self.field.zero();
+        for k in 1..=n {
+            self.field.add_assign(&mut k_elem, &self.field.one());
+            if F::is_zero(&k_elem) {
+                return None;
+            }
+
+            if k > 1 {
+                let mut new_power = vec![self.field.zero(); n];
+                for (i, p) in power.iter().enumerate() {
+                    if F::is_zero(p) {
+                        continue;
+                    }
+                    for (j, hh) in h[..n - i].iter().enumerate() {
+                        self.field.add_mul_assign(&mut new_power[i + j], p, hh);
+                    }
+                }
+                power = new_power;
+            }
+
+            if !F::is_zero(&power[k - 1]) {
+                exp[x] = E::from_u32(k as u32);
+                res.append_monomial_back(self.field.div(&power[k - 1], &k_elem), &exp);
+            }
+        }
+
+        Some(res)
+    }
+}
+
+/// Statistics of the coefficients of an integer polynomial, see
+/// [`MultivariatePolynomial::coefficient_stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CoefficientStats {
+    /// The largest number of bits of a coefficient.
+    pub max_bits: u32,
+    /// The average number of bits of the coefficients.
+    pub average_bits: f64,
+    /// The number of coefficients that are stored as heap-allocated integers.
+    pub large_count: usize,
+    /// The number of terms divided by the number of terms of a dense polynomial
+    /// with the same degree in every variable.
+    pub density: f64,
+}
+
+impl<E: Exponent> MultivariatePolynomial<IntegerRing, E> {
+    /// Compute statistics of the size of the coefficients and the density of the polynomial,
+    /// which can be used to select an algorithm, for example to switch to modular arithmetic
+    /// when the coefficients become large.
+    pub fn coefficient_stats(&self) -> CoefficientStats {
+        if self.is_zero() {
+            return CoefficientStats::default();
+        }
+
+        let mut stats = CoefficientStats::default();
+        let mut total_bits = 0u64;
+        for c in &self.coefficients {
+            let bits = match c {
+                Integer::Natural(n) => 64 - n.unsigned_abs().leading_zeros(),
+                Integer::Large(n) => {
+                    stats.large_count += 1;
+                    n.significant_bits()
+                }
+            };
+            stats.max_bits = stats.max_bits.max(bits);
+            total_bits += bits as u64;
+        }
+        stats.average_bits = total_bits as f64 / self.nterms as f64;
+
+        let dense_terms: f64 = (0..self.nvars)
+            .map(|v| self.degree(v).to_u32() as f64 + 1.)
+            .product();
+        stats.density = self.nterms as f64 / dense_terms;
+
+        stats
+    }
+}
+
+impl<E: Exponent> MultivariatePolynomial<IntegerRing, E> {
+    /// Compute the maximum norm, which is the largest absolute value of the coefficients.
+    pub fn max_norm(&self) -> Integer {
+        self.coefficients
+            .iter()
+            .max_by(|a, b| a.abs_cmp(b))
+            .map(|c| c.abs())
+            .unwrap_or_else(Integer::zero)
+    }
+
+    /// Compute the l1 norm, which is the sum of the absolute values of the coefficients.
+    pub fn l1_norm(&self) -> Integer {
+        let mut norm = Integer::zero();
+        for c in &self.coefficients {
+            norm += &c.abs();
+        }
+        norm
+    }
+
+    /// Compute the l2 norm, rounded up.
+    pub fn l2_norm_ceil(&self) -> Integer {
+        let mut sum = ArbitraryPrecisionInteger::new();
+        for c in &self.coefficients {
+            match c {
+                Integer::Natural(n) => sum += ArbitraryPrecisionInteger::from(*n).square(),
+                Integer::Large(n) => sum += n.clone().square(),
+            }
+        }
+
+        let (root, rem) = sum.sqrt_rem(ArbitraryPrecisionInteger::new());
+        if rem == 0 {
+            Integer::from_large(root)
+        } else {
+            Integer::from_large(root + 1)
+        }
+    }
+
+    /// Compute the Mignotte bound `2^d * |self|_2` on the absolute value of the
+    /// coefficients of every factor of the polynomial, where `d` is the sum of the
+    /// degrees in all variables. It can be used to choose the number of primes or
+    /// the size of a prime power that is needed to reconstruct a factor from its
+    /// modular images.
+    pub fn mignotte_bound(&self) -> Integer {
+        let d: u32 = (0..self.nvars).map(|v| self.degree(v).to_u32()).sum();
+        &Integer::new(2).pow(d as u64) * &self.l2_norm_ceil()
+    }
+}
+
+impl<E: Exponent> MultivariatePolynomial<IntegerRing, E> {
+    /// Compute the square root of the polynomial, if it is a perfect square.
+    pub fn sqrt(&self) -> Option<Self> {
+        self.nth_root(2)
+    }
+
+    /// Compute the polynomial `q` such that `q^n = self`, if it exists.
+    /// The root is constructed term by term, starting from the leading term,
+    /// and the algorithm aborts as soon as a term cannot be part of a root.
+    pub fn nth_root(&self, n: u32) -> Option<Self> {
+        assert!(n > 0, "Cannot take the zeroth root");
+
+        if n == 1 || self.is_zero() {
+            return Some(self.clone());
+        }
+
+        let mut lexp = Vec::with_capacity(self.nvars);
+        for e in self.last_exponents() {
+            if e.to_u32() % n != 0 {
+                return None;
+            }
+            lexp.push(E::from_u32(e.to_u32() / n));
+        }
+
+        let lc = self.lcoeff().nth_root(n)?;
+
+        // the root cannot have a higher degree than deg(self) / n in any variable
+        let max_deg: Vec<_> = (0..self.nvars)
+            .map(|v| self.degree(v).to_u32() / n)
+            .collect();
+
+        // every new term t of the root satisfies lt(self - root^n) = n * lt(root)^(n-1) * t
+        let div_coeff = &Integer::new(n as i64) * &lc.pow(n as u64 - 1);
+        let div_exp: Vec<_> = lexp.iter().map(|e| e.to_u32() * (n - 1)).collect();
+
+        let mut root = self.new_from_monomial(lc, lexp);
+        loop {
+            let r = self - &root.pow(n as u64);
+            if r.is_zero() {
+                return Some(root);
+            }
+
+            let (q, rem) = self.field.quot_rem(&r.lcoeff(), &div_coeff);
+            if !rem.is_zero() {
+                return None;
+            }
+
+            let mut exp = Vec::with_capacity(self.nvars);
+            for ((e, d), m) in r.last_exponents().iter().zip(&div_exp).zip(&max_deg) {
+                let e = e.to_u32();
+                if e < *d || e - d > *m {
+                    return None;
+                }
+                exp.push(E::from_u32(e - d));
+            }
+
+            // the new term must be smaller than all terms in the root
+            if Self::cmp_exponents(&exp, root.exponents(0)) != Ordering::Less {
+                return None;
+            }
+
+            root.append_monomial(q, &exp);
+        }
+    }
 }
 
 /// View object for a term in a multivariate polynomial.
@@ -2204,3 +3339,313 @@ impl<'a, F: Ring, E: Exponent> IntoIterator for &'a MultivariatePolynomial<F, E>
         }
     }
 }
+
+/// Shift the dense univariate polynomial `c` by `a`, where `powers[k]` is `(x+a)^(2^k)`.
+fn dense_taylor_shift<F: Ring>(
+    field: &F,
+    mut c: Vec<F::Element>,
+    a: &F::Element,
+    powers: &[Vec<F::Element>],
+) -> Vec<F::Element> {
+    let n = c.len();
+    if n <= SHIFT_HORNER_LENGTH {
+        // after step i, the coefficient of x^i is final
+        for i in 0..n {
+            for j in (i..n - 1).rev() {
+                let (low, high) = c.split_at_mut(j + 1);
+                field.add_mul_assign(&mut low[j], &high[0], a);
+            }
+        }
+        return c;
+    }
+
+    // split at the largest power of two m = 2^k < n
+    let k = (n - 1).ilog2() as usize;
+    let m = 1 << k;
+    let high = dense_taylor_shift(field, c.split_off(m), a, powers);
+    let mut res = dense_taylor_shift(field, c, a, powers);
+    res.resize(n, field.zero());
+    for (r, h) in res
+        .iter_mut()
+        .zip(dense_karatsuba_mul(field, &powers[k], &high))
+    {
+        field.add_assign(r, &h);
+    }
+    res
+}
+
+/// Divide the dense univariate polynomial `a` by `b`, where `b` has a non-zero leading
+/// coefficient. The quotient and remainder have no trailing zeros.
+pub(crate) fn dense_quot_rem<F: Field>(
+    field: &F,
+    a: &[F::Element],
+    b: &[F::Element],
+) -> (Vec<F::Element>, Vec<F::Element>) {
+    let mut r = a.to_vec();
+    if r.len() < b.len() {
+        return (vec![], r);
+    }
+
+    let inv_lc = field.inv(b.last().unwrap());
+    let mut q = vec![field.zero(); r.len() - b.len() + 1];
+    for i in (0..q.len()).rev() {
+        let c = field.mul(&r[i + b.len() - 1], &inv_lc);
+        for (j, bb) in b.iter().enumerate() {
+            field.sub_mul_assign(&mut r[i + j], bb, &c);
+        }
+        q[i] = c;
+    }
+
+    while r.last().map(|c| F::is_zero(c)).unwrap_or(false) {
+        r.pop();
+    }
+    while q.last().map(|c| F::is_zero(c)).unwrap_or(false) {
+        q.pop();
+    }
+    (q, r)
+}
+
+/// Multiply two dense univariate polynomials with Karatsuba's algorithm.
+fn dense_karatsuba_mul<F: Ring>(field: &F, a: &[F::Element], b: &[F::Element]) -> Vec<F::Element> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+
+    let mut res = vec![field.zero(); a.len() + b.len() - 1];
+    if a.len() < KARATSUBA_LENGTH || b.len() < KARATSUBA_LENGTH {
+        for (i, aa) in a.iter().enumerate() {
+            for (j, bb) in b.iter().enumerate() {
+                field.add_mul_assign(&mut res[i + j], aa, bb);
+            }
+        }
+        return res;
+    }
+
+    let m = a.len().max(b.len()) / 2;
+    if a.len() <= m || b.len() <= m {
+        // split the longer polynomial into chunks of the length of the shorter one
+        let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+        for (i, chunk) in long.chunks(short.len()).enumerate() {
+            for (r, p) in res[i * short.len()..]
+                .iter_mut()
+                .zip(dense_karatsuba_mul(field, short, chunk))
+            {
+                field.add_assign(r, &p);
+            }
+        }
+        return res;
+    }
+
+    // (a0 + x^m a1)(b0 + x^m b1) = z0 + x^m ((a0 + a1)(b0 + b1) - z0 - z2) + x^(2m) z2
+    let (a0, a1) = a.split_at(m);
+    let (b0, b1) = b.split_at(m);
+    let z0 = dense_karatsuba_mul(field, a0, b0);
+    let z2 = dense_karatsuba_mul(field, a1, b1);
+
+    let sum = |p0: &[F::Element], p1: &[F::Element]| {
+        let mut s = p0.to_vec();
+        s.resize(p0.len().max(p1.len()), field.zero());
+        for (s, p) in s.iter_mut().zip(p1) {
+            field.add_assign(s, p);
+        }
+        s
+    };
+    let mut z1 = dense_karatsuba_mul(field, &sum(a0, a1), &sum(b0, b1));
+    for (z, p) in z1.iter_mut().zip(&z0) {
+        field.sub_assign(z, p);
+    }
+    for (z, p) in z1.iter_mut().zip(&z2) {
+        field.sub_assign(z, p);
+    }
+
+    for (r, p) in res.iter_mut().zip(&z0) {
+        field.add_assign(r, p);
+    }
+    for (r, p) in res[m..].iter_mut().zip(&z1) {
+        field.add_assign(r, p);
+    }
+    for (r, p) in res[2 * m..].iter_mut().zip(&z2) {
+        field.add_assign(r, p);
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::{
+        rings::integer::{Integer, IntegerRing},
+        state::State,
+    };
+
+    use super::{dense_karatsuba_mul, MultivariatePolynomial};
+
+    #[test]
+    fn test_karatsuba_mul() {
+        let field = IntegerRing::new();
+        for (n, m) in [(40, 100), (70, 70), (5, 90)] {
+            let a: Vec<_> = (0..n).map(|i| Integer::Natural(i * 7 % 11 - 5)).collect();
+            let b: Vec<_> = (0..m).map(|i| Integer::Natural(i * 3 % 13 - 6)).collect();
+
+            let mut expected = vec![Integer::zero(); a.len() + b.len() - 1];
+            for (i, aa) in a.iter().enumerate() {
+                for (j, bb) in b.iter().enumerate() {
+                    expected[i + j] = &expected[i + j] + &(aa * bb);
+                }
+            }
+            assert_eq!(dense_karatsuba_mul(&field, &a, &b), expected);
+        }
+    }
+
+    #[test]
+    fn test_shift() {
+        let field = IntegerRing::new();
+        let mut p = MultivariatePolynomial::<_, u16>::new(2, field, None, None);
+        for i in 0..100 {
+            p.append_monomial(Integer::Natural(i as i64 - 37), &[i, i % 3]);
+        }
+
+        // x -> x - 2 as sum_i c_i y^j (x - 2)^i
+        let mut x_shift = MultivariatePolynomial::new(2, field, None, None);
+        x_shift.append_monomial(Integer::Natural(-2), &[0, 0]);
+        x_shift.append_monomial(Integer::Natural(1), &[1, 0]);
+        let mut expected = MultivariatePolynomial::new(2, field, None, None);
+        for t in &p {
+            let mut m = MultivariatePolynomial::new(2, field, None, None);
+            m.append_monomial(t.coefficient.clone(), &[0, t.exponents[1]]);
+            expected = expected + &m * &x_shift.pow(t.exponents[0] as u64);
+        }
+
+        let shifted = p.shift(0, &Integer::Natural(-2));
+        assert_eq!(shifted, expected);
+        assert_eq!(shifted.shift(0, &Integer::Natural(2)), p);
+    }
+
+    #[test]
+    fn test_fingerprint_independent_of_identifiers() {
+        let field = IntegerRing::new();
+
+        let mut state1 = State::new();
+        let x1 = state1.get_or_insert_var("x");
+        let y1 = state1.get_or_insert_var("y");
+        let mut p1 = MultivariatePolynomial::<_, u8>::new(2, field, None, Some(&[x1, y1]));
+        p1.append_monomial(Integer::Natural(3), &[2, 1]);
+        p1.append_monomial(Integer::Natural(-1), &[0, 4]);
+
+        // a state with additional variables and y and x in the reverse order
+        let mut state2 = State::new();
+        state2.get_or_insert_var("z");
+        let y2 = state2.get_or_insert_var("y");
+        let x2 = state2.get_or_insert_var("x");
+        assert_ne!(x1, x2);
+        let mut p2 = MultivariatePolynomial::<_, u8>::new(2, field, None, Some(&[y2, x2]));
+        p2.append_monomial(Integer::Natural(3), &[1, 2]);
+        p2.append_monomial(Integer::Natural(-1), &[4, 0]);
+
+        assert_eq!(p1.fingerprint(&state1), p2.fingerprint(&state2));
+
+        let mut p3 = p2.clone();
+        p3.append_monomial(Integer::Natural(1), &[1, 0]);
+        assert_ne!(p1.fingerprint(&state1), p3.fingerprint(&state2));
+    }
+
+    #[test]
+    fn test_check_consistency() {
+        let mut p = MultivariatePolynomial::<_, u8>::new(2, IntegerRing::new(), None, None);
+        p.append_monomial(Integer::Natural(2), &[1, 0]);
+        p.append_monomial(Integer::Natural(-3), &[0, 2]);
+        p.check_consistency();
+    }
+
+    #[test]
+    #[should_panic(expected = "0 coefficient")]
+    fn test_check_consistency_zero_coefficient() {
+        let mut p = MultivariatePolynomial::<_, u8>::new(1, IntegerRing::new(), None, None);
+        p.append_monomial(Integer::Natural(2), &[1]);
+        p.coefficients[0] = Integer::zero();
+        p.check_consistency();
+    }
+
+    #[test]
+    fn test_mul_truncated_exponent_overflow() {
+        let field = IntegerRing::new();
+        let poly = |exps: &[u8]| {
+            let mut p = MultivariatePolynomial::<_, u8>::new(1, field, None, None);
+            for e in exps {
+                p.append_monomial(Integer::one(), &[*e]);
+            }
+            p
+        };
+
+        // x^200 * x^60 does not fit in a u8, but is truncated
+        let a = poly(&[0, 200]);
+        let b = poly(&[0, 60]);
+        let expected = poly(&[0, 60, 200]);
+        assert_eq!(a.mul_truncated(&b, &[250]), expected);
+        assert_eq!(a.mul_truncated_total_degree(&b, 250), expected);
+    }
+
+    /// A random polynomial in `nvars` variables with `nterms` terms, in which every
+    /// exponent is either zero or in `exps`.
+    fn random_packing_poly(
+        rng: &mut impl Rng,
+        nvars: usize,
+        nterms: usize,
+        exps: std::ops::RangeInclusive<u16>,
+    ) -> MultivariatePolynomial<IntegerRing, u16> {
+        let mut p = MultivariatePolynomial::new(nvars, IntegerRing::new(), None, None);
+        for _ in 0..nterms {
+            let e: Vec<_> = (0..nvars)
+                .map(|_| {
+                    if rng.gen_bool(0.5) {
+                        rng.gen_range(exps.clone())
+                    } else {
+                        0
+                    }
+                })
+                .collect();
+            p.append_monomial(Integer::Natural(rng.gen_range(-100..=100)), &e);
+        }
+        p
+    }
+
+    #[test]
+    fn test_heap_mul_packed_u128() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for nvars in 9..=16 {
+            // the exponents of the product reach 254
+            let a = random_packing_poly(&mut rng, nvars, 30, 100..=127);
+            let b = random_packing_poly(&mut rng, nvars, 30, 100..=127);
+
+            let mut expected = a.new_from(None);
+            for t in &b {
+                expected = expected + a.clone().mul_monomial(t.coefficient, t.exponents);
+            }
+
+            assert_eq!(
+                a.heap_mul_packed_exp_u128(&b),
+                expected,
+                "{} variables",
+                nvars
+            );
+        }
+    }
+
+    #[test]
+    fn test_heap_division_packed_u128() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for nvars in 9..=16 {
+            // the exponents of the dividend reach 127
+            let d = random_packing_poly(&mut rng, nvars, 10, 40..=64);
+            let q = random_packing_poly(&mut rng, nvars, 20, 40..=63);
+            let r = random_packing_poly(&mut rng, nvars, 10, 100..=127);
+            let a = &q * &d + r;
+
+            let (q1, r1) = a.heap_division_packed_exp_u128(&d, false);
+            let (q2, r2) = a.heap_division(&d, false);
+            assert_eq!((&q1, &r1), (&q2, &r2), "{} variables", nvars);
+            assert_eq!(&q1 * &d + r1, a);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use crate::rings::{
+    integer::{Integer, IntegerRing},
+    rational::{Rational, RationalField},
+    Ring,
+};
+
+use super::{polynomial::MultivariatePolynomial, Exponent};
+
+/// The largest number of points that is tested when searching a point
+/// where a polynomial is negative.
+const MAX_SAMPLE_POINTS: usize = 4096;
+
+/// A ring with a total order that is compatible with its operations.
+pub trait OrderedRing: Ring {
+    fn is_negative(&self, a: &Self::Element) -> bool;
+    /// Convert a small integer to an element of the ring.
+    fn element_from_i64(&self, a: i64) -> Self::Element;
+}
+
+impl OrderedRing for IntegerRing {
+    fn is_negative(&self, a: &Integer) -> bool {
+        a.is_negative()
+    }
+
+    fn element_from_i64(&self, a: i64) -> Integer {
+        Integer::Natural(a)
+    }
+}
+
+impl OrderedRing for RationalField {
+    fn is_negative(&self, a: &Rational) -> bool {
+        a.is_negative()
+    }
+
+    fn element_from_i64(&self, a: i64) -> Rational {
+        Rational::Natural(a, 1)
+    }
+}
+
+/// The result of a positivity check of a polynomial on the positive orthant `x_i >= 0`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Positivity<R: Ring> {
+    /// The polynomial is positive on the orthant, except at the origin
+    /// if the polynomial is homogeneous.
+    Positive,
+    /// The polynomial is non-negative on the orthant.
+    NonNegative,
+    /// The polynomial is negative at the given point.
+    Negative(Vec<R::Element>),
+    /// The positivity could not be decided.
+    Unknown,
+}
+
+impl<R: OrderedRing, E: Exponent> MultivariatePolynomial<R, E> {
+    /// Check if the polynomial is positive on the positive orthant `x_i >= 0`.
+    ///
+    /// The check uses Pólya's theorem: a polynomial is positive if the coefficients
+    /// of its homogenization, multiplied by `(x_0+x_1+...+x_n)^k` for some `k`, are
+    /// all positive. This is tried for `k` up to `max_multiplier_degree`. Since the
+    /// test is only sufficient, the polynomial is also evaluated at small integer points
+    /// to find a point where it is negative.
+    pub fn check_positivity(&self, max_multiplier_degree: usize) -> Positivity<R> {
+        if self.is_zero() {
+            return Positivity::NonNegative;
+        }
+
+        if self.is_constant() {
+            return if self.field.is_negative(&self.coefficients[0]) {
+                Positivity::Negative(vec![self.field.zero(); self.nvars])
+            } else {
+                Positivity::Positive
+            };
+        }
+
+        let homogeneous = (1..self.nterms).all(|t| {
+            Self::total_degree(self.exponents(t)) == Self::total_degree(self.exponents(0))
+        });
+
+        let mut nonnegative = false;
+        if (0..self.nterms).all(|t| !self.field.is_negative(&self.coefficients[t])) {
+            // the polynomial is a sum of non-negative terms
+            nonnegative = true;
+        }
+
+        let h = if homogeneous {
+            self.clone()
+        } else {
+            self.homogenize()
+        };
+
+        let mut sum = MultivariatePolynomial::new(h.nvars, h.field, Some(h.nvars), None);
+        for i in 0..h.nvars {
+            let mut exp = vec![E::zero(); h.nvars];
+            exp[i] = E::one();
+            sum.append_monomial(h.field.one(), &exp);
+        }
+
+        let mut p = h.clone();
+        for k in 0..=max_multiplier_degree {
+            if k > 0 {
+                p = p * &sum;
+            }
+
+            if (0..p.nterms).any(|t| p.field.is_negative(&p.coefficients[t])) {
+                continue;
+            }
+
+            nonnegative = true;
+
+            // all coefficients must be positive, including those of absent monomials
+            let degree = Self::total_degree(p.exponents(0));
+            if p.nterms == binomial(degree as usize + p.nvars - 1, p.nvars - 1) {
+                return Positivity::Positive;
+            }
+        }
+
+        if nonnegative {
+            return Positivity::NonNegative;
+        }
+
+        match self.find_negative_point() {
+            Some(point) => Positivity::Negative(point),
+            None => Positivity::Unknown,
+        }
+    }
+
+    fn total_degree(exponents: &[E]) -> u32 {
+        exponents.iter().map(|e| e.to_u32()).sum()
+    }
+
+    /// Homogenize the polynomial with a new last variable `x_0`,
+    /// as `x_0^d * p(x_1/x_0, ..., x_n/x_0)`.
+    fn homogenize(&self) -> Self {
+        let degree = (0..self.nterms)
+            .map(|t| Self::total_degree(self.exponents(t)))
+            .max()
+            .unwrap_or(0);
+
+        let mut res =
+            MultivariatePolynomial::new(self.nvars + 1, self.field, Some(self.nterms), None);
+
+        let mut exp = vec![E::zero(); self.nvars + 1];
+        for t in 0..self.nterms {
+            exp[..self.nvars].copy_from_slice(self.exponents(t));
+            exp[self.nvars] = E::from_u32(degree - Self::total_degree(self.exponents(t)));
+            res.append_monomial(self.coefficients[t].clone(), &exp);
+        }
+        res
+    }
+
+    /// Evaluate the polynomial at points with coordinates in `{0,1,2}`
+    /// to find a point where it is negative.
+    fn find_negative_point(&self) -> Option<Vec<R::Element>> {
+        if 3f64.powi(self.nvars as i32) > MAX_SAMPLE_POINTS as f64 {
+            return None;
+        }
+
+        let mut point = vec![0; self.nvars];
+        loop {
+            let values: Vec<_> = point
+                .iter()
+                .map(|x| self.field.element_from_i64(*x))
+                .collect();
+
+            let mut res = self.field.zero();
+            for t in 0..self.nterms {
+                let mut term = self.coefficients[t].clone();
+                for (v, e) in values.iter().zip(self.exponents(t)) {
+                    self.field
+                        .mul_assign(&mut term, &self.field.pow(v, e.to_u32() as u64));
+                }
+                self.field.add_assign(&mut res, &term);
+            }
+
+            if self.field.is_negative(&res) {
+                return Some(values);
+            }
+
+            // go to the next point
+            let mut i = 0;
+            while i < self.nvars && point[i] == 2 {
+                point[i] = 0;
+                i += 1;
+            }
+            if i == self.nvars {
+                return None;
+            }
+            point[i] += 1;
+        }
+    }
+}
+
+fn binomial(n: usize, k: usize) -> usize {
+    let mut res = 1;
+    for i in 0..k {
+        res = res * (n - i) / (i + 1);
+    }
+    res
+}
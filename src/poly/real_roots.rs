@@ -0,0 +1,188 @@
+use crate::rings::Field;
+
+use super::{
+    polynomial::{dense_quot_rem, MultivariatePolynomial},
+    positivity::OrderedRing,
+    Exponent,
+};
+
+/// The Sturm sequence of a square-free univariate polynomial, which counts the
+/// number of distinct real roots in an interval exactly.
+#[derive(Clone, Debug)]
+pub struct SturmSequence<F: Field + OrderedRing> {
+    field: F,
+    /// Dense coefficient lists, starting from the constant term.
+    polys: Vec<Vec<F::Element>>,
+}
+
+impl<F: Field + OrderedRing> SturmSequence<F> {
+    /// Create the Sturm sequence for a polynomial with dense coefficients `coeffs`,
+    /// starting from the constant term. Multiple roots are removed first.
+    fn new(field: F, coeffs: Vec<F::Element>) -> Self {
+        let mut seq = Self::build(field, coeffs);
+
+        // the last polynomial is the gcd of the polynomial and its derivative
+        if seq.polys.last().unwrap().len() > 1 {
+            let square_free = dense_quot_rem(&field, &seq.polys[0], seq.polys.last().unwrap()).0;
+            seq = Self::build(field, square_free);
+        }
+
+        seq
+    }
+
+    fn build(field: F, coeffs: Vec<F::Element>) -> Self {
+        let derivative: Vec<_> = coeffs
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, c)| field.mul(c, &field.element_from_i64(i as i64)))
+            .collect();
+
+        let mut polys = vec![coeffs, derivative];
+        loop {
+            let n = polys.len();
+            if polys[n - 1].is_empty() {
+                polys.pop();
+                break;
+            }
+
+            let r = dense_quot_rem(&field, &polys[n - 2], &polys[n - 1]).1;
+            polys.push(r.iter().map(|c| field.neg(c)).collect());
+        }
+
+        SturmSequence { field, polys }
+    }
+
+    /// Count the sign changes in the sequence evaluated at `x`, ignoring zeros.
+    fn sign_changes(&self, x: &F::Element) -> usize {
+        count_changes(
+            self.polys
+                .iter()
+                .map(|p| evaluate(&self.field, p, x))
+                .filter(|v| !F::is_zero(v))
+                .map(|v| self.field.is_negative(&v)),
+        )
+    }
+
+    /// Count the sign changes at positive or negative infinity.
+    fn sign_changes_at_infinity(&self, negative: bool) -> usize {
+        count_changes(self.polys.iter().map(|p| {
+            let lc_negative = self.field.is_negative(p.last().unwrap());
+            lc_negative ^ (negative && p.len() % 2 == 0)
+        }))
+    }
+
+    /// Count the number of distinct real roots in the interval `(a, b]`.
+    pub fn count_roots_in(&self, a: &F::Element, b: &F::Element) -> usize {
+        if !self.field.is_negative(&self.field.sub(a, b)) {
+            return 0;
+        }
+
+        self.sign_changes(a) - self.sign_changes(b)
+    }
+
+    /// Count the number of distinct real roots.
+    pub fn count_roots(&self) -> usize {
+        self.sign_changes_at_infinity(true) - self.sign_changes_at_infinity(false)
+    }
+
+    /// Get disjoint intervals `(a, b]` that each contain exactly one distinct
+    /// real root, in increasing order.
+    pub fn isolate_roots(&self) -> Vec<(F::Element, F::Element)> {
+        let field = &self.field;
+        let p = &self.polys[0];
+
+        // Cauchy's bound: all roots lie in (-b, b)
+        let lc = p.last().unwrap();
+        let mut bound = field.zero();
+        for c in &p[..p.len() - 1] {
+            let mut r = field.div(c, lc);
+            if field.is_negative(&r) {
+                r = field.neg(&r);
+            }
+            if field.is_negative(&field.sub(&bound, &r)) {
+                bound = r;
+            }
+        }
+        field.add_assign(&mut bound, &field.one());
+
+        let mut res = vec![];
+        self.bisect(field.neg(&bound), bound, &mut res);
+        res
+    }
+
+    fn bisect(&self, a: F::Element, b: F::Element, res: &mut Vec<(F::Element, F::Element)>) {
+        match self.count_roots_in(&a, &b) {
+            0 => {}
+            1 => res.push((a, b)),
+            _ => {
+                let half = self.field.inv(&self.field.element_from_i64(2));
+                let mid = self.field.mul(&self.field.add(&a, &b), &half);
+                self.bisect(a, mid.clone(), res);
+                self.bisect(mid, b, res);
+            }
+        }
+    }
+}
+
+impl<F: Field + OrderedRing, E: Exponent> MultivariatePolynomial<F, E> {
+    /// Create the Sturm sequence of the polynomial, which must be non-zero
+    /// and only depend on the variable `x`.
+    pub fn sturm_sequence(&self, x: usize) -> SturmSequence<F> {
+        assert!(!self.is_zero(), "Cannot count the roots of zero");
+
+        let mut coeffs = vec![self.field.zero(); self.degree(x).to_u32() as usize + 1];
+        for t in self {
+            assert!(
+                t.exponents
+                    .iter()
+                    .enumerate()
+                    .all(|(i, e)| i == x || e.is_zero()),
+                "Polynomial is not univariate in variable {}",
+                x
+            );
+            coeffs[t.exponents[x].to_u32() as usize] = t.coefficient.clone();
+        }
+
+        SturmSequence::new(self.field, coeffs)
+    }
+
+    /// Count the number of distinct real roots in the interval `(a, b]`
+    /// of a polynomial that only depends on `x`.
+    pub fn count_real_roots_in(&self, x: usize, interval: (&F::Element, &F::Element)) -> usize {
+        self.sturm_sequence(x)
+            .count_roots_in(interval.0, interval.1)
+    }
+
+    /// Count the number of distinct real roots of a polynomial that only depends on `x`.
+    pub fn count_real_roots(&self, x: usize) -> usize {
+        self.sturm_sequence(x).count_roots()
+    }
+
+    /// Get disjoint intervals `(a, b]` that each contain exactly one distinct
+    /// real root of a polynomial that only depends on `x`, in increasing order.
+    pub fn isolate_real_roots(&self, x: usize) -> Vec<(F::Element, F::Element)> {
+        self.sturm_sequence(x).isolate_roots()
+    }
+}
+
+fn count_changes(signs: impl Iterator<Item = bool>) -> usize {
+    let mut changes = 0;
+    let mut last = None;
+    for s in signs {
+        if last.map(|l| l != s).unwrap_or(false) {
+            changes += 1;
+        }
+        last = Some(s);
+    }
+    changes
+}
+
+fn evaluate<F: Field>(field: &F, p: &[F::Element], x: &F::Element) -> F::Element {
+    let mut res = field.zero();
+    for c in p.iter().rev() {
+        field.mul_assign(&mut res, x);
+        field.add_assign(&mut res, c);
+    }
+    res
+}
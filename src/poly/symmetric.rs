@@ -0,0 +1,259 @@
+//! Detection of symmetries of polynomials under permutations of variables. A polynomial
+//! that is symmetric in a set of variables can be written in the elementary symmetric
+//! polynomials of these variables, which have a lower total degree. Gcds and
+//! factorizations can then be computed in this smaller representation.
+
+use ahash::HashMap;
+use smallvec::SmallVec;
+
+use crate::rings::{
+    finite_field::{FiniteField, FiniteFieldCore, FiniteFieldWorkspace},
+    EuclideanDomain, Ring,
+};
+
+use super::{
+    factor::FactorizationError, gcd::PolynomialGCD, polynomial::MultivariatePolynomial, Exponent,
+    INLINED_EXPONENTS,
+};
+
+impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
+    /// Check if the polynomial is invariant under the exchange of the variables `x` and `y`.
+    pub fn is_symmetric_under_swap(&self, x: usize, y: usize) -> bool {
+        let terms: HashMap<&[E], &F::Element> = (0..self.nterms)
+            .map(|t| (self.exponents(t), &self.coefficients[t]))
+            .collect();
+
+        let mut swapped: SmallVec<[E; INLINED_EXPONENTS]> = SmallVec::new();
+        (0..self.nterms).all(|t| {
+            swapped.clear();
+            swapped.extend_from_slice(self.exponents(t));
+            swapped.swap(x, y);
+            terms.get(swapped.as_slice()) == Some(&&self.coefficients[t])
+        })
+    }
+
+    /// Check if the polynomial is invariant under all permutations of `vars`.
+    pub fn is_symmetric(&self, vars: &[usize]) -> bool {
+        vars.windows(2)
+            .all(|w| self.is_symmetric_under_swap(w[0], w[1]))
+    }
+
+    /// Get the maximal sets of at least two variables in which the polynomial is symmetric.
+    /// Since the exchanges of variables generate all permutations, the polynomial is
+    /// invariant under all permutations of every returned set.
+    pub fn symmetric_groups(&self) -> Vec<Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = vec![];
+        'next: for x in 0..self.nvars {
+            if (0..self.nterms).all(|t| self.exponents(t)[x].is_zero()) {
+                continue;
+            }
+
+            for g in &mut groups {
+                if self.is_symmetric_under_swap(g[0], x) {
+                    g.push(x);
+                    continue 'next;
+                }
+            }
+            groups.push(vec![x]);
+        }
+
+        groups.retain(|g| g.len() > 1);
+        groups
+    }
+
+    /// Get the elementary symmetric polynomials `e_1, ..., e_n` in the variables `vars`.
+    pub fn elementary_symmetric(&self, vars: &[usize]) -> Vec<Self> {
+        // e_k(x_1..x_i) = e_k(x_1..x_{i-1}) + x_i * e_{k-1}(x_1..x_{i-1})
+        let mut e = vec![self.new_from_constant(self.field.one())];
+        for (i, x) in vars.iter().enumerate() {
+            let mut exp = vec![E::zero(); self.nvars];
+            exp[*x] = E::one();
+            let xi = self.new_from_monomial(self.field.one(), exp);
+
+            e.push(self.new_from(None));
+            for k in (1..=i + 1).rev() {
+                let add = &xi * &e[k - 1];
+                e[k] = &e[k] + &add;
+            }
+        }
+
+        e.remove(0);
+        e
+    }
+
+    /// Write a polynomial that is symmetric in `vars` in the elementary symmetric polynomials
+    /// `e_1, ..., e_n` of these variables. In the result, the variable `vars[i]` represents `e_{i+1}`.
+    ///
+    /// Returns `None` if the polynomial is not symmetric in `vars`.
+    pub fn to_elementary_symmetric(&self, vars: &[usize]) -> Option<Self> {
+        if !self.is_symmetric(vars) {
+            return None;
+        }
+
+        let e = self.elementary_symmetric(vars);
+
+        let mut f = self.clone();
+        let mut res = self.new_from(None);
+        while !f.is_zero() {
+            // the leading term in a lexicographical order of the exponents of `vars`
+            let t = (0..f.nterms)
+                .max_by(|a, b| {
+                    let (a, b) = (f.exponents(*a), f.exponents(*b));
+                    vars.iter().map(|x| a[*x]).cmp(vars.iter().map(|x| b[*x]))
+                })
+                .unwrap();
+
+            // the leading exponents of a symmetric polynomial are non-increasing, and
+            // are produced by e_1^(a_1-a_2) * e_2^(a_2-a_3) * ... * e_n^a_n
+            let exp = f.exponents(t);
+            let mut new_exp: SmallVec<[E; INLINED_EXPONENTS]> = exp.into();
+            let mut rest: SmallVec<[E; INLINED_EXPONENTS]> = exp.into();
+            for (i, x) in vars.iter().enumerate() {
+                let next = vars.get(i + 1).map(|y| exp[*y]).unwrap_or(E::zero());
+                if exp[*x] < next {
+                    return None;
+                }
+                new_exp[*x] = exp[*x] - next;
+                rest[*x] = E::zero();
+            }
+
+            let c = f.coefficients[t].clone();
+            let mut sub = f.new_from_monomial(c.clone(), rest.to_vec());
+            for (ei, x) in e.iter().zip(vars) {
+                if !new_exp[*x].is_zero() {
+                    sub = &sub * &ei.pow(new_exp[*x].to_u32() as u64);
+                }
+            }
+
+            f = f - sub;
+            res.append_monomial(c, &new_exp);
+        }
+
+        Some(res)
+    }
+
+    /// Substitute the elementary symmetric polynomials of `vars` in a polynomial obtained
+    /// from [`MultivariatePolynomial::to_elementary_symmetric`].
+    pub fn from_elementary_symmetric(&self, vars: &[usize]) -> Self {
+        let e = self.elementary_symmetric(vars);
+
+        let mut res = self.new_from(None);
+        for t in 0..self.nterms {
+            let mut rest = self.exponents(t).to_vec();
+            for x in vars {
+                rest[*x] = E::zero();
+            }
+
+            let mut term = self.new_from_monomial(self.coefficients[t].clone(), rest);
+            for (ei, x) in e.iter().zip(vars) {
+                let p = self.exponents(t)[*x];
+                if !p.is_zero() {
+                    term = &term * &ei.pow(p.to_u32() as u64);
+                }
+            }
+            res = res + term;
+        }
+        res
+    }
+}
+
+/// Get the sets of variables in which all polynomials `polys` are symmetric.
+fn common_symmetric_groups<F: Ring, E: Exponent>(
+    polys: &[&MultivariatePolynomial<F, E>],
+) -> Vec<Vec<usize>> {
+    let mut groups = polys[0].symmetric_groups();
+    for p in &polys[1..] {
+        let other = p.symmetric_groups();
+        groups = groups
+            .iter()
+            .flat_map(|g| {
+                other.iter().map(move |h| {
+                    g.iter()
+                        .filter(|x| h.contains(x))
+                        .cloned()
+                        .collect::<Vec<_>>()
+                })
+            })
+            .filter(|g| g.len() > 1)
+            .collect();
+    }
+    groups
+}
+
+impl<R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> MultivariatePolynomial<R, E> {
+    /// Compute the gcd of two polynomials. If both polynomials are symmetric in some
+    /// variables, the gcd is computed in the elementary symmetric polynomials of these
+    /// variables, as the gcd of symmetric polynomials is symmetric. Otherwise, the regular
+    /// gcd is computed.
+    pub fn gcd_symmetric(a: &Self, b: &Self) -> Self {
+        if a.is_zero() || b.is_zero() {
+            return MultivariatePolynomial::gcd(a, b);
+        }
+
+        let groups = common_symmetric_groups(&[a, b]);
+        if groups.is_empty() {
+            return MultivariatePolynomial::gcd(a, b);
+        }
+
+        let (mut a, mut b) = (a.clone(), b.clone());
+        for g in &groups {
+            a = a.to_elementary_symmetric(g).unwrap();
+            b = b.to_elementary_symmetric(g).unwrap();
+        }
+
+        let mut gcd = MultivariatePolynomial::gcd(&a, &b);
+        for g in groups.iter().rev() {
+            gcd = gcd.from_elementary_symmetric(g);
+        }
+
+        let u = gcd.field.get_inv_unit(&gcd.lcoeff());
+        gcd.mul_coeff(u)
+    }
+}
+
+impl<UField: FiniteFieldWorkspace, E: Exponent> MultivariatePolynomial<FiniteField<UField>, E>
+where
+    FiniteField<UField>: FiniteFieldCore<UField>,
+    <FiniteField<UField> as Ring>::Element: Copy,
+{
+    /// Factor the polynomial like [`MultivariatePolynomial::factor`]. If the polynomial
+    /// is symmetric in some variables, it is first factored in the elementary symmetric
+    /// polynomials of these variables. The resulting symmetric factors are then factored
+    /// further, which splits a large factorization into smaller ones.
+    pub fn factor_symmetric(&self) -> Result<Vec<(Self, usize)>, FactorizationError> {
+        let groups = self.symmetric_groups();
+        if groups.is_empty() {
+            return self.factor();
+        }
+
+        let mut f = self.clone();
+        for g in &groups {
+            f = f.to_elementary_symmetric(g).unwrap();
+        }
+
+        let mut lc = self.field.one();
+        let mut res: Vec<(Self, usize)> = vec![];
+        for (s, m) in f.factor()? {
+            let mut s = s;
+            for g in groups.iter().rev() {
+                s = s.from_elementary_symmetric(g);
+            }
+
+            for (h, n) in s.factor()? {
+                if h.is_constant() {
+                    let c = self.field.pow(&h.lcoeff(), (m * n) as u64);
+                    lc = self.field.mul(&lc, &c);
+                } else if let Some(r) = res.iter_mut().find(|(r, _)| *r == h) {
+                    r.1 += m * n;
+                } else {
+                    res.push((h, m * n));
+                }
+            }
+        }
+
+        if !self.field.is_one(&lc) {
+            res.insert(0, (self.new_from_constant(lc), 1));
+        }
+        Ok(res)
+    }
+}
@@ -1,14 +1,20 @@
 use std::fmt::{self, Display, Write};
 
+use ahash::HashMap;
 use colored::Colorize;
 
 use crate::{
     poly::{polynomial::MultivariatePolynomial, Exponent},
-    representations::{number::BorrowedNumber, Add, Atom, AtomView, Fun, Mul, Num, Pow, Var},
+    representations::{
+        default::DefaultRepresentation, number::BorrowedNumber, Add, Atom, AtomView, Fun, Mul, Num,
+        Pow, Var,
+    },
     rings::{
-        finite_field::FiniteFieldCore, rational_polynomial::RationalPolynomial, Ring, RingPrinter,
+        finite_field::FiniteFieldCore,
+        rational_polynomial::{RationalPolynomial, RationalPolynomialNormalization},
+        EuclideanDomain, Ring, RingPrinter,
     },
-    state::State,
+    state::{PrintWeight, State},
 };
 
 #[derive(Debug, Copy, Clone)]
@@ -17,6 +23,7 @@ pub struct SymbolicaPrintOptions {
     pub color_top_level_sum: bool,
     pub print_finite_field: bool,
     pub explicit_rational_polynomial: bool,
+    pub rational_polynomial_normalization: RationalPolynomialNormalization,
 }
 
 impl Default for SymbolicaPrintOptions {
@@ -26,6 +33,7 @@ impl Default for SymbolicaPrintOptions {
             color_top_level_sum: true,
             print_finite_field: true,
             explicit_rational_polynomial: false,
+            rational_polynomial_normalization: RationalPolynomialNormalization::None,
         }
     }
 }
@@ -168,6 +176,77 @@ impl<'a, P: Atom> AtomView<'a, P> {
     }
 }
 
+impl<'a> AtomView<'a, DefaultRepresentation> {
+    /// Export the expression as a graph in the Graphviz `dot` format. Every node
+    /// shows the type of the atom and its size in bytes. Identical subexpressions
+    /// are merged into a single node, so that the output is a DAG.
+    pub fn to_dot(&self, state: &State) -> String {
+        let mut nodes = HashMap::default();
+        let mut out = String::from("digraph G {\n\tnode [shape=box];\n");
+        self.write_dot_node(state, &mut nodes, &mut out);
+        out.push_str("}\n");
+        out
+    }
+
+    /// Write the node of this atom and of all its children, if they are not written yet.
+    /// Returns the index of the node.
+    fn write_dot_node(
+        &self,
+        state: &State,
+        nodes: &mut HashMap<&'a [u8], usize>,
+        out: &mut String,
+    ) -> usize {
+        if let Some(id) = nodes.get(self.get_data()) {
+            return *id;
+        }
+
+        let label = match self {
+            AtomView::Num(_) => format!(
+                "{}",
+                AtomPrinter::new(
+                    *self,
+                    PrintMode::default().set_color_top_level_sum(false),
+                    state
+                )
+            ),
+            AtomView::Var(v) => state.get_name(v.get_name()).unwrap().to_string(),
+            AtomView::Fun(f) => format!("fn {}", state.get_name(f.get_name()).unwrap()),
+            AtomView::Pow(_) => "^".to_owned(),
+            AtomView::Mul(_) => "*".to_owned(),
+            AtomView::Add(_) => "+".to_owned(),
+        };
+
+        let id = nodes.len();
+        nodes.insert(self.get_data(), id);
+        writeln!(
+            out,
+            "\t{} [label=\"{}\\n{} B\"];",
+            id,
+            label.replace('"', "\\\""),
+            self.get_data().len()
+        )
+        .unwrap();
+
+        let children: Vec<_> = match self {
+            AtomView::Num(_) | AtomView::Var(_) => vec![],
+            AtomView::Fun(f) => f.iter().collect(),
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                vec![base, exp]
+            }
+            AtomView::Mul(m) => m.iter().collect(),
+            AtomView::Add(a) => a.iter().collect(),
+        };
+
+        for (i, c) in children.iter().enumerate() {
+            let child_id = c.write_dot_node(state, nodes, out);
+            writeln!(out, "\t{} -> {} [label=\"{}\"];", id, child_id, i).unwrap();
+        }
+
+        id
+    }
+}
+
 impl<'a, P: Atom> fmt::Debug for AtomView<'a, P> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         self.fmt_debug(fmt)
@@ -211,7 +290,12 @@ impl<'a, A: Num<'a>> FormattedPrintNum for A {
             BorrowedNumber::FiniteField(num, fi) => {
                 f.write_fmt(format_args!("[m_{}%f_{}]", num.0, fi.0))
             }
-            BorrowedNumber::RationalPolynomial(p) => f.write_fmt(format_args!("{}", p,)),
+            BorrowedNumber::RationalPolynomial(p) => {
+                f.write_fmt(format_args!("{}", p.to_rational_polynomial()))
+            }
+            BorrowedNumber::AlgebraicNumber(a, ai) => {
+                f.write_fmt(format_args!("[{}%a_{}]", a.to_algebraic_number(), ai.0))
+            }
         }
     }
 
@@ -244,15 +328,76 @@ impl<'a, A: Num<'a>> FormattedPrintNum for A {
             BorrowedNumber::RationalPolynomial(p) => f.write_fmt(format_args!(
                 "({})",
                 RationalPolynomialPrinter {
-                    poly: p,
+                    poly: &p.to_rational_polynomial(),
                     state,
                     print_mode,
                 }
             )),
+            BorrowedNumber::AlgebraicNumber(a, ai) => f.write_fmt(format_args!(
+                "[{}%{}]",
+                a.to_algebraic_number(),
+                state.get_algebraic_extension(ai).poly()
+            )),
         }
     }
 }
 
+/// Compute the weight of an expression using the weight function of the state.
+fn print_weight<P: Atom>(x: AtomView<'_, P>, weight: &PrintWeight) -> i64 {
+    match x {
+        AtomView::Num(_) => 0,
+        AtomView::Var(v) => weight(v.get_name()),
+        AtomView::Fun(f) => weight(f.get_name()),
+        AtomView::Pow(p) => {
+            let (b, e) = p.get_base_exp();
+            let w = print_weight(b, weight);
+            match e {
+                AtomView::Num(n) => match n.get_number_view() {
+                    BorrowedNumber::Natural(e, 1) => w.saturating_mul(e),
+                    _ => w,
+                },
+                _ => w,
+            }
+        }
+        AtomView::Mul(m) => m
+            .iter()
+            .map(|x| print_weight(x, weight))
+            .fold(0, i64::saturating_add),
+        AtomView::Add(a) => a.iter().map(|x| print_weight(x, weight)).max().unwrap(),
+    }
+}
+
+/// Sort the terms of a sum by their weight, if the state has a weight function.
+fn ordered_terms<'a, P: Atom>(
+    terms: impl Iterator<Item = AtomView<'a, P>>,
+    state: &State,
+) -> Vec<AtomView<'a, P>> {
+    let mut terms: Vec<_> = terms.collect();
+    if let Some(weight) = state.get_print_weight() {
+        terms.sort_by_cached_key(|x| print_weight(*x, weight));
+    }
+    terms
+}
+
+/// Sort the factors of a product by their weight, if the state has a weight function.
+/// Non-commutative factors, such as non-commutative functions and their powers, keep their
+/// order and are printed after the other factors, followed by the numerical coefficient.
+fn ordered_factors<'a, P: Atom>(
+    factors: impl Iterator<Item = AtomView<'a, P>>,
+    state: &State,
+) -> Vec<AtomView<'a, P>> {
+    let mut factors: Vec<_> = factors.collect();
+    if let Some(weight) = state.get_print_weight() {
+        let rank = |x: &AtomView<'a, P>| match x {
+            AtomView::Num(_) => (2, 0),
+            _ if x.is_noncommutative(state) => (1, 0),
+            _ => (0, print_weight(*x, weight)),
+        };
+        factors.sort_by_cached_key(rank);
+    }
+    factors
+}
+
 impl<'a, A: Mul<'a>> FormattedPrintMul for A {
     fn fmt_debug(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut first = true;
@@ -282,7 +427,7 @@ impl<'a, A: Mul<'a>> FormattedPrintMul for A {
     ) -> fmt::Result {
         let mut first = true;
         print_state.level += 1;
-        for x in self.iter() {
+        for x in ordered_factors(self.iter(), state) {
             if !first {
                 f.write_char('*')?;
             }
@@ -406,7 +551,7 @@ impl<'a, A: Add<'a>> FormattedPrintAdd for A {
     ) -> fmt::Result {
         let mut first = true;
         print_state.level += 1;
-        for x in self.iter() {
+        for x in ordered_terms(self.iter(), state) {
             if !first {
                 if print_state.level == 1 && print_mode.get_terms_on_new_line() {
                     f.write_char('\n')?;
@@ -460,7 +605,7 @@ impl<'a, 'b, R: Ring, E: Exponent> RationalPolynomialPrinter<'a, 'b, R, E> {
     }
 }
 
-impl<'a, 'b, R: Ring, E: Exponent> Display for RationalPolynomialPrinter<'a, 'b, R, E> {
+impl<'a, 'b, R: EuclideanDomain, E: Exponent> Display for RationalPolynomialPrinter<'a, 'b, R, E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let print_explicit = match self.print_mode {
             PrintMode::Symbolica(s) => s.explicit_rational_polynomial,
@@ -500,21 +645,36 @@ impl<'a, 'b, R: Ring, E: Exponent> Display for RationalPolynomialPrinter<'a, 'b,
             return Ok(());
         }
 
-        if self.poly.denominator.is_one() {
-            f.write_fmt(format_args!(
-                "{}",
-                PolynomialPrinter {
-                    poly: &self.poly.numerator,
-                    state: self.state,
-                    print_mode: self.print_mode,
+        let norm = match self.print_mode {
+            PrintMode::Symbolica(s) => s.rational_polynomial_normalization,
+            PrintMode::Mathematica => RationalPolynomialNormalization::None,
+        };
+        let (c_num, c_den, poly) = self.poly.normalized_parts(norm);
+        let (num, den) = (&poly.numerator, &poly.denominator);
+        let field = num.field;
+
+        let has_prefactor = !field.is_one(&c_num) || !field.is_one(&c_den);
+        if has_prefactor {
+            if field.is_one(&c_den) && c_num == field.neg(&field.one()) && !num.is_one() {
+                f.write_char('-')?;
+            } else {
+                field.fmt_display(&c_num, f)?;
+                if !field.is_one(&c_den) {
+                    f.write_char('/')?;
+                    field.fmt_display(&c_den, f)?;
                 }
-            ))
-        } else {
-            if self.poly.numerator.nterms < 2 {
+                if !num.is_one() {
+                    f.write_char('*')?;
+                }
+            }
+        }
+
+        if !has_prefactor || !num.is_one() {
+            if num.nterms < 2 || !has_prefactor && den.is_one() {
                 f.write_fmt(format_args!(
                     "{}",
                     PolynomialPrinter {
-                        poly: &self.poly.numerator,
+                        poly: num,
                         state: self.state,
                         print_mode: self.print_mode,
                     }
@@ -523,50 +683,41 @@ impl<'a, 'b, R: Ring, E: Exponent> Display for RationalPolynomialPrinter<'a, 'b,
                 f.write_fmt(format_args!(
                     "({})",
                     PolynomialPrinter {
-                        poly: &self.poly.numerator,
+                        poly: num,
                         state: self.state,
                         print_mode: self.print_mode,
                     }
                 ))?;
             }
+        }
 
-            if self.poly.denominator.nterms == 1 {
-                let var_count = self
-                    .poly
-                    .denominator
-                    .exponents
-                    .iter()
-                    .filter(|x| !x.is_zero())
-                    .count();
-
-                if var_count == 0
-                    || self
-                        .poly
-                        .denominator
-                        .field
-                        .is_one(&self.poly.denominator.coefficients[0])
-                        && var_count == 1
-                {
-                    return f.write_fmt(format_args!(
-                        "/{}",
-                        PolynomialPrinter {
-                            poly: &self.poly.denominator,
-                            state: self.state,
-                            print_mode: self.print_mode,
-                        }
-                    ));
-                }
-            }
+        if den.is_one() {
+            return Ok(());
+        }
 
-            f.write_fmt(format_args!(
-                "/({})",
-                PolynomialPrinter {
-                    poly: &self.poly.denominator,
-                    state: self.state,
-                    print_mode: self.print_mode,
-                }
-            ))
+        if den.nterms == 1 {
+            let var_count = den.exponents.iter().filter(|x| !x.is_zero()).count();
+
+            if var_count == 0 || field.is_one(&den.coefficients[0]) && var_count == 1 {
+                return f.write_fmt(format_args!(
+                    "/{}",
+                    PolynomialPrinter {
+                        poly: den,
+                        state: self.state,
+                        print_mode: self.print_mode,
+                    }
+                ));
+            }
         }
+
+        f.write_fmt(format_args!(
+            "/({})",
+            PolynomialPrinter {
+                poly: den,
+                state: self.state,
+                print_mode: self.print_mode,
+            }
+        ))
     }
 }
 pub struct PolynomialPrinter<'a, 'b, F: Ring + Display, E: Exponent> {
@@ -635,7 +786,7 @@ impl<'a, 'b, F: Ring + Display, E: Exponent> Display for PolynomialPrinter<'a, '
 
                 f.write_str(self.state.get_name(*var_id).unwrap())?;
 
-                if e.to_u32() != 1 {
+                if e.to_fraction() != (1, 1) {
                     write!(f, "^{}", e)?;
                 }
             }
@@ -656,3 +807,39 @@ impl<'a, 'b, F: Ring + Display, E: Exponent> Display for PolynomialPrinter<'a, '
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::parse,
+        representations::default::DefaultRepresentation,
+        state::{FunctionAttribute, State, Workspace},
+    };
+
+    use super::AtomPrinter;
+
+    #[test]
+    fn test_print_weight_noncommutative_power() {
+        let mut state = State::new();
+        let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+        for name in ["A", "B"] {
+            let id = state.get_or_insert_var(name);
+            state.set_function_attributes(id, &[FunctionAttribute::NonCommutative]);
+        }
+
+        let input = parse("z*A(x)*B(y)^2")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+        let mut expr = workspace.new_atom();
+        input.to_view().normalize(&workspace, &state, &mut expr);
+
+        let y = state.get_or_insert_var("y");
+        state.set_print_weight(Some(Box::new(move |id| if id == y { 1 } else { 0 })));
+
+        assert_eq!(
+            AtomPrinter::new(expr.to_view(), <_>::default(), &state).to_string(),
+            "z*A(x)*B(y)^2"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use std::{
+    cell::RefCell,
+    time::{Duration, Instant},
+};
+
+/// The progress of a long-running algorithm.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// The name of the algorithm.
+    pub algorithm: &'static str,
+    /// The number of work units that are done, such as the number of
+    /// products of terms in a multiplication.
+    pub done: usize,
+    /// The total number of work units, if known.
+    pub total: Option<usize>,
+    /// The time since the observer was set.
+    pub elapsed: Duration,
+}
+
+/// An observer that is notified periodically of the progress of long-running
+/// algorithms, such as polynomial multiplication, the polynomial gcd and the
+/// normalization of large sums.
+pub trait ProgressObserver {
+    fn on_progress(&mut self, progress: &Progress);
+}
+
+impl<F: FnMut(&Progress)> ProgressObserver for F {
+    fn on_progress(&mut self, progress: &Progress) {
+        self(progress)
+    }
+}
+
+struct ActiveObserver {
+    observer: Box<dyn ProgressObserver>,
+    interval: Duration,
+    start: Instant,
+    last_report: Instant,
+}
+
+thread_local!(
+    static OBSERVER: RefCell<Option<ActiveObserver>> = const { RefCell::new(None) };
+);
+
+/// Set the progress observer for the algorithms that run on the current thread,
+/// or remove it with `None`. The observer is called at most once per `interval`.
+/// Returns the previous observer.
+pub fn set_progress_observer(
+    observer: Option<Box<dyn ProgressObserver>>,
+    interval: Duration,
+) -> Option<Box<dyn ProgressObserver>> {
+    let now = Instant::now();
+    OBSERVER.with(|o| {
+        o.replace(observer.map(|observer| ActiveObserver {
+            observer,
+            interval,
+            start: now,
+            last_report: now,
+        }))
+        .map(|a| a.observer)
+    })
+}
+
+/// Report the progress of `algorithm` to the observer of the current thread,
+/// if there is one and if the last report is at least one interval ago.
+pub fn report(algorithm: &'static str, done: usize, total: Option<usize>) {
+    OBSERVER.with(|o| {
+        // take the observer, so that the observer itself can use algorithms that report progress
+        let mut active = match o.borrow_mut().take() {
+            Some(a) => a,
+            None => return,
+        };
+
+        let now = Instant::now();
+        if now.duration_since(active.last_report) >= active.interval {
+            active.last_report = now;
+            active.observer.on_progress(&Progress {
+                algorithm,
+                done,
+                total,
+                elapsed: now.duration_since(active.start),
+            });
+        }
+
+        let mut o = o.borrow_mut();
+        if o.is_none() {
+            *o = Some(active);
+        }
+    });
+}
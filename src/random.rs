@@ -0,0 +1,249 @@
+//! Generation of random polynomials and expressions with a prescribed shape,
+//! for benchmarks and property tests with realistic inputs.
+
+use ahash::HashSet;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    poly::{polynomial::MultivariatePolynomial, Exponent},
+    representations::{
+        number::Number, Atom, AtomView, Identifier, Num, OwnedAdd, OwnedAtom, OwnedFun, OwnedMul,
+        OwnedNum, OwnedPow, OwnedVar,
+    },
+    rings::Ring,
+    state::{State, Workspace},
+    utils,
+};
+
+impl<F: Ring, E: Exponent> MultivariatePolynomial<F, E> {
+    /// Generate a polynomial over `field` in `nvars` variables with `nterms` different
+    /// monomials of total degree at most `max_degree`, or with all such monomials if there
+    /// are fewer. The coefficients are non-zero and are sampled from the half-open
+    /// range `coeff_range`, which must contain an integer that is non-zero in the field.
+    pub fn random(
+        field: F,
+        nvars: usize,
+        nterms: usize,
+        max_degree: u32,
+        coeff_range: (i64, i64),
+        rng: &mut impl Rng,
+    ) -> Self {
+        /// The number of zero coefficients in a row after which the range is considered to
+        /// only contain zeros of the field. A range with a non-zero element yields one with
+        /// a probability of at least a half, so that this bound is never reached by chance.
+        const MAX_ZERO_SAMPLES: usize = 64;
+
+        assert!(
+            coeff_range.0 < coeff_range.1 && coeff_range != (0, 1),
+            "The coefficient range must contain a non-zero integer"
+        );
+
+        // the number of monomials is binomial(nvars + max_degree, nvars)
+        let mut max_terms = 1u64;
+        for i in 1..=nvars as u64 {
+            max_terms = max_terms.saturating_mul(max_degree as u64 + i) / i;
+        }
+        let nterms = nterms.min(max_terms.min(usize::MAX as u64) as usize);
+
+        let sample_coefficient = |rng: &mut _| {
+            for _ in 0..MAX_ZERO_SAMPLES {
+                let c = field.sample(rng, coeff_range);
+                if !F::is_zero(&c) {
+                    return c;
+                }
+            }
+            panic!(
+                "The coefficient range {:?} does not contain a non-zero element of {}",
+                coeff_range, field
+            );
+        };
+
+        let mut poly = MultivariatePolynomial::new(nvars, field, Some(nterms), None);
+
+        // rejection sampling rarely finds the last missing monomials,
+        // so that they are all enumerated if most of them are needed
+        if nterms as u64 * 2 >= max_terms {
+            let mut monomials = utils::monomials(nvars, max_degree);
+            monomials.shuffle(rng);
+            for m in &monomials[..nterms] {
+                let exponents: Vec<E> = m.iter().map(|e| E::from_u32(*e)).collect();
+                let coeff = sample_coefficient(rng);
+                poly.append_monomial(coeff, &exponents);
+            }
+            return poly;
+        }
+
+        let mut seen: HashSet<Vec<E>> = HashSet::default();
+        let mut order: Vec<usize> = (0..nvars).collect();
+        while seen.len() < nterms {
+            let mut exponents = vec![E::zero(); nvars];
+            let mut remaining = rng.gen_range(0..=max_degree);
+            order.shuffle(rng);
+            for i in &order {
+                let e = rng.gen_range(0..=remaining);
+                exponents[*i] = E::from_u32(e);
+                remaining -= e;
+            }
+
+            if !seen.insert(exponents.clone()) {
+                continue;
+            }
+
+            let coeff = sample_coefficient(rng);
+            poly.append_monomial(coeff, &exponents);
+        }
+
+        poly
+    }
+}
+
+/// A generator of random normalized expressions in a fixed set of variables and functions.
+#[derive(Clone, Debug)]
+pub struct RandomAtomGenerator {
+    pub variables: Vec<Identifier>,
+    pub functions: Vec<Identifier>,
+    /// The maximal depth of an expression.
+    pub max_depth: usize,
+    /// The maximal number of arguments of a sum, product or function.
+    pub max_args: usize,
+    /// The maximal absolute value of an exponent.
+    pub max_degree: u32,
+    /// The half-open range from which the numbers are sampled.
+    pub coeff_range: (i64, i64),
+}
+
+impl RandomAtomGenerator {
+    /// Create a generator for expressions in the variables `variables` and the functions `functions`.
+    pub fn new(variables: &[Identifier], functions: &[Identifier]) -> Self {
+        RandomAtomGenerator {
+            variables: variables.to_vec(),
+            functions: functions.to_vec(),
+            max_depth: 3,
+            max_args: 4,
+            max_degree: 3,
+            coeff_range: (-10, 10),
+        }
+    }
+
+    /// Generate a normalized expression and write it in `out`.
+    pub fn generate<P: Atom>(
+        &self,
+        rng: &mut impl Rng,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let mut h = workspace.new_atom();
+        self.generate_unnormalized(rng, self.max_depth, workspace, state, h.get_mut());
+        h.get().to_view().normalize(workspace, state, out);
+    }
+
+    fn generate_unnormalized<P: Atom>(
+        &self,
+        rng: &mut impl Rng,
+        depth: usize,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        // only numbers and variables at the maximal depth
+        let kind = if depth == 0 {
+            rng.gen_range(0..=1)
+        } else {
+            rng.gen_range(0..=5)
+        };
+        let nargs = rng.gen_range(1..=self.max_args.max(1));
+
+        match kind {
+            1 if !self.variables.is_empty() => {
+                out.transform_to_var()
+                    .set_from_id(*self.variables.choose(rng).unwrap());
+            }
+            2 if !self.functions.is_empty() => {
+                let f = out.transform_to_fun();
+                f.set_from_name(*self.functions.choose(rng).unwrap());
+                for _ in 0..nargs {
+                    let mut arg = workspace.new_atom();
+                    self.generate_unnormalized(rng, depth - 1, workspace, state, arg.get_mut());
+                    f.add_arg(arg.get().to_view());
+                }
+                f.set_dirty(true);
+            }
+            3 => {
+                let mut base = workspace.new_atom();
+                self.generate_unnormalized(rng, depth - 1, workspace, state, base.get_mut());
+
+                let mut norm_base = workspace.new_atom();
+                base.get()
+                    .to_view()
+                    .normalize(workspace, state, norm_base.get_mut());
+
+                // prevent division by zero
+                let mut e = rng.gen_range(1..=self.max_degree.max(1) as i64);
+                let is_zero = matches!(norm_base.get().to_view(), AtomView::Num(n) if n.is_zero());
+                if !is_zero && rng.gen_bool(0.25) {
+                    e = -e;
+                }
+
+                let mut exp = workspace.new_atom();
+                exp.get_mut()
+                    .transform_to_num()
+                    .set_from_number(Number::Natural(e, 1));
+
+                let p = out.transform_to_pow();
+                p.set_from_base_and_exp(norm_base.get().to_view(), exp.get().to_view());
+                p.set_dirty(true);
+            }
+            4 => {
+                let m = out.transform_to_mul();
+                for _ in 0..nargs {
+                    let mut arg = workspace.new_atom();
+                    self.generate_unnormalized(rng, depth - 1, workspace, state, arg.get_mut());
+                    m.extend(arg.get().to_view());
+                }
+                m.set_dirty(true);
+            }
+            5 => {
+                let a = out.transform_to_add();
+                for _ in 0..nargs {
+                    let mut arg = workspace.new_atom();
+                    self.generate_unnormalized(rng, depth - 1, workspace, state, arg.get_mut());
+                    a.extend(arg.get().to_view());
+                }
+                a.set_dirty(true);
+            }
+            _ => {
+                let n = rng.gen_range(self.coeff_range.0..self.coeff_range.1);
+                out.transform_to_num()
+                    .set_from_number(Number::Natural(n, 1));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{poly::polynomial::MultivariatePolynomial, rings::integer::IntegerRing};
+
+    #[test]
+    fn test_random_all_monomials() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        // there are 35 monomials in 3 variables of total degree at most 4
+        for (nterms, expected) in [(10, 10), (30, 30), (35, 35), (100, 35)] {
+            let p = MultivariatePolynomial::<_, u8>::random(
+                IntegerRing::new(),
+                3,
+                nterms,
+                4,
+                (-10, 10),
+                &mut rng,
+            );
+            assert_eq!(p.nterms, expected);
+            assert!(p.coefficients.iter().all(|c| !c.is_zero()));
+            assert!(p.into_iter().all(|t| t.exponents.iter().sum::<u8>() <= 4));
+        }
+    }
+}
@@ -206,6 +206,69 @@ pub trait ListSlice<'a>: Clone {
     fn get(&self, index: usize) -> AtomView<'a, Self::P>;
     fn get_subslice(&self, range: Range<usize>) -> Self;
     fn eq(&self, other: &Self) -> bool;
+
+    /// Iterate over all contiguous subslices of length `size`, in order.
+    fn windows(&self, size: usize) -> ListSliceWindows<Self> {
+        assert!(size > 0, "Window size must be positive");
+        ListSliceWindows {
+            slice: self.clone(),
+            size,
+            start: 0,
+        }
+    }
+
+    /// Iterate over all ways to split the slice into a prefix and a suffix.
+    fn splits(&self) -> ListSliceSplits<Self> {
+        ListSliceSplits {
+            slice: self.clone(),
+            index: 0,
+        }
+    }
+}
+
+/// An iterator over contiguous subslices of a [`ListSlice`].
+pub struct ListSliceWindows<S> {
+    slice: S,
+    size: usize,
+    start: usize,
+}
+
+impl<'a, S: ListSlice<'a>> Iterator for ListSliceWindows<S> {
+    type Item = S;
+
+    fn next(&mut self) -> Option<S> {
+        if self.start + self.size > self.slice.len() {
+            return None;
+        }
+
+        let window = self.slice.get_subslice(self.start..self.start + self.size);
+        self.start += 1;
+        Some(window)
+    }
+}
+
+/// An iterator over the splits of a [`ListSlice`] into a prefix and a suffix.
+pub struct ListSliceSplits<S> {
+    slice: S,
+    index: usize,
+}
+
+impl<'a, S: ListSlice<'a>> Iterator for ListSliceSplits<S> {
+    type Item = (S, S);
+
+    fn next(&mut self) -> Option<(S, S)> {
+        if self.index > self.slice.len() {
+            return None;
+        }
+
+        let len = self.slice.len();
+        let split = (
+            self.slice.get_subslice(0..self.index),
+            self.slice.get_subslice(self.index..len),
+        );
+        self.index += 1;
+        Some(split)
+    }
 }
 
 pub enum AtomView<'a, P: Atom> {
@@ -246,6 +309,36 @@ impl<'a, 'b, P: Atom> PartialEq<AtomView<'b, P>> for AtomView<'a, P> {
     }
 }
 
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Get the number of children: the arguments of a function, the factors of a product,
+    /// the terms of a sum, and the base and exponent of a power.
+    pub fn children_count(&self) -> usize {
+        match self {
+            Self::Num(_) | Self::Var(_) => 0,
+            Self::Fun(f) => f.get_nargs(),
+            Self::Pow(_) => 2,
+            Self::Mul(m) => m.get_nargs(),
+            Self::Add(a) => a.get_nargs(),
+        }
+    }
+
+    /// Get child `index`, without iterating over the children before it.
+    /// The base and exponent of a power have index 0 and 1.
+    pub fn nth_child(&self, index: usize) -> Option<AtomView<'a, P>> {
+        if index >= self.children_count() {
+            return None;
+        }
+
+        match self {
+            Self::Num(_) | Self::Var(_) => None,
+            Self::Fun(f) => Some(f.to_slice().get(index)),
+            Self::Pow(p) => Some(p.to_slice().get(index)),
+            Self::Mul(m) => Some(m.to_slice().get(index)),
+            Self::Add(a) => Some(a.to_slice().get(index)),
+        }
+    }
+}
+
 #[derive(Copy, Clone, PartialEq)]
 pub enum OwnedAtom<P: Atom> {
     Num(P::ON),
@@ -449,4 +542,15 @@ impl<P: Atom> ResettableBuffer for OwnedAtom<P> {
             Self::Empty => {}
         }
     }
+    fn heap_size(&self) -> usize {
+        match self {
+            Self::Num(n) => n.heap_size(),
+            Self::Var(v) => v.heap_size(),
+            Self::Fun(f) => f.heap_size(),
+            Self::Pow(p) => p.heap_size(),
+            Self::Mul(m) => m.heap_size(),
+            Self::Add(a) => a.heap_size(),
+            Self::Empty => 0,
+        }
+    }
 }
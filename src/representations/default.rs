@@ -108,6 +108,10 @@ impl ResettableBuffer for OwnedNumD {
     fn reset(&mut self) {
         self.data.clear();
     }
+
+    fn heap_size(&self) -> usize {
+        self.data.capacity()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -174,6 +178,10 @@ impl ResettableBuffer for OwnedVarD {
     fn reset(&mut self) {
         self.data.clear();
     }
+
+    fn heap_size(&self) -> usize {
+        self.data.capacity()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -301,6 +309,10 @@ impl ResettableBuffer for OwnedFunD {
     fn reset(&mut self) {
         self.data.clear();
     }
+
+    fn heap_size(&self) -> usize {
+        self.data.capacity()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -376,6 +388,10 @@ impl ResettableBuffer for OwnedPowD {
     fn reset(&mut self) {
         self.data.clear();
     }
+
+    fn heap_size(&self) -> usize {
+        self.data.capacity()
+    }
 }
 
 #[derive(Clone)]
@@ -546,6 +562,10 @@ impl ResettableBuffer for OwnedMulD {
     fn reset(&mut self) {
         self.data.clear();
     }
+
+    fn heap_size(&self) -> usize {
+        self.data.capacity()
+    }
 }
 
 #[derive(Clone)]
@@ -680,6 +700,10 @@ impl ResettableBuffer for OwnedAddD {
         self.data.put_u32_le(0_u32);
         (0u64, 1).write_packed(&mut self.data);
     }
+
+    fn heap_size(&self) -> usize {
+        self.data.capacity()
+    }
 }
 
 impl Atom for DefaultRepresentation {
@@ -9,16 +9,18 @@ use rug::{
 
 use crate::{
     poly::polynomial::MultivariatePolynomial,
+    representations::Identifier,
     rings::{
+        algebraic_number::AlgebraicNumber,
         finite_field::{
             FiniteField, FiniteFieldCore, FiniteFieldElement, FiniteFieldWorkspace, ToFiniteField,
         },
         integer::{Integer, IntegerRing},
         rational::{Rational, RationalField},
         rational_polynomial::RationalPolynomial,
-        Field, Ring,
+        EuclideanDomain, Field, Ring,
     },
-    state::{FiniteFieldIndex, State},
+    state::{AlgebraicExtensionIndex, FiniteFieldIndex, State},
     utils,
 };
 
@@ -29,6 +31,7 @@ const U64_NUM: u8 = 0b00000100;
 const FIN_NUM: u8 = 0b00000101;
 const ARB_NUM: u8 = 0b00000111;
 const RAT_POLY: u8 = 0b00001000;
+const ALG_NUM: u8 = 0b00001001;
 const U8_DEN: u8 = 0b00010000;
 const U16_DEN: u8 = 0b00100000;
 const U32_DEN: u8 = 0b00110000;
@@ -50,6 +53,16 @@ fn get_size_of_natural(num_type: u8) -> u8 {
     }
 }
 
+/// Get the number of bytes of a packed fraction of naturals with tag `disc`,
+/// or `None` if the tag does not belong to such a fraction.
+fn get_packed_frac_size(disc: u8) -> Option<usize> {
+    let (num_type, den_type) = (disc & NUM_MASK, (disc & DEN_MASK) >> 4);
+    if num_type == 0 || num_type > U64_NUM || den_type > U64_NUM {
+        return None;
+    }
+    Some(1 + get_size_of_natural(num_type) as usize + get_size_of_natural(den_type) as usize)
+}
+
 pub trait ConvertToRing: Ring {
     /// Convert from a Symbolica `Number` to a Ring.
     fn element_from_number(&self, number: Number) -> Self::Element;
@@ -65,15 +78,47 @@ pub enum Number {
     Large(ArbitraryPrecisionRational),
     FiniteField(FiniteFieldElement<u64>, FiniteFieldIndex),
     RationalPolynomial(RationalPolynomial<IntegerRing, u16>),
+    AlgebraicNumber(AlgebraicNumber<RationalField>, AlgebraicExtensionIndex),
+}
+
+impl From<Rational> for Number {
+    fn from(r: Rational) -> Number {
+        match r {
+            Rational::Natural(n, d) => Number::Natural(n, d),
+            Rational::Large(r) => Number::from_large(r),
+        }
+    }
 }
 
 impl Number {
+    /// Create a number from an arbitrary precision rational, using
+    /// a natural fraction if the numerator and denominator fit in an `i64`.
+    pub fn from_large(r: ArbitraryPrecisionRational) -> Number {
+        match (r.numer().to_i64(), r.denom().to_i64()) {
+            (Some(n), Some(d)) => Number::Natural(n, d),
+            _ => Number::Large(r),
+        }
+    }
+
     pub fn is_zero(&self) -> bool {
         match self {
             Self::Natural(num, _) => *num == 0,
             Self::Large(_) => false,
             Self::FiniteField(num, _) => num.0 == 0,
             Self::RationalPolynomial(r) => r.numerator.is_zero(),
+            Self::AlgebraicNumber(a, _) => a.poly.is_zero(),
+        }
+    }
+
+    /// Create a number from an element of the algebraic extension with index `i`,
+    /// using a rational number if the element is constant.
+    pub fn from_algebraic(a: AlgebraicNumber<RationalField>, i: AlgebraicExtensionIndex) -> Number {
+        if a.poly.is_zero() {
+            Number::Natural(0, 1)
+        } else if a.poly.is_constant() {
+            Number::from(a.poly.lcoeff())
+        } else {
+            Number::AlgebraicNumber(a, i)
         }
     }
 }
@@ -97,12 +142,116 @@ impl<'a> SerializedRational<'a> {
     }
 }
 
+/// An algebraic number, serialized as its dense list of rational coefficients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializedAlgebraicNumber<'a> {
+    ncoeffs: usize,
+    data: &'a [u8],
+}
+
+impl<'a> SerializedAlgebraicNumber<'a> {
+    pub fn to_algebraic_number(&self) -> AlgebraicNumber<RationalField> {
+        let mut poly = MultivariatePolynomial::new(1, RationalField::new(), None, None);
+        let mut source = self.data;
+        for k in 0..self.ncoeffs {
+            let n;
+            (n, source) = source.get_number_view();
+            let c = n
+                .to_rational()
+                .expect("Coefficient of an algebraic number must be rational");
+            if !RationalField::is_zero(&c) {
+                poly.append_monomial(c, &[k as u16]);
+            }
+        }
+        AlgebraicNumber { poly }
+    }
+}
+
+/// A rational polynomial, serialized as its variable map followed by the terms of the
+/// numerator and the denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializedRationalPolynomial<'a> {
+    nvars: usize,
+    data: &'a [u8],
+}
+
+impl<'a> SerializedRationalPolynomial<'a> {
+    pub fn to_rational_polynomial(&self) -> RationalPolynomial<IntegerRing, u16> {
+        let mut source = self.data;
+        let var_map: Option<Vec<Identifier>> = if source.get_u8() == 1 {
+            Some(
+                (0..self.nvars)
+                    .map(|_| Identifier::from(source.get_u32_le()))
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        let mut read_poly = || {
+            let nterms = source.get_u64_le() as usize;
+            let mut poly = MultivariatePolynomial::new(
+                self.nvars,
+                IntegerRing::new(),
+                Some(nterms),
+                var_map.as_deref(),
+            );
+            for _ in 0..nterms {
+                let n;
+                (n, source) = source.get_number_view();
+                poly.coefficients
+                    .push(IntegerRing::new().element_from_borrowed_number(n));
+                for _ in 0..self.nvars {
+                    poly.exponents.push(source.get_u16_le());
+                }
+            }
+            poly.nterms = nterms;
+            poly
+        };
+
+        let numerator = read_poly();
+        let denominator = read_poly();
+        RationalPolynomial {
+            numerator,
+            denominator,
+        }
+    }
+}
+
+/// Write the variable map and the terms of the numerator and denominator of `p`.
+fn serialize_rational_polynomial(p: &RationalPolynomial<IntegerRing, u16>, dest: &mut Vec<u8>) {
+    if let Some(var_map) = &p.numerator.var_map {
+        dest.put_u8(1);
+        for v in var_map {
+            dest.put_u32_le(v.to_u32());
+        }
+    } else {
+        dest.put_u8(0);
+    }
+
+    for poly in [&p.numerator, &p.denominator] {
+        dest.put_u64_le(poly.nterms as u64);
+        for t in 0..poly.nterms {
+            match &poly.coefficients[t] {
+                Integer::Natural(n) => Number::Natural(*n, 1).write_packed(dest),
+                Integer::Large(l) => {
+                    Number::Large(ArbitraryPrecisionRational::from(l.clone())).write_packed(dest)
+                }
+            }
+            for e in poly.exponents(t) {
+                dest.put_u16_le(*e);
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BorrowedNumber<'a> {
     Natural(i64, i64),
     Large(SerializedRational<'a>),
     FiniteField(FiniteFieldElement<u64>, FiniteFieldIndex),
-    RationalPolynomial(&'a RationalPolynomial<IntegerRing, u16>),
+    RationalPolynomial(SerializedRationalPolynomial<'a>),
+    AlgebraicNumber(SerializedAlgebraicNumber<'a>, AlgebraicExtensionIndex),
 }
 
 impl ConvertToRing for RationalField {
@@ -115,6 +264,9 @@ impl ConvertToRing for RationalField {
             Number::RationalPolynomial(_) => {
                 panic!("Cannot convert rational polynomial to rational")
             }
+            Number::AlgebraicNumber(_, _) => {
+                panic!("Cannot convert algebraic number to rational")
+            }
         }
     }
 
@@ -127,6 +279,9 @@ impl ConvertToRing for RationalField {
             BorrowedNumber::RationalPolynomial(_) => {
                 panic!("Cannot convert rational polynomial to rational")
             }
+            BorrowedNumber::AlgebraicNumber(_, _) => {
+                panic!("Cannot convert algebraic number to rational")
+            }
         }
     }
 }
@@ -148,6 +303,9 @@ impl ConvertToRing for IntegerRing {
             Number::RationalPolynomial(_) => {
                 panic!("Cannot convert rational polynomial to rational")
             }
+            Number::AlgebraicNumber(_, _) => {
+                panic!("Cannot convert algebraic number to rational")
+            }
         }
     }
 
@@ -167,6 +325,9 @@ impl ConvertToRing for IntegerRing {
             BorrowedNumber::RationalPolynomial(_) => {
                 panic!("Cannot convert rational polynomial to integer")
             }
+            BorrowedNumber::AlgebraicNumber(_, _) => {
+                panic!("Cannot convert algebraic number to integer")
+            }
         }
     }
 }
@@ -194,6 +355,9 @@ where
             Number::RationalPolynomial(_) => {
                 panic!("Cannot convert rational polynomial to finite field")
             }
+            Number::AlgebraicNumber(_, _) => {
+                panic!("Cannot convert algebraic number to finite field")
+            }
         }
     }
 
@@ -215,6 +379,9 @@ where
             BorrowedNumber::RationalPolynomial(_) => {
                 panic!("Cannot convert rational polynomial to finite field")
             }
+            BorrowedNumber::AlgebraicNumber(_, _) => {
+                panic!("Cannot convert algebraic number to finite field")
+            }
         }
     }
 }
@@ -226,9 +393,10 @@ impl BorrowedNumber<'_> {
                 let gcd = utils::gcd_signed(*num, *den);
                 Number::Natural(*num / gcd, *den / gcd)
             }
-            Self::Large(_) | Self::FiniteField(_, _) | Self::RationalPolynomial(_) => {
-                self.to_owned()
-            }
+            Self::Large(_)
+            | Self::FiniteField(_, _)
+            | Self::RationalPolynomial(_)
+            | Self::AlgebraicNumber(_, _) => self.to_owned(),
         }
     }
 
@@ -237,7 +405,8 @@ impl BorrowedNumber<'_> {
             Self::Natural(num, den) => Number::Natural(*num, *den),
             Self::Large(r) => Number::Large(r.to_rat()),
             Self::FiniteField(num, field) => Number::FiniteField(*num, *field),
-            Self::RationalPolynomial(p) => Number::RationalPolynomial((*p).clone()),
+            Self::RationalPolynomial(p) => Number::RationalPolynomial(p.to_rational_polynomial()),
+            Self::AlgebraicNumber(a, i) => Number::AlgebraicNumber(a.to_algebraic_number(), *i),
         }
     }
 
@@ -272,9 +441,20 @@ impl BorrowedNumber<'_> {
             (Self::FiniteField(_, _), _) | (_, BorrowedNumber::FiniteField(_, _)) => {
                 panic!("Cannot add finite field to non-finite number. Convert other number first?");
             }
+            (Self::AlgebraicNumber(_, i), _) | (_, BorrowedNumber::AlgebraicNumber(_, i)) => {
+                let e = state.get_algebraic_extension(*i);
+                Number::from_algebraic(
+                    e.add(
+                        &self.to_algebraic_number(*i, state),
+                        &other.to_algebraic_number(*i, state),
+                    ),
+                    *i,
+                )
+            }
             (Self::Natural(n, d), BorrowedNumber::RationalPolynomial(p))
             | (Self::RationalPolynomial(p), BorrowedNumber::Natural(n, d)) => {
-                let r = (*p).clone();
+                let r = p.to_rational_polynomial();
+                let p = &r;
                 let r2 = RationalPolynomial {
                     numerator: MultivariatePolynomial::new_from_constant(
                         &p.numerator,
@@ -289,7 +469,8 @@ impl BorrowedNumber<'_> {
             }
             (BorrowedNumber::Large(l), BorrowedNumber::RationalPolynomial(p))
             | (Self::RationalPolynomial(p), BorrowedNumber::Large(l)) => {
-                let r = (*p).clone();
+                let r = p.to_rational_polynomial();
+                let p = &r;
                 let (n, d) = l.to_rat().into_numer_denom();
                 let r2 = RationalPolynomial {
                     numerator: MultivariatePolynomial::new_from_constant(
@@ -304,14 +485,12 @@ impl BorrowedNumber<'_> {
                 Number::RationalPolynomial(&r + &r2)
             }
             (Self::RationalPolynomial(p1), BorrowedNumber::RationalPolynomial(p2)) => {
+                let mut p1 = p1.to_rational_polynomial();
+                let mut p2 = p2.to_rational_polynomial();
                 if p1.get_var_map() != p2.get_var_map() {
-                    let mut p1 = (*p1).clone();
-                    let mut p2 = (*p2).clone();
                     p1.unify_var_map(&mut p2);
-                    Number::RationalPolynomial(&p1 + &p2)
-                } else {
-                    Number::RationalPolynomial(*p1 + *p2)
                 }
+                Number::RationalPolynomial(&p1 + &p2)
             }
         }
     }
@@ -332,7 +511,7 @@ impl BorrowedNumber<'_> {
                 Number::Large(r1 * r2.to_rat())
             }
             (Self::Large(r1), BorrowedNumber::Large(r2)) => {
-                Number::Large(r1.to_rat() + r2.to_rat())
+                Number::from_large(r1.to_rat() * r2.to_rat())
             }
             (Self::FiniteField(n1, i1), BorrowedNumber::FiniteField(n2, i2)) => {
                 assert!(
@@ -347,36 +526,234 @@ impl BorrowedNumber<'_> {
             (Self::FiniteField(_, _), _) | (_, BorrowedNumber::FiniteField(_, _)) => {
                 panic!("Cannot multiply finite field to non-finite number. Convert other number first?");
             }
+            (Self::AlgebraicNumber(_, i), _) | (_, BorrowedNumber::AlgebraicNumber(_, i)) => {
+                let e = state.get_algebraic_extension(*i);
+                Number::from_algebraic(
+                    e.mul(
+                        &self.to_algebraic_number(*i, state),
+                        &other.to_algebraic_number(*i, state),
+                    ),
+                    *i,
+                )
+            }
             (Self::Natural(n, d), BorrowedNumber::RationalPolynomial(p))
             | (Self::RationalPolynomial(p), BorrowedNumber::Natural(n, d)) => {
-                let mut r = (*p).clone();
+                let mut r = p.to_rational_polynomial();
                 r.numerator = r.numerator.mul_coeff(Integer::Natural(*n));
                 r.denominator = r.denominator.mul_coeff(Integer::Natural(*d));
                 Number::RationalPolynomial(r)
             }
             (BorrowedNumber::Large(l), BorrowedNumber::RationalPolynomial(p))
             | (Self::RationalPolynomial(p), BorrowedNumber::Large(l)) => {
-                let mut r = (*p).clone();
+                let mut r = p.to_rational_polynomial();
                 let (n, d) = l.to_rat().into_numer_denom();
                 r.numerator = r.numerator.mul_coeff(Integer::Large(n));
                 r.denominator = r.denominator.mul_coeff(Integer::Large(d));
                 Number::RationalPolynomial(r)
             }
             (Self::RationalPolynomial(p1), BorrowedNumber::RationalPolynomial(p2)) => {
+                let mut p1 = p1.to_rational_polynomial();
+                let mut p2 = p2.to_rational_polynomial();
                 if p1.get_var_map() != p2.get_var_map() {
-                    let mut p1 = (*p1).clone();
-                    let mut p2 = (*p2).clone();
                     p1.unify_var_map(&mut p2);
-                    Number::RationalPolynomial(&p1 * &p2)
-                } else {
-                    Number::RationalPolynomial(*p1 * *p2)
                 }
+                Number::RationalPolynomial(&p1 * &p2)
+            }
+        }
+    }
+
+    /// Convert the number to a rational number, if it is one.
+    fn to_rational(self) -> Option<Rational> {
+        match self {
+            Self::Natural(n, d) => Some(Rational::Natural(n, d)),
+            Self::Large(r) => Some(Rational::Large(r.to_rat())),
+            Self::FiniteField(_, _) | Self::RationalPolynomial(_) | Self::AlgebraicNumber(_, _) => {
+                None
+            }
+        }
+    }
+
+    /// Convert a rational number or an algebraic number to an element of
+    /// the algebraic extension with index `i`.
+    fn to_algebraic_number(
+        self,
+        i: AlgebraicExtensionIndex,
+        state: &State,
+    ) -> AlgebraicNumber<RationalField> {
+        match self {
+            Self::AlgebraicNumber(a, i2) => {
+                assert!(
+                    i == i2,
+                    "Cannot combine numbers from different algebraic extensions: {}, {}",
+                    state.get_algebraic_extension(i).poly(),
+                    state.get_algebraic_extension(i2).poly()
+                );
+                a.to_algebraic_number()
+            }
+            _ => {
+                state
+                    .get_algebraic_extension(i)
+                    .from_coefficient(self.to_rational().expect(
+                        "Cannot convert finite field or rational polynomial to algebraic number",
+                    ))
+            }
+        }
+    }
+
+    /// Convert a rational number to a constant rational polynomial with the same
+    /// variables as `p`.
+    fn to_rational_polynomial(
+        self,
+        p: &RationalPolynomial<IntegerRing, u16>,
+    ) -> RationalPolynomial<IntegerRing, u16> {
+        let r = self
+            .to_rational()
+            .expect("Cannot convert finite field to rational polynomial");
+        RationalPolynomial {
+            numerator: MultivariatePolynomial::new_from_constant(&p.numerator, r.numerator()),
+            denominator: MultivariatePolynomial::new_from_constant(&p.denominator, r.denominator()),
+        }
+    }
+
+    pub fn neg(&self, state: &State) -> Number {
+        match self {
+            Self::Natural(_, _) | Self::Large(_) => Number::from(-self.to_rational().unwrap()),
+            Self::FiniteField(n, i) => Number::FiniteField(state.get_finite_field(*i).neg(n), *i),
+            Self::RationalPolynomial(p) => Number::RationalPolynomial(-p.to_rational_polynomial()),
+            Self::AlgebraicNumber(a, i) => Number::AlgebraicNumber(
+                state
+                    .get_algebraic_extension(*i)
+                    .neg(&a.to_algebraic_number()),
+                *i,
+            ),
+        }
+    }
+
+    pub fn inv(&self, state: &State) -> Number {
+        match self {
+            Self::Natural(_, _) | Self::Large(_) => {
+                let r = self.to_rational().unwrap();
+                assert!(!RationalField::is_zero(&r), "Cannot invert 0");
+                Number::from(RationalField::new().inv(&r))
+            }
+            Self::FiniteField(n, i) => Number::FiniteField(state.get_finite_field(*i).inv(n), *i),
+            Self::RationalPolynomial(p) => {
+                Number::RationalPolynomial(p.to_rational_polynomial().inv())
             }
+            Self::AlgebraicNumber(a, i) => Number::AlgebraicNumber(
+                state
+                    .get_algebraic_extension(*i)
+                    .inv(&a.to_algebraic_number()),
+                *i,
+            ),
         }
     }
 
-    pub fn pow(&self, other: &BorrowedNumber<'_>, _: &State) -> (Number, Number) {
-        // TODO: normalize 4^1/3 to 2^(2/3)?
+    pub fn div(&self, other: &BorrowedNumber<'_>, state: &State) -> Number {
+        match (self, other) {
+            (Self::FiniteField(n1, i1), BorrowedNumber::FiniteField(n2, i2)) => {
+                assert!(
+                    i1 == i2,
+                    "Cannot divide numbers from different finite fields: p1={}, p2={}",
+                    state.get_finite_field(*i1).get_prime(),
+                    state.get_finite_field(*i2).get_prime()
+                );
+                let f = state.get_finite_field(*i1);
+                Number::FiniteField(f.div(n1, n2), *i1)
+            }
+            (Self::FiniteField(_, _), _) | (_, BorrowedNumber::FiniteField(_, _)) => {
+                panic!(
+                    "Cannot divide finite field by non-finite number. Convert other number first?"
+                );
+            }
+            (Self::AlgebraicNumber(_, i), _) | (_, BorrowedNumber::AlgebraicNumber(_, i)) => {
+                let e = state.get_algebraic_extension(*i);
+                Number::from_algebraic(
+                    e.div(
+                        &self.to_algebraic_number(*i, state),
+                        &other.to_algebraic_number(*i, state),
+                    ),
+                    *i,
+                )
+            }
+            (Self::RationalPolynomial(p1), BorrowedNumber::RationalPolynomial(p2)) => {
+                let mut p1 = p1.to_rational_polynomial();
+                let mut p2 = p2.to_rational_polynomial();
+                if p1.get_var_map() != p2.get_var_map() {
+                    p1.unify_var_map(&mut p2);
+                }
+                Number::RationalPolynomial(&p1 * &p2.inv())
+            }
+            (Self::RationalPolynomial(p), n) => {
+                let p = p.to_rational_polynomial();
+                Number::RationalPolynomial(&p * &n.to_rational_polynomial(&p).inv())
+            }
+            (n, BorrowedNumber::RationalPolynomial(p)) => {
+                let p = p.to_rational_polynomial();
+                Number::RationalPolynomial(&n.to_rational_polynomial(&p) * &p.inv())
+            }
+            _ => {
+                let r2 = other.to_rational().unwrap();
+                assert!(!RationalField::is_zero(&r2), "Cannot divide by 0");
+                Number::from(RationalField::new().div(&self.to_rational().unwrap(), &r2))
+            }
+        }
+    }
+
+    /// Compute the greatest common divisor of two numbers. For rational numbers, this is
+    /// the gcd of the numerators divided by the lcm of the denominators.
+    pub fn gcd(&self, other: &BorrowedNumber<'_>, state: &State) -> Number {
+        match (self, other) {
+            (Self::FiniteField(n1, i1), BorrowedNumber::FiniteField(n2, i2)) => {
+                assert!(
+                    i1 == i2,
+                    "Cannot compute the gcd of numbers from different finite fields: p1={}, p2={}",
+                    state.get_finite_field(*i1).get_prime(),
+                    state.get_finite_field(*i2).get_prime()
+                );
+                let f = state.get_finite_field(*i1);
+                Number::FiniteField(f.gcd(n1, n2), *i1)
+            }
+            (Self::FiniteField(_, _), _) | (_, BorrowedNumber::FiniteField(_, _)) => {
+                panic!("Cannot compute the gcd of a finite field and a non-finite number. Convert other number first?");
+            }
+            (Self::AlgebraicNumber(_, i), _) | (_, BorrowedNumber::AlgebraicNumber(_, i)) => {
+                let e = state.get_algebraic_extension(*i);
+                Number::from_algebraic(
+                    e.gcd(
+                        &self.to_algebraic_number(*i, state),
+                        &other.to_algebraic_number(*i, state),
+                    ),
+                    *i,
+                )
+            }
+            (Self::RationalPolynomial(p1), BorrowedNumber::RationalPolynomial(p2)) => {
+                let mut p1 = p1.to_rational_polynomial();
+                let mut p2 = p2.to_rational_polynomial();
+                if p1.get_var_map() != p2.get_var_map() {
+                    p1.unify_var_map(&mut p2);
+                }
+                Number::RationalPolynomial(p1.gcd(&p2))
+            }
+            (Self::RationalPolynomial(p), _) => {
+                let p = p.to_rational_polynomial();
+                Number::RationalPolynomial(p.gcd(&other.to_rational_polynomial(&p)))
+            }
+            (_, BorrowedNumber::RationalPolynomial(p)) => {
+                let p = p.to_rational_polynomial();
+                Number::RationalPolynomial(self.to_rational_polynomial(&p).gcd(&p))
+            }
+            _ => Number::from(
+                RationalField::new()
+                    .gcd(&self.to_rational().unwrap(), &other.to_rational().unwrap()),
+            ),
+        }
+    }
+
+    /// Raise the number to the power `other`. The result is returned as a base and an exponent,
+    /// where the exponent is one if the power could be evaluated. Rational powers of positive
+    /// rational numbers are evaluated when the root is exact.
+    pub fn pow(&self, other: &BorrowedNumber<'_>, state: &State) -> (Number, Number) {
         match (self, other) {
             (&Self::Natural(mut n1, mut d1), &BorrowedNumber::Natural(mut n2, d2)) => {
                 if n2 < 0 {
@@ -385,9 +762,15 @@ impl BorrowedNumber<'_> {
                 }
 
                 assert!(n2 <= u32::MAX as i64, "Power is too large: {}", n2);
+
+                if d2 != 1 && n1 > 0 && d1 > 0 {
+                    if let Some(r) = exact_root(&ArbitraryPrecisionRational::from((n1, d1)), d2) {
+                        return (Number::from_large(r.pow(n2 as u32)), Number::Natural(1, 1));
+                    }
+                }
+
                 if let Some(pn) = n1.checked_pow(n2 as u32) {
                     if let Some(pd) = d1.checked_pow(n2 as u32) {
-                        // TODO: simplify 4^(1/2)
                         return (Number::Natural(pn, pd), Number::Natural(1, d2));
                     }
                 }
@@ -397,6 +780,52 @@ impl BorrowedNumber<'_> {
                     Number::Natural(1, d2),
                 )
             }
+            (Self::Large(r), &BorrowedNumber::Natural(n2, d2)) => {
+                let mut r = r.to_rat();
+                if n2 < 0 {
+                    r.recip_mut();
+                }
+
+                assert!(
+                    n2.unsigned_abs() <= u32::MAX as u64,
+                    "Power is too large: {}",
+                    n2
+                );
+
+                if d2 != 1 && r > 0 {
+                    if let Some(root) = exact_root(&r, d2) {
+                        return (
+                            Number::from_large(root.pow(n2.unsigned_abs() as u32)),
+                            Number::Natural(1, 1),
+                        );
+                    }
+                }
+
+                (
+                    Number::Large(r.pow(n2.unsigned_abs() as u32)),
+                    Number::Natural(1, d2),
+                )
+            }
+            (&Self::FiniteField(n, i), &BorrowedNumber::Natural(n2, 1)) => {
+                let f = state.get_finite_field(i);
+                let r = f.pow(&n, n2.unsigned_abs());
+                if n2 < 0 {
+                    (Number::FiniteField(f.inv(&r), i), Number::Natural(1, 1))
+                } else {
+                    (Number::FiniteField(r, i), Number::Natural(1, 1))
+                }
+            }
+            (&Self::AlgebraicNumber(a, i), &BorrowedNumber::Natural(n2, d2)) => {
+                let e = state.get_algebraic_extension(i);
+                let mut a = a.to_algebraic_number();
+                if n2 < 0 {
+                    a = e.inv(&a);
+                }
+                (
+                    Number::from_algebraic(e.pow(&a, n2.unsigned_abs()), i),
+                    Number::Natural(1, d2),
+                )
+            }
             (&Self::RationalPolynomial(r), &BorrowedNumber::Natural(n2, d2)) => {
                 assert!(
                     n2.unsigned_abs() <= u32::MAX as u64,
@@ -404,8 +833,9 @@ impl BorrowedNumber<'_> {
                     n2
                 );
 
+                let r = r.to_rational_polynomial();
                 if n2 < 0 {
-                    let r = r.clone().inv();
+                    let r = r.inv();
                     (
                         Number::RationalPolynomial(r.pow(n2.unsigned_abs() as u64)),
                         Number::Natural(1, d2),
@@ -426,8 +856,16 @@ impl BorrowedNumber<'_> {
             }
         }
     }
+}
+
+impl PartialOrd for BorrowedNumber<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-    pub fn cmp(&self, other: &BorrowedNumber) -> Ordering {
+impl Ord for BorrowedNumber<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
             (&Self::Natural(n1, d1), &BorrowedNumber::Natural(n2, d2)) => {
                 // TODO: improve
@@ -455,18 +893,69 @@ impl BorrowedNumber<'_> {
                 }
             }
             (Self::Large(n1), BorrowedNumber::Large(n2)) => n1.to_rat().cmp(&n2.to_rat()),
-            (Self::FiniteField(n1, _), BorrowedNumber::FiniteField(n2, _)) => n1.0.cmp(&n2.0),
             (&Self::Natural(n1, d1), BorrowedNumber::Large(n2)) => {
                 ArbitraryPrecisionRational::from((n1, d1)).cmp(&n2.to_rat())
             }
             (Self::Large(n1), &BorrowedNumber::Natural(n2, d2)) => {
                 n1.to_rat().cmp(&ArbitraryPrecisionRational::from((n2, d2)))
             }
-            _ => unreachable!(),
+            (Self::FiniteField(n1, i1), BorrowedNumber::FiniteField(n2, i2)) => {
+                i1.0.cmp(&i2.0).then(n1.0.cmp(&n2.0))
+            }
+            (Self::RationalPolynomial(p1), BorrowedNumber::RationalPolynomial(p2)) => {
+                let (p1, p2) = (p1.to_rational_polynomial(), p2.to_rational_polynomial());
+                cmp_polynomials(&p1.numerator, &p2.numerator)
+                    .then_with(|| cmp_polynomials(&p1.denominator, &p2.denominator))
+            }
+            (Self::AlgebraicNumber(a1, i1), BorrowedNumber::AlgebraicNumber(a2, i2)) => {
+                i1.0.cmp(&i2.0)
+                    .then(a1.ncoeffs.cmp(&a2.ncoeffs))
+                    .then(a1.data.cmp(a2.data))
+            }
+            // rational numbers come first, followed by finite field numbers,
+            // algebraic numbers and rational polynomials
+            (Self::RationalPolynomial(_), _) => Ordering::Greater,
+            (_, BorrowedNumber::RationalPolynomial(_)) => Ordering::Less,
+            (Self::AlgebraicNumber(_, _), _) => Ordering::Greater,
+            (_, BorrowedNumber::AlgebraicNumber(_, _)) => Ordering::Less,
+            (Self::FiniteField(_, _), _) => Ordering::Greater,
+            (_, BorrowedNumber::FiniteField(_, _)) => Ordering::Less,
         }
     }
 }
 
+/// Compare two polynomials by their number of terms, exponents and coefficients.
+fn cmp_polynomials(
+    a: &MultivariatePolynomial<IntegerRing, u16>,
+    b: &MultivariatePolynomial<IntegerRing, u16>,
+) -> Ordering {
+    a.nterms
+        .cmp(&b.nterms)
+        .then_with(|| a.exponents.cmp(&b.exponents))
+        .then_with(|| a.coefficients.cmp(&b.coefficients))
+}
+
+/// Get the dense list of coefficients of an algebraic number, starting from the constant term.
+fn dense_coefficients(a: &AlgebraicNumber<RationalField>) -> Vec<Rational> {
+    let mut coeffs = vec![Rational::Natural(0, 1); a.poly.degree(0) as usize + 1];
+    for t in 0..a.poly.nterms {
+        coeffs[a.poly.exponents(t)[0] as usize] = a.poly.coefficients[t].clone();
+    }
+    coeffs
+}
+
+/// Compute the `n`-th root of the non-negative rational `r`, if it is exact.
+fn exact_root(r: &ArbitraryPrecisionRational, n: i64) -> Option<ArbitraryPrecisionRational> {
+    let n = u32::try_from(n).ok()?;
+    let num = r.numer().clone().root(n);
+    let den = r.denom().clone().root(n);
+    if num.clone().pow(n) == *r.numer() && den.clone().pow(n) == *r.denom() {
+        Some(ArbitraryPrecisionRational::from((num, den)))
+    } else {
+        None
+    }
+}
+
 impl PackedRationalNumberWriter for Number {
     fn write_packed(&self, dest: &mut Vec<u8>) {
         match self {
@@ -499,12 +988,18 @@ impl PackedRationalNumberWriter for Number {
             }
             Self::RationalPolynomial(p) => {
                 dest.put_u8(RAT_POLY);
-                // note that this is not a linear representation
-                // FIXME: pointer alignment
-                let v = std::mem::ManuallyDrop::new(p);
-                let lin_buf = unsafe { utils::any_as_u8_slice(&v) };
-
-                dest.extend(lin_buf);
+                let mut data = vec![];
+                serialize_rational_polynomial(p, &mut data);
+                (data.len() as u64, p.numerator.nvars as u64).write_packed(dest);
+                dest.extend(data);
+            }
+            Self::AlgebraicNumber(a, i) => {
+                dest.put_u8(ALG_NUM);
+                let coeffs = dense_coefficients(a);
+                (i.0 as u64, coeffs.len() as u64).write_packed(dest);
+                for c in coeffs {
+                    Number::from(c).write_packed(dest);
+                }
             }
         }
     }
@@ -512,7 +1007,7 @@ impl PackedRationalNumberWriter for Number {
     fn write_packed_fixed(&self, mut dest: &mut [u8]) {
         match self {
             Self::Natural(num, den) => (*num, *den).write_packed_fixed(dest),
-            Self::Large(_) | Number::RationalPolynomial(_) => {
+            Self::Large(_) | Number::RationalPolynomial(_) | Number::AlgebraicNumber(_, _) => {
                 todo!("Writing large packed rational not implemented")
             }
             Self::FiniteField(num, f) => {
@@ -531,8 +1026,19 @@ impl PackedRationalNumberWriter for Number {
                 1 + (n as i64, d as i64).get_packed_size() + n as u64 + d as u64
             }
             Self::FiniteField(m, i) => 2 + (m.0, i.0 as u64).get_packed_size(),
-            Self::RationalPolynomial(_) => {
-                1 + std::mem::size_of::<RationalPolynomial<IntegerRing, u16>>() as u64
+            Self::RationalPolynomial(p) => {
+                let mut data = vec![];
+                serialize_rational_polynomial(p, &mut data);
+                1 + (data.len() as u64, p.numerator.nvars as u64).get_packed_size()
+                    + data.len() as u64
+            }
+            Self::AlgebraicNumber(a, i) => {
+                let coeffs = dense_coefficients(a);
+                1 + (i.0 as u64, coeffs.len() as u64).get_packed_size()
+                    + coeffs
+                        .into_iter()
+                        .map(|c| Number::from(c).get_packed_size())
+                        .sum::<u64>()
             }
         }
     }
@@ -552,6 +1058,9 @@ pub trait PackedRationalNumberWriter {
 /// A reader for generalized rational numbers. See [`ArbitraryPrecisionRationalNumberWriter`].
 pub trait PackedRationalNumberReader {
     fn get_number_view(&self) -> (BorrowedNumber, &[u8]);
+    /// Read a rational number like [`Self::get_number_view`], but return `None` instead of
+    /// panicking if the data is truncated or does not encode a valid rational number.
+    fn get_rational_view_checked(&self) -> Option<(BorrowedNumber<'_>, &[u8])>;
     fn get_frac_u64(&self) -> (u64, u64, &[u8]);
     fn get_frac_i64(&self) -> (i64, i64, &[u8]);
     fn skip_rational(&self) -> &[u8];
@@ -565,10 +1074,32 @@ impl PackedRationalNumberReader for [u8] {
         let mut source = self;
         let disc = source.get_u8();
         if disc == RAT_POLY {
-            let rat = unsafe { std::mem::transmute(&source[0]) };
+            let (len, nvars);
+            (len, nvars, source) = source.get_frac_u64();
+            let (data, rest) = source.split_at(len as usize);
+            (
+                BorrowedNumber::RationalPolynomial(SerializedRationalPolynomial {
+                    nvars: nvars as usize,
+                    data,
+                }),
+                rest,
+            )
+        } else if disc == ALG_NUM {
+            let (index, ncoeffs);
+            (index, ncoeffs, source) = source.get_frac_u64();
+            let mut rest = source;
+            for _ in 0..ncoeffs {
+                rest = rest.skip_rational();
+            }
             (
-                BorrowedNumber::RationalPolynomial(rat),
-                &source[std::mem::size_of::<RationalPolynomial<IntegerRing, u16>>()..],
+                BorrowedNumber::AlgebraicNumber(
+                    SerializedAlgebraicNumber {
+                        ncoeffs: ncoeffs as usize,
+                        data: &source[..source.len() - rest.len()],
+                    },
+                    AlgebraicExtensionIndex(index as usize),
+                ),
+                rest,
             )
         } else if (disc & NUM_MASK) == ARB_NUM {
             let (num, den);
@@ -599,6 +1130,37 @@ impl PackedRationalNumberReader for [u8] {
         }
     }
 
+    fn get_rational_view_checked(&self) -> Option<(BorrowedNumber<'_>, &[u8])> {
+        let disc = *self.first()?;
+        if disc & NUM_MASK == ARB_NUM {
+            if disc & DEN_MASK != ARB_DEN {
+                return None;
+            }
+
+            let header = &self[1..];
+            if header.len() < get_packed_frac_size(*header.first()?)? {
+                return None;
+            }
+            let (num_len, den_len, digits) = header.get_frac_u64();
+            let num_len = usize::try_from(num_len).ok()?;
+            let den_len = usize::try_from(den_len).ok()?;
+            let den_end = num_len.checked_add(den_len)?;
+            if digits.len() < den_end || digits[num_len..den_end].iter().all(|d| *d == 0) {
+                return None;
+            }
+        } else {
+            if self.len() < get_packed_frac_size(disc)? {
+                return None;
+            }
+            let (num, den, _) = self.get_frac_u64();
+            if num > i64::MAX as u64 || den == 0 || den > i64::MAX as u64 {
+                return None;
+            }
+        }
+
+        Some(self.get_number_view())
+    }
+
     #[inline(always)]
     fn get_frac_u64(&self) -> (u64, u64, &[u8]) {
         let mut source = self;
@@ -737,7 +1299,16 @@ impl PackedRationalNumberReader for [u8] {
             dest.advance(num_size + den_size);
             dest
         } else if v_num == RAT_POLY {
-            dest.advance(std::mem::size_of::<RationalPolynomial<IntegerRing, u16>>());
+            let len;
+            (len, _, dest) = dest.get_frac_u64();
+            dest.advance(len as usize);
+            dest
+        } else if v_num == ALG_NUM {
+            let ncoeffs;
+            (_, ncoeffs, dest) = dest.get_frac_u64();
+            for _ in 0..ncoeffs {
+                dest = dest.skip_rational();
+            }
             dest
         } else if v_num == FIN_NUM {
             let var_size = dest.get_u8();
@@ -889,3 +1460,79 @@ impl PackedRationalNumberWriter for (u64, u64) {
         size as u64
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::parse,
+        representations::{
+            default::DefaultRepresentation, number::Number, AtomView, Num, OwnedAtom, OwnedNum,
+        },
+        rings::{integer::IntegerRing, rational::RationalField},
+        state::{ResettableBuffer, State, Workspace},
+    };
+
+    #[test]
+    fn test_rational_polynomial_roundtrip() {
+        let mut state = State::new();
+        let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+        let expr = parse("(x^2+123456789012345678901234567890*y)/(x-3*y^3)")
+            .unwrap()
+            .to_atom(&mut state, &workspace)
+            .unwrap();
+        let r = expr
+            .to_view()
+            .to_rational_polynomial(
+                &workspace,
+                &state,
+                RationalField::new(),
+                IntegerRing::new(),
+                None,
+            )
+            .unwrap();
+
+        let mut a = OwnedAtom::<DefaultRepresentation>::new();
+        a.transform_to_num()
+            .set_from_number(Number::RationalPolynomial(r.clone()));
+
+        let mut b = OwnedAtom::<DefaultRepresentation>::new();
+        b.from_view(&a.to_view());
+
+        if let AtomView::Num(n) = b.to_view() {
+            assert_eq!(
+                n.get_number_view().to_owned(),
+                Number::RationalPolynomial(r)
+            );
+        } else {
+            panic!("Expected a number");
+        }
+    }
+
+    #[test]
+    fn test_large_mul() {
+        let mut state = State::new();
+        let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+
+        for (input, expected) in [
+            (
+                "123456789012345678901234567890*98765432109876543210987654321",
+                "12193263113702179522618503273362292333223746380111126352690",
+            ),
+            (
+                "123456789012345678901234567890*-98765432109876543210987654321/3",
+                "-4064421037900726507539501091120764111074582126703708784230",
+            ),
+        ] {
+            let a = parse(input)
+                .unwrap()
+                .to_atom(&mut state, &workspace)
+                .unwrap();
+            let b = parse(expected)
+                .unwrap()
+                .to_atom(&mut state, &workspace)
+                .unwrap();
+            assert_eq!(a.to_view(), b.to_view());
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Residues of rational functions at poles of any order.
+
+use ahash::HashMap;
+
+use crate::{
+    derivative::depends_on,
+    poly::{polynomial::MultivariatePolynomial, FunctionAbstraction},
+    representations::{
+        number::Number, Atom, AtomView, Identifier, OwnedAdd, OwnedAtom, OwnedNum, OwnedVar,
+    },
+    rings::{
+        integer::{Integer, IntegerRing},
+        rational::RationalField,
+        rational_polynomial::{FromNumeratorAndDenominator, RationalPolynomial},
+    },
+    state::{State, Workspace},
+};
+
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Compute the residue of the expression at `x = point` and write it in `out`.
+    ///
+    /// The expression must be a rational function of `x`. Its coefficients and the point
+    /// may contain other variables and subexpressions such as `f(y)` or `2^(1/2)`, as long
+    /// as they do not depend on `x`. The residue at a pole of order `k` is the coefficient
+    /// of `(x - point)^(k-1)` in the power series of `(x - point)^k` times the expression,
+    /// and is zero if `point` is not a pole.
+    ///
+    /// Only rational functions of `x` are supported: an expression such as `sin(x)/x^2`
+    /// returns an error, since there are no power series of general functions.
+    pub fn residue(
+        &self,
+        x: Identifier,
+        point: AtomView<'_, P>,
+        workspace: &Workspace<P>,
+        state: &mut State,
+        out: &mut OwnedAtom<P>,
+    ) -> Result<(), String> {
+        if depends_on(point, x) {
+            return Err("The point cannot depend on the variable".to_owned());
+        }
+
+        // move the point to the origin with x -> x + point
+        let mut shift = workspace.new_atom();
+        let add = shift.get_mut().transform_to_add();
+        let mut x_atom = workspace.new_atom();
+        x_atom.get_mut().transform_to_var().set_from_id(x);
+        add.extend(x_atom.get().to_view());
+        add.extend(point);
+        add.set_dirty(true);
+        let mut shift_norm = workspace.new_atom();
+        shift
+            .get()
+            .to_view()
+            .normalize(workspace, state, shift_norm.get_mut());
+
+        let mut map = HashMap::default();
+        map.insert(x, shift_norm.get().to_view());
+        let mut shifted = workspace.new_atom();
+        self.replace_all_multiple(&map, workspace, state, shifted.get_mut());
+
+        // expand so that the constant terms at the point cancel
+        let mut expanded = workspace.new_atom();
+        shifted
+            .get()
+            .to_view()
+            .expand(workspace, state, expanded.get_mut());
+
+        let mut abstraction = FunctionAbstraction::new();
+        let r: RationalPolynomial<IntegerRing, u16> = expanded
+            .get()
+            .to_view()
+            .to_rational_polynomial_with_abstraction(
+                workspace,
+                state,
+                RationalField::new(),
+                IntegerRing::new(),
+                &mut abstraction,
+            )
+            .map_err(|e| e.into_owned())?;
+
+        if abstraction
+            .get_map()
+            .iter()
+            .any(|(_, a)| depends_on(a.to_view(), x))
+        {
+            return Err(format!(
+                "The expression is not a rational function of {}",
+                state.get_name(x).unwrap()
+            ));
+        }
+
+        let residue = r
+            .numerator
+            .var_map
+            .as_ref()
+            .and_then(|vars| vars.iter().position(|v| *v == x))
+            .and_then(|x| residue_at_origin(&r.numerator, &r.denominator, x));
+
+        match residue {
+            Some(residue) if !residue.numerator.is_zero() => {
+                out.from_rational_polynomial_with_abstraction(
+                    workspace,
+                    state,
+                    &residue,
+                    &abstraction,
+                );
+            }
+            _ => {
+                out.transform_to_num()
+                    .set_from_number(Number::Natural(0, 1));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compute the residue of `num / den` at `x = 0`, or return `None` if the
+/// residue is zero because `den` does not vanish at zero.
+fn residue_at_origin(
+    num: &MultivariatePolynomial<IntegerRing, u16>,
+    den: &MultivariatePolynomial<IntegerRing, u16>,
+    x: usize,
+) -> Option<RationalPolynomial<IntegerRing, u16>> {
+    // den = x^k * (d_0 + t) with a pole of order k, where d_0 does not depend on x
+    // and every term of t is divisible by x
+    let k = den.into_iter().map(|term| term.exponents[x]).min()?;
+    if k == 0 {
+        return None;
+    }
+
+    let mut d0 = den.new_from(None);
+    let mut t = den.new_from(None);
+    for term in den {
+        let mut e = term.exponents.to_vec();
+        e[x] -= k;
+        if e[x] == 0 {
+            d0.append_monomial(term.coefficient.clone(), &e);
+        } else {
+            t.append_monomial(term.coefficient.clone(), &e);
+        }
+    }
+
+    // 1/(d_0 + t) = sum_m (-t)^m / d_0^(m+1), so that d_0^k times the series up to
+    // x^(k-1) is s = sum_{m<k} (-t)^m d_0^(k-1-m), which is computed with the Horner scheme
+    let mut max_degrees = vec![u16::MAX; num.nvars];
+    max_degrees[x] = k - 1;
+    let neg_t = -t;
+    let mut s = d0.new_from_constant(Integer::one());
+    let mut d0_pow = s.clone();
+    for _ in 1..k {
+        d0_pow = &d0_pow * &d0;
+        s = &d0_pow + &neg_t.mul_truncated(&s, &max_degrees);
+    }
+
+    // the residue is the coefficient of x^(k-1) in num * s / d_0^k
+    let mut res = num.new_from(None);
+    for term in &num.mul_truncated(&s, &max_degrees) {
+        if term.exponents[x] == k - 1 {
+            let mut e = term.exponents.to_vec();
+            e[x] = 0;
+            res.append_monomial(term.coefficient.clone(), &e);
+        }
+    }
+
+    Some(RationalPolynomial::from_num_den(
+        res,
+        &d0_pow * &d0,
+        IntegerRing::new(),
+        true,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::parse,
+        representations::{default::DefaultRepresentation, OwnedAtom},
+        rings::{
+            integer::IntegerRing, rational::RationalField, rational_polynomial::RationalPolynomial,
+        },
+        state::{ResettableBuffer, State, Workspace},
+    };
+
+    #[test]
+    fn test_residue() {
+        let mut state = State::new();
+        let workspace: Workspace<DefaultRepresentation> = Workspace::new();
+        let x = state.get_or_insert_var("x");
+        let vars = ["a", "b", "c"].map(|v| state.get_or_insert_var(v));
+
+        for (input, point, expected) in [
+            ("1/(x^3*(1+a*x))", "0", "a^2"),
+            ("(x+3)/(x^3*(x-1)^2)", "0", "11"),
+            ("1/((x-b)^2*(2*x+c))", "b", "-2/(2*b+c)^2"),
+            ("x/(x^2+1)", "1", "0"),
+        ] {
+            let [expr, point, expected] = [input, point, expected]
+                .map(|e| parse(e).unwrap().to_atom(&mut state, &workspace).unwrap());
+
+            let mut out = OwnedAtom::new();
+            expr.to_view()
+                .residue(x, point.to_view(), &workspace, &mut state, &mut out)
+                .unwrap();
+
+            let to_rat = |a: &OwnedAtom<DefaultRepresentation>| -> RationalPolynomial<_, u16> {
+                a.to_view()
+                    .to_rational_polynomial(
+                        &workspace,
+                        &state,
+                        RationalField::new(),
+                        IntegerRing::new(),
+                        Some(&vars),
+                    )
+                    .unwrap()
+            };
+            assert_eq!(to_rat(&out), to_rat(&expected), "{}", input);
+        }
+    }
+}
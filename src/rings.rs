@@ -1,13 +1,20 @@
+pub mod algebraic_number;
 pub mod finite_field;
 pub mod integer;
 pub mod linear_system;
 pub mod rational;
 pub mod rational_polynomial;
+pub mod rationalized_polynomial;
 
 use std::fmt::{Debug, Display, Error, Formatter};
 
-pub trait Ring: Clone + Copy + PartialEq + Debug + Display {
-    type Element: Clone + PartialEq + Debug;
+/// Rings and their elements are `Send + Sync`, so that the arithmetic of polynomials over
+/// any ring can use multiple threads: the multiplication operator of
+/// [`MultivariatePolynomial`](crate::poly::polynomial::MultivariatePolynomial)
+/// multiplies large polynomials in parallel, see
+/// [`mul_dispatch`](crate::poly::polynomial::MultivariatePolynomial::mul_dispatch).
+pub trait Ring: Clone + Copy + PartialEq + Debug + Display + Send + Sync {
+    type Element: Clone + PartialEq + Debug + Send + Sync;
 
     fn add(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
     fn sub(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
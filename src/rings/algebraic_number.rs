@@ -0,0 +1,313 @@
+use std::{
+    any::Any,
+    fmt::{Display, Error, Formatter},
+    sync::Mutex,
+};
+
+use crate::poly::polynomial::MultivariatePolynomial;
+
+use super::{EuclideanDomain, Field, Ring};
+
+/// An algebraic extension `R[a]/(p(a))` of the field `R`, where `p` is the
+/// irreducible minimal polynomial of the algebraic number `a`. For example,
+/// `p(a) = a^2 - 3` yields exact arithmetic with `sqrt(3)`.
+///
+/// The minimal polynomial is interned for the remainder of the program, so that the
+/// extension can be copied like any other ring. Creating an extension with a minimal
+/// polynomial that was seen before reuses the interned polynomial.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AlgebraicExtension<R: Field + 'static> {
+    poly: &'static MultivariatePolynomial<R, u16>,
+}
+
+/// An element of an [`AlgebraicExtension`], represented as a polynomial in the algebraic
+/// number with a degree that is smaller than the degree of the minimal polynomial.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AlgebraicNumber<R: Ring> {
+    pub poly: MultivariatePolynomial<R, u16>,
+}
+
+impl<R: Ring> Display for AlgebraicNumber<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.poly.fmt(f)
+    }
+}
+
+/// The minimal polynomials of all extensions, as `&'static MultivariatePolynomial<R, u16>`
+/// for any field `R`.
+static MINIMAL_POLYNOMIALS: Mutex<Vec<Box<dyn Any + Send>>> = Mutex::new(Vec::new());
+
+/// Get a reference to the interned copy of `poly`, which is stored if it is new.
+fn intern_minimal_polynomial<R: Field + 'static>(
+    poly: MultivariatePolynomial<R, u16>,
+) -> &'static MultivariatePolynomial<R, u16> {
+    let mut interned = MINIMAL_POLYNOMIALS.lock().unwrap();
+    for p in interned.iter() {
+        if let Some(p) = p.downcast_ref::<&'static MultivariatePolynomial<R, u16>>() {
+            if **p == poly {
+                return p;
+            }
+        }
+    }
+
+    let p: &'static _ = Box::leak(Box::new(poly));
+    interned.push(Box::new(p));
+    p
+}
+
+impl<R: Field + 'static> AlgebraicExtension<R> {
+    /// Create the extension defined by the univariate minimal polynomial `poly`,
+    /// which is made monic. The irreducibility of `poly` is not checked.
+    pub fn new(poly: MultivariatePolynomial<R, u16>) -> Self {
+        assert!(
+            poly.nvars == 1 && poly.degree(0) > 0,
+            "The minimal polynomial must be univariate and have a positive degree"
+        );
+
+        let inv = poly.field.inv(&poly.lcoeff());
+        let poly = poly.mul_coeff(inv);
+
+        AlgebraicExtension {
+            poly: intern_minimal_polynomial(poly),
+        }
+    }
+
+    /// Get the monic minimal polynomial.
+    pub fn poly(&self) -> &MultivariatePolynomial<R, u16> {
+        self.poly
+    }
+
+    /// Get the degree of the extension.
+    pub fn degree(&self) -> u16 {
+        self.poly.degree(0)
+    }
+
+    /// Get the algebraic number `a` that generates the extension.
+    pub fn generator(&self) -> AlgebraicNumber<R> {
+        self.to_element(self.poly.new_from_monomial(self.poly.field.one(), vec![1]))
+    }
+
+    /// Convert a polynomial in the algebraic number to an element of the extension,
+    /// by taking the remainder of the division by the minimal polynomial.
+    pub fn to_element(&self, poly: MultivariatePolynomial<R, u16>) -> AlgebraicNumber<R> {
+        if poly.is_constant() {
+            return AlgebraicNumber {
+                poly: self.poly.new_from_constant(if poly.is_zero() {
+                    self.poly.field.zero()
+                } else {
+                    poly.lcoeff()
+                }),
+            };
+        }
+
+        assert_eq!(
+            poly.nvars, 1,
+            "An algebraic number must be a univariate polynomial"
+        );
+
+        if poly.degree(0) < self.degree() {
+            return AlgebraicNumber { poly };
+        }
+
+        AlgebraicNumber {
+            poly: poly.quot_rem_univariate(&mut self.poly.clone()).1,
+        }
+    }
+
+    /// Convert an element of the coefficient field to an element of the extension.
+    pub fn from_coefficient(&self, c: R::Element) -> AlgebraicNumber<R> {
+        AlgebraicNumber {
+            poly: self.poly.new_from_constant(c),
+        }
+    }
+}
+
+impl<R: Field + 'static> Display for AlgebraicExtension<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}]", self.poly)
+    }
+}
+
+impl<R: Field + 'static> Ring for AlgebraicExtension<R> {
+    type Element = AlgebraicNumber<R>;
+
+    fn add(&self, a: &Self::Element, b: &Self::Element) -> Self::Element {
+        AlgebraicNumber {
+            poly: &a.poly + &b.poly,
+        }
+    }
+
+    fn sub(&self, a: &Self::Element, b: &Self::Element) -> Self::Element {
+        AlgebraicNumber {
+            poly: &a.poly - &b.poly,
+        }
+    }
+
+    fn mul(&self, a: &Self::Element, b: &Self::Element) -> Self::Element {
+        self.to_element(&a.poly * &b.poly)
+    }
+
+    fn add_assign(&self, a: &mut Self::Element, b: &Self::Element) {
+        *a = self.add(a, b);
+    }
+
+    fn sub_assign(&self, a: &mut Self::Element, b: &Self::Element) {
+        *a = self.sub(a, b);
+    }
+
+    fn mul_assign(&self, a: &mut Self::Element, b: &Self::Element) {
+        *a = self.mul(a, b);
+    }
+
+    fn add_mul_assign(&self, a: &mut Self::Element, b: &Self::Element, c: &Self::Element) {
+        self.add_assign(a, &self.mul(b, c));
+    }
+
+    fn sub_mul_assign(&self, a: &mut Self::Element, b: &Self::Element, c: &Self::Element) {
+        self.sub_assign(a, &self.mul(b, c));
+    }
+
+    fn neg(&self, a: &Self::Element) -> Self::Element {
+        AlgebraicNumber {
+            poly: -a.poly.clone(),
+        }
+    }
+
+    fn zero(&self) -> Self::Element {
+        AlgebraicNumber {
+            poly: self.poly.new_from(None),
+        }
+    }
+
+    fn one(&self) -> Self::Element {
+        self.from_coefficient(self.poly.field.one())
+    }
+
+    fn pow(&self, b: &Self::Element, mut e: u64) -> Self::Element {
+        let mut x = b.clone();
+        let mut res = self.one();
+        while e > 0 {
+            if e & 1 == 1 {
+                res = self.mul(&res, &x);
+            }
+            e >>= 1;
+            if e > 0 {
+                x = self.mul(&x, &x);
+            }
+        }
+        res
+    }
+
+    fn is_zero(a: &Self::Element) -> bool {
+        a.poly.is_zero()
+    }
+
+    fn is_one(&self, a: &Self::Element) -> bool {
+        a.poly.is_one()
+    }
+
+    fn get_unit(&self, a: &Self::Element) -> Self::Element {
+        a.clone()
+    }
+
+    fn get_inv_unit(&self, a: &Self::Element) -> Self::Element {
+        self.inv(a)
+    }
+
+    fn sample(&self, rng: &mut impl rand::RngCore, range: (i64, i64)) -> Self::Element {
+        let mut poly = self.poly.new_from(None);
+        for i in 0..self.degree() {
+            let c = self.poly.field.sample(rng, range);
+            if !R::is_zero(&c) {
+                poly.append_monomial(c, &[i]);
+            }
+        }
+        AlgebraicNumber { poly }
+    }
+
+    fn fmt_display(&self, element: &Self::Element, f: &mut Formatter<'_>) -> Result<(), Error> {
+        element.fmt(f)
+    }
+}
+
+impl<R: Field + 'static> EuclideanDomain for AlgebraicExtension<R> {
+    fn rem(&self, _: &Self::Element, _: &Self::Element) -> Self::Element {
+        self.zero()
+    }
+
+    fn quot_rem(&self, a: &Self::Element, b: &Self::Element) -> (Self::Element, Self::Element) {
+        (self.div(a, b), self.zero())
+    }
+
+    fn gcd(&self, a: &Self::Element, b: &Self::Element) -> Self::Element {
+        if Self::is_zero(a) && Self::is_zero(b) {
+            self.zero()
+        } else {
+            self.one()
+        }
+    }
+}
+
+impl<R: Field + 'static> Field for AlgebraicExtension<R> {
+    fn div(&self, a: &Self::Element, b: &Self::Element) -> Self::Element {
+        self.mul(a, &self.inv(b))
+    }
+
+    fn div_assign(&self, a: &mut Self::Element, b: &Self::Element) {
+        *a = self.div(a, b);
+    }
+
+    /// Compute the inverse using the extended Euclidean algorithm
+    /// on the element and the minimal polynomial.
+    fn inv(&self, a: &Self::Element) -> Self::Element {
+        assert!(!Self::is_zero(a), "Division by zero");
+
+        let mut r0 = self.poly.clone();
+        let mut r1 = self.to_element(a.poly.clone()).poly;
+        let mut s0 = self.poly.new_from(None);
+        let mut s1 = self.poly.new_from_constant(self.poly.field.one());
+
+        while !r1.is_zero() {
+            let (q, r) = r0.quot_rem_univariate(&mut r1);
+            let s = &s0 - &(&q * &s1);
+            r0 = r1;
+            r1 = r;
+            s0 = s1;
+            s1 = s;
+        }
+
+        assert!(
+            r0.is_constant(),
+            "The minimal polynomial {} is not irreducible",
+            self.poly
+        );
+
+        let inv = self.poly.field.inv(&r0.lcoeff());
+        self.to_element(s0.mul_coeff(inv))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        poly::polynomial::MultivariatePolynomial,
+        rings::rational::{Rational, RationalField},
+    };
+
+    use super::AlgebraicExtension;
+
+    #[test]
+    fn test_intern_minimal_polynomial() {
+        let mut poly = MultivariatePolynomial::<_, u16>::new(1, RationalField::new(), None, None);
+        poly.append_monomial(Rational::Natural(-5, 1), &[0]);
+        poly.append_monomial(Rational::Natural(2, 1), &[2]);
+
+        let ext1 = AlgebraicExtension::new(poly.clone());
+        let ext2 = AlgebraicExtension::new(poly.clone());
+        assert!(std::ptr::eq(ext1.poly(), ext2.poly()));
+
+        poly.append_monomial(Rational::Natural(1, 1), &[1]);
+        let ext3 = AlgebraicExtension::new(poly);
+        assert!(!std::ptr::eq(ext1.poly(), ext3.poly()));
+    }
+}
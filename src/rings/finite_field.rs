@@ -1,3 +1,4 @@
+use ahash::HashMap;
 use rand::Rng;
 use std::fmt::{Display, Error, Formatter};
 use std::hash::Hash;
@@ -571,6 +572,123 @@ impl Field for FiniteField<u64> {
     }
 }
 
+impl<UField: FiniteFieldWorkspace> FiniteField<UField>
+where
+    FiniteField<UField>: FiniteFieldCore<UField, Element = FiniteFieldElement<UField>>,
+{
+    /// Check if `a` is a square, using Euler's criterion.
+    pub fn is_square(&self, a: &FiniteFieldElement<UField>) -> bool {
+        if Self::is_zero(a) {
+            return true;
+        }
+
+        let p = self.get_prime().to_u64();
+        self.is_one(&self.pow(a, (p - 1) / 2))
+    }
+
+    /// Compute a square root of `a` using the Tonelli-Shanks algorithm, or return `None`
+    /// if `a` is not a square. Of the two roots `r` and `-r`, the one with the smallest
+    /// standard form is returned.
+    pub fn sqrt(&self, a: &FiniteFieldElement<UField>) -> Option<FiniteFieldElement<UField>> {
+        if Self::is_zero(a) {
+            return Some(*a);
+        }
+        if !self.is_square(a) {
+            return None;
+        }
+
+        let p = self.get_prime().to_u64();
+
+        // write p - 1 = q * 2^s with q odd
+        let mut q = p - 1;
+        let mut s = 0;
+        while q % 2 == 0 {
+            q /= 2;
+            s += 1;
+        }
+
+        let r = if s == 1 {
+            self.pow(a, (p + 1) / 4)
+        } else {
+            // find a quadratic non-residue
+            let mut z = self.add(&self.one(), &self.one());
+            while self.is_square(&z) {
+                z = self.add(&z, &self.one());
+            }
+
+            let mut m = s;
+            let mut c = self.pow(&z, q);
+            let mut t = self.pow(a, q);
+            let mut r = self.pow(a, q.div_ceil(2));
+
+            while !self.is_one(&t) {
+                // find the smallest i such that t^(2^i) = 1
+                let mut i = 0;
+                let mut t2 = t;
+                while !self.is_one(&t2) {
+                    t2 = self.mul(&t2, &t2);
+                    i += 1;
+                }
+
+                let mut b = c;
+                for _ in 0..m - i - 1 {
+                    b = self.mul(&b, &b);
+                }
+
+                m = i;
+                c = self.mul(&b, &b);
+                t = self.mul(&t, &c);
+                r = self.mul(&r, &b);
+            }
+            r
+        };
+
+        if self.from_element(r).to_u64() <= p / 2 {
+            Some(r)
+        } else {
+            Some(self.neg(&r))
+        }
+    }
+
+    /// Find the smallest `e < order` such that `base^e = a` using the baby-step giant-step
+    /// algorithm, or return `None` if there is no such `e`. The algorithm takes
+    /// `O(sqrt(order))` time and memory, so it is meant for elements of small order.
+    /// The order of the multiplicative group, `p - 1`, is always a valid choice for `order`.
+    pub fn discrete_log(
+        &self,
+        base: &FiniteFieldElement<UField>,
+        a: &FiniteFieldElement<UField>,
+        order: u64,
+    ) -> Option<u64> {
+        if Self::is_zero(base) || Self::is_zero(a) {
+            return None;
+        }
+
+        let m = (order as f64).sqrt().ceil().max(1.) as u64;
+
+        // baby steps: base^j for 0 <= j < m, keeping the smallest j
+        let mut baby_steps = HashMap::default();
+        let mut x = self.one();
+        for j in 0..m {
+            baby_steps.entry(x).or_insert(j);
+            x = self.mul(&x, base);
+        }
+
+        // giant steps: a * base^(-i m)
+        let factor = self.inv(&self.pow(base, m));
+        let mut y = *a;
+        for i in 0..m {
+            if let Some(j) = baby_steps.get(&y) {
+                let e = i * m + j;
+                return if e < order { Some(e) } else { None };
+            }
+            y = self.mul(&y, &factor);
+        }
+
+        None
+    }
+}
+
 /// Do a deterministic Miller test to check if `n` is a prime.
 /// Since `n` is a `u64`, a basis of only 7 witnesses has to be tested.
 ///
@@ -228,6 +228,32 @@ impl Integer {
         }
     }
 
+    /// Compute the exact `n`-th root of the integer, if it exists.
+    /// Negative numbers only have a root when `n` is odd.
+    pub fn nth_root(&self, n: u32) -> Option<Self> {
+        assert!(n > 0, "Cannot take the zeroth root");
+
+        if n == 1 || self.is_zero() || self.is_one() {
+            return Some(self.clone());
+        }
+
+        if self.is_negative() && n & 1 == 0 {
+            return None;
+        }
+
+        let r = match self {
+            Self::Natural(n1) => ArbitraryPrecisionInteger::from(*n1),
+            Self::Large(r) => r.clone(),
+        };
+
+        let (root, rem) = r.root_rem(ArbitraryPrecisionInteger::new(), n);
+        if rem == 0 {
+            Some(Self::from_large(root))
+        } else {
+            None
+        }
+    }
+
     /// Use Garner's algorithm for the Chinese remainder theorem
     /// to reconstruct an x that satisfies n1 = x % p1 and n2 = x % p2.
     /// The x will be in the range [-p1*p2/2,p1*p2/2].
@@ -278,6 +304,31 @@ impl Integer {
 
         Self::from_large(res)
     }
+
+    /// Compute the extended GCD `(g, s, t)` of `a` and `b`, such that
+    /// `s * a + t * b = g` and `g >= 0`.
+    pub fn extended_gcd(a: &Self, b: &Self) -> (Self, Self, Self) {
+        let ring = IntegerRing::new();
+
+        let (mut old_r, mut r) = (a.clone(), b.clone());
+        let (mut old_s, mut s) = (Self::one(), Self::zero());
+        let (mut old_t, mut t) = (Self::zero(), Self::one());
+
+        while !r.is_zero() {
+            let (q, new_r) = ring.quot_rem(&old_r, &r);
+            old_r = std::mem::replace(&mut r, new_r);
+            let new_s = &old_s - &(&q * &s);
+            old_s = std::mem::replace(&mut s, new_s);
+            let new_t = &old_t - &(&q * &t);
+            old_t = std::mem::replace(&mut t, new_t);
+        }
+
+        if old_r.is_negative() {
+            (-&old_r, -&old_s, -&old_t)
+        } else {
+            (old_r, old_s, old_t)
+        }
+    }
 }
 
 impl Display for Integer {
@@ -1,3 +1,4 @@
+use rug::{ops::Pow, Integer as ArbitraryPrecisionInteger, Rational as ArbitraryPrecisionRational};
 use smallvec::SmallVec;
 use std::{
     fmt::{Display, Write},
@@ -5,16 +6,28 @@ use std::{
     slice::Chunks,
 };
 
-use super::Field;
+use crate::{
+    poly::{gcd::PolynomialGCD, polynomial::MultivariatePolynomial, Exponent},
+    utils,
+};
+
+use super::{
+    integer::{Integer, IntegerRing},
+    rational::{Rational, RationalField},
+    rational_polynomial::{
+        FromNumeratorAndDenominator, RationalPolynomial, RationalPolynomialField,
+    },
+    EuclideanDomain, Field, Ring,
+};
 
 #[derive(Debug, Eq, PartialEq)]
-pub struct Matrix<F: Field> {
+pub struct Matrix<F: Ring> {
     pub shape: (u32, u32),
     pub data: SmallVec<[F::Element; 25]>,
     pub field: F,
 }
 
-impl<F: Field> Matrix<F> {
+impl<F: Ring> Matrix<F> {
     pub fn new(rows: u32, cols: u32, field: F) -> Self {
         Self {
             shape: (rows, cols),
@@ -34,9 +47,34 @@ impl<F: Field> Matrix<F> {
     pub fn row_iter(&self) -> Chunks<'_, F::Element> {
         self.data.chunks(self.shape.1 as usize)
     }
+
+    /// Create the `n x n` identity matrix.
+    pub fn identity(n: u32, field: F) -> Self {
+        let mut m = Self::new(n, n, field);
+        for i in 0..n {
+            m[(i, i)] = m.field.one();
+        }
+        m
+    }
+
+    /// Multiply `self` with `other` from the right.
+    pub fn mul(&self, other: &Self) -> Self {
+        assert!(self.shape.1 == other.shape.0 && self.field == other.field);
+
+        let mut res = Self::new(self.shape.0, other.shape.1, self.field);
+        for i in 0..self.shape.0 {
+            for j in 0..other.shape.1 {
+                for k in 0..self.shape.1 {
+                    self.field
+                        .add_mul_assign(&mut res[(i, j)], &self[(i, k)], &other[(k, j)]);
+                }
+            }
+        }
+        res
+    }
 }
 
-impl<F: Field> Index<(u32, u32)> for Matrix<F> {
+impl<F: Ring> Index<(u32, u32)> for Matrix<F> {
     type Output = F::Element;
 
     fn index(&self, index: (u32, u32)) -> &Self::Output {
@@ -44,13 +82,13 @@ impl<F: Field> Index<(u32, u32)> for Matrix<F> {
     }
 }
 
-impl<F: Field> IndexMut<(u32, u32)> for Matrix<F> {
+impl<F: Ring> IndexMut<(u32, u32)> for Matrix<F> {
     fn index_mut(&mut self, index: (u32, u32)) -> &mut F::Element {
         &mut self.data[(index.0 * self.shape.1 + index.1) as usize]
     }
 }
 
-impl<F: Field> Display for Matrix<F> {
+impl<F: Ring> Display for Matrix<F> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_char('{')?;
         for (ri, r) in self.row_iter().enumerate() {
@@ -241,11 +279,997 @@ impl<F: Field> Matrix<F> {
     }
 }
 
+impl<F: Field> Matrix<F> {
+    /// Transform the matrix into reduced row echelon form with respect to the first
+    /// `max_col` columns, so that every pivot is one and is the only non-zero entry in
+    /// its column. The other columns are augmented.
+    ///
+    /// Returns the pivot columns, where the pivot of row `i` is in column `pivots[i]`.
+    /// The rows after the last pivot row are zero in the first `max_col` columns.
+    pub fn row_reduce(&mut self, max_col: u32) -> Vec<u32> {
+        let (neqs, ncols) = self.shape;
+        let max_col = max_col.min(ncols);
+
+        let mut pivots = vec![];
+        let mut i = 0;
+        for j in 0..max_col {
+            if i == neqs {
+                break;
+            }
+
+            let Some(k) = (i..neqs).find(|k| !F::is_zero(&self[(*k, j)])) else {
+                continue;
+            };
+
+            if k != i {
+                for l in j..ncols {
+                    let old = self[(i, l)].clone();
+                    self[(i, l)] = self[(k, l)].clone();
+                    self[(k, l)] = old;
+                }
+            }
+
+            let inv_x = self.field.inv(&self[(i, j)]);
+            for l in j..ncols {
+                self[(i, l)] = self.field.mul(&self[(i, l)], &inv_x);
+            }
+
+            for k in 0..neqs {
+                if k != i && !F::is_zero(&self[(k, j)]) {
+                    let s = self[(k, j)].clone();
+                    for l in j..ncols {
+                        self[(k, l)] = self
+                            .field
+                            .sub(&self[(k, l)], &self.field.mul(&self[(i, l)], &s));
+                    }
+                }
+            }
+
+            pivots.push(j);
+            i += 1;
+        }
+
+        pivots
+    }
+
+    /// Find a solution of `A * x = b`, where `A` is `self`, that may be underdetermined.
+    /// All free variables are set to zero.
+    pub fn solve_particular(&self, b: &Self) -> Result<Self, LinearSolverError<F>> {
+        assert!(self.shape.0 == b.shape.0 && b.shape.1 == 1 && self.field == b.field);
+
+        let (neqs, nvars) = self.shape;
+
+        let mut m = Self::new(neqs, nvars + 1, self.field);
+        for r in 0..neqs {
+            for c in 0..nvars {
+                m[(r, c)] = self[(r, c)].clone();
+            }
+            m[(r, nvars)] = b.data[r as usize].clone();
+        }
+
+        let pivots = m.row_reduce(nvars);
+        let i = pivots.len() as u32;
+
+        for k in i..neqs {
+            if !F::is_zero(&m[(k, nvars)]) {
+                return Err(LinearSolverError::Inconsistent);
+            }
+        }
+
+        let mut result = Self::new(nvars, 1, self.field);
+        for (r, c) in pivots.into_iter().enumerate() {
+            result.data[c as usize] = m[(r as u32, nvars)].clone();
+        }
+
+        Ok(result)
+    }
+}
+
+impl Matrix<IntegerRing> {
+    /// Replace rows `r1` and `r2` by `a * r1 + b * r2` and `c * r1 + d * r2`.
+    fn combine_rows(
+        &mut self,
+        r1: u32,
+        r2: u32,
+        (a, b, c, d): (&Integer, &Integer, &Integer, &Integer),
+    ) {
+        for j in 0..self.shape.1 {
+            let x = self[(r1, j)].clone();
+            let y = self[(r2, j)].clone();
+            self[(r1, j)] = &(a * &x) + &(b * &y);
+            self[(r2, j)] = &(c * &x) + &(d * &y);
+        }
+    }
+
+    /// Replace columns `c1` and `c2` by `a * c1 + b * c2` and `c * c1 + d * c2`.
+    fn combine_cols(
+        &mut self,
+        c1: u32,
+        c2: u32,
+        (a, b, c, d): (&Integer, &Integer, &Integer, &Integer),
+    ) {
+        for i in 0..self.shape.0 {
+            let x = self[(i, c1)].clone();
+            let y = self[(i, c2)].clone();
+            self[(i, c1)] = &(a * &x) + &(b * &y);
+            self[(i, c2)] = &(c * &x) + &(d * &y);
+        }
+    }
+
+    fn swap_rows(&mut self, r1: u32, r2: u32) {
+        if r1 != r2 {
+            for j in 0..self.shape.1 {
+                let (i1, i2) = (
+                    (r1 * self.shape.1 + j) as usize,
+                    (r2 * self.shape.1 + j) as usize,
+                );
+                self.data.swap(i1, i2);
+            }
+        }
+    }
+
+    fn swap_cols(&mut self, c1: u32, c2: u32) {
+        if c1 != c2 {
+            for i in 0..self.shape.0 {
+                let (i1, i2) = (
+                    (i * self.shape.1 + c1) as usize,
+                    (i * self.shape.1 + c2) as usize,
+                );
+                self.data.swap(i1, i2);
+            }
+        }
+    }
+
+    /// Compute the row-style Hermite normal form `H` of `self`, together with a unimodular
+    /// transformation matrix `U` such that `U * self = H`. `H` is upper triangular,
+    /// its pivots are positive and the entries above each pivot are reduced modulo the pivot.
+    pub fn hermite_normal_form(&self) -> (Self, Self) {
+        let (nrows, ncols) = self.shape;
+        let mut h = Self {
+            shape: self.shape,
+            data: self.data.clone(),
+            field: self.field,
+        };
+        let mut u = Self::identity(nrows, self.field);
+
+        let mut r = 0;
+        for j in 0..ncols {
+            if r == nrows {
+                break;
+            }
+
+            // clear the column below the pivot row
+            for i in r + 1..nrows {
+                if h[(i, j)].is_zero() {
+                    continue;
+                }
+
+                let (g, s, t) = Integer::extended_gcd(&h[(r, j)], &h[(i, j)]);
+                let a = &h[(r, j)] / &g;
+                let b = &h[(i, j)] / &g;
+                let ops = (&s, &t, &-&b, &a);
+                h.combine_rows(r, i, ops);
+                u.combine_rows(r, i, ops);
+            }
+
+            if h[(r, j)].is_zero() {
+                continue;
+            }
+
+            if h[(r, j)].is_negative() {
+                let m1 = Integer::new(-1);
+                let z = Integer::zero();
+                let ops = (&m1, &z, &z, &m1);
+                h.combine_rows(r, r, ops);
+                u.combine_rows(r, r, ops);
+            }
+
+            // reduce the entries above the pivot
+            for i in 0..r {
+                let (q, _) = self.field.quot_rem(&h[(i, j)], &h[(r, j)]);
+                if !q.is_zero() {
+                    let one = Integer::one();
+                    let z = Integer::zero();
+                    let ops = (&one, &-&q, &z, &one);
+                    h.combine_rows(i, r, ops);
+                    u.combine_rows(i, r, ops);
+                }
+            }
+
+            r += 1;
+        }
+
+        (h, u)
+    }
+
+    /// Compute the Smith normal form `S` of `self`, together with unimodular
+    /// transformation matrices `U` and `V` such that `U * self * V = S`. `S` is diagonal
+    /// with non-negative entries, where every diagonal entry divides the next.
+    pub fn smith_normal_form(&self) -> (Self, Self, Self) {
+        let (nrows, ncols) = self.shape;
+        let mut s = Self {
+            shape: self.shape,
+            data: self.data.clone(),
+            field: self.field,
+        };
+        let mut u = Self::identity(nrows, self.field);
+        let mut v = Self::identity(ncols, self.field);
+
+        for t in 0..nrows.min(ncols) {
+            // move the smallest non-zero entry to the pivot position
+            let mut pivot: Option<(u32, u32)> = None;
+            for i in t..nrows {
+                for j in t..ncols {
+                    if !s[(i, j)].is_zero()
+                        && (pivot.is_none() || s[(i, j)].abs_cmp(&s[pivot.unwrap()]).is_lt())
+                    {
+                        pivot = Some((i, j));
+                    }
+                }
+            }
+
+            let Some((pi, pj)) = pivot else {
+                break;
+            };
+
+            s.swap_rows(t, pi);
+            u.swap_rows(t, pi);
+            s.swap_cols(t, pj);
+            v.swap_cols(t, pj);
+
+            loop {
+                for i in t + 1..nrows {
+                    if s[(i, t)].is_zero() {
+                        continue;
+                    }
+
+                    let (g, x, y) = Integer::extended_gcd(&s[(t, t)], &s[(i, t)]);
+                    let a = &s[(t, t)] / &g;
+                    let b = &s[(i, t)] / &g;
+                    let ops = (&x, &y, &-&b, &a);
+                    s.combine_rows(t, i, ops);
+                    u.combine_rows(t, i, ops);
+                }
+
+                let mut changed = false;
+                for j in t + 1..ncols {
+                    if s[(t, j)].is_zero() {
+                        continue;
+                    }
+
+                    changed = true;
+                    let (g, x, y) = Integer::extended_gcd(&s[(t, t)], &s[(t, j)]);
+                    let a = &s[(t, t)] / &g;
+                    let b = &s[(t, j)] / &g;
+                    let ops = (&x, &y, &-&b, &a);
+                    s.combine_cols(t, j, ops);
+                    v.combine_cols(t, j, ops);
+                }
+
+                if changed {
+                    continue;
+                }
+
+                // the pivot must divide all remaining entries
+                let mut bad_row = None;
+                'search: for i in t + 1..nrows {
+                    for j in t + 1..ncols {
+                        if !self.field.rem(&s[(i, j)], &s[(t, t)]).is_zero() {
+                            bad_row = Some(i);
+                            break 'search;
+                        }
+                    }
+                }
+
+                if let Some(i) = bad_row {
+                    let one = Integer::one();
+                    let z = Integer::zero();
+                    let ops = (&one, &one, &z, &one);
+                    s.combine_rows(t, i, ops);
+                    u.combine_rows(t, i, ops);
+                } else {
+                    break;
+                }
+            }
+
+            if s[(t, t)].is_negative() {
+                let m1 = Integer::new(-1);
+                let z = Integer::zero();
+                let ops = (&m1, &z, &z, &m1);
+                s.combine_rows(t, t, ops);
+                u.combine_rows(t, t, ops);
+            }
+        }
+
+        (s, u, v)
+    }
+
+    /// Reduce the lattice basis formed by the rows of `self` with the
+    /// Lenstra-Lenstra-Lovász algorithm, using `delta = 3/4`. The rows must be linearly
+    /// independent. The returned rows span the same lattice and are short and nearly orthogonal.
+    pub fn lll_reduce(&self) -> Self {
+        let nrows = self.rows();
+        let mut b: Vec<Vec<ArbitraryPrecisionInteger>> = self
+            .row_iter()
+            .map(|r| {
+                r.iter()
+                    .map(|x| match x {
+                        Integer::Natural(n) => ArbitraryPrecisionInteger::from(*n),
+                        Integer::Large(l) => l.clone(),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let delta = ArbitraryPrecisionRational::from((3, 4));
+        let half = ArbitraryPrecisionRational::from((1, 2));
+        let (mut mu, mut norms) = gram_schmidt(&b);
+
+        let mut k = 1;
+        while k < nrows {
+            // size reduction
+            for j in (0..k).rev() {
+                if ArbitraryPrecisionRational::from(mu[k][j].abs_ref()) <= half {
+                    continue;
+                }
+
+                let q = ArbitraryPrecisionInteger::from(mu[k][j].round_ref());
+
+                let (lower, upper) = b.split_at_mut(k);
+                for (x, y) in upper[0].iter_mut().zip(&lower[j]) {
+                    *x -= ArbitraryPrecisionInteger::from(&q * y);
+                }
+                let (lower, upper) = mu.split_at_mut(k);
+                for (x, y) in upper[0][..j].iter_mut().zip(&lower[j][..j]) {
+                    *x -= ArbitraryPrecisionRational::from(y * &q);
+                }
+                mu[k][j] -= q;
+            }
+
+            // Lovász condition
+            let bound = (delta.clone()
+                - ArbitraryPrecisionRational::from(mu[k][k - 1].square_ref()))
+                * &norms[k - 1];
+            if norms[k] >= bound {
+                k += 1;
+            } else {
+                b.swap(k, k - 1);
+                (mu, norms) = gram_schmidt(&b);
+                k = (k - 1).max(1);
+            }
+        }
+
+        Self {
+            shape: self.shape,
+            data: b.into_iter().flatten().map(Integer::from_large).collect(),
+            field: self.field,
+        }
+    }
+
+    /// Find integers `a_i`, not all zero, such that `sum_i a_i values_i = 0`, using lattice
+    /// reduction on the values scaled by `10^digits`. The relation is only accepted if
+    /// the sum vanishes up to `10^(-digits/2)`, so `digits` should be of the order of the
+    /// precision of `values`.
+    pub fn find_integer_relation(values: &[Rational], digits: u32) -> Option<Vec<Integer>> {
+        let n = values.len() as u32;
+        let scale = ArbitraryPrecisionInteger::from(10).pow(digits);
+
+        let mut m = Self::new(n, n + 1, IntegerRing::new());
+        for (i, v) in values.iter().enumerate() {
+            let v = match v {
+                Rational::Natural(num, den) => ArbitraryPrecisionRational::from((*num, *den)),
+                Rational::Large(r) => r.clone(),
+            };
+            m[(i as u32, i as u32)] = Integer::one();
+            m[(i as u32, n)] =
+                Integer::from_large(ArbitraryPrecisionInteger::from((v * &scale).round_ref()));
+        }
+
+        let reduced = m.lll_reduce();
+        let relation: Vec<_> = reduced.row_iter().next()?[..n as usize].to_vec();
+        if relation.iter().all(|x| x.is_zero()) {
+            return None;
+        }
+
+        let field = RationalField::new();
+        let mut sum = field.zero();
+        for (a, v) in relation.iter().zip(values) {
+            field.add_mul_assign(&mut sum, &a.to_rational(), v);
+        }
+
+        let tolerance = ArbitraryPrecisionInteger::from(10).pow(digits / 2);
+        let sum = match sum {
+            Rational::Natural(num, den) => ArbitraryPrecisionRational::from((num, den)),
+            Rational::Large(r) => r,
+        };
+        if sum.abs() * tolerance < 1 {
+            Some(relation)
+        } else {
+            None
+        }
+    }
+}
+
+/// Compute the Gram-Schmidt coefficients `mu[i][j]` and the squared norms of the
+/// orthogonalized vectors of the rows `b`.
+fn gram_schmidt(
+    b: &[Vec<ArbitraryPrecisionInteger>],
+) -> (
+    Vec<Vec<ArbitraryPrecisionRational>>,
+    Vec<ArbitraryPrecisionRational>,
+) {
+    let n = b.len();
+    let mut b_star: Vec<Vec<ArbitraryPrecisionRational>> = Vec::with_capacity(n);
+    let mut mu = vec![vec![ArbitraryPrecisionRational::new(); n]; n];
+    let mut norms = Vec::with_capacity(n);
+
+    for i in 0..n {
+        let mut v: Vec<ArbitraryPrecisionRational> =
+            b[i].iter().map(ArbitraryPrecisionRational::from).collect();
+        for j in 0..i {
+            if norms[j] == 0 {
+                continue;
+            }
+
+            let mut dot = ArbitraryPrecisionRational::new();
+            for (x, y) in b[i].iter().zip(&b_star[j]) {
+                dot += ArbitraryPrecisionRational::from(x) * y;
+            }
+            mu[i][j] = dot / &norms[j];
+
+            for (vv, y) in v.iter_mut().zip(&b_star[j]) {
+                *vv -= ArbitraryPrecisionRational::from(&mu[i][j] * y);
+            }
+        }
+
+        let mut norm = ArbitraryPrecisionRational::new();
+        for x in &v {
+            norm += ArbitraryPrecisionRational::from(x.square_ref());
+        }
+        norms.push(norm);
+        b_star.push(v);
+    }
+
+    (mu, norms)
+}
+
+/// A sparse matrix in compressed row format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseMatrix<F: Ring> {
+    pub shape: (u32, u32),
+    row_offsets: Vec<usize>,
+    columns: Vec<u32>,
+    values: Vec<F::Element>,
+    pub field: F,
+}
+
+impl<F: Ring> SparseMatrix<F> {
+    /// Create a sparse matrix from a list of `(row, column, value)` entries.
+    /// Entries at the same position are added and zeros are removed.
+    pub fn new(rows: u32, cols: u32, mut entries: Vec<(u32, u32, F::Element)>, field: F) -> Self {
+        entries.sort_by_key(|(r, c, _)| (*r, *c));
+
+        let mut row_offsets = Vec::with_capacity(rows as usize + 1);
+        let mut columns: Vec<u32> = Vec::with_capacity(entries.len());
+        let mut values: Vec<F::Element> = Vec::with_capacity(entries.len());
+
+        row_offsets.push(0);
+        let mut cur_row = 0;
+        for (r, c, v) in entries {
+            assert!(r < rows && c < cols, "Entry ({}, {}) out of bounds", r, c);
+
+            while cur_row < r {
+                Self::remove_last_zero(&mut columns, &mut values, row_offsets[cur_row as usize]);
+                row_offsets.push(values.len());
+                cur_row += 1;
+            }
+
+            if values.len() > row_offsets[r as usize] && *columns.last().unwrap() == c {
+                field.add_assign(values.last_mut().unwrap(), &v);
+            } else {
+                Self::remove_last_zero(&mut columns, &mut values, row_offsets[r as usize]);
+                columns.push(c);
+                values.push(v);
+            }
+        }
+
+        while cur_row < rows {
+            Self::remove_last_zero(&mut columns, &mut values, row_offsets[cur_row as usize]);
+            row_offsets.push(values.len());
+            cur_row += 1;
+        }
+
+        Self {
+            shape: (rows, cols),
+            row_offsets,
+            columns,
+            values,
+            field,
+        }
+    }
+
+    /// Remove the last entry if it is zero and belongs to the current row.
+    fn remove_last_zero(columns: &mut Vec<u32>, values: &mut Vec<F::Element>, row_start: usize) {
+        if values.len() > row_start && F::is_zero(values.last().unwrap()) {
+            columns.pop();
+            values.pop();
+        }
+    }
+
+    /// Create a sparse matrix from a dense one.
+    pub fn from_dense(m: &Matrix<F>) -> Self {
+        let mut entries = vec![];
+        for (r, row) in m.row_iter().enumerate() {
+            for (c, v) in row.iter().enumerate() {
+                if !F::is_zero(v) {
+                    entries.push((r as u32, c as u32, v.clone()));
+                }
+            }
+        }
+
+        Self::new(m.shape.0, m.shape.1, entries, m.field)
+    }
+
+    /// Get the number of non-zero entries.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Iterate over the column indices and values of the non-zero entries in row `r`.
+    pub fn row(&self, r: u32) -> impl Iterator<Item = (u32, &F::Element)> {
+        let range = self.row_offsets[r as usize]..self.row_offsets[r as usize + 1];
+        self.columns[range.clone()]
+            .iter()
+            .copied()
+            .zip(&self.values[range])
+    }
+
+    /// Compute the matrix-vector product `A * v`.
+    pub fn mul_vec(&self, v: &[F::Element]) -> Vec<F::Element> {
+        assert_eq!(v.len(), self.shape.1 as usize, "Vector length mismatched");
+
+        (0..self.shape.0)
+            .map(|r| {
+                let mut acc = self.field.zero();
+                for (c, x) in self.row(r) {
+                    self.field.add_mul_assign(&mut acc, x, &v[c as usize]);
+                }
+                acc
+            })
+            .collect()
+    }
+}
+
+impl<F: Field> SparseMatrix<F> {
+    /// Solves `A * x = b` for a square and non-singular matrix `A` using the Wiedemann
+    /// algorithm. Only matrix-vector products are used, so the sparsity of `A` is preserved.
+    ///
+    /// The algorithm is probabilistic and is therefore only suitable for large fields,
+    /// such as finite fields with a large prime. It is retried a few times with different
+    /// random projections before the matrix is reported as singular.
+    pub fn solve_wiedemann(
+        &self,
+        b: &[F::Element],
+    ) -> Result<Vec<F::Element>, LinearSolverError<F>> {
+        let (neqs, nvars) = self.shape;
+        assert_eq!(b.len(), neqs as usize, "Vector length mismatched");
+
+        if neqs != nvars {
+            return Err(LinearSolverError::Underdetermined {
+                min_rank: 0,
+                max_rank: neqs.min(nvars),
+                row_reduced_matrix: None,
+            });
+        }
+
+        if b.iter().all(F::is_zero) {
+            return Ok(vec![self.field.zero(); nvars as usize]);
+        }
+
+        let mut rng = utils::rng("linear_system::solve_wiedemann");
+        let n = nvars as usize;
+
+        for _ in 0..8 {
+            // the projected Krylov sequence u^T A^i b for i < 2n
+            let u: Vec<_> = (0..n)
+                .map(|_| self.field.sample(&mut rng, (1, i64::MAX)))
+                .collect();
+
+            let mut seq = Vec::with_capacity(2 * n);
+            let mut v = b.to_vec();
+            for i in 0..2 * n {
+                if i > 0 {
+                    v = self.mul_vec(&v);
+                }
+
+                let mut s = self.field.zero();
+                for (ui, vi) in u.iter().zip(&v) {
+                    self.field.add_mul_assign(&mut s, ui, vi);
+                }
+                seq.push(s);
+            }
+
+            // the reversed minimal polynomial f(x) = sum_k f_k x^k with f_k = c[L - k]
+            let c = self.berlekamp_massey(&seq);
+            let l = c.len() - 1;
+            if l == 0 || F::is_zero(&c[l]) {
+                continue;
+            }
+
+            // x = -1/f_0 sum_{k=1}^L f_k A^(k-1) b
+            let mut x = vec![self.field.zero(); n];
+            let mut v = b.to_vec();
+            for k in 1..=l {
+                if k > 1 {
+                    v = self.mul_vec(&v);
+                }
+
+                for (xi, vi) in x.iter_mut().zip(&v) {
+                    self.field.add_mul_assign(xi, &c[l - k], vi);
+                }
+            }
+
+            let scale = self.field.neg(&self.field.inv(&c[l]));
+            for xi in &mut x {
+                self.field.mul_assign(xi, &scale);
+            }
+
+            if self.mul_vec(&x) == b {
+                return Ok(x);
+            }
+        }
+
+        Err(LinearSolverError::Underdetermined {
+            min_rank: 0,
+            max_rank: nvars,
+            row_reduced_matrix: None,
+        })
+    }
+
+    /// Compute the shortest linear recurrence `sum_j c_j s_(i-j) = 0` with `c_0 = 1`
+    /// that generates the sequence `s`.
+    fn berlekamp_massey(&self, s: &[F::Element]) -> Vec<F::Element> {
+        let mut c = vec![self.field.one()];
+        let mut prev = vec![self.field.one()];
+        let mut l = 0;
+        let mut m = 1;
+        let mut prev_d = self.field.one();
+
+        for i in 0..s.len() {
+            let mut d = s[i].clone();
+            for j in 1..=l.min(c.len() - 1) {
+                self.field.add_mul_assign(&mut d, &c[j], &s[i - j]);
+            }
+
+            if F::is_zero(&d) {
+                m += 1;
+                continue;
+            }
+
+            let coeff = self.field.div(&d, &prev_d);
+            let old_c = c.clone();
+
+            if c.len() < prev.len() + m {
+                c.resize(prev.len() + m, self.field.zero());
+            }
+            for (j, p) in prev.iter().enumerate() {
+                self.field.sub_mul_assign(&mut c[j + m], &coeff, p);
+            }
+
+            if 2 * l <= i {
+                l = i + 1 - l;
+                prev = old_c;
+                prev_d = d;
+                m = 1;
+            } else {
+                m += 1;
+            }
+        }
+
+        c.resize(l + 1, self.field.zero());
+        c
+    }
+}
+
+/// A sparse row of polynomial entries, sorted by column.
+type PolynomialRow<R, E> = Vec<(u32, MultivariatePolynomial<R, E>)>;
+
+type RationalPolynomialSolverError<R, E> = LinearSolverError<RationalPolynomialField<R, E>>;
+
+impl<R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> SparseMatrix<RationalPolynomialField<R, E>>
+where
+    RationalPolynomial<R, E>: FromNumeratorAndDenominator<R, R, E>,
+{
+    /// Bring the matrix into reduced row echelon form, using only the first `max_col`
+    /// columns as pivots. The other columns are augmented. The pivots are chosen with the
+    /// Markowitz criterion to limit the fill-in, so that they are not necessarily the leftmost
+    /// entries. Every pivot column has a single non-zero entry, which is 1.
+    ///
+    /// The denominators of every row are cleared first, so that the elimination is performed
+    /// on polynomials. Rows are combined fraction-free and their content is removed after
+    /// every step, so that no rational functions are constructed until the pivots are
+    /// divided out at the end. This makes the method suitable for large sparse systems
+    /// with rational function coefficients, such as integration-by-parts identities.
+    ///
+    /// Returns the reduced matrix, whose first rows are the pivot rows ordered by pivot
+    /// column, and the pivot columns. The number of pivots is the rank of the first `max_col` columns.
+    pub fn row_reduce_fraction_free(&self, max_col: u32) -> (Self, Vec<u32>) {
+        let max_col = max_col.min(self.shape.1);
+        let ring = self.field.get_ring();
+
+        let mut rows: Vec<PolynomialRow<R, E>> = (0..self.shape.0)
+            .map(|r| Self::clear_denominators(self.row(r)))
+            .collect();
+
+        let mut col_count = vec![0usize; max_col as usize];
+        for row in &rows {
+            for (c, _) in row {
+                if *c < max_col {
+                    col_count[*c as usize] += 1;
+                }
+            }
+        }
+
+        // forward elimination over the rows that are not yet used as a pivot row
+        let mut active = vec![true; rows.len()];
+        let mut pivots: Vec<(usize, u32)> = vec![];
+        loop {
+            let mut best: Option<(usize, usize, (usize, usize))> = None;
+            'search: for (r, row) in rows.iter().enumerate() {
+                if !active[r] {
+                    continue;
+                }
+
+                for (i, (c, p)) in row.iter().enumerate() {
+                    if *c >= max_col {
+                        break;
+                    }
+
+                    let cost = ((row.len() - 1) * (col_count[*c as usize] - 1), p.nterms);
+                    if best.map(|b| cost < b.2).unwrap_or(true) {
+                        best = Some((r, i, cost));
+                        if cost == (0, 1) {
+                            break 'search;
+                        }
+                    }
+                }
+            }
+
+            let Some((pr, pi, _)) = best else {
+                break;
+            };
+
+            active[pr] = false;
+            let pivot_col = rows[pr][pi].0;
+            for (c, _) in &rows[pr] {
+                if *c < max_col {
+                    col_count[*c as usize] -= 1;
+                }
+            }
+
+            for r in 0..rows.len() {
+                if !active[r] || rows[r].binary_search_by_key(&pivot_col, |x| x.0).is_err() {
+                    continue;
+                }
+
+                for (c, _) in &rows[r] {
+                    if *c < max_col {
+                        col_count[*c as usize] -= 1;
+                    }
+                }
+
+                rows[r] = Self::eliminate(&rows[pr], &rows[r], pivot_col);
+
+                for (c, _) in &rows[r] {
+                    if *c < max_col {
+                        col_count[*c as usize] += 1;
+                    }
+                }
+            }
+
+            pivots.push((pr, pivot_col));
+        }
+
+        // back substitution, where later pivot rows do not contain earlier pivot columns
+        for (t, (pr, pivot_col)) in pivots.iter().enumerate().rev() {
+            for (r, _) in &pivots[..t] {
+                if rows[*r].binary_search_by_key(pivot_col, |x| x.0).is_ok() {
+                    rows[*r] = Self::eliminate(&rows[*pr], &rows[*r], *pivot_col);
+                }
+            }
+        }
+
+        pivots.sort_by_key(|(_, c)| *c);
+
+        let mut entries = vec![];
+        for (new_r, (r, pivot_col)) in pivots.iter().enumerate() {
+            let row = &rows[*r];
+            let pivot = &row.iter().find(|(c, _)| c == pivot_col).unwrap().1;
+            for (c, p) in row {
+                let e = if c == pivot_col {
+                    RationalPolynomial::from_num_den(
+                        pivot.new_from_constant(ring.one()),
+                        pivot.new_from_constant(ring.one()),
+                        ring,
+                        false,
+                    )
+                } else {
+                    RationalPolynomial::from_num_den(p.clone(), pivot.clone(), ring, true)
+                };
+                entries.push((new_r as u32, *c, e));
+            }
+        }
+
+        let mut new_r = pivots.len() as u32;
+        for (r, row) in rows.iter().enumerate() {
+            if !active[r] {
+                continue;
+            }
+            for (c, p) in row {
+                let den = p.new_from_constant(ring.one());
+                entries.push((
+                    new_r,
+                    *c,
+                    RationalPolynomial::from_num_den(p.clone(), den, ring, false),
+                ));
+            }
+            new_r += 1;
+        }
+
+        (
+            Self::new(self.shape.0, self.shape.1, entries, self.field),
+            pivots.into_iter().map(|(_, c)| c).collect(),
+        )
+    }
+
+    /// Solves `A * x = b` for `x`, where `A` is `self`, using fraction-free elimination
+    /// with Markowitz pivoting. See [`SparseMatrix::row_reduce_fraction_free`].
+    pub fn solve_fraction_free(
+        &self,
+        b: &[RationalPolynomial<R, E>],
+    ) -> Result<Vec<RationalPolynomial<R, E>>, RationalPolynomialSolverError<R, E>> {
+        let (neqs, nvars) = self.shape;
+        assert_eq!(b.len(), neqs as usize, "Vector length mismatched");
+
+        let mut entries = vec![];
+        for r in 0..neqs {
+            for (c, v) in self.row(r) {
+                entries.push((r, c, v.clone()));
+            }
+            entries.push((r, nvars, b[r as usize].clone()));
+        }
+        let augmented = Self::new(neqs, nvars + 1, entries, self.field);
+
+        let (m, pivots) = augmented.row_reduce_fraction_free(nvars);
+        let rank = pivots.len() as u32;
+        if (rank..neqs).any(|r| m.row(r).next().is_some()) {
+            return Err(LinearSolverError::Inconsistent);
+        }
+
+        if rank < nvars {
+            return Err(LinearSolverError::Underdetermined {
+                min_rank: rank,
+                max_rank: rank,
+                row_reduced_matrix: None,
+            });
+        }
+
+        let zero = match b.first() {
+            Some(e) => RationalPolynomial::from_num_den(
+                e.numerator.new_from(None),
+                e.numerator.new_from_constant(self.field.get_ring().one()),
+                self.field.get_ring(),
+                false,
+            ),
+            None => self.field.zero(),
+        };
+
+        Ok((0..nvars)
+            .map(|r| {
+                m.row(r)
+                    .find(|(c, _)| *c == nvars)
+                    .map(|(_, v)| v.clone())
+                    .unwrap_or_else(|| zero.clone())
+            })
+            .collect())
+    }
+
+    /// Multiply the row by the least common multiple of its denominators
+    /// and remove the content.
+    fn clear_denominators<'b>(
+        row: impl Iterator<Item = (u32, &'b RationalPolynomial<R, E>)>,
+    ) -> PolynomialRow<R, E>
+    where
+        R: 'b,
+        E: 'b,
+    {
+        let row: Vec<_> = row.collect();
+        let Some((_, first)) = row.first() else {
+            return vec![];
+        };
+
+        let mut lcm = first.denominator.clone();
+        for (_, e) in &row[1..] {
+            let g = MultivariatePolynomial::gcd(&lcm, &e.denominator);
+            lcm = &lcm * &(&e.denominator / &g);
+        }
+
+        let mut res = row
+            .into_iter()
+            .map(|(c, e)| (c, &e.numerator * &(&lcm / &e.denominator)))
+            .collect();
+        Self::remove_content(&mut res);
+        res
+    }
+
+    /// Compute `(p/g) row - (a/g) pivot_row`, where `p` is the pivot, `a` is the entry of
+    /// `row` in the pivot column and `g = gcd(p, a)`, and remove the content of the result.
+    fn eliminate(
+        pivot_row: &PolynomialRow<R, E>,
+        row: &PolynomialRow<R, E>,
+        pivot_col: u32,
+    ) -> PolynomialRow<R, E> {
+        let p = &pivot_row.iter().find(|(c, _)| *c == pivot_col).unwrap().1;
+        let a = &row.iter().find(|(c, _)| *c == pivot_col).unwrap().1;
+        let g = MultivariatePolynomial::gcd(p, a);
+        let (p, a) = (p / &g, a / &g);
+
+        let mut res = Vec::with_capacity(row.len() + pivot_row.len());
+        let (mut i, mut j) = (0, 0);
+        while i < row.len() || j < pivot_row.len() {
+            let ci = row.get(i).map(|x| x.0).unwrap_or(u32::MAX);
+            let cj = pivot_row.get(j).map(|x| x.0).unwrap_or(u32::MAX);
+
+            let (c, e) = if ci < cj {
+                i += 1;
+                (ci, &row[i - 1].1 * &p)
+            } else if cj < ci {
+                j += 1;
+                (cj, -(&pivot_row[j - 1].1 * &a))
+            } else {
+                i += 1;
+                j += 1;
+                (ci, &row[i - 1].1 * &p - &pivot_row[j - 1].1 * &a)
+            };
+
+            if c != pivot_col && !e.is_zero() {
+                res.push((c, e));
+            }
+        }
+
+        Self::remove_content(&mut res);
+        res
+    }
+
+    /// Divide all entries of the row by their greatest common divisor.
+    fn remove_content(row: &mut PolynomialRow<R, E>) {
+        let Some((_, first)) = row.first() else {
+            return;
+        };
+
+        let mut g = first.clone();
+        for (_, e) in &row[1..] {
+            if g.is_one() {
+                return;
+            }
+            g = MultivariatePolynomial::gcd(&g, e);
+        }
+
+        if !g.is_one() {
+            for (_, e) in row.iter_mut() {
+                *e = &*e / &g;
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rings::finite_field::{FiniteField, FiniteFieldCore};
-    use crate::rings::rational::{Rational, RationalField};
+    use crate::{
+        representations::Identifier,
+        rings::finite_field::{FiniteField, FiniteFieldCore},
+    };
 
     #[test]
     fn test_solve_trivial() {
@@ -349,6 +1373,25 @@ mod tests {
         let _r = a.solve(&b).unwrap();
     }
 
+    #[test]
+    fn test_row_reduce() {
+        let field = FiniteField::<u32>::new(17);
+        let mut a = Matrix {
+            shape: (3, 4),
+            data: [1, 2, 0, 3, 2, 4, 1, 1, 3, 6, 1, 4]
+                .into_iter()
+                .map(|n| field.to_element(n))
+                .collect(),
+            field,
+        };
+
+        let pivots = a.row_reduce(3);
+        assert_eq!(pivots, [0, 2]);
+
+        let res: Vec<_> = a.data.iter().map(|i| a.field.from_element(*i)).collect();
+        assert_eq!(&res, &[1, 2, 0, 3, 0, 0, 1, 12, 0, 0, 0, 0]);
+    }
+
     #[test]
     fn test_solve_underdetermined1() {
         let field = FiniteField::<u32>::new(17);
@@ -518,4 +1561,203 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_hermite_normal_form() {
+        let field = IntegerRing::new();
+        let a = Matrix {
+            shape: (4, 4),
+            data: [3, 3, 1, 4, 0, 1, 0, 0, 0, 0, 19, 16, 0, 0, 0, 3]
+                .into_iter()
+                .map(Integer::new)
+                .collect(),
+            field,
+        };
+
+        let (h, u) = a.hermite_normal_form();
+
+        let res: Vec<_> = [3, 0, 1, 1, 0, 1, 0, 0, 0, 0, 19, 1, 0, 0, 0, 3]
+            .into_iter()
+            .map(Integer::new)
+            .collect();
+        assert_eq!(h.data.as_slice(), res.as_slice());
+        assert_eq!(u.mul(&a), h);
+    }
+
+    #[test]
+    fn test_smith_normal_form() {
+        let field = IntegerRing::new();
+        let a = Matrix {
+            shape: (3, 3),
+            data: [2, 4, 4, -6, 6, 12, 10, -4, -16]
+                .into_iter()
+                .map(Integer::new)
+                .collect(),
+            field,
+        };
+
+        let (s, u, v) = a.smith_normal_form();
+
+        let res: Vec<_> = [2, 0, 0, 0, 6, 0, 0, 0, 12]
+            .into_iter()
+            .map(Integer::new)
+            .collect();
+        assert_eq!(s.data.as_slice(), res.as_slice());
+        assert_eq!(u.mul(&a).mul(&v), s);
+    }
+
+    #[test]
+    fn test_solve_wiedemann() {
+        let field = FiniteField::<u32>::new(1000003);
+        let n = 20;
+
+        // a sparse banded matrix with a non-zero diagonal
+        let mut entries = vec![];
+        for i in 0..n {
+            entries.push((i, i, field.to_element(i + 2)));
+            entries.push((i, (i * 7 + 3) % n, field.to_element(5)));
+            if i + 1 < n {
+                entries.push((i, i + 1, field.to_element(1)));
+            }
+        }
+        let a = SparseMatrix::new(n, n, entries, field);
+
+        let x: Vec<_> = (0..n).map(|i| field.to_element(i * i + 1)).collect();
+        let b = a.mul_vec(&x);
+
+        let r = a.solve_wiedemann(&b).unwrap();
+        assert_eq!(r, x);
+    }
+
+    #[test]
+    fn test_solve_fraction_free() {
+        let ring = IntegerRing::new();
+        let field = RationalPolynomialField::<_, u8>::new(ring);
+        let var_map = [Identifier::from(0), Identifier::from(1)];
+        let poly = |terms: &[(i64, [u8; 2])]| {
+            let mut p = MultivariatePolynomial::new(2, ring, None, Some(&var_map));
+            for (c, e) in terms {
+                p.append_monomial(Integer::Natural(*c), e);
+            }
+            p
+        };
+        let rat = |num, den| RationalPolynomial::from_num_den(num, den, ring, true);
+        let one = poly(&[(1, [0, 0])]);
+
+        // a system in x and y with rational function coefficients
+        let entries = vec![
+            (0, 0, rat(poly(&[(1, [1, 0])]), one.clone())),
+            (0, 2, rat(one.clone(), poly(&[(1, [1, 0]), (1, [0, 0])]))),
+            (1, 0, rat(poly(&[(1, [0, 1])]), poly(&[(1, [1, 0])]))),
+            (1, 1, rat(poly(&[(1, [1, 0]), (1, [0, 1])]), one.clone())),
+            (2, 1, rat(poly(&[(2, [0, 0])]), one.clone())),
+            (2, 2, rat(poly(&[(1, [0, 1]), (-3, [0, 0])]), one.clone())),
+            (3, 0, rat(poly(&[(2, [1, 0])]), one.clone())),
+            (
+                3,
+                2,
+                rat(poly(&[(2, [0, 0])]), poly(&[(1, [1, 0]), (1, [0, 0])])),
+            ),
+        ];
+        let a = SparseMatrix::new(4, 3, entries, field);
+
+        let x = vec![
+            rat(one.clone(), poly(&[(1, [0, 1]), (1, [0, 0])])),
+            rat(poly(&[(1, [1, 1])]), one.clone()),
+            rat(poly(&[(-2, [0, 0])]), one.clone()),
+        ];
+        let b: Vec<_> = (0..4)
+            .map(|r| {
+                let mut row = a.row(r).map(|(c, v)| field.mul(v, &x[c as usize]));
+                let first = row.next().unwrap();
+                row.fold(first, |acc, v| field.add(&acc, &v))
+            })
+            .collect();
+
+        let r = a.solve_fraction_free(&b).unwrap();
+        assert_eq!(r, x);
+
+        let (m, pivots) = a.row_reduce_fraction_free(3);
+        assert_eq!(pivots, vec![0, 1, 2]);
+        assert_eq!(m.nnz(), 3);
+    }
+
+    #[test]
+    fn test_solve_particular() {
+        let field = FiniteField::<u32>::new(17);
+        // the second column is twice the first, so the system is underdetermined
+        let a = Matrix {
+            shape: (3, 3),
+            data: [1, 2, 0, 2, 4, 1, 0, 0, 1]
+                .into_iter()
+                .map(|n| field.to_element(n))
+                .collect(),
+            field,
+        };
+        let b = Matrix {
+            shape: (3, 1),
+            data: [3, 8, 2].into_iter().map(|n| field.to_element(n)).collect(),
+            field,
+        };
+        let r = a.solve_particular(&b).unwrap();
+
+        let res: Vec<_> = r
+            .data
+            .into_iter()
+            .map(|i| a.field.from_element(i))
+            .collect();
+        assert_eq!(&res, &[3, 0, 2]);
+
+        let b = Matrix {
+            shape: (3, 1),
+            data: [3, 7, 2].into_iter().map(|n| field.to_element(n)).collect(),
+            field,
+        };
+        assert!(matches!(
+            a.solve_particular(&b),
+            Err(LinearSolverError::Inconsistent)
+        ));
+    }
+
+    #[test]
+    fn test_lll_reduce() {
+        let field = IntegerRing::new();
+        let a = Matrix {
+            shape: (3, 3),
+            data: [1, 1, 1, -1, 0, 2, 3, 5, 6]
+                .into_iter()
+                .map(Integer::new)
+                .collect(),
+            field,
+        };
+
+        let r = a.lll_reduce();
+
+        let res: Vec<_> = [0, 1, 0, 1, 0, 1, -1, 0, 2]
+            .into_iter()
+            .map(Integer::new)
+            .collect();
+        assert_eq!(r.data.as_slice(), res.as_slice());
+    }
+
+    #[test]
+    fn test_find_integer_relation() {
+        // an approximation of sqrt(2) with 30 digits
+        let scale = ArbitraryPrecisionInteger::from(10).pow(30);
+        let x = ArbitraryPrecisionRational::from((
+            (ArbitraryPrecisionInteger::from(2) * &scale * &scale).sqrt(),
+            scale,
+        ));
+        let values = [
+            Rational::Natural(1, 1),
+            Rational::Large(x.clone()),
+            Rational::Large(x.square()),
+        ];
+
+        let mut rel = Matrix::find_integer_relation(&values, 20).unwrap();
+        if rel[2].is_negative() {
+            rel.iter_mut().for_each(|a| *a = -&*a);
+        }
+        assert_eq!(rel, [Integer::new(-2), Integer::new(0), Integer::new(1)]);
+    }
 }
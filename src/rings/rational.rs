@@ -14,7 +14,7 @@ use super::{
     EuclideanDomain, Field, Ring,
 };
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct RationalField;
 
 impl RationalField {
@@ -83,6 +83,108 @@ impl Rational {
             Self::Large(r) => Integer::Large(r.numer().clone()),
         }
     }
+
+    pub fn denominator(&self) -> Integer {
+        match self {
+            Self::Natural(_, d) => Integer::Natural(*d),
+            Self::Large(r) => Integer::Large(r.denom().clone()),
+        }
+    }
+
+    /// Create a rational number from an arbitrary precision one, using the
+    /// small representation if the numerator and denominator fit.
+    pub fn from_large(r: ArbitraryPrecisionRational) -> Self {
+        match (r.numer().to_i64(), r.denom().to_i64()) {
+            (Some(n), Some(d)) => Self::Natural(n, d),
+            _ => Self::Large(r),
+        }
+    }
+
+    /// Use the small representation if the numerator and denominator fit.
+    fn demote(&mut self) {
+        if let Self::Large(r) = self {
+            if let (Some(n), Some(d)) = (r.numer().to_i64(), r.denom().to_i64()) {
+                *self = Self::Natural(n, d);
+            }
+        }
+    }
+
+    /// Reconstruct the rational number `n/d` with `|n|, d <= sqrt(m/2)` that is congruent
+    /// to `v` modulo `m`, using the extended Euclidean algorithm. Such a number is unique
+    /// if it exists.
+    pub fn rational_reconstruction(v: &Integer, m: &Integer) -> Option<Rational> {
+        let to_large = |n: &Integer| match n {
+            Integer::Natural(n) => ArbitraryPrecisionInteger::from(*n),
+            Integer::Large(r) => r.clone(),
+        };
+
+        let m = to_large(m);
+        let bound = (m.clone() / 2u32).sqrt();
+
+        let mut v = to_large(v) % &m;
+        if v < 0 {
+            v += &m;
+        }
+
+        let (mut r0, mut r1) = (m, v);
+        let (mut t0, mut t1) = (
+            ArbitraryPrecisionInteger::new(),
+            ArbitraryPrecisionInteger::from(1),
+        );
+        while r1 > bound {
+            let q = r0.clone() / &r1;
+            let r2 = r0 - q.clone() * &r1;
+            r0 = std::mem::replace(&mut r1, r2);
+            let t2 = t0 - q * &t1;
+            t0 = std::mem::replace(&mut t1, t2);
+        }
+
+        if t1 == 0 || t1.clone().abs() > bound || r1.clone().gcd(&t1) != 1 {
+            return None;
+        }
+
+        if t1 < 0 {
+            t1 = -t1;
+            r1 = -r1;
+        }
+
+        Some(Self::from_large(ArbitraryPrecisionRational::from((r1, t1))))
+    }
+}
+
+/// Add `n1/d1` and `n2/d2` without leaving the small representation, or return `None`
+/// if an intermediate result overflows. The result is normalized with as few gcd
+/// computations as possible: none for integers and coprime denominators, and a
+/// single one for equal denominators.
+#[inline]
+fn add_natural(n1: i64, d1: i64, n2: i64, d2: i64) -> Option<Rational> {
+    if d1 == d2 {
+        let num = n1.checked_add(n2)?;
+        if d1 == 1 {
+            return Some(Rational::Natural(num, 1));
+        }
+        if num == 0 {
+            return Some(Rational::Natural(0, 1));
+        }
+
+        let g = utils::gcd_signed(num, d1);
+        return Some(Rational::Natural(num / g, d1 / g));
+    }
+
+    // the common factors of the result can only come from the gcd of the denominators
+    let g = utils::gcd_signed(d1, d2);
+    let (e1, e2) = (d1 / g, d2 / g);
+    let num = n1.checked_mul(e2)?.checked_add(n2.checked_mul(e1)?)?;
+    if num == 0 {
+        return Some(Rational::Natural(0, 1));
+    }
+
+    if g == 1 {
+        return Some(Rational::Natural(num, d1.checked_mul(d2)?));
+    }
+
+    let g2 = utils::gcd_signed(num, g);
+    Some(Rational::Natural(num / g2, e1.checked_mul(d2 / g2)?))
 }
 
 impl Display for Rational {
@@ -114,28 +216,21 @@ impl Ring for RationalField {
     fn add(&self, a: &Self::Element, b: &Self::Element) -> Self::Element {
         match (a, b) {
             (Rational::Natural(n1, d1), Rational::Natural(n2, d2)) => {
-                if let Some(lcm) = d2.checked_mul(d1 / utils::gcd_signed(*d1, *d2)) {
-                    if let Some(num2) = n2.checked_mul(lcm / d2) {
-                        if let Some(num1) = n1.checked_mul(lcm / d1) {
-                            if let Some(num) = num1.checked_add(num2) {
-                                let g = utils::gcd_signed(num, lcm);
-                                return Rational::Natural(num / g, lcm / g);
-                            }
-                        }
-                    }
+                if let Some(r) = add_natural(*n1, *d1, *n2, *d2) {
+                    return r;
                 }
-                Rational::Large(
+
+                Rational::from_large(
                     ArbitraryPrecisionRational::from((*n1, *d1))
                         + ArbitraryPrecisionRational::from((*n2, *d2)),
                 )
             }
-            // TODO: check downcast
             (Rational::Natural(n1, d1), Rational::Large(r2))
             | (Rational::Large(r2), Rational::Natural(n1, d1)) => {
                 let r1 = ArbitraryPrecisionRational::from((*n1, *d1));
-                Rational::Large(r1 + r2)
+                Rational::from_large(r1 + r2)
             }
-            (Rational::Large(r1), Rational::Large(r2)) => Rational::Large((r1 + r2).into()),
+            (Rational::Large(r1), Rational::Large(r2)) => Rational::from_large((r1 + r2).into()),
         }
     }
 
@@ -146,6 +241,12 @@ impl Ring for RationalField {
 
     fn mul(&self, a: &Self::Element, b: &Self::Element) -> Self::Element {
         match (a, b) {
+            (Rational::Natural(n1, 1), Rational::Natural(n2, 1)) => match n1.checked_mul(*n2) {
+                Some(nn) => Rational::Natural(nn, 1),
+                None => Rational::Large(ArbitraryPrecisionRational::from(
+                    ArbitraryPrecisionInteger::from(*n1) * *n2,
+                )),
+            },
             (Rational::Natural(n1, d1), Rational::Natural(n2, d2)) => {
                 let gcd1 = utils::gcd_signed(*n1, *d2);
                 let gcd2 = utils::gcd_signed(*d1, *n2);
@@ -167,19 +268,34 @@ impl Ring for RationalField {
                     ))),
                 }
             }
-            // FIXME: downcast
             (Rational::Natural(n1, d1), Rational::Large(r2))
             | (Rational::Large(r2), Rational::Natural(n1, d1)) => {
                 let r1 = ArbitraryPrecisionRational::from((*n1, *d1));
-                Rational::Large(r1 * r2)
+                Rational::from_large(r1 * r2)
             }
-            (Rational::Large(r1), Rational::Large(r2)) => Rational::Large((r1 * r2).into()),
+            (Rational::Large(r1), Rational::Large(r2)) => Rational::from_large((r1 * r2).into()),
         }
     }
 
     fn add_assign(&self, a: &mut Self::Element, b: &Self::Element) {
-        // TODO: optimize
-        *a = self.add(a, b);
+        match (&mut *a, b) {
+            (Rational::Natural(n1, d1), Rational::Natural(n2, d2)) => {
+                if let Some(r) = add_natural(*n1, *d1, *n2, *d2) {
+                    *a = r;
+                } else {
+                    *a = self.add(a, b);
+                }
+            }
+            (Rational::Natural(..), Rational::Large(_)) => *a = self.add(a, b),
+            (Rational::Large(r1), Rational::Natural(n2, d2)) => {
+                *r1 += ArbitraryPrecisionRational::from((*n2, *d2));
+                a.demote();
+            }
+            (Rational::Large(r1), Rational::Large(r2)) => {
+                *r1 += r2;
+                a.demote();
+            }
+        }
     }
 
     fn sub_assign(&self, a: &mut Self::Element, b: &Self::Element) {
@@ -191,11 +307,34 @@ impl Ring for RationalField {
     }
 
     fn add_mul_assign(&self, a: &mut Self::Element, b: &Self::Element, c: &Self::Element) {
-        self.add_assign(a, &(b * c));
+        if let (Rational::Natural(n, 1), Rational::Natural(n1, 1), Rational::Natural(n2, 1)) =
+            (&mut *a, b, c)
+        {
+            if let Some(r) = n1.checked_mul(*n2).and_then(|p| n.checked_add(p)) {
+                *n = r;
+                return;
+            }
+        }
+
+        self.add_assign(a, &self.mul(b, c));
     }
 
     fn sub_mul_assign(&self, a: &mut Self::Element, b: &Self::Element, c: &Self::Element) {
-        self.sub_assign(a, &(b * c));
+        if let (Rational::Natural(n, 1), Rational::Natural(n1, 1), Rational::Natural(n2, 1)) =
+            (&mut *a, b, c)
+        {
+            if let Some(r) = n1.checked_mul(*n2).and_then(|p| n.checked_sub(p)) {
+                *n = r;
+                return;
+            }
+        }
+
+        let mut p = self.mul(b, c);
+        match &mut p {
+            Rational::Natural(n, _) if *n != i64::MIN => *n = -*n,
+            _ => p = self.neg(&p),
+        }
+        self.add_assign(a, &p);
     }
 
     fn neg(&self, a: &Self::Element) -> Self::Element {
@@ -6,8 +6,11 @@ use std::{
 };
 
 use crate::{
+    error,
     poly::{gcd::PolynomialGCD, polynomial::MultivariatePolynomial, Exponent},
     representations::Identifier,
+    state::State,
+    utils,
 };
 
 use super::{
@@ -20,6 +23,7 @@ use super::{
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct RationalPolynomialField<R: Ring, E: Exponent> {
     ring: R,
+    reduce: bool,
     _phantom_exp: PhantomData<E>,
 }
 
@@ -27,9 +31,31 @@ impl<R: Ring, E: Exponent> RationalPolynomialField<R, E> {
     pub fn new(coeff_ring: R) -> Self {
         Self {
             ring: coeff_ring,
+            reduce: true,
             _phantom_exp: PhantomData,
         }
     }
+
+    /// Create a field in which arithmetic does not compute GCDs to cancel
+    /// common factors between numerator and denominator. The results
+    /// can be brought into canonical form with [`RationalPolynomial::reduce`].
+    pub fn new_unreduced(coeff_ring: R) -> Self {
+        Self {
+            ring: coeff_ring,
+            reduce: false,
+            _phantom_exp: PhantomData,
+        }
+    }
+
+    /// Check if the arithmetic in this field cancels common factors.
+    pub fn is_reducing(&self) -> bool {
+        self.reduce
+    }
+
+    /// Get the coefficient ring of the numerators and denominators.
+    pub fn get_ring(&self) -> R {
+        self.ring
+    }
 }
 
 pub trait FromNumeratorAndDenominator<R: Ring, OR: Ring, E: Exponent> {
@@ -63,6 +89,16 @@ impl<R: Ring, E: Exponent> RationalPolynomial<R, E> {
         }
     }
 
+    /// Compute a 128-bit fingerprint of the rational polynomial that is stable across processes
+    /// and states and independent of the order of the variable map,
+    /// see [`MultivariatePolynomial::fingerprint`].
+    pub fn fingerprint(&self, state: &State) -> u128 {
+        let mut h = utils::StableHasher::new();
+        h.write(&self.numerator.fingerprint(state).to_le_bytes());
+        h.write(&self.denominator.fingerprint(state).to_le_bytes());
+        h.finish()
+    }
+
     pub fn get_var_map(&self) -> Option<&[Identifier]> {
         self.numerator.var_map.as_ref().map(|x| x.as_slice())
     }
@@ -74,6 +110,58 @@ impl<R: Ring, E: Exponent> RationalPolynomial<R, E> {
         self.numerator.unify_var_map(&mut other.numerator);
         self.denominator.unify_var_map(&mut other.denominator);
     }
+
+    /// Add two rational polynomials without cancelling common factors.
+    /// Use [`RationalPolynomial::reduce`] to bring the result into canonical form.
+    pub fn add_unreduced(&self, other: &Self) -> Self {
+        if self.denominator == other.denominator {
+            return Self {
+                numerator: &self.numerator + &other.numerator,
+                denominator: self.denominator.clone(),
+            };
+        }
+
+        Self {
+            numerator: &self.numerator * &other.denominator + &other.numerator * &self.denominator,
+            denominator: &self.denominator * &other.denominator,
+        }
+    }
+
+    /// Subtract two rational polynomials without cancelling common factors.
+    /// Use [`RationalPolynomial::reduce`] to bring the result into canonical form.
+    pub fn sub_unreduced(&self, other: &Self) -> Self {
+        if self.denominator == other.denominator {
+            return Self {
+                numerator: &self.numerator - &other.numerator,
+                denominator: self.denominator.clone(),
+            };
+        }
+
+        Self {
+            numerator: &self.numerator * &other.denominator - &other.numerator * &self.denominator,
+            denominator: &self.denominator * &other.denominator,
+        }
+    }
+
+    /// Multiply two rational polynomials without cancelling common factors.
+    /// Use [`RationalPolynomial::reduce`] to bring the result into canonical form.
+    pub fn mul_unreduced(&self, other: &Self) -> Self {
+        Self {
+            numerator: &self.numerator * &other.numerator,
+            denominator: &self.denominator * &other.denominator,
+        }
+    }
+
+    /// Divide two rational polynomials without cancelling common factors.
+    /// Use [`RationalPolynomial::reduce`] to bring the result into canonical form.
+    pub fn div_unreduced(&self, other: &Self) -> Self {
+        assert!(!other.numerator.is_zero(), "Cannot divide by 0");
+
+        Self {
+            numerator: &self.numerator * &other.denominator,
+            denominator: &self.denominator * &other.numerator,
+        }
+    }
 }
 
 impl<E: Exponent> FromNumeratorAndDenominator<RationalField, IntegerRing, E>
@@ -87,47 +175,23 @@ impl<E: Exponent> FromNumeratorAndDenominator<RationalField, IntegerRing, E>
     ) -> Self {
         let content = num.field.gcd(&num.content(), &den.content());
 
-        let mut num_int = MultivariatePolynomial::new(
-            num.nvars,
-            IntegerRing::new(),
-            None,
-            num.var_map.as_ref().map(|x| x.as_slice()),
-        );
-        num_int.nterms = num.nterms;
-        num_int.exponents = num.exponents;
-
-        let mut den_int = MultivariatePolynomial::new(
-            den.nvars,
-            IntegerRing::new(),
-            Some(den.nterms),
-            den.var_map.as_ref().map(|x| x.as_slice()),
-        );
-        den_int.nterms = den.nterms;
-        den_int.exponents = den.exponents;
-
-        if num.field.is_one(&content) {
-            num_int.coefficients = num
-                .coefficients
-                .into_iter()
-                .map(|c| c.numerator())
-                .collect();
-            den_int.coefficients = den
-                .coefficients
-                .into_iter()
-                .map(|c| c.numerator())
-                .collect();
+        let (num_int, den_int) = if num.field.is_one(&content) {
+            (
+                num.map_coeff(|c| c.numerator(), IntegerRing::new()),
+                den.map_coeff(|c| c.numerator(), IntegerRing::new()),
+            )
         } else {
-            num_int.coefficients = num
-                .coefficients
-                .into_iter()
-                .map(|c| num.field.div(&c, &content).numerator())
-                .collect();
-            den_int.coefficients = den
-                .coefficients
-                .into_iter()
-                .map(|c| den.field.div(&c, &content).numerator())
-                .collect();
-        }
+            (
+                num.map_coeff(
+                    |c| num.field.div(c, &content).numerator(),
+                    IntegerRing::new(),
+                ),
+                den.map_coeff(
+                    |c| den.field.div(c, &content).numerator(),
+                    IntegerRing::new(),
+                ),
+            )
+        };
 
         <RationalPolynomial<IntegerRing, E> as FromNumeratorAndDenominator<
             IntegerRing,
@@ -224,6 +288,29 @@ impl<R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> RationalPolynomial<R, E
 where
     Self: FromNumeratorAndDenominator<R, R, E>,
 {
+    /// Cancel the common factors of the numerator and denominator and normalize
+    /// the denominator. This is only required after unreduced arithmetic.
+    pub fn reduce(&mut self) {
+        let field = self.numerator.field;
+        let num = std::mem::replace(&mut self.numerator, self.denominator.new_from(None));
+        let den = std::mem::replace(&mut self.denominator, self.numerator.new_from(None));
+        *self = Self::from_num_den(num, den, field, true);
+    }
+
+    /// Invert the rational polynomial, returning an error if it is zero.
+    pub fn try_inv(self) -> Result<Self, error::Error> {
+        if self.numerator.is_zero() {
+            Err(error::Error::DivisionByZero)
+        } else {
+            Ok(self.inv())
+        }
+    }
+
+    /// Divide two rational polynomials, returning an error if `other` is zero.
+    pub fn try_div(&self, other: &Self) -> Result<Self, error::Error> {
+        Ok(self * &other.clone().try_inv()?)
+    }
+
     #[inline]
     pub fn inv(self) -> Self {
         assert!(!self.numerator.is_zero(), "Cannot invert 0");
@@ -232,26 +319,33 @@ where
         Self::from_num_den(self.denominator, self.numerator, field, false)
     }
 
+    /// Raise the rational polynomial to the power `e`. Since the numerator and denominator
+    /// are coprime, no GCD computations are required.
     pub fn pow(&self, e: u64) -> Self {
-        assert!(
-            e <= u32::MAX as u64,
-            "Power of exponentation is larger than 2^32: {}",
-            e
-        );
-        let e = e as u32;
+        Self {
+            numerator: self.numerator.pow(e),
+            denominator: self.denominator.pow(e),
+        }
+    }
 
-        // TODO: do binary exponentation
-        let mut poly = Self {
-            numerator: MultivariatePolynomial::new_from(&self.numerator, None),
-            denominator: MultivariatePolynomial::new_from(&self.denominator, None),
-        };
-        poly.numerator = poly.numerator.add_monomial(self.numerator.field.one());
-        poly.denominator = poly.denominator.add_monomial(self.numerator.field.one());
+    /// Take the derivative of the rational polynomial in variable `x`, using
+    /// `(num/den)' = (num' den - num den')/den^2`. Since `den^2` and `den'` share
+    /// the factor `gcd(den, den')`, it is cancelled before the final reduction.
+    pub fn derivative(&self, x: usize) -> Self {
+        let field = self.numerator.field;
+        let num_der = self.numerator.derivative(x);
+        let den_der = self.denominator.derivative(x);
 
-        for _ in 0..e {
-            poly = &poly * self;
+        if den_der.is_zero() {
+            return Self::from_num_den(num_der, self.denominator.clone(), field, true);
         }
-        poly
+
+        let g = MultivariatePolynomial::gcd(&self.denominator, &den_der);
+        let den_red = &self.denominator / &g;
+
+        let num = &num_der * &den_red - &self.numerator * &(&den_der / &g);
+        let den = &self.denominator * &den_red;
+        Self::from_num_den(num, den, field, true)
     }
 
     pub fn gcd(&self, other: &Self) -> Self {
@@ -265,6 +359,78 @@ where
     }
 }
 
+/// The normalization of a rational polynomial that is used for printing.
+/// The stored form is not affected.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum RationalPolynomialNormalization {
+    /// Print the numerator and denominator as they are stored.
+    #[default]
+    None,
+    /// Print a numerator without content and a denominator with a normalized leading
+    /// coefficient. Over a field the denominator is monic, over the integers its
+    /// leading coefficient is positive.
+    PrimitiveMonic,
+    /// Factor out the content of both the numerator and the denominator, and print
+    /// the ratio of the contents in front of primitive polynomials.
+    ContentFront,
+}
+
+impl<R: EuclideanDomain, E: Exponent> RationalPolynomial<R, E> {
+    /// Write the rational polynomial as `c_num/c_den * num/den` according to the
+    /// normalization `norm`. Returns `(c_num, c_den, num/den)`.
+    pub fn normalized_parts(
+        &self,
+        norm: RationalPolynomialNormalization,
+    ) -> (R::Element, R::Element, Self) {
+        let field = self.numerator.field;
+        if norm == RationalPolynomialNormalization::None || self.numerator.is_zero() {
+            return (field.one(), field.one(), self.clone());
+        }
+
+        let (mut c_num, mut c_den, num, den) = match norm {
+            RationalPolynomialNormalization::PrimitiveMonic => (
+                field.one(),
+                field.one(),
+                self.numerator.clone(),
+                self.denominator.clone(),
+            ),
+            _ => {
+                let c_num = self.numerator.content();
+                let c_den = self.denominator.content();
+                (
+                    c_num.clone(),
+                    c_den.clone(),
+                    self.numerator.clone().div_coeff(&c_num),
+                    self.denominator.clone().div_coeff(&c_den),
+                )
+            }
+        };
+
+        // make the leading coefficient of the denominator a unit
+        let u = field.get_inv_unit(&den.lcoeff());
+        let den = den.mul_coeff(u.clone());
+        let mut num = num.mul_coeff(u);
+
+        if norm == RationalPolynomialNormalization::PrimitiveMonic {
+            c_num = num.content();
+            num = num.div_coeff(&c_num);
+        } else {
+            let g = field.gcd(&c_num, &c_den);
+            c_num = field.quot_rem(&c_num, &g).0;
+            c_den = field.quot_rem(&c_den, &g).0;
+        }
+
+        (
+            c_num,
+            c_den,
+            Self {
+                numerator: num,
+                denominator: den,
+            },
+        )
+    }
+}
+
 impl<R: Ring, E: Exponent> Display for RationalPolynomial<R, E> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.denominator.is_one() {
@@ -288,25 +454,43 @@ where
     type Element = RationalPolynomial<R, E>;
 
     fn add(&self, a: &Self::Element, b: &Self::Element) -> Self::Element {
-        a + b
+        if self.reduce {
+            a + b
+        } else {
+            a.add_unreduced(b)
+        }
     }
 
     fn sub(&self, a: &Self::Element, b: &Self::Element) -> Self::Element {
-        // TODO: optimize
-        self.add(a, &self.neg(b))
+        if self.reduce {
+            a - b
+        } else {
+            a.sub_unreduced(b)
+        }
     }
 
     fn mul(&self, a: &Self::Element, b: &Self::Element) -> Self::Element {
-        a * b
+        if self.reduce {
+            a * b
+        } else {
+            a.mul_unreduced(b)
+        }
     }
 
     fn add_assign(&self, a: &mut Self::Element, b: &Self::Element) {
-        // TODO: optimize
-        *a = self.add(a, b);
+        if self.reduce {
+            a.add_assign_with_sign(b, false);
+        } else {
+            *a = a.add_unreduced(b);
+        }
     }
 
     fn sub_assign(&self, a: &mut Self::Element, b: &Self::Element) {
-        *a = self.sub(a, b);
+        if self.reduce {
+            a.add_assign_with_sign(b, true);
+        } else {
+            *a = a.sub_unreduced(b);
+        }
     }
 
     fn mul_assign(&self, a: &mut Self::Element, b: &Self::Element) {
@@ -314,11 +498,11 @@ where
     }
 
     fn add_mul_assign(&self, a: &mut Self::Element, b: &Self::Element, c: &Self::Element) {
-        self.add_assign(a, &(b * c));
+        self.add_assign(a, &self.mul(b, c));
     }
 
     fn sub_mul_assign(&self, a: &mut Self::Element, b: &Self::Element, c: &Self::Element) {
-        self.sub_assign(a, &(b * c));
+        self.sub_assign(a, &self.mul(b, c));
     }
 
     fn neg(&self, a: &Self::Element) -> Self::Element {
@@ -340,25 +524,7 @@ where
     }
 
     fn pow(&self, b: &Self::Element, e: u64) -> Self::Element {
-        assert!(
-            e <= u32::MAX as u64,
-            "Power of exponentation is larger than 2^32: {}",
-            e
-        );
-        let e = e as u32;
-
-        // TODO: do binary exponentation
-        let mut poly = RationalPolynomial {
-            numerator: MultivariatePolynomial::new_from(&b.numerator, None),
-            denominator: MultivariatePolynomial::new_from(&b.denominator, None),
-        };
-        poly.numerator = poly.numerator.add_monomial(self.ring.one());
-        poly.denominator = poly.denominator.add_monomial(self.ring.one());
-
-        for _ in 0..e {
-            poly = self.mul(&poly, b);
-        }
-        poly
+        b.pow(e)
     }
 
     fn is_zero(a: &Self::Element) -> bool {
@@ -415,7 +581,11 @@ where
     RationalPolynomial<R, E>: FromNumeratorAndDenominator<R, R, E>,
 {
     fn div(&self, a: &Self::Element, b: &Self::Element) -> Self::Element {
-        a / b
+        if self.reduce {
+            a / b
+        } else {
+            a.div_unreduced(b)
+        }
     }
 
     fn div_assign(&self, a: &mut Self::Element, b: &Self::Element) {
@@ -427,12 +597,22 @@ where
     }
 }
 
-impl<'a, 'b, R: EuclideanDomain + PolynomialGCD<E> + PolynomialGCD<E>, E: Exponent>
-    Add<&'a RationalPolynomial<R, E>> for &'b RationalPolynomial<R, E>
-{
-    type Output = RationalPolynomial<R, E>;
+impl<R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> RationalPolynomial<R, E> {
+    /// Compute `self + other` or `self - other` if `negate` is set.
+    fn add_with_sign(&self, other: &Self, negate: bool) -> Self {
+        let combine = |a: MultivariatePolynomial<R, E>, b: MultivariatePolynomial<R, E>| {
+            if negate {
+                a - b
+            } else {
+                a + b
+            }
+        };
+
+        if self.denominator == other.denominator {
+            let num = combine(self.numerator.clone(), other.numerator.clone());
+            return Self::from_reduced_sum(num, self.denominator.clone(), &self.denominator);
+        }
 
-    fn add(self, other: &'a RationalPolynomial<R, E>) -> Self::Output {
         let denom_gcd = MultivariatePolynomial::gcd(&self.denominator, &other.denominator);
 
         let mut a_denom_red = Cow::Borrowed(&self.denominator);
@@ -445,10 +625,10 @@ impl<'a, 'b, R: EuclideanDomain + PolynomialGCD<E> + PolynomialGCD<E>, E: Expone
 
         let num1 = &self.numerator * &b_denom_red;
         let num2 = &other.numerator * &a_denom_red;
-        let mut num = num1 + num2;
+        let num = combine(num1, num2);
 
         // prefer small * large over medium * medium sized polynomials
-        let mut den = if self.denominator.nterms > other.denominator.nterms
+        let den = if self.denominator.nterms > other.denominator.nterms
             && self.denominator.nterms > a_denom_red.nterms
         {
             b_denom_red.as_ref() * &self.denominator
@@ -456,25 +636,75 @@ impl<'a, 'b, R: EuclideanDomain + PolynomialGCD<E> + PolynomialGCD<E>, E: Expone
             a_denom_red.as_ref() * &other.denominator
         };
 
-        let g = MultivariatePolynomial::gcd(&num, &denom_gcd);
+        Self::from_reduced_sum(num, den, &denom_gcd)
+    }
+
+    /// Construct `num/den` from the sum of two reduced fractions. Only a factor of
+    /// `denom_gcd`, the GCD of the denominators of the summands, can be shared
+    /// between the numerator and denominator.
+    fn from_reduced_sum(
+        mut num: MultivariatePolynomial<R, E>,
+        mut den: MultivariatePolynomial<R, E>,
+        denom_gcd: &MultivariatePolynomial<R, E>,
+    ) -> Self {
+        if num.is_zero() {
+            return Self {
+                numerator: num,
+                denominator: MultivariatePolynomial::new_from_constant(&den, den.field.one()),
+            };
+        }
 
-        if !g.is_one() {
-            num = num / &g;
-            den = den / &g;
+        if !denom_gcd.is_one() {
+            let g = MultivariatePolynomial::gcd(&num, denom_gcd);
+            if !g.is_one() {
+                num = num / &g;
+                den = den / &g;
+            }
         }
 
-        RationalPolynomial {
+        Self {
             numerator: num,
             denominator: den,
         }
     }
+
+    /// Add `other` to `self` in place, or subtract it if `negate` is set.
+    /// If the denominators are the same, the numerator is reused.
+    fn add_assign_with_sign(&mut self, other: &Self, negate: bool) {
+        if self.denominator != other.denominator {
+            *self = self.add_with_sign(other, negate);
+            return;
+        }
+
+        let empty = self.numerator.new_from(None);
+        let num = std::mem::replace(&mut self.numerator, empty);
+        let num = if negate {
+            num - other.numerator.clone()
+        } else {
+            num + other.numerator.clone()
+        };
+
+        let empty = self.denominator.new_from(None);
+        let den = std::mem::replace(&mut self.denominator, empty);
+        *self = Self::from_reduced_sum(num, den, &other.denominator);
+    }
+}
+
+impl<'a, 'b, R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> Add<&'a RationalPolynomial<R, E>>
+    for &'b RationalPolynomial<R, E>
+{
+    type Output = RationalPolynomial<R, E>;
+
+    fn add(self, other: &'a RationalPolynomial<R, E>) -> Self::Output {
+        self.add_with_sign(other, false)
+    }
 }
 
 impl<R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> Sub for RationalPolynomial<R, E> {
     type Output = Self;
 
     fn sub(self, other: Self) -> Self {
-        self.add(&other.neg())
+        self.add_with_sign(&other, true)
     }
 }
 
@@ -484,7 +714,7 @@ impl<'a, 'b, R: EuclideanDomain + PolynomialGCD<E>, E: Exponent> Sub<&'a Rationa
     type Output = RationalPolynomial<R, E>;
 
     fn sub(self, other: &'a RationalPolynomial<R, E>) -> Self::Output {
-        (self.clone()).sub(other.clone())
+        self.add_with_sign(other, true)
     }
 }
 
@@ -0,0 +1,377 @@
+use std::{
+    fmt::{Display, Error, Formatter},
+    marker::PhantomData,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+use rand::Rng as _;
+
+use crate::poly::{polynomial::MultivariatePolynomial, Exponent};
+
+use super::{
+    integer::{Integer, IntegerRing},
+    rational::RationalField,
+    EuclideanDomain, Field, Ring,
+};
+
+/// A polynomial with rational coefficients, stored as a polynomial with integer
+/// coefficients and a common positive denominator. Arithmetic only operates on
+/// integers, which avoids normalizing every rational coefficient in inner loops.
+///
+/// The common factors of the coefficients and the denominator are not removed
+/// during arithmetic. Call [`RationalizedPolynomial::normalize`] to remove them,
+/// or convert to a polynomial over the rationals with
+/// [`RationalizedPolynomial::to_rational_polynomial`] when needed.
+#[derive(Clone, Debug)]
+pub struct RationalizedPolynomial<E: Exponent> {
+    pub numerator: MultivariatePolynomial<IntegerRing, E>,
+    pub denominator: Integer,
+}
+
+impl<E: Exponent> RationalizedPolynomial<E> {
+    /// Create the polynomial `numerator / denominator`.
+    pub fn new(numerator: MultivariatePolynomial<IntegerRing, E>, denominator: Integer) -> Self {
+        assert!(!denominator.is_zero(), "Denominator cannot be zero");
+
+        if denominator.is_negative() {
+            Self {
+                numerator: -numerator,
+                denominator: -&denominator,
+            }
+        } else {
+            Self {
+                numerator,
+                denominator,
+            }
+        }
+    }
+
+    /// Write a polynomial over the rationals with the least common multiple
+    /// of the denominators of its coefficients.
+    pub fn from_rational_polynomial(p: &MultivariatePolynomial<RationalField, E>) -> Self {
+        let ring = IntegerRing::new();
+
+        let mut denominator = Integer::one();
+        for c in &p.coefficients {
+            let d = c.denominator();
+            let g = ring.gcd(&denominator, &d);
+            denominator = &denominator * &(&d / &g);
+        }
+
+        let numerator = p.map_coeff(
+            |c| &c.numerator() * &(&denominator / &c.denominator()),
+            ring,
+        );
+
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+
+    /// Convert the polynomial to a polynomial over the rationals.
+    pub fn to_rational_polynomial(&self) -> MultivariatePolynomial<RationalField, E> {
+        let field = RationalField::new();
+        let den = self.denominator.to_rational();
+        self.numerator
+            .map_coeff(|c| field.div(&c.to_rational(), &den), field)
+    }
+
+    /// Remove the common factors of the coefficients and the denominator.
+    pub fn normalize(&mut self) {
+        if self.numerator.is_zero() {
+            self.denominator = Integer::one();
+            return;
+        }
+
+        let ring = IntegerRing::new();
+        let g = ring.gcd(&self.numerator.content(), &self.denominator);
+        if !g.is_one() {
+            self.numerator =
+                std::mem::replace(&mut self.numerator, MultivariatePolynomial::zero(ring))
+                    .div_coeff(&g);
+            self.denominator = &self.denominator / &g;
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numerator.is_zero()
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.numerator.is_constant()
+            && !self.numerator.is_zero()
+            && self.numerator.coefficients[0] == self.denominator
+    }
+
+    /// Raise the polynomial to the power `e`.
+    pub fn pow(&self, e: u64) -> Self {
+        Self {
+            numerator: self.numerator.pow(e),
+            denominator: self.denominator.pow(e),
+        }
+    }
+
+    /// Compute the gcd of two polynomials, which is only defined up to a rational factor.
+    /// The returned gcd has integer coefficients without content and a positive leading
+    /// coefficient.
+    pub fn gcd(&self, other: &Self) -> Self {
+        let g = MultivariatePolynomial::gcd(&self.numerator, &other.numerator);
+        let g = if g.is_zero() {
+            g
+        } else {
+            let c = g.content();
+            let c = if g.lcoeff().is_negative() { -&c } else { c };
+            g.div_coeff(&c)
+        };
+
+        Self {
+            numerator: g,
+            denominator: Integer::one(),
+        }
+    }
+
+    /// Divide `self` by `div` if the division is exact. Over the rationals, a polynomial
+    /// divides another one if and only if its primitive part divides the integer
+    /// polynomial, so only integer arithmetic is required.
+    pub fn divides(&self, div: &Self) -> Option<Self> {
+        assert!(!div.is_zero(), "Cannot divide by 0 polynomial");
+
+        let content = div.numerator.content();
+        let prim = div.numerator.clone().div_coeff(&content);
+        let q = self.numerator.divides(&prim)?;
+
+        Some(Self::new(
+            q.mul_coeff(div.denominator.clone()),
+            &self.denominator * &content,
+        ))
+    }
+
+    /// Bring both polynomials to the same number of variables if one of them is a constant.
+    fn lift_constant(
+        a: &MultivariatePolynomial<IntegerRing, E>,
+        b: &MultivariatePolynomial<IntegerRing, E>,
+    ) -> MultivariatePolynomial<IntegerRing, E> {
+        if a.nvars != b.nvars && a.is_constant() && !a.is_zero() {
+            b.new_from_constant(a.coefficients[0].clone())
+        } else {
+            a.clone()
+        }
+    }
+
+    fn add_with_sign(&self, other: &Self, negate: bool) -> Self {
+        let ring = IntegerRing::new();
+        let a = Self::lift_constant(&self.numerator, &other.numerator);
+        let mut b = Self::lift_constant(&other.numerator, &self.numerator);
+        if negate {
+            b = -b;
+        }
+
+        if self.denominator == other.denominator {
+            return Self {
+                numerator: a + b,
+                denominator: self.denominator.clone(),
+            };
+        }
+
+        let g = ring.gcd(&self.denominator, &other.denominator);
+        let fa = &other.denominator / &g;
+        let fb = &self.denominator / &g;
+        Self {
+            numerator: a.mul_coeff(fa.clone()) + b.mul_coeff(fb),
+            denominator: &self.denominator * &fa,
+        }
+    }
+}
+
+impl<E: Exponent> PartialEq for RationalizedPolynomial<E> {
+    fn eq(&self, other: &Self) -> bool {
+        let a = Self::lift_constant(&self.numerator, &other.numerator);
+        let b = Self::lift_constant(&other.numerator, &self.numerator);
+        a.mul_coeff(other.denominator.clone()) == b.mul_coeff(self.denominator.clone())
+    }
+}
+
+impl<'a, E: Exponent> Add<&'a RationalizedPolynomial<E>> for &RationalizedPolynomial<E> {
+    type Output = RationalizedPolynomial<E>;
+
+    fn add(self, other: &'a RationalizedPolynomial<E>) -> Self::Output {
+        self.add_with_sign(other, false)
+    }
+}
+
+impl<'a, E: Exponent> Sub<&'a RationalizedPolynomial<E>> for &RationalizedPolynomial<E> {
+    type Output = RationalizedPolynomial<E>;
+
+    fn sub(self, other: &'a RationalizedPolynomial<E>) -> Self::Output {
+        self.add_with_sign(other, true)
+    }
+}
+
+impl<'a, E: Exponent> Mul<&'a RationalizedPolynomial<E>> for &RationalizedPolynomial<E> {
+    type Output = RationalizedPolynomial<E>;
+
+    fn mul(self, other: &'a RationalizedPolynomial<E>) -> Self::Output {
+        let numerator = if self.numerator.is_constant() && !self.numerator.is_zero() {
+            other
+                .numerator
+                .clone()
+                .mul_coeff(self.numerator.coefficients[0].clone())
+        } else if other.numerator.is_constant() && !other.numerator.is_zero() {
+            self.numerator
+                .clone()
+                .mul_coeff(other.numerator.coefficients[0].clone())
+        } else {
+            &self.numerator * &other.numerator
+        };
+
+        RationalizedPolynomial {
+            numerator,
+            denominator: &self.denominator * &other.denominator,
+        }
+    }
+}
+
+impl<E: Exponent> Neg for RationalizedPolynomial<E> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            numerator: -self.numerator,
+            denominator: self.denominator,
+        }
+    }
+}
+
+impl<E: Exponent> Display for RationalizedPolynomial<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut p = self.clone();
+        p.normalize();
+        if p.denominator.is_one() {
+            p.numerator.fmt(f)
+        } else {
+            f.write_fmt(format_args!("({})/{}", p.numerator, p.denominator))
+        }
+    }
+}
+
+/// The ring of polynomials over the rationals, where the elements are
+/// stored as [`RationalizedPolynomial`]s.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RationalizedPolynomialRing<E: Exponent> {
+    _phantom_exp: PhantomData<E>,
+}
+
+impl<E: Exponent> RationalizedPolynomialRing<E> {
+    pub fn new() -> Self {
+        Self {
+            _phantom_exp: PhantomData,
+        }
+    }
+}
+
+impl<E: Exponent> Default for RationalizedPolynomialRing<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: Exponent> Display for RationalizedPolynomialRing<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Q[x]")
+    }
+}
+
+impl<E: Exponent> Ring for RationalizedPolynomialRing<E> {
+    type Element = RationalizedPolynomial<E>;
+
+    fn add(&self, a: &Self::Element, b: &Self::Element) -> Self::Element {
+        a + b
+    }
+
+    fn sub(&self, a: &Self::Element, b: &Self::Element) -> Self::Element {
+        a - b
+    }
+
+    fn mul(&self, a: &Self::Element, b: &Self::Element) -> Self::Element {
+        a * b
+    }
+
+    fn add_assign(&self, a: &mut Self::Element, b: &Self::Element) {
+        *a = &*a + b;
+    }
+
+    fn sub_assign(&self, a: &mut Self::Element, b: &Self::Element) {
+        *a = &*a - b;
+    }
+
+    fn mul_assign(&self, a: &mut Self::Element, b: &Self::Element) {
+        *a = &*a * b;
+    }
+
+    fn add_mul_assign(&self, a: &mut Self::Element, b: &Self::Element, c: &Self::Element) {
+        *a = &*a + &(b * c);
+    }
+
+    fn sub_mul_assign(&self, a: &mut Self::Element, b: &Self::Element, c: &Self::Element) {
+        *a = &*a - &(b * c);
+    }
+
+    fn neg(&self, a: &Self::Element) -> Self::Element {
+        a.clone().neg()
+    }
+
+    fn zero(&self) -> Self::Element {
+        RationalizedPolynomial {
+            numerator: MultivariatePolynomial::new(0, IntegerRing::new(), None, None),
+            denominator: Integer::one(),
+        }
+    }
+
+    fn one(&self) -> Self::Element {
+        RationalizedPolynomial {
+            numerator: MultivariatePolynomial::one(IntegerRing::new()),
+            denominator: Integer::one(),
+        }
+    }
+
+    fn pow(&self, b: &Self::Element, e: u64) -> Self::Element {
+        b.pow(e)
+    }
+
+    fn is_zero(a: &Self::Element) -> bool {
+        a.is_zero()
+    }
+
+    fn is_one(&self, a: &Self::Element) -> bool {
+        a.is_one()
+    }
+
+    /// Get the leading coefficient, as the units are the non-zero constants.
+    fn get_unit(&self, a: &Self::Element) -> Self::Element {
+        RationalizedPolynomial::new(
+            a.numerator.new_from_constant(a.numerator.lcoeff()),
+            a.denominator.clone(),
+        )
+    }
+
+    fn get_inv_unit(&self, a: &Self::Element) -> Self::Element {
+        RationalizedPolynomial::new(
+            a.numerator.new_from_constant(a.denominator.clone()),
+            a.numerator.lcoeff(),
+        )
+    }
+
+    fn sample(&self, rng: &mut impl rand::RngCore, range: (i64, i64)) -> Self::Element {
+        let r = rng.gen_range(range.0..range.1);
+        RationalizedPolynomial {
+            numerator: MultivariatePolynomial::new(0, IntegerRing::new(), None, None)
+                .add_monomial(Integer::Natural(r)),
+            denominator: Integer::one(),
+        }
+    }
+
+    fn fmt_display(&self, element: &Self::Element, f: &mut Formatter<'_>) -> Result<(), Error> {
+        element.fmt(f)
+    }
+}
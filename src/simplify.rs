@@ -0,0 +1,232 @@
+use crate::{
+    id::RuleSet,
+    representations::{
+        number::{BorrowedNumber, Number},
+        Add, Atom, AtomView, Fun, Identifier, Mul, Num, OwnedAdd, OwnedAtom, OwnedMul, OwnedNum,
+        OwnedPow, OwnedVar, Pow, Var,
+    },
+    state::{ResettableBuffer, State, Workspace},
+};
+
+/// A transformation that is tried by [`AtomView::simplify`].
+pub enum SimplifyPass<'a, P: Atom> {
+    /// Expand all products and integer powers.
+    Expand,
+    /// Collect the terms of a sum in powers of a variable.
+    Collect(Identifier),
+    /// Write rational subexpressions as a single fraction without common factors.
+    NormalizeFractions,
+    /// Apply a set of rewrite rules, for example trigonometric identities.
+    Rules(&'a RuleSet<P>),
+}
+
+/// Options for [`AtomView::simplify`].
+pub struct SimplifyOptions<'a, P: Atom> {
+    /// Intermediate results with more nodes than `max_size` are discarded,
+    /// which prevents expansions from blowing up.
+    pub max_size: usize,
+    /// The transformations to try. At most `passes.len()` transformations are chained.
+    pub passes: Vec<SimplifyPass<'a, P>>,
+}
+
+impl<'a, P: Atom> SimplifyPass<'a, P> {
+    fn apply(
+        &self,
+        expr: AtomView<'_, P>,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        match self {
+            SimplifyPass::Expand => {
+                expr.expand(workspace, state, out);
+            }
+            SimplifyPass::Collect(x) => {
+                expr.collect(*x, workspace, state, out);
+            }
+            SimplifyPass::NormalizeFractions => {
+                expr.normalize_fractions(workspace, state, out);
+            }
+            SimplifyPass::Rules(rules) => {
+                rules.apply(expr, state, workspace, out, None);
+            }
+        }
+    }
+}
+
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Count the number of nodes of the expression tree, which is the measure of
+    /// size that is used by [`AtomView::simplify`].
+    pub fn node_count(&self) -> usize {
+        match self {
+            AtomView::Num(_) | AtomView::Var(_) => 1,
+            AtomView::Fun(f) => 1 + f.iter().map(|a| a.node_count()).sum::<usize>(),
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                1 + base.node_count() + exp.node_count()
+            }
+            AtomView::Mul(m) => 1 + m.iter().map(|a| a.node_count()).sum::<usize>(),
+            AtomView::Add(a) => 1 + a.iter().map(|a| a.node_count()).sum::<usize>(),
+        }
+    }
+
+    /// Collect the terms of a sum in powers of `x`, such that `a*x+b*x+c` becomes `x*(a+b)+c`.
+    /// Only integer powers of `x` are collected. The expression should be expanded.
+    pub fn collect(
+        &self,
+        x: Identifier,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let AtomView::Add(a) = self else {
+            out.from_view(self);
+            return;
+        };
+
+        let power = |f: AtomView<P>| -> Option<i64> {
+            match f {
+                AtomView::Var(v) if v.get_name() == x => Some(1),
+                AtomView::Pow(p) => {
+                    let (base, exp) = p.get_base_exp();
+                    match (base, exp) {
+                        (AtomView::Var(v), AtomView::Num(n)) if v.get_name() == x => {
+                            match n.get_number_view() {
+                                BorrowedNumber::Natural(n, 1) => Some(n),
+                                _ => None,
+                            }
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }
+        };
+
+        // the coefficients of every power of `x`, sorted by power
+        let mut coeffs: Vec<(i64, OwnedAtom<P>)> = vec![];
+        for term in a.iter() {
+            let mut rest = OwnedAtom::<P>::new();
+            let pow = match term {
+                AtomView::Mul(m) if m.iter().any(|f| power(f).is_some()) => {
+                    let mut pow = 0;
+                    let mul = rest.transform_to_mul();
+                    for f in m.iter() {
+                        if let Some(p) = power(f) {
+                            pow = p;
+                        } else {
+                            mul.extend(f);
+                        }
+                    }
+                    mul.set_dirty(true);
+                    pow
+                }
+                _ => match power(term) {
+                    Some(p) => {
+                        rest.transform_to_num()
+                            .set_from_number(Number::Natural(1, 1));
+                        p
+                    }
+                    None => {
+                        rest.from_view(&term);
+                        0
+                    }
+                },
+            };
+
+            match coeffs.binary_search_by_key(&pow, |(p, _)| *p) {
+                Ok(i) => {
+                    let c = &mut coeffs[i].1;
+                    if !matches!(c.to_view(), AtomView::Add(_)) {
+                        let mut add = OwnedAtom::<P>::new();
+                        add.transform_to_add().extend(c.to_view());
+                        std::mem::swap(c, &mut add);
+                    }
+                    if let OwnedAtom::Add(add) = c {
+                        add.extend(rest.to_view());
+                        add.set_dirty(true);
+                    }
+                }
+                Err(i) => coeffs.insert(i, (pow, rest)),
+            }
+        }
+
+        let mut res = workspace.new_atom();
+        let add = res.get_mut().transform_to_add();
+        for (pow, c) in &coeffs {
+            let mut term = workspace.new_atom();
+            let mul = term.get_mut().transform_to_mul();
+            mul.extend(c.to_view());
+
+            if *pow != 0 {
+                let mut var = workspace.new_atom();
+                var.get_mut().transform_to_var().set_from_id(x);
+                let mut exp = workspace.new_atom();
+                exp.get_mut()
+                    .transform_to_num()
+                    .set_from_number(Number::Natural(*pow, 1));
+                let mut p = workspace.new_atom();
+                let pow = p.get_mut().transform_to_pow();
+                pow.set_from_base_and_exp(var.get().to_view(), exp.get().to_view());
+                pow.set_dirty(true);
+                mul.extend(p.get().to_view());
+            }
+            mul.set_dirty(true);
+            add.extend(term.get().to_view());
+        }
+        add.set_dirty(true);
+
+        res.get().to_view().normalize(workspace, state, out);
+    }
+
+    /// Search for the smallest representation of the expression, measured in
+    /// [`AtomView::node_count`], by chaining the transformations in `options`.
+    /// Ties are broken by the canonical ordering of expressions, so that the
+    /// result does not depend on the order in which candidates are found.
+    pub fn simplify(
+        &self,
+        options: &SimplifyOptions<'_, P>,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        out.from_view(self);
+        let mut best_size = self.node_count();
+
+        // all expressions found so far, where the last ones form the frontier
+        let mut start = OwnedAtom::new();
+        start.from_view(self);
+        let mut seen = vec![start];
+        let mut frontier = 0..1;
+
+        for _ in 0..options.passes.len() {
+            for i in frontier.clone() {
+                for pass in &options.passes {
+                    let mut candidate = OwnedAtom::new();
+                    pass.apply(seen[i].to_view(), workspace, state, &mut candidate);
+
+                    let size = candidate.to_view().node_count();
+                    if size > options.max_size
+                        || seen.iter().any(|s| s.to_view() == candidate.to_view())
+                    {
+                        continue;
+                    }
+
+                    if size < best_size
+                        || size == best_size && candidate.to_view().cmp(&out.to_view()).is_lt()
+                    {
+                        out.from_view(&candidate.to_view());
+                        best_size = size;
+                    }
+
+                    seen.push(candidate);
+                }
+            }
+
+            if frontier.end == seen.len() {
+                break;
+            }
+            frontier = frontier.end..seen.len();
+        }
+    }
+}
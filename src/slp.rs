@@ -0,0 +1,443 @@
+use std::fmt::Write;
+
+use ahash::{HashMap, HashMapExt};
+
+use crate::{
+    poly::{
+        horner::HornerScheme, polynomial::MultivariatePolynomial, positivity::OrderedRing, Exponent,
+    },
+    representations::{
+        number::{BorrowedNumber, ConvertToRing},
+        Add, Atom, AtomView, Fun, Identifier, Mul, Num, Pow, Var,
+    },
+    rings::{Field, Ring, RingPrinter},
+    state::State,
+};
+
+/// An instruction of a straight-line program. The arguments
+/// refer to the results of earlier instructions.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    /// A constant from the constant table.
+    Constant(usize),
+    /// The input variable with the given index.
+    Variable(usize),
+    Add(Vec<usize>),
+    Mul(Vec<usize>),
+    Pow(usize, u32),
+    Inv(usize),
+    Fun(Identifier, Vec<usize>),
+}
+
+/// A function that evaluates a function in a straight-line program.
+pub type EvaluationFn<F> = Box<dyn Fn(&[<F as Ring>::Element]) -> <F as Ring>::Element>;
+
+/// The evaluation function of a conditional `f(c, a, b)`, which yields `a`
+/// if `c` is positive and `b` otherwise.
+pub fn conditional_evaluation_fn<F: OrderedRing + 'static>(field: F) -> EvaluationFn<F> {
+    Box::new(move |args: &[F::Element]| {
+        if !F::is_zero(&args[0]) && !field.is_negative(&args[0]) {
+            args[1].clone()
+        } else {
+            args[2].clone()
+        }
+    })
+}
+
+/// A straight-line program: a list of instructions without branches, where every
+/// instruction may use the results of earlier ones. Identical instructions are
+/// only stored once and instructions with constant arguments are folded.
+pub struct Slp<F: Ring> {
+    pub field: F,
+    pub constants: Vec<F::Element>,
+    pub instructions: Vec<Instruction>,
+    /// The names of the input variables, if known.
+    pub variables: Vec<Identifier>,
+    pub outputs: Vec<usize>,
+    cse: HashMap<Instruction, usize>,
+}
+
+impl<F: Ring> Slp<F> {
+    pub fn new(field: F) -> Self {
+        Slp {
+            field,
+            constants: vec![],
+            instructions: vec![],
+            variables: vec![],
+            outputs: vec![],
+            cse: HashMap::new(),
+        }
+    }
+
+    /// Add an instruction, or return the index of an identical earlier instruction.
+    fn push(&mut self, instr: Instruction) -> usize {
+        if let Some(i) = self.cse.get(&instr) {
+            return *i;
+        }
+
+        let i = self.instructions.len();
+        self.instructions.push(instr.clone());
+        self.cse.insert(instr, i);
+        i
+    }
+
+    /// Get the value of the instruction `i` if it is a constant.
+    pub fn get_constant(&self, i: usize) -> Option<&F::Element> {
+        match &self.instructions[i] {
+            Instruction::Constant(c) => Some(&self.constants[*c]),
+            _ => None,
+        }
+    }
+
+    pub fn push_constant(&mut self, c: F::Element) -> usize {
+        self.constants.push(c);
+        self.push(Instruction::Constant(self.constants.len() - 1))
+    }
+
+    pub fn push_variable(&mut self, var: usize) -> usize {
+        self.push(Instruction::Variable(var))
+    }
+
+    /// Add the constant `c` that is the result of folding the constants `args`,
+    /// reusing the argument if there is only one.
+    fn fold_constant(&mut self, c: F::Element, args: &[usize]) -> usize {
+        if args.len() == 1 {
+            args[0]
+        } else {
+            self.push_constant(c)
+        }
+    }
+
+    /// Add the sum of the arguments, folding all constant arguments into one.
+    pub fn push_add(&mut self, args: &[usize]) -> usize {
+        let mut constant = self.field.zero();
+        let mut new_args = vec![];
+        let mut const_args = vec![];
+        for &a in args {
+            if let Some(c) = self.get_constant(a) {
+                self.field.add_assign(&mut constant, c);
+                const_args.push(a);
+            } else {
+                new_args.push(a);
+            }
+        }
+
+        if new_args.is_empty() {
+            return self.fold_constant(constant, &const_args);
+        }
+
+        if !F::is_zero(&constant) {
+            let c = self.fold_constant(constant, &const_args);
+            new_args.push(c);
+        }
+
+        if new_args.len() == 1 {
+            return new_args[0];
+        }
+
+        new_args.sort_unstable();
+        self.push(Instruction::Add(new_args))
+    }
+
+    /// Add the product of the arguments, folding all constant arguments into one.
+    pub fn push_mul(&mut self, args: &[usize]) -> usize {
+        let mut constant = self.field.one();
+        let mut new_args = vec![];
+        let mut const_args = vec![];
+        for &a in args {
+            if let Some(c) = self.get_constant(a) {
+                self.field.mul_assign(&mut constant, c);
+                const_args.push(a);
+            } else {
+                new_args.push(a);
+            }
+        }
+
+        if new_args.is_empty() || F::is_zero(&constant) {
+            return self.fold_constant(constant, &const_args);
+        }
+
+        if !self.field.is_one(&constant) {
+            let c = self.fold_constant(constant, &const_args);
+            new_args.push(c);
+        }
+
+        if new_args.len() == 1 {
+            return new_args[0];
+        }
+
+        new_args.sort_unstable();
+        self.push(Instruction::Mul(new_args))
+    }
+
+    pub fn push_pow(&mut self, base: usize, exp: u32) -> usize {
+        match exp {
+            0 => self.push_constant(self.field.one()),
+            1 => base,
+            _ => {
+                if let Some(c) = self.get_constant(base) {
+                    let r = self.field.pow(c, exp as u64);
+                    self.push_constant(r)
+                } else {
+                    self.push(Instruction::Pow(base, exp))
+                }
+            }
+        }
+    }
+
+    pub fn push_fun(&mut self, name: Identifier, args: &[usize]) -> usize {
+        self.push(Instruction::Fun(name, args.to_vec()))
+    }
+
+    /// Mark the result of instruction `i` as an output of the program.
+    pub fn add_output(&mut self, i: usize) {
+        self.outputs.push(i);
+    }
+
+    /// Add the instructions of a Horner scheme and return the index of the result.
+    pub fn push_horner_scheme(&mut self, scheme: &HornerScheme<F>) -> usize {
+        match scheme {
+            HornerScheme::Node { var, pow, mul, add } => {
+                let x = self.push_variable(*var);
+                let x_pow = self.push_pow(x, *pow);
+                let m = self.push_horner_scheme(mul);
+                let r = self.push_mul(&[x_pow, m]);
+
+                if let Some(add) = add {
+                    let a = self.push_horner_scheme(add);
+                    self.push_add(&[r, a])
+                } else {
+                    r
+                }
+            }
+            HornerScheme::Leaf(c) => self.push_constant(c.clone()),
+        }
+    }
+
+    /// Convert a polynomial to a straight-line program with a single output,
+    /// using a Horner scheme in the variable order of the polynomial.
+    pub fn from_polynomial<E: Exponent>(poly: &MultivariatePolynomial<F, E>) -> Self {
+        let mut slp = Slp::new(poly.field);
+        if let Some(var_map) = &poly.var_map {
+            slp.variables = var_map.to_vec();
+        }
+
+        let order: Vec<_> = (0..poly.nvars).collect();
+        let r = slp.push_horner_scheme(&poly.to_horner_scheme(&order));
+        slp.add_output(r);
+        slp
+    }
+
+    /// Count the number of additions and multiplications, where a power `x^n` is
+    /// counted as `n-1` multiplications.
+    pub fn op_count(&self) -> usize {
+        self.instructions
+            .iter()
+            .map(|i| match i {
+                Instruction::Add(a) | Instruction::Mul(a) => a.len() - 1,
+                Instruction::Pow(_, e) => *e as usize - 1,
+                Instruction::Inv(_) => 1,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Write the program as a list of C-like statements `Z<i> = ...;`, followed by
+    /// the assignment of the outputs to `out[i]`.
+    pub fn to_code(&self, state: &State) -> String {
+        let mut code = String::new();
+        for (i, instr) in self.instructions.iter().enumerate() {
+            write!(code, "Z{} = ", i).unwrap();
+            match instr {
+                Instruction::Constant(c) => write!(
+                    code,
+                    "{}",
+                    RingPrinter {
+                        ring: &self.field,
+                        element: &self.constants[*c]
+                    }
+                ),
+                Instruction::Variable(v) => match self.variables.get(*v) {
+                    Some(id) => write!(code, "{}", state.get_name(*id).unwrap()),
+                    None => write!(code, "x{}", v),
+                },
+                Instruction::Add(a) => write!(
+                    code,
+                    "{}",
+                    a.iter()
+                        .map(|x| format!("Z{}", x))
+                        .collect::<Vec<_>>()
+                        .join("+")
+                ),
+                Instruction::Mul(a) => write!(
+                    code,
+                    "{}",
+                    a.iter()
+                        .map(|x| format!("Z{}", x))
+                        .collect::<Vec<_>>()
+                        .join("*")
+                ),
+                Instruction::Pow(b, e) => write!(code, "pow(Z{}, {})", b, e),
+                Instruction::Inv(b) => write!(code, "1/Z{}", b),
+                Instruction::Fun(name, a) => write!(
+                    code,
+                    "{}({})",
+                    state.get_name(*name).unwrap(),
+                    a.iter()
+                        .map(|x| format!("Z{}", x))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            }
+            .unwrap();
+            code.push_str(";\n");
+        }
+
+        for (i, o) in self.outputs.iter().enumerate() {
+            writeln!(code, "out[{}] = Z{};", i, o).unwrap();
+        }
+
+        code
+    }
+}
+
+impl<F: Field> Slp<F> {
+    pub fn push_inv(&mut self, base: usize) -> usize {
+        if let Some(c) = self.get_constant(base) {
+            let r = self.field.inv(c);
+            self.push_constant(r)
+        } else {
+            self.push(Instruction::Inv(base))
+        }
+    }
+
+    /// Evaluate the program, where the input variable `i` has value `values[i]`.
+    /// Functions are evaluated using `functions`.
+    pub fn evaluate(
+        &self,
+        values: &[F::Element],
+        functions: &HashMap<Identifier, EvaluationFn<F>>,
+    ) -> Result<Vec<F::Element>, String> {
+        let mut res: Vec<F::Element> = Vec::with_capacity(self.instructions.len());
+        for instr in &self.instructions {
+            let r = match instr {
+                Instruction::Constant(c) => self.constants[*c].clone(),
+                Instruction::Variable(v) => values
+                    .get(*v)
+                    .ok_or_else(|| format!("No value for variable {}", v))?
+                    .clone(),
+                Instruction::Add(a) => {
+                    let mut r = res[a[0]].clone();
+                    for x in &a[1..] {
+                        self.field.add_assign(&mut r, &res[*x]);
+                    }
+                    r
+                }
+                Instruction::Mul(a) => {
+                    let mut r = res[a[0]].clone();
+                    for x in &a[1..] {
+                        self.field.mul_assign(&mut r, &res[*x]);
+                    }
+                    r
+                }
+                Instruction::Pow(b, e) => self.field.pow(&res[*b], *e as u64),
+                Instruction::Inv(b) => {
+                    if F::is_zero(&res[*b]) {
+                        return Err("Division by zero".to_owned());
+                    }
+                    self.field.inv(&res[*b])
+                }
+                Instruction::Fun(name, a) => {
+                    let f = functions
+                        .get(name)
+                        .ok_or_else(|| format!("No evaluation function for {:?}", name))?;
+                    let args: Vec<_> = a.iter().map(|x| res[*x].clone()).collect();
+                    f(&args)
+                }
+            };
+            res.push(r);
+        }
+
+        Ok(self.outputs.iter().map(|o| res[*o].clone()).collect())
+    }
+
+    /// Add the instructions of an expression and return the index of the result.
+    /// New variables are added to the list of variables.
+    pub fn push_atom<P: Atom>(&mut self, atom: AtomView<'_, P>) -> Result<usize, String>
+    where
+        F: ConvertToRing,
+    {
+        match atom {
+            AtomView::Num(n) => {
+                let n = n.get_number_view();
+                if let BorrowedNumber::RationalPolynomial(_) = n {
+                    return Err("Rational polynomial coefficients are not supported".to_owned());
+                }
+                Ok(self.push_constant(self.field.element_from_borrowed_number(n)))
+            }
+            AtomView::Var(v) => {
+                let id = v.get_name();
+                let index = match self.variables.iter().position(|x| *x == id) {
+                    Some(i) => i,
+                    None => {
+                        self.variables.push(id);
+                        self.variables.len() - 1
+                    }
+                };
+                Ok(self.push_variable(index))
+            }
+            AtomView::Fun(f) => {
+                let args = f
+                    .iter()
+                    .map(|a| self.push_atom(a))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(self.push_fun(f.get_name(), &args))
+            }
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                let AtomView::Num(n) = exp else {
+                    return Err("Exponent must be a number".to_owned());
+                };
+                let BorrowedNumber::Natural(e, 1) = n.get_number_view() else {
+                    return Err("Exponent must be a small integer".to_owned());
+                };
+                if e.unsigned_abs() > u32::MAX as u64 {
+                    return Err("Exponent must be a small integer".to_owned());
+                }
+
+                let b = self.push_atom(base)?;
+                let r = self.push_pow(b, e.unsigned_abs() as u32);
+                if e < 0 {
+                    Ok(self.push_inv(r))
+                } else {
+                    Ok(r)
+                }
+            }
+            AtomView::Mul(m) => {
+                let args = m
+                    .iter()
+                    .map(|a| self.push_atom(a))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(self.push_mul(&args))
+            }
+            AtomView::Add(a) => {
+                let args = a
+                    .iter()
+                    .map(|a| self.push_atom(a))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(self.push_add(&args))
+            }
+        }
+    }
+
+    /// Convert an expression to a straight-line program with a single output.
+    pub fn from_atom<P: Atom>(atom: AtomView<'_, P>, field: F) -> Result<Self, String>
+    where
+        F: ConvertToRing,
+    {
+        let mut slp = Slp::new(field);
+        let r = slp.push_atom(atom)?;
+        slp.add_output(r);
+        Ok(slp)
+    }
+}
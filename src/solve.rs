@@ -0,0 +1,685 @@
+//! Solving equations in one variable.
+//!
+//! Polynomial equations up to degree four are solved in radicals. Polynomials with
+//! rational coefficients are factored over the integers first, so that higher degrees
+//! can be solved if all factors have at most degree four. An equation `c*f(g(x)) + d = 0`
+//! is reduced to `g(x) = f^-1(-d/c)` if an inverse of `f` is declared with
+//! [`State::set_inverse_function`].
+
+use std::collections::BTreeMap;
+
+use crate::{
+    derivative::depends_on,
+    poly::polynomial::MultivariatePolynomial,
+    representations::{
+        number::{BorrowedNumber, Number},
+        Add, Atom, AtomView, Fun, Identifier, Mul, Num, OwnedAdd, OwnedAtom, OwnedFun, OwnedMul,
+        OwnedNum, OwnedPow, Pow, Var,
+    },
+    rings::{
+        rational::{Rational, RationalField},
+        rationalized_polynomial::RationalizedPolynomial,
+    },
+    state::{Assumptions, InverseDomain, ResettableBuffer, State, Workspace},
+};
+
+/// An equation `lhs = rhs`.
+pub struct Equation<P: Atom> {
+    pub lhs: OwnedAtom<P>,
+    pub rhs: OwnedAtom<P>,
+}
+
+/// A solution of an equation.
+pub struct Solution<P: Atom> {
+    pub value: OwnedAtom<P>,
+    /// Expressions and the assumptions they have to satisfy for the solution to be
+    /// valid and for the list of solutions to be complete. For example, `x = exp(c)` solves
+    /// `log(x) = c` only for real `c`, and `x = log(c)` is the only solution of `exp(x) = c`
+    /// with a real `x`.
+    pub conditions: Vec<(OwnedAtom<P>, Assumptions)>,
+}
+
+impl<P: Atom> Equation<P> {
+    pub fn new(lhs: OwnedAtom<P>, rhs: OwnedAtom<P>) -> Self {
+        Equation { lhs, rhs }
+    }
+
+    /// Solve the equation for `x`. Roots of a polynomial with multiplicity are only
+    /// returned once, and the roots of a polynomial with rational coefficients that is
+    /// irreducible over the integers are returned as radicals with principal branches.
+    pub fn solve(
+        &self,
+        x: Identifier,
+        workspace: &Workspace<P>,
+        state: &State,
+    ) -> Result<Vec<Solution<P>>, String> {
+        let b = Builder { workspace, state };
+        let diff = b.add(&[&self.lhs, &b.scale(-1, 1, &self.rhs)]);
+        solve_zero(&diff, x, &b)
+    }
+}
+
+/// Construct normalized expressions.
+struct Builder<'a, P: Atom> {
+    workspace: &'a Workspace<P>,
+    state: &'a State,
+}
+
+impl<'a, P: Atom> Builder<'a, P> {
+    fn normalize(&self, a: &OwnedAtom<P>) -> OwnedAtom<P> {
+        let mut out = OwnedAtom::new();
+        a.to_view().normalize(self.workspace, self.state, &mut out);
+        out
+    }
+
+    fn num(&self, num: i64, den: i64) -> OwnedAtom<P> {
+        self.number(Number::Natural(num, den))
+    }
+
+    fn number(&self, n: Number) -> OwnedAtom<P> {
+        let mut out = OwnedAtom::<P>::new();
+        out.transform_to_num().set_from_number(n);
+        out
+    }
+
+    fn add(&self, args: &[&OwnedAtom<P>]) -> OwnedAtom<P> {
+        if args.is_empty() {
+            return self.num(0, 1);
+        }
+
+        let mut a = OwnedAtom::<P>::new();
+        let add = a.transform_to_add();
+        for x in args {
+            add.extend(x.to_view());
+        }
+        add.set_dirty(true);
+        self.normalize(&a)
+    }
+
+    fn mul(&self, args: &[&OwnedAtom<P>]) -> OwnedAtom<P> {
+        if args.is_empty() {
+            return self.num(1, 1);
+        }
+
+        let mut a = OwnedAtom::<P>::new();
+        let mul = a.transform_to_mul();
+        for x in args {
+            mul.extend(x.to_view());
+        }
+        mul.set_dirty(true);
+        self.normalize(&a)
+    }
+
+    /// Compute `base^(num/den)`.
+    fn pow(&self, base: &OwnedAtom<P>, num: i64, den: i64) -> OwnedAtom<P> {
+        let exp = self.num(num, den);
+        let mut a = OwnedAtom::<P>::new();
+        let pow = a.transform_to_pow();
+        pow.set_from_base_and_exp(base.to_view(), exp.to_view());
+        pow.set_dirty(true);
+        self.normalize(&a)
+    }
+
+    /// Compute `c*a` for a rational `c`.
+    fn scale(&self, num: i64, den: i64, a: &OwnedAtom<P>) -> OwnedAtom<P> {
+        self.mul(&[&self.num(num, den), a])
+    }
+
+    fn div(&self, a: &OwnedAtom<P>, b: &OwnedAtom<P>) -> OwnedAtom<P> {
+        self.mul(&[a, &self.pow(b, -1, 1)])
+    }
+
+    fn fun(&self, name: Identifier, arg: &OwnedAtom<P>) -> OwnedAtom<P> {
+        let mut a = OwnedAtom::<P>::new();
+        let f = a.transform_to_fun();
+        f.set_from_name(name);
+        f.add_arg(arg.to_view());
+        f.set_dirty(true);
+        self.normalize(&a)
+    }
+
+    fn copy(&self, a: AtomView<'_, P>) -> OwnedAtom<P> {
+        let mut out = OwnedAtom::new();
+        out.from_view(&a);
+        out
+    }
+
+    fn expand(&self, a: &OwnedAtom<P>) -> OwnedAtom<P> {
+        let mut out = OwnedAtom::new();
+        a.to_view().expand(self.workspace, self.state, &mut out);
+        out
+    }
+
+    fn is_zero(&self, a: &OwnedAtom<P>) -> bool {
+        matches!(self.expand(a).to_view(), AtomView::Num(n) if n.is_zero())
+    }
+}
+
+fn solve_zero<P: Atom>(
+    expr: &OwnedAtom<P>,
+    x: Identifier,
+    b: &Builder<'_, P>,
+) -> Result<Vec<Solution<P>>, String> {
+    let expanded = b.expand(expr);
+    let Some(coeffs) = polynomial_coefficients(expanded.to_view(), x, b)? else {
+        return solve_transcendental(expanded.to_view(), x, b);
+    };
+
+    // the degree of the polynomial and thus its roots are only correct if a
+    // symbolic leading coefficient does not vanish
+    let conditions = match coeffs.last_key_value() {
+        Some((&d, lc)) if d > 0 && to_rational(lc.to_view()).is_none() => {
+            vec![(
+                b.copy(lc.to_view()),
+                Assumptions {
+                    nonzero: true,
+                    ..Assumptions::default()
+                },
+            )]
+        }
+        _ => vec![],
+    };
+
+    Ok(solve_polynomial(coeffs, b)?
+        .into_iter()
+        .map(|value| Solution {
+            value,
+            conditions: conditions
+                .iter()
+                .map(|(c, a)| (b.copy(c.to_view()), *a))
+                .collect(),
+        })
+        .collect())
+}
+
+/// Get the power of `x` in a factor, or `None` if the factor is not a polynomial in `x`.
+fn power_of<P: Atom>(factor: AtomView<'_, P>, x: Identifier) -> Option<usize> {
+    match factor {
+        AtomView::Var(v) if v.get_name() == x => Some(1),
+        AtomView::Pow(p) => {
+            let (base, exp) = p.get_base_exp();
+            match (base, exp) {
+                (AtomView::Var(v), AtomView::Num(n)) if v.get_name() == x => {
+                    match n.get_number_view() {
+                        BorrowedNumber::Natural(n, 1) if n > 0 => Some(n as usize),
+                        _ => None,
+                    }
+                }
+                _ if depends_on(factor, x) => None,
+                _ => Some(0),
+            }
+        }
+        _ if depends_on(factor, x) => None,
+        _ => Some(0),
+    }
+}
+
+/// Get the non-zero coefficients of the powers of `x` in an expanded expression, or `None`
+/// if the expression is not a polynomial in `x`. The zero expression has no coefficients.
+/// Returns an error if the degree exceeds the maximal exponent of a polynomial.
+fn polynomial_coefficients<P: Atom>(
+    expr: AtomView<'_, P>,
+    x: Identifier,
+    b: &Builder<'_, P>,
+) -> Result<Option<BTreeMap<usize, OwnedAtom<P>>>, String> {
+    let too_high = || {
+        format!(
+            "The degree of the polynomial in the variable exceeds {}",
+            u16::MAX
+        )
+    };
+
+    let terms: Vec<_> = match expr {
+        AtomView::Add(a) => a.iter().collect(),
+        _ => vec![expr],
+    };
+
+    let mut coeffs: BTreeMap<usize, Vec<OwnedAtom<P>>> = BTreeMap::new();
+    for t in terms {
+        let (pow, coeff) = match t {
+            AtomView::Mul(m) => {
+                let mut pow = 0usize;
+                let mut rest = vec![];
+                for f in m.iter() {
+                    match power_of(f, x) {
+                        None => return Ok(None),
+                        Some(0) => rest.push(b.copy(f)),
+                        Some(p) => pow = pow.checked_add(p).ok_or_else(too_high)?,
+                    }
+                }
+
+                let rest: Vec<_> = rest.iter().collect();
+                (pow, b.mul(&rest))
+            }
+            _ => match power_of(t, x) {
+                None => return Ok(None),
+                Some(0) => (0, b.copy(t)),
+                Some(p) => (p, b.num(1, 1)),
+            },
+        };
+
+        coeffs.entry(pow).or_default().push(coeff);
+    }
+
+    let coeffs: BTreeMap<_, _> = coeffs
+        .into_iter()
+        .map(|(pow, c)| match c.len() {
+            1 => (pow, c.into_iter().next().unwrap()),
+            _ => (pow, b.add(&c.iter().collect::<Vec<_>>())),
+        })
+        .filter(|(_, c)| !b.is_zero(c))
+        .collect();
+
+    match coeffs.last_key_value() {
+        Some((&d, _)) if d > u16::MAX as usize => Err(too_high()),
+        _ => Ok(Some(coeffs)),
+    }
+}
+
+/// Get the rational value of a number.
+fn to_rational<P: Atom>(a: AtomView<'_, P>) -> Option<Rational> {
+    match a {
+        AtomView::Num(n) => match n.get_number_view() {
+            BorrowedNumber::Natural(n, d) => Some(Rational::Natural(n, d)),
+            BorrowedNumber::Large(r) => Some(Rational::Large(r.to_rat())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn solve_polynomial<P: Atom>(
+    coeffs: BTreeMap<usize, OwnedAtom<P>>,
+    b: &Builder<'_, P>,
+) -> Result<Vec<OwnedAtom<P>>, String> {
+    let (Some((&shift, _)), Some((&degree, _))) =
+        (coeffs.first_key_value(), coeffs.last_key_value())
+    else {
+        return Err("The equation holds for all values".to_owned());
+    };
+    if degree == 0 {
+        return Err("The equation does not depend on the variable".to_owned());
+    }
+
+    // divide out the powers of x
+    let mut roots = vec![];
+    if shift > 0 {
+        roots.push(b.num(0, 1));
+    }
+    let degree = degree - shift;
+    if degree == 0 {
+        return Ok(roots);
+    }
+
+    let rational: Option<Vec<_>> = coeffs
+        .iter()
+        .map(|(pow, c)| Some((pow - shift, to_rational(c.to_view())?)))
+        .collect();
+    match rational {
+        Some(rational) if degree > 1 => {
+            let mut p = MultivariatePolynomial::<_, u16>::new(
+                1,
+                RationalField::new(),
+                Some(rational.len()),
+                None,
+            );
+            for (pow, c) in rational {
+                p.append_monomial(c, &[pow as u16]);
+            }
+
+            let p = RationalizedPolynomial::from_rational_polynomial(&p).numerator;
+            for (f, _) in p.factor_univariate(0).map_err(|e| e.to_string())? {
+                if f.is_constant() {
+                    continue;
+                }
+
+                let mut fc: Vec<_> = (0..=f.degree(0)).map(|_| b.num(0, 1)).collect();
+                for t in &f {
+                    fc[t.exponents[0] as usize] = b.number(t.coefficient.to_rational().into());
+                }
+                roots.extend(solve_radicals(&fc, b)?);
+            }
+        }
+        _ => {
+            let mut dense: Vec<_> = (0..=degree).map(|_| b.num(0, 1)).collect();
+            for (pow, c) in coeffs {
+                dense[pow - shift] = c;
+            }
+            roots.extend(solve_radicals(&dense, b)?);
+        }
+    }
+
+    Ok(roots)
+}
+
+/// Solve `c[0] + c[1]*x + ... + c[n]*x^n = 0` with `n <= 4` in radicals.
+fn solve_radicals<P: Atom>(
+    c: &[OwnedAtom<P>],
+    b: &Builder<'_, P>,
+) -> Result<Vec<OwnedAtom<P>>, String> {
+    match c.len() - 1 {
+        1 => Ok(vec![b.div(&b.scale(-1, 1, &c[0]), &c[1])]),
+        2 => {
+            let disc = b.add(&[&b.pow(&c[1], 2, 1), &b.mul(&[&b.num(-4, 1), &c[2], &c[0]])]);
+            let den = b.scale(2, 1, &c[2]);
+            let neg_b = b.scale(-1, 1, &c[1]);
+
+            if b.is_zero(&disc) {
+                return Ok(vec![b.div(&neg_b, &den)]);
+            }
+
+            let sqrt = b.pow(&disc, 1, 2);
+            Ok([b.scale(-1, 1, &sqrt), sqrt]
+                .iter()
+                .map(|s| b.div(&b.add(&[&neg_b, s]), &den))
+                .collect())
+        }
+        3 => {
+            // substitute x = t - c2/(3c3) to get t^3 + p*t + q = 0
+            let a = &c[3];
+            let a2 = b.pow(a, 2, 1);
+            let p = b.div(
+                &b.add(&[
+                    &b.mul(&[&b.num(3, 1), a, &c[1]]),
+                    &b.scale(-1, 1, &b.pow(&c[2], 2, 1)),
+                ]),
+                &b.scale(3, 1, &a2),
+            );
+            let q = b.div(
+                &b.add(&[
+                    &b.scale(2, 1, &b.pow(&c[2], 3, 1)),
+                    &b.mul(&[&b.num(-9, 1), a, &c[2], &c[1]]),
+                    &b.mul(&[&b.num(27, 1), &a2, &c[0]]),
+                ]),
+                &b.scale(27, 1, &b.pow(a, 3, 1)),
+            );
+            let shift = b.div(&b.scale(-1, 1, &c[2]), &b.scale(3, 1, a));
+
+            Ok(solve_depressed_cubic(&p, &q, b)
+                .iter()
+                .map(|t| b.add(&[t, &shift]))
+                .collect())
+        }
+        4 => {
+            // substitute x = y - c3/(4c4) to get y^4 + p*y^2 + q*y + r = 0
+            let bb = b.div(&c[3], &c[4]);
+            let cc = b.div(&c[2], &c[4]);
+            let dd = b.div(&c[1], &c[4]);
+            let ee = b.div(&c[0], &c[4]);
+
+            let p = b.add(&[&cc, &b.scale(-3, 8, &b.pow(&bb, 2, 1))]);
+            let q = b.add(&[
+                &dd,
+                &b.mul(&[&b.num(-1, 2), &bb, &cc]),
+                &b.scale(1, 8, &b.pow(&bb, 3, 1)),
+            ]);
+            let r = b.add(&[
+                &ee,
+                &b.mul(&[&b.num(-1, 4), &bb, &dd]),
+                &b.mul(&[&b.num(1, 16), &b.pow(&bb, 2, 1), &cc]),
+                &b.scale(-3, 256, &b.pow(&bb, 4, 1)),
+            ]);
+            let shift = b.scale(-1, 4, &bb);
+
+            let mut ys = vec![];
+            if b.is_zero(&q) {
+                // biquadratic: y^2 = z with z^2 + p*z + r = 0
+                for z in solve_radicals(&[r, p, b.num(1, 1)], b)? {
+                    let s = b.pow(&z, 1, 2);
+                    ys.push(b.scale(-1, 1, &s));
+                    ys.push(s);
+                }
+            } else {
+                // Ferrari: 8m^3 + 8p*m^2 + (2p^2 - 8r)*m - q^2 = 0 has a non-zero root m
+                let resolvent = [
+                    b.scale(-1, 1, &b.pow(&q, 2, 1)),
+                    b.add(&[&b.scale(2, 1, &b.pow(&p, 2, 1)), &b.scale(-8, 1, &r)]),
+                    b.scale(8, 1, &p),
+                    b.num(8, 1),
+                ];
+                let m = solve_radicals(&resolvent, b)?.swap_remove(0);
+                let s = b.pow(&b.scale(2, 1, &m), 1, 2);
+
+                // y = (s1*s + s2*sqrt(-(2p + 2m + 2*s1*q/s)))/2
+                for s1 in [1, -1] {
+                    let t = b.add(&[
+                        &b.scale(-2, 1, &p),
+                        &b.scale(-2, 1, &m),
+                        &b.scale(-2 * s1, 1, &b.div(&q, &s)),
+                    ]);
+                    let sqrt_t = b.pow(&t, 1, 2);
+                    for s2 in [1, -1] {
+                        ys.push(b.scale(
+                            1,
+                            2,
+                            &b.add(&[&b.scale(s1, 1, &s), &b.scale(s2, 1, &sqrt_t)]),
+                        ));
+                    }
+                }
+            }
+
+            Ok(ys.iter().map(|y| b.add(&[y, &shift])).collect())
+        }
+        d => Err(format!(
+            "Cannot solve a polynomial of degree {} in radicals",
+            d
+        )),
+    }
+}
+
+/// Solve `t^3 + p*t + q = 0` with Cardano's formula.
+fn solve_depressed_cubic<P: Atom>(
+    p: &OwnedAtom<P>,
+    q: &OwnedAtom<P>,
+    b: &Builder<'_, P>,
+) -> Vec<OwnedAtom<P>> {
+    let p_zero = b.is_zero(p);
+    if p_zero && b.is_zero(q) {
+        return vec![b.num(0, 1)];
+    }
+
+    // the cube roots of unity
+    let sqrt3 = b.scale(1, 2, &b.pow(&b.num(-3, 1), 1, 2));
+    let w1 = b.add(&[&b.num(-1, 2), &sqrt3]);
+    let w2 = b.add(&[&b.num(-1, 2), &b.scale(-1, 1, &sqrt3)]);
+
+    // C^3 = -q/2 + sqrt(q^2/4 + p^3/27), which is non-zero if p is non-zero
+    let c = if p_zero {
+        b.pow(&b.scale(-1, 1, q), 1, 3)
+    } else {
+        let d = b.add(&[
+            &b.scale(1, 4, &b.pow(q, 2, 1)),
+            &b.scale(1, 27, &b.pow(p, 3, 1)),
+        ]);
+        b.pow(&b.add(&[&b.scale(-1, 2, q), &b.pow(&d, 1, 2)]), 1, 3)
+    };
+
+    [b.num(1, 1), w1, w2]
+        .iter()
+        .map(|w| {
+            let wc = b.mul(&[w, &c]);
+            if p_zero {
+                wc
+            } else {
+                // t = w*C - p/(3*w*C)
+                let corr = b.mul(&[&b.num(-1, 3), p, &b.pow(&wc, -1, 1)]);
+                b.add(&[&wc, &corr])
+            }
+        })
+        .collect()
+}
+
+/// Convert the domain of an inverse function to assumptions.
+fn domain_assumptions(domain: InverseDomain) -> Option<Assumptions> {
+    match domain {
+        InverseDomain::All => Some(Assumptions::default()),
+        InverseDomain::Real => Some(Assumptions {
+            real: true,
+            ..Default::default()
+        }),
+        InverseDomain::Positive => Some(
+            Assumptions {
+                positive: true,
+                ..Default::default()
+            }
+            .complete(),
+        ),
+        InverseDomain::None => None,
+    }
+}
+
+/// Solve `c*f(g(x)) + d = 0` by inverting `f`.
+fn solve_transcendental<P: Atom>(
+    expr: AtomView<'_, P>,
+    x: Identifier,
+    b: &Builder<'_, P>,
+) -> Result<Vec<Solution<P>>, String> {
+    let terms: Vec<_> = match expr {
+        AtomView::Add(a) => a.iter().collect(),
+        _ => vec![expr],
+    };
+    let (dependent, rest): (Vec<_>, Vec<_>) = terms.into_iter().partition(|t| depends_on(*t, x));
+
+    let [term] = dependent[..] else {
+        return Err("Cannot isolate the variable".to_owned());
+    };
+
+    let (f, coeff) = match term {
+        AtomView::Fun(f) => (f, b.num(1, 1)),
+        AtomView::Mul(m) => {
+            let (f, c): (Vec<_>, Vec<_>) = m.iter().partition(|t| depends_on(*t, x));
+            match f[..] {
+                [AtomView::Fun(f)] => {
+                    let c: Vec<_> = c.into_iter().map(|c| b.copy(c)).collect();
+                    (f, b.mul(&c.iter().collect::<Vec<_>>()))
+                }
+                _ => return Err("Cannot isolate the variable".to_owned()),
+            }
+        }
+        _ => return Err("Cannot isolate the variable".to_owned()),
+    };
+
+    if f.get_nargs() != 1 {
+        return Err("Cannot invert a function with multiple arguments".to_owned());
+    }
+    let arg = b.copy(f.iter().next().unwrap());
+    let name = f.get_name();
+    let fun_name = b.state.get_name(name).unwrap();
+
+    // f(arg) = value
+    let rest: Vec<_> = rest.into_iter().map(|t| b.copy(t)).collect();
+    let rest = b.add(&rest.iter().collect::<Vec<_>>());
+    let value = b.div(&b.scale(-1, 1, &rest), &coeff);
+
+    let (inner, condition) = if let Some((inverse, domain)) = b.state.get_inverse_function(name) {
+        // f(inverse(v)) = v always holds, but inverse(f(y)) = y only in the domain
+        let a = domain_assumptions(domain).ok_or_else(|| {
+            format!(
+                "The inverse of {} only gives a subset of the solutions",
+                fun_name
+            )
+        })?;
+        let inner = b.fun(inverse, &value);
+        (b.copy(inner.to_view()), (inner, a))
+    } else if let Some((g, domain)) = b.state.get_function_with_inverse(name) {
+        // f is the inverse of g, so that f(g(v)) = v if v is in the domain
+        let a = domain_assumptions(domain)
+            .ok_or_else(|| format!("{} is only an inverse on a subset of its domain", fun_name))?;
+        (b.fun(g, &value), (value, a))
+    } else {
+        return Err(format!("No inverse of {} is declared", fun_name));
+    };
+
+    let mut solutions = solve_zero(&b.add(&[&arg, &b.scale(-1, 1, &inner)]), x, b)?;
+
+    if condition.1 != Assumptions::default() {
+        for s in &mut solutions {
+            s.conditions
+                .insert(0, (b.copy(condition.0.to_view()), condition.1));
+        }
+    }
+
+    Ok(solutions)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::parse,
+        representations::{default::DefaultRepresentation, OwnedAtom},
+        state::{State, Workspace},
+    };
+
+    use super::Equation;
+
+    fn equation(
+        lhs: &str,
+        rhs: &str,
+        state: &mut State,
+        workspace: &Workspace<DefaultRepresentation>,
+    ) -> Equation<DefaultRepresentation> {
+        let lhs: OwnedAtom<DefaultRepresentation> =
+            parse(lhs).unwrap().to_atom(state, workspace).unwrap();
+        let rhs = parse(rhs).unwrap().to_atom(state, workspace).unwrap();
+        Equation::new(lhs, rhs)
+    }
+
+    #[test]
+    fn test_identically_zero() {
+        let mut state = State::new();
+        let workspace = Workspace::new();
+        let x = state.get_or_insert_var("x");
+
+        for (lhs, rhs) in [("x*(x+1)", "x^2+x"), ("0", "0"), ("a*x", "x*a")] {
+            let e = equation(lhs, rhs, &mut state, &workspace);
+            assert_eq!(
+                e.solve(x, &workspace, &state).err().as_deref(),
+                Some("The equation holds for all values")
+            );
+        }
+
+        let e = equation("x-x", "1", &mut state, &workspace);
+        assert_eq!(
+            e.solve(x, &workspace, &state).err().as_deref(),
+            Some("The equation does not depend on the variable")
+        );
+    }
+
+    #[test]
+    fn test_symbolic_leading_coefficient() {
+        let mut state = State::new();
+        let workspace = Workspace::new();
+        let x = state.get_or_insert_var("x");
+
+        let a = parse("a").unwrap().to_atom(&mut state, &workspace).unwrap();
+        let e = equation("a*x^2+b*x", "0", &mut state, &workspace);
+        let solutions = e.solve(x, &workspace, &state).unwrap();
+        assert_eq!(solutions.len(), 2);
+        for s in &solutions {
+            assert_eq!(s.conditions.len(), 1);
+            assert_eq!(s.conditions[0].0.to_view(), a.to_view());
+            assert!(s.conditions[0].1.nonzero);
+        }
+
+        let e = equation("2*x^2+b*x", "0", &mut state, &workspace);
+        let solutions = e.solve(x, &workspace, &state).unwrap();
+        assert!(solutions.iter().all(|s| s.conditions.is_empty()));
+    }
+
+    #[test]
+    fn test_high_degree() {
+        let mut state = State::new();
+        let workspace = Workspace::new();
+        let x = state.get_or_insert_var("x");
+
+        // the roots are 0, 1 and -1, and the zero coefficients are not stored
+        let e = equation("x^60002", "x^60000", &mut state, &workspace);
+        assert_eq!(e.solve(x, &workspace, &state).unwrap().len(), 3);
+
+        for lhs in ["x^1000000000", "x^70000+x"] {
+            let e = equation(lhs, "1", &mut state, &workspace);
+            let err = e.solve(x, &workspace, &state).err().unwrap();
+            assert!(err.starts_with("The degree of the polynomial"));
+        }
+    }
+}
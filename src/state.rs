@@ -1,25 +1,159 @@
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
+    io::{self, Read, Write},
     ops::{Deref, DerefMut},
+    sync::Mutex,
 };
 
 use ahash::{HashMap, HashMapExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use smartstring::alias::String;
 
 use crate::{
-    representations::{Atom, Identifier, OwnedAtom},
-    rings::finite_field::{FiniteField, FiniteFieldCore},
+    budget::{MemoryBudget, OutOfBudget},
+    interpolation::InterpolationTable,
+    poly::polynomial::MultivariatePolynomial,
+    representations::{
+        number::{BorrowedNumber, Number, PackedRationalNumberReader, PackedRationalNumberWriter},
+        Atom, AtomView, Identifier, OwnedAtom,
+    },
+    rings::{
+        algebraic_number::AlgebraicExtension,
+        finite_field::{FiniteField, FiniteFieldCore},
+        rational::{Rational, RationalField},
+        Field, Ring,
+    },
+    statistics::SymbolStatistics,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct FiniteFieldIndex(pub(crate) usize);
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlgebraicExtensionIndex(pub(crate) usize);
+
+/// Assumptions on the value of a variable, that enable simplifications
+/// which are not valid for arbitrary complex values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Assumptions {
+    pub positive: bool,
+    pub real: bool,
+    pub integer: bool,
+    pub nonzero: bool,
+}
+
+impl Assumptions {
+    /// Add all assumptions that are implied by the current ones,
+    /// for example a positive variable is also real and non-zero.
+    pub fn complete(mut self) -> Self {
+        if self.positive {
+            self.real = true;
+            self.nonzero = true;
+        }
+        if self.integer {
+            self.real = true;
+        }
+        self
+    }
+}
+
+/// An attribute of a function that changes how it is normalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FunctionAttribute {
+    /// The function does not commute with other non-commutative functions,
+    /// so that their order in a product is preserved.
+    NonCommutative,
+    /// The function is a Kronecker delta `f(i, j)`, which is symmetric
+    /// and is contracted over indices that appear twice in a term.
+    KroneckerDelta,
+    /// The function is a Levi-Civita symbol `f(i_1, ..., i_n)`, which is antisymmetric
+    /// and is contracted with other Levi-Civita symbols with `n` arguments over indices
+    /// that appear twice in a term.
+    LeviCivita,
+    /// The function is a conditional `f(c, a, b)` that equals `a` if the
+    /// condition `c` is positive and `b` otherwise. A step function at `t`
+    /// can be written as `f(x - t, 1, 0)`.
+    Conditional,
+    /// The function is a list `f(a_1, ..., a_n)` of expressions, which supports
+    /// element access, mapping and zipping, see the list module. Lists are function
+    /// atoms with this attribute rather than a separate kind of atom.
+    List,
+}
+
+/// The arguments `x` for which `g(f(x)) = x` holds, when `g` is declared as the
+/// inverse of `f` on its principal branch. The composite `f(g(x)) = x` always holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InverseDomain {
+    /// `g(f(x)) = x` for all `x`.
+    All,
+    /// `g(f(x)) = x` for real `x`, as for `log(exp(x))`.
+    Real,
+    /// `g(f(x)) = x` for positive `x`.
+    Positive,
+    /// `g(f(x))` is never simplified, as for `asin(sin(x))`, whose argument
+    /// cannot be restricted to the principal domain with the available assumptions.
+    None,
+}
+
+impl InverseDomain {
+    fn to_u8(self) -> u8 {
+        match self {
+            InverseDomain::All => 0,
+            InverseDomain::Real => 1,
+            InverseDomain::Positive => 2,
+            InverseDomain::None => 3,
+        }
+    }
+
+    fn from_u8(d: u8) -> Option<Self> {
+        match d {
+            0 => Some(InverseDomain::All),
+            1 => Some(InverseDomain::Real),
+            2 => Some(InverseDomain::Positive),
+            3 => Some(InverseDomain::None),
+            _ => None,
+        }
+    }
+}
+
+/// The magic bytes at the start of an exported state.
+const STATE_MAGIC: &[u8; 8] = b"SYMSTATE";
+
+/// The version of the format of an exported state.
+const STATE_FORMAT_VERSION: u32 = 1;
+
+/// A flag of an exported state that signals that a table of print weights is present.
+const STATE_FLAG_PRINT_WEIGHTS: u32 = 1;
+
+/// Read `len` bytes from `source`. The buffer grows with the data that is actually
+/// read, so that a corrupted length does not cause a large allocation.
+fn read_bytes<R: Read>(source: &mut R, len: u64) -> io::Result<Vec<u8>> {
+    let mut buf = vec![];
+    source.take(len).read_to_end(&mut buf)?;
+    if (buf.len() as u64) < len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Unexpected end of the state",
+        ));
+    }
+    Ok(buf)
+}
+
+/// A weight of an identifier that is used to order the output of the printer.
+pub type PrintWeight = dyn Fn(Identifier) -> i64 + Send + Sync;
+
 /// A global state, that stores mappings from variable and function names to ids.
 pub struct State {
     // get variable maps from here
     str_to_var_id: HashMap<String, Identifier>,
     var_to_str_map: Vec<String>,
     finite_fields: Vec<FiniteField<u64>>,
+    algebraic_extensions: Vec<AlgebraicExtension<RationalField>>,
+    assumptions: HashMap<Identifier, Assumptions>,
+    function_attributes: HashMap<Identifier, Vec<FunctionAttribute>>,
+    inverse_functions: HashMap<Identifier, (Identifier, InverseDomain)>,
+    interpolation_tables: HashMap<Identifier, InterpolationTable>,
+    print_weight: Option<Box<PrintWeight>>,
 }
 
 impl State {
@@ -28,6 +162,12 @@ impl State {
             str_to_var_id: HashMap::new(),
             var_to_str_map: vec![],
             finite_fields: vec![],
+            algebraic_extensions: vec![],
+            assumptions: HashMap::new(),
+            function_attributes: HashMap::new(),
+            inverse_functions: HashMap::new(),
+            interpolation_tables: HashMap::new(),
+            print_weight: None,
         }
     }
 
@@ -47,6 +187,20 @@ impl State {
         }
     }
 
+    /// Register a new variable with a name that starts with `prefix` and that is not
+    /// in use yet, so that it is distinct from all existing variables and all other
+    /// temporary variables.
+    pub fn new_temporary_var(&mut self, prefix: &str) -> Identifier {
+        let mut n = self.var_to_str_map.len();
+        loop {
+            let name = format!("{}{}", prefix, n);
+            if !self.str_to_var_id.contains_key(name.as_str()) {
+                return self.get_or_insert_var(name);
+            }
+            n += 1;
+        }
+    }
+
     /// Get the name for a given id.
     pub fn get_name(&self, id: Identifier) -> Option<&String> {
         self.var_to_str_map.get(id.to_u32() as usize)
@@ -56,6 +210,132 @@ impl State {
         self.get_name(id).map(|n| n.ends_with('_'))
     }
 
+    /// Get the wildcard level of an identifier, which is the number of trailing
+    /// underscores (at most 3). A level of 0 means that the identifier is not a wildcard.
+    /// A wildcard `x___` of level 3 may also match an empty sequence of arguments.
+    pub fn get_wildcard_level(&self, id: Identifier) -> u8 {
+        self.get_name(id)
+            .map(|n| n.bytes().rev().take(3).take_while(|c| *c == b'_').count() as u8)
+            .unwrap_or(0)
+    }
+
+    /// Set the assumptions on a variable. Assumptions that are implied,
+    /// such as realness for a positive variable, are added automatically.
+    pub fn set_assumptions(&mut self, id: Identifier, assumptions: Assumptions) {
+        self.assumptions.insert(id, assumptions.complete());
+    }
+
+    /// Get the assumptions on a variable. Without assumptions,
+    /// the variable is treated as an arbitrary complex value.
+    pub fn get_assumptions(&self, id: Identifier) -> Assumptions {
+        self.assumptions.get(&id).copied().unwrap_or_default()
+    }
+
+    /// Set the attributes of a function.
+    pub fn set_function_attributes(&mut self, id: Identifier, attributes: &[FunctionAttribute]) {
+        if attributes.is_empty() {
+            self.function_attributes.remove(&id);
+        } else {
+            self.function_attributes.insert(id, attributes.to_vec());
+        }
+    }
+
+    /// Get the attributes of a function.
+    pub fn get_function_attributes(&self, id: Identifier) -> &[FunctionAttribute] {
+        self.function_attributes
+            .get(&id)
+            .map(|a| a.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Check if a function is non-commutative.
+    pub fn is_noncommutative(&self, id: Identifier) -> bool {
+        self.get_function_attributes(id)
+            .contains(&FunctionAttribute::NonCommutative)
+    }
+
+    /// Declare `inverse` as the inverse of the function `f` on its principal branch,
+    /// so that normalization simplifies `f(inverse(x))` to `x`, and `inverse(f(x))`
+    /// to `x` if `x` lies in `domain` under the assumptions on its variables.
+    pub fn set_inverse_function(
+        &mut self,
+        f: Identifier,
+        inverse: Identifier,
+        domain: InverseDomain,
+    ) {
+        self.inverse_functions.insert(f, (inverse, domain));
+    }
+
+    /// Get the declared inverse of the function `f` and the domain on which
+    /// it inverts `f`.
+    pub fn get_inverse_function(&self, f: Identifier) -> Option<(Identifier, InverseDomain)> {
+        self.inverse_functions.get(&f).copied()
+    }
+
+    /// Get a function that has `inverse` as its declared inverse, and the domain on
+    /// which `inverse` inverts it. If there are several, the one with the lowest
+    /// identifier is returned.
+    pub fn get_function_with_inverse(
+        &self,
+        inverse: Identifier,
+    ) -> Option<(Identifier, InverseDomain)> {
+        self.inverse_functions
+            .iter()
+            .filter(|(_, (i, _))| *i == inverse)
+            .map(|(f, (_, d))| (*f, *d))
+            .min_by_key(|(f, _)| *f)
+    }
+
+    /// Attach a numerical interpolation table to the function `f`, so that `f(x)` is
+    /// evaluated with a spline for numerical `x`, or remove it with `None`.
+    pub fn set_interpolation_table(&mut self, f: Identifier, table: Option<InterpolationTable>) {
+        if let Some(table) = table {
+            self.interpolation_tables.insert(f, table);
+        } else {
+            self.interpolation_tables.remove(&f);
+        }
+    }
+
+    /// Get the interpolation table of the function `f`.
+    pub fn get_interpolation_table(&self, f: Identifier) -> Option<&InterpolationTable> {
+        self.interpolation_tables.get(&f)
+    }
+
+    /// Check if a function is a conditional.
+    pub fn is_conditional(&self, id: Identifier) -> bool {
+        self.get_function_attributes(id)
+            .contains(&FunctionAttribute::Conditional)
+    }
+
+    /// Check if a function is a list.
+    pub fn is_list(&self, id: Identifier) -> bool {
+        self.get_function_attributes(id)
+            .contains(&FunctionAttribute::List)
+    }
+
+    /// Get the function with the attribute [`FunctionAttribute::KroneckerDelta`]
+    /// with the lowest identifier, which is used in the contraction of Levi-Civita symbols.
+    pub fn get_kronecker_delta(&self) -> Option<Identifier> {
+        self.function_attributes
+            .iter()
+            .filter(|(_, a)| a.contains(&FunctionAttribute::KroneckerDelta))
+            .map(|(id, _)| *id)
+            .min_by_key(|id| id.to_u32())
+    }
+
+    /// Set a weight function over identifiers that the printer uses to order
+    /// terms and factors, or remove it with `None`. Terms and factors with a lower
+    /// weight are printed first. The weight of a variable or function is given by
+    /// `weight`, which is multiplied by the exponent of a power and summed over
+    /// the factors of a product. The normalization order is not changed.
+    pub fn set_print_weight(&mut self, weight: Option<Box<PrintWeight>>) {
+        self.print_weight = weight;
+    }
+
+    pub fn get_print_weight(&self) -> Option<&PrintWeight> {
+        self.print_weight.as_deref()
+    }
+
     pub fn get_finite_field(&self, fi: FiniteFieldIndex) -> &FiniteField<u64> {
         &self.finite_fields[fi.0]
     }
@@ -70,23 +350,505 @@ impl State {
         self.finite_fields.push(f);
         FiniteFieldIndex(self.finite_fields.len() - 1)
     }
+
+    pub fn get_algebraic_extension(
+        &self,
+        ai: AlgebraicExtensionIndex,
+    ) -> &AlgebraicExtension<RationalField> {
+        &self.algebraic_extensions[ai.0]
+    }
+
+    /// Get the index of the algebraic extension defined by the univariate minimal
+    /// polynomial `poly`, registering the extension if it is new. Numbers in this
+    /// extension can be used as coefficients of atoms.
+    pub fn get_or_insert_algebraic_extension(
+        &mut self,
+        poly: &MultivariatePolynomial<RationalField, u16>,
+    ) -> AlgebraicExtensionIndex {
+        let mut poly = poly.clone();
+        poly.var_map = None;
+        let inv = poly.field.inv(&poly.lcoeff());
+        let poly = poly.mul_coeff(inv);
+
+        for (i, e) in self.algebraic_extensions.iter().enumerate() {
+            if *e.poly() == poly {
+                return AlgebraicExtensionIndex(i);
+            }
+        }
+
+        self.algebraic_extensions
+            .push(AlgebraicExtension::new(poly));
+        AlgebraicExtensionIndex(self.algebraic_extensions.len() - 1)
+    }
+
+    /// Create a state that only contains the identifiers that occur in `statistics`, with
+    /// their assumptions, attributes, inverses and interpolation tables, for example to reduce the size of an export.
+    /// The relative order of the identifiers is preserved. The registered finite fields and
+    /// algebraic extensions are kept, but the print weight is not.
+    ///
+    /// Expressions can be moved to the new state with [`AtomView::rename_pruned`]
+    /// using the returned map from old to new identifiers.
+    ///
+    /// [`AtomView::rename_pruned`]: crate::representations::AtomView::rename_pruned
+    pub fn prune(&self, statistics: &SymbolStatistics) -> (State, Vec<(Identifier, Identifier)>) {
+        let mut state = State::new();
+        let mut map = vec![];
+        for id in statistics.get_identifiers() {
+            let new_id = state.get_or_insert_var(self.get_name(id).unwrap());
+            if let Some(a) = self.assumptions.get(&id) {
+                state.assumptions.insert(new_id, *a);
+            }
+            state.set_function_attributes(new_id, self.get_function_attributes(id));
+            if let Some(t) = self.interpolation_tables.get(&id) {
+                state.interpolation_tables.insert(new_id, t.clone());
+            }
+            map.push((id, new_id));
+        }
+
+        let rename = |id: Identifier| map.iter().find(|(old, _)| *old == id).map(|(_, n)| *n);
+        for (f, (inverse, domain)) in &self.inverse_functions {
+            if let (Some(f), Some(inverse)) = (rename(*f), rename(*inverse)) {
+                state.inverse_functions.insert(f, (inverse, *domain));
+            }
+        }
+
+        state.finite_fields = self.finite_fields.clone();
+        state.algebraic_extensions = self.algebraic_extensions.clone();
+        (state, map)
+    }
+
+    /// Write the names of all variables and functions, their assumptions, attributes and inverses,
+    /// the registered finite fields, algebraic extensions and interpolation tables, and the
+    /// print weights of all identifiers to `dest`. The state can be restored with [`State::import`],
+    /// which preserves the numbering of identifiers, so that serialized expressions
+    /// remain valid.
+    ///
+    /// The data starts with a magic number, a format version and a flag word, so that
+    /// the format can be extended without breaking older exports.
+    pub fn export<W: Write>(&self, dest: &mut W) -> io::Result<()> {
+        dest.write_all(STATE_MAGIC)?;
+        dest.write_u32::<LittleEndian>(STATE_FORMAT_VERSION)?;
+        let flags = if self.print_weight.is_some() {
+            STATE_FLAG_PRINT_WEIGHTS
+        } else {
+            0
+        };
+        dest.write_u32::<LittleEndian>(flags)?;
+
+        dest.write_u64::<LittleEndian>(self.var_to_str_map.len() as u64)?;
+        for (i, name) in self.var_to_str_map.iter().enumerate() {
+            dest.write_u32::<LittleEndian>(name.len() as u32)?;
+            dest.write_all(name.as_bytes())?;
+
+            let a = self.get_assumptions(Identifier::from(i as u32));
+            let flags = a.positive as u8
+                | (a.real as u8) << 1
+                | (a.integer as u8) << 2
+                | (a.nonzero as u8) << 3
+                | (self.is_noncommutative(Identifier::from(i as u32)) as u8) << 4
+                | (self.is_conditional(Identifier::from(i as u32)) as u8) << 5;
+            let attributes = self.get_function_attributes(Identifier::from(i as u32));
+            let flags = flags
+                | (attributes.contains(&FunctionAttribute::KroneckerDelta) as u8) << 6
+                | (attributes.contains(&FunctionAttribute::LeviCivita) as u8) << 7;
+            dest.write_u8(flags)?;
+        }
+
+        dest.write_u64::<LittleEndian>(self.finite_fields.len() as u64)?;
+        for f in &self.finite_fields {
+            dest.write_u64::<LittleEndian>(f.get_prime())?;
+        }
+
+        dest.write_u64::<LittleEndian>(self.algebraic_extensions.len() as u64)?;
+        for e in &self.algebraic_extensions {
+            let poly = e.poly();
+            let mut coeffs = vec![RationalField::new().zero(); e.degree() as usize + 1];
+            for t in 0..poly.nterms {
+                coeffs[poly.exponents(t)[0] as usize] = poly.coefficients[t].clone();
+            }
+
+            let mut buf = vec![];
+            for c in coeffs {
+                Number::from(c).write_packed(&mut buf);
+            }
+            dest.write_u32::<LittleEndian>(e.degree() as u32)?;
+            dest.write_u64::<LittleEndian>(buf.len() as u64)?;
+            dest.write_all(&buf)?;
+        }
+
+        let mut inverses: Vec<_> = self.inverse_functions.iter().collect();
+        inverses.sort_by_key(|(f, _)| f.to_u32());
+        dest.write_u64::<LittleEndian>(inverses.len() as u64)?;
+        for (f, (inverse, domain)) in inverses {
+            dest.write_u32::<LittleEndian>(f.to_u32())?;
+            dest.write_u32::<LittleEndian>(inverse.to_u32())?;
+            dest.write_u8(domain.to_u8())?;
+        }
+
+        let mut lists: Vec<_> = self
+            .function_attributes
+            .iter()
+            .filter(|(_, a)| a.contains(&FunctionAttribute::List))
+            .map(|(f, _)| f.to_u32())
+            .collect();
+        lists.sort();
+        dest.write_u64::<LittleEndian>(lists.len() as u64)?;
+        for f in lists {
+            dest.write_u32::<LittleEndian>(f)?;
+        }
+
+        let mut tables: Vec<_> = self.interpolation_tables.iter().collect();
+        tables.sort_by_key(|(f, _)| f.to_u32());
+        dest.write_u64::<LittleEndian>(tables.len() as u64)?;
+        for (f, t) in tables {
+            let (x, y) = t.get_nodes();
+            dest.write_u32::<LittleEndian>(f.to_u32())?;
+            dest.write_u64::<LittleEndian>(x.len() as u64)?;
+            for v in x.iter().chain(y) {
+                dest.write_f64::<LittleEndian>(*v)?;
+            }
+        }
+
+        // the weight is a closure, so that its value for every identifier is stored
+        if let Some(w) = &self.print_weight {
+            for i in 0..self.var_to_str_map.len() {
+                dest.write_i64::<LittleEndian>(w(Identifier::from(i as u32)))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read a state that was written with [`State::export`]. The print weights are restored
+    /// as a table, in which identifiers that are created after the import have weight 0.
+    pub fn import<R: Read>(source: &mut R) -> io::Result<State> {
+        let mut magic = [0; 8];
+        source.read_exact(&mut magic)?;
+        if &magic != STATE_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "The data is not an exported state",
+            ));
+        }
+
+        let version = source.read_u32::<LittleEndian>()?;
+        if version != STATE_FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported state format version {}", version),
+            ));
+        }
+
+        let flags = source.read_u32::<LittleEndian>()?;
+        if flags & !STATE_FLAG_PRINT_WEIGHTS != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported state flags {:#x}", flags),
+            ));
+        }
+
+        let mut state = State::new();
+
+        let n_vars = source.read_u64::<LittleEndian>()?;
+        for i in 0..n_vars {
+            let len = source.read_u32::<LittleEndian>()?;
+            let name = read_bytes(source, len as u64)?;
+            let name = std::str::from_utf8(&name)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+            let id = state.get_or_insert_var(name);
+            if id.to_u32() as u64 != i {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Duplicate name {}", name),
+                ));
+            }
+
+            let flags = source.read_u8()?;
+            let mut attributes = vec![];
+            if flags & 16 != 0 {
+                attributes.push(FunctionAttribute::NonCommutative);
+            }
+            if flags & 32 != 0 {
+                attributes.push(FunctionAttribute::Conditional);
+            }
+            if flags & 64 != 0 {
+                attributes.push(FunctionAttribute::KroneckerDelta);
+            }
+            if flags & 128 != 0 {
+                attributes.push(FunctionAttribute::LeviCivita);
+            }
+            state.set_function_attributes(id, &attributes);
+
+            if flags & 15 != 0 {
+                state.assumptions.insert(
+                    id,
+                    Assumptions {
+                        positive: flags & 1 != 0,
+                        real: flags & 2 != 0,
+                        integer: flags & 4 != 0,
+                        nonzero: flags & 8 != 0,
+                    },
+                );
+            }
+        }
+
+        let n_fields = source.read_u64::<LittleEndian>()?;
+        for _ in 0..n_fields {
+            let p = source.read_u64::<LittleEndian>()?;
+            if p < 3 || p % 2 == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid finite field prime {}", p),
+                ));
+            }
+            state.finite_fields.push(FiniteField::<u64>::new(p));
+        }
+
+        let n_extensions = source.read_u64::<LittleEndian>()?;
+        for _ in 0..n_extensions {
+            let degree = source.read_u32::<LittleEndian>()?;
+            if degree == 0 || degree > u16::MAX as u32 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid degree {} of a minimal polynomial", degree),
+                ));
+            }
+
+            let len = source.read_u64::<LittleEndian>()?;
+            let buf = read_bytes(source, len)?;
+
+            let mut poly = MultivariatePolynomial::new(1, RationalField::new(), None, None);
+            let mut data = buf.as_slice();
+            for k in 0..=degree as u16 {
+                let (n, rest) = data.get_rational_view_checked().ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Invalid coefficient of a minimal polynomial",
+                    )
+                })?;
+                let c = match n {
+                    BorrowedNumber::Natural(n, d) => Rational::Natural(n, d),
+                    BorrowedNumber::Large(r) => Rational::Large(r.to_rat()),
+                    _ => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "Minimal polynomial must have rational coefficients",
+                        ))
+                    }
+                };
+                if !RationalField::is_zero(&c) {
+                    poly.append_monomial(c, &[k]);
+                }
+                data = rest;
+            }
+
+            if poly.degree(0) as u32 != degree {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "The leading coefficient of a minimal polynomial is zero",
+                ));
+            }
+
+            state
+                .algebraic_extensions
+                .push(AlgebraicExtension::new(poly));
+        }
+
+        let n_inverses = source.read_u64::<LittleEndian>()?;
+        for _ in 0..n_inverses {
+            let f = source.read_u32::<LittleEndian>()?;
+            let inverse = source.read_u32::<LittleEndian>()?;
+            if f as u64 >= n_vars || inverse as u64 >= n_vars {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Unknown function in inverse declaration",
+                ));
+            }
+
+            let domain = InverseDomain::from_u8(source.read_u8()?).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "Unknown inverse domain")
+            })?;
+            state
+                .inverse_functions
+                .insert(Identifier::from(f), (Identifier::from(inverse), domain));
+        }
+
+        let n_lists = source.read_u64::<LittleEndian>()?;
+        for _ in 0..n_lists {
+            let f = source.read_u32::<LittleEndian>()?;
+            if f as u64 >= n_vars {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Unknown function in list declaration",
+                ));
+            }
+
+            let f = Identifier::from(f);
+            let mut attributes = state.get_function_attributes(f).to_vec();
+            attributes.push(FunctionAttribute::List);
+            state.set_function_attributes(f, &attributes);
+        }
+
+        let n_tables = source.read_u64::<LittleEndian>()?;
+        for _ in 0..n_tables {
+            let f = source.read_u32::<LittleEndian>()?;
+            if f as u64 >= n_vars {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Unknown function in interpolation table",
+                ));
+            }
+
+            let n_nodes = source.read_u64::<LittleEndian>()?;
+            let n_values = n_nodes.checked_mul(2).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "Too many interpolation nodes")
+            })?;
+            let mut values = vec![];
+            for _ in 0..n_values {
+                values.push(source.read_f64::<LittleEndian>()?);
+            }
+            let y = values.split_off(n_nodes as usize);
+            let table = InterpolationTable::new(values, y)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            state
+                .interpolation_tables
+                .insert(Identifier::from(f), table);
+        }
+
+        if flags & STATE_FLAG_PRINT_WEIGHTS != 0 {
+            let mut weights = vec![];
+            for _ in 0..n_vars {
+                weights.push(source.read_i64::<LittleEndian>()?);
+            }
+            state.print_weight = Some(Box::new(move |id: Identifier| {
+                weights.get(id.to_u32() as usize).copied().unwrap_or(0)
+            }));
+        }
+
+        Ok(state)
+    }
 }
 
 /// A workspace that stores reusable buffers.
 pub struct Workspace<P: Atom> {
     atom_stack: Stack<OwnedAtom<P>>,
+    budget: Option<MemoryBudget>,
 }
 
 impl<P: Atom> Workspace<P> {
     pub fn new() -> Self {
         Self {
             atom_stack: Stack::new(),
+            budget: None,
+        }
+    }
+
+    /// Limit the memory of the atom buffers of the workspace. The budget is checked
+    /// by the algorithms that return an [`OutOfBudget`] error, such as
+    /// [`RuleSet::try_apply`](crate::id::RuleSet::try_apply).
+    pub fn with_memory_budget(mut self, budget: MemoryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Get the number of bytes that are held by the atom buffers of the workspace.
+    /// The growth of a buffer that is in use is only counted once it is returned.
+    pub fn memory_usage(&self) -> usize {
+        self.atom_stack.heap_size()
+    }
+
+    /// Return an error if the memory budget of the workspace is exceeded.
+    /// `location` is used in the diagnostics.
+    pub fn check_budget(&self, location: &'static str) -> Result<(), OutOfBudget> {
+        match self.budget {
+            Some(b) => b.check(self.memory_usage(), location),
+            None => Ok(()),
         }
     }
 
     pub fn new_atom(&self) -> BufferHandle<OwnedAtom<P>> {
         self.atom_stack.get_buf_ref()
     }
+
+    /// Move an atom that is not part of any workspace, for example one that is
+    /// received from another thread, into a handle. When the handle is dropped,
+    /// the atom buffer is recycled by this workspace.
+    pub fn attach_atom(&self, atom: OwnedAtom<P>) -> BufferHandle<'_, OwnedAtom<P>> {
+        self.atom_stack.attach(atom)
+    }
+
+    /// Get the statistics of the atom buffers, which can be used to
+    /// tune [`Workspace::reserve_atoms`].
+    pub fn atom_stats(&self) -> StackStats {
+        self.atom_stack.stats()
+    }
+
+    /// Make sure that at least `n` atom buffers are available, so that
+    /// a subsequent computation does not have to allocate new ones.
+    pub fn reserve_atoms(&self, n: usize) {
+        self.atom_stack.reserve(n);
+    }
+
+    /// Release the memory of all atom buffers that are not in use.
+    pub fn clear(&self) {
+        self.atom_stack.clear();
+    }
+}
+
+/// A pool of workspaces that can be shared between threads. Every thread that
+/// calls [`WorkspacePool::with`] gets a workspace that no other thread uses at the same
+/// time, and the workspace is returned to the pool afterwards, so that its buffers
+/// are reused by the next computation. This is an alternative to a `thread_local`
+/// workspace that works for every representation `P`.
+///
+/// Atoms that should outlive the closure, or that should be moved to another
+/// thread, can be detached from the workspace with [`SendAtom`].
+pub struct WorkspacePool<P: Atom> {
+    workspaces: Mutex<Vec<Workspace<P>>>,
+}
+
+impl<P: Atom> Default for WorkspacePool<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Atom> WorkspacePool<P> {
+    pub fn new() -> Self {
+        Self {
+            workspaces: Mutex::new(vec![]),
+        }
+    }
+
+    /// Call `f` with a workspace that is not in use by any other thread.
+    /// A new workspace is created if all workspaces in the pool are in use.
+    pub fn with<R>(&self, f: impl FnOnce(&Workspace<P>) -> R) -> R {
+        let workspace = self
+            .workspaces
+            .lock()
+            .unwrap()
+            .pop()
+            .unwrap_or_else(Workspace::new);
+
+        let r = f(&workspace);
+        self.workspaces.lock().unwrap().push(workspace);
+        r
+    }
+
+    /// Get the number of workspaces that are available in the pool.
+    pub fn len(&self) -> usize {
+        self.workspaces.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop all workspaces that are available in the pool.
+    pub fn clear(&self) {
+        self.workspaces.lock().unwrap().clear();
+    }
 }
 
 /// A buffer that can be reset to its initial state.
@@ -96,14 +858,43 @@ pub trait ResettableBuffer: Sized {
     fn new() -> Self;
     /// Reset the buffer to its initial state. Must not allocate.
     fn reset(&mut self);
+
+    /// Get the number of bytes that the buffer has allocated on the heap.
+    fn heap_size(&self) -> usize {
+        0
+    }
 }
 
 /// A stack of resettable buffers. Any buffer lend from this stack
 /// will be returned to it when it is dropped. If a buffer is requested
 /// on an empty stack, a new buffer will be created. Use a stack to prevent
 /// allocations by recycling used buffers first before creating new ones.
+///
+/// The buffers are allocated individually by the global allocator, so the stack
+/// is a free list and not an arena. The bookkeeping for [`Stack::stats`] is only
+/// done when a buffer is created, attached or detached, and not when a buffer
+/// is recycled.
 pub struct Stack<T: ResettableBuffer> {
     buffers: RefCell<Vec<T>>,
+    /// The number of buffers that were created by the stack.
+    created: Cell<usize>,
+    /// The number of buffers that belong to the stack, either available or lent out.
+    owned: Cell<usize>,
+    /// The heap size of all buffers, the ones in use counted at the time they were lent.
+    heap_size: Cell<usize>,
+}
+
+/// Statistics of the buffers of a [`Stack`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StackStats {
+    /// The number of buffers that were newly created.
+    pub created: usize,
+    /// The number of buffers that are currently lent out.
+    pub in_use: usize,
+    /// The number of buffers that are available for reuse.
+    pub available: usize,
+    /// The number of bytes that are held by the buffers.
+    pub heap_size: usize,
 }
 
 impl<T: ResettableBuffer> Stack<T> {
@@ -112,32 +903,93 @@ impl<T: ResettableBuffer> Stack<T> {
     pub fn new() -> Self {
         Self {
             buffers: RefCell::new(vec![]),
+            created: Cell::new(0),
+            owned: Cell::new(0),
+            heap_size: Cell::new(0),
+        }
+    }
+
+    /// Get the number of bytes that are held by the buffers of the stack.
+    pub fn heap_size(&self) -> usize {
+        self.heap_size.get()
+    }
+
+    /// Get the buffer statistics.
+    pub fn stats(&self) -> StackStats {
+        let available = self.buffers.borrow().len();
+        StackStats {
+            created: self.created.get(),
+            in_use: self.owned.get() - available,
+            available,
+            heap_size: self.heap_size.get(),
+        }
+    }
+
+    /// Create a buffer that belongs to the stack.
+    fn create(&self) -> T {
+        let b = T::new();
+        self.created.set(self.created.get() + 1);
+        self.owned.set(self.owned.get() + 1);
+        self.heap_size.set(self.heap_size.get() + b.heap_size());
+        b
+    }
+
+    /// Create new buffers until at least `n` buffers are available.
+    pub fn reserve(&self, n: usize) {
+        let mut buffers = self.buffers.borrow_mut();
+        let missing = n.saturating_sub(buffers.len());
+        for _ in 0..missing {
+            buffers.push(self.create());
         }
     }
 
+    /// Drop all available buffers at once.
+    pub fn clear(&self) {
+        let mut buffers = self.buffers.borrow_mut();
+        let size: usize = buffers.iter().map(|b| b.heap_size()).sum();
+        self.heap_size.set(self.heap_size.get() - size);
+        self.owned.set(self.owned.get() - buffers.len());
+        buffers.clear();
+        buffers.shrink_to_fit();
+    }
+
     /// Get a buffer from the stack if the stack is not empty,
     /// else create a new one.
     #[inline]
     pub fn get_buf_ref(&self) -> BufferHandle<T> {
-        let b = self
-            .buffers
-            .borrow_mut()
-            .pop()
-            .map(|mut b| {
+        let b = match self.buffers.borrow_mut().pop() {
+            Some(mut b) => {
                 b.reset();
                 b
-            })
-            .unwrap_or_else(|| T::new());
+            }
+            None => self.create(),
+        };
 
         BufferHandle {
+            size: b.heap_size(),
             buf: Some(b),
             parent: self,
         }
     }
 
-    /// Return a buffer to the stack.
+    /// Lend a buffer that was not created by this stack. It will be
+    /// returned to this stack once the handle is dropped.
+    pub fn attach(&self, b: T) -> BufferHandle<'_, T> {
+        let size = b.heap_size();
+        self.owned.set(self.owned.get() + 1);
+        self.heap_size.set(self.heap_size.get() + size);
+        BufferHandle {
+            buf: Some(b),
+            size,
+            parent: self,
+        }
+    }
+
+    /// Return a buffer that had a heap size of `size` when it was lent.
     #[inline]
-    fn return_arg(&self, b: T) {
+    fn return_arg(&self, b: T, size: usize) {
+        self.heap_size
+            .set(self.heap_size.get() + b.heap_size() - size);
         self.buffers.borrow_mut().push(b);
     }
 }
@@ -146,6 +998,8 @@ impl<T: ResettableBuffer> Stack<T> {
 /// the buffer is returned to the stack it was created by.
 pub struct BufferHandle<'a, T: ResettableBuffer> {
     buf: Option<T>,
+    /// The heap size of the buffer when it was lent.
+    size: usize,
     parent: &'a Stack<T>,
 }
 
@@ -175,13 +1029,248 @@ impl<'a, T: ResettableBuffer> BufferHandle<'a, T> {
     pub fn get_mut(&mut self) -> &mut T {
         self.buf.as_mut().unwrap()
     }
+
+    /// Take the underlying buffer out of the handle, so that it will
+    /// not be returned to the stack it was created by.
+    pub fn into_inner(mut self) -> T {
+        let b = self.buf.take().unwrap();
+        self.parent
+            .heap_size
+            .set(self.parent.heap_size.get() - self.size);
+
+        self.parent.owned.set(self.parent.owned.get() - 1);
+        b
+    }
 }
 
 impl<'a, T: ResettableBuffer> Drop for BufferHandle<'a, T> {
     /// Upon dropping the handle, the buffer is returned to the stack it was created by.
     #[inline]
     fn drop(&mut self) {
-        self.parent
-            .return_arg(std::mem::take(&mut self.buf).unwrap())
+        if let Some(b) = self.buf.take() {
+            self.parent.return_arg(b, self.size)
+        }
+    }
+}
+
+/// An atom that is detached from any workspace, and can therefore be moved to other
+/// threads when the representation `P` allows it. Use [`SendAtom::into_handle`] to
+/// let the workspace of the receiving thread recycle the buffer.
+#[derive(Clone)]
+pub struct SendAtom<P: Atom> {
+    atom: OwnedAtom<P>,
+}
+
+impl<P: Atom> SendAtom<P> {
+    /// Copy the atom `view` into a new detached atom.
+    pub fn new(view: AtomView<'_, P>) -> Self {
+        Self {
+            atom: OwnedAtom::new_from_view(&view),
+        }
+    }
+
+    pub fn to_view(&self) -> AtomView<'_, P> {
+        self.atom.to_view()
+    }
+
+    pub fn into_inner(self) -> OwnedAtom<P> {
+        self.atom
+    }
+
+    /// Move the atom into a handle of `workspace`.
+    pub fn into_handle(self, workspace: &Workspace<P>) -> BufferHandle<'_, OwnedAtom<P>> {
+        workspace.attach_atom(self.atom)
+    }
+}
+
+impl<P: Atom> From<OwnedAtom<P>> for SendAtom<P> {
+    fn from(atom: OwnedAtom<P>) -> Self {
+        Self { atom }
+    }
+}
+
+impl<'a, P: Atom> From<BufferHandle<'a, OwnedAtom<P>>> for SendAtom<P> {
+    /// Detach the atom from the workspace without copying it.
+    fn from(handle: BufferHandle<'a, OwnedAtom<P>>) -> Self {
+        Self {
+            atom: handle.into_inner(),
+        }
+    }
+}
+
+impl<P: Atom> std::fmt::Debug for SendAtom<P> {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.atom.fmt(fmt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rug::{Integer as ArbitraryPrecisionInteger, Rational as ArbitraryPrecisionRational};
+
+    use crate::{
+        interpolation::InterpolationTable,
+        poly::polynomial::MultivariatePolynomial,
+        representations::{
+            number::{Number, PackedRationalNumberWriter},
+            Identifier,
+        },
+        rings::{
+            finite_field::{FiniteField, FiniteFieldCore},
+            rational::{Rational, RationalField},
+        },
+    };
+
+    use super::{FunctionAttribute, State};
+
+    #[test]
+    fn test_export_import() {
+        let mut state = State::new();
+        let x = state.get_or_insert_var("x");
+        let f = state.get_or_insert_var("f");
+        let l = state.get_or_insert_var("l");
+        state.set_function_attributes(l, &[FunctionAttribute::List]);
+        state.set_interpolation_table(
+            f,
+            Some(InterpolationTable::new(vec![0., 1., 2.], vec![1., 3., 2.]).unwrap()),
+        );
+        state.set_print_weight(Some(Box::new(
+            move |id: Identifier| {
+                if id == x {
+                    -5
+                } else {
+                    1
+                }
+            },
+        )));
+
+        let mut data = vec![];
+        state.export(&mut data).unwrap();
+        let imported = State::import(&mut data.as_slice()).unwrap();
+
+        assert_eq!(imported.get_name(l).map(|n| n.as_str()), Some("l"));
+        assert!(imported
+            .get_function_attributes(l)
+            .contains(&FunctionAttribute::List));
+        assert_eq!(
+            imported.get_interpolation_table(f).unwrap().evaluate(1.),
+            Some(3.)
+        );
+        let weight = imported.get_print_weight().unwrap();
+        assert_eq!((weight(x), weight(f)), (-5, 1));
+
+        // a truncated state or a different magic number is rejected
+        assert!(State::import(&mut &data[..data.len() - 1]).is_err());
+        data[0] = b'X';
+        assert!(State::import(&mut data.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_import_large_length() {
+        let mut data = vec![];
+        State::new().export(&mut data).unwrap();
+
+        // a name with a length of 4 GB that is not followed by data
+        data.truncate(16);
+        data.extend(1u64.to_le_bytes());
+        data.extend(u32::MAX.to_le_bytes());
+        assert!(State::import(&mut data.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_import_corrupted() {
+        let mut state = State::new();
+        let f = state.get_or_insert_var("f");
+        state.set_interpolation_table(
+            f,
+            Some(InterpolationTable::new(vec![0., 1.], vec![1., 3.]).unwrap()),
+        );
+        state.get_or_insert_finite_field(FiniteField::<u64>::new(7));
+
+        // x^2 + 2^80/3 x - 1/3 has a large and a fractional coefficient
+        let mut poly = MultivariatePolynomial::new(1, RationalField::new(), None, None);
+        poly.append_monomial(Rational::new(-1, 3), &[0]);
+        poly.append_monomial(
+            Rational::Large(ArbitraryPrecisionRational::from((
+                ArbitraryPrecisionInteger::from(1) << 80,
+                3,
+            ))),
+            &[1],
+        );
+        poly.append_monomial(Rational::new(1, 1), &[2]);
+        state.get_or_insert_algebraic_extension(&poly);
+
+        let mut data = vec![];
+        state.export(&mut data).unwrap();
+        assert!(State::import(&mut data.as_slice()).is_ok());
+
+        for len in 0..data.len() {
+            assert!(State::import(&mut &data[..len]).is_err());
+        }
+
+        // corrupted data is either rejected or imported, but does not panic
+        for i in 16..data.len() {
+            for v in [0, 1, 2, 0x7f, 0x80, 0xff] {
+                let mut corrupted = data.clone();
+                corrupted[i] = v;
+                let _ = State::import(&mut corrupted.as_slice());
+            }
+        }
+
+        // replace the finite fields and algebraic extensions of an empty state
+        let mut empty = vec![];
+        State::new().export(&mut empty).unwrap();
+        let with_section = |section: &[u8]| {
+            let mut data = empty[..24].to_vec();
+            data.extend(section);
+            data.extend(&empty[40..]);
+            data
+        };
+
+        // an even prime
+        let mut section = 1u64.to_le_bytes().to_vec();
+        section.extend(4u64.to_le_bytes());
+        section.extend(0u64.to_le_bytes());
+        assert!(State::import(&mut with_section(&section).as_slice()).is_err());
+
+        // a minimal polynomial of degree 1 with only zero coefficients
+        let mut section = 0u64.to_le_bytes().to_vec();
+        section.extend(1u64.to_le_bytes());
+        section.extend(1u32.to_le_bytes());
+        let mut coeffs = vec![];
+        Number::Natural(0, 1).write_packed(&mut coeffs);
+        Number::Natural(0, 1).write_packed(&mut coeffs);
+        section.extend((coeffs.len() as u64).to_le_bytes());
+        section.extend(&coeffs);
+        assert!(State::import(&mut with_section(&section).as_slice()).is_err());
+
+        // a degree above the maximum exponent
+        let mut section = 0u64.to_le_bytes().to_vec();
+        section.extend(1u64.to_le_bytes());
+        section.extend((u16::MAX as u32 + 1).to_le_bytes());
+        section.extend(0u64.to_le_bytes());
+        assert!(State::import(&mut with_section(&section).as_slice()).is_err());
+
+        // an interpolation table whose number of values overflows
+        let mut state = State::new();
+        state.get_or_insert_var("f");
+        let mut data = vec![];
+        state.export(&mut data).unwrap();
+        data.truncate(data.len() - 8);
+        data.extend(1u64.to_le_bytes());
+        data.extend(0u32.to_le_bytes());
+        data.extend((u64::MAX / 2 + 1).to_le_bytes());
+        assert!(State::import(&mut data.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_new_temporary_var() {
+        let mut state = State::new();
+        let x = state.get_or_insert_var("x");
+        let taken = state.get_or_insert_var("__abs2");
+        let t1 = state.new_temporary_var("__abs");
+        let t2 = state.new_temporary_var("__abs");
+        assert!(t1 != x && t1 != taken && t2 != taken && t1 != t2);
+        assert!(state.get_name(t1).unwrap().starts_with("__abs"));
     }
 }
@@ -0,0 +1,176 @@
+use ahash::{HashMap, HashMapExt};
+use smallvec::SmallVec;
+
+use crate::{
+    poly::INLINED_EXPONENTS,
+    representations::{
+        number::BorrowedNumber, Add, Atom, AtomView, Fun, Identifier, Mul, Num, OwnedAtom, Pow, Var,
+    },
+    state::{State, Workspace},
+};
+
+/// The usage of a single variable or function in a set of expressions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SymbolUsage {
+    /// The number of occurrences of the identifier.
+    pub count: usize,
+    /// The highest integer power with which the identifier occurs, where a
+    /// function `f(x)^2` counts as a power of `f`.
+    pub max_power: i64,
+    /// Set if the identifier is used as a variable.
+    pub variable: bool,
+    /// Set if the identifier is used as a function.
+    pub function: bool,
+    /// Set if the identifier occurs in a power with a non-integer exponent.
+    pub non_integer_power: bool,
+}
+
+/// The usage of all variables and functions in a set of expressions, which can be
+/// used to construct a variable map or to prune the state with [`State::prune`].
+#[derive(Debug, Clone, Default)]
+pub struct SymbolStatistics {
+    usage: HashMap<Identifier, SymbolUsage>,
+}
+
+impl SymbolStatistics {
+    pub fn new() -> Self {
+        Self {
+            usage: HashMap::new(),
+        }
+    }
+
+    /// Add the usage of all identifiers in `expr`.
+    pub fn add<P: Atom>(&mut self, expr: AtomView<'_, P>) {
+        match expr {
+            AtomView::Num(n) => {
+                if let BorrowedNumber::RationalPolynomial(r) = n.get_number_view() {
+                    let r = r.to_rational_polynomial();
+                    if let Some(var_map) = r.get_var_map() {
+                        for (i, v) in var_map.iter().enumerate() {
+                            let num_deg = r.numerator.degree(i) as i64;
+                            let den_deg = r.denominator.degree(i) as i64;
+                            let power = if num_deg > 0 || den_deg == 0 {
+                                num_deg
+                            } else {
+                                -den_deg
+                            };
+                            self.record(*v, false, power, false);
+                        }
+                    }
+                }
+            }
+            AtomView::Var(v) => self.record(v.get_name(), false, 1, false),
+            AtomView::Fun(f) => {
+                self.record(f.get_name(), true, 1, false);
+                for a in f.iter() {
+                    self.add(a);
+                }
+            }
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+
+                let power = match exp {
+                    AtomView::Num(n) => match n.get_number_view() {
+                        BorrowedNumber::Natural(n, 1) => Some(n),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+
+                match base {
+                    AtomView::Var(v) => {
+                        self.record(v.get_name(), false, power.unwrap_or(1), power.is_none())
+                    }
+                    AtomView::Fun(f) => {
+                        self.record(f.get_name(), true, power.unwrap_or(1), power.is_none());
+                        for a in f.iter() {
+                            self.add(a);
+                        }
+                    }
+                    _ => self.add(base),
+                }
+
+                self.add(exp);
+            }
+            AtomView::Mul(m) => {
+                for a in m.iter() {
+                    self.add(a);
+                }
+            }
+            AtomView::Add(a) => {
+                for a in a.iter() {
+                    self.add(a);
+                }
+            }
+        }
+    }
+
+    fn record(&mut self, id: Identifier, function: bool, power: i64, non_integer_power: bool) {
+        let u = self.usage.entry(id).or_insert(SymbolUsage {
+            count: 0,
+            max_power: power,
+            variable: false,
+            function: false,
+            non_integer_power: false,
+        });
+        u.count += 1;
+        u.max_power = u.max_power.max(power);
+        u.variable |= !function;
+        u.function |= function;
+        u.non_integer_power |= non_integer_power;
+    }
+
+    /// Get the usage of the identifier `id`, or `None` if it does not occur.
+    pub fn get(&self, id: Identifier) -> Option<&SymbolUsage> {
+        self.usage.get(&id)
+    }
+
+    /// Iterate over all identifiers that occur and their usage, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (Identifier, &SymbolUsage)> {
+        self.usage.iter().map(|(id, u)| (*id, u))
+    }
+
+    /// Get all identifiers that occur, sorted by identifier.
+    pub fn get_identifiers(&self) -> Vec<Identifier> {
+        let mut ids: Vec<_> = self.usage.keys().copied().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Get a variable map, sorted by identifier, of all variables that only occur
+    /// with integer powers. Variables that occur in a non-integer power are
+    /// left out, so that they must be treated as part of the coefficient.
+    pub fn get_var_map(&self) -> SmallVec<[Identifier; INLINED_EXPONENTS]> {
+        let mut vars: SmallVec<_> = self
+            .usage
+            .iter()
+            .filter(|(_, u)| u.variable && !u.non_integer_power)
+            .map(|(id, _)| *id)
+            .collect();
+        vars.sort();
+        vars
+    }
+}
+
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Get the usage of all variables and functions in the expression.
+    pub fn get_symbol_statistics(&self) -> SymbolStatistics {
+        let mut stats = SymbolStatistics::new();
+        stats.add(*self);
+        stats
+    }
+
+    /// Rename the identifiers in the expression using the map returned by [`State::prune`],
+    /// so that the output is valid in the pruned state `state`.
+    pub fn rename_pruned(
+        &self,
+        map: &[(Identifier, Identifier)],
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let mut renamed = workspace.new_atom();
+        self.rename_identifiers(map, workspace, renamed.get_mut());
+        renamed.get().to_view().normalize(workspace, state, out);
+    }
+}
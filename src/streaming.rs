@@ -1,4 +1,4 @@
-use std::{sync::Mutex, thread::LocalKey};
+use std::{cmp::Ordering, sync::Mutex, thread::LocalKey};
 
 use rayon::prelude::*;
 
@@ -8,8 +8,12 @@ use crate::{
         OwnedAtom, OwnedNum,
     },
     state::{ResettableBuffer, State, Workspace},
+    utils,
 };
 
+/// The minimal number of pending terms before they are merged into the sum.
+const MIN_PENDING_TERMS: usize = 1024;
+
 thread_local!(static WORKSPACE: Workspace<DefaultRepresentation> = Workspace::new());
 
 pub trait GetLocalWorkspace: Atom + Sized {
@@ -166,6 +170,20 @@ where
     ) -> Self {
         self.move_out_to_in();
 
+        if utils::is_deterministic() {
+            // collect the results in the original order
+            let terms: Vec<_> = std::mem::take(&mut self.exp_in.mem_buf)
+                .into_par_iter()
+                .map(|x| P::get_local_workspace().with(|workspace| f(workspace, x)))
+                .collect();
+
+            for t in terms {
+                self.exp_out.push(t);
+            }
+
+            return self;
+        }
+
         let out_wrap = Mutex::new(self.exp_out);
 
         self.exp_in.par_bridge().for_each(|x| {
@@ -185,3 +203,129 @@ where
         self.exp_out.to_expression(workspace, state)
     }
 }
+
+/// A sum that stays normalized while terms are added to it one by one.
+/// New terms are buffered and merged into the sorted list of terms in bulk,
+/// so that adding a term does not require normalizing the entire sum.
+pub struct AtomAccumulator<P: Atom> {
+    terms: Vec<OwnedAtom<P>>,
+    pending: Vec<OwnedAtom<P>>,
+}
+
+impl<P: Atom> Default for AtomAccumulator<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Atom> AtomAccumulator<P> {
+    /// Create an empty sum.
+    pub fn new() -> Self {
+        Self {
+            terms: vec![],
+            pending: vec![],
+        }
+    }
+
+    /// Add a term to the sum. If the term is a sum itself, all its terms are added.
+    pub fn add_term(&mut self, view: AtomView<P>, workspace: &Workspace<P>, state: &State) {
+        let mut t = OwnedAtom::new();
+        if view.is_dirty() {
+            view.normalize(workspace, state, &mut t);
+        } else {
+            t.from_view(&view);
+        }
+
+        if let OwnedAtom::Add(a) = &t {
+            for arg in a.to_add_view().iter() {
+                self.pending.push(OwnedAtom::new_from_view(&arg));
+            }
+        } else if !matches!(t.to_view(), AtomView::Num(n) if n.is_zero()) {
+            self.pending.push(t);
+        }
+
+        if self.pending.len() >= MIN_PENDING_TERMS.max(self.terms.len() / 4) {
+            self.merge_pending(workspace, state);
+        }
+    }
+
+    /// Get the number of terms in the sum.
+    pub fn nterms(&mut self, workspace: &Workspace<P>, state: &State) -> usize {
+        self.merge_pending(workspace, state);
+        self.terms.len()
+    }
+
+    /// Remove all terms.
+    pub fn clear(&mut self) {
+        self.terms.clear();
+        self.pending.clear();
+    }
+
+    /// Write the normalized sum into `out`.
+    pub fn to_atom(&mut self, workspace: &Workspace<P>, state: &State, out: &mut OwnedAtom<P>) {
+        self.merge_pending(workspace, state);
+
+        match self.terms.as_slice() {
+            [] => out
+                .transform_to_num()
+                .set_from_number(Number::Natural(0, 1)),
+            [t] => out.from_view(&t.to_view()),
+            terms => {
+                let add = out.transform_to_add();
+                for t in terms {
+                    add.extend(t.to_view());
+                }
+            }
+        }
+    }
+
+    /// Sort the pending terms and merge them into the sorted list of terms.
+    fn merge_pending(&mut self, workspace: &Workspace<P>, state: &State) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        self.pending
+            .sort_by(|a, b| a.to_view().cmp_terms(&b.to_view()));
+
+        let mut handle = workspace.new_atom();
+        let helper = handle.get_mut();
+
+        let mut res: Vec<OwnedAtom<P>> = Vec::with_capacity(self.terms.len() + self.pending.len());
+        let mut old = std::mem::take(&mut self.terms).into_iter().peekable();
+        let mut new = self.pending.drain(..).peekable();
+
+        loop {
+            let t = match (old.peek(), new.peek()) {
+                (Some(a), Some(b)) => {
+                    if a.to_view().cmp_terms(&b.to_view()) == Ordering::Greater {
+                        new.next()
+                    } else {
+                        old.next()
+                    }
+                }
+                (Some(_), None) => old.next(),
+                (None, Some(_)) => new.next(),
+                (None, None) => break,
+            };
+            let mut t = t.unwrap();
+
+            if let Some(last) = res.last_mut() {
+                if last.to_view().cmp_terms(&t.to_view()) == Ordering::Equal
+                    && last.merge_terms(&mut t, helper, state)
+                {
+                    if let AtomView::Num(n) = last.to_view() {
+                        if n.is_zero() {
+                            res.pop();
+                        }
+                    }
+                    continue;
+                }
+            }
+
+            res.push(t);
+        }
+
+        self.terms = res;
+    }
+}
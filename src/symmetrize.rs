@@ -0,0 +1,233 @@
+use crate::{
+    representations::{
+        number::{BorrowedNumber, Number},
+        Add, Atom, AtomView, Fun, Identifier, Mul, Num, OwnedAdd, OwnedAtom, OwnedFun, OwnedMul,
+        OwnedNum, OwnedPow, OwnedVar, Pow, Var,
+    },
+    state::{State, Workspace},
+};
+
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Simultaneously rename all variables and functions using `map`, including
+    /// the variables of rational polynomial coefficients. The output is not normalized.
+    pub(crate) fn rename_identifiers(
+        &self,
+        map: &[(Identifier, Identifier)],
+        workspace: &Workspace<P>,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let rename = |id: Identifier| {
+            map.iter()
+                .find(|(from, _)| *from == id)
+                .map(|(_, to)| *to)
+                .unwrap_or(id)
+        };
+
+        match self {
+            AtomView::Num(n) => match n.get_number_view() {
+                BorrowedNumber::RationalPolynomial(r) => {
+                    let mut r = r.to_rational_polynomial();
+                    for p in [&mut r.numerator, &mut r.denominator] {
+                        if let Some(var_map) = &mut p.var_map {
+                            for v in var_map.iter_mut() {
+                                *v = rename(*v);
+                            }
+                        }
+                    }
+                    out.transform_to_num()
+                        .set_from_number(Number::RationalPolynomial(r));
+                }
+                _ => out.from_view(self),
+            },
+            AtomView::Var(v) => {
+                out.transform_to_var().set_from_id(rename(v.get_name()));
+            }
+            AtomView::Fun(f) => {
+                let fun = out.transform_to_fun();
+                fun.set_from_name(rename(f.get_name()));
+                for arg in f.iter() {
+                    let mut arg_h = workspace.new_atom();
+                    arg.rename_identifiers(map, workspace, arg_h.get_mut());
+                    fun.add_arg(arg_h.get().to_view());
+                }
+                fun.set_dirty(true);
+            }
+            AtomView::Pow(p) => {
+                let (base, exp) = p.get_base_exp();
+                let mut base_h = workspace.new_atom();
+                base.rename_identifiers(map, workspace, base_h.get_mut());
+                let mut exp_h = workspace.new_atom();
+                exp.rename_identifiers(map, workspace, exp_h.get_mut());
+
+                let pow = out.transform_to_pow();
+                pow.set_from_base_and_exp(base_h.get().to_view(), exp_h.get().to_view());
+                pow.set_dirty(true);
+            }
+            AtomView::Mul(m) => {
+                let mul = out.transform_to_mul();
+                for arg in m.iter() {
+                    let mut arg_h = workspace.new_atom();
+                    arg.rename_identifiers(map, workspace, arg_h.get_mut());
+                    mul.extend(arg_h.get().to_view());
+                }
+                mul.set_dirty(true);
+            }
+            AtomView::Add(a) => {
+                let add = out.transform_to_add();
+                for arg in a.iter() {
+                    let mut arg_h = workspace.new_atom();
+                    arg.rename_identifiers(map, workspace, arg_h.get_mut());
+                    add.extend(arg_h.get().to_view());
+                }
+                add.set_dirty(true);
+            }
+        }
+    }
+
+    /// Sum the expression over all permutations of the identifiers in `vars`,
+    /// where every term is multiplied by the sign of the permutation if `antisymmetric` is set.
+    fn sum_over_permutations(
+        &self,
+        vars: &[Identifier],
+        antisymmetric: bool,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let mut minus_one = workspace.new_atom();
+        minus_one
+            .transform_to_num()
+            .set_from_number(Number::Natural(-1, 1));
+
+        let mut sum_h = workspace.new_atom();
+        let sum = sum_h.transform_to_add();
+
+        for_each_permutation(vars, |perm, negative| {
+            let map: Vec<_> = vars.iter().cloned().zip(perm.iter().cloned()).collect();
+            let mut renamed = workspace.new_atom();
+            self.rename_identifiers(&map, workspace, renamed.get_mut());
+            let mut term = workspace.new_atom();
+            renamed
+                .get()
+                .to_view()
+                .normalize(workspace, state, term.get_mut());
+
+            // add the terms separately, so that they can be merged
+            let mut add_term = |t: AtomView<'_, P>| {
+                if antisymmetric && negative {
+                    let mut mul_h = workspace.new_atom();
+                    let mul = mul_h.transform_to_mul();
+                    mul.extend(t);
+                    mul.extend(minus_one.get().to_view());
+                    mul.set_dirty(true);
+                    sum.extend(mul_h.get().to_view());
+                } else {
+                    sum.extend(t);
+                }
+            };
+
+            if let AtomView::Add(a) = term.get().to_view() {
+                for t in a.iter() {
+                    add_term(t);
+                }
+            } else {
+                add_term(term.get().to_view());
+            }
+        });
+
+        sum.set_dirty(true);
+        sum_h.get().to_view().normalize(workspace, state, out);
+    }
+
+    /// Sum the expression over all permutations of the variables and functions `vars`.
+    /// The result is not divided by the number of permutations.
+    pub fn symmetrize(
+        &self,
+        vars: &[Identifier],
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        self.sum_over_permutations(vars, false, workspace, state, out);
+    }
+
+    /// Sum the expression over all permutations of the variables and functions `vars`,
+    /// multiplied by the sign of the permutation. The result is not divided by the
+    /// number of permutations.
+    pub fn antisymmetrize(
+        &self,
+        vars: &[Identifier],
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        self.sum_over_permutations(vars, true, workspace, state, out);
+    }
+
+    /// Find the smallest form of the expression under all permutations of the variables
+    /// and functions `vars`, so that expressions that are equal up to a relabeling of
+    /// `vars`, such as integrals with different loop momentum routings, get the same form.
+    /// Returns the renaming that maps the expression to its canonical form.
+    ///
+    /// All `n!` permutations are tried, so `vars` should be small.
+    pub fn canonical_relabel(
+        &self,
+        vars: &[Identifier],
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) -> Vec<(Identifier, Identifier)> {
+        let mut best_map = vec![];
+        self.normalize(workspace, state, out);
+
+        let mut renamed = workspace.new_atom();
+        let mut term = workspace.new_atom();
+        for_each_permutation(vars, |perm, _| {
+            let map: Vec<_> = vars.iter().cloned().zip(perm.iter().cloned()).collect();
+            self.rename_identifiers(&map, workspace, renamed.get_mut());
+            renamed
+                .get()
+                .to_view()
+                .normalize(workspace, state, term.get_mut());
+
+            if term.get().to_view().cmp(&out.to_view()).is_lt() {
+                std::mem::swap(out, term.get_mut());
+                best_map = map;
+            }
+        });
+
+        best_map.retain(|(from, to)| from != to);
+        best_map
+    }
+}
+
+/// Call `f` for every permutation of `vars`, together with a flag that
+/// is set if the permutation is odd. The permutations are generated with Heap's
+/// algorithm, where every step swaps two entries and therefore flips the sign.
+fn for_each_permutation(vars: &[Identifier], mut f: impl FnMut(&[Identifier], bool)) {
+    let mut perm = vars.to_vec();
+    let mut counters = vec![0; vars.len()];
+    let mut negative = false;
+    let mut i = 0;
+    loop {
+        f(&perm, negative);
+
+        while i < vars.len() && counters[i] >= i {
+            counters[i] = 0;
+            i += 1;
+        }
+
+        if i >= vars.len() {
+            break;
+        }
+
+        if i & 1 == 0 {
+            perm.swap(0, i);
+        } else {
+            perm.swap(counters[i], i);
+        }
+        counters[i] += 1;
+        negative = !negative;
+        i = 0;
+    }
+}
@@ -0,0 +1,397 @@
+//! Normalization rules for functions with the attributes
+//! [`FunctionAttribute::KroneckerDelta`](crate::state::FunctionAttribute::KroneckerDelta)
+//! and [`FunctionAttribute::LeviCivita`](crate::state::FunctionAttribute::LeviCivita).
+//!
+//! An index is summed over when it appears twice in a term. A Kronecker delta
+//! with a summed index is contracted with the other occurrence of the index, and
+//! two Levi-Civita symbols with `n` arguments that share summed indices are contracted
+//! with the identity for `n` dimensions
+//! `eps(a_1,..,a_k,c_1,..,c_m)*eps(a_1,..,a_k,d_1,..,d_m) = k! det(delta(c_i,d_j))`.
+//! Since the dimension of a Kronecker delta is not known, the trace `delta(i,i)` is kept.
+
+use ahash::{HashMap, HashMapExt};
+
+use crate::{
+    representations::{
+        number::{BorrowedNumber, Number},
+        Add, Atom, AtomView, Fun, Identifier, Mul, Num, OwnedAdd, OwnedAtom, OwnedFun, OwnedMul,
+        OwnedNum, Pow, Var,
+    },
+    state::{FunctionAttribute, State, Workspace},
+};
+
+/// The sign of the permutation `perm` of `0..perm.len()`.
+pub(crate) fn permutation_sign(perm: &[usize]) -> i64 {
+    let mut sign = 1;
+    for i in 0..perm.len() {
+        for j in i + 1..perm.len() {
+            if perm[i] > perm[j] {
+                sign = -sign;
+            }
+        }
+    }
+    sign
+}
+
+/// All permutations of `0..n` in lexicographic order.
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![vec![]];
+    }
+
+    let mut res = vec![];
+    for p in permutations(n - 1) {
+        for i in 0..n {
+            let mut q = p.clone();
+            q.insert(i, n - 1);
+            res.push(q);
+        }
+    }
+    res.sort();
+    res
+}
+
+/// The number of occurrences of the variable `x` in `expr`.
+fn count_var<P: Atom>(expr: AtomView<'_, P>, x: Identifier) -> usize {
+    match expr {
+        AtomView::Num(_) => 0,
+        AtomView::Var(v) => (v.get_name() == x) as usize,
+        AtomView::Fun(f) => f.iter().map(|a| count_var(a, x)).sum(),
+        AtomView::Pow(p) => {
+            let (b, e) = p.get_base_exp();
+            count_var(b, x) + count_var(e, x)
+        }
+        AtomView::Mul(m) => m.iter().map(|a| count_var(a, x)).sum(),
+        AtomView::Add(a) => a.iter().map(|a| count_var(a, x)).sum(),
+    }
+}
+
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Get the arguments of the function if it has the attribute `attribute`.
+    fn get_index_function_args(
+        &self,
+        attribute: FunctionAttribute,
+        state: &State,
+    ) -> Option<Vec<AtomView<'a, P>>> {
+        let AtomView::Fun(f) = self else {
+            return None;
+        };
+        if !state
+            .get_function_attributes(f.get_name())
+            .contains(&attribute)
+        {
+            return None;
+        }
+
+        let args: Vec<_> = f.iter().collect();
+        if attribute == FunctionAttribute::KroneckerDelta && args.len() != 2 {
+            return None;
+        }
+        Some(args)
+    }
+
+    /// Simplify a Kronecker delta or a Levi-Civita symbol with normalized arguments,
+    /// by evaluating it for numerical arguments and by sorting its arguments.
+    /// Returns `false` if the function is already simplified, in which case `out` is not set.
+    pub(crate) fn simplify_index_function(
+        &self,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) -> bool {
+        let AtomView::Fun(f) = self else {
+            return false;
+        };
+
+        if let Some(args) = self.get_index_function_args(FunctionAttribute::KroneckerDelta, state) {
+            let (a, b) = (args[0], args[1]);
+            if let (AtomView::Num(_), AtomView::Num(_)) = (a, b) {
+                let value = (a == b) as i64;
+                out.transform_to_num()
+                    .set_from_number(Number::Natural(value, 1));
+                return true;
+            }
+
+            if a.cmp(&b).is_gt() {
+                let fun = out.transform_to_fun();
+                fun.set_from_name(f.get_name());
+                fun.add_arg(b);
+                fun.add_arg(a);
+                return true;
+            }
+
+            return false;
+        }
+
+        let Some(args) = self.get_index_function_args(FunctionAttribute::LeviCivita, state) else {
+            return false;
+        };
+
+        let mut order: Vec<usize> = (0..args.len()).collect();
+        order.sort_by(|i, j| args[*i].cmp(&args[*j]));
+        if order.windows(2).any(|w| args[w[0]] == args[w[1]]) {
+            out.transform_to_num()
+                .set_from_number(Number::Natural(0, 1));
+            return true;
+        }
+
+        let sign = permutation_sign(&order);
+        let numbers: Option<Vec<_>> = order
+            .iter()
+            .map(|i| match args[*i] {
+                AtomView::Num(n) => match n.get_number_view() {
+                    BorrowedNumber::Natural(k, 1) => Some(k),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        if let Some(numbers) = numbers {
+            if numbers.iter().enumerate().all(|(i, k)| *k == i as i64 + 1) {
+                out.transform_to_num()
+                    .set_from_number(Number::Natural(sign, 1));
+                return true;
+            }
+        }
+
+        if sign == 1 {
+            return false;
+        }
+
+        let mut fun_h = workspace.new_atom();
+        let fun = fun_h.get_mut().transform_to_fun();
+        fun.set_from_name(f.get_name());
+        for i in &order {
+            fun.add_arg(args[*i]);
+        }
+
+        let mut num_h = workspace.new_atom();
+        num_h
+            .get_mut()
+            .transform_to_num()
+            .set_from_number(Number::Natural(-1, 1));
+
+        let mut mul_h = workspace.new_atom();
+        let mul = mul_h.get_mut().transform_to_mul();
+        mul.extend(fun_h.get().to_view());
+        mul.extend(num_h.get().to_view());
+        mul.set_dirty(true);
+        mul_h.get().to_view().normalize(workspace, state, out);
+        true
+    }
+
+    /// Contract a Kronecker delta or a pair of Levi-Civita symbols with a summed index
+    /// in a normalized product or square and write the normalized result in `out`.
+    /// Returns `false` if there is nothing to contract, in which case `out` is not set.
+    pub(crate) fn contract_indices(
+        &self,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) -> bool {
+        let atoms: Vec<_> = match self {
+            AtomView::Mul(m) => {
+                if !m.iter().any(|a| {
+                    a.get_squared_index_function(state).is_some() || a.is_index_function(state)
+                }) {
+                    return false;
+                }
+                m.iter().collect()
+            }
+            AtomView::Pow(_) if self.get_squared_index_function(state).is_some() => vec![*self],
+            _ => return false,
+        };
+
+        // a square of a delta or an epsilon can be contracted with itself
+        let mut factors = vec![];
+        for a in atoms {
+            if let Some(base) = a.get_squared_index_function(state) {
+                factors.push(base);
+                factors.push(base);
+            } else {
+                factors.push(a);
+            }
+        }
+
+        let is_summed = |x: AtomView<'_, P>| -> Option<Identifier> {
+            let AtomView::Var(v) = x else {
+                return None;
+            };
+            let id = v.get_name();
+            let count: usize = factors.iter().map(|f| count_var(*f, id)).sum();
+            (count == 2).then_some(id)
+        };
+
+        for (i, f) in factors.iter().enumerate() {
+            let Some(args) = f.get_index_function_args(FunctionAttribute::KroneckerDelta, state)
+            else {
+                continue;
+            };
+            if args[0] == args[1] {
+                continue;
+            }
+
+            for (keep, drop) in [(args[0], args[1]), (args[1], args[0])] {
+                let Some(index) = is_summed(drop) else {
+                    continue;
+                };
+
+                let mut map = HashMap::new();
+                map.insert(index, keep);
+
+                let mut mul_h = workspace.new_atom();
+                let mul = mul_h.get_mut().transform_to_mul();
+                for (j, g) in factors.iter().enumerate() {
+                    if i != j {
+                        let mut arg_h = workspace.new_atom();
+                        g.replace_variables(&map, workspace, arg_h.get_mut());
+                        mul.extend(arg_h.get().to_view());
+                    }
+                }
+
+                mul.set_dirty(true);
+                mul_h.get().to_view().normalize(workspace, state, out);
+                return true;
+            }
+        }
+
+        for (i, f) in factors.iter().enumerate() {
+            let Some(args1) = f.get_index_function_args(FunctionAttribute::LeviCivita, state)
+            else {
+                continue;
+            };
+
+            for (j, g) in factors.iter().enumerate().skip(i + 1) {
+                let Some(args2) = g.get_index_function_args(FunctionAttribute::LeviCivita, state)
+                else {
+                    continue;
+                };
+                if args1.len() != args2.len() {
+                    continue;
+                }
+
+                // the summed indices shared by both symbols, in their order in the first
+                let shared: Vec<usize> = (0..args1.len())
+                    .filter(|k| is_summed(args1[*k]).is_some() && args2.contains(&args1[*k]))
+                    .collect();
+                if shared.is_empty() {
+                    continue;
+                }
+
+                // the free indices are contracted with Kronecker deltas
+                let delta = state.get_kronecker_delta();
+                if delta.is_none() && shared.len() < args1.len() {
+                    continue;
+                }
+
+                self.contract_levi_civita(
+                    &factors,
+                    (i, j),
+                    (&args1, &args2),
+                    &shared,
+                    delta,
+                    workspace,
+                    state,
+                    out,
+                );
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// If the atom is the square of a Kronecker delta or a Levi-Civita symbol, return the base.
+    fn get_squared_index_function(&self, state: &State) -> Option<AtomView<'a, P>> {
+        let AtomView::Pow(p) = self else {
+            return None;
+        };
+        let (base, exp) = p.get_base_exp();
+        match exp {
+            AtomView::Num(n)
+                if matches!(n.get_number_view(), BorrowedNumber::Natural(2, 1))
+                    && base.is_index_function(state) =>
+            {
+                Some(base)
+            }
+            _ => None,
+        }
+    }
+
+    /// Check if the atom is a Kronecker delta or a Levi-Civita symbol.
+    fn is_index_function(&self, state: &State) -> bool {
+        self.get_index_function_args(FunctionAttribute::KroneckerDelta, state)
+            .is_some()
+            || self
+                .get_index_function_args(FunctionAttribute::LeviCivita, state)
+                .is_some()
+    }
+
+    /// Replace the Levi-Civita symbols at the positions `pair` in `factors`, with
+    /// arguments `args1` and `args2`, by the contraction over the indices `args1[k]`
+    /// for `k` in `shared`, expressed in terms of the Kronecker delta `delta`.
+    /// The remaining factors are multiplied into every term.
+    #[allow(clippy::too_many_arguments)]
+    fn contract_levi_civita(
+        &self,
+        factors: &[AtomView<'_, P>],
+        pair: (usize, usize),
+        (args1, args2): (&[AtomView<'_, P>], &[AtomView<'_, P>]),
+        shared: &[usize],
+        delta: Option<Identifier>,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        // move the shared indices to the front of both symbols, in the same order
+        let mut order1: Vec<usize> = shared.to_vec();
+        order1.extend((0..args1.len()).filter(|k| !shared.contains(k)));
+        let mut order2: Vec<usize> = shared
+            .iter()
+            .map(|k| args2.iter().position(|a| *a == args1[*k]).unwrap())
+            .collect();
+        let free: Vec<usize> = (0..args2.len()).filter(|k| !order2.contains(k)).collect();
+        order2.extend(free);
+
+        let mut coeff = permutation_sign(&order1) * permutation_sign(&order2);
+        for k in 2..=shared.len() as i64 {
+            coeff *= k;
+        }
+
+        let free1 = &order1[shared.len()..];
+        let free2 = &order2[shared.len()..];
+
+        let mut add_h = workspace.new_atom();
+        let add = add_h.get_mut().transform_to_add();
+        for perm in permutations(free1.len()) {
+            let mut num_h = workspace.new_atom();
+            num_h
+                .get_mut()
+                .transform_to_num()
+                .set_from_number(Number::Natural(coeff * permutation_sign(&perm), 1));
+
+            let mut mul_h = workspace.new_atom();
+            let mul = mul_h.get_mut().transform_to_mul();
+            mul.extend(num_h.get().to_view());
+            for (k, p) in perm.iter().enumerate() {
+                let mut fun_h = workspace.new_atom();
+                let fun = fun_h.get_mut().transform_to_fun();
+                fun.set_from_name(delta.unwrap());
+                fun.add_arg(args1[free1[k]]);
+                fun.add_arg(args2[free2[*p]]);
+                fun.set_dirty(true);
+                mul.extend(fun_h.get().to_view());
+            }
+            for (k, f) in factors.iter().enumerate() {
+                if k != pair.0 && k != pair.1 {
+                    mul.extend(*f);
+                }
+            }
+            mul.set_dirty(true);
+            add.extend(mul_h.get().to_view());
+        }
+        add.set_dirty(true);
+
+        add_h.get().to_view().normalize(workspace, state, out);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{
+    representations::{
+        number::{BorrowedNumber, Number},
+        Add, Atom, AtomView, Mul, Num, OwnedAdd, OwnedAtom, OwnedNum,
+    },
+    state::{State, Workspace},
+};
+
+/// Write the sum of `terms` in `out`.
+fn sum_terms<P: Atom>(
+    terms: &[AtomView<'_, P>],
+    workspace: &Workspace<P>,
+    state: &State,
+    out: &mut OwnedAtom<P>,
+) {
+    match terms {
+        [] => out
+            .transform_to_num()
+            .set_from_number(Number::Natural(0, 1)),
+        [t] => out.from_view(t),
+        _ => {
+            let mut add_h = workspace.new_atom();
+            let add = add_h.transform_to_add();
+            for t in terms {
+                add.extend(*t);
+            }
+            add.set_dirty(true);
+            add_h.get().to_view().normalize(workspace, state, out);
+        }
+    }
+}
+
+pub(crate) fn number_to_f64(n: BorrowedNumber<'_>) -> Option<f64> {
+    match n {
+        BorrowedNumber::Natural(n, d) => Some(n as f64 / d as f64),
+        BorrowedNumber::Large(r) => Some(r.to_rat().to_f64()),
+        BorrowedNumber::FiniteField(_, _)
+        | BorrowedNumber::RationalPolynomial(_)
+        | BorrowedNumber::AlgebraicNumber(_, _) => None,
+    }
+}
+
+impl<'a, P: Atom> AtomView<'a, P> {
+    /// Get the numerical coefficient of a term as a floating point number,
+    /// or `None` if the coefficient is not a rational number.
+    pub fn get_coefficient_f64(&self) -> Option<f64> {
+        match self {
+            AtomView::Num(n) => number_to_f64(n.get_number_view()),
+            AtomView::Mul(m) => match m.iter().last() {
+                Some(AtomView::Num(n)) => number_to_f64(n.get_number_view()),
+                _ => Some(1.),
+            },
+            _ => Some(1.),
+        }
+    }
+
+    /// Split the terms of the expression into the sum of all terms for which
+    /// `predicate` holds, written in `matching`, and the sum of all other terms,
+    /// written in `rest`.
+    pub fn split_by<F: Fn(AtomView<'_, P>) -> bool>(
+        &self,
+        predicate: F,
+        workspace: &Workspace<P>,
+        state: &State,
+        matching: &mut OwnedAtom<P>,
+        rest: &mut OwnedAtom<P>,
+    ) {
+        let mut yes = vec![];
+        let mut no = vec![];
+
+        if let AtomView::Add(a) = self {
+            for t in a.iter() {
+                if predicate(t) {
+                    yes.push(t);
+                } else {
+                    no.push(t);
+                }
+            }
+        } else if predicate(*self) {
+            yes.push(*self);
+        } else {
+            no.push(*self);
+        }
+
+        sum_terms(&yes, workspace, state, matching);
+        sum_terms(&no, workspace, state, rest);
+    }
+
+    /// Split the expression into the terms with a positive and the terms with a
+    /// negative numerical coefficient. Terms with a coefficient that
+    /// is not a rational number are considered positive.
+    pub fn split_by_sign(
+        &self,
+        workspace: &Workspace<P>,
+        state: &State,
+        positive: &mut OwnedAtom<P>,
+        negative: &mut OwnedAtom<P>,
+    ) {
+        self.split_by(
+            |t| t.get_coefficient_f64().map(|c| c >= 0.).unwrap_or(true),
+            workspace,
+            state,
+            positive,
+            negative,
+        );
+    }
+
+    /// Remove all terms whose numerical coefficient is smaller than `threshold` in absolute value.
+    /// Terms with a coefficient that is not a rational number are kept.
+    /// Returns `true` if a term was removed.
+    pub fn drop_small_coefficients(
+        &self,
+        threshold: f64,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) -> bool {
+        let mut dropped = workspace.new_atom();
+        self.split_by(
+            |t| {
+                t.get_coefficient_f64()
+                    .map(|c| c.abs() >= threshold)
+                    .unwrap_or(true)
+            },
+            workspace,
+            state,
+            out,
+            dropped.get_mut(),
+        );
+
+        !matches!(dropped.get().to_view(), AtomView::Num(n) if n.is_zero())
+    }
+}
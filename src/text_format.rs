@@ -0,0 +1,314 @@
+//! A canonical plain-text format for expressions, for durable storage and
+//! line-based diffs. In contrast to the output of the printer, the format
+//! is guaranteed to round-trip through the parser.
+//!
+//! A file starts with the version of the format, followed by a declaration
+//! of every symbol in the expression with its attributes and assumptions,
+//! and the expression with one term per line:
+//! ```text
+//! symbolica 1
+//! symbol f noncommutative
+//! symbol x positive real
+//! expr
+//! x^2*f((1/2),[3%7])
+//! +x*(-1)
+//! ```
+
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+};
+
+use crate::{
+    parser::parse,
+    printer::{PolynomialPrinter, PrintMode, SymbolicaPrintOptions},
+    representations::{
+        number::BorrowedNumber, Add, Atom, AtomView, Fun, Identifier, Mul, Num, OwnedAtom, Pow, Var,
+    },
+    rings::{finite_field::FiniteFieldCore, rational_polynomial::RationalPolynomialNormalization},
+    state::{Assumptions, FunctionAttribute, ResettableBuffer, State, Workspace},
+};
+
+/// The version of the text format that is written by [`export_text`].
+pub const TEXT_FORMAT_VERSION: u32 = 1;
+
+/// Write the normalized expression `expr` in the canonical text format,
+/// which can be read with [`import_text`].
+pub fn export_text<P: Atom, W: Write>(
+    expr: AtomView<'_, P>,
+    state: &State,
+    dest: &mut W,
+) -> io::Result<()> {
+    writeln!(dest, "symbolica {}", TEXT_FORMAT_VERSION)?;
+
+    let mut symbols = vec![];
+    collect_symbols(expr, &mut symbols);
+    let mut symbols: Vec<_> = symbols
+        .into_iter()
+        .map(|id| (state.get_name(id).unwrap().as_str(), id))
+        .collect();
+    symbols.sort();
+    symbols.dedup();
+
+    for (name, id) in symbols {
+        write!(dest, "symbol {}", name)?;
+        if state.is_noncommutative(id) {
+            write!(dest, " noncommutative")?;
+        }
+        if state.is_conditional(id) {
+            write!(dest, " conditional")?;
+        }
+        for (attribute, word) in [
+            (FunctionAttribute::KroneckerDelta, "kroneckerdelta"),
+            (FunctionAttribute::LeviCivita, "levicivita"),
+            (FunctionAttribute::List, "list"),
+        ] {
+            if state.get_function_attributes(id).contains(&attribute) {
+                write!(dest, " {}", word)?;
+            }
+        }
+
+        let a = state.get_assumptions(id);
+        for (set, flag) in [
+            (a.positive, "positive"),
+            (a.real, "real"),
+            (a.integer, "integer"),
+            (a.nonzero, "nonzero"),
+        ] {
+            if set {
+                write!(dest, " {}", flag)?;
+            }
+        }
+        writeln!(dest)?;
+    }
+
+    writeln!(dest, "expr")?;
+    let mut out = String::new();
+    match expr {
+        AtomView::Add(a) => {
+            for (i, t) in a.iter().enumerate() {
+                if i > 0 {
+                    out.push('+');
+                }
+                write_atom(t, state, &mut out);
+                out.push('\n');
+            }
+        }
+        _ => {
+            write_atom(expr, state, &mut out);
+            out.push('\n');
+        }
+    }
+    dest.write_all(out.as_bytes())
+}
+
+/// Read an expression that was written by [`export_text`]. The attributes and
+/// assumptions of the declared symbols are set in `state`.
+pub fn import_text<P: Atom>(
+    input: &str,
+    state: &mut State,
+    workspace: &Workspace<P>,
+) -> Result<OwnedAtom<P>, String> {
+    let mut lines = input.lines();
+
+    let version = match lines.next().and_then(|l| l.strip_prefix("symbolica ")) {
+        Some(v) => v
+            .trim()
+            .parse::<u32>()
+            .map_err(|e| format!("Could not parse version: {}", e))?,
+        None => return Err("Missing header".into()),
+    };
+    if version > TEXT_FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported version {}, the latest supported version is {}",
+            version, TEXT_FORMAT_VERSION
+        ));
+    }
+
+    loop {
+        let line = match lines.next() {
+            Some(l) => l.trim(),
+            None => return Err("Missing expression".into()),
+        };
+
+        if line == "expr" {
+            break;
+        }
+
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("symbol"), Some(name)) => {
+                let id = state.get_or_insert_var(name);
+                let mut attributes = vec![];
+                let mut assumptions = Assumptions::default();
+                for w in words {
+                    match w {
+                        "noncommutative" => attributes.push(FunctionAttribute::NonCommutative),
+                        "conditional" => attributes.push(FunctionAttribute::Conditional),
+                        "kroneckerdelta" => attributes.push(FunctionAttribute::KroneckerDelta),
+                        "levicivita" => attributes.push(FunctionAttribute::LeviCivita),
+                        "list" => attributes.push(FunctionAttribute::List),
+                        "positive" => assumptions.positive = true,
+                        "real" => assumptions.real = true,
+                        "integer" => assumptions.integer = true,
+                        "nonzero" => assumptions.nonzero = true,
+                        _ => return Err(format!("Unknown attribute {} of {}", w, name)),
+                    }
+                }
+                state.set_function_attributes(id, &attributes);
+                state.set_assumptions(id, assumptions);
+            }
+            _ => return Err(format!("Unexpected line: {}", line)),
+        }
+    }
+
+    let expr: String = lines.collect();
+    let atom = parse(&expr)?.to_atom(state, workspace)?;
+    let mut out = OwnedAtom::new();
+    atom.to_view().normalize(workspace, state, &mut out);
+    Ok(out)
+}
+
+fn collect_symbols<P: Atom>(expr: AtomView<'_, P>, symbols: &mut Vec<Identifier>) {
+    match expr {
+        AtomView::Num(n) => {
+            if let BorrowedNumber::RationalPolynomial(r) = n.get_number_view() {
+                if let Some(var_map) = r.to_rational_polynomial().get_var_map() {
+                    symbols.extend_from_slice(var_map);
+                }
+            }
+        }
+        AtomView::Var(v) => symbols.push(v.get_name()),
+        AtomView::Fun(f) => {
+            symbols.push(f.get_name());
+            for a in f.iter() {
+                collect_symbols(a, symbols);
+            }
+        }
+        AtomView::Pow(p) => {
+            let (b, e) = p.get_base_exp();
+            collect_symbols(b, symbols);
+            collect_symbols(e, symbols);
+        }
+        AtomView::Mul(m) => {
+            for a in m.iter() {
+                collect_symbols(a, symbols);
+            }
+        }
+        AtomView::Add(a) => {
+            for a in a.iter() {
+                collect_symbols(a, symbols);
+            }
+        }
+    }
+}
+
+fn write_atom<P: Atom>(expr: AtomView<'_, P>, state: &State, out: &mut String) {
+    match expr {
+        AtomView::Num(n) => write_number(n.get_number_view(), state, out),
+        AtomView::Var(v) => out.push_str(state.get_name(v.get_name()).unwrap()),
+        AtomView::Fun(f) => {
+            out.push_str(state.get_name(f.get_name()).unwrap());
+            out.push('(');
+            for (i, a) in f.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_atom(a, state, out);
+            }
+            out.push(')');
+        }
+        AtomView::Pow(p) => {
+            let (b, e) = p.get_base_exp();
+            write_factor(b, state, out);
+            out.push('^');
+            write_factor(e, state, out);
+        }
+        AtomView::Mul(m) => {
+            for (i, a) in m.iter().enumerate() {
+                if i > 0 {
+                    out.push('*');
+                }
+                if let AtomView::Pow(_) = a {
+                    write_atom(a, state, out);
+                } else {
+                    write_factor(a, state, out);
+                }
+            }
+        }
+        AtomView::Add(a) => {
+            for (i, a) in a.iter().enumerate() {
+                if i > 0 {
+                    out.push('+');
+                }
+                write_atom(a, state, out);
+            }
+        }
+    }
+}
+
+/// Write an argument of a product or power, using parentheses if needed.
+fn write_factor<P: Atom>(expr: AtomView<'_, P>, state: &State, out: &mut String) {
+    match expr {
+        AtomView::Num(_) | AtomView::Var(_) | AtomView::Fun(_) => write_atom(expr, state, out),
+        _ => {
+            out.push('(');
+            write_atom(expr, state, out);
+            out.push(')');
+        }
+    }
+}
+
+/// Write a number. Negative numbers and fractions are put in parentheses,
+/// and other coefficients in the square brackets that the parser uses for coefficients.
+fn write_number(n: BorrowedNumber, state: &State, out: &mut String) {
+    match n {
+        BorrowedNumber::Natural(num, 1) if num >= 0 => write!(out, "{}", num).unwrap(),
+        BorrowedNumber::Natural(num, 1) => write!(out, "({})", num).unwrap(),
+        BorrowedNumber::Natural(num, den) => write!(out, "({}/{})", num, den).unwrap(),
+        BorrowedNumber::Large(r) => {
+            let r = r.to_rat();
+            if r.is_integer() && *r.numer() >= 0 {
+                write!(out, "{}", r).unwrap()
+            } else {
+                write!(out, "({})", r).unwrap()
+            }
+        }
+        BorrowedNumber::FiniteField(num, fi) => {
+            let ff = state.get_finite_field(fi);
+            write!(out, "[{}%{}]", ff.from_element(num), ff.get_prime()).unwrap()
+        }
+        BorrowedNumber::AlgebraicNumber(a, ai) => write!(
+            out,
+            "[{}%{}]",
+            a.to_algebraic_number(),
+            state.get_algebraic_extension(ai).poly()
+        )
+        .unwrap(),
+        BorrowedNumber::RationalPolynomial(p) => {
+            let p = p.to_rational_polynomial();
+            let print_mode = PrintMode::Symbolica(SymbolicaPrintOptions {
+                terms_on_new_line: false,
+                color_top_level_sum: false,
+                print_finite_field: true,
+                explicit_rational_polynomial: false,
+                rational_polynomial_normalization: RationalPolynomialNormalization::None,
+            });
+            write!(
+                out,
+                "[{},{}",
+                PolynomialPrinter::new(&p.numerator, state, print_mode),
+                PolynomialPrinter::new(&p.denominator, state, print_mode)
+            )
+            .unwrap();
+
+            if let Some(var_map) = p.get_var_map() {
+                for (i, v) in var_map.iter().enumerate() {
+                    out.push(if i == 0 { ';' } else { ',' });
+                    out.push_str(state.get_name(*v).unwrap());
+                }
+            }
+            out.push(']');
+        }
+    }
+}
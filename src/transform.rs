@@ -0,0 +1,245 @@
+//! Integral transforms by table lookup. A transform of `f(t)` to a function of `s` is
+//! represented by the function `T(f, t, s)`, which is rewritten with a table of transform
+//! pairs through the pattern engine. Terms without a matching pair remain unevaluated.
+//!
+//! Default tables exist for the Laplace transform `laplace(f, t, s)` of
+//! `int_0^inf f(t) exp(-s t) dt` and the Mellin transform `mellin(f, t, s)` of
+//! `int_0^inf t^(s-1) f(t) dt`. The pairs hold where the integrals converge.
+
+use ahash::HashMap;
+
+use crate::{
+    derivative::depends_on,
+    id::{Match, Pattern, PatternRestriction, Rule, RuleSet},
+    parser::parse,
+    representations::{
+        number::Number, Add, Atom, AtomView, Identifier, Mul, OwnedAdd, OwnedAtom, OwnedFun,
+        OwnedMul, OwnedNum, OwnedVar, Var,
+    },
+    state::{State, Workspace},
+};
+
+/// The Laplace transform pairs: the lhs, the rhs and the wildcards that must not
+/// depend on `t_`.
+const LAPLACE_PAIRS: &[(&str, &str, &[&str])] = &[
+    ("laplace(1, t_, s_)", "s_^-1", &[]),
+    ("laplace(t_, t_, s_)", "s_^-2", &[]),
+    ("laplace(t_^n_, t_, s_)", "gamma(n_+1)*s_^(-n_-1)", &["n_"]),
+    ("laplace(exp(t_), t_, s_)", "(s_-1)^-1", &[]),
+    ("laplace(exp(a_*t_), t_, s_)", "(s_-a_)^-1", &["a_"]),
+    ("laplace(sin(t_), t_, s_)", "(s_^2+1)^-1", &[]),
+    ("laplace(sin(a_*t_), t_, s_)", "a_*(s_^2+a_^2)^-1", &["a_"]),
+    ("laplace(cos(t_), t_, s_)", "s_*(s_^2+1)^-1", &[]),
+    ("laplace(cos(a_*t_), t_, s_)", "s_*(s_^2+a_^2)^-1", &["a_"]),
+    // frequency shift
+    ("laplace(exp(t_)*f_, t_, s_)", "laplace(f_, t_, s_-1)", &[]),
+    (
+        "laplace(exp(a_*t_)*f_, t_, s_)",
+        "laplace(f_, t_, s_-a_)",
+        &["a_"],
+    ),
+];
+
+/// The Mellin transform pairs, see [`LAPLACE_PAIRS`].
+const MELLIN_PAIRS: &[(&str, &str, &[&str])] = &[
+    (
+        "mellin(exp(a_*t_), t_, s_)",
+        "gamma(s_)*(-a_)^(-s_)",
+        &["a_"],
+    ),
+    (
+        "mellin((t_+1)^a_, t_, s_)",
+        "gamma(s_)*gamma(a_*-1-s_)*gamma(a_*-1)^-1",
+        &["a_"],
+    ),
+    // multiplication by a power of t shifts s
+    ("mellin(t_*f_, t_, s_)", "mellin(f_, t_, s_+1)", &[]),
+    ("mellin(t_^b_*f_, t_, s_)", "mellin(f_, t_, s_+b_)", &["b_"]),
+];
+
+/// Check if the match `m` does not depend on the variable matched by `t`.
+fn independent_of<P: Atom>(m: &Match<'_, P>, t: &Match<'_, P>) -> bool {
+    let Match::Single(AtomView::Var(t)) = t else {
+        return false;
+    };
+
+    match m {
+        Match::Single(v) => !depends_on(*v, t.get_name()),
+        Match::Multiple(_, vs) => vs.iter().all(|v| !depends_on(*v, t.get_name())),
+        Match::FunctionName(_) => true,
+    }
+}
+
+/// A table of transform pairs for an integral transform, applied as rewrite rules
+/// to the function that represents the unevaluated transform.
+pub struct TransformTable<P: Atom> {
+    name: Identifier,
+    rules: RuleSet<P>,
+}
+
+impl<P: Atom> TransformTable<P> {
+    /// Create an empty table for the transform represented by the function `name`.
+    pub fn new(name: Identifier) -> Self {
+        TransformTable {
+            name,
+            rules: RuleSet::new(),
+        }
+    }
+
+    /// Create a table of common Laplace transform pairs.
+    pub fn laplace(state: &mut State, workspace: &Workspace<P>) -> Self
+    where
+        P: 'static,
+    {
+        Self::from_pairs("laplace", LAPLACE_PAIRS, state, workspace)
+    }
+
+    /// Create a table of common Mellin transform pairs.
+    pub fn mellin(state: &mut State, workspace: &Workspace<P>) -> Self
+    where
+        P: 'static,
+    {
+        Self::from_pairs("mellin", MELLIN_PAIRS, state, workspace)
+    }
+
+    fn from_pairs(
+        name: &str,
+        pairs: &[(&str, &str, &[&str])],
+        state: &mut State,
+        workspace: &Workspace<P>,
+    ) -> Self
+    where
+        P: 'static,
+    {
+        let mut table = Self::new(state.get_or_insert_var(name));
+        let t = state.get_or_insert_var("t_");
+
+        for (lhs, rhs, constants) in pairs {
+            let lhs_atom = parse(lhs).unwrap().to_atom(state, workspace).unwrap();
+            let rhs_atom = parse(rhs).unwrap().to_atom(state, workspace).unwrap();
+
+            // the wildcards may be matched before or after t_, so that
+            // the independence is checked on both
+            let mut restrictions: HashMap<_, Vec<_>> = HashMap::default();
+            for c in *constants {
+                let c = state.get_or_insert_var(c);
+                restrictions
+                    .entry(c)
+                    .or_default()
+                    .push(PatternRestriction::Cmp(t, Box::new(independent_of)));
+                restrictions
+                    .entry(t)
+                    .or_default()
+                    .push(PatternRestriction::Cmp(
+                        c,
+                        Box::new(|t: &Match<'_, P>, c: &Match<'_, P>| independent_of(c, t)),
+                    ));
+            }
+
+            table.add_rule(Rule {
+                name: format!("{} = {}", lhs, rhs),
+                lhs: Pattern::from_view(lhs_atom.to_view(), state),
+                rhs: Pattern::from_view(rhs_atom.to_view(), state),
+                restrictions,
+                priority: 0,
+            });
+        }
+
+        table
+    }
+
+    /// Get the function that represents the unevaluated transform.
+    pub fn get_name(&self) -> Identifier {
+        self.name
+    }
+
+    /// Add a transform pair. The lhs of the rule should be the transform
+    /// function applied to `(f, t_, s_)`, where `t_` matches the variable of the
+    /// function `f` and `s_` the variable of the transform.
+    pub fn add_rule(&mut self, rule: Rule<P>) -> &mut Self {
+        self.rules.add_rule(rule);
+        self
+    }
+
+    /// Get the transform pairs.
+    pub fn get_rules(&self) -> &[Rule<P>] {
+        self.rules.get_rules()
+    }
+
+    /// Transform `expr` as a function of `t` to a function of `s` and write the result in `out`.
+    /// The transform is linear, so that it is applied to every term separately and factors
+    /// that do not depend on `t` are taken out. Terms without a matching transform pair remain
+    /// as unevaluated transforms.
+    pub fn transform(
+        &self,
+        expr: AtomView<'_, P>,
+        t: Identifier,
+        s: Identifier,
+        workspace: &Workspace<P>,
+        state: &State,
+        out: &mut OwnedAtom<P>,
+    ) {
+        let terms: Vec<_> = match expr {
+            AtomView::Add(a) => a.iter().collect(),
+            _ => vec![expr],
+        };
+
+        let mut t_atom = workspace.new_atom();
+        t_atom.get_mut().transform_to_var().set_from_id(t);
+        let mut s_atom = workspace.new_atom();
+        s_atom.get_mut().transform_to_var().set_from_id(s);
+
+        let mut sum = workspace.new_atom();
+        let add = sum.get_mut().transform_to_add();
+        for term in terms {
+            let factors: Vec<_> = match term {
+                AtomView::Mul(m) => m.iter().collect(),
+                _ => vec![term],
+            };
+            let (dependent, constant): (Vec<_>, Vec<_>) =
+                factors.into_iter().partition(|f| depends_on(*f, t));
+
+            let mut f = workspace.new_atom();
+            match dependent.len() {
+                0 => {
+                    f.get_mut()
+                        .transform_to_num()
+                        .set_from_number(Number::Natural(1, 1));
+                }
+                1 => f.get_mut().from_view(&dependent[0]),
+                _ => {
+                    let mul = f.get_mut().transform_to_mul();
+                    for d in dependent {
+                        mul.extend(d);
+                    }
+                    mul.set_dirty(true);
+                }
+            }
+
+            let mut transform = workspace.new_atom();
+            let fun = transform.get_mut().transform_to_fun();
+            fun.set_from_name(self.name);
+            fun.add_arg(f.get().to_view());
+            fun.add_arg(t_atom.get().to_view());
+            fun.add_arg(s_atom.get().to_view());
+            fun.set_dirty(true);
+
+            let mut new_term = workspace.new_atom();
+            let mul = new_term.get_mut().transform_to_mul();
+            for c in constant {
+                mul.extend(c);
+            }
+            mul.extend(transform.get().to_view());
+            mul.set_dirty(true);
+            add.extend(new_term.get().to_view());
+        }
+        add.set_dirty(true);
+
+        let mut norm = workspace.new_atom();
+        sum.get()
+            .to_view()
+            .normalize(workspace, state, norm.get_mut());
+        self.rules
+            .apply(norm.get().to_view(), state, workspace, out, None);
+    }
+}
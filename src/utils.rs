@@ -1,7 +1,62 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rand::{rngs::StdRng, SeedableRng};
 use smallvec::SmallVec;
 
-pub(crate) unsafe fn any_as_u8_slice<T: Sized>(p: &T) -> &[u8] {
-    std::slice::from_raw_parts((p as *const T) as *const u8, std::mem::size_of::<T>())
+static DETERMINISTIC: AtomicBool = AtomicBool::new(false);
+
+/// Force all probabilistic and parallel algorithms to produce reproducible results.
+/// Random number generators are seeded with a fixed seed per subtask and
+/// parallel results are collected in a deterministic order.
+pub fn set_deterministic(deterministic: bool) {
+    DETERMINISTIC.store(deterministic, Ordering::Relaxed);
+}
+
+/// Check if deterministic mode is enabled.
+pub fn is_deterministic() -> bool {
+    DETERMINISTIC.load(Ordering::Relaxed)
+}
+
+/// Get a random number generator for the subtask with the given name.
+/// In deterministic mode, the generator is seeded with a hash of the name.
+pub fn rng(subtask: &str) -> StdRng {
+    if is_deterministic() {
+        // FNV-1a, which is stable across processes and platforms
+        let mut seed = 0xcbf29ce484222325u64;
+        for b in subtask.bytes() {
+            seed ^= b as u64;
+            seed = seed.wrapping_mul(0x100000001b3);
+        }
+        StdRng::seed_from_u64(seed)
+    } else {
+        StdRng::from_rng(rand::thread_rng()).unwrap()
+    }
+}
+
+/// A 128-bit FNV-1a hasher, which is stable across processes and platforms.
+pub struct StableHasher(u128);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StableHasher {
+    pub fn new() -> Self {
+        StableHasher(0x6c62272e07bb014262b821756295c58d)
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u128;
+            self.0 = self.0.wrapping_mul(0x0000000001000000000000000000013b);
+        }
+    }
+
+    pub fn finish(&self) -> u128 {
+        self.0
+    }
 }
 
 pub fn gcd_unsigned(mut a: u64, mut b: u64) -> u64 {
@@ -25,6 +80,24 @@ pub fn gcd_signed(mut a: i64, mut b: i64) -> i64 {
     b.abs()
 }
 
+/// Get all exponents of `nvars` variables with a total degree of at most `degree`.
+pub fn monomials(nvars: usize, degree: u32) -> Vec<Vec<u32>> {
+    let mut res: Vec<Vec<u32>> = vec![vec![]];
+    for _ in 0..nvars {
+        let mut new_res = vec![];
+        for m in &res {
+            let used: u32 = m.iter().sum();
+            for e in 0..=degree - used {
+                let mut m = m.clone();
+                m.push(e);
+                new_res.push(m);
+            }
+        }
+        res = new_res;
+    }
+    res
+}
+
 pub struct CombinationIterator {
     indices: SmallVec<[u32; 10]>,
     k: u32,